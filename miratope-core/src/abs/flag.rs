@@ -668,6 +668,71 @@ impl FlagSet {
     }
 }
 
+/// A consistent orientation of the faces of a polyhedron, as found by
+/// [`Abstract::face_orientation`].
+#[derive(Clone, Debug)]
+pub struct FaceOrientation {
+    /// The edges of every face, as pairs of vertices in the order in which the
+    /// boundary of the face goes through them. Any two faces sharing an edge go
+    /// through it in opposite directions.
+    pub edges: Vec<Vec<[usize; 2]>>,
+
+    /// The index of the connected component every face belongs to. Each
+    /// component is oriented independently.
+    pub components: Vec<usize>,
+}
+
+impl Abstract {
+    /// Orients the faces of a polyhedron consistently, so that every edge is
+    /// traversed in opposite directions by its two faces. The orientation of
+    /// a face is read off from its flags of even parity, each of which goes
+    /// from its vertex to the other vertex of its edge.
+    ///
+    /// Returns `None` if the polytope isn't a polyhedron, or if any of its
+    /// components is non-orientable.
+    ///
+    /// # Panics
+    /// You must call [`Polytope::element_sort`] before calling this method.
+    pub fn face_orientation(&self) -> Option<FaceOrientation> {
+        if self.rank() != 4 {
+            return None;
+        }
+
+        let face_count = self.el_count(3);
+        let mut edges = vec![Vec::new(); face_count];
+        let mut components = vec![usize::MAX; face_count];
+        let mut component = 0;
+
+        for face in 0..face_count {
+            if components[face] != usize::MAX {
+                continue;
+            }
+
+            // We walk through the component of any face we haven't reached.
+            let edge = self[(3, face)].subs[0];
+            let vertex = self[(2, edge)].subs[0];
+            let first_flag = OrientedFlag::from(vec![0, vertex, edge, face, 0]);
+
+            for flag_event in OrientedFlagIter::with_flags(self, FlagChanges::all(4), first_flag) {
+                let flag = flag_event.flag()?;
+                let face = flag[3];
+                components[face] = component;
+
+                if flag.orientation == Orientation::Even {
+                    let (vertex, edge) = (flag[1], flag[2]);
+                    let subs = &self[(2, edge)].subs;
+                    let other = if subs[0] == vertex { subs[1] } else { subs[0] };
+                    edges[face].push([vertex, other]);
+                }
+            }
+
+            component += 1;
+        }
+
+        Some(FaceOrientation { edges, components })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -987,4 +1052,33 @@ mod tests {
         test_flags(&mut snic, 240);
         test_flags(&mut ti, 360);
     }
+
+    /// Checks that the faces of a compound of two cubes are oriented
+    /// consistently, and that the hemicube can't be oriented.
+    #[test]
+    fn face_orientation() {
+        let mut compound = Abstract::compound([Abstract::cube(), Abstract::cube()].into_iter());
+        compound.element_sort();
+        let orientation = compound.face_orientation().unwrap();
+
+        // Every edge is traversed once in each direction.
+        let mut directed_edges = HashSet::new();
+        for edges in &orientation.edges {
+            assert_eq!(edges.len(), 4);
+            for &edge in edges {
+                assert!(directed_edges.insert(edge));
+            }
+        }
+        for &[v, w] in &directed_edges {
+            assert!(directed_edges.contains(&[w, v]));
+        }
+        assert_eq!(directed_edges.len(), 48);
+        assert_eq!(orientation.components, [vec![0; 6], vec![1; 6]].concat());
+
+        let hemicube = Abstract::from_incidence(
+            "INC 3\n4\n6\n0 1\n0 2\n0 3\n1 2\n1 3\n2 3\n3\n0 3 5 2\n0 4 5 1\n1 3 4 2",
+        )
+        .unwrap();
+        assert!(hemicube.face_orientation().is_none());
+    }
 }
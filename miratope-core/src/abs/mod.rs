@@ -3,7 +3,9 @@
 pub mod antiprism;
 pub mod flag;
 pub mod product;
+pub mod quotient;
 pub mod ranked;
+pub mod topology;
 pub mod valid;
 
 use std::{
@@ -266,11 +268,16 @@ impl Abstract {
     }
 
     /// Returns the omnitruncate of a polytope, along with the flags that make
-    /// up its respective vertices.
+    /// up its respective vertices. The omnitruncate of the nullitope is the
+    /// nullitope itself, which has no vertices.
     ///
     /// # Panics
     /// You must call [`Polytope::element_sort`] before calling this method.
     pub fn omnitruncate_and_flags(&self) -> (Self, Vec<Flag>) {
+        if self.rank() == 0 {
+            return (Self::nullitope(), Vec::new());
+        }
+
         let mut flag_sets = vec![FlagSet::new_all(self)];
         let mut new_flag_sets = Vec::new();
         let rank = self.rank();
@@ -460,7 +467,43 @@ impl Abstract {
         // write proof].
         (unsafe { builder.build() }, verts_subflags)
     }
-    
+
+    /// Returns the rectification of the polytope as an abstract polytope,
+    /// whose vertices are the edges of the original. For polyhedra, this is
+    /// also known as the medial. Returns `None` if the polytope has no edges.
+    pub fn rectify(&self) -> Option<Self> {
+        self.wythoff_truncate(vec![1])
+    }
+
+    /// Returns the truncation of the polytope as an abstract polytope, whose
+    /// vertices are the two ends of each edge of the original. Returns `None`
+    /// if the polytope has no edges.
+    pub fn truncate(&self) -> Option<Self> {
+        self.wythoff_truncate(vec![0, 1])
+    }
+
+    /// Returns the Petrie contraction of a polyhedron, which contracts every
+    /// Petrie polygon into a vertex. Each edge lies on two Petrie polygons and
+    /// joins them, and each vertex of the original becomes a face. This is the
+    /// dual of the [Petrial](Polytope::petrial), so it's only defined when the
+    /// Petrial is. Returns `None` otherwise, or if the polytope isn't a
+    /// polyhedron.
+    pub fn petrie_contract(&self) -> Option<Self> {
+        self.petrial().map(|petrial| petrial.dual())
+    }
+
+    /// Sorts a copy of the polytope and truncates it with the given ringed
+    /// nodes, as long as it has ranks for them.
+    fn wythoff_truncate(&self, truncate_type: Vec<usize>) -> Option<Self> {
+        if self.rank() < 3 {
+            return None;
+        }
+
+        let mut poly = self.clone();
+        poly.element_sort();
+        Some(poly.truncate_and_flags(truncate_type).0)
+    }
+
     /// Returns whether a polytope is compound
     ///
     /// # Panics
@@ -859,10 +902,11 @@ impl Polytope for Abstract {
             faces.push(face.into_iter().collect());
         }
 
-        // Safety: TODO we need to define the safety guarantees of this function.
-        let ranks = unsafe { self.ranks_mut() };
+        // We build the Petrial on a copy, so that the polytope is left
+        // unchanged if it turns out not to be valid.
+        let mut ranks = self.ranks().clone();
 
-        // Removes the faces and maximal polytope from self.
+        // Removes the faces and maximal polytope.
         ranks.pop();
         ranks.pop();
 
@@ -887,19 +931,32 @@ impl Polytope for Abstract {
         ranks.push(new_faces);
         ranks.push(ElementList::max(face_count));
 
-        // Checks for dyadicity, since that sometimes fails.
-        ranks.ranks().is_dyadic().is_ok()
-
-        // TODO MAKE THIS SOUND instead of just returning whether it failed or not!
+        // The result isn't always a valid polytope, most often because it
+        // isn't dyadic, so we check it before replacing the original.
+        match AbstractBuilder::from(ranks).build_checked() {
+            Ok(petrial) => {
+                *self = petrial;
+                true
+            }
+            Err(_) => false,
+        }
     }
 
     fn petrie_polygon_with(&mut self, flag: Flag) -> Option<Self> {
+        self.element_sort();
         Some(Self::polygon(self.petrie_polygon_vertices(flag)?.len()))
     }
 
-    /// Returns the flag omnitruncate of a polytope.
+    /// Returns the flag omnitruncate of a polytope, whose vertices are the
+    /// flags of the original.
     fn omnitruncate(&self) -> Self {
-        self.omnitruncate_and_flags().0
+        if self.sorted() {
+            self.omnitruncate_and_flags().0
+        } else {
+            let mut poly = self.clone();
+            poly.element_sort();
+            poly.omnitruncate_and_flags().0
+        }
     }
 
     /// Builds a [ditope](https://polytope.miraheze.org/wiki/Ditope) of a given
@@ -1133,6 +1190,76 @@ mod tests {
         }
     }
 
+    /// Rectifies and truncates a cube and a hexagon.
+    #[test]
+    fn truncations() {
+        test(&Abstract::cube().rectify().unwrap(), [1, 12, 24, 14, 1]);
+        test(&Abstract::cube().truncate().unwrap(), [1, 24, 36, 14, 1]);
+        test(&Abstract::polygon(6).rectify().unwrap(), [1, 6, 6, 1]);
+        test(&Abstract::polygon(6).truncate().unwrap(), [1, 12, 12, 1]);
+        assert!(Abstract::dyad().rectify().is_none());
+    }
+
+    /// Contracts the Petrie polygons of a tetrahedron, which gives the
+    /// hemi-octahedron, and of a cube.
+    #[test]
+    fn petrie_contract() {
+        let hemioctahedron = Abstract::tetrahedron().petrie_contract().unwrap();
+        test(&hemioctahedron, [1, 3, 6, 4, 1]);
+        assert!(!hemioctahedron.orientable());
+
+        test(&Abstract::cube().petrie_contract().unwrap(), [1, 4, 12, 8, 1]);
+        assert!(Abstract::polygon(4).pyramid().petrie_contract().is_none());
+        assert!(Abstract::polygon(5).petrie_contract().is_none());
+    }
+
+    /// Omnitruncates a cube, a hexagon, a point and the nullitope.
+    #[test]
+    fn omnitruncate() {
+        test(&Abstract::cube().omnitruncate(), [1, 48, 72, 26, 1]);
+        test(&Abstract::polygon(6).omnitruncate(), [1, 12, 12, 1]);
+        test(&Abstract::point().omnitruncate(), [1, 1]);
+        test(&Abstract::nullitope().omnitruncate(), [1]);
+    }
+
+    /// Takes sections of a cube and a tesseract.
+    #[test]
+    fn section() {
+        let cube = Abstract::cube();
+        test(&cube.section(0, 0, 3, 0).unwrap().unwrap(), [1, 4, 4, 1]);
+        test(&cube.section(1, 0, 4, 0).unwrap().unwrap(), [1, 3, 3, 1]);
+
+        // A vertex and a face through it bound a dyad, while a vertex and a
+        // face away from it don't bound anything.
+        let vertices = cube.element_vertices(3, 0).unwrap();
+        let inside = vertices[0];
+        let outside = (0..8).find(|v| !vertices.contains(v)).unwrap();
+        test(&cube.section(1, inside, 3, 0).unwrap().unwrap(), [1, 2, 1]);
+        assert!(cube.section(1, outside, 3, 0).unwrap().is_none());
+
+        // Every vertex of every cell of a tesseract has a triangle as its
+        // figure within the cell.
+        let tesseract = Abstract::hypercube(5);
+        for idx in 0..8 {
+            for v in tesseract.element_vertices(4, idx).unwrap() {
+                test(&tesseract.section(1, v, 4, idx).unwrap().unwrap(), [1, 3, 3, 1]);
+            }
+        }
+    }
+
+    /// Checks that the Petrial of a cube is valid, and that a polytope whose
+    /// Petrial isn't is left unchanged.
+    #[test]
+    fn petrial() {
+        let mut cube = Abstract::cube();
+        assert!(cube.petrial_mut());
+        test(&cube, [1, 8, 12, 4, 1]);
+
+        let mut pyramid = Abstract::polygon(4).pyramid();
+        assert!(!pyramid.petrial_mut());
+        test(&pyramid, [1, 5, 8, 5, 1]);
+    }
+
     /// Tests a few duals.
     #[test]
     fn dual() {
@@ -1,6 +1,23 @@
 //! Contains the code for the polytope products.
 
 use super::*;
+use crate::cancel::{Cancelled, CancellationToken};
+
+/// The four products that can be taken between two polytopes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProductType {
+    /// The [pyramid product](https://polytope.miraheze.org/wiki/Pyramid_product).
+    Pyramid,
+
+    /// The [prism product](https://polytope.miraheze.org/wiki/Prism_product).
+    Prism,
+
+    /// The [tegum product](https://polytope.miraheze.org/wiki/Tegum_product).
+    Tegum,
+
+    /// The [honeycomb product](https://polytope.miraheze.org/wiki/Honeycomb_product).
+    Comb,
+}
 
 /// When we compute any polytope product, we add the elements of any given rank
 /// in lexicographic order of the ranks of the elements they come from. This
@@ -115,7 +132,14 @@ impl<const MIN: bool, const MAX: bool> Index<(usize, usize)> for OffsetMemo<MIN,
 /// of elements in the set of polytopes. The elements of a specific rank are
 /// sorted first by lexicographic order of the ranks, then by lexicographic
 /// order of the indices of the elements.
-fn product<const MIN: bool, const MAX: bool>(p: &Abstract, q: &Abstract) -> Abstract {
+///
+/// The token is checked once for every element of `p` that's multiplied, so
+/// that big products can be stopped midway.
+fn product<const MIN: bool, const MAX: bool>(
+    p: &Abstract,
+    q: &Abstract,
+    cancel: &CancellationToken,
+) -> Result<Abstract, Cancelled> {
     // The ranks of p and q.
     let p_rank = p.rank();
     let q_rank = q.rank();
@@ -123,7 +147,7 @@ fn product<const MIN: bool, const MAX: bool>(p: &Abstract, q: &Abstract) -> Abst
     // For anything but a pyramid product, we'll just special-case the product
     // with a nullitope as the nullitope.
     if (MIN || MAX) && (p_rank == 0 || q_rank == 0) {
-        return Abstract::nullitope();
+        return Ok(Abstract::nullitope());
     }
 
     // 0 or 1 depending on whether the minimum/maximum elements are in the
@@ -167,6 +191,8 @@ fn product<const MIN: bool, const MAX: bool>(p: &Abstract, q: &Abstract) -> Abst
             // Takes the product of every element in p with rank p_els_rank,
             // with every element in q with rank q_els_rank.
             for (p_idx, p_el) in p[p_el_rank].iter().enumerate() {
+                cancel.check()?;
+
                 for (q_idx, q_el) in q[q_el_rank].iter().enumerate() {
                     let mut subs = Subelements::new();
 
@@ -214,7 +240,30 @@ fn product<const MIN: bool, const MAX: bool>(p: &Abstract, q: &Abstract) -> Abst
     // Safety: we've built one of the four products on polytopes. For a
     // proof that these constructions yield valid abstract polytopes, see
     // [TODO: write proof].
-    unsafe { builder.build() }
+    Ok(unsafe { builder.build() })
+}
+
+/// Builds one of the four products of two polytopes, stopping early if the
+/// token is cancelled. The vertices are ordered just as in the corresponding
+/// method of [`Polytope`].
+pub fn try_product(
+    p: &Abstract,
+    q: &Abstract,
+    product_type: ProductType,
+    cancel: &CancellationToken,
+) -> Result<Abstract, Cancelled> {
+    match product_type {
+        ProductType::Pyramid => product::<false, false>(q, p, cancel),
+        ProductType::Prism => product::<true, false>(p, q, cancel),
+        ProductType::Tegum => product::<false, true>(q, p, cancel),
+        ProductType::Comb => product::<true, true>(p, q, cancel),
+    }
+}
+
+/// Builds a product that can't be cancelled.
+fn uncancellable(p: &Abstract, q: &Abstract, product_type: ProductType) -> Abstract {
+    try_product(p, q, product_type, &CancellationToken::new())
+        .expect("a fresh token is never cancelled")
 }
 
 /// Builds a [duopyramid](https://polytope.miraheze.org/wiki/Pyramid_product)
@@ -224,13 +273,13 @@ fn product<const MIN: bool, const MAX: bool>(p: &Abstract, q: &Abstract) -> Abst
 /// `p` in the same order, following those corresponding to `q` in the same
 /// order.
 pub(super) fn duopyramid(p: &Abstract, q: &Abstract) -> Abstract {
-    product::<false, false>(q, p)
+    uncancellable(p, q, ProductType::Pyramid)
 }
 
 /// Builds a [duoprism](https://polytope.miraheze.org/wiki/Prism_product)
 /// from two polytopes. This is a [`product`] where `MIN` and `!MAX`.
 pub(super) fn duoprism(p: &Abstract, q: &Abstract) -> Abstract {
-    product::<true, false>(p, q)
+    uncancellable(p, q, ProductType::Prism)
 }
 
 /// Builds a [duotegum](https://polytope.miraheze.org/wiki/Tegum_product)
@@ -240,13 +289,13 @@ pub(super) fn duoprism(p: &Abstract, q: &Abstract) -> Abstract {
 /// `p` in the same order, following those corresponding to `q` in the same
 /// order.
 pub(super) fn duotegum(p: &Abstract, q: &Abstract) -> Abstract {
-    product::<false, true>(q, p)
+    uncancellable(p, q, ProductType::Tegum)
 }
 
 /// Builds a [duocomb](https://polytope.miraheze.org/wiki/Honeycomb_product)
 /// from two polytopes. This is a [`product`] where `MIN` and `MAX`.
 pub(super) fn duocomb(p: &Abstract, q: &Abstract) -> Abstract {
-    product::<true, true>(p, q)
+    uncancellable(p, q, ProductType::Comb)
 }
 
 #[cfg(test)]
@@ -321,4 +370,21 @@ mod tests {
     fn duocomb() {
         test_duoproduct(Abstract::duocomb, |m, n| [1, m * n, 2 * m * n, m * n, 1])
     }
+
+    /// Checks that a cancelled token stops a product.
+    #[test]
+    fn cancelled() {
+        let cube = Abstract::cube();
+        let cancel = CancellationToken::new();
+        test(
+            &try_product(&cube, &cube, ProductType::Prism, &cancel).unwrap(),
+            [1, 64, 192, 240, 160, 60, 12, 1],
+        );
+
+        cancel.cancel();
+        assert_eq!(
+            try_product(&cube, &cube, ProductType::Prism, &cancel).err(),
+            Some(Cancelled)
+        );
+    }
 }
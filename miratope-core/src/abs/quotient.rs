@@ -0,0 +1,198 @@
+//! Contains the code to build the quotient of a polytope by a group of its
+//! automorphisms, like the hemicube, the quotient of the cube by the central
+//! inversion.
+
+use std::collections::{HashMap, VecDeque};
+
+use super::{Abstract, AbstractBuilder, AbstractError, AbstractResult, Ranked, SubelementList, Subelements};
+
+use vec_like::*;
+
+/// An automorphism of a polytope, as the index of the image of every element.
+/// The list at index `r` holds the images of the elements of rank `r`.
+pub type ElementMap = Vec<Vec<usize>>;
+
+impl Abstract {
+    /// Extends a permutation of the vertices into the automorphism that takes
+    /// every element to the one whose subelements are the images of its own.
+    /// Returns `None` if there's no such automorphism.
+    pub fn element_map(&self, vertex_map: &[usize]) -> Option<ElementMap> {
+        let rank = self.rank();
+        if rank == 0 || vertex_map.len() != self.vertex_count() {
+            return None;
+        }
+
+        let mut element_map = vec![vec![0], vertex_map.to_vec()];
+        for r in 2..=rank {
+            let prev = &element_map[r - 1];
+            let sorted_subs = |idx: usize| {
+                let mut subs: Vec<usize> = self[(r, idx)].subs.iter().copied().collect();
+                subs.sort_unstable();
+                subs
+            };
+            let index_of: HashMap<_, _> = (0..self.el_count(r)).map(|idx| (sorted_subs(idx), idx)).collect();
+
+            let mut images = Vec::with_capacity(self.el_count(r));
+            let mut seen = vec![false; self.el_count(r)];
+            for idx in 0..self.el_count(r) {
+                let mut subs: Vec<usize> = sorted_subs(idx).into_iter().map(|sub| prev[sub]).collect();
+                subs.sort_unstable();
+
+                let image = *index_of.get(&subs)?;
+                if std::mem::replace(&mut seen[image], true) {
+                    return None;
+                }
+                images.push(image);
+            }
+
+            element_map.push(images);
+        }
+
+        Some(element_map)
+    }
+
+    /// Returns the quotient of the polytope by the group generated by some
+    /// automorphisms, along with the index of the element of the quotient
+    /// each element is sent to. The elements of the quotient are numbered in
+    /// the order their first preimages appear.
+    ///
+    /// Returns an error if the quotient isn't a valid polytope, which happens
+    /// whenever some element gets identified with an element it's incident
+    /// to, like when the automorphisms fix a flag of some section. In
+    /// particular, we reject any element with two subelements in the same
+    /// orbit, like a square whose opposite edges are identified.
+    ///
+    /// # Panics
+    /// Panics if some automorphism doesn't have an image for every element.
+    pub fn quotient_and_orbits(&self, identifications: &[ElementMap]) -> AbstractResult<(Self, ElementMap)> {
+        let rank = self.rank();
+
+        // Orbits are the connected components of the graph where each element
+        // is joined to its images, since the group is finite.
+        let mut orbits = Vec::with_capacity(rank + 1);
+        let mut orbit_counts = Vec::with_capacity(rank + 1);
+        for r in 0..=rank {
+            let el_count = self.el_count(r);
+            let mut orbit_of = vec![None; el_count];
+            let mut orbit_count = 0;
+
+            for idx in 0..el_count {
+                if orbit_of[idx].is_some() {
+                    continue;
+                }
+
+                orbit_of[idx] = Some(orbit_count);
+                let mut queue = VecDeque::from([idx]);
+                while let Some(el) = queue.pop_front() {
+                    for identification in identifications {
+                        let image = identification[r][el];
+                        if orbit_of[image].is_none() {
+                            orbit_of[image] = Some(orbit_count);
+                            queue.push_back(image);
+                        }
+                    }
+                }
+                orbit_count += 1;
+            }
+
+            orbits.push(orbit_of.into_iter().map(Option::unwrap).collect::<Vec<_>>());
+            orbit_counts.push(orbit_count);
+        }
+
+        let mut builder = AbstractBuilder::with_rank_capacity(rank);
+        builder.push_min();
+        if rank >= 1 {
+            builder.push_vertices(orbit_counts[1]);
+        }
+
+        // The subelements of an orbit are the orbits of the subelements of
+        // any element in it.
+        for r in 2..rank {
+            let mut representatives = vec![None; orbit_counts[r]];
+            for (idx, &orbit) in orbits[r].iter().enumerate() {
+                representatives[orbit].get_or_insert(idx);
+            }
+
+            let mut list = SubelementList::with_capacity(orbit_counts[r]);
+            for (orbit, idx) in representatives.into_iter().map(Option::unwrap).enumerate() {
+                let mut subs: Vec<usize> = self[(r, idx)].subs.iter().map(|&sub| orbits[r - 1][sub]).collect();
+                let len = subs.len();
+                subs.sort_unstable();
+                subs.dedup();
+                if subs.len() != len {
+                    return Err(AbstractError::Identified { el: (r, orbit) });
+                }
+                list.push(Subelements::from(subs));
+            }
+            builder.push(list);
+        }
+
+        if rank >= 2 {
+            builder.push_max();
+        }
+
+        Ok((builder.build_checked()?, orbits))
+    }
+
+    /// Returns the quotient of the polytope by the group generated by some
+    /// automorphisms. See [`Self::quotient_and_orbits`].
+    pub fn quotient(&self, identifications: &[ElementMap]) -> AbstractResult<Self> {
+        Ok(self.quotient_and_orbits(identifications)?.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{conc::Concrete, geometry::Point, Polytope};
+
+    use super::*;
+
+    /// The half-turn of a hexagon extends to its edges, and identifies it
+    /// down to a triangle.
+    #[test]
+    fn hexagon() {
+        let hexagon = Abstract::polygon(6);
+        let half_turn = hexagon.element_map(&[3, 4, 5, 0, 1, 2]).unwrap();
+        assert_eq!(half_turn[2], [3, 4, 5, 0, 1, 2]);
+
+        let (triangle, orbits) = hexagon.quotient_and_orbits(&[half_turn]).unwrap();
+        assert_eq!(triangle.el_count_iter().collect::<Vec<_>>(), [1, 3, 3, 1]);
+        assert_eq!(orbits[1], [0, 1, 2, 0, 1, 2]);
+
+        // Identifying nothing leaves the polytope as it is.
+        let identity = hexagon.element_map(&(0..6).collect::<Vec<_>>()).unwrap();
+        let same = hexagon.quotient(&[identity]).unwrap();
+        assert_eq!(same.el_count_iter().collect::<Vec<_>>(), [1, 6, 6, 1]);
+    }
+
+    /// Quotients that identify incident elements aren't polytopes.
+    #[test]
+    fn invalid() {
+        // Swaps two vertices of a triangle, which fixes an edge.
+        let triangle = Abstract::polygon(3);
+        let reflection = triangle.element_map(&[1, 0, 2]).unwrap();
+        assert!(triangle.quotient(&[reflection]).is_err());
+
+        // The half-turn of a cube about a face axis identifies the opposite
+        // edges of two of its squares.
+        let cube = Concrete::cube();
+        let half_turn: Vec<_> = cube
+            .vertices
+            .iter()
+            .map(|v| {
+                let image = Point::from_vec(vec![-v[0], -v[1], v[2]]);
+                cube.vertices.iter().position(|w| (w - &image).norm() < 1e-9).unwrap()
+            })
+            .collect();
+        let half_turn = cube.abs.element_map(&half_turn).unwrap();
+        assert!(matches!(
+            cube.abs.quotient(&[half_turn]),
+            Err(AbstractError::Identified { el: (3, _) })
+        ));
+
+        // Permutations that don't come from automorphisms are rejected.
+        let square = Abstract::polygon(4);
+        assert!(square.element_map(&[0, 2, 1, 3]).is_none());
+        assert!(square.element_map(&[0, 1, 2]).is_none());
+    }
+}
@@ -8,7 +8,7 @@ use std::{
     slice, vec,
 };
 
-use super::Abstract;
+use super::{Abstract, AbstractResult};
 
 use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
 use vec_like::*;
@@ -604,6 +604,33 @@ impl AbstractBuilder {
     pub unsafe fn build(self) -> Abstract {
         unsafe { Abstract::from_ranks(self.0) }
     }
+
+    /// Returns the built polytope, consuming the builder in the process, after
+    /// checking that it's bounded, that its incidences are consistent and
+    /// sorted, and that it's dyadic. Unlike [`Self::build`], this can be
+    /// called on anything.
+    ///
+    /// The subelements of each element must be sorted by index. The
+    /// superelements will be, as long as the elements were pushed in order.
+    pub fn build_checked(self) -> AbstractResult<Abstract> {
+        self.0.is_valid()?;
+        self.0.check_sorted()?;
+
+        // Safety: we've just checked the polytope is valid and sorted.
+        unsafe {
+            let mut abs = self.build();
+            abs.set_sorted(true);
+            Ok(abs)
+        }
+    }
+
+    /// Returns the built polytope like [`Self::build_checked`], but also
+    /// checks that it's strongly connected, which rules out compounds.
+    pub fn build_checked_connected(self) -> AbstractResult<Abstract> {
+        let abs = self.build_checked()?;
+        abs.ranks().check_strongly_connected()?;
+        Ok(abs)
+    }
 }
 
 impl Extend<Subelements> for AbstractBuilder {
@@ -680,6 +707,12 @@ impl ElementHash {
         self.0.get(idx)
     }
 
+    /// Gets the index that an element of the original polytope gets in the new
+    /// one, or `None` if it isn't a subelement of the chosen element.
+    pub fn index(&self, rank: usize, idx: usize) -> Option<usize> {
+        self.get(rank)?.get(&idx).copied()
+    }
+
     /// Gets the indices of the elements of a given rank in the original
     /// polytope.
     fn to_elements(&self, rank: usize) -> Vec<usize> {
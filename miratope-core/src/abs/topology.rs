@@ -0,0 +1,300 @@
+//! Contains the code to find the topological invariants of a polytope, like
+//! its Euler characteristic and, for polyhedra, the genus of their surface.
+
+use std::{collections::BTreeMap, fmt::Display};
+
+use super::{Abstract, Ranked};
+use crate::Polytope;
+
+/// The genus of a closed surface, which along with its orientability
+/// determines it up to homeomorphism.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Genus {
+    /// An orientable surface, made out of a sphere with a number of handles.
+    Orientable(usize),
+
+    /// A non-orientable surface, made out of a sphere with a number of
+    /// cross-caps.
+    NonOrientable(usize),
+}
+
+impl Genus {
+    /// Returns the genus of a connected closed surface with a given Euler
+    /// characteristic, or `None` if no such surface exists.
+    pub fn new(euler_characteristic: isize, orientable: bool) -> Option<Self> {
+        let genus = 2 - euler_characteristic;
+        if genus < 0 {
+            return None;
+        }
+
+        if orientable {
+            (genus % 2 == 0).then_some(Self::Orientable(genus as usize / 2))
+        } else {
+            (genus > 0).then_some(Self::NonOrientable(genus as usize))
+        }
+    }
+
+    /// Returns the genus of the orientable double cover of a non-orientable
+    /// surface, which has twice its Euler characteristic.
+    pub fn double_cover(self) -> Option<Self> {
+        match self {
+            Self::Orientable(_) => None,
+            Self::NonOrientable(genus) => Some(Self::Orientable(genus - 1)),
+        }
+    }
+}
+
+impl Display for Genus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Orientable(0) => write!(f, "sphere"),
+            Self::Orientable(1) => write!(f, "torus"),
+            Self::Orientable(genus) => write!(f, "orientable surface of genus {}", genus),
+            Self::NonOrientable(1) => write!(f, "projective plane"),
+            Self::NonOrientable(2) => write!(f, "Klein bottle"),
+            Self::NonOrientable(genus) => write!(f, "non-orientable surface of genus {}", genus),
+        }
+    }
+}
+
+/// The topological invariants of a polytope.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Topology {
+    /// The Euler characteristic, the alternating sum of the numbers of
+    /// elements from the vertices up to the facets.
+    pub euler_characteristic: isize,
+
+    /// The Euler characteristics of the elements of each rank, along with how
+    /// many elements have each of them. The list at index `r` holds the
+    /// elements of rank `r`.
+    pub element_euler_characteristics: Vec<Vec<(isize, usize)>>,
+
+    /// Whether the polytope is orientable.
+    pub orientable: bool,
+
+    /// The surface a polyhedron is made out of, if it's connected and its
+    /// vertex figures are single polygons.
+    pub genus: Option<Genus>,
+}
+
+impl Display for Topology {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Euler characteristic: {}", self.euler_characteristic)?;
+        writeln!(
+            f,
+            "Orientable: {}",
+            if self.orientable { "yes" } else { "no" }
+        )?;
+
+        if let Some(genus) = self.genus {
+            writeln!(f, "Surface: {}", genus)?;
+            if let Some(cover) = genus.double_cover() {
+                writeln!(f, "Orientable double cover: {}", cover)?;
+            }
+        }
+
+        for (rank, characteristics) in self
+            .element_euler_characteristics
+            .iter()
+            .enumerate()
+            .skip(2)
+        {
+            let characteristics: Vec<_> = characteristics
+                .iter()
+                .map(|(euler, count)| format!("{} ×{}", euler, count))
+                .collect();
+            writeln!(f, "Rank {} elements: {}", rank, characteristics.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Abstract {
+    /// Returns the [Euler characteristic](https://polytope.miraheze.org/wiki/Euler_characteristic)
+    /// of the polytope, the alternating sum of its numbers of vertices,
+    /// edges, and so on up to its facets. Spherical polytopes of even and odd
+    /// rank have characteristics 2 and 0, respectively.
+    pub fn euler_characteristic(&self) -> isize {
+        (1..self.rank())
+            .map(|r| if r % 2 == 1 { 1 } else { -1 } * self.el_count(r) as isize)
+            .sum()
+    }
+
+    /// Computes the topological invariants of the polytope.
+    pub fn topology(&self) -> Topology {
+        let mut poly = self.clone();
+        poly.element_sort();
+        let rank = poly.rank();
+
+        let element_euler_characteristics = (0..rank)
+            .map(|r| {
+                let mut counts = BTreeMap::new();
+                for idx in 0..poly.el_count(r) {
+                    let euler = poly.element(r, idx).unwrap().euler_characteristic();
+                    *counts.entry(euler).or_default() += 1;
+                }
+                counts.into_iter().collect()
+            })
+            .collect();
+
+        let euler_characteristic = poly.euler_characteristic();
+        let orientable = poly.orientable();
+
+        // The surface of a polyhedron is only a manifold if the faces around
+        // each vertex form a single cycle. The vertex figures are the duals of
+        // the facets of the dual.
+        let genus = if rank == 4 && !poly.is_compound() && {
+            let dual = poly.dual();
+            (0..dual.el_count(rank - 1)).all(|idx| {
+                let mut figure = dual.element(rank - 1, idx).unwrap();
+                figure.element_sort();
+                !figure.is_compound()
+            })
+        } {
+            Genus::new(euler_characteristic, orientable)
+        } else {
+            None
+        };
+
+        Topology {
+            euler_characteristic,
+            element_euler_characteristics,
+            orientable,
+            genus,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abs::{AbstractBuilder, SubelementList, Subelements};
+
+    use vec_like::*;
+
+    /// Spheres have the expected Euler characteristics.
+    #[test]
+    fn euler_characteristic() {
+        for rank in 1..=6 {
+            let expected = if rank % 2 == 0 { 2 } else { 0 };
+            assert_eq!(Abstract::simplex(rank).euler_characteristic(), expected);
+            assert_eq!(Abstract::hypercube(rank).euler_characteristic(), expected);
+        }
+    }
+
+    /// The cube is a sphere, and its elements are spheres too.
+    #[test]
+    fn cube() {
+        let topology = Abstract::cube().topology();
+        assert_eq!(topology.euler_characteristic, 2);
+        assert!(topology.orientable);
+        assert_eq!(topology.genus, Some(Genus::Orientable(0)));
+        assert_eq!(topology.element_euler_characteristics[3], [(0, 6)]);
+        assert_eq!(topology.element_euler_characteristics[2], [(2, 12)]);
+    }
+
+    /// Builds the {4,4}₍₃,₀₎, a torus made out of a 3 × 3 grid of squares.
+    fn torus() -> Abstract {
+        let vertex = |i: usize, j: usize| 3 * (i % 3) + j % 3;
+        let sorted = |mut subs: Vec<usize>| {
+            subs.sort_unstable();
+            Subelements::from(subs)
+        };
+        let mut edges = SubelementList::new();
+        for i in 0..3 {
+            for j in 0..3 {
+                edges.push(sorted(vec![vertex(i, j), vertex(i, j + 1)]));
+            }
+        }
+        for i in 0..3 {
+            for j in 0..3 {
+                edges.push(sorted(vec![vertex(i, j), vertex(i + 1, j)]));
+            }
+        }
+
+        let mut faces = SubelementList::new();
+        for i in 0..3 {
+            for j in 0..3 {
+                faces.push(sorted(vec![
+                    vertex(i, j),
+                    vertex(i + 1, j),
+                    9 + vertex(i, j),
+                    9 + vertex(i, j + 1),
+                ]));
+            }
+        }
+
+        let mut builder = AbstractBuilder::new();
+        builder.push_min();
+        builder.push_vertices(9);
+        builder.push(edges);
+        builder.push(faces);
+        builder.push_max();
+        builder.build_checked().unwrap()
+    }
+
+    /// The {4,4}₍₃,₀₎ is a torus.
+    #[test]
+    fn torus_genus() {
+        let topology = torus().topology();
+        assert_eq!(topology.euler_characteristic, 0);
+        assert!(topology.orientable);
+        assert_eq!(topology.genus, Some(Genus::Orientable(1)));
+    }
+
+    /// The hemicube is a projective plane, covered by the cube.
+    #[test]
+    fn hemicube() {
+        let cube = crate::conc::Concrete::cube();
+        let inversion: Vec<_> = cube
+            .vertices
+            .iter()
+            .map(|v| {
+                cube.vertices
+                    .iter()
+                    .position(|w| (v + w).norm() < 1e-9)
+                    .unwrap()
+            })
+            .collect();
+        let hemicube = cube
+            .abs
+            .quotient(&[cube.abs.element_map(&inversion).unwrap()])
+            .unwrap();
+
+        let topology = hemicube.topology();
+        assert_eq!(topology.euler_characteristic, 1);
+        assert!(!topology.orientable);
+        assert_eq!(topology.genus, Some(Genus::NonOrientable(1)));
+        assert_eq!(
+            topology.genus.unwrap().double_cover(),
+            Some(Genus::Orientable(0))
+        );
+    }
+
+    /// Compounds aren't surfaces, even though they have Euler
+    /// characteristics.
+    #[test]
+    fn compound() {
+        let mut compound = Abstract::cube();
+        compound.comp_append(Abstract::cube());
+        let topology = compound.topology();
+        assert_eq!(topology.euler_characteristic, 4);
+        assert_eq!(topology.genus, None);
+    }
+
+    /// Builds the genera of a few surfaces from their Euler characteristics.
+    #[test]
+    fn genus() {
+        assert_eq!(Genus::new(2, true), Some(Genus::Orientable(0)));
+        assert_eq!(Genus::new(0, true), Some(Genus::Orientable(1)));
+        assert_eq!(Genus::new(1, false), Some(Genus::NonOrientable(1)));
+        assert_eq!(Genus::new(1, true), None);
+        assert_eq!(Genus::new(3, false), None);
+        assert_eq!(
+            Genus::NonOrientable(1).double_cover(),
+            Some(Genus::Orientable(0))
+        );
+        assert_eq!(Genus::NonOrientable(1).to_string(), "projective plane");
+    }
+}
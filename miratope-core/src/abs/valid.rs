@@ -1,9 +1,7 @@
 //! Contains the code that verifies whether a set of [`Ranks`] correspond to a
 //! valid [`Abstract`](crate::Abstract) polytope.
 
-// TODO: finish these!
-
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use strum_macros::Display;
 use vec_like::VecLike;
@@ -83,9 +81,26 @@ pub enum AbstractError {
         more: bool,
     },
 
+    /// Some element's subelements or superelements aren't sorted by index, or
+    /// have repeats.
+    Unsorted {
+        /// The coordinates of the element at fault.
+        el: (usize, usize),
+
+        /// Whether the unsorted indices are of subelements or superelements.
+        incidence_type: IncidenceType,
+    },
+
     /// The polytope is not strictly connected, i.e. some section's flags don't
     /// form a connected graph under flag changes.
     Connected(Section),
+
+    /// Some element of a quotient has two subelements that were identified
+    /// with one another.
+    Identified {
+        /// The coordinates of the element at fault.
+        el: (usize, usize),
+    },
 }
 
 impl std::fmt::Display for AbstractError {
@@ -137,12 +152,26 @@ impl std::fmt::Display for AbstractError {
                 section
             ),
 
+            // The polytope has unsorted indices.
+            AbstractError::Unsorted { el, incidence_type } => write!(
+                f,
+                "Polytope has unsorted indices: the {}s of {:?} aren't strictly increasing",
+                incidence_type, el
+            ),
+
             // The polytope is not strictly connected.
             AbstractError::Connected(section) => write!(
                 f,
                 "Polytope is not strictly connected: {} is not connected",
                 section
             ),
+
+            // The quotient identifies some subelements of an element.
+            AbstractError::Identified { el } => write!(
+                f,
+                "Quotient is not a polytope: some subelements of {:?} were identified",
+                el
+            ),
         }
     }
 }
@@ -274,7 +303,7 @@ impl Ranks {
 
         // For every element, by looking through the subelements of its
         // subelements, we need to find each exactly twice.
-        for r in 2..self.rank() {
+        for r in 2..=self.rank() {
             for (idx, el) in self[r].iter().enumerate() {
                 let mut hash_sub_subs = HashMap::new();
 
@@ -316,18 +345,195 @@ impl Ranks {
         Ok(())
     }
 
-    /// Determines whether the polytope is connected. A valid non-compound
-    /// polytope should always return `true`.
-    pub fn is_connected(&self, _section: Section) -> bool {
-        todo!()
-        /*
-        let section = self.get_section(section).unwrap();
-        section.flags().count() == section.oriented_flags().count() */
+    /// Checks whether the subelements and superelements of every element are
+    /// sorted by index, without repeats.
+    pub fn check_sorted(&self) -> AbstractResult<()> {
+        let increasing = |indices: &[usize]| indices.windows(2).all(|w| w[0] < w[1]);
+
+        for (r, elements) in self.iter().enumerate() {
+            for (idx, el) in elements.iter().enumerate() {
+                for (indices, incidence_type) in [
+                    (el.subs.as_slice(), IncidenceType::Subelement),
+                    (el.sups.as_slice(), IncidenceType::Superelement),
+                ] {
+                    if !increasing(indices) {
+                        return Err(AbstractError::Unsorted {
+                            el: (r, idx),
+                            incidence_type,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the elements under a given one of each rank, including itself.
+    fn elements_under(&self, rank: usize, idx: usize) -> Vec<HashSet<usize>> {
+        let mut under = vec![HashSet::new(); rank + 1];
+        under[rank].insert(idx);
+
+        for r in (1..=rank).rev() {
+            let (lower, upper) = under.split_at_mut(r);
+            for &el in &upper[0] {
+                lower[r - 1].extend(self[(r, el)].subs.iter().copied());
+            }
+        }
+
+        under
+    }
+
+    /// Determines whether a section is connected, given the elements under its
+    /// upper element. That is, whether its elements other than the lower and
+    /// upper ones are all linked by chains of incidences.
+    fn section_connected(&self, section: Section, under: &[HashSet<usize>]) -> bool {
+        let (lo_rank, hi_rank) = (section.lo_rank, section.hi_rank);
+        if hi_rank < lo_rank + 3 {
+            return true;
+        }
+
+        // The elements of the section of each rank, other than the lower one.
+        let mut section_els = vec![HashSet::new(); hi_rank - lo_rank];
+        section_els[0].insert(section.lo_idx);
+        for r in 1..section_els.len() {
+            let (lower, upper) = section_els.split_at_mut(r);
+            for &el in &lower[r - 1] {
+                for &sup in &self[(lo_rank + r - 1, el)].sups {
+                    if under[lo_rank + r].contains(&sup) {
+                        upper[0].insert(sup);
+                    }
+                }
+            }
+        }
+
+        let Some(&first) = section_els[1].iter().next() else {
+            return true;
+        };
+        let total: usize = section_els[1..].iter().map(HashSet::len).sum();
+        let mut found = HashSet::from([(1, first)]);
+        let mut stack = vec![(1, first)];
+
+        while let Some((r, idx)) = stack.pop() {
+            let el = &self[(lo_rank + r, idx)];
+            let subs = el.subs.iter().map(|&sub| (r - 1, sub));
+            let sups = el.sups.iter().map(|&sup| (r + 1, sup));
+
+            for (r, idx) in subs.chain(sups) {
+                if (1..section_els.len()).contains(&r) && section_els[r].contains(&idx) && found.insert((r, idx)) {
+                    stack.push((r, idx));
+                }
+            }
+        }
+
+        found.len() == total
+    }
+
+    /// Determines whether a section of the polytope is connected, i.e.
+    /// whether its elements other than the lower and upper ones are all linked
+    /// by chains of incidences. Assumes the incidences are consistent.
+    pub fn is_connected(&self, section: Section) -> bool {
+        let under = self.elements_under(section.hi_rank, section.hi_idx);
+        self.section_connected(section, &under)
+    }
+
+    /// Checks whether the polytope is strongly connected, i.e. whether every
+    /// section of it is connected. A valid non-compound polytope should always
+    /// pass. Assumes the incidences are consistent.
+    pub fn check_strongly_connected(&self) -> AbstractResult<()> {
+        for hi_rank in 3..=self.rank() {
+            for hi_idx in 0..self.el_count(hi_rank) {
+                let under = self.elements_under(hi_rank, hi_idx);
+
+                for lo_rank in 0..=hi_rank - 3 {
+                    for &lo_idx in &under[lo_rank] {
+                        let section = Section::new(lo_rank, lo_idx, hi_rank, hi_idx);
+                        if !self.section_connected(section, &under) {
+                            return Err(AbstractError::Connected(section));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
     }
 
     /// Determines whether the polytope is strongly connected. A valid
     /// non-compound polytope should always return `true`.
     pub fn is_strongly_connected(&self) -> bool {
-        todo!()
+        self.check_strongly_connected().is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        abs::{Abstract, AbstractBuilder, Subelements},
+        Polytope,
+    };
+
+    /// Builds a polygon from the vertices of each edge.
+    fn polygon(edges: &[&[usize]], vertex_count: usize) -> AbstractBuilder {
+        let mut builder = AbstractBuilder::new();
+        builder.push_min();
+        builder.push_vertices(vertex_count);
+        builder.extend(edges.iter().map(|edge| Subelements::from(edge.to_vec())));
+        builder.push_max();
+        builder
+    }
+
+    /// Valid polytopes pass every check.
+    #[test]
+    fn valid() {
+        let abs = polygon(&[&[0, 1], &[1, 2], &[0, 2]], 3).build_checked_connected().unwrap();
+        assert!(abs.sorted());
+
+        let ranks = Abstract::hypercube(5).ranks().clone();
+        assert!(ranks.is_strongly_connected());
+        assert!(AbstractBuilder::from(ranks).build_checked_connected().is_ok());
+    }
+
+    /// A compound is valid, but not connected.
+    #[test]
+    fn compound() {
+        let edges: [&[usize]; 6] = [&[0, 1], &[1, 2], &[0, 2], &[3, 4], &[4, 5], &[3, 5]];
+        assert!(polygon(&edges, 6).build_checked().is_ok());
+
+        match polygon(&edges, 6).build_checked_connected() {
+            Err(AbstractError::Connected(section)) => assert_eq!(section, Section::new(0, 0, 3, 0)),
+            res => panic!("expected a connection error, got {:?}", res.map(|_| ())),
+        }
+    }
+
+    /// Some invalid polytopes, with the errors they give.
+    #[test]
+    fn invalid() {
+        // Unsorted subelements.
+        match polygon(&[&[0, 1], &[2, 1], &[0, 2]], 3).build_checked() {
+            Err(AbstractError::Unsorted { el, .. }) => assert_eq!(el, (2, 1)),
+            res => panic!("expected a sorting error, got {:?}", res.map(|_| ())),
+        }
+
+        // An edge with a single vertex.
+        match polygon(&[&[0, 1], &[1, 2], &[2]], 3).build_checked() {
+            Err(AbstractError::Dyadic { more, .. }) => assert!(!more),
+            res => panic!("expected a dyadicity error, got {:?}", res.map(|_| ())),
+        }
+
+        // A vertex that doesn't know it's in an edge.
+        let mut ranks = polygon(&[&[0, 1], &[1, 2], &[0, 2]], 3).ranks().clone();
+        ranks[(1, 0)].sups = vec![0].into();
+        match AbstractBuilder::from(ranks).build_checked() {
+            Err(AbstractError::Consistency { el, index, .. }) => assert_eq!((el, index), ((2, 2), 0)),
+            res => panic!("expected a consistency error, got {:?}", res.map(|_| ())),
+        }
+
+        // No maximal element.
+        let mut builder = AbstractBuilder::new();
+        builder.push_min();
+        builder.push_vertices(2);
+        assert!(matches!(builder.build_checked(), Err(AbstractError::Bounded { max_count: 2, .. })));
     }
 }
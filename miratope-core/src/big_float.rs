@@ -0,0 +1,697 @@
+//! Defines an arbitrary precision [`BigFloat`] type, which can be used in
+//! place of `f64` whenever the latter isn't precise enough.
+//!
+//! This module is only available with the `big-float` feature.
+
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    ops::{
+        Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+    },
+    str::FromStr,
+};
+
+use crate::float::Float;
+
+use approx::{AbsDiffEq, RelativeEq, UlpsEq};
+use dashu_base::{Abs, ParseError, Sign};
+use dashu_float::{
+    round::mode::{HalfAway, HalfEven},
+    Context, DBig, FBig,
+};
+use nalgebra::{ComplexField, Field, RealField, SimdValue};
+use num_traits::{FromPrimitive, Num, One, Signed, Zero};
+use serde::{Serialize, Serializer};
+use simba::scalar::SubsetOf;
+
+/// The underlying binary float type.
+type Big = FBig<HalfEven>;
+
+/// The 128 most significant bits of π.
+const PI_BITS: u128 = 0xC90F_DAA2_2168_C234_C4C6_628B_80DC_1CD1;
+
+/// The 128 most significant bits of sqrt(2).
+const SQRT_2_BITS: u128 = 0xB504_F333_F9DE_6484_597D_89B3_754A_BE9F;
+
+/// The 128 most significant bits of sqrt(3).
+const SQRT_3_BITS: u128 = 0xDDB3_D742_C265_539D_92BA_16B8_3C5C_1DC5;
+
+/// The 128 most significant bits of sqrt(5).
+const SQRT_5_BITS: u128 = 0x8F1B_BCDC_BFA5_3E0A_F9CE_6030_2E76_E41A;
+
+/// A binary floating point number with `BITS` bits of precision.
+///
+/// Every operation is rounded to `BITS` bits, which makes rounding errors
+/// negligible for anything Miratope does, at the cost of being orders of
+/// magnitude slower than an `f64`. The trigonometric and hyperbolic functions
+/// are computed through `f64`, as no exact algorithm needs them.
+///
+/// There's no infinities or NaNs, so dividing by zero or taking the square
+/// root of a negative number panics.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BigFloat<const BITS: usize>(Big);
+
+impl<const BITS: usize> BigFloat<BITS> {
+    /// Builds the float `significand * 2^exponent` in a `const` context.
+    /// Irrational constants can only be given up to 128 bits this way.
+    const fn from_parts(sign: Sign, significand: u128, exponent: isize) -> Self {
+        Self(Big::from_parts_const(sign, significand, exponent, Some(BITS)))
+    }
+
+    /// The context every operation is carried out in.
+    fn context() -> Context<HalfEven> {
+        Context::new(BITS)
+    }
+
+    /// Rounds a float to `BITS` bits.
+    fn new(x: Big) -> Self {
+        Self(x.with_precision(BITS).value())
+    }
+
+    /// Computes a function through `f64`.
+    fn via_f64(&self, f: impl FnOnce(f64) -> f64) -> Self {
+        Self::f64(f(self.to_f64()))
+    }
+}
+
+impl<const BITS: usize> Float for BigFloat<BITS> {
+    /// The vertices we work with come from `f64` coordinates, which are only
+    /// precise up to about `1e-16`, so this can't be much smaller than that.
+    /// It's still orders of magnitude below `f64::EPS`, since rounding errors
+    /// don't pile up anymore.
+    const EPS: Self = Self::from_parts(Sign::Positive, 1, -40);
+    const ZERO: Self = Self::from_parts(Sign::Positive, 0, 0);
+    const ONE: Self = Self::from_parts(Sign::Positive, 1, 0);
+    const TWO: Self = Self::from_parts(Sign::Positive, 1, 1);
+    const THREE: Self = Self::from_parts(Sign::Positive, 3, 0);
+    const FOUR: Self = Self::from_parts(Sign::Positive, 1, 2);
+    const FIVE: Self = Self::from_parts(Sign::Positive, 5, 0);
+    const PI: Self = Self::from_parts(Sign::Positive, PI_BITS, -126);
+    const TAU: Self = Self::from_parts(Sign::Positive, PI_BITS, -125);
+    const SQRT_2: Self = Self::from_parts(Sign::Positive, SQRT_2_BITS, -127);
+    const HALF_SQRT_2: Self = Self::from_parts(Sign::Positive, SQRT_2_BITS, -128);
+    const SQRT_3: Self = Self::from_parts(Sign::Positive, SQRT_3_BITS, -127);
+    const SQRT_5: Self = Self::from_parts(Sign::Positive, SQRT_5_BITS, -126);
+
+    /// # Panics
+    /// Panics if the float isn't finite.
+    fn f64(f: f64) -> Self {
+        let x = Big::try_from(f).ok().filter(|x| x.repr().is_finite());
+        Self::new(x.expect("float isn't finite"))
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.0.to_f64().value()
+    }
+
+    fn usize(u: usize) -> Self {
+        Self::new(Big::from(u))
+    }
+
+    fn u32(u: u32) -> Self {
+        Self::new(Big::from(u))
+    }
+}
+
+impl<const BITS: usize> Default for BigFloat<BITS> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+/// Writes the float in decimal, with as many digits as it's precise to.
+impl<const BITS: usize> Display for BigFloat<BITS> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        // Each decimal digit takes up about 3.32 bits.
+        let digits = BITS * 3 / 10 + 1;
+        let x = self.0.clone().with_rounding::<HalfAway>();
+        Display::fmt(&x.with_base_and_precision::<10>(digits).value(), f)
+    }
+}
+
+/// Parses a float in decimal, rounding it to `BITS` bits.
+impl<const BITS: usize> FromStr for BigFloat<BITS> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let x = DBig::from_str(s)?.with_rounding::<HalfEven>();
+        Ok(Self(x.with_base_and_precision::<2>(BITS).value()))
+    }
+}
+
+/// Serializes the float as a decimal string, so that no precision is lost.
+impl<const BITS: usize> Serialize for BigFloat<BITS> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Implements a binary operation and its assigning counterpart through a
+/// method of [`Context`].
+macro_rules! impl_op {
+    ($Op: ident, $op: ident, $OpAssign: ident, $op_assign: ident) => {
+        impl<const BITS: usize> $Op for BigFloat<BITS> {
+            type Output = Self;
+
+            fn $op(self, rhs: Self) -> Self {
+                Self(Self::context().$op(self.0.repr(), rhs.0.repr()).value())
+            }
+        }
+
+        impl<const BITS: usize> $OpAssign for BigFloat<BITS> {
+            fn $op_assign(&mut self, rhs: Self) {
+                *self = self.clone().$op(rhs);
+            }
+        }
+    };
+}
+
+impl_op!(Add, add, AddAssign, add_assign);
+impl_op!(Sub, sub, SubAssign, sub_assign);
+impl_op!(Mul, mul, MulAssign, mul_assign);
+impl_op!(Div, div, DivAssign, div_assign);
+impl_op!(Rem, rem, RemAssign, rem_assign);
+
+impl<const BITS: usize> Neg for BigFloat<BITS> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+impl<const BITS: usize> Zero for BigFloat<BITS> {
+    fn zero() -> Self {
+        Self::ZERO
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.repr().is_zero()
+    }
+}
+
+impl<const BITS: usize> One for BigFloat<BITS> {
+    fn one() -> Self {
+        Self::ONE
+    }
+}
+
+impl<const BITS: usize> Num for BigFloat<BITS> {
+    type FromStrRadixErr = ParseError;
+
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseError> {
+        if radix == 10 {
+            s.parse()
+        } else {
+            Err(ParseError::UnsupportedRadix)
+        }
+    }
+}
+
+impl<const BITS: usize> Signed for BigFloat<BITS> {
+    fn abs(&self) -> Self {
+        Self(self.0.clone().abs())
+    }
+
+    fn abs_sub(&self, other: &Self) -> Self {
+        RealField::max(self.clone() - other.clone(), Self::ZERO)
+    }
+
+    fn signum(&self) -> Self {
+        if self.is_zero() {
+            Self::ZERO
+        } else {
+            Self(self.0.signum())
+        }
+    }
+
+    fn is_positive(&self) -> bool {
+        self.0.repr().sign() == Sign::Positive && !self.is_zero()
+    }
+
+    fn is_negative(&self) -> bool {
+        self.0.repr().sign() == Sign::Negative && !self.is_zero()
+    }
+}
+
+impl<const BITS: usize> FromPrimitive for BigFloat<BITS> {
+    fn from_i64(n: i64) -> Option<Self> {
+        Some(Self::new(Big::from(n)))
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(Self::new(Big::from(n)))
+    }
+
+    fn from_f64(f: f64) -> Option<Self> {
+        f.is_finite().then(|| Self::f64(f))
+    }
+}
+
+impl<const BITS: usize> AbsDiffEq for BigFloat<BITS> {
+    type Epsilon = Self;
+
+    fn default_epsilon() -> Self {
+        Self::EPS
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self) -> bool {
+        Signed::abs(&(self.clone() - other.clone())) <= epsilon
+    }
+}
+
+impl<const BITS: usize> RelativeEq for BigFloat<BITS> {
+    fn default_max_relative() -> Self {
+        Self::EPS
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: Self, max_relative: Self) -> bool {
+        if self.abs_diff_eq(other, epsilon) {
+            return true;
+        }
+
+        let largest = RealField::max(Signed::abs(self), Signed::abs(other));
+        Signed::abs(&(self.clone() - other.clone())) <= largest * max_relative
+    }
+}
+
+/// There's no meaningful notion of units in the last place for a float of
+/// arbitrary precision, so this just compares absolute differences.
+impl<const BITS: usize> UlpsEq for BigFloat<BITS> {
+    fn default_max_ulps() -> u32 {
+        4
+    }
+
+    fn ulps_eq(&self, other: &Self, epsilon: Self, _: u32) -> bool {
+        self.abs_diff_eq(other, epsilon)
+    }
+}
+
+impl<const BITS: usize> SimdValue for BigFloat<BITS> {
+    const LANES: usize = 1;
+    type Element = Self;
+    type SimdBool = bool;
+
+    fn splat(val: Self) -> Self {
+        val
+    }
+
+    fn extract(&self, _: usize) -> Self {
+        self.clone()
+    }
+
+    unsafe fn extract_unchecked(&self, _: usize) -> Self {
+        self.clone()
+    }
+
+    fn replace(&mut self, _: usize, val: Self) {
+        *self = val;
+    }
+
+    unsafe fn replace_unchecked(&mut self, _: usize, val: Self) {
+        *self = val;
+    }
+
+    fn select(self, cond: bool, other: Self) -> Self {
+        if cond { self } else { other }
+    }
+}
+
+impl<const BITS: usize> Field for BigFloat<BITS> {}
+
+impl<const BITS: usize> SubsetOf<BigFloat<BITS>> for BigFloat<BITS> {
+    fn to_superset(&self) -> Self {
+        self.clone()
+    }
+
+    fn from_superset_unchecked(element: &Self) -> Self {
+        element.clone()
+    }
+
+    fn is_in_subset(_: &Self) -> bool {
+        true
+    }
+}
+
+impl<const BITS: usize> SubsetOf<BigFloat<BITS>> for f64 {
+    fn to_superset(&self) -> BigFloat<BITS> {
+        BigFloat::f64(*self)
+    }
+
+    fn from_superset_unchecked(element: &BigFloat<BITS>) -> Self {
+        element.to_f64()
+    }
+
+    fn is_in_subset(_: &BigFloat<BITS>) -> bool {
+        true
+    }
+}
+
+impl<const BITS: usize> SubsetOf<BigFloat<BITS>> for f32 {
+    fn to_superset(&self) -> BigFloat<BITS> {
+        BigFloat::f64(*self as f64)
+    }
+
+    fn from_superset_unchecked(element: &BigFloat<BITS>) -> Self {
+        element.0.to_f32().value()
+    }
+
+    fn is_in_subset(_: &BigFloat<BITS>) -> bool {
+        true
+    }
+}
+
+impl<const BITS: usize> ComplexField for BigFloat<BITS> {
+    type RealField = Self;
+
+    fn from_real(re: Self) -> Self {
+        re
+    }
+
+    fn real(self) -> Self {
+        self
+    }
+
+    fn imaginary(self) -> Self {
+        Self::ZERO
+    }
+
+    fn modulus(self) -> Self {
+        Signed::abs(&self)
+    }
+
+    fn modulus_squared(self) -> Self {
+        self.clone() * self
+    }
+
+    fn argument(self) -> Self {
+        if self.is_negative() { Self::PI } else { Self::ZERO }
+    }
+
+    fn norm1(self) -> Self {
+        Signed::abs(&self)
+    }
+
+    fn scale(self, factor: Self) -> Self {
+        self * factor
+    }
+
+    fn unscale(self, factor: Self) -> Self {
+        self / factor
+    }
+
+    fn floor(self) -> Self {
+        Self(self.0.floor())
+    }
+
+    fn ceil(self) -> Self {
+        Self(self.0.ceil())
+    }
+
+    fn round(self) -> Self {
+        Self(self.0.round())
+    }
+
+    fn trunc(self) -> Self {
+        Self(self.0.trunc())
+    }
+
+    fn fract(self) -> Self {
+        Self(self.0.fract())
+    }
+
+    fn mul_add(self, a: Self, b: Self) -> Self {
+        self * a + b
+    }
+
+    fn abs(self) -> Self {
+        Signed::abs(&self)
+    }
+
+    fn hypot(self, other: Self) -> Self {
+        (self.modulus_squared() + other.modulus_squared()).sqrt()
+    }
+
+    fn recip(self) -> Self {
+        Self(Self::context().inv(self.0.repr()).value())
+    }
+
+    fn conjugate(self) -> Self {
+        self
+    }
+
+    fn sin(self) -> Self {
+        self.via_f64(f64::sin)
+    }
+
+    fn cos(self) -> Self {
+        self.via_f64(f64::cos)
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        (self.clone().sin(), self.cos())
+    }
+
+    fn tan(self) -> Self {
+        self.via_f64(f64::tan)
+    }
+
+    fn asin(self) -> Self {
+        self.via_f64(f64::asin)
+    }
+
+    fn acos(self) -> Self {
+        self.via_f64(f64::acos)
+    }
+
+    fn atan(self) -> Self {
+        self.via_f64(f64::atan)
+    }
+
+    fn sinh(self) -> Self {
+        self.via_f64(f64::sinh)
+    }
+
+    fn cosh(self) -> Self {
+        self.via_f64(f64::cosh)
+    }
+
+    fn tanh(self) -> Self {
+        self.via_f64(f64::tanh)
+    }
+
+    fn asinh(self) -> Self {
+        self.via_f64(f64::asinh)
+    }
+
+    fn acosh(self) -> Self {
+        self.via_f64(f64::acosh)
+    }
+
+    fn atanh(self) -> Self {
+        self.via_f64(f64::atanh)
+    }
+
+    fn log(self, base: Self) -> Self {
+        self.ln() / base.ln()
+    }
+
+    fn log2(self) -> Self {
+        self.ln() / <Self as RealField>::ln_2()
+    }
+
+    fn log10(self) -> Self {
+        self.ln() / <Self as RealField>::ln_10()
+    }
+
+    fn ln(self) -> Self {
+        Self(Self::context().ln(self.0.repr()).value())
+    }
+
+    fn ln_1p(self) -> Self {
+        Self(Self::context().ln_1p(self.0.repr()).value())
+    }
+
+    fn sqrt(self) -> Self {
+        Self(Self::context().sqrt(self.0.repr()).value())
+    }
+
+    fn exp(self) -> Self {
+        Self(Self::context().exp(self.0.repr()).value())
+    }
+
+    fn exp2(self) -> Self {
+        (self * <Self as RealField>::ln_2()).exp()
+    }
+
+    fn exp_m1(self) -> Self {
+        Self(Self::context().exp_m1(self.0.repr()).value())
+    }
+
+    fn powi(self, n: i32) -> Self {
+        Self(Self::context().powi(self.0.repr(), n.into()).value())
+    }
+
+    fn powf(self, n: Self) -> Self {
+        Self(Self::context().powf(self.0.repr(), n.0.repr()).value())
+    }
+
+    fn powc(self, n: Self) -> Self {
+        self.powf(n)
+    }
+
+    fn cbrt(self) -> Self {
+        let third = Self::THREE.recip();
+        let cbrt = Signed::abs(&self).powf(third);
+        if self.is_negative() { -cbrt } else { cbrt }
+    }
+
+    fn is_finite(&self) -> bool {
+        self.0.repr().is_finite()
+    }
+
+    fn try_sqrt(self) -> Option<Self> {
+        (!self.is_negative()).then(|| self.sqrt())
+    }
+}
+
+impl<const BITS: usize> RealField for BigFloat<BITS> {
+    fn is_sign_positive(&self) -> bool {
+        self.0.repr().sign() == Sign::Positive
+    }
+
+    fn is_sign_negative(&self) -> bool {
+        self.0.repr().sign() == Sign::Negative
+    }
+
+    fn copysign(self, sign: Self) -> Self {
+        let abs = Signed::abs(&self);
+        if sign.is_sign_negative() { -abs } else { abs }
+    }
+
+    fn max(self, other: Self) -> Self {
+        Ord::max(self, other)
+    }
+
+    fn min(self, other: Self) -> Self {
+        Ord::min(self, other)
+    }
+
+    fn clamp(self, min: Self, max: Self) -> Self {
+        Ord::clamp(self, min, max)
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        Self::f64(self.to_f64().atan2(other.to_f64()))
+    }
+
+    fn min_value() -> Option<Self> {
+        None
+    }
+
+    fn max_value() -> Option<Self> {
+        None
+    }
+
+    fn pi() -> Self {
+        Self::PI
+    }
+
+    fn two_pi() -> Self {
+        Self::TAU
+    }
+
+    fn frac_pi_2() -> Self {
+        Self::PI / Self::TWO
+    }
+
+    fn frac_pi_3() -> Self {
+        Self::PI / Self::THREE
+    }
+
+    fn frac_pi_4() -> Self {
+        Self::PI / Self::FOUR
+    }
+
+    fn frac_pi_6() -> Self {
+        Self::PI / Self::usize(6)
+    }
+
+    fn frac_pi_8() -> Self {
+        Self::PI / Self::usize(8)
+    }
+
+    fn frac_1_pi() -> Self {
+        Self::PI.recip()
+    }
+
+    fn frac_2_pi() -> Self {
+        Self::TWO / Self::PI
+    }
+
+    fn frac_2_sqrt_pi() -> Self {
+        Self::TWO / Self::PI.sqrt()
+    }
+
+    fn e() -> Self {
+        Self::ONE.exp()
+    }
+
+    fn log2_e() -> Self {
+        <Self as RealField>::ln_2().recip()
+    }
+
+    fn log10_e() -> Self {
+        <Self as RealField>::ln_10().recip()
+    }
+
+    fn ln_2() -> Self {
+        Self::TWO.ln()
+    }
+
+    fn ln_10() -> Self {
+        Self::usize(10).ln()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_abs_diff_eq;
+
+    /// The float type we test with.
+    type F = BigFloat<128>;
+
+    #[test]
+    /// Checks the constants against their defining equations.
+    fn constants() {
+        let eps = F::f64(1e-35);
+        assert_abs_diff_eq!(F::SQRT_2 * F::SQRT_2, F::TWO, epsilon = eps.clone());
+        assert_abs_diff_eq!(F::SQRT_3 * F::SQRT_3, F::THREE, epsilon = eps.clone());
+        assert_abs_diff_eq!(F::SQRT_5 * F::SQRT_5, F::FIVE, epsilon = eps.clone());
+        assert_abs_diff_eq!(F::HALF_SQRT_2 * F::SQRT_2, F::ONE, epsilon = eps);
+
+        assert_eq!(F::TAU, F::PI * F::TWO);
+        assert_eq!(F::PI.to_f64(), std::f64::consts::PI);
+    }
+
+    #[test]
+    /// Checks that a sum that cancels out in `f64` doesn't in a [`BigFloat`].
+    fn cancellation() {
+        let big = F::f64(1e20);
+        assert_eq!(big.clone() + F::ONE - big, F::ONE);
+        assert_eq!(1e20 + 1.0 - 1e20, 0.0);
+    }
+
+    #[test]
+    /// Parses and prints floats.
+    fn parse() {
+        let x: F = "0.1".parse().unwrap();
+        assert_abs_diff_eq!(x.clone() * F::usize(10), F::ONE, epsilon = F::f64(1e-35));
+        assert_eq!(x.to_string(), "0.1");
+        let third = (F::ONE / F::THREE).to_string();
+        assert!(third.starts_with(&format!("0.{}", "3".repeat(30))));
+        assert!("abc".parse::<F>().is_err());
+    }
+}
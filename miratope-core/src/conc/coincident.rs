@@ -0,0 +1,128 @@
+//! Detects faces that lie on the same plane, as often happens in compounds.
+//! These faces overlap when rendered, and need special handling.
+
+use std::collections::BTreeMap;
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::Ranked,
+    float::Float,
+    geometry::{Point, Subspace, predicates},
+};
+
+use ordered_float::OrderedFloat;
+
+impl Concrete {
+    /// Returns three vertices of a face that span its plane, or `None` for
+    /// degenerate faces.
+    fn face_span(&self, idx: usize) -> Option<[usize; 3]> {
+        let vertices = self.abs.element_vertices(3, idx)?;
+        let (&first, rest) = vertices.split_first()?;
+        let mut subspace = Subspace::new(self.vertices[first].clone());
+        let mut plane = vec![first];
+        for &v in rest {
+            if subspace.add(&self.vertices[v]).is_some() {
+                plane.push(v);
+            }
+        }
+
+        plane.try_into().ok()
+    }
+
+    /// Groups together the faces that lie on the same plane. Only groups with
+    /// at least two faces are returned, each in increasing order.
+    ///
+    /// Every face is tested against the plane of the first face of every group
+    /// using [`predicates::coplanar`]. To avoid testing against every group,
+    /// these are indexed by the distance from their plane to the origin.
+    pub fn coplanar_faces(&self) -> Vec<Vec<usize>> {
+        if self.rank() < 4 {
+            return Vec::new();
+        }
+
+        // Planes closer to each other than the tolerance can be farther apart
+        // than it from the origin, so we look through a generous window.
+        let window = 1e3 * f64::EPS;
+        let origin = Point::zeros(self.dim_or());
+
+        let mut groups: Vec<([usize; 3], Vec<usize>)> = Vec::new();
+        let mut by_distance: BTreeMap<OrderedFloat<f64>, Vec<usize>> = BTreeMap::new();
+
+        for idx in 0..self.el_count(3) {
+            let Some(plane) = self.face_span(idx) else {
+                continue;
+            };
+
+            let vertices = self.abs.element_vertices(3, idx).unwrap();
+            let subspace = Subspace::from_points(plane.iter().map(|&v| &self.vertices[v]));
+            let distance = subspace.project(&origin).norm();
+
+            let found = by_distance
+                .range(OrderedFloat(distance - window)..=OrderedFloat(distance + window))
+                .flat_map(|(_, group)| group)
+                .copied()
+                .find(|&group| {
+                    let base = groups[group].0.map(|v| &self.vertices[v]);
+                    vertices
+                        .iter()
+                        .all(|&v| predicates::coplanar(&base, &self.vertices[v], f64::EPS))
+                });
+
+            match found {
+                Some(group) => groups[group].1.push(idx),
+                None => {
+                    by_distance
+                        .entry(OrderedFloat(distance))
+                        .or_default()
+                        .push(groups.len());
+                    groups.push((plane, vec![idx]));
+                }
+            }
+        }
+
+        groups
+            .into_iter()
+            .map(|(_, faces)| faces)
+            .filter(|faces| faces.len() > 1)
+            .collect()
+    }
+
+    /// Returns, for every face, how many faces before it lie on the same
+    /// plane. Faces with a nonzero value can be slightly offset when rendering
+    /// to avoid flickering.
+    pub fn coplanar_face_layers(&self) -> Vec<usize> {
+        let mut layers = vec![0; self.el_count(3)];
+        for group in self.coplanar_faces() {
+            for (layer, idx) in group.into_iter().enumerate() {
+                layers[idx] = layer;
+            }
+        }
+
+        layers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+
+    /// A cube has no coplanar faces.
+    #[test]
+    fn cube() {
+        assert!(Concrete::cube().coplanar_faces().is_empty());
+    }
+
+    /// Every face of a compound of two identical cubes is coplanar with
+    /// another.
+    #[test]
+    fn compound() {
+        let mut compound = Concrete::cube();
+        compound.comp_append(Concrete::cube());
+
+        let groups = compound.coplanar_faces();
+        assert_eq!(groups.len(), 6);
+        assert!(groups.iter().all(|group| group.len() == 2));
+        assert_eq!(compound.coplanar_face_layers().iter().sum::<usize>(), 6);
+    }
+}
@@ -0,0 +1,234 @@
+//! Collapses the degenerate elements left behind by parametric operations.
+
+use std::collections::HashMap;
+
+use super::{cut::prune, Concrete, ConcretePolytope};
+use crate::{
+    abs::{Abstract, AbstractBuilder, Ranked, SubelementList, Subelements},
+    geometry::Point,
+    Polytope,
+};
+
+use partitions::{partition_vec, PartitionVec};
+use vec_like::*;
+
+impl Concrete {
+    /// Collapses every edge shorter than a given tolerance, merging its
+    /// vertices into their average. Elements that become degenerate, such as
+    /// faces with a single remaining edge, are collapsed too, and elements that
+    /// end up with the same subelements are merged.
+    pub fn collapse_short_edges(&self, tolerance: f64) -> Self {
+        let rank = self.rank();
+        if rank < 3 {
+            return self.clone();
+        }
+
+        // Groups together the vertices joined by short edges.
+        let mut clusters: PartitionVec<()> = partition_vec![(); self.vertices.len()];
+        for edge in &self[2] {
+            let (v0, v1) = (edge.subs[0], edge.subs[1]);
+            if (&self.vertices[v0] - &self.vertices[v1]).norm() < tolerance {
+                clusters.union(v0, v1);
+            }
+        }
+
+        self.merge_vertex_clusters(&mut clusters)
+    }
+
+    /// Merges together every pair of vertices closer than a given tolerance,
+    /// even if they aren't joined by an edge, as often happens in files with
+    /// rounded coordinates. Elements are remapped and merged just like in
+    /// [`Self::collapse_short_edges`]. Returns the welded polytope, together
+    /// with the number of vertices that were merged away.
+    pub fn weld_vertices(&self, tolerance: f64) -> (Self, usize) {
+        let rank = self.rank();
+        if rank < 2 {
+            return (self.clone(), 0);
+        }
+
+        // Sweeps the vertices by their first coordinate, so that only nearby
+        // pairs are compared.
+        let mut order: Vec<usize> = (0..self.vertices.len()).collect();
+        let first = |v: usize| self.vertices[v].get(0).copied().unwrap_or(0.0);
+        order.sort_unstable_by(|&v, &w| first(v).total_cmp(&first(w)));
+
+        let mut clusters: PartitionVec<()> = partition_vec![(); self.vertices.len()];
+        for (i, &v) in order.iter().enumerate() {
+            for &w in &order[i + 1..] {
+                if first(w) - first(v) >= tolerance {
+                    break;
+                }
+
+                if (&self.vertices[v] - &self.vertices[w]).norm() < tolerance {
+                    clusters.union(v, w);
+                }
+            }
+        }
+
+        let welded = self.merge_vertex_clusters(&mut clusters);
+        let merged = self.vertices.len() - welded.vertices.len();
+        (welded, merged)
+    }
+
+    /// Replaces every cluster of vertices by their average, collapsing the
+    /// elements that become degenerate and merging those that end up with the
+    /// same subelements.
+    ///
+    /// If a single element of some rank is left, the polytope collapses into
+    /// it, so that for instance a polytope whose vertices all merge becomes a
+    /// point.
+    fn merge_vertex_clusters(&self, clusters: &mut PartitionVec<()>) -> Self {
+        let rank = self.rank();
+
+        // Each cluster of vertices becomes a single vertex.
+        let mut vertices = Vec::new();
+        let mut new_idx: Vec<Option<usize>> = vec![None; self.vertices.len()];
+        for cluster in clusters.all_sets() {
+            let mut sum = Point::zeros(self.dim_or());
+            let mut count = 0;
+            for (v, _) in cluster {
+                sum += &self.vertices[v];
+                new_idx[v] = Some(vertices.len());
+                count += 1;
+            }
+            vertices.push(sum / count as f64);
+        }
+
+        if vertices.len() == 1 {
+            return Self::new(vertices, Abstract::point());
+        }
+
+        let mut ranks = vec![SubelementList::min(), SubelementList::vertices(vertices.len())];
+
+        // Maps every element to its new index, or to `None` if it collapsed.
+        for r in 2..rank {
+            let mut els = SubelementList::new();
+            let mut index_of = HashMap::new();
+            let mut next_idx = Vec::with_capacity(self.el_count(r));
+
+            for el in &self[r] {
+                let mut subs: Vec<usize> = el.subs.iter().filter_map(|&sub| new_idx[sub]).collect();
+                subs.sort_unstable();
+                subs.dedup();
+
+                // An element with a single subelement has collapsed.
+                if subs.len() < 2 {
+                    next_idx.push(None);
+                    continue;
+                }
+
+                let idx = *index_of.entry(subs.clone()).or_insert_with(|| {
+                    els.push(Subelements::from(subs));
+                    els.len() - 1
+                });
+                next_idx.push(Some(idx));
+            }
+
+            // If a single element is left, the polytope collapses into it. If
+            // none are, the elements of the previous rank become the facets.
+            if els.len() < 2 {
+                ranks.extend((els.len() == 1).then_some(els));
+                break;
+            }
+
+            ranks.push(els);
+            new_idx = next_idx;
+        }
+
+        if ranks.last().unwrap().len() != 1 {
+            ranks.push(SubelementList::max(ranks.last().unwrap().len()));
+        }
+
+        // Removes the elements that don't belong to the collapsed polytope.
+        for r in (1..ranks.len() - 1).rev() {
+            let used = prune(&mut ranks, r);
+            if r == 1 {
+                vertices = vertices
+                    .into_iter()
+                    .zip(used)
+                    .filter_map(|(v, u)| u.then_some(v))
+                    .collect();
+            }
+        }
+
+        let mut builder = AbstractBuilder::new();
+        for subelements in ranks {
+            builder.push(subelements);
+        }
+
+        // Safety: merging vertices and removing the elements that collapse as
+        // a result preserves the incidences of every remaining element.
+        unsafe { Self::new(vertices, builder.build()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+
+    /// Collapses the top face of a cube into a point, giving a square
+    /// pyramid.
+    #[test]
+    fn pyramid() {
+        let mut cube = Concrete::cube();
+        for v in &mut cube.vertices {
+            if v[2] > 0.0 {
+                v[0] *= 1e-12;
+                v[1] *= 1e-12;
+            }
+        }
+
+        test(&cube.collapse_short_edges(1e-9), [1, 5, 8, 5, 1]);
+    }
+
+    /// Collapses every edge of a cube into a point.
+    #[test]
+    fn point() {
+        let cube = Concrete::cube();
+        let point = cube.collapse_short_edges(2.0 * cube.circumsphere().unwrap().radius());
+        test(&point, [1, 1]);
+        assert!(point.vertices[0].norm() < 1e-9);
+    }
+
+    /// Collapses the lateral edges of a prism, which turns it into its base.
+    #[test]
+    fn prism_base() {
+        let prism = Concrete::polygon(5).prism_with(1e-3);
+        let pentagon = prism.collapse_short_edges(1e-2);
+        test(&pentagon, [1, 5, 5, 1]);
+        pentagon.assert_valid();
+    }
+
+    /// Collapses a rectangle with two short sides into a dyad.
+    #[test]
+    fn rectangle_dyad() {
+        let rectangle = Concrete::dyad().prism_with(1e-6);
+        let dyad = rectangle.collapse_short_edges(1e-3);
+        test(&dyad, [1, 2, 1]);
+        dyad.assert_valid();
+    }
+
+    /// Checks that nothing happens without short edges.
+    #[test]
+    fn no_short_edges() {
+        test(&Concrete::cube().collapse_short_edges(1e-9), [1, 8, 12, 6, 1]);
+    }
+
+    /// Welds a compound of a cube and a slightly perturbed copy into a single
+    /// cube.
+    #[test]
+    fn weld() {
+        let mut copy = Concrete::cube();
+        for v in &mut copy.vertices {
+            v[0] += 1e-9;
+        }
+
+        let mut compound = Concrete::cube();
+        compound.comp_append(copy);
+
+        let (welded, merged) = compound.weld_vertices(1e-6);
+        assert_eq!(merged, 8);
+        test(&welded, [1, 8, 12, 6, 1]);
+    }
+}
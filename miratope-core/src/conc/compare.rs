@@ -0,0 +1,427 @@
+//! Compares two polytopes, reporting what changed from one to the other.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt::Display,
+};
+
+use super::{Concrete, ConcretePolytope};
+use crate::{abs::Ranked, float::Float, geometry::PointOrd};
+
+use vec_like::*;
+
+/// The facets added and removed between two polytopes on the same vertices.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FacetDiff {
+    /// The indices of the facets of the new polytope that weren't in the old
+    /// one.
+    pub added: Vec<usize>,
+
+    /// The indices of the facets of the old polytope that aren't in the new
+    /// one.
+    pub removed: Vec<usize>,
+}
+
+/// The edges added and removed between two polytopes, which might not have
+/// the same vertices. Edges are compared by the positions of their endpoints.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EdgeDiff {
+    /// The indices of the edges of the new polytope that don't join the same
+    /// points as any edge of the old one.
+    pub added: Vec<usize>,
+
+    /// The indices of the edges of the old polytope that don't join the same
+    /// points as any edge of the new one.
+    pub removed: Vec<usize>,
+}
+
+impl EdgeDiff {
+    /// Returns whether both polytopes have the same edges.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// The distinct edge lengths of a polytope, in increasing order, with the
+/// number of edges of each length.
+pub type EdgeLengths = Vec<(f64, usize)>;
+
+/// A report on the differences between an old and a new polytope.
+#[derive(Clone, Debug)]
+pub struct PolytopeDiff {
+    /// The element counts of the old and new polytopes.
+    pub f_vectors: (Vec<usize>, Vec<usize>),
+
+    /// The distinct edge lengths of the old and new polytopes, in increasing
+    /// order, with the number of edges of each length.
+    pub edge_lengths: (EdgeLengths, EdgeLengths),
+
+    /// The orders of the symmetry groups of the old and new polytopes, if they
+    /// could be computed.
+    pub symmetry_orders: (Option<usize>, Option<usize>),
+
+    /// The facets added and removed, if both polytopes have the same vertices.
+    pub facets: Option<FacetDiff>,
+}
+
+impl PolytopeDiff {
+    /// Returns whether no differences were found.
+    pub fn is_empty(&self) -> bool {
+        self.f_vectors.0 == self.f_vectors.1
+            && self.edge_length_changes().is_empty()
+            && self.symmetry_orders.0 == self.symmetry_orders.1
+            && self
+                .facets
+                .as_ref()
+                .is_none_or(|f| f.added.is_empty() && f.removed.is_empty())
+    }
+
+    /// Returns every edge length whose number of edges changed, along with the
+    /// old and new number of edges.
+    pub fn edge_length_changes(&self) -> Vec<(f64, usize, usize)> {
+        let (old, new) = &self.edge_lengths;
+        let mut changes = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        // Merges both sorted lists of lengths.
+        while i < old.len() || j < new.len() {
+            let change = match (old.get(i), new.get(j)) {
+                (Some(&(a, m)), Some(&(b, n))) if (a - b).abs() < f64::EPS => {
+                    i += 1;
+                    j += 1;
+                    (a, m, n)
+                }
+                (Some(&(a, m)), Some(&(b, _))) if a < b => {
+                    i += 1;
+                    (a, m, 0)
+                }
+                (Some(&(a, m)), None) => {
+                    i += 1;
+                    (a, m, 0)
+                }
+                (_, Some(&(b, n))) => {
+                    j += 1;
+                    (b, 0, n)
+                }
+                (None, None) => unreachable!(),
+            };
+
+            if change.1 != change.2 {
+                changes.push(change);
+            }
+        }
+
+        changes
+    }
+}
+
+impl Display for PolytopeDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences found.");
+        }
+
+        if self.f_vectors.0 != self.f_vectors.1 {
+            writeln!(f, "Element counts: {:?} → {:?}", self.f_vectors.0, self.f_vectors.1)?;
+        }
+
+        for (len, old, new) in self.edge_length_changes() {
+            writeln!(f, "Edges of length {}: {} → {}", len, old, new)?;
+        }
+
+        if self.symmetry_orders.0 != self.symmetry_orders.1 {
+            let order = |o: Option<usize>| o.map_or_else(|| String::from("?"), |o| o.to_string());
+            writeln!(
+                f,
+                "Symmetry order: {} → {}",
+                order(self.symmetry_orders.0),
+                order(self.symmetry_orders.1)
+            )?;
+        }
+
+        if let Some(facets) = &self.facets {
+            if !facets.added.is_empty() {
+                writeln!(f, "Added facets: {:?}", facets.added)?;
+            }
+            if !facets.removed.is_empty() {
+                writeln!(f, "Removed facets: {:?}", facets.removed)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A description of the vertex set of a polytope that doesn't change under
+/// isometries. Vertex sets that are congruent have matching keys, and although
+/// the converse can fail, it does so only for carefully built sets.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VertexSetKey {
+    /// The number of dimensions of the vertices.
+    dim: Option<usize>,
+
+    /// The sorted distances from every vertex to their gravicenter.
+    radii: Vec<f64>,
+
+    /// The sorted distances between every pair of vertices.
+    distances: Vec<f64>,
+}
+
+impl VertexSetKey {
+    /// Returns whether two keys agree up to floating point errors.
+    pub fn matches(&self, other: &Self) -> bool {
+        let close = |a: &[f64], b: &[f64]| {
+            a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() < f64::EPS)
+        };
+
+        self.dim == other.dim
+            && close(&self.radii, &other.radii)
+            && close(&self.distances, &other.distances)
+    }
+}
+
+impl Concrete {
+    /// Returns a key for the vertex set of the polytope, which can be used to
+    /// find polytopes on the same vertices up to isometry.
+    pub fn vertex_set_key(&self) -> VertexSetKey {
+        let sorted = |mut v: Vec<f64>| {
+            v.sort_unstable_by(f64::total_cmp);
+            v
+        };
+
+        let radii = match self.gravicenter() {
+            Some(center) => self.vertices.iter().map(|v| (v - &center).norm()).collect(),
+            None => Vec::new(),
+        };
+        let distances = self
+            .vertices
+            .iter()
+            .enumerate()
+            .flat_map(|(i, v)| self.vertices[i + 1..].iter().map(move |w| (v - w).norm()))
+            .collect();
+
+        VertexSetKey {
+            dim: self.dim(),
+            radii: sorted(radii),
+            distances: sorted(distances),
+        }
+    }
+
+    /// Returns the distinct edge lengths of the polytope, in increasing order,
+    /// along with the number of edges of each length.
+    pub fn edge_length_counts(&self) -> EdgeLengths {
+        let mut lengths: Vec<_> = (0..self.edge_count())
+            .filter_map(|idx| self.edge_len(idx))
+            .collect();
+        lengths.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let mut counts: EdgeLengths = Vec::new();
+        for len in lengths {
+            match counts.last_mut() {
+                Some((last, count)) if (len - *last).abs() < f64::EPS => *count += 1,
+                _ => counts.push((len, 1)),
+            }
+        }
+
+        counts
+    }
+
+    /// Returns the index in `self` of every vertex of `other`, if both
+    /// polytopes have the same vertices.
+    fn match_vertices(&self, other: &Self) -> Option<Vec<usize>> {
+        if self.vertices.len() != other.vertices.len() || self.dim() != other.dim() {
+            return None;
+        }
+
+        let index_of: BTreeMap<_, _> = self
+            .vertices
+            .iter()
+            .map(|v| PointOrd::new(v.clone()))
+            .zip(0..)
+            .collect();
+
+        let mut used = HashSet::new();
+        other
+            .vertices
+            .iter()
+            .map(|v| {
+                let idx = *index_of.get(&PointOrd::new(v.clone()))?;
+                used.insert(idx).then_some(idx)
+            })
+            .collect()
+    }
+
+    /// Returns the facets of `other` that aren't in `self`, and those of `self`
+    /// that aren't in `other`, given the index in `self` of every vertex of
+    /// `other`. Facets are compared by their vertex sets.
+    fn facet_diff(&self, other: &Self, to_self: &[usize]) -> FacetDiff {
+        let rank = self.rank();
+        if rank < 2 || other.rank() != rank {
+            return FacetDiff::default();
+        }
+
+        let facet_sets = |poly: &Self, map: &dyn Fn(usize) -> usize| -> Vec<Vec<usize>> {
+            (0..poly.el_count(rank - 1))
+                .map(|idx| {
+                    let mut vertices: Vec<usize> = poly
+                        .abs
+                        .element_vertices(rank - 1, idx)
+                        .unwrap()
+                        .into_iter()
+                        .map(map)
+                        .collect();
+                    vertices.sort_unstable();
+                    vertices
+                })
+                .collect()
+        };
+
+        let old = facet_sets(self, &|v| v);
+        let new = facet_sets(other, &|v| to_self[v]);
+        let old_set: HashSet<_> = old.iter().collect();
+        let new_set: HashSet<_> = new.iter().collect();
+
+        FacetDiff {
+            added: (0..new.len()).filter(|&i| !old_set.contains(&new[i])).collect(),
+            removed: (0..old.len()).filter(|&i| !new_set.contains(&old[i])).collect(),
+        }
+    }
+
+    /// Returns the endpoints of every edge, in order.
+    fn edge_points(&self) -> Vec<(PointOrd<f64>, PointOrd<f64>)> {
+        (0..self.edge_count())
+            .map(|idx| {
+                let subs = &self[(2, idx)].subs;
+                let p = PointOrd::new(self.vertices[subs[0]].clone());
+                let q = PointOrd::new(self.vertices[subs[1]].clone());
+                if p <= q {
+                    (p, q)
+                } else {
+                    (q, p)
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the edges of `other` that aren't in `self`, and those of `self`
+    /// that aren't in `other`. Unlike with facets, the polytopes don't need to
+    /// have the same vertices.
+    pub fn edge_diff(&self, other: &Self) -> EdgeDiff {
+        if self.rank() < 3 || other.rank() < 3 || self.dim() != other.dim() {
+            return EdgeDiff::default();
+        }
+
+        let old = self.edge_points();
+        let new = other.edge_points();
+        let old_set: BTreeSet<_> = old.iter().collect();
+        let new_set: BTreeSet<_> = new.iter().collect();
+
+        EdgeDiff {
+            added: (0..new.len()).filter(|&i| !old_set.contains(&new[i])).collect(),
+            removed: (0..old.len()).filter(|&i| !new_set.contains(&old[i])).collect(),
+        }
+    }
+
+    /// Compares this polytope with another, reporting what changed from this
+    /// one to the other. Added and removed facets are only reported when both
+    /// polytopes have the same vertices, possibly in a different order.
+    pub fn diff(&self, other: &Self) -> PolytopeDiff {
+        let order = |poly: &Self| {
+            poly.clone()
+                .get_symmetry_group()
+                .map(|(_, vertex_map)| vertex_map.len())
+        };
+
+        PolytopeDiff {
+            f_vectors: (self.el_count_iter().collect(), other.el_count_iter().collect()),
+            edge_lengths: (self.edge_length_counts(), other.edge_length_counts()),
+            symmetry_orders: (order(self), order(other)),
+            facets: self
+                .match_vertices(other)
+                .map(|to_self| self.facet_diff(other, &to_self)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{file::FromFile, Polytope};
+
+    /// Checks that a polytope doesn't differ from itself.
+    #[test]
+    fn same() {
+        let cube = Concrete::cube();
+        let diff = cube.diff(&cube);
+        assert!(diff.is_empty());
+        assert_eq!(diff.facets, Some(FacetDiff::default()));
+    }
+
+    /// Checks the differences between a cube and an octahedron.
+    #[test]
+    fn cube_octahedron() {
+        let diff = Concrete::cube().diff(&Concrete::octahedron());
+        assert!(!diff.is_empty());
+        assert_eq!(diff.f_vectors.0, vec![1, 8, 12, 6, 1]);
+        assert_eq!(diff.f_vectors.1, vec![1, 6, 12, 8, 1]);
+        assert_eq!(diff.symmetry_orders.0, diff.symmetry_orders.1);
+        assert!(diff.facets.is_none());
+    }
+
+    /// Checks the facets added and removed between two polygons on the same
+    /// vertices.
+    #[test]
+    fn added_removed() {
+        let square = Concrete::from_off("2OFF 4 1 1 1 -1 1 -1 -1 1 -1 4 0 1 2 3").unwrap();
+        let bowtie = Concrete::from_off("2OFF 4 1 -1 -1 1 1 -1 1 1 -1 4 0 1 2 3").unwrap();
+
+        let diff = square.diff(&bowtie);
+        let facets = diff.facets.unwrap();
+        assert_eq!(facets.added.len(), 2);
+        assert_eq!(facets.removed.len(), 2);
+        assert_eq!(diff.f_vectors.0, diff.f_vectors.1);
+    }
+
+    /// Checks the edges added and removed between two polygons, and between
+    /// polytopes on different vertices.
+    #[test]
+    fn edges() {
+        let square = Concrete::from_off("2OFF 4 1 1 1 -1 1 -1 -1 1 -1 4 0 1 2 3").unwrap();
+        let bowtie = Concrete::from_off("2OFF 4 1 -1 -1 1 1 -1 1 1 -1 4 0 1 2 3").unwrap();
+        assert!(square.edge_diff(&square).is_empty());
+
+        // The bowtie keeps two sides of the square, and adds both diagonals.
+        let diff = square.edge_diff(&bowtie);
+        assert_eq!((diff.added.len(), diff.removed.len()), (2, 2));
+
+        // Scaling the cube moves every edge.
+        let cube = Concrete::cube();
+        let mut scaled = cube.clone();
+        scaled.scale(2.0);
+        let diff = cube.edge_diff(&scaled);
+        assert_eq!((diff.added.len(), diff.removed.len()), (12, 12));
+    }
+
+    /// Checks that vertex sets are compared up to isometry.
+    #[test]
+    fn vertex_set_key() {
+        let cube = Concrete::cube();
+        let key = cube.vertex_set_key();
+
+        // Moves the cube around.
+        let mut moved = cube.clone();
+        for v in moved.vertices.iter_mut() {
+            *v = crate::geometry::Point::from_vec(vec![v[1] + 1.0, -v[0], v[2] - 2.0]);
+        }
+        assert!(key.matches(&moved.vertex_set_key()));
+
+        // A faceting on the same vertices, with the vertices in another order.
+        let mut square = Concrete::from_off("2OFF 4 1 1 1 -1 1 -1 -1 1 -1 4 0 1 2 3").unwrap();
+        let bowtie = Concrete::from_off("2OFF 4 1 -1 -1 1 1 -1 1 1 -1 4 0 1 2 3").unwrap();
+        assert!(square.vertex_set_key().matches(&bowtie.vertex_set_key()));
+
+        square.scale(2.0);
+        assert!(!square.vertex_set_key().matches(&bowtie.vertex_set_key()));
+        assert!(!key.matches(&Concrete::octahedron().vertex_set_key()));
+    }
+}
@@ -0,0 +1,319 @@
+//! Cuts a polytope by a halfspace.
+
+use super::Concrete;
+use crate::{
+    abs::{AbstractBuilder, Ranked, SubelementList, Subelements},
+    float::Float,
+    geometry::{Hyperplane, Segment, predicates::Sign},
+    Polytope,
+};
+
+use vec_like::*;
+
+/// The position of an element with respect to a cutting hyperplane.
+#[derive(Clone, Copy, Debug, Default)]
+struct Side {
+    /// Whether the element has a vertex strictly inside the kept halfspace.
+    inside: bool,
+
+    /// Whether the element has a vertex strictly outside the kept halfspace.
+    outside: bool,
+}
+
+impl Side {
+    /// Whether every vertex of the element lies on the hyperplane.
+    fn on(self) -> bool {
+        !self.inside && !self.outside
+    }
+
+    /// Whether the element has vertices strictly on both sides.
+    fn crosses(self) -> bool {
+        self.inside && self.outside
+    }
+}
+
+/// Collects some indices into a list of subelements, removing duplicates.
+fn distinct(indices: impl Iterator<Item = usize>) -> Subelements {
+    let mut indices: Vec<_> = indices.collect();
+    indices.sort_unstable();
+    indices.dedup();
+    indices.into()
+}
+
+/// Removes the elements of a given rank that aren't subelements of any element
+/// of the next rank, and returns which elements were kept. Elements of the top
+/// rank are always kept.
+pub(super) fn prune(ranks: &mut [SubelementList], r: usize) -> Vec<bool> {
+    if r + 1 >= ranks.len() {
+        return vec![true; ranks[r].len()];
+    }
+
+    let mut used = vec![false; ranks[r].len()];
+    for subs in &ranks[r + 1] {
+        for &sub in subs {
+            used[sub] = true;
+        }
+    }
+
+    let mut new_idx = Vec::with_capacity(used.len());
+    let mut count = 0;
+    for &u in &used {
+        new_idx.push(count);
+        count += u as usize;
+    }
+
+    let old = std::mem::take(&mut ranks[r]);
+    ranks[r] = old
+        .into_iter()
+        .zip(&used)
+        .filter_map(|(subs, &u)| u.then_some(subs))
+        .collect();
+
+    for subs in &mut ranks[r + 1] {
+        for sub in subs.iter_mut() {
+            *sub = new_idx[*sub];
+        }
+    }
+
+    used
+}
+
+impl Concrete {
+    /// Intersects the polytope with the closed halfspace on one side of a
+    /// hyperplane. If `keep_side` is set, we keep the side the hyperplane's
+    /// normal points to. The resulting piece gets a new facet on the cutting
+    /// hyperplane.
+    ///
+    /// The cut is exact for convex polytopes. For non-convex ones, the new
+    /// facet is the full cross-section, which might not be what you expect.
+    /// Returns the nullitope if nothing is kept.
+    pub fn cut_halfspace(&self, hyperplane: &Hyperplane<f64>, keep_side: bool) -> Self {
+        let rank = self.rank();
+
+        // The positions of the vertices.
+        let mut sides = vec![Side::default(); self.vertices.len()];
+        for (v, side) in sides.iter_mut().enumerate() {
+            let sign = hyperplane.side(&self.vertices[v], f64::EPS);
+            let sign = if keep_side { sign } else { -sign };
+            side.inside = sign == Sign::Positive;
+            side.outside = sign == Sign::Negative;
+        }
+
+        // A point is kept unless it's strictly outside.
+        if rank < 2 {
+            return if rank == 1 && !sides[0].outside {
+                self.clone()
+            } else {
+                Self::nullitope()
+            };
+        }
+
+        if !sides.iter().any(|s| s.inside) {
+            return Self::nullitope();
+        }
+        if !sides.iter().any(|s| s.outside) {
+            return self.clone();
+        }
+
+        // A dyad that crosses the hyperplane ends at the intersection.
+        if rank == 2 {
+            let segment = Segment(&self.vertices[0], &self.vertices[1]);
+            let p = hyperplane.intersect(segment).unwrap();
+            let outside = sides.iter().position(|s| s.outside).unwrap();
+
+            let mut vertices = self.vertices.clone();
+            vertices[outside] = p;
+            return Self::new(vertices, self.abs.clone());
+        }
+
+        let mut vertices = Vec::new();
+        let mut new_ranks = vec![SubelementList::min()];
+
+        // The index of the kept part of every element of the previous rank,
+        // and of its intersection with the hyperplane.
+        let mut kept = Vec::new();
+        let mut sections = Vec::new();
+
+        // The vertices on the kept side, or on the hyperplane.
+        for (v, side) in sides.iter().enumerate() {
+            if side.outside {
+                kept.push(None);
+            } else {
+                kept.push(Some(vertices.len()));
+                vertices.push(self.vertices[v].clone());
+            }
+            sections.push(None);
+        }
+        let mut vertex_subs = SubelementList::vertices(vertices.len());
+
+        for r in 2..rank {
+            let mut new_sides = Vec::with_capacity(self.el_count(r));
+            let mut new_kept = Vec::with_capacity(self.el_count(r));
+            let mut new_sections = Vec::with_capacity(self.el_count(r));
+            let mut els = SubelementList::new();
+
+            for (idx, el) in self[r].iter().enumerate() {
+                let mut side = Side::default();
+                for &sub in &el.subs {
+                    side.inside |= sides[sub].inside;
+                    side.outside |= sides[sub].outside;
+                }
+
+                // The intersection of the element with the hyperplane.
+                let section = if side.crosses() {
+                    let section_idx = if r == 2 {
+                        // We got ourselves a new vertex.
+                        let edge = &self[(2, idx)].subs;
+                        let segment =
+                            Segment(&self.vertices[edge[0]], &self.vertices[edge[1]]);
+                        let p = hyperplane.intersect(segment).unwrap();
+                        vertices.push(p);
+                        vertex_subs.push(Subelements::from(vec![0]));
+                        vertex_subs.len() - 1
+                    } else {
+                        let subs = distinct(el.subs.iter().filter_map(|&sub| sections[sub]));
+                        new_ranks.last_mut().unwrap().push(subs);
+                        new_ranks.last().unwrap().len() - 1
+                    };
+                    Some(section_idx)
+                } else if side.on() {
+                    None
+                } else {
+                    // The element might touch the hyperplane at a subelement.
+                    el.subs
+                        .iter()
+                        .find(|&&sub| sides[sub].on())
+                        .and_then(|&sub| kept[sub])
+                };
+
+                // The part of the element on the kept side.
+                let kept_idx = if side.inside || side.on() {
+                    let mut subs: Subelements =
+                        el.subs.iter().filter_map(|&sub| kept[sub]).collect();
+                    if side.crosses() {
+                        subs.push(section.unwrap());
+                    }
+                    els.push(subs);
+                    Some(els.len() - 1)
+                } else {
+                    None
+                };
+
+                new_sides.push(side);
+                new_kept.push(kept_idx);
+                new_sections.push(section);
+            }
+
+            if r == 2 {
+                new_ranks.push(std::mem::take(&mut vertex_subs));
+            }
+            new_ranks.push(els);
+            sides = new_sides;
+            kept = new_kept;
+            sections = new_sections;
+        }
+
+        // The new facet on the cutting hyperplane, unless the polytope already
+        // has a facet there.
+        let facets = new_ranks.last_mut().unwrap();
+        if !sides.iter().any(|s| s.on()) {
+            let cap = distinct(sections.iter().filter_map(|&s| s));
+            if !cap.is_empty() {
+                facets.push(cap);
+            }
+        }
+
+        // Removes the elements that don't belong to any kept facet.
+        let facet_count = facets.len();
+        new_ranks.push(SubelementList::max(facet_count));
+        for r in (1..rank).rev() {
+            let used = prune(&mut new_ranks, r);
+            if r == 1 {
+                vertices = vertices
+                    .into_iter()
+                    .zip(used)
+                    .filter_map(|(v, u)| u.then_some(v))
+                    .collect();
+            }
+        }
+
+        let mut builder = AbstractBuilder::new();
+        for subelements in new_ranks {
+            builder.push(subelements);
+        }
+
+        // Safety: every element of the cut is either the kept part of an
+        // element of the original polytope, or its intersection with the
+        // hyperplane, and these have the same incidences as the originals.
+        unsafe { Self::new(vertices, builder.build()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        abs::Abstract,
+        geometry::{Point, Vector},
+        test,
+    };
+
+    /// Cuts a cube in half.
+    #[test]
+    fn cube_half() {
+        let plane = Hyperplane::new(Vector::from_vec(vec![1.0, 0.0, 0.0]), 0.0);
+        test(&Concrete::cube().cut_halfspace(&plane, true), [1, 8, 12, 6, 1]);
+    }
+
+    /// Cuts a corner off a cube.
+    #[test]
+    fn cube_corner() {
+        let normal = Vector::from_vec(vec![1.0, 1.0, 1.0]).normalize();
+        let plane = Hyperplane::new(normal, 0.6);
+        test(&Concrete::cube().cut_halfspace(&plane, false), [1, 10, 15, 7, 1]);
+    }
+
+    /// Cuts a cube through four of its vertices.
+    #[test]
+    fn cube_through_vertices() {
+        let normal = Vector::from_vec(vec![1.0, 1.0, 0.0]).normalize();
+        let plane = Hyperplane::new(normal, 0.0);
+        test(&Concrete::cube().cut_halfspace(&plane, true), [1, 6, 9, 5, 1]);
+    }
+
+    /// Cuts a dyad, which ends up ending at the hyperplane.
+    #[test]
+    fn dyad() {
+        let plane = Hyperplane::new(Vector::from_vec(vec![1.0]), 0.25);
+        let dyad = Concrete::dyad();
+
+        let cut = dyad.cut_halfspace(&plane, true);
+        test(&cut, [1, 2, 1]);
+        let mut xs: Vec<f64> = cut.vertices.iter().map(|v| v[0]).collect();
+        xs.sort_unstable_by(f64::total_cmp);
+        assert_eq!(xs, [0.25, 0.5]);
+
+        let cut = dyad.cut_halfspace(&plane, false);
+        test(&cut, [1, 2, 1]);
+        assert!(((&cut.vertices[0] - &cut.vertices[1]).norm() - 0.75).abs() < f64::EPS);
+    }
+
+    /// Cuts a point, which is either kept or dropped.
+    #[test]
+    fn point() {
+        let plane = Hyperplane::new(Vector::from_vec(vec![1.0]), 0.0);
+        let point = |x| Concrete::new(vec![Point::from_vec(vec![x])], Abstract::point());
+
+        test(&point(1.0).cut_halfspace(&plane, true), [1, 1]);
+        assert!(point(1.0).cut_halfspace(&plane, false).is_nullitope());
+        test(&point(0.0).cut_halfspace(&plane, false), [1, 1]);
+    }
+
+    /// Cuts nothing off.
+    #[test]
+    fn cube_whole() {
+        let plane = Hyperplane::new(Vector::from_vec(vec![0.0, 0.0, 1.0]), 2.0);
+        test(&Concrete::cube().cut_halfspace(&plane, false), [1, 8, 12, 6, 1]);
+        assert!(Concrete::cube().cut_halfspace(&plane, true).is_nullitope());
+    }
+}
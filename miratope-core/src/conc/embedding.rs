@@ -0,0 +1,227 @@
+//! Assigns coordinates to abstract polytopes, so that they can at least be
+//! displayed, even if they have no faithful realization.
+
+use super::{Concrete, random::XorShift};
+use crate::{
+    abs::{Abstract, Ranked},
+    float::Float,
+    geometry::Point,
+};
+
+use vec_like::*;
+
+/// The options for a force-directed embedding of an abstract polytope.
+#[derive(Clone, Copy, Debug)]
+pub struct EmbeddingOptions {
+    /// The dimension of the space the polytope is embedded in.
+    pub dim: usize,
+
+    /// The number of steps of the simulation.
+    pub iterations: usize,
+
+    /// Whether to constrain the vertices to the unit sphere. This gives much
+    /// better results for polytopes that are "round", like most regular and
+    /// uniform ones.
+    pub on_sphere: bool,
+
+    /// The seed for the initial positions of the vertices.
+    pub seed: u64,
+}
+
+impl Default for EmbeddingOptions {
+    fn default() -> Self {
+        Self {
+            dim: 3,
+            iterations: 300,
+            on_sphere: false,
+            seed: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+}
+
+impl EmbeddingOptions {
+    /// Lays out the vertices of an abstract polytope, by simulating its edges
+    /// as springs and its vertices as charges that repel each other. The
+    /// resulting coordinates are centered at the origin and scaled to unit
+    /// average edge length.
+    pub fn embed(&self, abs: &Abstract) -> Vec<Point<f64>> {
+        let dim = self.dim;
+        let iterations = self.iterations;
+        let vertex_count = abs.vertex_count();
+        let mut rng = XorShift::new(self.seed);
+        let mut vertices: Vec<Point<f64>> = (0..vertex_count)
+            .map(|_| Point::from_fn(dim, |_, _| rng.next_f64()))
+            .collect();
+
+        if vertex_count < 2 || dim == 0 {
+            return vertices;
+        }
+
+        let edges: Vec<(usize, usize)> = abs
+            .get_element_list(2)
+            .map(|edges| edges.iter().map(|e| (e.subs[0], e.subs[1])).collect())
+            .unwrap_or_default();
+
+        // The ideal distance between vertices.
+        let k = (1.0 / vertex_count as f64).powf(1.0 / dim as f64);
+
+        if self.on_sphere {
+            project_to_sphere(&mut vertices);
+        }
+
+        for i in 0..iterations {
+            let temperature = 0.1 * (1.0 - i as f64 / iterations as f64);
+            let mut forces = vec![Point::zeros(dim); vertex_count];
+
+            // Every pair of vertices repels.
+            for u in 0..vertex_count {
+                for v in u + 1..vertex_count {
+                    let delta = &vertices[u] - &vertices[v];
+                    let dist = delta.norm().max(1e-9);
+                    let force = delta * (k * k / (dist * dist));
+                    forces[u] += &force;
+                    forces[v] -= &force;
+                }
+            }
+
+            // Every edge attracts its vertices.
+            for &(u, v) in &edges {
+                let delta = &vertices[u] - &vertices[v];
+                let force = &delta * (delta.norm() / k);
+                forces[u] -= &force;
+                forces[v] += &force;
+            }
+
+            // Moves every vertex, by at most the temperature.
+            for (v, mut force) in vertices.iter_mut().zip(forces) {
+                // On a sphere, only the tangential part of the force matters.
+                if self.on_sphere {
+                    let radial = force.dot(v);
+                    force -= &*v * radial;
+                }
+
+                let norm = force.norm();
+                if norm > 0.0 {
+                    *v += force * (norm.min(temperature) / norm);
+                }
+            }
+
+            if self.on_sphere {
+                project_to_sphere(&mut vertices);
+            }
+        }
+
+        // Centers the embedding. Spherical embeddings are already centered.
+        if !self.on_sphere {
+            let center = vertices.iter().sum::<Point<f64>>() / vertex_count as f64;
+            for v in &mut vertices {
+                *v -= &center;
+            }
+        }
+
+        // Rescales the embedding.
+        if !edges.is_empty() {
+            let mean_len = edges
+                .iter()
+                .map(|&(u, v)| (&vertices[u] - &vertices[v]).norm())
+                .sum::<f64>()
+                / edges.len() as f64;
+
+            if mean_len > 0.0 {
+                for v in &mut vertices {
+                    *v /= mean_len;
+                }
+            }
+        }
+
+        vertices
+    }
+}
+
+/// Moves every point onto the unit sphere. Points at the origin are left
+/// untouched.
+fn project_to_sphere(vertices: &mut [Point<f64>]) {
+    for v in vertices {
+        let norm = v.norm();
+        if norm > f64::EPS {
+            *v /= norm;
+        }
+    }
+}
+
+/// Lays out the vertices of an abstract polytope in a space of a given
+/// dimension, using the default [`EmbeddingOptions`] otherwise.
+pub fn spring_layout(abs: &Abstract, dim: usize, iterations: usize) -> Vec<Point<f64>> {
+    EmbeddingOptions {
+        dim,
+        iterations,
+        ..Default::default()
+    }
+    .embed(abs)
+}
+
+impl Concrete {
+    /// Builds a concrete polytope out of an abstract one, giving it a
+    /// best-effort embedding of its skeleton in 3D space.
+    pub fn from_abstract(abs: Abstract) -> Self {
+        Self::from_abstract_with(abs, &EmbeddingOptions::default())
+    }
+
+    /// Builds a concrete polytope out of an abstract one, embedding its
+    /// skeleton with the given options.
+    pub fn from_abstract_with(abs: Abstract, options: &EmbeddingOptions) -> Self {
+        let vertices = options.embed(&abs);
+        Self::new(vertices, abs)
+    }
+
+    /// Returns whether all of the vertices of the polytope coincide, as is the
+    /// case for polytopes stored without meaningful coordinates.
+    pub fn is_degenerate(&self) -> bool {
+        self.vertices.len() > 1
+            && self.vertices.iter().all(|v| (v - &self.vertices[0]).norm() < f64::EPS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{conc::ConcretePolytope, Polytope};
+
+    /// Embeds an abstract cube, and checks that the edges come out of similar
+    /// lengths.
+    #[test]
+    fn cube() {
+        let cube = Concrete::from_abstract(Abstract::cube());
+        assert_eq!(cube.vertices.len(), 8);
+        assert!(!cube.is_degenerate());
+
+        for idx in 0..cube.edge_count() {
+            let len = cube.edge_len(idx).unwrap();
+            assert!(len > 0.5 && len < 1.5, "edge length {} out of range", len);
+        }
+    }
+
+    /// Checks that the embedding is reproducible.
+    #[test]
+    fn deterministic() {
+        let abs = Abstract::tetrahedron();
+        assert_eq!(spring_layout(&abs, 3, 50), spring_layout(&abs, 3, 50));
+    }
+
+    /// Embeds an octahedron on a sphere, and checks that all vertices are
+    /// equidistant from the origin.
+    #[test]
+    fn sphere() {
+        let options = EmbeddingOptions {
+            on_sphere: true,
+            ..Default::default()
+        };
+        let octahedron = Concrete::from_abstract_with(Abstract::octahedron(), &options);
+
+        let radius = octahedron.vertices[0].norm();
+        assert!(radius > 0.0);
+        for v in &octahedron.vertices {
+            assert!((v.norm() - radius).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Performs [Stott expansions](https://polytope.miraheze.org/wiki/Expansion)
+//! and contractions, which move every facet of a polytope along its normal by
+//! the same distance.
+//!
+//! The new polytope has a vertex for every pair of a vertex and a facet
+//! containing it, so its lattice is the truncate of the original with the
+//! vertex and facet nodes ringed. Since the lattice doesn't depend on the
+//! distance, expansions with different distances can be interpolated between,
+//! and a distance of zero gives back the original vertices.
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    Polytope,
+    abs::Ranked,
+    geometry::{Point, Subspace, Vector},
+};
+
+impl Concrete {
+    /// Returns the unit normal of a facet within the hull of the polytope,
+    /// pointing away from a given center. If the facet goes through the
+    /// center, we return an arbitrary one of its normals. Returns `None` if
+    /// the facet doesn't exist or doesn't span a hyperplane of the hull.
+    pub(super) fn facet_normal(
+        &self,
+        idx: usize,
+        center: &Point<f64>,
+        hull: &Subspace<f64>,
+    ) -> Option<Vector<f64>> {
+        let vertices = self.abs.element_vertices(self.rank() - 1, idx)?;
+        let facet = Subspace::from_points(vertices.iter().map(|&v| &self.vertices[v]));
+        if facet.rank() + 1 != hull.rank() {
+            return None;
+        }
+
+        facet.normal(center).map(|n| -n).or_else(|| {
+            hull.basis
+                .iter()
+                .find_map(|b| facet.normal(&(&facet.offset + b)))
+        })
+    }
+
+    /// Returns the unit normal of every facet, pointing away from the
+    /// gravicenter. Returns `None` if some facet doesn't span a hyperplane of
+    /// the polytope.
+    fn facet_normals(&self) -> Option<Vec<Vector<f64>>> {
+        let rank = self.rank();
+        let center = self.gravicenter()?;
+        let hull = Subspace::from_points(self.vertices().iter());
+
+        (0..self.el_count(rank - 1))
+            .map(|idx| self.facet_normal(idx, &center, &hull))
+            .collect()
+    }
+
+    /// Moves every facet of the polytope outwards along its normal by a given
+    /// distance, or inwards if it's negative. Returns `None` if the polytope
+    /// has no facets, or if some facet doesn't span a hyperplane.
+    pub fn expand(&self, distance: f64) -> Option<Self> {
+        let rank = self.rank();
+        if rank < 3 {
+            return None;
+        }
+
+        let mut poly = self.clone();
+        poly.element_sort();
+        let normals = poly.facet_normals()?;
+
+        // Every vertex is a vertex of the original, together with a facet
+        // through it.
+        let (abs, subflags) = poly.abs.truncate_and_flags(vec![0, rank - 2]);
+        let vertices: Vec<Point<f64>> = subflags
+            .into_iter()
+            .map(|subflag| &poly.vertices[subflag[0]] + &normals[subflag[1]] * distance)
+            .collect();
+
+        Some(Self::new(vertices, abs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{float::Float, test};
+
+    /// Expands a square into an octagon.
+    #[test]
+    fn square() {
+        let square = Concrete::polygon(4);
+        let octagon = square.expand(1.0).unwrap();
+        test(&octagon, [1, 8, 8, 1]);
+    }
+
+    /// Expands a cube into a small rhombicuboctahedron.
+    #[test]
+    fn cube() {
+        let cube = Concrete::cube();
+        let sirco = cube.expand(1.0 / f64::SQRT_2).unwrap();
+        test(&sirco, [1, 24, 48, 26, 1]);
+        assert!(sirco.is_equilateral_with(1.0));
+
+        // A contraction has the same elements.
+        test(&cube.expand(-0.5).unwrap(), [1, 24, 48, 26, 1]);
+    }
+
+    /// Points and dyads have no facets to move.
+    #[test]
+    fn no_facets() {
+        assert!(Concrete::point().expand(1.0).is_none());
+        assert!(Concrete::dyad().expand(1.0).is_none());
+    }
+}
@@ -0,0 +1,411 @@
+//! Searches for sets of facet orbits that close up into a polytope.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::{ridges::RidgeOrbits, subdim::FacetCandidates, ProgressReporter};
+
+/// Modified binary search that finds the first element whose first element is greater than `min`.
+pub(super) fn binary(vec: &[(usize,usize)], min: usize) -> usize {
+    let mut lo  = -1;
+    let mut hi  = vec.len() as isize;
+    let mut c = (lo+hi)/2;
+
+    while hi - lo > 1 {
+        if vec[c as usize].0 > min {
+            hi = c;
+        } else {
+            lo = c;
+        }
+        c = (lo+hi)/2;
+    }
+
+    hi as usize
+}
+
+/// The facetings as sorted sets of facet orbits, indexed so that the ones
+/// inside another can be found without comparing every pair.
+struct FacetingIndex {
+    /// The facet orbits of each faceting, sorted.
+    sets: Vec<Vec<(usize, usize)>>,
+
+    /// The index of the faceting with each set of facet orbits.
+    by_set: HashMap<Vec<(usize, usize)>, usize>,
+
+    /// For each facet orbit, the facetings whose smallest facet orbit it is.
+    by_first: HashMap<(usize, usize), Vec<usize>>,
+}
+
+impl FacetingIndex {
+    /// Indexes a list of facetings.
+    fn new(vec: &[Vec<(usize, usize)>]) -> Self {
+        let sets: Vec<Vec<(usize, usize)>> = vec
+            .iter()
+            .map(|facets| {
+                let mut set = facets.clone();
+                set.sort_unstable();
+                set
+            })
+            .collect();
+
+        let mut by_set = HashMap::new();
+        let mut by_first = HashMap::<(usize, usize), Vec<usize>>::new();
+        for (idx, set) in sets.iter().enumerate() {
+            by_set.entry(set.clone()).or_insert(idx);
+            if let Some(&first) = set.first() {
+                by_first.entry(first).or_default().push(idx);
+            }
+        }
+
+        Self { sets, by_set, by_first }
+    }
+
+    /// Returns the facetings whose facet orbits are a strict subset of those
+    /// of a given faceting. Any such subset starts with one of the facet
+    /// orbits of the faceting, so we only look at the facetings starting with
+    /// these.
+    fn strict_subsets(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        let set = &self.sets[idx];
+
+        set.iter()
+            .filter_map(|facet| self.by_first.get(facet))
+            .flatten()
+            .copied()
+            .filter(move |&sub| self.sets[sub].len() < set.len() && is_subset(&self.sets[sub], set))
+    }
+}
+
+/// Returns whether a sorted list is contained in another.
+fn is_subset(sub: &[(usize, usize)], set: &[(usize, usize)]) -> bool {
+    let mut iter = set.iter();
+    sub.iter().all(|x| iter.any(|y| y == x))
+}
+
+/// Returns the elements of a sorted list that aren't in a sorted sublist.
+fn complement(sub: &[(usize, usize)], set: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut sub = sub.iter().peekable();
+    set.iter()
+        .filter(|&x| {
+            if sub.peek() == Some(&x) {
+                sub.next();
+                false
+            } else {
+                true
+            }
+        })
+        .copied()
+        .collect()
+}
+
+/// For each faceting, checks if it is a compound of other facetings, and labels
+/// it if so. A faceting that contains another is a compound, but the rest of
+/// it might not be among the facetings, for instance if it was filtered out.
+/// Such compounds are labeled as having no components.
+pub(super) fn label_irc(vec: &[Vec<(usize, usize)>]) -> HashMap<usize, Option<(usize, usize)>> {
+    let index = FacetingIndex::new(vec);
+    let mut out = HashMap::new(); // Map of the index of the compound to the indices of the components.
+
+    'a: for a in 0..vec.len() {
+        let mut compound = false;
+
+        for b in index.strict_subsets(a) {
+            compound = true;
+            if let Some(&c) = index.by_set.get(&complement(&index.sets[b], &index.sets[a])) {
+                out.insert(a, Some((b, c)));
+                continue 'a;
+            }
+        }
+
+        if compound {
+            out.insert(a, None);
+        }
+    }
+
+    out
+}
+
+/// For each faceting, checks if it is a compound of other facetings, and removes it if so.
+pub(super) fn filter_irc(vec: &[Vec<(usize, usize)>]) -> Vec<usize> {
+    let index = FacetingIndex::new(vec);
+    (0..vec.len()).filter(|&a| index.strict_subsets(a).next().is_none()).collect()
+}
+
+/// A partial set of facet orbits in the search queue.
+struct QueueEntry {
+    /// The facet orbits, as pairs of hyperplane orbit and facet indices.
+    facets: Vec<(usize, usize)>,
+
+    /// The hyperplane orbits before this one can't be added anymore.
+    min_hp: usize,
+
+    /// How many times each ridge orbit is covered by the facets.
+    ridge_muls: Vec<usize>,
+}
+
+/// A depth-first search for the sets of facet orbits in which every ridge is
+/// shared by exactly two facets. Iterating over it returns these sets, with
+/// compound facets split into their components.
+pub(super) struct CombinationSearch<'a> {
+    /// The candidate facets.
+    candidates: &'a FacetCandidates,
+
+    /// The orbits of the ridges of the candidate facets.
+    ridge_orbits: &'a RidgeOrbits,
+
+    /// For each candidate facet, how many times it covers each ridge orbit.
+    ridge_muls: Vec<Vec<Vec<usize>>>,
+
+    /// For each ridge orbit, the candidate facets that cover it once, sorted
+    /// by hyperplane orbit.
+    ones: Vec<Vec<(usize, usize)>>,
+
+    /// The partial facetings still to check.
+    queue: VecDeque<QueueEntry>,
+
+    /// Whether to keep adding facets to complete facetings, to find compounds.
+    extend_complete: bool,
+
+    /// The maximum number of facet orbits in a faceting.
+    max_facets: Option<usize>,
+
+    /// If set, the search reports its progress here, calling the results by
+    /// this name.
+    pub progress: Option<(&'a mut ProgressReporter, &'static str)>,
+
+    /// The number of facetings returned so far.
+    found: usize,
+
+    /// The number of returned facetings that the caller discarded.
+    pub skipped: usize,
+}
+
+impl<'a> CombinationSearch<'a> {
+    /// Initializes the search, given the number of hyperplanes in each
+    /// hyperplane orbit.
+    pub fn new(
+        candidates: &'a FacetCandidates,
+        f_counts: &[usize],
+        ridge_orbits: &'a RidgeOrbits,
+        extend_complete: bool,
+        max_facets: Option<usize>,
+    ) -> Self {
+        let orbit_count = ridge_orbits.orbits.len();
+        let mut ridge_muls = Vec::new();
+        let mut ones = vec![Vec::<(usize, usize)>::new(); orbit_count];
+
+        for (hp, list) in candidates.facets.iter().enumerate() {
+            let mut ridge_muls_hp = Vec::new();
+            for (f, candidate) in list.iter().enumerate() {
+                let mut ridge_muls_facet = vec![0; orbit_count];
+
+                for ridge_idx in &candidate.ridges {
+                    let ridge_orbit = ridge_orbits.orbit_of[hp][ridge_idx.0][ridge_idx.1];
+                    let ridge_count = candidates.ridge_counts[hp][ridge_idx.0];
+                    let total_ridge_count = ridge_orbits.orbits[ridge_orbit].count;
+                    let mul = f_counts[hp] * ridge_count / total_ridge_count;
+
+                    if mul == 1 {
+                        ones[ridge_orbit].push((hp, f));
+                    }
+
+                    ridge_muls_facet[ridge_orbit] = mul;
+                }
+
+                ridge_muls_hp.push(ridge_muls_facet);
+            }
+            ridge_muls.push(ridge_muls_hp);
+        }
+
+        let mut queue = VecDeque::new();
+        for (hp, list) in candidates.facets.iter().enumerate() {
+            for f in 0..list.len() {
+                queue.push_back(QueueEntry {
+                    facets: vec![(hp, f)],
+                    min_hp: hp,
+                    ridge_muls: vec![0; orbit_count],
+                });
+            }
+        }
+
+        Self {
+            candidates,
+            ridge_orbits,
+            ridge_muls,
+            ones,
+            queue,
+            extend_complete,
+            max_facets,
+            progress: None,
+            found: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Splits compound facets into their components.
+    fn split_compounds(&self, facets: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        let mut new_facets = Vec::new();
+
+        for &(hp, idx) in facets {
+            let mut queue = VecDeque::new();
+            queue.push_back(idx);
+            while let Some(next) = queue.pop_front() {
+                if let Some(Some(components)) = self.candidates.compounds[hp].get(&next) {
+                    queue.push_back(components.0);
+                    queue.push_back(components.1);
+                } else {
+                    new_facets.push((hp, next));
+                }
+            }
+        }
+
+        new_facets
+    }
+
+    /// Reports how far along the search is.
+    fn print_progress(&mut self, facets: &[(usize, usize)]) {
+        let (found, skipped) = (self.found, self.skipped);
+        if let Some((progress, label)) = &mut self.progress {
+            progress.step(found - skipped, None, || {
+                if skipped > 0 {
+                    format!("{} {} found, {} skipped, {:?}", found - skipped, label, skipped, facets)
+                } else {
+                    format!("{} {} found, {:?}", found, label, facets)
+                }
+            });
+        }
+    }
+}
+
+impl<'a> Iterator for CombinationSearch<'a> {
+    type Item = Vec<(usize, usize)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(QueueEntry { facets, min_hp, ridge_muls: mut new_ridge_muls }) = self.queue.pop_back() {
+            if self.progress.as_ref().is_some_and(|(progress, _)| progress.is_cancelled()) {
+                return None;
+            }
+
+            self.print_progress(&facets);
+
+            let &(hp, f) = facets.last().unwrap();
+            for ridge_idx in &self.candidates.facets[hp][f].ridges {
+                let ridge_orbit = self.ridge_orbits.orbit_of[hp][ridge_idx.0][ridge_idx.1];
+                new_ridge_muls[ridge_orbit] += self.ridge_muls[hp][f][ridge_orbit];
+                if new_ridge_muls[ridge_orbit] > 2 {
+                    break;
+                }
+            }
+
+            // A ridge shared by more than two facets makes the faceting exotic,
+            // one shared by a single facet makes it incomplete.
+            if new_ridge_muls.iter().any(|&mul| mul > 2) {
+                continue;
+            }
+            let incomplete = new_ridge_muls.contains(&1);
+
+            let at_max = self.max_facets == Some(facets.len());
+            let used_hps: HashSet<usize> = facets.iter().skip(1).map(|facet| facet.0).collect();
+
+            if incomplete {
+                if at_max {
+                    continue;
+                }
+                // Only the first uncovered ridge orbit needs to be covered now.
+                if let Some(idx) = new_ridge_muls.iter().position(|&mul| mul == 1) {
+                    let ones = &self.ones[idx];
+                    for facet in ones.iter().skip(binary(ones, min_hp)) {
+                        if !used_hps.contains(&facet.0) {
+                            let mut new_facets = facets.clone();
+                            new_facets.push(*facet);
+                            self.queue.push_back(QueueEntry {
+                                facets: new_facets,
+                                min_hp,
+                                ridge_muls: new_ridge_muls.clone(),
+                            });
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let components = self.split_compounds(&facets);
+
+            if self.extend_complete && !at_max {
+                for (hp, list) in self.candidates.facets.iter().enumerate().skip(min_hp+1) {
+                    if !used_hps.contains(&hp) {
+                        for f in 0..list.len() {
+                            let mut new_facets = facets.clone();
+                            new_facets.push((hp, f));
+                            self.queue.push_back(QueueEntry {
+                                facets: new_facets,
+                                min_hp: hp,
+                                ridge_muls: new_ridge_muls.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+
+            self.found += 1;
+            return Some(components);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_search() {
+        let vec = [(0, 0), (1, 2), (1, 3), (3, 0)];
+        assert_eq!(binary(&vec, 0), 1);
+        assert_eq!(binary(&vec, 1), 3);
+        assert_eq!(binary(&vec, 5), 4);
+    }
+
+    #[test]
+    fn compounds() {
+        let facetings = vec![vec![(0, 0)], vec![(0, 0), (1, 0)], vec![(1, 0)]];
+
+        let labels = label_irc(&facetings);
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[&1], Some((0, 2)));
+
+        assert_eq!(filter_irc(&facetings), vec![0, 2]);
+    }
+
+    /// A compound whose only possible components lack their complements is
+    /// labeled without components.
+    #[test]
+    fn missing_complement() {
+        let facetings = vec![vec![(0, 0), (1, 0), (2, 0)], vec![(1, 0), (2, 0)]];
+
+        let labels = label_irc(&facetings);
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[&0], None);
+
+        assert_eq!(filter_irc(&facetings), vec![1]);
+    }
+
+    /// Subsets are found no matter how the facetings and their facets are
+    /// ordered, and a subset without a complement doesn't hide one with it.
+    #[test]
+    fn unsorted_compounds() {
+        let facetings = vec![
+            vec![(2, 0), (0, 1), (1, 0), (0, 0)],
+            vec![(0, 0), (0, 1), (2, 0)],
+            vec![(1, 0), (0, 0)],
+            vec![(2, 0), (0, 1)],
+            vec![(1, 1)],
+        ];
+
+        let labels = label_irc(&facetings);
+        assert_eq!(labels.len(), 2);
+        assert_eq!(labels[&0], Some((2, 3)));
+        assert_eq!(labels[&1], None);
+
+        assert_eq!(filter_irc(&facetings), vec![2, 3, 4]);
+    }
+}
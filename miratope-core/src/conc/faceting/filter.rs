@@ -0,0 +1,196 @@
+//! Filters the candidate facets found on every hyperplane by their own
+//! properties, before searching for the sets of them that close up. Unlike the
+//! bounds on hyperplanes, these can tell apart the facets on the same
+//! hyperplane.
+
+use std::collections::HashMap;
+
+use super::{
+    hyperplanes::in_range,
+    subdim::{FacetCandidate, FacetCandidates},
+};
+use crate::{
+    abs::Ranks,
+    geometry::{Hypersphere, Point},
+};
+
+use vec_like::*;
+
+/// The bounds that a candidate facet must satisfy to be used in a faceting.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FacetFilter {
+    /// The minimum circumradius of a facet.
+    pub min_circumradius: Option<f64>,
+
+    /// The maximum circumradius of a facet.
+    pub max_circumradius: Option<f64>,
+
+    /// The minimum number of vertices of a facet.
+    pub min_vertices: Option<usize>,
+
+    /// The maximum number of vertices of a facet.
+    pub max_vertices: Option<usize>,
+
+    /// The number of elements of each rank of a facet, from the vertices up
+    /// to its own facets.
+    pub element_counts: Option<Vec<usize>>,
+}
+
+impl FacetFilter {
+    /// Returns whether the filter accepts every facet.
+    pub fn is_trivial(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns whether a facet passes the filter. Its edges must use the
+    /// indices of the given vertices.
+    fn accepts(&self, facet: &Ranks, vertices: &[Point<f64>]) -> bool {
+        let mut facet_vertices: Vec<usize> = facet[2]
+            .iter()
+            .flat_map(|edge| edge.subs.iter().copied())
+            .collect();
+        facet_vertices.sort_unstable();
+        facet_vertices.dedup();
+        let vertex_count = facet_vertices.len();
+
+        if self.min_vertices.is_some_and(|min| vertex_count < min)
+            || self.max_vertices.is_some_and(|max| vertex_count > max)
+        {
+            return false;
+        }
+
+        if let Some(counts) = &self.element_counts {
+            let facet_counts =
+                std::iter::once(vertex_count).chain((2..facet.len() - 1).map(|r| facet[r].len()));
+            if !facet_counts.eq(counts.iter().copied()) {
+                return false;
+            }
+        }
+
+        if self.min_circumradius.is_some() || self.max_circumradius.is_some() {
+            let points = facet_vertices
+                .into_iter()
+                .map(|v| vertices[v].clone())
+                .collect();
+
+            // Facets without a circumsphere only pass if there's no bound.
+            return Hypersphere::circumsphere(&points).is_some_and(|sphere| {
+                in_range(
+                    &sphere.radius(),
+                    &self.min_circumradius,
+                    &self.max_circumradius,
+                )
+            });
+        }
+
+        true
+    }
+}
+
+/// Returns whether a candidate facet passes a filter, or if it's a compound,
+/// whether all of its components do.
+fn keeps(
+    idx: usize,
+    facets: &[FacetCandidate],
+    compounds: &HashMap<usize, Option<(usize, usize)>>,
+    filter: &FacetFilter,
+    vertices: &[Point<f64>],
+    memo: &mut [Option<bool>],
+) -> bool {
+    if let Some(kept) = memo[idx] {
+        return kept;
+    }
+
+    let kept = match compounds.get(&idx) {
+        Some(&Some((a, b))) => {
+            keeps(a, facets, compounds, filter, vertices, memo)
+                && keeps(b, facets, compounds, filter, vertices, memo)
+        }
+        _ => filter.accepts(&facets[idx].global, vertices),
+    };
+    memo[idx] = Some(kept);
+    kept
+}
+
+/// Removes the candidate facets that don't pass a filter, and returns how many
+/// were removed. A compound facet is kept only when every one of its
+/// components passes, since it's output as these.
+pub(super) fn filter_candidates(
+    candidates: &mut FacetCandidates,
+    filter: &FacetFilter,
+    vertices: &[Point<f64>],
+) -> usize {
+    let mut removed = 0;
+
+    for (facets, compounds) in candidates.facets.iter_mut().zip(&mut candidates.compounds) {
+        let mut memo = vec![None; facets.len()];
+        let keep: Vec<bool> = (0..facets.len())
+            .map(|idx| keeps(idx, facets, compounds, filter, vertices, &mut memo))
+            .collect();
+
+        // The new index of every facet that's kept.
+        let mut new_idx = vec![None; facets.len()];
+        let mut count = 0;
+        for (idx, &kept) in keep.iter().enumerate() {
+            if kept {
+                new_idx[idx] = Some(count);
+                count += 1;
+            }
+        }
+        removed += facets.len() - count;
+
+        let old_facets: Vec<FacetCandidate> = std::mem::take(facets);
+        *facets = old_facets
+            .into_iter()
+            .zip(&keep)
+            .filter_map(|(facet, &kept)| kept.then_some(facet))
+            .collect();
+
+        *compounds = compounds
+            .iter()
+            .filter_map(|(&idx, &components)| {
+                let idx = new_idx[idx]?;
+                Some((idx, components.map(|(a, b)| (new_idx[a].unwrap(), new_idx[b].unwrap()))))
+            })
+            .collect::<HashMap<_, _>>();
+    }
+
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Polytope, abs::Ranked, conc::Concrete, float::Float};
+
+    /// Filters a cube, as if it were a facet.
+    #[test]
+    fn cube() {
+        let cube = Concrete::cube();
+        let accepts = |filter: FacetFilter| filter.accepts(cube.abs.ranks(), &cube.vertices);
+        let radius = f64::SQRT_3 / 2.0;
+
+        assert!(FacetFilter::default().is_trivial());
+        assert!(accepts(FacetFilter::default()));
+        assert!(accepts(FacetFilter {
+            min_circumradius: Some(radius),
+            max_circumradius: Some(radius),
+            min_vertices: Some(8),
+            max_vertices: Some(8),
+            element_counts: Some(vec![8, 12, 6]),
+        }));
+
+        assert!(!accepts(FacetFilter {
+            max_circumradius: Some(0.5),
+            ..Default::default()
+        }));
+        assert!(!accepts(FacetFilter {
+            min_vertices: Some(9),
+            ..Default::default()
+        }));
+        assert!(!accepts(FacetFilter {
+            element_counts: Some(vec![8, 12]),
+            ..Default::default()
+        }));
+    }
+}
@@ -0,0 +1,430 @@
+//! Enumerates the orbits of edges and hyperplanes of a polytope under a vertex
+//! map.
+
+use std::collections::{BTreeMap, HashSet};
+
+use super::ProgressReporter;
+use crate::{
+    float::Float,
+    geometry::{Point, PointOrd, Subspace},
+    group::{stab_chain::StabChain, vertex_map::VertexMap},
+};
+
+use ordered_float::OrderedFloat;
+
+/// Returns whether a length is within the (optional) bounds, up to floating
+/// point error.
+pub(super) fn in_range<T: Float>(value: &T, min: &Option<T>, max: &Option<T>) -> bool {
+    if let Some(min) = min {
+        if *value < min.clone() - T::EPS {
+            return false;
+        }
+    }
+    if let Some(max) = max {
+        if *value > max.clone() + T::EPS {
+            return false;
+        }
+    }
+    true
+}
+
+/// An orbit of hyperplanes under the symmetry group.
+#[derive(Clone)]
+pub(super) struct HyperplaneOrbit<T: Float> {
+    /// A representative hyperplane of the orbit.
+    pub hyperplane: Subspace<T>,
+
+    /// The indices of the vertices on the representative hyperplane.
+    pub vertices: Vec<usize>,
+
+    /// The number of hyperplanes in the orbit.
+    pub count: usize,
+}
+
+/// The bounds that a hyperplane must satisfy to be considered for a facet.
+pub(super) struct HyperplaneFilter<T: Float> {
+    /// The minimum edge length.
+    pub min_edge_length: Option<T>,
+
+    /// The maximum edge length.
+    pub max_edge_length: Option<T>,
+
+    /// The minimum distance from the hyperplane to the origin.
+    pub min_inradius: Option<T>,
+
+    /// The maximum distance from the hyperplane to the origin.
+    pub max_inradius: Option<T>,
+
+    /// Whether to skip hyperplanes through the origin.
+    pub exclude_hemis: bool,
+}
+
+impl<T: Float> HyperplaneFilter<T> {
+    /// Returns whether a hyperplane at a given distance from the origin passes
+    /// the filter.
+    fn accepts_inradius(&self, inradius: T) -> bool {
+        in_range(&inradius, &self.min_inradius, &self.max_inradius)
+            && !(self.exclude_hemis && inradius.fabs() < T::EPS)
+    }
+}
+
+/// Returns the distinct distances between a vertex and any other, in
+/// increasing order.
+pub(super) fn edge_lengths<T: Float>(vertices: &[Point<T>], vertex_orbits: &[Vec<usize>]) -> Vec<T> {
+    let mut all_lengths = Vec::new();
+
+    for orbit in vertex_orbits {
+        let rep = orbit[0];
+        for i in rep+1..vertices.len() {
+            all_lengths.push((&vertices[rep] - &vertices[i]).norm());
+        }
+    }
+    all_lengths.sort_unstable_by(|a, b| a.partial_cmp(b).expect("edge length is NaN"));
+
+    let mut possible_lengths = Vec::new();
+    if let Some(first) = all_lengths.first() {
+        possible_lengths.push(first.clone());
+    }
+    for pair in all_lengths.windows(2) {
+        if pair[1].clone() - pair[0].clone() > T::EPS {
+            possible_lengths.push(pair[1].clone());
+        }
+    }
+
+    possible_lengths
+}
+
+/// Splits the pairs of vertices with a valid length into orbits. Only pairs
+/// with a vertex orbit representative as their first vertex are tried.
+pub(super) fn edge_orbits<T: Float>(
+    points: &[PointOrd<T>],
+    vertex_orbits: &[Vec<usize>],
+    vertex_map: &VertexMap,
+    min_edge_length: &Option<T>,
+    max_edge_length: &Option<T>,
+    mut progress: Option<&mut ProgressReporter>,
+) -> Vec<Vec<Vec<usize>>> {
+    let mut pair_orbits = Vec::new();
+    let mut checked = vec![vec![false; points.len()]; points.len()];
+
+    for (orbit_idx, orbit) in vertex_orbits.iter().enumerate() {
+        let rep = orbit[0]; // We only need one representative per orbit.
+        for vertex in rep+1..points.len() {
+            if let Some(progress) = progress.as_deref_mut() {
+                if progress.is_cancelled() {
+                    return pair_orbits;
+                }
+
+                progress.step(orbit_idx, Some(vertex_orbits.len()), || {
+                    format!("{} edge orbits, verts [{}, {}]", pair_orbits.len(), rep, vertex)
+                });
+            }
+
+            if !checked[rep][vertex] {
+                let edge_length = (&points[vertex].0 - &points[rep].0).norm();
+                if !in_range(&edge_length, min_edge_length, max_edge_length) {
+                    continue;
+                }
+
+                let mut new_orbit = Vec::new();
+                for row in vertex_map {
+                    let (a1, a2) = (row[rep], row[vertex]);
+                    let c1 = a1.min(a2);
+                    let c2 = a1.max(a2);
+
+                    if !checked[c1][c2] {
+                        new_orbit.push(vec![c1, c2]);
+                        checked[c1][c2] = true;
+                    }
+                }
+                pair_orbits.push(new_orbit);
+            }
+        }
+    }
+
+    pair_orbits
+}
+
+/// Given a hyperplane, finds all vertices on it and adds its orbit if it
+/// hasn't been found yet.
+fn try_add_hyperplane<T: Float>(
+    hyperplane: Subspace<T>,
+    vertices: &[Point<T>],
+    chain: &StabChain,
+    filter: &HyperplaneFilter<T>,
+    checked: &mut HashSet<Vec<usize>>,
+    hyperplane_orbits: &mut Vec<HyperplaneOrbit<T>>,
+) {
+    if !hyperplane.is_hyperplane() {
+        return;
+    }
+
+    let inradius = hyperplane.distance(&Point::zeros(vertices[0].len()));
+    if !filter.accepts_inradius(inradius) {
+        return;
+    }
+
+    let mut hyperplane_vertices = Vec::new();
+    for (idx, v) in vertices.iter().enumerate() {
+        if hyperplane.distance(v) < T::EPS {
+            hyperplane_vertices.push(idx);
+        }
+    }
+    hyperplane_vertices.sort_unstable();
+
+    // Check if the hyperplane has been found already.
+    let orbit = chain.orbit_of_set(&hyperplane_vertices);
+    if orbit.iter().any(|hp_v| checked.contains(hp_v)) {
+        return;
+    }
+
+    checked.insert(hyperplane_vertices.clone());
+    hyperplane_orbits.push(HyperplaneOrbit {
+        hyperplane,
+        vertices: hyperplane_vertices,
+        count: orbit.len(),
+    });
+}
+
+/// Enumerates the hyperplanes orthogonal to a representative of each vertex
+/// orbit. This only finds the facets that lie below some vertex, but is much
+/// faster than trying every tuple of vertices.
+pub(super) fn hyperplanes_below_vertices<T: Float>(
+    vertices: &[Point<T>],
+    vertex_orbits: &[Vec<usize>],
+    chain: &StabChain,
+    rank: usize,
+    filter: &HyperplaneFilter<T>,
+    progress: &mut ProgressReporter,
+) -> Vec<HyperplaneOrbit<T>> {
+    let mut hyperplane_orbits = Vec::new();
+
+    for (orbit_idx, v_orbit) in vertex_orbits.iter().enumerate() {
+        let mut map = BTreeMap::<OrderedFloat<f64>, Vec<usize>>::new();
+        let point = &vertices[v_orbit[0]];
+
+        // The dot products are bucketed by rounding them to multiples of the
+        // epsilon. These multiples are small enough to be exact as `f64`s.
+        for (idx, vertex) in vertices.iter().enumerate() {
+            let dot = OrderedFloat((vertex.dot(point) / T::EPS).round().to_f64());
+            map.entry(dot).or_default().push(idx);
+        }
+
+        let mut checked = HashSet::new();
+
+        for (dbg_count, l) in map.values().enumerate() {
+            let mut list = l.clone();
+            list.sort_unstable();
+
+            if progress.is_cancelled() {
+                return hyperplane_orbits;
+            }
+
+            progress.step(orbit_idx, Some(vertex_orbits.len()), || {
+                format!("loop {}, verts {:?}", dbg_count, list)
+            });
+
+            // WLOG checks if the vertices are all the right distance away from the first vertex.
+            let count = list[1..]
+                .iter()
+                .filter(|v| {
+                    let edge_length = (&vertices[**v] - &vertices[list[0]]).norm();
+                    in_range(&edge_length, &filter.min_edge_length, &filter.max_edge_length)
+                })
+                .count();
+            if count < rank-2 {
+                continue
+            }
+
+            // We define a hyperplane from the list of vertices.
+            let hyperplane = Subspace::from_points(list.iter().map(|x| &vertices[*x]));
+            try_add_hyperplane(hyperplane, vertices, chain, filter, &mut checked, &mut hyperplane_orbits);
+        }
+    }
+
+    hyperplane_orbits
+}
+
+/// Enumerates the hyperplanes spanned by the vertices. Starting from the edge
+/// orbits, we add one vertex at a time, keeping one representative for every
+/// orbit of subspaces.
+pub(super) fn hyperplanes_through_edges<T: Float>(
+    vertices: &[Point<T>],
+    edge_orbits: &[Vec<Vec<usize>>],
+    chain: &StabChain,
+    rank: usize,
+    filter: &HyperplaneFilter<T>,
+    progress: &mut ProgressReporter,
+) -> Vec<HyperplaneOrbit<T>> {
+    let (min_edge_length, max_edge_length) = (&filter.min_edge_length, &filter.max_edge_length);
+
+    // Enumerate subspaces between lines and hyperplanes
+    let mut tuple_orbits: Vec<Vec<usize>> = edge_orbits.iter().map(|orbit| orbit[0].clone()).collect();
+    for number in 3..rank-1 {
+        let mut checked = HashSet::new();
+        let mut new_tuple_orbits = Vec::new();
+
+        let tuple_count = tuple_orbits.len();
+        for (tuple_idx, tuple) in tuple_orbits.into_iter().enumerate() {
+            for new_vertex in tuple[tuple.len()-1]..vertices.len() {
+                if progress.is_cancelled() {
+                    return Vec::new();
+                }
+
+                progress.step(tuple_idx, Some(tuple_count), || {
+                    format!("{} {}-plane orbits, verts {:?}", new_tuple_orbits.len(), number-1, tuple)
+                });
+
+                let edge_length = (&vertices[tuple[0]] - &vertices[new_vertex]).norm();
+                if !in_range(&edge_length, min_edge_length, max_edge_length) {
+                    continue;
+                }
+
+                let mut new_tuple = tuple.clone();
+                new_tuple.push(new_vertex);
+
+                let already_seen = chain
+                    .orbit_of_set(&new_tuple)
+                    .iter()
+                    .any(|moved| checked.contains(moved));
+                if already_seen {
+                    continue;
+                }
+
+                new_tuple.sort_unstable();
+
+                let subspace = Subspace::from_points(new_tuple.iter().map(|x| &vertices[*x]));
+                if subspace.rank() == number-1 {
+                    new_tuple_orbits.push(new_tuple.clone());
+                }
+
+                checked.insert(new_tuple);
+            }
+        }
+        progress.info(format!("{} {}-plane orbit{}", new_tuple_orbits.len(), number-1, if new_tuple_orbits.len() == 1 {""} else {"s"}));
+        tuple_orbits = new_tuple_orbits;
+    }
+
+    // Enumerate hyperplanes
+    let mut hyperplane_orbits = Vec::new();
+    let mut checked = HashSet::new();
+
+    let tuple_count = tuple_orbits.len();
+    for (tuple_idx, rep) in tuple_orbits.into_iter().enumerate() {
+        let last_vert = rep[rep.len()-1];
+
+        for new_vertex in last_vert+1..vertices.len() {
+            let mut tuple = rep.clone();
+            tuple.push(new_vertex);
+
+            if progress.is_cancelled() {
+                return hyperplane_orbits;
+            }
+
+            progress.step(tuple_idx, Some(tuple_count), || {
+                format!("{} hyperplane orbits, verts {:?}", hyperplane_orbits.len(), tuple)
+            });
+
+            let edge_length = (&vertices[new_vertex] - &vertices[rep[0]]).norm();
+            if !in_range(&edge_length, min_edge_length, max_edge_length) {
+                continue;
+            }
+
+            let hyperplane = Subspace::from_points(tuple.iter().map(|x| &vertices[*x]));
+            try_add_hyperplane(hyperplane, vertices, chain, filter, &mut checked, &mut hyperplane_orbits);
+        }
+    }
+
+    hyperplane_orbits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::dvector;
+
+    /// The rotations of a square, as a vertex map.
+    fn square_map() -> VertexMap {
+        vec![
+            vec![0, 1, 2, 3],
+            vec![1, 2, 3, 0],
+            vec![2, 3, 0, 1],
+            vec![3, 0, 1, 2],
+        ]
+        .into()
+    }
+
+    /// The vertices of a square.
+    fn square_points() -> Vec<PointOrd<f64>> {
+        vec![
+            PointOrd::new(dvector![1.0, 0.0]),
+            PointOrd::new(dvector![0.0, 1.0]),
+            PointOrd::new(dvector![-1.0, 0.0]),
+            PointOrd::new(dvector![0.0, -1.0]),
+        ]
+    }
+
+    #[test]
+    fn in_range_bounds() {
+        assert!(in_range(&1.0, &None, &None));
+        assert!(in_range(&1.0, &Some(1.0), &Some(1.0)));
+        assert!(!in_range(&0.5, &Some(1.0), &None));
+        assert!(!in_range(&1.5, &None, &Some(1.0)));
+    }
+
+    #[test]
+    fn square_edge_orbits() {
+        let map = square_map();
+        let points = square_points();
+        let v_orbits = map.orbits();
+
+        let orbits = edge_orbits(&points, &v_orbits, &map, &None, &None, None);
+        let mut sizes: Vec<usize> = orbits.iter().map(Vec::len).collect();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![2, 4]);
+
+        let orbits = edge_orbits(&points, &v_orbits, &map, &None, &Some(1.5), None);
+        assert_eq!(orbits.len(), 1);
+        assert_eq!(orbits[0].len(), 4);
+    }
+
+    #[test]
+    fn square_hyperplanes() {
+        let map = square_map();
+        let chain = StabChain::from_vertex_map(&map);
+        let vertices: Vec<_> = square_points().into_iter().map(|p| p.0).collect();
+        let v_orbits = chain.orbits();
+        let mut progress = ProgressReporter::callback(|_| {});
+
+        let mut filter = HyperplaneFilter {
+            min_edge_length: None,
+            max_edge_length: None,
+            min_inradius: None,
+            max_inradius: None,
+            exclude_hemis: false,
+        };
+        let orbits =
+            hyperplanes_below_vertices(&vertices, &v_orbits, &chain, 3, &filter, &mut progress);
+        assert_eq!(orbits.len(), 1);
+        assert_eq!(orbits[0].vertices, vec![1, 3]);
+        assert_eq!(orbits[0].count, 2);
+
+        filter.exclude_hemis = true;
+        let orbits =
+            hyperplanes_below_vertices(&vertices, &v_orbits, &chain, 3, &filter, &mut progress);
+        assert!(orbits.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "big-float")]
+    fn nearly_equal_edge_lengths() {
+        use crate::big_float::BigFloat;
+
+        let vertices = [dvector![0.0], dvector![1.0], dvector![-1.0 - 1e-9]];
+        let orbits = [vec![0]];
+        assert_eq!(edge_lengths(&vertices, &orbits).len(), 1);
+
+        let vertices = vertices.map(|v| v.map(BigFloat::<128>::f64));
+        assert_eq!(edge_lengths(&vertices, &orbits).len(), 2);
+    }
+}
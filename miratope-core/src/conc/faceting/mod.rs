@@ -0,0 +1,609 @@
+//! The faceting algorithm.
+//!
+//! Facetings are found in several phases, each in its own module:
+//!
+//! 1. The vertices, edges and hyperplanes of the polytope are split into
+//!    orbits under the symmetry group.
+//! 2. The representative of each orbit of hyperplanes is faceted recursively,
+//!    as a lower-dimensional polytope under its stabilizer. The facets of these
+//!    facetings are the possible facets and ridges of the faceted polytope.
+//! 3. The possible ridges are split into orbits.
+//! 4. We search for the sets of facet orbits in which every ridge is shared by
+//!    exactly two facets.
+//! 5. The faceted polytopes are built from their sets of facet orbits.
+
+mod combine;
+mod filter;
+mod hyperplanes;
+mod output;
+mod progress;
+mod ridges;
+mod signature;
+mod sort;
+mod subdim;
+
+use std::{collections::HashMap, vec, path::PathBuf};
+
+pub use self::filter::FacetFilter;
+pub use self::progress::{FacetingPhase, FacetingProgress, ProgressReporter};
+pub use self::signature::{FacetingSignature, SignatureError, SignatureResult};
+
+use self::{
+    combine::{filter_irc, CombinationSearch},
+    filter::filter_candidates,
+    hyperplanes::{
+        edge_lengths, edge_orbits, hyperplanes_below_vertices, hyperplanes_through_edges,
+        HyperplaneFilter,
+    },
+    output::{build_abstract, ElementInterner},
+    ridges::RidgeOrbits,
+    subdim::facet_hyperplanes,
+};
+#[cfg(feature = "big-float")]
+use crate::big_float::BigFloat;
+use crate::{
+    abs::Ranked,
+    conc::{Concrete, ConcretePolytope},
+    float::Float,
+    group::{stab_chain::StabChain, vertex_map::VertexMap, Group}, geometry::{Matrix, PointOrd, Point}, Polytope
+};
+
+/// The floats that the hyperplanes and their facetings are found with.
+///
+/// Delicate facetings, with nearly coincident hyperplanes, may need more
+/// precision than an `f64` has, at a large performance cost.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Precision {
+    /// 64-bit floats.
+    #[default]
+    Double,
+
+    /// [`BigFloat`]s with 128 bits of precision.
+    #[cfg(feature = "big-float")]
+    Quadruple,
+
+    /// [`BigFloat`]s with 256 bits of precision.
+    #[cfg(feature = "big-float")]
+    Octuple,
+}
+
+/// Input for the faceting function
+pub enum GroupEnum {
+    /// Group of matrices
+    ConcGroup(Group<vec::IntoIter<Matrix<f64>>>),
+    /// Group of vertex mappings
+    VertexMap(VertexMap),
+    /// True: take chiral group
+    /// False: take full group
+    Chiral(bool),
+}
+
+/// The options of a faceting search.
+#[derive(Clone, Debug, Default)]
+pub struct FacetingOptions {
+    /// Whether to check for all possible edge lengths and facet with each of them.
+    pub any_single_edge_length: bool,
+
+    /// The minimum edge length.
+    pub min_edge_length: Option<f64>,
+
+    /// The maximum edge length.
+    pub max_edge_length: Option<f64>,
+
+    /// The minimum inradius.
+    pub min_inradius: Option<f64>,
+
+    /// The maximum inradius.
+    pub max_inradius: Option<f64>,
+
+    /// Whether to exclude planes passing through the origin.
+    pub exclude_hemis: bool,
+
+    /// Whether to only consider hyperplanes perpendicular to a vertex.
+    pub only_below_vertex: bool,
+
+    /// The maximum number of facet types considered. 1 for isotopic.
+    pub noble: Option<usize>,
+
+    /// The maximum number of facets generated in each hyperplane.
+    pub max_per_hyperplane: Option<usize>,
+
+    /// The filter that the possible facets must pass.
+    pub facet_filter: FacetFilter,
+
+    /// Only use uniform or semiuniform elements.
+    pub uniform: bool,
+
+    /// Whether to include trivial compounds (compounds of other full-symmetric facetings).
+    pub include_compounds: bool,
+
+    /// Whether to check if the faceting is compound or fissary and mark it.
+    pub mark_fissary: bool,
+
+    /// Whether to include the facet numbers in the name.
+    pub label_facets: bool,
+
+    /// Whether to save the facetings in memory.
+    pub save: bool,
+
+    /// Whether to save the facets in memory.
+    pub save_facets: bool,
+
+    /// Whether to save to file.
+    pub save_to_file: bool,
+
+    /// The path to save to, if saving to file.
+    pub file_path: String,
+
+    /// The floats that the hyperplanes and their facetings are found with.
+    pub precision: Precision,
+}
+
+const CL: &str = "\r                                                                                                                   \r";
+
+const DELAY: u128 = 200;
+
+impl Concrete {
+    /// Enumerates the facetings of a polytope under a provided symmetry group or vertex map.
+    /// If the symmetry group is not provided, it uses the full symmetry of the polytope.
+    pub fn faceting(
+        &mut self,
+        vertices: Vec<Point<f64>>,
+        symmetry: GroupEnum,
+        options: &FacetingOptions,
+        progress: &mut ProgressReporter,
+    ) -> Vec<(Concrete, Option<String>)> {
+        macro_rules! faceting_with {
+            ($T: ty) => {
+                self.faceting_with::<$T>(vertices, symmetry, options, progress)
+            };
+        }
+
+        match options.precision {
+            Precision::Double => faceting_with!(f64),
+            #[cfg(feature = "big-float")]
+            Precision::Quadruple => faceting_with!(BigFloat<128>),
+            #[cfg(feature = "big-float")]
+            Precision::Octuple => faceting_with!(BigFloat<256>),
+        }
+    }
+
+    /// Enumerates the facetings of a polytope, finding the hyperplanes and
+    /// their facetings with floats of type `T`.
+    fn faceting_with<T: Float>(
+        &mut self,
+        vertices: Vec<Point<f64>>,
+        symmetry: GroupEnum,
+        options: &FacetingOptions,
+        progress: &mut ProgressReporter,
+    ) -> Vec<(Concrete, Option<String>)> {
+        let FacetingOptions {
+            any_single_edge_length,
+            min_edge_length,
+            max_edge_length,
+            min_inradius,
+            max_inradius,
+            exclude_hemis,
+            only_below_vertex,
+            noble,
+            max_per_hyperplane,
+            ref facet_filter,
+            uniform,
+            include_compounds,
+            mark_fissary,
+            label_facets,
+            save,
+            save_facets,
+            save_to_file,
+            ref file_path,
+            precision: _,
+        } = *options;
+
+        let rank = self.rank();
+
+        if rank < 4 {
+            progress.info("\nFaceting polytopes of rank less than 3 is not supported!\n");
+            return Vec::new()
+        }
+
+        let points: Vec<Point<T>> = vertices.iter().map(|v| v.map(T::f64)).collect();
+        let vertices_ord: Vec<PointOrd<T>> = points.iter().map(|v| PointOrd::new(v.clone())).collect();
+        let mut min_edge_length = min_edge_length.map(T::f64);
+        let mut max_edge_length = max_edge_length.map(T::f64);
+        let min_inradius = min_inradius.map(T::f64);
+        let max_inradius = max_inradius.map(T::f64);
+
+        let vertex_map = match symmetry {
+            GroupEnum::ConcGroup(group) => {
+                progress.phase(FacetingPhase::Symmetry);
+                self.get_vertex_map(group)
+            },
+            GroupEnum::VertexMap(a) => a,
+            GroupEnum::Chiral(chiral) => {
+                progress.phase(FacetingPhase::Symmetry);
+                let group = if chiral {
+                    self.get_rotation_group_with(progress.token())
+                } else {
+                    self.get_symmetry_group_with(progress.token())
+                };
+
+                let Ok(group) = group else {
+                    progress.phase(FacetingPhase::Cancelled);
+                    return Vec::new()
+                };
+
+                let g = group.unwrap();
+                progress.info(format!("{}ymmetry order {}", if chiral {"Rotation s"} else {"S"}, g.0.count()));
+                g.1
+            },
+        };
+
+        let mut output = Vec::new();
+
+        progress.phase(FacetingPhase::Vertices);
+
+        // Orbits of sets of vertices are found through a stabilizer chain, so that we don't go through every element of the group.
+        let chain = StabChain::from_vertex_map(&vertex_map);
+
+        // Checking every r-tuple of vertices would take too long, so we put pairs into orbits first to reduce the number.
+        let vertex_orbits = chain.orbits();
+        let orbit_count = vertex_orbits.len();
+
+        progress.info(format!("{} vertices in {} orbit{}", vertices.len(), orbit_count, if orbit_count == 1 {""} else {"s"}));
+
+        let mut possible_lengths = Vec::new();
+
+        if any_single_edge_length {
+            progress.phase(FacetingPhase::EdgeLengths);
+            possible_lengths = edge_lengths(&points, &vertex_orbits);
+            progress.info(format!("Found {} edge lengths: {:?}", possible_lengths.len(), possible_lengths));
+
+            if possible_lengths.is_empty() {
+                return output
+            }
+        }
+        let mut edge_length_idx = 0;
+
+        loop {
+            if any_single_edge_length {
+                let edge_length = &possible_lengths[edge_length_idx];
+                min_edge_length = Some(edge_length.clone());
+                max_edge_length = Some(edge_length.clone());
+                progress.info(format!("\nChecking edge length {} ({}/{})", edge_length, edge_length_idx+1, possible_lengths.len()));
+            }
+
+            progress.phase(FacetingPhase::Hyperplanes);
+
+            let filter = HyperplaneFilter {
+                min_edge_length: min_edge_length.clone(),
+                max_edge_length: max_edge_length.clone(),
+                min_inradius: min_inradius.clone(),
+                max_inradius: max_inradius.clone(),
+                exclude_hemis,
+            };
+
+            let hyperplane_orbits = if only_below_vertex {
+                hyperplanes_below_vertices(&points, &vertex_orbits, &chain, rank, &filter, progress)
+            } else {
+                let pair_orbits = edge_orbits(&vertices_ord, &vertex_orbits, &vertex_map, &min_edge_length, &max_edge_length, Some(&mut *progress));
+                progress.info(format!("{} edge orbit{}", pair_orbits.len(), if pair_orbits.len() == 1 {""} else {"s"}));
+
+                hyperplanes_through_edges(&points, &pair_orbits, &chain, rank, &filter, progress)
+            };
+
+            if progress.is_cancelled() {
+                progress.phase(FacetingPhase::Cancelled);
+                return output
+            }
+
+            let f_counts: Vec<usize> = hyperplane_orbits.iter().map(|orbit| orbit.count).collect();
+            let sum: u64 = f_counts.iter().map(|count| *count as u64).sum();
+
+            progress.info(format!("{} hyperplanes in {} orbit{}", sum, hyperplane_orbits.len(), if hyperplane_orbits.len() == 1 {""} else {"s"}));
+
+            progress.phase(FacetingPhase::Subfacetings);
+
+            let mut candidates = facet_hyperplanes(
+                &hyperplane_orbits,
+                &vertices_ord,
+                &vertex_map,
+                rank,
+                &min_edge_length,
+                &max_edge_length,
+                max_per_hyperplane,
+                uniform,
+                if noble == Some(1) { Some(&vertex_map) } else { None },
+                Some(&mut *progress),
+            );
+
+            if progress.is_cancelled() {
+                progress.phase(FacetingPhase::Cancelled);
+                return output
+            }
+
+            // Cuts down the candidate facets before combining them.
+            if !facet_filter.is_trivial() {
+                let removed = filter_candidates(&mut candidates, facet_filter, &vertices);
+                progress.info(format!("{} candidate facet{} filtered out", removed, if removed == 1 {""} else {"s"}));
+            }
+
+            progress.phase(FacetingPhase::Ridges);
+
+            let ridge_orbits = RidgeOrbits::new_compact(&candidates, &hyperplane_orbits, &vertex_map, Some(&mut *progress));
+
+            if progress.is_cancelled() {
+                progress.phase(FacetingPhase::Cancelled);
+                return output
+            }
+
+            // Actually do the faceting
+            progress.phase(FacetingPhase::Combining);
+
+            let mut search = CombinationSearch::new(&candidates, &f_counts, &ridge_orbits, include_compounds, noble);
+            search.progress = Some((&mut *progress, "facetings"));
+
+            let mut output_facets: Vec<Vec<(usize, usize)>> = search
+                .map(|mut facets| {
+                    facets.sort_unstable();
+                    facets
+                })
+                .collect();
+
+            if progress.is_cancelled() {
+                progress.phase(FacetingPhase::Cancelled);
+                return output
+            }
+
+            progress.info(format!("{} facetings", output_facets.len()));
+
+            output_facets.sort_unstable();
+
+            if !include_compounds {
+                progress.info("\nFiltering mixed compounds...");
+                let output_idxs = filter_irc(&output_facets);
+                output_facets = output_idxs.into_iter().map(|idx| output_facets[idx].clone()).collect();
+            }
+
+            // Output the faceted polytopes. We will build them from their sets of facet orbits.
+
+            progress.info(format!("Found {} facetings", output_facets.len()));
+            progress.phase(FacetingPhase::Building);
+            let faceting_count = output_facets.len();
+            let mut used_facets = HashMap::new(); // used for outputting the facets at the end if `save_facets` is `true`.
+            let mut faceting_idx = 0; // We used to use `output.len()` but this doesn't work if you skip outputting the polytopes.
+            let mut interner = ElementInterner::new(rank); // shared by every faceting, since most of them share facets.
+
+            for facets in output_facets {
+                let mut facets_fmt = String::new();
+                for facet in &facets {
+                    facets_fmt.push_str(&format!(" ({},{})", facet.0, facet.1));
+                }
+
+                if progress.is_cancelled() {
+                    break
+                }
+
+                progress.step(faceting_idx, Some(faceting_count), || format!("Faceting {}", faceting_idx));
+
+                if (!save && !save_facets) || (!save && facets.iter().all(|facet| used_facets.contains_key(facet))) {
+                    progress.info(format!("Faceting {}:{}", faceting_idx, facets_fmt));
+
+                    faceting_idx += 1;
+                    continue
+                }
+
+                let (ranks, to_old_idx, starts) = interner.build(&facets, &candidates, &vertex_map);
+
+                let mut used_facets_current = Vec::new();
+                if save_facets {
+                    for (facet_orbit, start) in facets.iter().zip(starts) {
+                        if !used_facets.contains_key(facet_orbit) {
+                            used_facets_current.push((*facet_orbit, start));
+                        }
+                    }
+                }
+
+                if let Some(mut abs) = build_abstract(ranks) {
+                    let poly = Concrete {
+                        vertices: to_old_idx.into_iter().map(|i| vertices[i].clone()).collect(),
+                        abs: abs.clone(),
+                    };
+
+                    let mut fissary_status = "";
+                    if mark_fissary {
+                        abs.element_sort();
+
+                        if abs.is_compound() {
+                            fissary_status = " [C]";
+                        } else if poly.is_fissary() {
+                            fissary_status = " [F]";
+                        }
+                    }
+
+                    if save {
+                        let name = format!("faceting {}{}{}{}",
+                            if any_single_edge_length {edge_length_idx.to_string() + "."} else {"".to_string()},
+                            faceting_idx,
+                            if label_facets {" -".to_owned() + &facets_fmt.to_string()} else {"".to_string()},
+                            fissary_status
+                        );
+
+                        if save_to_file {
+                            let mut path = PathBuf::from(file_path);
+                            path.push(format!("{}.off", name));
+                            match poly.to_path(&path, Default::default()) {
+                                Err(why) => panic!("couldn't write to {}: {}", path.display(), why),
+                                Ok(_) => (),
+                            }
+                        } else {
+                            output.push((poly.clone(), Some(name)));
+                        }
+                    }
+
+                    if save_facets {
+                        for (orbit, idx) in used_facets_current {
+                            used_facets.insert(orbit, poly.facet(idx).unwrap());
+                        }
+                    }
+
+                    progress.info(format!("Faceting {}:{}{}", faceting_idx, facets_fmt, fissary_status));
+
+                    faceting_idx += 1;
+                }
+            }
+
+            if save_facets {
+                let mut used_facets_vec: Vec<(&(usize, usize), &Concrete)> = used_facets.iter().collect();
+                used_facets_vec.sort_by(|a,b| a.0.cmp(b.0));
+
+                for i in used_facets_vec {
+                    let mut poly = i.1.clone();
+                    poly.flatten();
+                    if let Some(sphere) = poly.circumsphere() {
+                        poly.recenter_with(&sphere.center);
+                    } else {
+                        poly.recenter();
+                    }
+                    if save_to_file {
+                        let mut path = PathBuf::from(file_path);
+                        path.push(format!("facet ({},{}).off", i.0.0, i.0.1));
+                        match poly.to_path(&path, Default::default()) {
+                            Err(why) => panic!("couldn't write to {}: {}", path.display(), why),
+                            Ok(_) => (),
+                        }
+                    } else {  
+                        output.push((poly, Some(format!("facet ({},{})", i.0.0, i.0.1))));
+                    }
+                }
+            }
+
+            if progress.is_cancelled() {
+                progress.phase(FacetingPhase::Cancelled);
+                return output
+            }
+
+            if any_single_edge_length {
+                edge_length_idx += 1;
+                if edge_length_idx < possible_lengths.len() {
+                    continue;
+                }
+            }
+
+            progress.phase(FacetingPhase::Complete);
+            return output
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the names and element counts of the facetings of a polytope
+    /// under its full symmetry, with compounds and fissary polytopes marked.
+    fn facetings(mut poly: Concrete, precision: Precision) -> Vec<(String, Vec<usize>)> {
+        let vertices = poly.vertices.clone();
+        let mut progress = ProgressReporter::callback(|_| {});
+
+        let options = FacetingOptions {
+            include_compounds: true,
+            mark_fissary: true,
+            label_facets: true,
+            save: true,
+            precision,
+            ..Default::default()
+        };
+
+        poly.faceting(vertices, GroupEnum::Chiral(false), &options, &mut progress)
+        .into_iter()
+        .map(|(poly, name)| (name.unwrap(), poly.el_count_iter().collect()))
+        .collect()
+    }
+
+    /// Checks the facetings of a polytope against a list of their names and
+    /// element counts. These pin down the output of the whole algorithm, so
+    /// that changes to any of its phases can't alter it unnoticed.
+    fn check(poly: Concrete, expected: &[(&str, &[usize])]) {
+        let found = facetings(poly, Precision::Double);
+        assert_eq!(found.len(), expected.len());
+
+        for ((name, counts), (expected_name, expected_counts)) in found.iter().zip(expected) {
+            assert_eq!(name, expected_name);
+            assert_eq!(counts.as_slice(), *expected_counts, "{}", name);
+        }
+    }
+
+    #[test]
+    fn cube() {
+        check(Concrete::cube(), &[
+            ("faceting 0 - (0,0)", &[1, 8, 12, 6, 1]),
+            ("faceting 1 - (0,0) (2,0) [C]", &[1, 8, 24, 14, 1]),
+            ("faceting 2 - (2,0) [C]", &[1, 8, 12, 8, 1]),
+        ]);
+    }
+
+    #[test]
+    fn octahedron() {
+        check(Concrete::octahedron(), &[
+            ("faceting 0 - (1,0)", &[1, 6, 12, 8, 1]),
+        ]);
+    }
+
+    #[test]
+    fn tesseract() {
+        check(Concrete::hypercube(5), &[
+            ("faceting 0 - (0,0)", &[1, 16, 32, 24, 8, 1]),
+            ("faceting 1 - (0,0) (0,2) (2,0) [C]", &[1, 16, 80, 136, 48, 1]),
+            ("faceting 2 - (0,0) (0,2) (2,1) (3,0) [C]", &[1, 16, 112, 232, 64, 1]),
+            ("faceting 3 - (0,0) (0,2) (2,1) (4,0) [C]", &[1, 16, 112, 232, 56, 1]),
+            ("faceting 4 - (0,0) (0,2) (2,2) [C]", &[1, 16, 112, 184, 48, 1]),
+            ("faceting 5 - (0,0) (0,2) (3,0) [C]", &[1, 16, 80, 88, 32, 1]),
+            ("faceting 6 - (0,0) (0,2) (4,0) [C]", &[1, 16, 80, 88, 24, 1]),
+            ("faceting 7 - (0,0) (0,2) (5,0) [C]", &[1, 16, 112, 184, 80, 1]),
+            ("faceting 8 - (0,0) (2,0) (3,0) [C]", &[1, 16, 80, 136, 56, 1]),
+            ("faceting 9 - (0,0) (2,0) (4,0) [C]", &[1, 16, 80, 136, 48, 1]),
+            ("faceting 10 - (0,0) (2,0) (5,0) [C]", &[1, 16, 112, 232, 104, 1]),
+            ("faceting 11 - (0,0) (2,1) [C]", &[1, 16, 112, 168, 40, 1]),
+            ("faceting 12 - (0,0) (2,1) (3,0) (4,0) [C]", &[1, 16, 112, 232, 64, 1]),
+            ("faceting 13 - (0,0) (2,2) (3,0) [C]", &[1, 16, 112, 184, 56, 1]),
+            ("faceting 14 - (0,0) (2,2) (4,0) [C]", &[1, 16, 112, 184, 48, 1]),
+            ("faceting 15 - (0,0) (3,0) (4,0) [C]", &[1, 16, 80, 88, 32, 1]),
+            ("faceting 16 - (0,0) (3,0) (5,0) [C]", &[1, 16, 112, 184, 88, 1]),
+            ("faceting 17 - (0,0) (4,0) (5,0) [C]", &[1, 16, 112, 184, 80, 1]),
+            ("faceting 18 - (0,2) (2,0) [C]", &[1, 16, 80, 112, 40, 1]),
+            ("faceting 19 - (0,2) (2,1) (3,0) [C]", &[1, 16, 112, 208, 56, 1]),
+            ("faceting 20 - (0,2) (2,1) (4,0) [C]", &[1, 16, 112, 208, 48, 1]),
+            ("faceting 21 - (0,2) (2,2)", &[1, 16, 80, 160, 40, 1]),
+            ("faceting 22 - (0,2) (3,0) [C]", &[1, 16, 48, 64, 24, 1]),
+            ("faceting 23 - (0,2) (4,0) [C]", &[1, 16, 48, 64, 16, 1]),
+            ("faceting 24 - (0,2) (5,0) [C]", &[1, 16, 80, 160, 72, 1]),
+            ("faceting 25 - (2,0) (3,0)", &[1, 16, 80, 112, 48, 1]),
+            ("faceting 26 - (2,0) (4,0)", &[1, 16, 80, 112, 40, 1]),
+            ("faceting 27 - (2,0) (5,0) [F]", &[1, 16, 112, 208, 96, 1]),
+            ("faceting 28 - (2,1)", &[1, 16, 112, 144, 32, 1]),
+            ("faceting 29 - (2,1) (3,0) (4,0) [C]", &[1, 16, 112, 208, 56, 1]),
+            ("faceting 30 - (2,2) (3,0) [F]", &[1, 16, 80, 160, 48, 1]),
+            ("faceting 31 - (2,2) (4,0)", &[1, 16, 80, 160, 40, 1]),
+            ("faceting 32 - (3,0) (4,0) [C]", &[1, 16, 48, 64, 24, 1]),
+            ("faceting 33 - (3,0) (5,0) [C]", &[1, 16, 80, 160, 80, 1]),
+            ("faceting 34 - (4,0) (5,0) [C]", &[1, 16, 80, 160, 72, 1]),
+        ]);
+    }
+
+    #[test]
+    fn sixteen_cell() {
+        check(Concrete::orthoplex(5), &[
+            ("faceting 0 - (1,0)", &[1, 8, 24, 32, 16, 1]),
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "big-float")]
+    fn big_float() {
+        for poly in [Concrete::cube(), Concrete::octahedron(), Concrete::orthoplex(5)] {
+            let expected = facetings(poly.clone(), Precision::Double);
+            assert_eq!(facetings(poly.clone(), Precision::Quadruple), expected);
+            assert_eq!(facetings(poly, Precision::Octuple), expected);
+        }
+    }
+}
@@ -0,0 +1,451 @@
+//! Builds polytopes out of sets of facet orbits.
+
+use std::collections::{HashMap, HashSet};
+
+use super::subdim::FacetCandidates;
+use crate::{
+    abs::{Abstract, AbstractBuilder, Element, ElementList, Ranks, Subelements, Superelements},
+    group::vertex_map::VertexMap,
+};
+
+use vec_like::*;
+
+/// Applies a row of the vertex map to a facet.
+fn map_facet(facet: &Ranks, local: &Ranks, row: &[usize]) -> Ranks {
+    let mut new_facet = facet.clone();
+
+    let mut new_list = ElementList::new();
+    for edge in &facet[2] {
+        let mut new = Element::new(Subelements::new(), Superelements::new());
+        for sub in &edge.subs {
+            new.subs.push(row[*sub])
+        }
+        new_list.push(new);
+    }
+    new_facet[2] = new_list;
+
+    new_facet.element_sort_strong_with_local(local);
+    new_facet
+}
+
+/// Returns the edges of a copy of a facet, sorted, so that copies of the same
+/// facet can be told apart without building them.
+fn mapped_edges(facet: &Ranks, row: &[usize]) -> ElementList {
+    let mut edges = ElementList::new();
+    for edge in &facet[2] {
+        let mut new = Element::new(Subelements::new(), Superelements::new());
+        for sub in &edge.subs {
+            new.subs.push(row[*sub])
+        }
+        new.subs.sort();
+        edges.push(new);
+    }
+    edges.0.sort_by(|a, b| a.subs.cmp(&b.subs));
+    edges
+}
+
+/// Interns the elements of the facets of every faceting found in a run. Most
+/// facetings share their facets with many others, so each copy of a facet is
+/// only mapped, sorted, and hashed the first time it's found.
+///
+/// Every element of rank `r` is given an id by its sorted subelements, as ids
+/// of rank `r - 1`, so that an element shared by different facets gets the
+/// same id in all of them. The ids of rank 1 are the global vertex indices.
+/// Building a faceting then only needs to renumber ids, with no hashing.
+pub(super) struct ElementInterner {
+    /// The rank of the facetings.
+    rank: usize,
+
+    /// The id of every element seen so far, by rank.
+    ids: Vec<HashMap<Subelements, usize>>,
+
+    /// The subelements of every element seen so far, by rank and id.
+    subs: Vec<Vec<Subelements>>,
+
+    /// The ids of the elements of every facet, from the vertices up to its
+    /// ridges, in the order they're first found in it.
+    facet_elements: Vec<Vec<Vec<usize>>>,
+
+    /// The ids of the distinct copies of every facet orbit found so far.
+    orbits: HashMap<(usize, usize), Vec<usize>>,
+
+    /// The index of every element in the faceting being built, by rank and
+    /// id. Unused entries are `usize::MAX`.
+    local: Vec<Vec<usize>>,
+}
+
+impl ElementInterner {
+    /// Initializes an empty interner for facetings of a given rank.
+    pub(super) fn new(rank: usize) -> Self {
+        Self {
+            rank,
+            ids: vec![HashMap::new(); rank],
+            subs: vec![Vec::new(); rank],
+            facet_elements: Vec::new(),
+            orbits: HashMap::new(),
+            local: vec![Vec::new(); rank],
+        }
+    }
+
+    /// Returns the id of an element of a given rank with some sorted
+    /// subelements, adding it if it's new.
+    fn intern(&mut self, rank: usize, subs: Subelements) -> usize {
+        let next = self.subs[rank].len();
+        *self.ids[rank].entry(subs).or_insert_with_key(|subs| {
+            self.subs[rank].push(subs.clone());
+            next
+        })
+    }
+
+    /// Returns the id of a facet whose edges use global vertex indices,
+    /// interning all of its elements.
+    pub(super) fn intern_facet(&mut self, facet: &Ranks) -> usize {
+        let mut elements = vec![Vec::new(); self.rank - 1];
+
+        let mut seen = HashSet::new();
+        for edge in &facet[2] {
+            for &v in &edge.subs {
+                if seen.insert(v) {
+                    elements[1].push(v);
+                }
+            }
+        }
+
+        // The ids of the elements of the previous rank, by their index in the
+        // facet.
+        let mut prev_ids: Vec<usize> = Vec::new();
+        for r in 2..self.rank {
+            let ids: Vec<usize> = facet[r]
+                .iter()
+                .map(|el| {
+                    let mut subs: Subelements = if r == 2 {
+                        el.subs.clone()
+                    } else {
+                        el.subs.iter().map(|&sub| prev_ids[sub]).collect()
+                    };
+                    subs.sort_unstable();
+                    self.intern(r, subs)
+                })
+                .collect();
+
+            if r < self.rank - 1 {
+                elements[r] = ids.clone();
+            }
+            prev_ids = ids;
+        }
+
+        let id = prev_ids[0];
+        if id == self.facet_elements.len() {
+            self.facet_elements.push(elements);
+        }
+        id
+    }
+
+    /// Returns the ids of the distinct copies of the facets in an orbit.
+    fn orbit(
+        &mut self,
+        facet: (usize, usize),
+        candidates: &FacetCandidates,
+        vertex_map: &VertexMap,
+    ) -> &[usize] {
+        if !self.orbits.contains_key(&facet) {
+            let candidate = &candidates.facets[facet.0][facet.1];
+            let mut of_this_orbit = HashSet::new();
+            let mut copies = Vec::new();
+
+            for row in vertex_map {
+                if of_this_orbit.insert(mapped_edges(&candidate.global, row)) {
+                    let new_facet = map_facet(&candidate.global, &candidate.local, row);
+                    copies.push(self.intern_facet(&new_facet));
+                }
+            }
+
+            self.orbits.insert(facet, copies);
+        }
+
+        &self.orbits[&facet]
+    }
+
+    /// Builds the [`Ranks`] of a faceting out of some facet orbits, with the
+    /// copies of each orbit in a row. We also return the global index of each
+    /// vertex, and the index at which each orbit starts.
+    pub(super) fn build(
+        &mut self,
+        facets: &[(usize, usize)],
+        candidates: &FacetCandidates,
+        vertex_map: &VertexMap,
+    ) -> (Ranks, Vec<usize>, Vec<usize>) {
+        let mut facet_ids = Vec::new();
+        let mut starts = Vec::new();
+        for &facet in facets {
+            starts.push(facet_ids.len());
+            facet_ids.extend_from_slice(self.orbit(facet, candidates, vertex_map));
+        }
+
+        let (ranks, to_old_idx) = self.build_ranks(&facet_ids);
+        (ranks, to_old_idx, starts)
+    }
+
+    /// Builds the [`Ranks`] of a faceting out of the ids of its facets. We
+    /// also return the global index of each vertex, in the order they're
+    /// first found in the facets.
+    pub(super) fn build_ranks(&mut self, facet_ids: &[usize]) -> (Ranks, Vec<usize>) {
+        let rank = self.rank;
+
+        // The ids of the elements of every rank, in the order they're first
+        // found.
+        let mut order = vec![Vec::new(); rank];
+        let mut add = |local: &mut Vec<Vec<usize>>, r: usize, id: usize| {
+            let local = &mut local[r];
+            if local.len() <= id {
+                local.resize(id + 1, usize::MAX);
+            }
+            if local[id] == usize::MAX {
+                local[id] = order[r].len();
+                order[r].push(id);
+            }
+        };
+
+        for &f in facet_ids {
+            add(&mut self.local, rank - 1, f);
+        }
+        for r in 1..rank - 1 {
+            for &f in facet_ids {
+                for &id in &self.facet_elements[f][r] {
+                    add(&mut self.local, r, id);
+                }
+            }
+        }
+
+        let mut ranks = Ranks::new();
+        ranks.push(vec![Element::new(vec![].into(), vec![].into())].into()); // nullitope
+        ranks.push(vec![Element::new(vec![0].into(), vec![].into()); order[1].len()].into()); // vertices
+
+        for (r, ids) in order.iter().enumerate().take(rank).skip(2) {
+            let mut new_rank = ElementList::new();
+            for &id in ids {
+                let subs = self.subs[r][id]
+                    .iter()
+                    .map(|&sub| self.local[r - 1][sub])
+                    .collect();
+                new_rank.push(Element::new(subs, Superelements::new()));
+            }
+            ranks.push(new_rank);
+        }
+
+        // body
+        let facet_count = order[rank - 1].len();
+        ranks.push(
+            vec![Element::new(
+                Subelements::from_iter(0..facet_count),
+                Superelements::new(),
+            )]
+            .into(),
+        );
+
+        // Clears the local indices for the next faceting.
+        for (local, ids) in self.local.iter_mut().zip(&order) {
+            for &id in ids {
+                local[id] = usize::MAX;
+            }
+        }
+
+        let to_old_idx = std::mem::take(&mut order[1]);
+        (ranks, to_old_idx)
+    }
+}
+
+/// Returns every distinct copy of the facets in some facet orbits.
+pub(super) fn distinct_facet_copies(
+    facets: &[(usize, usize)],
+    candidates: &FacetCandidates,
+    vertex_map: &VertexMap,
+) -> Vec<Ranks> {
+    let mut facet_set = HashSet::new();
+    let mut facet_vec = Vec::new();
+
+    for &(hp, f) in facets {
+        let candidate = &candidates.facets[hp][f];
+        for row in vertex_map {
+            let new_facet = map_facet(&candidate.global, &candidate.local, row);
+            if facet_set.insert(new_facet.clone()) {
+                facet_vec.push(new_facet);
+            }
+        }
+    }
+
+    facet_vec
+}
+
+/// Builds the [`Ranks`] of a polytope of a given rank out of the ranks of its
+/// facets, with vertex indices global.
+///
+/// If the vertex count is given, the polytope keeps every one of these
+/// vertices. Otherwise, only the vertices used by the facets are kept, and
+/// we also return the global index of each of them.
+pub(super) fn build_ranks(
+    mut facets: Vec<Ranks>,
+    rank: usize,
+    vertex_count: Option<usize>,
+) -> (Ranks, Vec<usize>) {
+    let mut ranks = Ranks::new();
+    ranks.push(vec![Element::new(vec![].into(), vec![].into())].into()); // nullitope
+
+    // vertices
+    let mut to_old_idx = Vec::new();
+    if let Some(count) = vertex_count {
+        to_old_idx = (0..count).collect();
+    } else {
+        let mut to_new_idx = HashMap::new();
+
+        for facet in &mut facets {
+            let mut new_list = ElementList::new();
+            for edge in &facet[2] {
+                let mut new = Element::new(Subelements::new(), Superelements::new());
+                for sub in &edge.subs {
+                    let idx = *to_new_idx.entry(*sub).or_insert_with(|| {
+                        to_old_idx.push(*sub);
+                        to_old_idx.len() - 1
+                    });
+                    new.subs.push(idx)
+                }
+                new_list.push(new);
+            }
+            facet[2] = new_list;
+        }
+    }
+    ranks.push(vec![Element::new(vec![0].into(), vec![].into()); to_old_idx.len()].into());
+
+    for r in 2..rank-1 { // edges and up
+        let mut subs_to_idx = HashMap::new();
+        let mut idx_to_subs = Vec::new();
+
+        for facet in &facets {
+            for el in &facet[r] {
+                if !subs_to_idx.contains_key(&el.subs) {
+                    subs_to_idx.insert(el.subs.clone(), idx_to_subs.len());
+                    idx_to_subs.push(el.subs.clone());
+                }
+            }
+        }
+        for facet in &mut facets {
+            let mut new_list = ElementList::new();
+            for el in &facet[r+1] {
+                let mut new = Element::new(Subelements::new(), Superelements::new());
+                for sub in &el.subs {
+                    new.subs.push(subs_to_idx[&facet[r][*sub].subs])
+                }
+                new_list.push(new);
+            }
+            facet[r+1] = new_list;
+        }
+
+        let mut new_rank = ElementList::new();
+        for el in idx_to_subs {
+            new_rank.push(Element::new(el, vec![].into()));
+        }
+        ranks.push(new_rank);
+    }
+
+    let mut new_rank = ElementList::new();
+    let mut set = HashSet::new();
+
+    for facet in &mut facets {
+        facet[rank-1][0].subs.sort();
+        let subs = facet[rank-1][0].subs.clone();
+        if set.insert(subs.clone()) {
+            new_rank.push(Element::new(subs, Superelements::new()));
+        }
+    }
+    let n_r_len = new_rank.len();
+    ranks.push(new_rank); // facets
+
+    ranks.push(vec![Element::new(Subelements::from_iter(0..n_r_len), Superelements::new())].into()); // body
+
+    (ranks, to_old_idx)
+}
+
+/// Builds an abstract polytope from its ranks, if it's valid. Every ridge of a
+/// faceting is shared by exactly two facets, so this fails only when some
+/// smaller section isn't dyadic.
+pub(super) fn build_abstract(ranks: Ranks) -> Option<Abstract> {
+    let mut builder = AbstractBuilder::new();
+    for rank in ranks {
+        builder.push_empty();
+        for el in rank {
+            let mut subs = el.subs;
+            subs.sort_unstable();
+            builder.push_subs(subs);
+        }
+    }
+
+    builder.build_checked().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Polytope, test};
+
+    /// Returns the facets of a polyhedron, with their edges using global
+    /// vertex indices.
+    fn facets(abs: &Abstract) -> Vec<Ranks> {
+        abs[3]
+            .iter()
+            .map(|face| {
+                let mut ranks = Ranks::new();
+                ranks.push(vec![Element::new(vec![].into(), vec![].into())].into());
+                ranks.push(
+                    vec![Element::new(vec![0].into(), vec![].into()); face.subs.len()].into(),
+                );
+
+                let mut edges = ElementList::new();
+                for &e in &face.subs {
+                    edges.push(Element::new(abs[2][e].subs.clone(), Superelements::new()));
+                }
+                ranks.push(edges);
+
+                let subs = Subelements::from_iter(0..face.subs.len());
+                ranks.push(vec![Element::new(subs, Superelements::new())].into());
+                ranks
+            })
+            .collect()
+    }
+
+    /// Builds a cube from its interned faces, and checks that the elements are
+    /// shared between facetings.
+    #[test]
+    fn cube() {
+        let cube = Abstract::cube();
+        let faces = facets(&cube);
+        let mut interner = ElementInterner::new(4);
+
+        let ids: Vec<usize> = faces.iter().map(|f| interner.intern_facet(f)).collect();
+        assert_eq!(ids, (0..6).collect::<Vec<_>>());
+        assert_eq!(interner.subs[2].len(), 12);
+
+        let (ranks, to_old_idx) = interner.build_ranks(&ids);
+        test(&build_abstract(ranks).unwrap(), [1, 8, 12, 6, 1]);
+        assert_eq!(to_old_idx.len(), 8);
+
+        // Interning a face again doesn't add any elements.
+        assert_eq!(interner.intern_facet(&faces[3]), 3);
+        assert_eq!(interner.subs[2].len(), 12);
+
+        // The same faces give the same polytope as before.
+        let (old_ranks, _) = build_ranks(faces, 4, None);
+        let (ranks, _) = interner.build_ranks(&ids);
+        for r in 0..=4 {
+            assert_eq!(ranks[r].len(), old_ranks[r].len());
+        }
+
+        // A single face uses its own vertices.
+        let (ranks, mut to_old_idx) = interner.build_ranks(&[0]);
+        assert_eq!(ranks[1].len(), 4);
+        assert_eq!(ranks[2].len(), 4);
+        let mut vertices = cube.element_vertices(3, 0).unwrap();
+        to_old_idx.sort_unstable();
+        vertices.sort_unstable();
+        assert_eq!(to_old_idx, vertices);
+    }
+}
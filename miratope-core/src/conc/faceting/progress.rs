@@ -0,0 +1,288 @@
+//! Reports how far along a faceting is, either to the standard output or to
+//! any other listener, like a progress bar in a GUI.
+
+use std::{
+    fmt::Display,
+    io::Write,
+    sync::mpsc::Sender,
+    time::{Duration, Instant},
+};
+
+use super::{CL, DELAY};
+use crate::cancel::CancellationToken;
+
+/// The phases of the faceting algorithm, in the order they run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FacetingPhase {
+    /// The symmetry group of the polytope is being computed.
+    Symmetry,
+
+    /// The vertices are being split into orbits.
+    Vertices,
+
+    /// The possible edge lengths are being computed.
+    EdgeLengths,
+
+    /// The hyperplanes through the vertices are being enumerated.
+    Hyperplanes,
+
+    /// Each hyperplane is being faceted.
+    Subfacetings,
+
+    /// The possible ridges are being split into orbits.
+    Ridges,
+
+    /// The facet orbits are being combined into facetings.
+    Combining,
+
+    /// The facetings are being built.
+    Building,
+
+    /// The faceting has finished.
+    Complete,
+
+    /// The faceting was cancelled before finishing.
+    Cancelled,
+}
+
+impl Display for FacetingPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Symmetry => "Computing symmetry group",
+            Self::Vertices => "Matching vertices",
+            Self::EdgeLengths => "Computing edge lengths",
+            Self::Hyperplanes => "Enumerating hyperplanes",
+            Self::Subfacetings => "Faceting hyperplanes",
+            Self::Ridges => "Computing ridges",
+            Self::Combining => "Combining",
+            Self::Building => "Building",
+            Self::Complete => "Faceting complete",
+            Self::Cancelled => "Faceting cancelled",
+        })
+    }
+}
+
+/// A snapshot of how far along a faceting is.
+#[derive(Clone, Debug)]
+pub struct FacetingProgress {
+    /// The phase the faceting is in.
+    pub phase: FacetingPhase,
+
+    /// How many steps of the phase are done.
+    pub done: usize,
+
+    /// How many steps the phase has in total, if known.
+    pub total: Option<usize>,
+
+    /// A description of the last thing that happened.
+    pub message: String,
+
+    /// The time spent in the current phase.
+    pub elapsed: Duration,
+}
+
+impl FacetingProgress {
+    /// Returns the fraction of the current phase that's done, if known.
+    pub fn fraction(&self) -> Option<f32> {
+        self.total
+            .filter(|&total| total > 0)
+            .map(|total| self.done as f32 / total as f32)
+    }
+
+    /// Estimates the time left in the current phase, assuming that every step
+    /// takes the same time.
+    pub fn eta(&self) -> Option<Duration> {
+        let total = self.total?;
+        if self.done == 0 || self.done > total {
+            return None;
+        }
+
+        Some(self.elapsed.mul_f64((total - self.done) as f64 / self.done as f64))
+    }
+}
+
+/// Where the progress reports go.
+enum Sink {
+    /// Reports are printed, overwriting the last line for each step.
+    Stdout,
+
+    /// Reports are passed to a function.
+    Callback(Box<dyn FnMut(&FacetingProgress) + Send>),
+}
+
+/// Sends reports on the progress of a faceting. Reports for the individual
+/// steps of a phase are throttled, so that they don't slow the faceting down.
+///
+/// The reporter also carries the token that can cancel the faceting, since it
+/// reaches all of its hot loops anyways.
+pub struct ProgressReporter {
+    /// Where the reports go.
+    sink: Sink,
+
+    /// The token that stops the faceting.
+    cancel: CancellationToken,
+
+    /// The last report.
+    progress: FacetingProgress,
+
+    /// When the current phase started.
+    phase_start: Instant,
+
+    /// When the last step was reported.
+    last_step: Instant,
+}
+
+impl Default for ProgressReporter {
+    fn default() -> Self {
+        Self::stdout()
+    }
+}
+
+impl ProgressReporter {
+    /// Initializes a reporter with a given sink.
+    fn new(sink: Sink) -> Self {
+        Self {
+            sink,
+            cancel: CancellationToken::new(),
+            progress: FacetingProgress {
+                phase: FacetingPhase::Symmetry,
+                done: 0,
+                total: None,
+                message: String::new(),
+                elapsed: Duration::ZERO,
+            },
+            phase_start: Instant::now(),
+            last_step: Instant::now(),
+        }
+    }
+
+    /// A reporter that prints to the standard output.
+    pub fn stdout() -> Self {
+        Self::new(Sink::Stdout)
+    }
+
+    /// A reporter that passes every report to a function.
+    pub fn callback(f: impl FnMut(&FacetingProgress) + Send + 'static) -> Self {
+        Self::new(Sink::Callback(Box::new(f)))
+    }
+
+    /// A reporter that sends every report through a channel. Reports are
+    /// silently dropped once the receiver hangs up.
+    pub fn channel(sender: Sender<FacetingProgress>) -> Self {
+        Self::callback(move |progress| {
+            let _ = sender.send(progress.clone());
+        })
+    }
+
+    /// Makes the faceting stop as soon as the token is cancelled. The
+    /// facetings built until then are still returned.
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Returns whether the faceting has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Returns the token that stops the faceting.
+    pub(super) fn token(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    /// Returns the last report.
+    pub fn progress(&self) -> &FacetingProgress {
+        &self.progress
+    }
+
+    /// Sends the current report.
+    fn send(&mut self, line: bool) {
+        self.progress.elapsed = self.phase_start.elapsed();
+
+        match &mut self.sink {
+            Sink::Stdout => {
+                if line {
+                    println!("{}{}", CL, self.progress.message);
+                } else {
+                    print!("{}{:.115}", CL, self.progress.message);
+                    std::io::stdout().flush().unwrap();
+                }
+            }
+            Sink::Callback(f) => f(&self.progress),
+        }
+    }
+
+    /// Starts a new phase of the faceting.
+    pub(super) fn phase(&mut self, phase: FacetingPhase) {
+        self.progress.phase = phase;
+        self.progress.done = 0;
+        self.progress.total = None;
+        self.progress.message = if matches!(phase, FacetingPhase::Complete | FacetingPhase::Cancelled) {
+            format!("\n{}\n", phase)
+        } else {
+            format!("\n{}...", phase)
+        };
+        self.phase_start = Instant::now();
+        self.send(true);
+    }
+
+    /// Reports a message that should always be shown.
+    pub(super) fn info(&mut self, message: impl Into<String>) {
+        self.progress.message = message.into();
+        self.send(true);
+    }
+
+    /// Records how many steps of the current phase are done, without sending
+    /// a report.
+    pub(super) fn count(&mut self, done: usize, total: Option<usize>) {
+        self.progress.done = done;
+        self.progress.total = total;
+    }
+
+    /// Reports a step of the current phase, if enough time has passed since
+    /// the last one. The message is only built if it's actually reported.
+    pub(super) fn step(
+        &mut self,
+        done: usize,
+        total: Option<usize>,
+        message: impl FnOnce() -> String,
+    ) {
+        self.count(done, total);
+
+        if self.last_step.elapsed().as_millis() > DELAY {
+            self.progress.message = message();
+            self.send(false);
+            self.last_step = Instant::now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    /// Checks that phases get sent through a channel, and that the time left
+    /// is extrapolated from the time taken so far.
+    #[test]
+    fn channel_reports() {
+        let (sender, receiver) = channel();
+        let mut reporter = ProgressReporter::channel(sender);
+        reporter.phase(FacetingPhase::Combining);
+        reporter.info("3 facetings");
+
+        let phases: Vec<FacetingPhase> = receiver.try_iter().map(|p| p.phase).collect();
+        assert_eq!(phases, vec![FacetingPhase::Combining; 2]);
+
+        let progress = FacetingProgress {
+            phase: FacetingPhase::Ridges,
+            done: 1,
+            total: Some(4),
+            message: String::new(),
+            elapsed: Duration::from_secs(2),
+        };
+        assert_eq!(progress.fraction(), Some(0.25));
+        assert_eq!(progress.eta(), Some(Duration::from_secs(6)));
+    }
+}
@@ -0,0 +1,209 @@
+//! Sorts the ridges of the candidate facets into orbits.
+
+use std::collections::{HashMap, HashSet};
+
+use super::{hyperplanes::HyperplaneOrbit, subdim::FacetCandidates, ProgressReporter};
+use crate::{
+    abs::{Element, ElementList, Ranks, Subelements, Superelements},
+    float::Float,
+    group::vertex_map::VertexMap,
+};
+
+use vec_like::*;
+
+/// An orbit of ridges under the symmetry group.
+pub(super) struct RidgeOrbit {
+    /// The number of ridges in the orbit.
+    pub count: usize,
+}
+
+/// The orbits of the ridges of every candidate facet.
+pub(super) struct RidgeOrbits {
+    /// For each hyperplane orbit, for each facet of a hyperplane, for each of
+    /// its ridges, the index of the ridge orbit it belongs to.
+    pub orbit_of: Vec<Vec<Vec<usize>>>,
+
+    /// The ridge orbits.
+    pub orbits: Vec<RidgeOrbit>,
+}
+
+/// Applies a map to the vertex indices of a ridge's edges.
+fn map_ridge(ridge: &Ranks, map: impl Fn(usize) -> usize) -> Ranks {
+    let mut new_ridge = ridge.clone();
+
+    let mut new_list = ElementList::new();
+    for edge in &ridge[2] {
+        let mut new = Element::new(Subelements::new(), Superelements::new());
+        for sub in &edge.subs {
+            new.subs.push(map(*sub))
+        }
+        new_list.push(new);
+    }
+    new_ridge[2] = new_list;
+
+    new_ridge.element_sort_strong();
+    new_ridge
+}
+
+impl RidgeOrbits {
+    /// Sorts the ridges into orbits, storing every ridge in a hash map. This is
+    /// fast, but takes memory proportional to the total number of ridges.
+    pub fn new<T: Float>(
+        candidates: &FacetCandidates,
+        hyperplane_orbits: &[HyperplaneOrbit<T>],
+        vertex_map: &VertexMap,
+    ) -> Self {
+        let mut orbit_of = Vec::new();
+        let mut orbits = Vec::new();
+        let mut ridge_orbits = HashMap::new();
+
+        for (hp_i, ridges_row) in candidates.ridges.iter().enumerate() {
+            let hp_v = &hyperplane_orbits[hp_i].vertices;
+            let mut r_i_o_row = Vec::new();
+
+            for ridges_row_row in ridges_row {
+                let mut r_i_o_row_row = Vec::new();
+
+                for ridge in ridges_row_row {
+                    let ridge = map_ridge(ridge, |v| hp_v[v]);
+
+                    match ridge_orbits.get(&ridge).copied() {
+                        Some(idx) => r_i_o_row_row.push(idx),
+                        None => {
+                            // adds all ridges with the same orbit to the map
+                            let orbit_idx = orbits.len();
+                            let mut count = 0;
+                            for row in vertex_map {
+                                let new_ridge = map_ridge(&ridge, |v| row[v]);
+                                if !ridge_orbits.contains_key(&new_ridge) {
+                                    ridge_orbits.insert(new_ridge, orbit_idx);
+                                    count += 1;
+                                }
+                            }
+                            r_i_o_row_row.push(orbit_idx);
+                            orbits.push(RidgeOrbit { count });
+                        }
+                    }
+                }
+                r_i_o_row.push(r_i_o_row_row);
+            }
+            orbit_of.push(r_i_o_row);
+        }
+
+        Self { orbit_of, orbits }
+    }
+
+    /// Sorts the ridges into orbits, storing only one representative per orbit.
+    /// This is slower than [`Self::new`], but uses much less memory.
+    pub fn new_compact<T: Float>(
+        candidates: &FacetCandidates,
+        hyperplane_orbits: &[HyperplaneOrbit<T>],
+        vertex_map: &VertexMap,
+        mut progress: Option<&mut ProgressReporter>,
+    ) -> Self {
+        let mut orbit_of = Vec::new();
+        let mut orbits = Vec::new();
+        let mut representatives = HashMap::new();
+
+        for (hp_i, ridges_row) in candidates.ridges.iter().enumerate() {
+            let hp_v = &hyperplane_orbits[hp_i].vertices;
+            let mut r_i_o_row = Vec::new();
+
+            for ridges_row_row in ridges_row {
+                let mut r_i_o_row_row = Vec::new();
+
+                for ridge in ridges_row_row {
+                    let ridge = map_ridge(ridge, |v| hp_v[v]);
+
+                    let found = vertex_map
+                        .iter()
+                        .find_map(|row| representatives.get(&map_ridge(&ridge, |v| row[v])).copied());
+
+                    match found {
+                        Some(idx) => r_i_o_row_row.push(idx),
+                        None => {
+                            // counts the ridges in the orbit
+                            let set: HashSet<Ranks> = vertex_map
+                                .iter()
+                                .map(|row| map_ridge(&ridge, |v| row[v]))
+                                .collect();
+
+                            let orbit_idx = orbits.len();
+                            representatives.insert(ridge, orbit_idx);
+                            r_i_o_row_row.push(orbit_idx);
+                            orbits.push(RidgeOrbit { count: set.len() });
+
+                            if let Some(progress) = progress.as_deref_mut() {
+                                // The caller discards the incomplete orbits.
+                                if progress.is_cancelled() {
+                                    return Self { orbit_of, orbits };
+                                }
+
+                                progress.step(hp_i, Some(hyperplane_orbits.len()), || {
+                                    format!("{}/{} hp, {} ridges", hp_i, hyperplane_orbits.len(), orbits.len())
+                                });
+                            }
+                        }
+                    }
+                }
+                r_i_o_row.push(r_i_o_row_row);
+            }
+            orbit_of.push(r_i_o_row);
+
+            if let Some(progress) = progress.as_deref_mut() {
+                progress.step(hp_i+1, Some(hyperplane_orbits.len()), || {
+                    format!("{}/{} hp, {} ridges", hp_i+1, hyperplane_orbits.len(), orbits.len())
+                });
+            }
+        }
+
+        Self { orbit_of, orbits }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        conc::{
+            faceting::{
+                hyperplanes::{edge_orbits, hyperplanes_through_edges, HyperplaneFilter},
+                subdim::facet_hyperplanes,
+            },
+            Concrete,
+        },
+        geometry::PointOrd,
+        group::stab_chain::StabChain,
+        Polytope,
+    };
+
+    /// Both ways of sorting the ridges of the candidate facets of a cube give
+    /// the same orbits: its edges, its face diagonals and its space diagonals.
+    #[test]
+    fn cube() {
+        let mut cube = Concrete::cube();
+        let vertex_map = cube.get_symmetry_group().unwrap().1;
+        let chain = StabChain::from_vertex_map(&vertex_map);
+        let points: Vec<_> = cube.vertices.iter().map(|v| PointOrd::new(v.clone())).collect();
+        let filter = HyperplaneFilter {
+            min_edge_length: None,
+            max_edge_length: None,
+            min_inradius: None,
+            max_inradius: None,
+            exclude_hemis: false,
+        };
+        let mut progress = ProgressReporter::callback(|_| {});
+
+        let pair_orbits = edge_orbits(&points, &chain.orbits(), &vertex_map, &None, &None, None);
+        let orbits = hyperplanes_through_edges(&cube.vertices, &pair_orbits, &chain, 4, &filter, &mut progress);
+        let candidates = facet_hyperplanes(&orbits, &points, &vertex_map, 4, &None, &None, None, false, None, None);
+
+        let ridges = RidgeOrbits::new(&candidates, &orbits, &vertex_map);
+        let compact = RidgeOrbits::new_compact(&candidates, &orbits, &vertex_map, Some(&mut progress));
+        assert_eq!(ridges.orbit_of, vec![vec![vec![0], vec![1]], vec![vec![0], vec![1], vec![2]], vec![vec![1]]]);
+        assert_eq!(ridges.orbits.iter().map(|orbit| orbit.count).collect::<Vec<_>>(), vec![12, 12, 4]);
+
+        assert_eq!(compact.orbit_of, ridges.orbit_of);
+        assert_eq!(compact.orbits.iter().map(|orbit| orbit.count).collect::<Vec<_>>(), vec![12, 12, 4]);
+    }
+}
@@ -0,0 +1,314 @@
+//! Re-instantiates a faceting on a slightly different vertex set, like one
+//! that's been re-optimized or snapped to exact coordinates, without running
+//! the whole enumeration again.
+//!
+//! The labels a faceting is output with depend on the order in which its
+//! hyperplanes and facets were found, which can change with the vertices. A
+//! [`FacetingSignature`] instead records the faceting by the vertex sets of its
+//! elements. Its vertices are matched to the closest new ones, and its facets
+//! are split into orbits under the new symmetry, which must map facets to
+//! facets.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use crate::{
+    abs::{Abstract, Ranked},
+    conc::Concrete,
+    geometry::{Point, Subspace},
+    group::vertex_map::VertexMap,
+};
+
+use vec_like::*;
+
+/// Any error encountered while re-instantiating a faceting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureError {
+    /// Two vertices of the faceting are closest to the same new vertex, there
+    /// are no new vertices, or the symmetry acts on a different number of them.
+    Matching,
+
+    /// The new symmetry maps the facet with a given index out of the faceting.
+    Symmetry(usize),
+
+    /// An element's vertices don't lie in a subspace of its rank anymore.
+    NotFlat {
+        /// The rank of the element.
+        rank: usize,
+
+        /// The index of the element.
+        idx: usize,
+    },
+}
+
+impl Display for SignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Matching => write!(f, "the vertices don't match the new ones"),
+            Self::Symmetry(idx) => write!(f, "facet {} isn't mapped to another facet", idx),
+            Self::NotFlat { rank, idx } => {
+                write!(f, "element {} of rank {} isn't flat anymore", idx, rank)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// The result of re-instantiating a faceting.
+pub type SignatureResult<T> = Result<T, SignatureError>;
+
+/// Returns the sorted vertices of every element of a polytope, rank by rank.
+fn element_vertex_sets(abs: &Abstract) -> Vec<Vec<Vec<usize>>> {
+    let mut sets = vec![vec![Vec::new()]];
+    if abs.rank() > 1 {
+        sets.push((0..abs.vertex_count()).map(|v| vec![v]).collect());
+    }
+
+    for r in 2..abs.rank() {
+        let rank_sets = abs[r]
+            .iter()
+            .map(|el| {
+                let mut vertices: Vec<usize> = el
+                    .subs
+                    .iter()
+                    .flat_map(|&sub| sets[r - 1][sub].iter().copied())
+                    .collect();
+                vertices.sort_unstable();
+                vertices.dedup();
+                vertices
+            })
+            .collect();
+        sets.push(rank_sets);
+    }
+
+    sets
+}
+
+/// The combinatorial structure of a faceting, which can be placed on any
+/// vertex set close to the one it was found on.
+#[derive(Clone)]
+pub struct FacetingSignature {
+    /// The vertices of the faceting.
+    vertices: Vec<Point<f64>>,
+
+    /// The abstract polytope of the faceting.
+    abs: Abstract,
+
+    /// The sorted vertices of every element, rank by rank.
+    vertex_sets: Vec<Vec<Vec<usize>>>,
+}
+
+impl FacetingSignature {
+    /// Records the structure of a faceting.
+    pub fn new(faceting: &Concrete) -> Self {
+        Self {
+            vertices: faceting.vertices.clone(),
+            abs: faceting.abs.clone(),
+            vertex_sets: element_vertex_sets(&faceting.abs),
+        }
+    }
+
+    /// Returns the sorted vertices of every facet of the faceting.
+    fn facets(&self) -> &[Vec<usize>] {
+        match self.abs.rank().checked_sub(1) {
+            Some(rank) if rank > 0 => &self.vertex_sets[rank],
+            _ => &[],
+        }
+    }
+
+    /// Matches every vertex of the faceting to the closest new vertex.
+    fn matching(
+        &self,
+        vertices: &[Point<f64>],
+        vertex_map: &VertexMap,
+    ) -> SignatureResult<Vec<usize>> {
+        if vertex_map.vertex_count() != vertices.len() {
+            return Err(SignatureError::Matching);
+        }
+
+        let mut used = vec![false; vertices.len()];
+        let mut matching = Vec::with_capacity(self.vertices.len());
+
+        for v in &self.vertices {
+            let closest = (0..vertices.len())
+                .min_by(|&i, &j| {
+                    (&vertices[i] - v)
+                        .norm_squared()
+                        .total_cmp(&(&vertices[j] - v).norm_squared())
+                })
+                .ok_or(SignatureError::Matching)?;
+
+            if std::mem::replace(&mut used[closest], true) {
+                return Err(SignatureError::Matching);
+            }
+            matching.push(closest);
+        }
+
+        Ok(matching)
+    }
+
+    /// Splits the facets into orbits under a group of permutations of the new
+    /// vertices, given the new vertex each of the faceting's vertices is
+    /// matched to. Orbits are listed by the first facet in them.
+    fn facet_orbits(
+        &self,
+        matching: &[usize],
+        vertex_map: &VertexMap,
+    ) -> SignatureResult<Vec<Vec<usize>>> {
+        let mut to_old = vec![None; vertex_map.vertex_count()];
+        for (old, &new) in matching.iter().enumerate() {
+            if let Some(entry) = to_old.get_mut(new) {
+                *entry = Some(old);
+            }
+        }
+
+        let facets = self.facets();
+        let index: HashMap<_, _> = facets
+            .iter()
+            .enumerate()
+            .map(|(idx, facet)| (facet.as_slice(), idx))
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut orbits = Vec::new();
+        for (idx, facet) in facets.iter().enumerate() {
+            if seen.contains(&idx) {
+                continue;
+            }
+
+            let mut orbit = Vec::new();
+            for row in vertex_map {
+                let image: Option<Vec<usize>> =
+                    facet.iter().map(|&v| to_old[row[matching[v]]]).collect();
+                let mut image = image.ok_or(SignatureError::Symmetry(idx))?;
+                image.sort_unstable();
+
+                let &image_idx = index
+                    .get(image.as_slice())
+                    .ok_or(SignatureError::Symmetry(idx))?;
+                if seen.insert(image_idx) {
+                    orbit.push(image_idx);
+                }
+            }
+
+            orbit.sort_unstable();
+            orbits.push(orbit);
+        }
+
+        Ok(orbits)
+    }
+
+    /// Returns the number of facet orbits of the faceting under a group of
+    /// permutations of some new vertices.
+    pub fn orbit_count(
+        &self,
+        vertices: &[Point<f64>],
+        vertex_map: &VertexMap,
+    ) -> SignatureResult<usize> {
+        let matching = self.matching(vertices, vertex_map)?;
+        Ok(self.facet_orbits(&matching, vertex_map)?.len())
+    }
+
+    /// Places the faceting on some new vertices with the given symmetry. Each
+    /// vertex of the faceting is moved to the closest new vertex, and we check
+    /// that the symmetry still maps facets to facets, and that every element
+    /// is still flat.
+    pub fn instantiate(
+        &self,
+        vertices: &[Point<f64>],
+        vertex_map: &VertexMap,
+    ) -> SignatureResult<Concrete> {
+        let matching = self.matching(vertices, vertex_map)?;
+        self.facet_orbits(&matching, vertex_map)?;
+
+        let new_vertices: Vec<Point<f64>> = matching.iter().map(|&v| vertices[v].clone()).collect();
+
+        // Every element of rank r must lie in a subspace of rank r - 1.
+        for (rank, sets) in self.vertex_sets.iter().enumerate().skip(2) {
+            for (idx, set) in sets.iter().enumerate() {
+                let subspace = Subspace::from_points(set.iter().map(|&v| &new_vertices[v]));
+                if subspace.rank() + 1 > rank {
+                    return Err(SignatureError::NotFlat { rank, idx });
+                }
+            }
+        }
+
+        Ok(Concrete::new(new_vertices, self.abs.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Polytope, test};
+
+    /// The alternate vertices of a cube, as a tetrahedron.
+    fn tetrahedron() -> Concrete {
+        let cube = Concrete::cube();
+        let vertices: Vec<_> = cube
+            .vertices
+            .iter()
+            .filter(|v| v.iter().product::<f64>() > 0.0)
+            .cloned()
+            .collect();
+        Concrete::convex_hull(&vertices).unwrap()
+    }
+
+    /// Places a faceting on scaled vertices.
+    #[test]
+    fn scaled() {
+        let mut cube = Concrete::cube();
+        let signature = FacetingSignature::new(&cube);
+
+        let vertices: Vec<_> = cube.vertices.iter().map(|v| v * 1.1).collect();
+        let vertex_map = cube.get_symmetry_group().unwrap().1;
+
+        assert_eq!(signature.orbit_count(&vertices, &vertex_map), Ok(1));
+        let new_cube = signature.instantiate(&vertices, &vertex_map).unwrap();
+        test(&new_cube, [1, 8, 12, 6, 1]);
+        assert_eq!(new_cube.vertices, vertices);
+    }
+
+    /// A tetrahedron is a faceting of the cube under its own symmetry, but not
+    /// under the symmetry of the cube.
+    #[test]
+    fn symmetry() {
+        let mut tet = tetrahedron();
+        let signature = FacetingSignature::new(&tet);
+        let vertex_map = tet.get_symmetry_group().unwrap().1;
+        assert_eq!(signature.orbit_count(&tet.vertices, &vertex_map), Ok(1));
+
+        let mut cube = Concrete::cube();
+        let vertex_map = cube.get_symmetry_group().unwrap().1;
+        assert!(matches!(
+            signature.instantiate(&cube.vertices, &vertex_map),
+            Err(SignatureError::Symmetry(_))
+        ));
+    }
+
+    /// Moving a single vertex bends the faces through it.
+    #[test]
+    fn not_flat() {
+        let cube = Concrete::cube();
+        let signature = FacetingSignature::new(&cube);
+
+        let mut vertices = cube.vertices.clone();
+        vertices[0][0] *= 1.1;
+        let vertex_map = VertexMap::identity(vertices.len());
+
+        assert_eq!(signature.orbit_count(&vertices, &vertex_map), Ok(6));
+        assert!(matches!(
+            signature.instantiate(&vertices, &vertex_map),
+            Err(SignatureError::NotFlat { rank: 3, .. })
+        ));
+        assert_eq!(
+            signature
+                .instantiate(&vertices[1..], &vertex_map)
+                .unwrap_err(),
+            SignatureError::Matching
+        );
+    }
+}
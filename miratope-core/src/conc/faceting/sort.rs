@@ -0,0 +1,168 @@
+//! Strong sorting of [`Ranks`], used to compare candidate ridges and facets.
+
+use std::collections::HashMap;
+
+use crate::abs::{Element, ElementList, Ranks, Subelements, Superelements};
+
+use vec_like::*;
+
+impl Ranks {
+    /// Sorts some stuff in a way that's useful for the faceting algorithm.
+    pub fn element_sort_strong(&mut self) {
+        for el in 0..self[2].len() {
+            self[2][el].subs.sort_unstable();
+        }
+
+        for rank in 2..self.len()-1 {
+            let mut all_subs = Vec::new();
+            for el in &self[rank] {
+                all_subs.push(el.subs.clone());
+            }
+            let mut sorted = all_subs.clone();
+            sorted.sort_unstable();
+
+            let mut perm = Vec::new();
+            for i in &all_subs {
+                perm.push(sorted.iter().position(|x| x == i).unwrap());
+            }
+
+            for i in 0..self[rank].len() {
+                self[rank][i].subs = sorted[i].clone();
+                self[rank][i].subs.sort_unstable();
+            }
+
+            let mut new_list = ElementList::new();
+            for i in 0..self[rank+1].len() {
+                let mut new = Element::new(Subelements::new(), Superelements::new());
+                for sub in &self[rank+1][i].subs {
+                    new.subs.push(perm[*sub]);
+                }
+                new.sort();
+                new_list.push(new);
+            }
+            self[rank+1] = new_list;
+        }
+    }
+
+    /// Sorts some stuff in a way that's useful for the faceting algorithm.
+    pub fn element_sort_strong_with_local(&mut self, local: &Ranks) {
+        for el in 0..self[2].len() {
+            self[2][el].subs.sort_unstable();
+        }
+
+        for rank in 2..self.len()-1 {
+            let mut all_subs = Vec::new();
+            for el in &self[rank] {
+                all_subs.push(el.subs.clone());
+            }
+            let mut sorted = all_subs.clone();
+            sorted.sort_unstable();
+
+            let mut perm = Vec::new();
+            for i in &all_subs {
+                perm.push(sorted.iter().position(|x| x == i).unwrap());
+            }
+
+            for i in 0..self[rank].len() {
+                self[rank][i].subs = sorted[i].clone();
+                self[rank][i].subs.sort_unstable();
+            }
+
+            let mut map_to_local = HashMap::new();
+
+            for i in 0..self[rank+1].len() {
+                for j in 0..self[rank+1][i].subs.len() {
+                    map_to_local.insert(self[rank+1][i].subs[j], local[rank+1][i].subs[j]);
+                }
+            }
+
+            let mut new_list = ElementList::new();
+            for i in 0..self[rank+1].len() {
+                let mut new = Element::new(Subelements::new(), Superelements::new());
+                for sub in &self[rank+1][i].subs {
+                    new.subs.push(perm[*map_to_local.get(sub).unwrap()]);
+                }
+                new.sort();
+                new_list.push(new);
+            }
+            self[rank+1] = new_list;
+        }
+    }
+
+    /*
+    /// Combines two `Ranks`. Only meant to be used in the faceting algorithm.
+    fn append(&mut self, other: &Ranks) {
+        let counts: Vec<usize> = self.iter().map(|x| x.len()).collect();
+
+        for r in 1..=2 {
+            for el in &other[r] {
+                self[r].push(el.clone());
+            }
+        }
+
+        for r in 3..self.rank() {
+            for el in &other[r] {
+                let mut new_el = el.clone();
+                for sub in &mut new_el.subs {
+                    *sub += counts[r-1];
+                }
+                for sup in &mut new_el.sups {
+                    *sup += counts[r+1];
+                }
+                self[r].push(new_el.clone());
+            }
+        }
+    }
+    */
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a square with its edges listed in a given order.
+    fn square(edges: [[usize; 2]; 4]) -> Ranks {
+        let el = |subs: Vec<usize>| Element::new(subs.into(), Superelements::new());
+
+        vec![
+            vec![el(vec![])].into(),
+            vec![el(vec![0]); 4].into(),
+            edges.iter().map(|e| el(e.to_vec())).collect::<Vec<_>>().into(),
+            vec![el(vec![0, 1, 2, 3])].into(),
+        ]
+        .into()
+    }
+
+    /// Returns the vertices of every edge.
+    fn edges(ranks: &Ranks) -> Vec<Vec<usize>> {
+        ranks[2].iter().map(|e| e.subs.iter().copied().collect()).collect()
+    }
+
+    /// Listing the edges in any order gives the same sorted square.
+    #[test]
+    fn square_order() {
+        let mut a = square([[0, 1], [1, 2], [2, 3], [3, 0]]);
+        let mut b = square([[2, 1], [0, 3], [3, 2], [1, 0]]);
+        a.element_sort_strong();
+        b.element_sort_strong();
+
+        assert_eq!(a, b);
+        assert_eq!(edges(&a), vec![vec![0, 1], vec![0, 3], vec![1, 2], vec![2, 3]]);
+    }
+
+    /// Relabeling the vertices of a square, as done with the vertices on a
+    /// hyperplane, doesn't change how its elements are sorted.
+    #[test]
+    fn square_with_local() {
+        let local = square([[2, 1], [0, 3], [3, 2], [1, 0]]);
+        let global = square([[7, 5], [3, 9], [9, 7], [5, 3]]);
+
+        let mut sorted = global.clone();
+        sorted.element_sort_strong();
+        let mut sorted_local = global;
+        sorted_local.element_sort_strong_with_local(&local);
+
+        assert_eq!(sorted, sorted_local);
+        assert_eq!(edges(&sorted), vec![vec![3, 5], vec![3, 9], vec![5, 7], vec![7, 9]]);
+    }
+}
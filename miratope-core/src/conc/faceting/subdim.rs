@@ -0,0 +1,542 @@
+//! Facets the hyperplanes of a polytope, by recursively faceting them as
+//! lower-dimensional polytopes.
+
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    combine::{label_irc, CombinationSearch},
+    hyperplanes::{edge_orbits, in_range, HyperplaneOrbit},
+    output::{build_abstract, build_ranks, distinct_facet_copies},
+    ridges::RidgeOrbits,
+    ProgressReporter,
+};
+use crate::{
+    abs::{Abstract, Element, ElementList, Ranked, Ranks},
+    conc::{Concrete, ConcretePolytope},
+    float::Float,
+    geometry::{PointOrd, Subspace},
+    group::vertex_map::VertexMap,
+    Polytope,
+};
+
+use vec_like::*;
+
+/// The data needed to only find noble facetings: every facet must be in the
+/// same orbit.
+#[derive(Clone, Copy)]
+pub(super) struct NoblePackage<'a> {
+    /// The vertex map of the whole polytope.
+    pub vertex_map: &'a VertexMap,
+
+    /// The global indices of the vertices on the hyperplane.
+    pub vertices: &'a [usize],
+
+    /// The number of hyperplanes in the orbit of the hyperplane.
+    pub count: usize,
+}
+
+/// A possible facet on a hyperplane.
+pub(super) struct FacetCandidate {
+    /// The facet, with vertex indices local to the hyperplane.
+    pub local: Ranks,
+
+    /// The facet, with vertex indices of the polytope being faceted.
+    pub global: Ranks,
+
+    /// The facet types of the facet, as pairs of hyperplane orbit and facet
+    /// indices one dimension down. These are ridges of the polytope.
+    pub ridges: Vec<(usize, usize)>,
+}
+
+/// The possible facets on every orbit of hyperplanes.
+pub(super) struct FacetCandidates {
+    /// For each hyperplane orbit, the possible facets on its representative.
+    pub facets: Vec<Vec<FacetCandidate>>,
+
+    /// For each hyperplane orbit, the map of compound facets to their
+    /// components, if these are among the facets.
+    pub compounds: Vec<HashMap<usize, Option<(usize, usize)>>>,
+
+    /// For each hyperplane orbit, the possible facets of each facet orbit of
+    /// the hyperplane, which are the possible ridges of the polytope.
+    pub ridges: Vec<Vec<Vec<Ranks>>>,
+
+    /// For each hyperplane orbit, the number of copies of each facet orbit of
+    /// the hyperplane.
+    pub ridge_counts: Vec<Vec<usize>>,
+}
+
+/// The facetings of a polytope in a subspace.
+pub(super) struct SubdimFacetings {
+    /// The facetings, along with the facet types of each of them.
+    pub facetings: Vec<(Ranks, Vec<(usize, usize)>)>,
+
+    /// The number of hyperplanes in each hyperplane orbit.
+    pub orbit_counts: Vec<usize>,
+
+    /// The possible facets of each hyperplane orbit. These will be the
+    /// possible ridges one dimension up.
+    pub facets: Vec<Vec<Ranks>>,
+
+    /// The map of compound facetings to their components, if these are among
+    /// the facetings.
+    pub compounds: HashMap<usize, Option<(usize, usize)>>,
+}
+
+/// Returns the facetings of a dyad. The only faceting of a dyad is itself,
+/// but we distinguish between snub and non-snub edges.
+fn dyad_facetings(vertex_map: &VertexMap) -> SubdimFacetings {
+    let vertex = |idx: usize| -> Ranks {
+        vec![
+            vec![].into(),
+            vec![
+                Element::new(vec![0].into(), vec![].into())
+                ].into(),
+            vec![
+                Element::new(vec![idx].into(), vec![].into())
+                ].into(),
+        ].into()
+    };
+
+    let snub = vertex_map.iter().all(|row| row[0] != 1);
+
+    if snub {
+        SubdimFacetings {
+            facetings: vec![(Abstract::dyad().ranks().clone(), vec![(0,0), (1,0)])],
+            orbit_counts: vec![1,1],
+            facets: vec![vec![vertex(0)], vec![vertex(1)]],
+            compounds: HashMap::new(),
+        }
+    } else {
+        SubdimFacetings {
+            facetings: vec![(Abstract::dyad().ranks().clone(), vec![(0,0)])],
+            orbit_counts: vec![2],
+            facets: vec![vec![vertex(0)]],
+            compounds: HashMap::new(),
+        }
+    }
+}
+
+/// Enumerates the orbits of hyperplanes of a polytope in a subspace, given in
+/// flattened coordinates. If doing a noble faceting, only the hyperplanes
+/// whose ridges can be shared by two facets are kept.
+fn subdim_hyperplanes<T: Float>(
+    rank: usize,
+    points: &[PointOrd<T>],
+    flat_points: &[PointOrd<T>],
+    vertex_map: &VertexMap,
+    min_edge_length: &Option<T>,
+    max_edge_length: &Option<T>,
+    noble_package: Option<NoblePackage<'_>>,
+) -> Vec<HyperplaneOrbit<T>> {
+    let total_vert_count = points.len();
+    let vertex_orbits = vertex_map.orbits();
+    let pair_orbits = edge_orbits(points, &vertex_orbits, vertex_map, min_edge_length, max_edge_length, None);
+
+    let mut hyperplane_orbits = Vec::new();
+    let mut checked = HashSet::<Vec<usize>>::new();
+
+    let mut noble_map = HashMap::<Vec<usize>, usize>::new();
+    let mut noble_counts = Vec::<usize>::new();
+    let mut noble_muls = Vec::<usize>::new();
+
+    for pair_orbit in pair_orbits {
+        let rep = &pair_orbit[0];
+
+        if rep[1]+rank-2 > total_vert_count {
+            continue;
+        }
+        let mut new_vertices: Vec<usize> = (rep[1]+1..rep[1]+rank-2).collect();
+        let mut update = if rank > 3 { rank-4 } else { 0 };
+
+        'b: loop {
+            'c: loop {
+                // WLOG checks if the vertices are all the right distance away from the first vertex.
+                for (v_i, v) in new_vertices.iter().enumerate() {
+                    let edge_length = (&points[*v].0 - &points[rep[0]].0).norm();
+                    if !in_range(&edge_length, min_edge_length, max_edge_length) {
+                        update = v_i;
+                        break 'c;
+                    }
+                }
+
+                // We start with a pair and add enough vertices to define a hyperplane.
+                let tuple = rep.iter().chain(&new_vertices);
+                let hyperplane = Subspace::from_points(tuple.map(|v| &flat_points[*v].0));
+                if !hyperplane.is_hyperplane() {
+                    break;
+                }
+
+                let mut hyperplane_vertices = Vec::new();
+                for (idx, v) in flat_points.iter().enumerate() {
+                    if hyperplane.distance(&v.0) < T::EPS {
+                        hyperplane_vertices.push(idx);
+                    }
+                }
+                hyperplane_vertices.sort_unstable();
+
+                // Check if the hyperplane has been found already.
+                if checked.contains(&hyperplane_vertices) {
+                    break;
+                }
+
+                // If it's new, we add all the ones in its orbit.
+                let mut representative = None;
+                let mut count = 0;
+                for row in vertex_map {
+                    let new_hp_v: Vec<usize> = hyperplane_vertices.iter().map(|idx| row[*idx]).collect();
+
+                    let mut sorted = new_hp_v.clone();
+                    sorted.sort_unstable();
+
+                    if !checked.contains(&sorted) {
+                        checked.insert(sorted);
+                        count += 1;
+
+                        if representative.is_none() {
+                            let new_hp = Subspace::from_points(new_hp_v.iter().map(|x| &flat_points[*x].0));
+                            representative = Some((new_hp, new_hp_v));
+                        }
+                    }
+                }
+
+                if let Some(noble) = noble_package {
+                    let mut global_hp_v: Vec<usize> = hyperplane_vertices.iter().map(|idx| noble.vertices[*idx]).collect();
+                    global_hp_v.sort_unstable();
+
+                    match noble_map.get(&global_hp_v).copied() {
+                        Some(idx) => {
+                            let mul = noble.count * count / noble_counts[idx];
+                            noble_muls[idx] += mul;
+                        },
+                        None => {
+                            let mut set = HashSet::new();
+                            for row in noble.vertex_map {
+                                let mut sorted: Vec<usize> = hyperplane_vertices.iter().map(|idx| row[noble.vertices[*idx]]).collect();
+                                sorted.sort_unstable();
+
+                                set.insert(sorted.clone());
+                                noble_map.insert(sorted, noble_counts.len());
+                            }
+
+                            let mul = noble.count * count / set.len();
+                            noble_counts.push(set.len());
+                            noble_muls.push(mul);
+                        },
+                    }
+                }
+
+                let (hyperplane, vertices) = representative.unwrap();
+                hyperplane_orbits.push(HyperplaneOrbit { hyperplane, vertices, count });
+                break;
+            }
+            if rank <= 3 {
+                break;
+            }
+            loop { // Increment new_vertices.
+                if new_vertices[update] == total_vert_count + update - rank + 3 {
+                    if update < 1 {
+                        break 'b;
+                    }
+                    else {
+                        update -= 1;
+                    }
+                } else {
+                    new_vertices[update] += 1;
+                    for i in update+1..rank-3 {
+                        new_vertices[i] = new_vertices[i-1]+1;
+                    }
+                    update = rank-4;
+                    break;
+                }
+            }
+        }
+    }
+
+    // Filter the invalid hyperplanes if noble faceting.
+    if let Some(noble) = noble_package {
+        hyperplane_orbits.retain(|orbit| {
+            let mut global_hp_v: Vec<usize> = orbit.vertices.iter().map(|x| noble.vertices[*x]).collect();
+            global_hp_v.sort_unstable();
+            noble_muls[noble_map[&global_hp_v]] >= 2
+        });
+    }
+
+    hyperplane_orbits
+}
+
+/// Facets the representative of every hyperplane orbit under its stabilizer.
+/// The possible ridges of the polytope are the facets of these facetings.
+pub(super) fn facet_hyperplanes<T: Float>(
+    hyperplane_orbits: &[HyperplaneOrbit<T>],
+    points: &[PointOrd<T>],
+    vertex_map: &VertexMap,
+    rank: usize,
+    min_edge_length: &Option<T>,
+    max_edge_length: &Option<T>,
+    max_per_hyperplane: Option<usize>,
+    uniform: bool,
+    noble_vertex_map: Option<&VertexMap>,
+    mut progress: Option<&mut ProgressReporter>,
+) -> FacetCandidates {
+    let mut candidates = FacetCandidates {
+        facets: Vec::new(),
+        compounds: Vec::new(),
+        ridges: Vec::new(),
+        ridge_counts: Vec::new(),
+    };
+
+    for (idx, orbit) in hyperplane_orbits.iter().enumerate() {
+        if progress.as_deref().is_some_and(ProgressReporter::is_cancelled) {
+            break;
+        }
+
+        let hp_v = &orbit.vertices;
+        let stabilizer = vertex_map.local_stabilizer(hp_v);
+        let hp_points = hp_v.iter().map(|v| points[*v].clone()).collect();
+
+        let noble_package = noble_vertex_map.map(|vertex_map| NoblePackage {
+            vertex_map,
+            vertices: hp_v,
+            count: orbit.count,
+        });
+
+        let subdim = faceting_subdim(
+            rank-1,
+            orbit.hyperplane.clone(),
+            hp_points,
+            stabilizer,
+            min_edge_length,
+            max_edge_length,
+            max_per_hyperplane,
+            uniform,
+            noble_package,
+            progress.as_deref_mut(),
+        );
+
+        let facets: Vec<FacetCandidate> = subdim.facetings
+            .into_iter()
+            .map(|(local, ridges)| {
+                let mut global = local.clone();
+                let mut new_edges = ElementList::new();
+                for edge in &local[2] {
+                    // Converts indices back to global
+                    let mut new_edge = Element::new(vec![].into(), vec![].into());
+                    for s in &edge.subs {
+                        new_edge.subs.push(hp_v[*s]);
+                    }
+                    new_edges.push(new_edge);
+                }
+                global[2] = new_edges;
+
+                FacetCandidate { local, global, ridges }
+            })
+            .collect();
+
+        if let Some(progress) = progress.as_deref_mut() {
+            progress.count(idx+1, Some(hyperplane_orbits.len()));
+            progress.info(format!("{}: {} facets, {} verts, {} copies", idx, facets.len(), hp_v.len(), orbit.count));
+        }
+
+        candidates.facets.push(facets);
+        candidates.compounds.push(subdim.compounds);
+        candidates.ridges.push(subdim.facets);
+        candidates.ridge_counts.push(subdim.orbit_counts);
+    }
+
+    candidates
+}
+
+/// Enumerates the facetings of a polytope of a given rank, whose vertices lie
+/// on a subspace. These are used as the facets one dimension up.
+pub(super) fn faceting_subdim<T: Float>(
+    rank: usize,
+    plane: Subspace<T>,
+    points: Vec<PointOrd<T>>,
+    vertex_map: VertexMap,
+    min_edge_length: &Option<T>,
+    max_edge_length: &Option<T>,
+    max_per_hyperplane: Option<usize>,
+    uniform: bool,
+    noble_package: Option<NoblePackage<'_>>,
+    progress: Option<&mut ProgressReporter>,
+) -> SubdimFacetings {
+    if rank == 2 {
+        return dyad_facetings(&vertex_map);
+    }
+
+    let total_vert_count = points.len();
+    let flat_points: Vec<PointOrd<T>> = points.iter().map(|p| PointOrd::new(plane.flatten(&p.0))).collect();
+
+    let hyperplane_orbits = subdim_hyperplanes(
+        rank,
+        &points,
+        &flat_points,
+        &vertex_map,
+        min_edge_length,
+        max_edge_length,
+        noble_package,
+    );
+
+    // Facet the hyperplanes
+    let candidates = facet_hyperplanes(
+        &hyperplane_orbits,
+        &flat_points,
+        &vertex_map,
+        rank,
+        min_edge_length,
+        max_edge_length,
+        max_per_hyperplane,
+        uniform,
+        None,
+        None,
+    );
+    let ridge_orbits = RidgeOrbits::new(&candidates, &hyperplane_orbits, &vertex_map);
+    let orbit_counts: Vec<usize> = hyperplane_orbits.iter().map(|orbit| orbit.count).collect();
+
+    // Actually do the faceting
+    let mut output = Vec::new();
+    let mut output_facets = Vec::new();
+
+    let mut search = CombinationSearch::new(&candidates, &orbit_counts, &ridge_orbits, noble_package.is_none(), None);
+    search.progress = progress.map(|progress| (progress, "facets"));
+
+    while let Some(new_facets) = search.next() {
+        // Output the faceted polytope. We will build it from the set of its facets.
+        let facet_vec = distinct_facet_copies(&new_facets, &candidates, &vertex_map);
+        let (ranks, _) = build_ranks(facet_vec.clone(), rank, Some(total_vert_count));
+
+        let isogonal = !uniform || {
+            let (ranks2, to_old_idx) = build_ranks(facet_vec, rank, None);
+            let abs = build_abstract(ranks2).expect("faceting is not dyadic");
+
+            let mut poly = Concrete {
+                vertices: to_old_idx.into_iter().map(|i| flat_points[i].0.map(|x| x.to_f64())).collect(),
+                abs,
+            };
+            poly.recenter();
+
+            poly.element_types()[1].len() <= 1 || {
+                poly.element_sort();
+                poly.defiss().iter().all(|component| component.element_types()[1].len() <= 1)
+            }
+        };
+
+        if isogonal {
+            output.push((ranks, new_facets.clone()));
+            output_facets.push(new_facets);
+        } else {
+            search.skipped += 1;
+        }
+
+        if let Some(max) = max_per_hyperplane {
+            if output.len() + search.skipped >= max {
+                break;
+            }
+        }
+    }
+
+    output.sort_by(|a,b| a.1.cmp(&b.1));
+    output_facets.sort_unstable();
+
+    SubdimFacetings {
+        facetings: output,
+        orbit_counts,
+        facets: candidates.facets
+            .into_iter()
+            .map(|row| row.into_iter().map(|candidate| candidate.global).collect())
+            .collect(),
+        compounds: label_irc(&output_facets),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        conc::faceting::hyperplanes::{edge_orbits, hyperplanes_through_edges, HyperplaneFilter},
+        group::stab_chain::StabChain,
+    };
+
+    /// The edges of a dyad are snub exactly when nothing swaps its vertices.
+    #[test]
+    fn dyad() {
+        let flip: VertexMap = vec![vec![0, 1], vec![1, 0]].into();
+        let subdim = dyad_facetings(&flip);
+        assert_eq!(subdim.facetings.len(), 1);
+        assert_eq!(subdim.facetings[0].1, vec![(0, 0)]);
+        assert_eq!(subdim.orbit_counts, vec![2]);
+
+        let subdim = dyad_facetings(&VertexMap::identity(2));
+        assert_eq!(subdim.facetings[0].1, vec![(0, 0), (1, 0)]);
+        assert_eq!(subdim.orbit_counts, vec![1, 1]);
+    }
+
+    /// A regular hexagon has the hexagon and the hexagram as facetings, from
+    /// the orbits of its sides and of its short diagonals.
+    #[test]
+    fn hexagon() {
+        let mut hexagon = Concrete::polygon(6);
+        let vertex_map = hexagon.get_symmetry_group().unwrap().1;
+        let plane = Subspace::from_points(hexagon.vertices.iter());
+        let points = hexagon.vertices.iter().map(|v| PointOrd::new(v.clone())).collect();
+
+        let subdim = faceting_subdim(3, plane, points, vertex_map, &None, &None, None, false, None, None);
+        assert_eq!(subdim.orbit_counts, vec![6, 6, 3]);
+        assert!(subdim.compounds.is_empty());
+
+        let facets: Vec<_> = subdim.facetings.iter().map(|(_, facets)| facets.clone()).collect();
+        assert_eq!(facets, vec![vec![(0, 0)], vec![(1, 0)]]);
+        for (ranks, _) in &subdim.facetings {
+            assert_eq!(ranks.iter().map(|els| els.len()).collect::<Vec<_>>(), vec![1, 6, 6, 1]);
+        }
+
+        // The long diagonals are possible facets, but are in no faceting.
+        assert_eq!(subdim.facets.iter().map(Vec::len).collect::<Vec<_>>(), vec![1, 1, 1]);
+    }
+
+    /// The candidate facets of a cube are its squares, its diagonal rectangles
+    /// with their two crossed variants, and the triangles below its vertices.
+    #[test]
+    fn cube_candidates() {
+        let mut cube = Concrete::cube();
+        let vertex_map = cube.get_symmetry_group().unwrap().1;
+        let chain = StabChain::from_vertex_map(&vertex_map);
+        let points: Vec<_> = cube.vertices.iter().map(|v| PointOrd::new(v.clone())).collect();
+        let filter = HyperplaneFilter {
+            min_edge_length: None,
+            max_edge_length: None,
+            min_inradius: None,
+            max_inradius: None,
+            exclude_hemis: false,
+        };
+        let mut progress = ProgressReporter::callback(|_| {});
+
+        let pair_orbits = edge_orbits(&points, &chain.orbits(), &vertex_map, &None, &None, None);
+        let orbits = hyperplanes_through_edges(&cube.vertices, &pair_orbits, &chain, 4, &filter, &mut progress);
+        assert_eq!(orbits.iter().map(|orbit| orbit.count).collect::<Vec<_>>(), vec![6, 6, 8]);
+
+        let candidates = facet_hyperplanes(&orbits, &points, &vertex_map, 4, &None, &None, None, false, None, None);
+        let ridges: Vec<Vec<_>> = candidates.facets
+            .iter()
+            .map(|row| row.iter().map(|candidate| candidate.ridges.clone()).collect())
+            .collect();
+        assert_eq!(ridges, vec![
+            vec![vec![(0, 0)]],
+            vec![vec![(0, 0), (1, 0)], vec![(0, 0), (2, 0)], vec![(1, 0), (2, 0)]],
+            vec![vec![(0, 0)]],
+        ]);
+        assert_eq!(candidates.ridge_counts, vec![vec![4, 2], vec![2, 2, 2], vec![3]]);
+
+        // The global facets are the local ones on the vertices of the hyperplane.
+        for (orbit, row) in orbits.iter().zip(&candidates.facets) {
+            for candidate in row {
+                assert_eq!(candidate.local[2].len(), candidate.global[2].len());
+                for (local, global) in candidate.local[2].iter().zip(&candidate.global[2]) {
+                    let mapped: Vec<usize> = local.subs.iter().map(|&v| orbit.vertices[v]).collect();
+                    assert_eq!(mapped, global.subs.iter().copied().collect::<Vec<_>>());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,506 @@
+//! Recognizes known polytopes by a fingerprint of their shape, so that the
+//! results of a faceting can be labeled with the names of the uniform
+//! polytopes among them.
+//!
+//! A fingerprint records the element counts of a polytope and of its facets,
+//! along with the lengths of its edges and the sizes of its polygons relative
+//! to its circumradius. This doesn't depend on the position, orientation, or scale of
+//! the polytope, and tells apart every uniform polytope we ship.
+//!
+//! A database of fingerprints is read from lines of the form
+//! `name<TAB>fingerprint` or `name<TAB>diagram`, where the diagram is a
+//! Coxeter diagram whose Wythoffian is fingerprinted. Empty lines and lines
+//! starting with `#` are skipped. A database can also be built from a folder
+//! of OFF files, named after the files.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    fs,
+    io::Error as IoError,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use super::{wythoff::WythoffError, Concrete};
+use crate::{
+    abs::Ranked,
+    file::{off::OffParseError, FromFile},
+    geometry::Point,
+    Polytope,
+};
+
+use vec_like::*;
+
+/// The fingerprints of the uniform polytopes in the library.
+const UNIFORMS: &str = include_str!("uniforms.txt");
+
+/// How many decimal places the lengths in a fingerprint are rounded to.
+const DIGITS: i32 = 4;
+
+/// Rounds a length to the precision of a fingerprint.
+fn round(x: f64) -> i64 {
+    (x * 10f64.powi(DIGITS)).round() as i64
+}
+
+/// Writes a rounded length as a decimal number.
+fn unround(x: i64) -> f64 {
+    x as f64 / 10f64.powi(DIGITS)
+}
+
+/// A summary of the shape of a polytope, which doesn't change under
+/// similarities. Two polytopes with the same fingerprint aren't necessarily
+/// the same, but in practice they almost always are.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fingerprint {
+    /// The number of elements of each rank.
+    counts: Vec<usize>,
+
+    /// The rounded lengths of the edges over the circumradius, with the number
+    /// of edges of each length.
+    edges: Vec<(i64, usize)>,
+
+    /// The polygons by their number of vertices, and their rounded
+    /// circumradius and perimeter over the circumradius of the polytope, with
+    /// the number of polygons of each kind.
+    polygons: Vec<((usize, i64, i64), usize)>,
+
+    /// The element counts of the facets, with the number of facets with each
+    /// of them. These are only recorded for polychora and up, as the polygons
+    /// already tell apart the faces of a polyhedron.
+    facets: Vec<(Vec<usize>, usize)>,
+}
+
+impl Fingerprint {
+    /// Returns the number of elements of each rank.
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts: Vec<String> = self.counts.iter().map(usize::to_string).collect();
+        write!(f, "{} |", counts.join(" "))?;
+
+        for &(len, count) in &self.edges {
+            write!(f, " {}*{}", unround(len), count)?;
+        }
+        write!(f, " |")?;
+
+        for &((n, radius, perimeter), count) in &self.polygons {
+            write!(f, " {}:{}:{}*{}", n, unround(radius), unround(perimeter), count)?;
+        }
+        write!(f, " |")?;
+
+        for (counts, count) in &self.facets {
+            let counts: Vec<String> = counts.iter().map(usize::to_string).collect();
+            write!(f, " {}*{}", counts.join(","), count)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a length and a count separated by `*`.
+fn parse_counted(s: &str) -> Option<(&str, usize)> {
+    let (value, count) = s.split_once('*')?;
+    Some((value, count.parse().ok()?))
+}
+
+/// Parses a length written in a fingerprint.
+fn parse_length(s: &str) -> Option<i64> {
+    Some(round(s.parse().ok()?))
+}
+
+impl FromStr for Fingerprint {
+    type Err = ();
+
+    /// Reads a fingerprint in the format it's displayed in.
+    fn from_str(s: &str) -> Result<Self, ()> {
+        let sections: Vec<&str> = s.split('|').collect();
+        let &[counts, edges, polygons, facets] = sections.as_slice() else {
+            return Err(());
+        };
+
+        let counts = counts
+            .split_whitespace()
+            .map(|count| count.parse().map_err(|_| ()))
+            .collect::<Result<_, _>>()?;
+
+        let edges = edges
+            .split_whitespace()
+            .map(|edge| {
+                let (len, count) = parse_counted(edge)?;
+                Some((parse_length(len)?, count))
+            })
+            .collect::<Option<_>>()
+            .ok_or(())?;
+
+        let polygons = polygons
+            .split_whitespace()
+            .map(|polygon| {
+                let (kind, count) = parse_counted(polygon)?;
+                let mut kind = kind.split(':');
+                let n = kind.next()?.parse().ok()?;
+                let radius = parse_length(kind.next()?)?;
+                let perimeter = parse_length(kind.next()?)?;
+                kind.next().is_none().then_some(((n, radius, perimeter), count))
+            })
+            .collect::<Option<_>>()
+            .ok_or(())?;
+
+        let facets = facets
+            .split_whitespace()
+            .map(|facet| {
+                let (counts, count) = parse_counted(facet)?;
+                let counts = counts.split(',').map(|n| n.parse().ok()).collect::<Option<_>>()?;
+                Some((counts, count))
+            })
+            .collect::<Option<_>>()
+            .ok_or(())?;
+
+        Ok(Self {
+            counts,
+            edges,
+            polygons,
+            facets,
+        })
+    }
+}
+
+/// Returns the largest distance from the average of some points to any of
+/// them.
+fn radius<'a, I: Iterator<Item = &'a Point<f64>> + Clone>(points: I) -> f64 {
+    let mut count = 0;
+    let mut center: Option<Point<f64>> = None;
+    for p in points.clone() {
+        count += 1;
+        center = Some(match center {
+            Some(c) => c + p,
+            None => p.clone(),
+        });
+    }
+
+    let Some(center) = center else {
+        return 0.0;
+    };
+    let center = center / count as f64;
+    points.map(|p| (p - &center).norm()).fold(0.0, f64::max)
+}
+
+/// Adds one to the count of a key.
+fn tally<K: Ord>(map: &mut BTreeMap<K, usize>, key: K) {
+    *map.entry(key).or_default() += 1;
+}
+
+impl Concrete {
+    /// Computes the fingerprint of the polytope.
+    pub fn fingerprint(&self) -> Fingerprint {
+        let counts = self.el_count_iter().collect();
+        let circumradius = radius(self.vertices.iter());
+
+        // A point has nothing to compare its lengths to.
+        let scale = if circumradius > 0.0 { circumradius.recip() } else { 0.0 };
+        let rank = self.rank();
+
+        let mut edges = BTreeMap::new();
+        if rank >= 2 {
+            for edge in self.ranks()[2].iter() {
+                let len = (&self.vertices[edge.subs[0]] - &self.vertices[edge.subs[1]]).norm();
+                tally(&mut edges, round(len * scale));
+            }
+        }
+
+        let mut polygons = BTreeMap::new();
+        if rank >= 3 {
+            for polygon in self.ranks()[3].iter() {
+                let mut vertices: Vec<usize> = polygon
+                    .subs
+                    .iter()
+                    .flat_map(|&edge| self.ranks()[2][edge].subs.iter().copied())
+                    .collect();
+                vertices.sort_unstable();
+                vertices.dedup();
+
+                let radius = radius(vertices.iter().map(|&v| &self.vertices[v]));
+                let perimeter: f64 = polygon
+                    .subs
+                    .iter()
+                    .map(|&edge| {
+                        let subs = &self.ranks()[2][edge].subs;
+                        (&self.vertices[subs[0]] - &self.vertices[subs[1]]).norm()
+                    })
+                    .sum();
+
+                tally(
+                    &mut polygons,
+                    (vertices.len(), round(radius * scale), round(perimeter * scale)),
+                );
+            }
+        }
+
+        let mut facets = BTreeMap::new();
+        if rank >= 5 {
+            for idx in 0..self.facet_count() {
+                if let Some(facet) = self.facet(idx) {
+                    tally(&mut facets, facet.el_count_iter().collect::<Vec<_>>());
+                }
+            }
+        }
+
+        Fingerprint {
+            counts,
+            edges: edges.into_iter().collect(),
+            polygons: polygons.into_iter().collect(),
+            facets: facets.into_iter().collect(),
+        }
+    }
+}
+
+/// Any error encountered while reading a fingerprint database.
+#[derive(Debug)]
+pub enum DatabaseError {
+    /// A line has no tab between the name and the fingerprint or diagram.
+    Format {
+        /// The line the error happened at, counting from 1.
+        line: usize,
+    },
+
+    /// A fingerprint couldn't be parsed.
+    Fingerprint {
+        /// The line the error happened at, counting from 1.
+        line: usize,
+    },
+
+    /// The Wythoffian of a diagram couldn't be built.
+    Wythoff {
+        /// The line the error happened at, counting from 1.
+        line: usize,
+
+        /// The error building the Wythoffian.
+        err: WythoffError,
+    },
+
+    /// An OFF file in a folder couldn't be read.
+    Off {
+        /// The path of the file.
+        path: PathBuf,
+
+        /// The error reading the file.
+        err: OffParseError,
+    },
+
+    /// There was a problem reading the file.
+    IoError(IoError),
+}
+
+impl From<IoError> for DatabaseError {
+    fn from(err: IoError) -> Self {
+        Self::IoError(err)
+    }
+}
+
+impl Display for DatabaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Format { line } => write!(f, "expected a name and a fingerprint at line {}", line),
+            Self::Fingerprint { line } => write!(f, "invalid fingerprint at line {}", line),
+            Self::Wythoff { line, err } => write!(f, "{} at line {}", err, line),
+            Self::Off { path, err } => write!(f, "{} in {}", err, path.display()),
+            Self::IoError(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for DatabaseError {}
+
+/// The result of reading a fingerprint database.
+pub type DatabaseResult<T> = Result<T, DatabaseError>;
+
+/// A database of named fingerprints.
+#[derive(Clone, Debug, Default)]
+pub struct FingerprintDb {
+    /// The names of the polytopes with each fingerprint.
+    entries: HashMap<Fingerprint, Vec<String>>,
+}
+
+impl FingerprintDb {
+    /// Returns the database of the uniform polyhedra and polychora in the
+    /// library, under the names of their files.
+    pub fn builtin() -> Self {
+        let mut db = Self::default();
+        db.parse(UNIFORMS).expect("the shipped database is valid");
+        db
+    }
+
+    /// Returns the number of distinct fingerprints in the database.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the database is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds a named fingerprint to the database.
+    pub fn insert(&mut self, fingerprint: Fingerprint, name: String) {
+        let names = self.entries.entry(fingerprint).or_default();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    /// Adds the entries of a database in text form, returning how many were
+    /// read.
+    pub fn parse(&mut self, src: &str) -> DatabaseResult<usize> {
+        let mut count = 0;
+
+        for (idx, line) in src.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (name, value) = line.split_once('\t').ok_or(DatabaseError::Format { line: idx + 1 })?;
+            let (name, value) = (name.trim(), value.trim());
+
+            // Diagrams never contain the separators of a fingerprint.
+            let fingerprint = if value.contains('|') {
+                value.parse().map_err(|_| DatabaseError::Fingerprint { line: idx + 1 })?
+            } else {
+                Concrete::parse_wythoff(value)
+                    .map_err(|err| DatabaseError::Wythoff { line: idx + 1, err })?
+                    .fingerprint()
+            };
+
+            self.insert(fingerprint, name.to_string());
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Adds the entries of a database file, returning how many were read.
+    pub fn load<P: AsRef<Path>>(&mut self, fp: P) -> DatabaseResult<usize> {
+        self.parse(&fs::read_to_string(fp)?)
+    }
+
+    /// Adds every OFF file in a folder and its subfolders, named after the
+    /// file, returning how many were read.
+    pub fn load_dir<P: AsRef<Path>>(&mut self, dir: P) -> DatabaseResult<usize> {
+        let mut count = 0;
+
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                count += self.load_dir(&path)?;
+            } else if path.extension().is_some_and(|ext| ext == "off") {
+                let poly = Concrete::from_off(&fs::read_to_string(&path)?)
+                    .map_err(|err| DatabaseError::Off { path: path.clone(), err })?;
+                let name = path.file_stem().unwrap_or_default().to_string_lossy();
+
+                self.insert(poly.fingerprint(), name.into_owned());
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Returns the names of the polytopes with some fingerprint, if any.
+    pub fn get(&self, fingerprint: &Fingerprint) -> Option<&[String]> {
+        self.entries.get(fingerprint).map(Vec::as_slice)
+    }
+
+    /// Returns the name a polytope is known by, or a list of the names it
+    /// might have if several polytopes have its fingerprint.
+    pub fn identify(&self, poly: &Concrete) -> Option<String> {
+        self.get(&poly.fingerprint()).map(|names| names.join(" / "))
+    }
+}
+
+impl Display for FingerprintDb {
+    /// Writes the database in the form it's read in, sorted by name.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines: Vec<_> = self
+            .entries
+            .iter()
+            .flat_map(|(fingerprint, names)| names.iter().map(move |name| (name, fingerprint)))
+            .collect();
+        lines.sort();
+
+        for (name, fingerprint) in lines {
+            writeln!(f, "{}\t{}", name, fingerprint)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conc::ConcretePolytope;
+
+    /// Fingerprints don't change under similarities, and can be read back.
+    #[test]
+    fn similarity() {
+        let cube = Concrete::cube();
+        let mut moved = cube.clone();
+        moved.scale(3.5);
+        moved.recenter_with(&Point::from_vec(vec![1.0, -2.0, 0.5]));
+
+        let fingerprint = cube.fingerprint();
+        assert_eq!(fingerprint, moved.fingerprint());
+        assert_eq!(fingerprint.counts(), [1, 8, 12, 6, 1]);
+        assert_eq!(fingerprint.to_string().parse(), Ok(fingerprint));
+    }
+
+    /// The shipped database recognizes the uniforms in the library, and no
+    /// other polytopes.
+    #[test]
+    fn builtin() {
+        let db = FingerprintDb::builtin();
+        assert_eq!(db.identify(&Concrete::cube()).as_deref(), Some("cube"));
+        assert_eq!(
+            db.identify(&Concrete::parse_wythoff("x5/2o5o").unwrap()).as_deref(),
+            Some("sissid")
+        );
+        assert_eq!(db.identify(&Concrete::parse_wythoff("x3o3x").unwrap()).as_deref(), Some("co"));
+        assert_eq!(db.identify(&Concrete::parse_wythoff("x4o x").unwrap()).as_deref(), Some("cube"));
+        assert_eq!(db.identify(&Concrete::parse_wythoff("x4o3x x").unwrap()), None);
+    }
+
+    /// The shipped fingerprints of the uniform polyhedra match the files in
+    /// the library.
+    #[test]
+    fn library() {
+        let mut db = FingerprintDb::default();
+        let count = db
+            .load_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/../lib/3D/uniform"))
+            .unwrap();
+        assert_eq!(count, 75);
+
+        let builtin = FingerprintDb::builtin();
+        for (fingerprint, names) in &db.entries {
+            assert_eq!(builtin.get(fingerprint), Some(names.as_slice()));
+        }
+    }
+
+    /// Reads entries given by their fingerprints, and reports bad lines.
+    #[test]
+    fn parse() {
+        let tet = Concrete::parse_wythoff("x3o3o").unwrap();
+        let mut db = FingerprintDb::default();
+        let src = format!("# A comment.\n\nTet\t{}\nTetrahedron\tx3o3o\n", tet.fingerprint());
+        assert_eq!(db.parse(&src).unwrap(), 2);
+        assert_eq!(db.len(), 1);
+        assert_eq!(db.identify(&tet).as_deref(), Some("Tet / Tetrahedron"));
+
+        assert!(matches!(db.parse("Tet x3o3o"), Err(DatabaseError::Format { line: 1 })));
+        assert!(matches!(db.parse("\nTet\t1 4 |"), Err(DatabaseError::Fingerprint { line: 2 })));
+        assert!(matches!(db.parse("Tet\tx4o4o"), Err(DatabaseError::Wythoff { line: 1, .. })));
+    }
+}
@@ -0,0 +1,322 @@
+//! Expresses a polytope in coordinate frames adapted to its symmetry, such as
+//! its principal axes or one of its rotation axes, and rotates it into a
+//! standard orientation.
+
+use std::{cmp::Reverse, f64::consts::TAU};
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::Ranked,
+    float::Float,
+    geometry::{Matrix, Point, Subspace, Vector},
+};
+
+/// The largest order of a rotation that we try to recognize.
+const MAX_ORDER: usize = 1000;
+
+/// A rotation axis of a polyhedron.
+#[derive(Clone, Debug)]
+pub struct RotationAxis {
+    /// A unit vector along the axis.
+    pub axis: Vector<f64>,
+
+    /// The order of the largest rotation around the axis.
+    pub order: usize,
+}
+
+/// Returns the axis of a 3D rotation matrix, if it's not the identity.
+fn rotation_axis(m: &Matrix<f64>) -> Option<Vector<f64>> {
+    // The identity has a trace of 3, and every other rotation less.
+    if m.trace() > 3.0 - f64::EPS.sqrt() {
+        return None;
+    }
+
+    // The antisymmetric part of a rotation is proportional to its axis,
+    // unless it's a half-turn.
+    let axis = Vector::from_vec(vec![
+        m[(2, 1)] - m[(1, 2)],
+        m[(0, 2)] - m[(2, 0)],
+        m[(1, 0)] - m[(0, 1)],
+    ]);
+    if axis.norm() > f64::EPS.sqrt() {
+        return Some(axis.normalize());
+    }
+
+    // A half-turn around `a` is `2aaᵀ - I`.
+    let plus_id = m + Matrix::identity(3, 3);
+    plus_id
+        .column_iter()
+        .map(|col| col.into_owned())
+        .max_by(|a, b| a.norm().total_cmp(&b.norm()))
+        .filter(|col| col.norm() > f64::EPS.sqrt())
+        .map(|col| col.normalize())
+}
+
+/// Returns the order of a rotation by some angle.
+fn rotation_order(angle: f64) -> Option<usize> {
+    (1..=MAX_ORDER).find(|&n| {
+        let turns = angle * n as f64 / TAU;
+        (turns - turns.round()).abs() < f64::EPS.sqrt()
+    })
+}
+
+/// Returns an orthonormal basis, as the columns of a matrix, whose last vector
+/// is along a given axis. If another direction is given, the first vector is
+/// along the part of it perpendicular to the axis. The rest of the basis is
+/// completed with the coordinate axes, and oriented so that it's not a
+/// reflection.
+pub fn frame_with_axis(axis: &Vector<f64>, secondary: Option<&Vector<f64>>) -> Matrix<f64> {
+    let dim = axis.len();
+    let mut basis: Vec<Vector<f64>> = Vec::with_capacity(dim);
+
+    /// Adds a vector to the basis, if it's not in the span of the others.
+    fn push(basis: &mut Vec<Vector<f64>>, mut v: Vector<f64>) {
+        for b in basis.iter() {
+            v -= b * b.dot(&v);
+        }
+
+        if v.norm() > f64::EPS.sqrt() {
+            basis.push(v.normalize());
+        }
+    }
+
+    push(&mut basis, axis.clone());
+    if let Some(secondary) = secondary {
+        push(&mut basis, secondary.clone());
+    }
+    for i in 0..dim {
+        let mut e = Vector::zeros(dim);
+        e[i] = 1.0;
+        push(&mut basis, e);
+    }
+
+    // The axis goes last, and the secondary direction first.
+    basis.rotate_left(1);
+    let mut frame = Matrix::from_columns(&basis);
+
+    // Rotating into the frame shouldn't reflect the polytope.
+    if dim >= 2 && frame.determinant() < 0.0 {
+        frame.column_mut(dim - 2).neg_mut();
+    }
+
+    frame
+}
+
+/// Returns the rotation that takes a unit vector to another, while fixing
+/// everything perpendicular to both of them. If the vectors are opposite, it
+/// instead turns by half a turn in a plane containing them.
+pub fn aligning_rotation(from: &Vector<f64>, to: &Vector<f64>) -> Matrix<f64> {
+    let dim = from.len();
+    let cos = from.dot(to).clamp(-1.0, 1.0);
+
+    // A unit vector perpendicular to `from` in the plane of the rotation.
+    let perp = (to - from * cos).try_normalize(f64::EPS).or_else(|| {
+        (cos < 0.0).then(|| {
+            let frame = frame_with_axis(from, None);
+            frame.column(0).into_owned()
+        })
+    });
+
+    let mut rotation = Matrix::identity(dim, dim);
+    let Some(perp) = perp else {
+        return rotation;
+    };
+
+    // The rotation acts on the plane spanned by `from` and `perp`.
+    let sin = (1.0 - cos * cos).sqrt();
+    rotation += (from * from.transpose() + &perp * perp.transpose()) * (cos - 1.0)
+        + (&perp * from.transpose() - from * perp.transpose()) * sin;
+    rotation
+}
+
+impl Concrete {
+    /// Rotates the polytope about the origin so that a given facet lies flat
+    /// at the bottom, like a die resting on a table. That is, the outward
+    /// normal of the facet is made to point along the negative direction of
+    /// the coordinate axis with the given index. Returns `false` if the facet
+    /// doesn't exist or doesn't span a hyperplane, or if the polytope isn't
+    /// full-dimensional.
+    pub fn rest_on_facet(&mut self, idx: usize, axis: usize) -> bool {
+        let Some(dim) = self.dim() else {
+            return false;
+        };
+        if self.rank() < 2 || axis >= dim {
+            return false;
+        }
+
+        let hull = Subspace::from_points(self.vertices.iter());
+        if hull.rank() != dim {
+            return false;
+        }
+
+        let Some(normal) = self
+            .gravicenter()
+            .and_then(|center| self.facet_normal(idx, &center, &hull))
+        else {
+            return false;
+        };
+
+        let mut down = Vector::zeros(dim);
+        down[axis] = -1.0;
+        let rotation = aligning_rotation(&normal, &down);
+        for v in &mut self.vertices {
+            *v = &rotation * &*v;
+        }
+
+        true
+    }
+
+    /// Returns the rotation axes of a polyhedron centered at the origin, in
+    /// decreasing order of their orders. Returns `None` if the polytope isn't
+    /// 3D, or if its symmetry group can't be computed.
+    pub fn rotation_axes(&self) -> Option<Vec<RotationAxis>> {
+        if self.dim() != Some(3) {
+            return None;
+        }
+
+        let (group, _) = self.clone().get_rotation_group()?;
+        let mut axes: Vec<RotationAxis> = Vec::new();
+
+        for m in group {
+            let Some(axis) = rotation_axis(&m) else {
+                continue;
+            };
+            let angle = ((m.trace() - 1.0) / 2.0).clamp(-1.0, 1.0).acos();
+            let Some(order) = rotation_order(angle) else {
+                continue;
+            };
+
+            // Every rotation around an axis is a power of one with the
+            // largest order.
+            match axes
+                .iter_mut()
+                .find(|other| other.axis.dot(&axis).abs() > 1.0 - f64::EPS.sqrt())
+            {
+                Some(other) => other.order = other.order.max(order),
+                None => axes.push(RotationAxis { axis, order }),
+            }
+        }
+
+        axes.sort_by_key(|a| Reverse(a.order));
+        Some(axes)
+    }
+
+    /// Returns the coordinates of the vertices in a frame, given by an
+    /// orthonormal basis as the columns of a matrix.
+    pub fn frame_coordinates(&self, frame: &Matrix<f64>) -> Vec<Point<f64>> {
+        let inverse = frame.transpose();
+        self.vertices.iter().map(|v| &inverse * v).collect()
+    }
+
+    /// Rotates the polytope so that a frame, given by an orthonormal basis as
+    /// the columns of a matrix, becomes the coordinate frame.
+    pub fn rotate_into_frame(&mut self, frame: &Matrix<f64>) {
+        self.vertices = self.frame_coordinates(frame);
+    }
+
+    /// Moves the gravicenter of a polyhedron to the origin and rotates it into
+    /// a standard orientation. The rotation axis of highest order becomes the
+    /// z-axis. The perpendicular rotation axis of highest order becomes the
+    /// x-axis, or failing that, the first vertex off the z-axis is moved to
+    /// the xz-plane.
+    ///
+    /// Polytopes that aren't 3D, or that have no rotation axes, get their
+    /// principal axes aligned instead. Returns `false` if neither can be done.
+    pub fn standard_orientation(&mut self) -> bool {
+        self.recenter();
+
+        let axes = self.rotation_axes().unwrap_or_default();
+        let Some(main) = axes.first() else {
+            return self.align_principal_axes();
+        };
+
+        let secondary = axes
+            .iter()
+            .find(|other| other.axis.dot(&main.axis).abs() < f64::EPS.sqrt())
+            .map(|other| other.axis.clone())
+            .or_else(|| {
+                self.vertices
+                    .iter()
+                    .find(|&v| (v - &main.axis * main.axis.dot(v)).norm() > f64::EPS.sqrt())
+                    .cloned()
+            });
+
+        let frame = frame_with_axis(&main.axis, secondary.as_ref());
+        self.rotate_into_frame(&frame);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+    use approx::assert_abs_diff_eq;
+
+    /// Rotates a polytope by a fixed, generic rotation.
+    fn rotated(mut poly: Concrete) -> Concrete {
+        let (s, c) = 0.7f64.sin_cos();
+        let (s2, c2) = 0.3f64.sin_cos();
+        let rot = Matrix::from_row_slice(3, 3, &[c, -s, 0.0, s, c, 0.0, 0.0, 0.0, 1.0])
+            * Matrix::from_row_slice(3, 3, &[1.0, 0.0, 0.0, 0.0, c2, -s2, 0.0, s2, c2]);
+
+        for v in &mut poly.vertices {
+            *v = &rot * &*v;
+        }
+        poly
+    }
+
+    /// The cube has 3 fourfold, 4 threefold and 6 twofold axes.
+    #[test]
+    fn cube_axes() {
+        let axes = rotated(Concrete::cube()).rotation_axes().unwrap();
+        let orders: Vec<usize> = axes.iter().map(|axis| axis.order).collect();
+        assert_eq!(orders, [vec![4; 3], vec![3; 4], vec![2; 6]].concat());
+    }
+
+    /// A rotated cube goes back to having its faces along the axes.
+    #[test]
+    fn cube_orientation() {
+        let mut cube = rotated(Concrete::cube());
+        assert!(cube.standard_orientation());
+
+        for v in &cube.vertices {
+            for &c in v.iter() {
+                assert_abs_diff_eq!(c.abs(), 0.5, epsilon = 1e-9);
+            }
+        }
+    }
+
+    /// Rotations take a vector to another, even an opposite one.
+    #[test]
+    fn aligning() {
+        let from = Vector::from_vec(vec![0.6, 0.0, 0.8]);
+        for to in [
+            Vector::from_vec(vec![0.0, 1.0, 0.0]),
+            from.clone(),
+            -from.clone(),
+        ] {
+            let rotation = aligning_rotation(&from, &to);
+            assert_abs_diff_eq!((&rotation * &from - &to).norm(), 0.0, epsilon = 1e-9);
+            assert_abs_diff_eq!(rotation.determinant(), 1.0, epsilon = 1e-9);
+        }
+    }
+
+    /// A rotated cube can rest on any of its faces.
+    #[test]
+    fn rest_on_facet() {
+        for idx in 0..6 {
+            let mut cube = rotated(Concrete::cube());
+            assert!(cube.rest_on_facet(idx, 1));
+
+            let facet = cube.abs.element_vertices(3, idx).unwrap();
+            for (v, vertex) in cube.vertices.iter().enumerate() {
+                let height = if facet.contains(&v) { -0.5 } else { 0.5 };
+                assert_abs_diff_eq!(vertex[1], height, epsilon = 1e-9);
+            }
+        }
+
+        assert!(!Concrete::cube().rest_on_facet(6, 1));
+        assert!(!Concrete::cube().rest_on_facet(0, 3));
+    }
+}
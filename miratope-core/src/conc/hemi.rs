@@ -0,0 +1,325 @@
+//! Detects hemi facets, that is, facets whose hyperplanes pass through the
+//! center of the polytope. Many operations, like reciprocation, fail on these.
+//!
+//! There's two ways around this. The dual can be taken in projective space,
+//! where every hemi facet becomes a vertex at infinity, or the center of
+//! reciprocation can be moved slightly off of the hemi facets.
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    DualError,
+    abs::{Abstract, Ranked},
+    float::Float,
+    geometry::{Hypersphere, Point, Subspace, Vector},
+};
+
+use rayon::prelude::*;
+
+impl Concrete {
+    /// Returns the indices of the facets whose hyperplanes pass through a
+    /// given center, in increasing order.
+    pub fn hemi_facets_with(&self, center: &Point<f64>) -> Vec<usize> {
+        let rank = self.rank();
+        if rank < 2 {
+            return Vec::new();
+        }
+
+        // We project the center onto the polytope's hyperplane to avoid skew
+        // weirdness, just like when reciprocating.
+        let o = Subspace::from_points(self.vertices.iter()).project(center);
+
+        (0..self.facet_count())
+            .into_par_iter()
+            .filter(|&idx| {
+                let subspace = Subspace::from_points(
+                    self.element_vertices_ref(rank - 1, idx)
+                        .unwrap()
+                        .into_iter(),
+                );
+                (subspace.project(&o) - &o).norm() < f64::EPS
+            })
+            .collect()
+    }
+
+    /// Returns the indices of the facets whose hyperplanes pass through the
+    /// gravicenter of the polytope, in increasing order.
+    pub fn hemi_facets(&self) -> Vec<usize> {
+        match self.gravicenter() {
+            Some(center) => self.hemi_facets_with(&center),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns whether the polytope is a hemi-polytope, that is, whether any
+    /// of its facets pass through its gravicenter.
+    pub fn is_hemi(&self) -> bool {
+        !self.hemi_facets().is_empty()
+    }
+
+    /// Returns the dual of a polytope with a given reciprocation sphere, in
+    /// projective space. The facets through the center of the sphere become
+    /// vertices at infinity, in the direction of their normals. Returns an
+    /// error if such a facet doesn't span a hyperplane, as it then has no
+    /// normal.
+    pub fn projective_dual_with(
+        &self,
+        sphere: &Hypersphere<f64>,
+    ) -> Result<ProjectiveDual, DualError> {
+        let rank = self.rank();
+        if rank < 2 {
+            return Ok(ProjectiveDual {
+                vertices: self
+                    .vertices
+                    .iter()
+                    .cloned()
+                    .map(ProjectivePoint::Finite)
+                    .collect(),
+                abs: self.abs.dual(),
+            });
+        }
+
+        // We project the sphere's center onto the polytope's hyperplane to
+        // avoid skew weirdness, just like when reciprocating.
+        let hull = Subspace::from_points(self.vertices.iter());
+        let o = hull.project(&sphere.center);
+
+        let vertices = (0..self.facet_count())
+            .map(|idx| {
+                let facet = Subspace::from_points(
+                    self.element_vertices_ref(rank - 1, idx)
+                        .unwrap()
+                        .into_iter(),
+                );
+                let mut p = facet.project(&o);
+
+                if sphere.reciprocate_mut(&mut p) {
+                    Ok(ProjectivePoint::Finite(p))
+                } else {
+                    self.facet_normal(idx, &o, &hull)
+                        .map(ProjectivePoint::Ideal)
+                        .ok_or(DualError(idx))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(ProjectiveDual {
+            vertices,
+            abs: self.abs.dual(),
+        })
+    }
+
+    /// Returns the dual of a polytope with the unit hypersphere, in projective
+    /// space. See [`Self::projective_dual_with`].
+    pub fn projective_dual(&self) -> Result<ProjectiveDual, DualError> {
+        self.projective_dual_with(&Hypersphere::unit(self.dim().unwrap_or(1)))
+    }
+
+    /// Returns a point close to a given center, but off the hyperplanes of
+    /// every facet, so that the dual with a sphere centered there exists. If
+    /// the center already works, it's returned projected onto the polytope's
+    /// hyperplane. Otherwise, it's moved off the hemi facets halfway towards
+    /// the closest hyperplane of any other facet.
+    ///
+    /// Returns `None` if some facet doesn't span a hyperplane, or if no
+    /// direction moves the center off of every hemi facet.
+    pub fn reciprocation_center_near(&self, center: &Point<f64>) -> Option<Point<f64>> {
+        let rank = self.rank();
+        let hull = Subspace::from_points(self.vertices.iter());
+        let o = hull.project(center);
+        if rank < 2 {
+            return Some(o);
+        }
+
+        // The normal of every facet, together with the distance from the
+        // center to its hyperplane along it.
+        let planes: Vec<(Vector<f64>, f64)> = (0..self.facet_count())
+            .map(|idx| {
+                let normal = self.facet_normal(idx, &o, &hull)?;
+                let v = self.abs.element_vertices(rank - 1, idx)?[0];
+                let dist = normal.dot(&(&self.vertices[v] - &o));
+                Some((normal, dist))
+            })
+            .collect::<Option<_>>()?;
+
+        let (hemi, other): (Vec<_>, Vec<_>) = planes
+            .into_iter()
+            .partition(|(_, dist)| dist.abs() < f64::EPS);
+        if hemi.is_empty() {
+            return Some(o);
+        }
+
+        // We try a few directions within the hull, and take the one that
+        // moves away from the hemi facets the fastest.
+        let dims = hull.basis.len();
+        let weighted = |weight: fn(usize) -> f64| {
+            hull.basis
+                .iter()
+                .enumerate()
+                .map(|(i, b)| b * weight(i))
+                .sum::<Vector<f64>>()
+                .normalize()
+        };
+        let (direction, speed) = hull
+            .basis
+            .iter()
+            .cloned()
+            .chain((dims > 1).then(|| weighted(|i| (i + 1) as f64)))
+            .chain((dims > 1).then(|| weighted(|i| if i % 2 == 0 { 1.0 } else { -1.0 })))
+            .map(|u| {
+                let speed = hemi
+                    .iter()
+                    .map(|(normal, _)| normal.dot(&u).abs())
+                    .fold(f64::INFINITY, f64::min);
+                (u, speed)
+            })
+            .max_by(|(_, s), (_, t)| s.total_cmp(t))?;
+
+        if speed < f64::EPS {
+            return None;
+        }
+
+        // How far we can move before reaching the hyperplane of another facet.
+        let step = other
+            .iter()
+            .filter_map(|(normal, dist)| {
+                let t = dist / normal.dot(&direction);
+                (t.is_finite() && t > 0.0).then_some(t)
+            })
+            .fold(f64::INFINITY, f64::min);
+        let step = if step.is_finite() {
+            step / 2.0
+        } else {
+            // Nothing stops us, so we move as far as the farthest vertex.
+            self.vertices
+                .iter()
+                .map(|v| (v - &o).norm())
+                .fold(0.0, f64::max)
+        };
+
+        Some(o + direction * step)
+    }
+
+    /// Returns a point close to the gravicenter that works as a center of
+    /// reciprocation. See [`Self::reciprocation_center_near`].
+    pub fn reciprocation_center(&self) -> Option<Point<f64>> {
+        self.reciprocation_center_near(&self.gravicenter()?)
+    }
+}
+
+/// A point in projective space, which may lie at infinity.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProjectivePoint {
+    /// An ordinary point.
+    Finite(Point<f64>),
+
+    /// A point at infinity, in the direction of a unit vector. The opposite
+    /// vector gives the same point.
+    Ideal(Vector<f64>),
+}
+
+impl ProjectivePoint {
+    /// Returns whether the point is at infinity.
+    pub fn is_ideal(&self) -> bool {
+        matches!(self, Self::Ideal(_))
+    }
+}
+
+/// The dual of a polytope in projective space, where the facets through the
+/// center of reciprocation become vertices at infinity. Even though it can't
+/// be drawn, its combinatorics and the directions of its infinite vertices can
+/// still be studied.
+#[derive(Clone, Debug)]
+pub struct ProjectiveDual {
+    /// The vertices of the dual, one for every facet of the original polytope.
+    pub vertices: Vec<ProjectivePoint>,
+
+    /// The underlying abstract polytope.
+    pub abs: Abstract,
+}
+
+impl ProjectiveDual {
+    /// Returns the indices of the vertices at infinity.
+    pub fn ideal_vertices(&self) -> Vec<usize> {
+        (0..self.vertices.len())
+            .filter(|&idx| self.vertices[idx].is_ideal())
+            .collect()
+    }
+
+    /// Returns the dual as a concrete polytope, or `None` if it has any
+    /// vertices at infinity.
+    pub fn into_concrete(self) -> Option<Concrete> {
+        let vertices = self
+            .vertices
+            .into_iter()
+            .map(|v| match v {
+                ProjectivePoint::Finite(p) => Some(p),
+                ProjectivePoint::Ideal(_) => None,
+            })
+            .collect::<Option<_>>()?;
+
+        Some(Concrete::new(vertices, self.abs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+
+    /// A cube has no hemi facets.
+    #[test]
+    fn cube() {
+        assert!(!Concrete::cube().is_hemi());
+    }
+
+    /// A quadrilateral with two of its edges through its center.
+    #[test]
+    fn bowtie() {
+        let mut bowtie = Concrete::polygon(4);
+        bowtie.vertices = vec![
+            Point::from_vec(vec![1.0, 0.0]),
+            Point::from_vec(vec![-1.0, 0.0]),
+            Point::from_vec(vec![0.0, 1.0]),
+            Point::from_vec(vec![0.0, -1.0]),
+        ];
+
+        assert_eq!(bowtie.hemi_facets().len(), 2);
+        assert!(bowtie.is_hemi());
+    }
+
+    /// The same quadrilateral, whose dual has two vertices at infinity, and
+    /// exists once the center is moved.
+    #[test]
+    fn bowtie_dual() {
+        let mut bowtie = Concrete::polygon(4);
+        bowtie.vertices = vec![
+            Point::from_vec(vec![1.0, 0.0]),
+            Point::from_vec(vec![-1.0, 0.0]),
+            Point::from_vec(vec![0.0, 1.0]),
+            Point::from_vec(vec![0.0, -1.0]),
+        ];
+        assert!(bowtie.try_dual().is_err());
+
+        let dual = bowtie.projective_dual().unwrap();
+        assert_eq!(dual.ideal_vertices().len(), 2);
+        assert_eq!(dual.abs.vertex_count(), 4);
+        assert!(dual.into_concrete().is_none());
+
+        let center = bowtie.reciprocation_center().unwrap();
+        assert!(bowtie.hemi_facets_with(&center).is_empty());
+        let sphere = Hypersphere::with_radius(center, 1.0);
+        assert!(bowtie.try_dual_with(&sphere).is_ok());
+    }
+
+    /// Polytopes without hemi facets keep their center, and their projective
+    /// dual is the usual one.
+    #[test]
+    fn cube_dual() {
+        let cube = Concrete::cube();
+        let center = cube.reciprocation_center().unwrap();
+        assert!(center.norm() < f64::EPS);
+
+        let dual = cube.projective_dual().unwrap().into_concrete().unwrap();
+        assert_eq!(dual.vertices, cube.try_dual().unwrap().vertices);
+    }
+}
@@ -0,0 +1,370 @@
+//! Builds the convex hull of a set of points, by
+//! [gift wrapping](https://en.wikipedia.org/wiki/Gift_wrapping_algorithm)
+//! around the ridges of every facet found so far.
+
+use std::collections::{BTreeSet, HashSet, VecDeque};
+
+use super::Concrete;
+use crate::{
+    Polytope,
+    abs::{Abstract, AbstractBuilder, SubelementList, Subelements},
+    float::Float,
+    geometry::{Point, PointOrd, Subspace, Vector, predicates},
+};
+
+use vec_like::*;
+
+/// A supporting hyperplane of the hull.
+struct Support {
+    /// A point on the hyperplane.
+    offset: Point<f64>,
+
+    /// The unit normal of the hyperplane, pointing out of the hull.
+    normal: Vector<f64>,
+}
+
+impl Support {
+    /// Returns the signed distance from a point to the hyperplane. This is
+    /// never positive for the points the hull is built from.
+    fn distance(&self, p: &Point<f64>) -> f64 {
+        (p - &self.offset).dot(&self.normal)
+    }
+
+    /// Returns the sorted indices of the points on the hyperplane.
+    ///
+    /// The normal picks up rounding errors as it's wrapped around the hull, so
+    /// it only tells us which points are close to the hyperplane. If these
+    /// span it, we test them again against the hyperplane through some of
+    /// them, so that whether a point is on a facet only depends on the points.
+    fn points_on(&self, points: &[Point<f64>]) -> Vec<usize> {
+        let near: Vec<usize> = (0..points.len())
+            .filter(|&idx| self.distance(&points[idx]).abs() < 2.0 * f64::EPS)
+            .collect();
+
+        let dim = self.normal.len();
+        let mut flat = Subspace::new(points[near[0]].clone());
+        let mut base = vec![&points[near[0]]];
+        for &idx in &near[1..] {
+            if base.len() < dim && flat.add(&points[idx]).is_some() {
+                base.push(&points[idx]);
+            }
+        }
+
+        if base.len() < dim {
+            return near
+                .into_iter()
+                .filter(|&idx| self.distance(&points[idx]).abs() < f64::EPS)
+                .collect();
+        }
+
+        near.into_iter()
+            .filter(|&idx| {
+                let mut simplex = base.clone();
+                simplex.push(&points[idx]);
+                predicates::orient_eps(&simplex, f64::EPS).is_zero()
+            })
+            .collect()
+    }
+
+    /// Rotates the hyperplane towards a unit direction orthogonal to its
+    /// normal, until it hits another point. The rotation keeps fixed the
+    /// subspace through the offset that's orthogonal to both vectors.
+    fn wrap(&self, points: &[Point<f64>], dir: &Vector<f64>) -> Self {
+        let mut angle = f64::PI;
+
+        for p in points {
+            let d = p - &self.offset;
+            let (height, width) = (d.dot(&self.normal), d.dot(dir));
+
+            // Points on the fixed subspace don't stop the rotation.
+            if height.abs() < f64::EPS && width.abs() < f64::EPS {
+                continue;
+            }
+
+            // Points are never above the hyperplane, but rounding might tell
+            // otherwise, and the sign of zero matters to the arctangent.
+            let depth = if height < 0.0 { -height } else { 0.0 };
+            angle = angle.min(depth.atan2(width));
+        }
+
+        Self {
+            offset: self.offset.clone(),
+            normal: (&self.normal * angle.cos() + dir * angle.sin()).normalize(),
+        }
+    }
+}
+
+/// Returns a unit vector orthogonal to a given one and to a subspace through
+/// the origin, given by an orthonormal basis.
+fn orthogonal(normal: &Vector<f64>, basis: &[Vector<f64>]) -> Option<Vector<f64>> {
+    let dim = normal.len();
+    (0..dim).find_map(|idx| {
+        let mut e = Vector::zeros(dim);
+        e[idx] = 1.0;
+
+        for b in basis.iter().chain(std::iter::once(normal)) {
+            e -= b * e.dot(b);
+        }
+
+        e.try_normalize(f64::EPS)
+    })
+}
+
+/// Finds a first facet of the hull, by starting from a hyperplane through the
+/// point with the largest first coordinate and wrapping it around the points
+/// on it until they span a hyperplane.
+fn first_facet(points: &[Point<f64>]) -> (Support, Vec<usize>) {
+    let dim = points[0].len();
+    let top = (0..points.len())
+        .max_by(|&i, &j| points[i][0].total_cmp(&points[j][0]))
+        .expect("the hull of no points has no facets");
+
+    let mut normal = Vector::zeros(dim);
+    normal[0] = 1.0;
+    let mut support = Support {
+        offset: points[top].clone(),
+        normal,
+    };
+
+    loop {
+        let facet = support.points_on(points);
+        let flat = Subspace::from_points(facet.iter().map(|&idx| &points[idx]));
+        if flat.rank() + 1 == dim {
+            return (support, facet);
+        }
+
+        let dir = orthogonal(&support.normal, &flat.basis)
+            .expect("the flat isn't a hyperplane, so it has an orthogonal direction");
+        support = support.wrap(points, &dir);
+    }
+}
+
+/// Returns the proper faces of the convex hull of some points that span the
+/// space they're in, rank by rank from the vertices to the facets. Every face
+/// is given by the sorted indices of the points on it, which might include
+/// points that aren't vertices.
+fn hull_faces(points: &[Point<f64>]) -> Vec<Vec<Vec<usize>>> {
+    let dim = points[0].len();
+
+    // The hull of points on a line is a segment.
+    if dim == 1 {
+        let cmp = |&i: &usize, &j: &usize| points[i][0].total_cmp(&points[j][0]);
+        let min = (0..points.len()).min_by(cmp).unwrap();
+        let max = (0..points.len()).max_by(cmp).unwrap();
+        return vec![vec![vec![min], vec![max]]];
+    }
+
+    let mut faces = vec![BTreeSet::new(); dim];
+    let (support, facet) = first_facet(points);
+    let mut found = HashSet::from([facet.clone()]);
+    let mut queue = VecDeque::from([(support, facet)]);
+
+    // The ridges we've already wrapped around.
+    let mut wrapped = HashSet::new();
+
+    while let Some((support, facet)) = queue.pop_front() {
+        // The faces of the facet are found recursively.
+        let subspace = Subspace::from_points(facet.iter().map(|&idx| &points[idx]));
+        let flat: Vec<_> = facet
+            .iter()
+            .map(|&idx| subspace.flatten(&points[idx]))
+            .collect();
+
+        for (rank, sub_faces) in hull_faces(&flat).into_iter().enumerate() {
+            for face in sub_faces {
+                let mut face: Vec<usize> = face.into_iter().map(|idx| facet[idx]).collect();
+                face.sort_unstable();
+
+                // Finds the facet on the other side of every new ridge.
+                if rank + 2 == dim && wrapped.insert(face.clone()) {
+                    let ridge = Subspace::from_points(face.iter().map(|&idx| &points[idx]));
+                    let inwards = facet
+                        .iter()
+                        .find_map(|&idx| ridge.normal(&points[idx]))
+                        .expect("a facet has points outside of its ridges");
+
+                    let next = Support {
+                        offset: points[face[0]].clone(),
+                        normal: support.normal.clone(),
+                    }
+                    .wrap(points, &-inwards);
+                    let next_facet = next.points_on(points);
+
+                    if found.insert(next_facet.clone()) {
+                        queue.push_back((next, next_facet));
+                    }
+                }
+
+                faces[rank].insert(face);
+            }
+        }
+
+        faces[dim - 1].insert(facet);
+    }
+
+    faces
+        .into_iter()
+        .map(|rank| rank.into_iter().collect())
+        .collect()
+}
+
+/// Builds the abstract polytope whose elements have the given vertices, rank
+/// by rank from the edges. An element's subelements are the elements of the
+/// previous rank whose vertices are among its own.
+fn build_abstract(vertex_count: usize, elements: &[Vec<Vec<usize>>]) -> AbstractBuilder {
+    let mut builder = AbstractBuilder::with_rank_capacity(elements.len() + 2);
+    builder.push_min();
+    builder.push_vertices(vertex_count);
+
+    if let Some(edges) = elements.first() {
+        let mut subelements = SubelementList::with_capacity(edges.len());
+        for edge in edges {
+            subelements.push(Subelements::from(edge.clone()));
+        }
+        builder.push(subelements);
+    }
+
+    for pair in elements.windows(2) {
+        let (lower, upper) = (&pair[0], &pair[1]);
+
+        // The elements of the lower rank that contain each vertex.
+        let mut by_vertex = vec![Vec::new(); vertex_count];
+        for (idx, el) in lower.iter().enumerate() {
+            for &v in el {
+                by_vertex[v].push(idx);
+            }
+        }
+
+        let mut subelements = SubelementList::with_capacity(upper.len());
+        for el in upper {
+            let mut subs: Vec<usize> = el
+                .iter()
+                .flat_map(|&v| &by_vertex[v])
+                .copied()
+                .filter(|&idx| lower[idx].iter().all(|v| el.binary_search(v).is_ok()))
+                .collect();
+            subs.sort_unstable();
+            subs.dedup();
+            subelements.push(Subelements::from(subs));
+        }
+        builder.push(subelements);
+    }
+
+    builder.push_max();
+    builder
+}
+
+impl Concrete {
+    /// Builds the convex hull of a set of points. Its rank is one more than
+    /// the dimension of the subspace the points span, and points that aren't
+    /// vertices of the hull, like those inside of it, are left out. Returns
+    /// `None` if there are no points.
+    pub fn convex_hull(points: &[Point<f64>]) -> Option<Self> {
+        // Removes repeated points.
+        let points: Vec<Point<f64>> = points
+            .iter()
+            .map(|p| PointOrd::new(p.clone()))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|p| p.0)
+            .collect();
+
+        if points.is_empty() {
+            return None;
+        }
+
+        let subspace = Subspace::from_points(points.iter());
+        if subspace.rank() == 0 {
+            return Some(Self::new(points, Abstract::point()));
+        }
+
+        let faces = hull_faces(&subspace.flatten_vec(&points));
+
+        // The indices of the points that are vertices of the hull.
+        let mut vertex_idx = vec![None; points.len()];
+        let mut vertices = Vec::new();
+        for vertex in &faces[0] {
+            vertex_idx[vertex[0]] = Some(vertices.len());
+            vertices.push(points[vertex[0]].clone());
+        }
+
+        let elements: Vec<Vec<Vec<usize>>> = faces[1..]
+            .iter()
+            .map(|rank| {
+                rank.iter()
+                    .map(|face| face.iter().filter_map(|&idx| vertex_idx[idx]).collect())
+                    .collect()
+            })
+            .collect();
+
+        // Safety: the faces of a convex polytope form a polytope.
+        let abs = unsafe { build_abstract(vertices.len(), &elements).build() };
+        Some(Self::new(vertices, abs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+
+    /// Returns every point whose coordinates are each taken from a list.
+    fn grid(dim: usize, values: &[f64]) -> Vec<Point<f64>> {
+        let mut points = vec![Point::zeros(dim)];
+        for idx in 0..dim {
+            points = points
+                .into_iter()
+                .flat_map(|p| {
+                    values.iter().map(move |&x| {
+                        let mut q = p.clone();
+                        q[idx] = x;
+                        q
+                    })
+                })
+                .collect();
+        }
+        points
+    }
+
+    /// Hypercubes, with points in their interior and on their faces.
+    #[test]
+    fn hypercubes() {
+        let square = Concrete::convex_hull(&grid(2, &[-1.0, 0.0, 1.0])).unwrap();
+        test(&square, [1, 4, 4, 1]);
+
+        let cube = Concrete::convex_hull(&grid(3, &[-1.0, 0.0, 1.0])).unwrap();
+        test(&cube, [1, 8, 12, 6, 1]);
+
+        let tesseract = Concrete::convex_hull(&grid(4, &[-1.0, 1.0])).unwrap();
+        test(&tesseract, [1, 16, 32, 24, 8, 1]);
+    }
+
+    /// The 24-cell, whose vertices are the permutations of (±1, ±1, 0, 0).
+    #[test]
+    fn icositetrachoron() {
+        let points: Vec<_> = grid(4, &[-1.0, 0.0, 1.0])
+            .into_iter()
+            .filter(|p| p.iter().filter(|x| **x != 0.0).count() == 2)
+            .collect();
+        let hull = Concrete::convex_hull(&points).unwrap();
+        test(&hull, [1, 24, 96, 96, 24, 1]);
+    }
+
+    /// Points that don't span the whole space.
+    #[test]
+    fn flat() {
+        let points: Vec<_> = grid(3, &[0.0, 1.0])
+            .into_iter()
+            .filter(|p| p[2] == 0.0)
+            .collect();
+        let square = Concrete::convex_hull(&points).unwrap();
+        test(&square, [1, 4, 4, 1]);
+
+        let dyad = Concrete::convex_hull(&grid(1, &[0.0, 0.5, 1.0])).unwrap();
+        test(&dyad, [1, 2, 1]);
+
+        let point = Concrete::convex_hull(&[Point::zeros(3), Point::zeros(3)]).unwrap();
+        test(&point, [1, 1]);
+        assert!(Concrete::convex_hull(&[]).is_none());
+    }
+}
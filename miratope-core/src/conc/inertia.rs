@@ -0,0 +1,188 @@
+//! Computes the centroid and moments of inertia of a solid polytope.
+
+use super::{simplicial::Simplex, Concrete, ConcretePolytope};
+use crate::{
+    float::Float,
+    geometry::{Matrix, Point},
+};
+
+/// The mass properties of a solid polytope of uniform density 1.
+#[derive(Clone, Debug)]
+pub struct MassProperties {
+    /// The volume of the polytope.
+    pub volume: f64,
+
+    /// The centroid of the solid polytope. This generally differs from the
+    /// gravicenter of its vertices.
+    pub centroid: Point<f64>,
+
+    /// The second moment of the polytope about its centroid, that is, the
+    /// integral of `x xᵀ` over the solid, where `x` is measured from the
+    /// centroid.
+    pub second_moment: Matrix<f64>,
+}
+
+impl MassProperties {
+    /// Returns the inertia tensor of the polytope about its centroid.
+    pub fn inertia_tensor(&self) -> Matrix<f64> {
+        let dim = self.centroid.len();
+        Matrix::identity(dim, dim) * self.second_moment.trace() - &self.second_moment
+    }
+
+    /// Returns the principal moments of inertia in increasing order, together
+    /// with a matrix whose columns are the corresponding principal axes. The
+    /// axes are oriented so that the matrix is a rotation, rather than a
+    /// reflection.
+    pub fn principal_axes(&self) -> (Vec<f64>, Matrix<f64>) {
+        let eigen = self.inertia_tensor().symmetric_eigen();
+        let mut order: Vec<usize> = (0..eigen.eigenvalues.len()).collect();
+        order.sort_by(|&i, &j| eigen.eigenvalues[i].total_cmp(&eigen.eigenvalues[j]));
+
+        let moments = order.iter().map(|&i| eigen.eigenvalues[i]).collect();
+        let mut axes = Matrix::from_columns(
+            &order
+                .iter()
+                .map(|&i| eigen.eigenvectors.column(i).into_owned())
+                .collect::<Vec<_>>(),
+        );
+
+        // Rotating into the axes shouldn't reflect the polytope.
+        if axes.determinant() < 0.0 {
+            axes.column_mut(0).neg_mut();
+        }
+
+        (moments, axes)
+    }
+}
+
+/// Returns the integral of `x xᵀ` over a simplex, counted with its sign.
+fn simplex_second_moment(simplex: &Simplex) -> Matrix<f64> {
+    let n = simplex.vertices.len() - 1;
+    let sum: Point<f64> = simplex.vertices.iter().sum();
+    let mut moment = &sum * sum.transpose();
+    for v in &simplex.vertices {
+        moment += v * v.transpose();
+    }
+
+    moment * (simplex.signed_volume() / ((n + 1) * (n + 2)) as f64)
+}
+
+impl Concrete {
+    /// Computes the volume, centroid and second moment of the solid polytope,
+    /// using its [simplicial decomposition](Self::simplexes). Returns `None` if
+    /// the polytope has no volume.
+    pub fn mass_properties(&self) -> Option<MassProperties> {
+        let simplexes = self.simplexes()?;
+        let dim = self.dim()?;
+
+        let mut volume = 0.0;
+        let mut moment = Point::zeros(dim);
+        let mut second_moment = Matrix::zeros(dim, dim);
+
+        for simplex in &simplexes {
+            let simplex_volume = simplex.signed_volume();
+            volume += simplex_volume;
+            moment += simplex.centroid() * simplex_volume;
+            second_moment += simplex_second_moment(simplex);
+        }
+
+        if volume.abs() < f64::EPS {
+            return None;
+        }
+
+        let centroid = moment / volume;
+        second_moment -= &centroid * centroid.transpose() * volume;
+
+        Some(MassProperties {
+            volume,
+            centroid,
+            second_moment,
+        })
+    }
+
+    /// Moves the centroid of the polytope to the origin, and rotates it so that
+    /// its principal axes become the coordinate axes, in increasing order of
+    /// their moments of inertia. Returns `false` if the polytope has no
+    /// volume, in which case it's left untouched.
+    pub fn align_principal_axes(&mut self) -> bool {
+        let properties = match self.mass_properties() {
+            Some(properties) => properties,
+            None => return false,
+        };
+
+        let (_, axes) = properties.principal_axes();
+        let rotation = axes.transpose();
+
+        for v in &mut self.vertices {
+            *v = &rotation * (&*v - &properties.centroid);
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+    use approx::assert_abs_diff_eq;
+
+    /// The inertia tensor of a unit cube is a multiple of the identity.
+    #[test]
+    fn cube() {
+        let properties = Concrete::cube().mass_properties().unwrap();
+        assert_abs_diff_eq!(properties.volume, 1.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(properties.centroid.norm(), 0.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(
+            (properties.inertia_tensor() - Matrix::identity(3, 3) / 6.0).norm(),
+            0.0,
+            epsilon = 1e-9
+        );
+    }
+
+    /// Stretches and moves a cube, and checks that it gets aligned back.
+    #[test]
+    fn align() {
+        let mut cuboid = Concrete::cube();
+        for v in &mut cuboid.vertices {
+            let (x, y) = (v[0] * 3.0, v[1] * 2.0);
+            v[0] = (x - y) / 2f64.sqrt() + 1.0;
+            v[1] = (x + y) / 2f64.sqrt() - 2.0;
+        }
+
+        assert!(cuboid.align_principal_axes());
+
+        // The longest axis has the least moment of inertia.
+        for v in &cuboid.vertices {
+            assert_abs_diff_eq!(v[0].abs(), 1.5, epsilon = 1e-9);
+            assert_abs_diff_eq!(v[1].abs(), 1.0, epsilon = 1e-9);
+            assert_abs_diff_eq!(v[2].abs(), 0.5, epsilon = 1e-9);
+        }
+    }
+
+    /// Returns the orientation of a tetrahedron, as the sign of its volume.
+    fn orientation(tet: &Concrete) -> f64 {
+        let v = &tet.vertices;
+        Matrix::from_columns(&[&v[1] - &v[0], &v[2] - &v[0], &v[3] - &v[0]])
+            .determinant()
+            .signum()
+    }
+
+    /// Aligning the principal axes of irregular tetrahedra doesn't turn them
+    /// into their mirror images.
+    #[test]
+    fn align_preserves_orientation() {
+        for i in 0..50 {
+            let mut tet = Concrete::simplex(4);
+            for (j, v) in tet.vertices.iter_mut().enumerate() {
+                for (k, x) in v.iter_mut().enumerate() {
+                    *x += ((i * 12 + j * 3 + k + 1) as f64 * 1.618).sin() / 3.0;
+                }
+            }
+
+            let before = orientation(&tet);
+            assert!(tet.align_principal_axes());
+            assert_eq!(orientation(&tet), before, "tetrahedron {} was reflected", i);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Builds [lace prisms](https://polytope.miraheze.org/wiki/Lace_prism) and
+//! lace towers, which stack polytopes in parallel hyperplanes and join them
+//! by lacing edges.
+//!
+//! The layers are lifted to one more dimension, each at its own height, and
+//! the result is built as their convex hull. The lacing elements are then the
+//! faces of the hull between consecutive layers, so they're invariant under
+//! every symmetry the layers share. This covers antiprisms, cupolas, and their
+//! analogs in any rank, as long as every vertex of every layer is a vertex of
+//! the hull.
+
+use std::{collections::BTreeSet, fmt::Display};
+
+use super::Concrete;
+use crate::{
+    conc::ConcretePolytope,
+    geometry::{Point, PointOrd},
+};
+
+/// Any error encountered while building a lace tower.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LaceError {
+    /// There are less than two layers.
+    TooFewLayers,
+
+    /// The layer with a given index has no vertices.
+    Empty(usize),
+
+    /// The layer with a given index lives in a different number of dimensions
+    /// than the first one.
+    Dimension(usize),
+
+    /// The layer with a given index isn't above the previous one.
+    Height(usize),
+
+    /// Some vertex of the layer with a given index isn't a vertex of the lace
+    /// tower, as it's inside of it or on one of its faces.
+    Hidden(usize),
+}
+
+impl Display for LaceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooFewLayers => write!(f, "a lace tower needs at least two layers"),
+            Self::Empty(idx) => write!(f, "layer {} has no vertices", idx),
+            Self::Dimension(idx) => write!(f, "layer {} has the wrong dimension", idx),
+            Self::Height(idx) => write!(f, "layer {} isn't above the previous one", idx),
+            Self::Hidden(idx) => write!(f, "layer {} isn't on the hull of the others", idx),
+        }
+    }
+}
+
+impl std::error::Error for LaceError {}
+
+/// The result of building a lace tower.
+pub type LaceResult<T> = Result<T, LaceError>;
+
+impl Concrete {
+    /// Builds a lace tower from some layers, each with the height of the
+    /// hyperplane it's placed in. The heights must increase, and are stored
+    /// as a new last coordinate.
+    pub fn lace_tower(layers: &[(&Self, f64)]) -> LaceResult<Self> {
+        if layers.len() < 2 {
+            return Err(LaceError::TooFewLayers);
+        }
+
+        let mut dim = None;
+        let mut lifted = Vec::with_capacity(layers.len());
+        for (idx, &(layer, height)) in layers.iter().enumerate() {
+            let layer_dim = layer.dim().ok_or(LaceError::Empty(idx))?;
+            if *dim.get_or_insert(layer_dim) != layer_dim {
+                return Err(LaceError::Dimension(idx));
+            }
+            if idx != 0 && height <= layers[idx - 1].1 {
+                return Err(LaceError::Height(idx));
+            }
+
+            let vertices: Vec<Point<f64>> =
+                layer.vertices().iter().map(|v| v.push(height)).collect();
+            lifted.push(vertices);
+        }
+
+        let points: Vec<Point<f64>> = lifted.iter().flatten().cloned().collect();
+        let tower = Self::convex_hull(&points).expect("the layers have vertices");
+
+        // Every vertex of every layer must be a vertex of the tower.
+        let vertices: BTreeSet<_> = tower
+            .vertices()
+            .iter()
+            .map(|v| PointOrd::new(v.clone()))
+            .collect();
+        for (idx, layer) in lifted.into_iter().enumerate() {
+            if !layer
+                .into_iter()
+                .all(|v| vertices.contains(&PointOrd::new(v)))
+            {
+                return Err(LaceError::Hidden(idx));
+            }
+        }
+
+        Ok(tower)
+    }
+
+    /// Builds a lace prism, with the polytope as its base and another one as
+    /// its top, at a given height above it.
+    pub fn lace_prism(&self, top: &Self, height: f64) -> LaceResult<Self> {
+        Self::lace_tower(&[(self, 0.0), (top, height)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Polytope, abs::Abstract, float::Float, test};
+
+    /// Returns a regular polygon with a given circumradius, rotated by an
+    /// angle.
+    fn polygon(n: usize, radius: f64, rot: f64) -> Concrete {
+        let mut polygon = Concrete::grunbaum_star_polygon_with_rot(n, 1, rot);
+        polygon.scale(radius);
+        polygon
+    }
+
+    /// Lace prisms of polygons.
+    #[test]
+    fn lace_prisms() {
+        let triangle = polygon(3, 1.0, 0.0);
+        test(
+            &triangle.lace_prism(&triangle, 1.0).unwrap(),
+            [1, 6, 9, 5, 1],
+        );
+
+        let square = polygon(4, 1.0, 0.0);
+        let gyro_square = polygon(4, 1.0, f64::PI / 4.0);
+        let antiprism = square.lace_prism(&gyro_square, 1.0).unwrap();
+        test(&antiprism, [1, 8, 16, 10, 1]);
+
+        let hexagon = polygon(6, 1.0, 0.0);
+        let triangle = polygon(3, 0.6, f64::PI / 6.0);
+        let cupola = hexagon.lace_prism(&triangle, 0.8).unwrap();
+        test(&cupola, [1, 9, 15, 8, 1]);
+    }
+
+    /// The icosahedron, as a lace tower of a point, a pentagon, a gyrated
+    /// pentagon, and a point.
+    #[test]
+    fn icosahedron() {
+        let point = Concrete::new(vec![Point::zeros(2)], Abstract::point());
+        let pentagon = polygon(5, 1.0, 0.0);
+        let gyro_pentagon = polygon(5, 1.0, f64::PI / 5.0);
+        let h = (f64::SQRT_5 - 1.0) / 2.0;
+
+        let icosahedron = Concrete::lace_tower(&[
+            (&point, -0.5 - h),
+            (&pentagon, -0.5),
+            (&gyro_pentagon, 0.5),
+            (&point, 0.5 + h),
+        ])
+        .unwrap();
+        test(&icosahedron, [1, 12, 30, 20, 1]);
+    }
+
+    /// Checks that invalid layers are caught.
+    #[test]
+    fn errors() {
+        let pentagon = polygon(5, 1.0, 0.0);
+        let pentagram = Concrete::grunbaum_star_polygon(5, 2);
+        let point = Concrete::point();
+
+        assert_eq!(
+            Concrete::lace_tower(&[(&pentagon, 0.0)]).unwrap_err(),
+            LaceError::TooFewLayers
+        );
+        assert_eq!(
+            pentagon
+                .lace_prism(&Concrete::nullitope(), 1.0)
+                .unwrap_err(),
+            LaceError::Empty(1)
+        );
+        assert_eq!(
+            pentagon.lace_prism(&point, 1.0).unwrap_err(),
+            LaceError::Dimension(1)
+        );
+        assert_eq!(
+            pentagon.lace_prism(&pentagram, 0.0).unwrap_err(),
+            LaceError::Height(1)
+        );
+        assert_eq!(
+            Concrete::lace_tower(&[(&pentagon, 0.0), (&pentagram, 1.0), (&pentagon, 2.0)])
+                .unwrap_err(),
+            LaceError::Hidden(1)
+        );
+    }
+}
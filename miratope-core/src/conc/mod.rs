@@ -1,9 +1,32 @@
 //! Declares the [`Concrete`] polytope type and all associated data structures.
 
+pub mod coincident;
+pub mod collapse;
+pub mod compare;
+pub mod cut;
 pub mod cycle;
 pub mod element_types;
+pub mod embedding;
+pub mod expand;
+pub mod fingerprint;
 pub mod faceting;
+pub mod frame;
+pub mod hemi;
+pub mod hull;
+pub mod inertia;
+pub mod lace;
+pub mod periodic;
+pub mod quotient;
+pub mod pieces;
+pub mod random;
+pub mod reflect;
+pub mod simplicial;
+pub mod stellation;
 pub mod symmetry;
+pub mod tiling;
+pub mod triangulation;
+pub mod wythoff;
+pub mod zonotope;
 
 use std::{
     collections::{HashMap, HashSet},
@@ -13,8 +36,10 @@ use std::{
 use super::{
     abs::{
         flag::{Flag, FlagChanges, FlagEvent, OrientedFlagIter},
+        product::{try_product, ProductType},
         Abstract, ElementList, Ranked, SubelementList,
     },
+    cancel::{Cancelled, CancellationToken},
     DualError, Polytope,
 };
 use crate::{
@@ -99,6 +124,75 @@ impl Concrete {
         // With no further info, we create a generic name for the polytope.
         Self { vertices, abs }
     }
+
+    /// Returns the omnitruncate of a polytope, along with the flag that each of
+    /// its vertices comes from. Every vertex is placed at the sum of the
+    /// centers of the elements in its flag.
+    pub fn omnitruncate_and_flags(&self) -> (Self, Vec<Flag>) {
+        let mut poly = self.clone();
+        poly.element_sort();
+        let (abs, flags) = poly.abs.omnitruncate_and_flags();
+        let element_vertices = poly.avg_vertex_map();
+
+        let vertices = flags
+            .iter()
+            .map(|flag| {
+                flag.iter()
+                    .copied()
+                    .enumerate()
+                    .skip(1)
+                    .take(poly.rank())
+                    .map(|el| &element_vertices[el])
+                    .sum()
+            })
+            .collect();
+
+        (Self::new(vertices, abs), flags)
+    }
+
+    /// Builds one of the four products of two polytopes, stopping early if the
+    /// token is cancelled. The result is the same as that of the corresponding
+    /// method of [`Polytope`].
+    pub fn try_product(
+        &self,
+        other: &Self,
+        product_type: ProductType,
+        cancel: &CancellationToken,
+    ) -> Result<Self, Cancelled> {
+        self.try_product_with(
+            other,
+            product_type,
+            &Point::zeros(self.dim_or()),
+            &Point::zeros(other.dim_or()),
+            1.0,
+            cancel,
+        )
+    }
+
+    /// Builds one of the four products of two polytopes, stopping early if the
+    /// token is cancelled. The offsets of the factors are used by pyramid and
+    /// tegum products, and the height only by pyramid products, just as in
+    /// [`ConcretePolytope::duopyramid_with`] and
+    /// [`ConcretePolytope::duotegum_with`].
+    pub fn try_product_with(
+        &self,
+        other: &Self,
+        product_type: ProductType,
+        self_offset: &Point<f64>,
+        other_offset: &Point<f64>,
+        height: f64,
+        cancel: &CancellationToken,
+    ) -> Result<Self, Cancelled> {
+        let abs = try_product(&self.abs, &other.abs, product_type, cancel)?;
+
+        let vertices = match product_type {
+            ProductType::Pyramid => duopyramid_vertices(&self.vertices, &other.vertices, self_offset, other_offset, height, false),
+            ProductType::Tegum => duopyramid_vertices(&self.vertices, &other.vertices, self_offset, other_offset, 0.0, true),
+            ProductType::Prism | ProductType::Comb => duoprism_vertices(&self.vertices, &other.vertices),
+        };
+
+        Ok(Self::new(vertices, abs))
+    }
 }
 
 impl Polytope for Concrete {
@@ -161,8 +255,10 @@ impl Polytope for Concrete {
     }
 
     /// Builds the Petrie polygon of a polytope from a given flag, or returns
-    /// `None` if it's invalid.
+    /// `None` if it's invalid. The polygon keeps the coordinates of the
+    /// original vertices, so it's generally skew.
     fn petrie_polygon_with(&mut self, flag: Flag) -> Option<Self> {
+        self.element_sort();
         let vertices = self.abs.petrie_polygon_vertices(flag)?;
         let n = vertices.len();
 
@@ -311,26 +407,8 @@ impl Polytope for Concrete {
         output
     }
     
-    // TODO: A method that builds an omnitruncate together with a map from flags
-    // to vertices? We got some math details to figure out.
     fn omnitruncate(&self) -> Self {
-        let (abs, flags) = self.abs.omnitruncate_and_flags();
-        let element_vertices = self.avg_vertex_map();
-
-        Self::new(
-            flags
-                .into_iter()
-                .map(|flag| {
-                    flag.into_iter()
-                        .enumerate()
-                        .skip(1)
-                        .take(self.rank())
-                        .map(|el| &element_vertices[el])
-                        .sum()
-                })
-                .collect(),
-            abs,
-        )
+        self.omnitruncate_and_flags().0
     }
 
     /// Builds a [duopyramid](https://polytope.miraheze.org/wiki/Pyramid_product)
@@ -644,6 +722,17 @@ pub trait ConcretePolytope: Polytope {
     /// Returns an arbitrary truncate of a polytope.
     fn truncate_with(&self, truncate_type: Vec<usize>, depth: Vec<f64>) -> Self;
 
+    /// Truncates a regular polytope with the depths chosen so that every edge
+    /// of the result has unit length. This amounts to a Wythoff construction
+    /// on the polytope's symmetry group, with the given ringed nodes.
+    fn uniform_truncate(&self, truncate_type: Vec<usize>) -> Self;
+
+    /// Cuts off every vertex of a polytope, so that each edge loses a fraction
+    /// `depth / 2` of its length at each end. A depth of 1 gives the
+    /// rectification, and greater depths give quasitruncations. Polytopes
+    /// without edges are returned unchanged, and dyads are just shortened.
+    fn truncate(&self, depth: f64) -> Self;
+
     /// Calculates the circumsphere of a polytope. Returns `None` if the
     /// polytope isn't circumscribable.
     fn circumsphere(&self) -> Option<Hypersphere<f64>> {
@@ -785,6 +874,10 @@ pub trait ConcretePolytope: Polytope {
     /// Builds an [antiprism](https://polytope.miraheze.org/wiki/Antiprism)
     /// based on a given polytope. Uses the specified [`Hypersphere`] to build
     /// the dual base, and separates the bases by the given height.
+    ///
+    /// This works in any rank, so that for instance a polyhedron gives a
+    /// polychoron whose lacing elements join each element of the base to the
+    /// duals of its superelements.
     fn try_antiprism_with(
         &self,
         sphere: &Hypersphere<f64>,
@@ -960,6 +1053,35 @@ pub trait ConcretePolytope: Polytope {
         self.volume()
     }
 
+    /// Computes the volume of a polytope by adding up the signed volumes of
+    /// the simplices in [`Concrete::simplexes`]. Unlike [`Self::volume`], this
+    /// doesn't need the elements to be sorted. The polytope is flattened
+    /// first, so padded or lifted polytopes are measured in their own span.
+    /// Returns `None` if the polytope isn't orientable, or if it doesn't span
+    /// a space of its own rank.
+    fn hypervolume(&self) -> Option<f64> {
+        let mut poly = self.con().clone();
+        poly.flatten();
+        let simplexes = poly.simplexes()?;
+        Some(simplexes.iter().map(simplicial::Simplex::signed_volume).sum())
+    }
+
+    /// Computes the total content of the facets of a polytope, like the
+    /// perimeter of a polygon or the surface area of a polyhedron. Every facet
+    /// is measured with [`Self::hypervolume`]. Returns `None` if
+    /// the polytope has no facets with a volume, or if some facet has none.
+    fn facet_content(&self) -> Option<f64> {
+        let poly = self.con();
+        let rank = poly.rank();
+        if rank < 3 {
+            return None;
+        }
+
+        (0..poly.el_count(rank - 1))
+            .map(|idx| poly.facet(idx)?.hypervolume())
+            .sum()
+    }
+
     /// Projects the vertices of the polytope into the lowest dimension possible.
     /// If the polytope's subspace is already of full rank, this is a no-op.
     fn flatten(&mut self);
@@ -967,6 +1089,19 @@ pub trait ConcretePolytope: Polytope {
     /// Flattens the vertices of a polytope into a specified subspace.
     fn flatten_into(&mut self, subspace: &Subspace<f64>);
 
+    /// Embeds the polytope into a space of a given dimension by appending
+    /// zero coordinates. If the polytope is already in at least that many
+    /// dimensions, this is a no-op.
+    fn pad_dim(&mut self, dim: usize);
+
+    /// Embeds the polytope into a space of one more dimension, placing it at a
+    /// given offset along the new axis.
+    fn lift(&mut self, offset: f64);
+
+    /// Removes the trailing coordinates that are zero for every vertex, and
+    /// returns how many were removed.
+    fn drop_zero_coords(&mut self) -> usize;
+
     /// Slices the polytope through a given plane.
     fn cross_section(&self, slice: &Hyperplane<f64>) -> Self;
   
@@ -1167,6 +1302,42 @@ impl ConcretePolytope for Concrete {
         }
     }
 
+    /// Embeds the polytope into a space of a given dimension by appending
+    /// zero coordinates. If the polytope is already in at least that many
+    /// dimensions, this is a no-op.
+    fn pad_dim(&mut self, dim: usize) {
+        if self.dim_or() < dim {
+            for v in &mut self.vertices {
+                *v = v.clone().resize_vertically(dim, 0.0);
+            }
+        }
+    }
+
+    /// Embeds the polytope into a space of one more dimension, placing it at a
+    /// given offset along the new axis.
+    fn lift(&mut self, offset: f64) {
+        let dim = self.dim_or();
+        for v in &mut self.vertices {
+            *v = v.clone().resize_vertically(dim + 1, offset);
+        }
+    }
+
+    /// Removes the trailing coordinates that are zero for every vertex, and
+    /// returns how many were removed.
+    fn drop_zero_coords(&mut self) -> usize {
+        let dim = self.dim_or();
+        let new_dim = (0..dim)
+            .rev()
+            .find(|&i| self.vertices.iter().any(|v| v[i].abs() > f64::EPS))
+            .map_or(0, |i| i + 1);
+
+        for v in &mut self.vertices {
+            *v = v.clone().resize_vertically(new_dim, 0.0);
+        }
+
+        dim - new_dim
+    }
+
     /// Takes the cross-section of a polytope through a given hyperplane.
     ///
     /// # Todo
@@ -1319,6 +1490,71 @@ impl ConcretePolytope for Concrete {
 
         Self::new(vertex_coords, abs)
     }
+
+    /// Truncates a regular polytope with the depths chosen so that every edge
+    /// of the result has unit length. This amounts to a Wythoff construction
+    /// on the polytope's symmetry group, with the given ringed nodes.
+    fn uniform_truncate(&self, truncate_type: Vec<usize>) -> Self {
+        let mut poly = self.clone();
+        poly.element_sort();
+        poly.recenter();
+
+        // The new vertex is at distance 1/2 from the mirror of each ringed
+        // node. The mirror of a node swaps the element of its rank in a flag
+        // with the one in the adjacent flag, and fixes all others.
+        let element_vertices = poly.avg_vertex_map();
+        let flag = poly.first_flag();
+        let depth = (1..poly.rank())
+            .map(|r| {
+                let other = flag.change(&poly.abs, r);
+                1.0 / (&element_vertices[r][flag[r]] - &element_vertices[r][other[r]]).norm()
+            })
+            .collect();
+
+        poly.truncate_with(truncate_type, depth)
+    }
+
+    fn truncate(&self, depth: f64) -> Self {
+        let rank = self.rank();
+        if rank < 2 {
+            return self.clone();
+        }
+
+        // The point at a fraction of the way from a vertex to the other end of
+        // an edge.
+        let along = |poly: &Self, v: usize, edge: usize, t: f64| {
+            let subs = &poly.abs[2][edge].subs;
+            let w = if subs[0] == v { subs[1] } else { subs[0] };
+            &poly.vertices[v] * (1.0 - t) + &poly.vertices[w] * t
+        };
+
+        if rank == 2 {
+            let vertices = (0..self.vertex_count())
+                .map(|v| along(self, v, 0, depth / 2.0))
+                .collect();
+            return Self::new(vertices, self.abs.clone());
+        }
+
+        let mut poly = self.clone();
+        poly.element_sort();
+
+        // The vertices on both ends of an edge meet at its midpoint.
+        if abs_diff_eq!(depth, 1.0, epsilon = f64::EPS) {
+            let (abs, subflags) = poly.abs.truncate_and_flags(vec![1]);
+            let vertices = subflags
+                .into_iter()
+                .map(|subflag| along(&poly, poly.abs[2][subflag[0]].subs[0], subflag[0], 0.5))
+                .collect();
+            return Self::new(vertices, abs);
+        }
+
+        let (abs, subflags) = poly.abs.truncate_and_flags(vec![0, 1]);
+        let vertices = subflags
+            .into_iter()
+            .map(|subflag| along(&poly, subflag[0], subflag[1], depth / 2.0))
+            .collect();
+        Self::new(vertices, abs)
+    }
   
 	  /// Checks if the polytope is [fissary](https://polytope.miraheze.org/wiki/Fissary).
     fn is_fissary(&self) -> bool {
@@ -1395,10 +1631,71 @@ impl ConcretePolytope for Concrete {
 #[cfg(test)]
 mod tests {
     use super::{Concrete, ConcretePolytope};
-    use crate::{float::Float, Polytope};
+    use crate::{
+        abs::{product::ProductType, Ranked},
+        cancel::CancellationToken,
+        float::Float,
+        geometry::Point,
+        Polytope,
+    };
 
     use approx::abs_diff_eq;
 
+    /// Tests the hypervolume and facet content of some hypercubes.
+    #[test]
+    fn hypervolume() {
+        for (n, facet_content) in [(3, 4.0), (4, 6.0), (5, 8.0)] {
+            let hypercube = Concrete::hypercube(n);
+            assert!(abs_diff_eq!(hypercube.hypervolume().unwrap(), 1.0, epsilon = f64::EPS));
+            assert!(abs_diff_eq!(
+                hypercube.facet_content().unwrap(),
+                facet_content,
+                epsilon = f64::EPS
+            ));
+        }
+
+        // The volume is the same as the one computed from the flags.
+        let mut antiprism = Concrete::polygon(5).try_antiprism().unwrap();
+        let hypervolume = antiprism.hypervolume().unwrap();
+        assert!(abs_diff_eq!(hypervolume, antiprism.volume_mut().unwrap(), epsilon = f64::EPS));
+
+        // Dyads have a length, but no facets to measure.
+        assert!(abs_diff_eq!(Concrete::dyad().hypervolume().unwrap(), 1.0, epsilon = f64::EPS));
+        assert!(Concrete::dyad().facet_content().is_none());
+
+        // Padded and lifted polytopes are measured in their own span.
+        let mut square = Concrete::polygon(4);
+        let area = square.hypervolume().unwrap();
+        square.pad_dim(4);
+        square.lift(1.0);
+        assert!(abs_diff_eq!(square.hypervolume().unwrap(), area, epsilon = f64::EPS));
+
+        // Skew polytopes have no volume.
+        let mut skew = Concrete::polygon(4);
+        skew.pad_dim(3);
+        skew.vertices[0][2] = 1.0;
+        assert!(skew.hypervolume().is_none());
+    }
+
+    /// Cancellable products match the usual ones, and stop once cancelled.
+    #[test]
+    fn try_product() {
+        let (p, q) = (Concrete::polygon(3), Concrete::polygon(4));
+        let p_offset = Point::from_vec(vec![0.5, 0.0]);
+        let q_offset = Point::from_vec(vec![0.0, -0.5]);
+        let cancel = CancellationToken::new();
+
+        let product = p
+            .try_product_with(&q, ProductType::Pyramid, &p_offset, &q_offset, 2.0, &cancel)
+            .unwrap();
+        let expected = p.duopyramid_with(&q, &p_offset, &q_offset, 2.0);
+        assert_eq!(product.vertices, expected.vertices);
+        assert!(product.el_count_iter().eq(expected.el_count_iter()));
+
+        cancel.cancel();
+        assert!(p.try_product(&q, ProductType::Prism, &cancel).is_err());
+    }
+
     /// Tests that a polytope has an expected volume.
     fn test_volume(mut poly: Concrete, volume: Option<f64>) {
         poly.element_sort();
@@ -1546,6 +1843,21 @@ mod tests {
         }
     }
 
+    /// Lifts a square into 4D, and drops the extra coordinates back.
+    #[test]
+    fn pad_and_drop() {
+        let mut square = Concrete::polygon(4);
+        square.pad_dim(3);
+        square.lift(0.0);
+        assert_eq!(square.dim(), Some(4));
+
+        assert_eq!(square.drop_zero_coords(), 2);
+        assert_eq!(square.dim(), Some(2));
+
+        square.lift(1.0);
+        assert_eq!(square.drop_zero_coords(), 0);
+    }
+
     #[test]
     fn hypercube() {
         for n in 1..=6 {
@@ -1553,6 +1865,125 @@ mod tests {
         }
     }
 
+    /// Builds an antiprism of a cube in 4D, and checks that its bases lie on
+    /// opposite hyperplanes.
+    #[test]
+    fn cubic_antiprism() {
+        let cube = Concrete::cube();
+        let antiprism = cube.try_antiprism_with(&cube.circumsphere().unwrap(), 2.0).unwrap();
+        crate::test(&antiprism, [1, 14, 48, 62, 28, 1]);
+        assert_eq!(antiprism.dim(), Some(4));
+
+        let heights: Vec<f64> = antiprism.vertices.iter().map(|v| v[3]).collect();
+        assert_eq!(heights.iter().filter(|&&h| abs_diff_eq!(h, -1.0)).count(), 8);
+        assert_eq!(heights.iter().filter(|&&h| abs_diff_eq!(h, 1.0)).count(), 6);
+    }
+
+    /// Builds antiprisms of polytopes of every rank with the unit sphere, as
+    /// the antiprism operation does.
+    #[test]
+    fn antiprism_ranks() {
+        let dyad = Concrete::point().try_antiprism().unwrap();
+        crate::test(&dyad, [1, 2, 1]);
+        assert!(dyad.is_equilateral_with(1.0));
+
+        crate::test(&Concrete::dyad().try_antiprism().unwrap(), [1, 4, 4, 1]);
+        crate::test(&Concrete::polygon(3).try_antiprism().unwrap(), [1, 6, 12, 8, 1]);
+        crate::test(&Concrete::tetrahedron().try_antiprism().unwrap(), [1, 8, 24, 32, 16, 1]);
+    }
+
+    /// Checks that every vertex of an omnitruncate comes from a flag.
+    #[test]
+    fn omnitruncate() {
+        let cube = Concrete::cube();
+        let (girco, flags) = cube.omnitruncate_and_flags();
+        crate::test(&girco, [1, 48, 72, 26, 1]);
+        assert_eq!(flags.len(), girco.vertex_count());
+
+        // The vertices of a flag are the vertices of the cube.
+        let mut flag_vertices: Vec<_> = flags.iter().map(|flag| flag[1]).collect();
+        flag_vertices.sort_unstable();
+        flag_vertices.dedup();
+        assert_eq!(flag_vertices.len(), 8);
+    }
+
+    /// The omnitruncate of the nullitope is the nullitope.
+    #[test]
+    fn omnitruncate_nullitope() {
+        let (nullitope, flags) = Concrete::nullitope().omnitruncate_and_flags();
+        crate::test(&nullitope, [1]);
+        assert!(flags.is_empty());
+    }
+
+    /// Checks the Petrie polygons of a few polytopes.
+    #[test]
+    fn petrie_polygon() {
+        let hexagon = Concrete::cube().petrie_polygon().unwrap();
+        crate::test(&hexagon, [1, 6, 6, 1]);
+        assert_eq!(hexagon.dim(), Some(3));
+        assert!(hexagon.is_equilateral_with(1.0));
+
+        let square = Concrete::polygon(4).petrie_polygon().unwrap();
+        crate::test(&square, [1, 4, 4, 1]);
+
+        // Polytopes of low rank have no Petrie polygons.
+        assert!(Concrete::nullitope().petrie_polygon().is_none());
+        assert!(Concrete::point().petrie_polygon().is_none());
+        assert!(Concrete::dyad().petrie_polygon().is_none());
+    }
+
+    /// Checks that the Petrial of a cube keeps its vertices and edges.
+    #[test]
+    fn petrial() {
+        let cube = Concrete::cube();
+        let petrial = cube.petrial().unwrap();
+        crate::test(&petrial, [1, 8, 12, 4, 1]);
+        assert_eq!(petrial.vertices, cube.vertices);
+
+        // Only polyhedra have Petrials.
+        let mut square = Concrete::polygon(4);
+        assert!(!square.petrial_mut());
+        crate::test(&square, [1, 4, 4, 1]);
+    }
+
+    /// Checks that uniform truncations of a cube have unit edges.
+    #[test]
+    fn uniform_truncate() {
+        let cube = Concrete::cube();
+
+        let truncated = cube.uniform_truncate(vec![0, 1]);
+        crate::test(&truncated, [1, 24, 36, 14, 1]);
+        assert!(truncated.is_equilateral_with(1.0));
+
+        let cantellated = cube.uniform_truncate(vec![0, 2]);
+        crate::test(&cantellated, [1, 24, 48, 26, 1]);
+        assert!(cantellated.is_equilateral_with(1.0));
+    }
+
+    /// Checks truncations of a cube with different depths.
+    #[test]
+    fn truncate() {
+        let cube = Concrete::cube();
+
+        let truncated = cube.truncate(2.0 - f64::SQRT_2);
+        crate::test(&truncated, [1, 24, 36, 14, 1]);
+        assert!(truncated.is_equilateral_with(f64::SQRT_2 - 1.0));
+
+        let rectified = cube.truncate(1.0);
+        crate::test(&rectified, [1, 12, 24, 14, 1]);
+        assert!(rectified.is_equilateral_with(f64::SQRT_2 / 2.0));
+    }
+
+    /// Checks that points and dyads can be truncated.
+    #[test]
+    fn truncate_small() {
+        crate::test(&Concrete::point().truncate(0.5), [1, 1]);
+
+        let dyad = Concrete::dyad().truncate(0.5);
+        crate::test(&dyad, [1, 2, 1]);
+        assert!(dyad.is_equilateral_with(0.5));
+    }
+
     #[test]
     fn orthoplex() {
         for n in 1..=6 {
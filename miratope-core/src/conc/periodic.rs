@@ -0,0 +1,528 @@
+//! Detects the translational symmetry of patches of Euclidean tilings, and
+//! names the symmetry group of the whole tiling.
+//!
+//! A patch is finite, so no translation maps it onto itself. Instead, we say
+//! that a translation is a period whenever it sends most edges onto edges,
+//! and no edge whose endpoints are sent onto vertices onto anything else.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt::Display,
+    vec,
+};
+
+use super::{symmetry::SymmetryGroup, Concrete, ConcretePolytope};
+use crate::{
+    abs::Ranked,
+    float::Float,
+    geometry::{Matrix, Point, PointOrd, Vector},
+    group::Group,
+};
+
+/// The least fraction of the edges a translation must send onto other edges
+/// for it to be a period. Vertices are used instead when there's no edges.
+const MIN_OVERLAP: f64 = 0.5;
+
+/// The largest coefficient of the lattice vectors we search through, when
+/// looking for the images of the basis, or for the translations that turn a
+/// glide reflection into a mirror.
+const SEARCH_RANGE: i32 = 2;
+
+/// A lattice of periods spanning the whole space.
+struct Lattice {
+    /// The matrix whose columns are the basis of the lattice.
+    basis: Matrix<f64>,
+
+    /// The inverse of the basis matrix.
+    inverse: Matrix<f64>,
+}
+
+impl Lattice {
+    /// Builds a lattice from a basis, if it spans the whole space.
+    fn new(basis: &[Vector<f64>]) -> Option<Self> {
+        let basis = Matrix::from_columns(basis);
+        let inverse = basis.clone().try_inverse()?;
+        Some(Self { basis, inverse })
+    }
+
+    /// Returns the coordinates of a point in the lattice basis, reduced so
+    /// that they're all in `[0, 1)`. Two points differ by a period exactly
+    /// when their reduced coordinates are the same.
+    fn reduce(&self, p: &Point<f64>) -> PointOrd<f64> {
+        let coords = (&self.inverse * p).map(|x| {
+            let frac = x - x.floor();
+            if frac > 1.0 - f64::EPS {
+                0.0
+            } else {
+                frac
+            }
+        });
+        PointOrd::new(coords)
+    }
+
+    /// Moves a point into the cell spanned by the basis.
+    fn reduce_point(&self, p: &Point<f64>) -> Point<f64> {
+        &self.basis * self.reduce(p).matrix()
+    }
+
+    /// Returns every nonzero lattice vector whose coefficients have absolute
+    /// value at most [`SEARCH_RANGE`].
+    fn vectors(&self) -> Vec<Vector<f64>> {
+        let dim = self.basis.ncols();
+        let mut coeffs = vec![-SEARCH_RANGE; dim];
+        let mut vectors = Vec::new();
+
+        loop {
+            if coeffs.iter().any(|&c| c != 0) {
+                let coeffs = Vector::from_iterator(dim, coeffs.iter().map(|&c| c as f64));
+                vectors.push(&self.basis * coeffs);
+            }
+
+            // Goes to the next tuple of coefficients.
+            let Some(idx) = coeffs.iter().position(|&c| c < SEARCH_RANGE) else {
+                return vectors;
+            };
+            coeffs[idx] += 1;
+            for c in &mut coeffs[..idx] {
+                *c = -SEARCH_RANGE;
+            }
+        }
+    }
+
+    /// Returns the linear parts of the symmetries of the lattice, i.e. the
+    /// orthogonal maps sending it onto itself.
+    fn automorphisms(&self) -> Vec<Matrix<f64>> {
+        let basis: Vec<Vector<f64>> = self.basis.column_iter().map(|c| c.into_owned()).collect();
+        let candidates = self.vectors();
+        let mut automorphisms = Vec::new();
+        let mut images: Vec<usize> = Vec::with_capacity(basis.len());
+        let mut next = 0;
+
+        // Walks through every way to send the basis to lattice vectors with
+        // the same inner products.
+        loop {
+            let level = images.len();
+            if level < basis.len() {
+                let b = &basis[level];
+                let found = (next..candidates.len()).find(|&idx| {
+                    let c = &candidates[idx];
+                    (c.norm_squared() - b.norm_squared()).abs() < f64::EPS
+                        && images
+                            .iter()
+                            .zip(&basis)
+                            .all(|(&i, a)| (c.dot(&candidates[i]) - b.dot(a)).abs() < f64::EPS)
+                });
+
+                if let Some(idx) = found {
+                    images.push(idx);
+                    next = 0;
+                    continue;
+                }
+            } else {
+                let columns: Vec<Vector<f64>> = images.iter().map(|&i| candidates[i].clone()).collect();
+                automorphisms.push(Matrix::from_columns(&columns) * &self.inverse);
+            }
+
+            // Backtracks to the last basis vector with untried images.
+            match images.pop() {
+                Some(idx) => next = idx + 1,
+                None => return automorphisms,
+            }
+        }
+    }
+}
+
+/// The translational symmetry of a patch of a tiling, along with the
+/// symmetries of the whole tiling modulo translations.
+#[derive(Clone, Debug, Default)]
+pub struct Periodicity {
+    /// A basis of the lattice of periods, made out of the shortest periods.
+    /// This is empty if the patch isn't periodic.
+    pub basis: Vec<Vector<f64>>,
+
+    /// The affine symmetries of the tiling modulo translations, each given
+    /// by its linear part and its translation part. These are only found when
+    /// the periods span the whole space.
+    pub symmetries: Vec<(Matrix<f64>, Vector<f64>)>,
+}
+
+impl Periodicity {
+    /// Returns the point group of the tiling, made out of the linear parts of
+    /// its symmetries.
+    pub fn point_group(&self) -> Option<Group<vec::IntoIter<Matrix<f64>>>> {
+        let dim = self.basis.first()?.len();
+        let linear: Vec<Matrix<f64>> = self.symmetries.iter().map(|(a, _)| a.clone()).collect();
+
+        // Safety: the linear parts of the symmetries of a tiling form a group.
+        (!linear.is_empty()).then(|| unsafe { Group::new(dim, linear.into_iter()) })
+    }
+
+    /// Names the wallpaper group of a planar tiling, using the usual
+    /// crystallographic notation.
+    pub fn wallpaper_group(&self) -> Option<&'static str> {
+        if self.basis.len() != 2 || self.basis[0].len() != 2 || self.symmetries.is_empty() {
+            return None;
+        }
+
+        let lattice = Lattice::new(&self.basis)?;
+        let vectors = lattice.vectors();
+        let cross = |u: &Vector<f64>, v: &Vector<f64>| u[0] * v[1] - u[1] * v[0];
+
+        // The direction of the line fixed by a reflection.
+        let axis = |a: &Matrix<f64>| {
+            let fixed = Matrix::identity(2, 2) + a;
+            let col = fixed.column_iter().max_by(|c, d| c.norm().total_cmp(&d.norm())).unwrap();
+            col / col.norm()
+        };
+
+        // Whether some reflection with this linear part fixes a line, rather
+        // than being a glide reflection.
+        let is_mirror = |(a, b): &(Matrix<f64>, Vector<f64>)| {
+            let u = axis(a);
+            let b = lattice.reduce_point(b);
+            (u.dot(&b)).abs() < f64::EPS || vectors.iter().any(|t| u.dot(&(&b + t)).abs() < f64::EPS)
+        };
+
+        // Whether the lattice has a basis made out of a vector along the axis
+        // of a reflection and a vector perpendicular to it.
+        let is_centered = |a: &Matrix<f64>| {
+            let u = axis(a);
+            let shortest = |along: bool| {
+                vectors
+                    .iter()
+                    .filter(|t| if along { cross(&u, t).abs() < f64::EPS } else { u.dot(t).abs() < f64::EPS })
+                    .min_by(|s, t| s.norm().total_cmp(&t.norm()))
+            };
+            match (shortest(true), shortest(false)) {
+                (Some(s), Some(t)) => (cross(s, t).abs() - lattice.basis.determinant().abs()).abs() > f64::EPS,
+                _ => true,
+            }
+        };
+
+        // Whether the axis of a reflection is along a shortest period.
+        let along_shortest = |a: &Matrix<f64>| cross(&axis(a), &self.basis[0]).abs() < f64::EPS;
+
+        let rotations = self.symmetries.iter().filter(|(a, _)| a.determinant() > 0.0).count();
+        let reflections: Vec<_> = self.symmetries.iter().filter(|(a, _)| a.determinant() < 0.0).collect();
+
+        let Some(&first) = reflections.first() else {
+            return match rotations {
+                1 => Some("p1"),
+                2 => Some("p2"),
+                3 => Some("p3"),
+                4 => Some("p4"),
+                6 => Some("p6"),
+                _ => None,
+            };
+        };
+
+        match rotations {
+            1 if is_centered(&first.0) => Some("cm"),
+            1 if is_mirror(first) => Some("pm"),
+            1 => Some("pg"),
+            2 if is_centered(&first.0) => Some("cmm"),
+            2 => match reflections.iter().filter(|r| is_mirror(r)).count() {
+                2 => Some("pmm"),
+                1 => Some("pmg"),
+                _ => Some("pgg"),
+            },
+            3 if reflections.iter().any(|(a, _)| along_shortest(a)) => Some("p31m"),
+            3 => Some("p3m1"),
+            4 if reflections.iter().any(|r| along_shortest(&r.0) && is_mirror(r)) => Some("p4m"),
+            4 => Some("p4g"),
+            6 => Some("p6m"),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Periodicity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = self.basis.len();
+        if count == 0 {
+            return write!(f, "not periodic");
+        }
+
+        if let Some(name) = self.wallpaper_group() {
+            return write!(f, "wallpaper group {}", name);
+        }
+
+        write!(f, "{} independent period{}", count, if count == 1 { "" } else { "s" })?;
+        match self.point_group() {
+            Some(group) => write!(f, ", point group {}", group.classify()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Concrete {
+    /// Returns the edges of the polytope, as sorted pairs of vertex indices.
+    fn edge_pairs(&self) -> Vec<(usize, usize)> {
+        (0..self.edge_count())
+            .map(|idx| {
+                let subs = &self[(2, idx)].subs;
+                (subs[0].min(subs[1]), subs[0].max(subs[1]))
+            })
+            .collect()
+    }
+
+    /// Returns whether a translation is a period of the polytope. See the
+    /// [module docs](self).
+    fn is_period(
+        &self,
+        index_of: &BTreeMap<PointOrd<f64>, usize>,
+        edges: &[(usize, usize)],
+        edge_set: &HashSet<(usize, usize)>,
+        t: &Vector<f64>,
+    ) -> bool {
+        let image = |t: &Vector<f64>| -> Vec<Option<usize>> {
+            self.vertices
+                .iter()
+                .map(|v| index_of.get(&PointOrd::new(v + t)).copied())
+                .collect()
+        };
+
+        let forward = image(t);
+        let backward = image(&-t);
+        let mut overlap = 0;
+
+        for &(a, b) in edges {
+            for image in [&forward, &backward] {
+                if let (Some(c), Some(d)) = (image[a], image[b]) {
+                    if !edge_set.contains(&(c.min(d), c.max(d))) {
+                        return false;
+                    }
+                    overlap += 1;
+                }
+            }
+        }
+
+        // Both directions are counted.
+        let (overlap, total) = if edges.is_empty() {
+            let count = |image: &[Option<usize>]| image.iter().flatten().count();
+            (count(&forward) + count(&backward), self.vertices.len())
+        } else {
+            (overlap, edges.len())
+        };
+        overlap as f64 >= 2.0 * MIN_OVERLAP * total as f64
+    }
+
+    /// Returns a basis of the lattice of periods of a patch of a tiling, made
+    /// out of the shortest periods. This is empty if the patch isn't
+    /// periodic.
+    ///
+    /// Every period is the difference between two vertices, so we measure
+    /// them from the vertex closest to the center of the patch.
+    pub fn periods(&self) -> Vec<Vector<f64>> {
+        let Some(center) = self.gravicenter() else {
+            return Vec::new();
+        };
+        let dim = self.dim_or();
+        let base = self
+            .vertices
+            .iter()
+            .min_by(|v, w| (*v - &center).norm().total_cmp(&(*w - &center).norm()))
+            .unwrap();
+
+        let mut candidates: Vec<Vector<f64>> =
+            self.vertices.iter().map(|v| v - base).filter(|t| t.norm() > f64::EPS).collect();
+        candidates.sort_by(|s, t| s.norm().total_cmp(&t.norm()));
+
+        let index_of: BTreeMap<PointOrd<f64>, usize> =
+            self.vertices.iter().map(|v| PointOrd::new(v.clone())).zip(0..).collect();
+        let edges = self.edge_pairs();
+        let edge_set: HashSet<(usize, usize)> = edges.iter().copied().collect();
+
+        // The shortest independent periods form a basis in up to three
+        // dimensions.
+        let mut basis: Vec<Vector<f64>> = Vec::new();
+        for t in candidates {
+            if basis.len() == dim {
+                break;
+            }
+
+            let mut columns = basis.clone();
+            columns.push(t.clone());
+            if Matrix::from_columns(&columns).rank(f64::EPS) == columns.len()
+                && self.is_period(&index_of, &edges, &edge_set, &t)
+            {
+                basis.push(t);
+            }
+        }
+
+        basis
+    }
+
+    /// Finds the symmetries of the tiling a patch comes from, modulo a
+    /// lattice of periods.
+    ///
+    /// The vertices and edges are put into classes of translates. The linear
+    /// part of a symmetry is a symmetry of the lattice, and once we know where
+    /// it sends a single vertex, we can check whether it sends every class of
+    /// vertices and edges onto another.
+    fn symmetries_mod(&self, lattice: &Lattice) -> Vec<(Matrix<f64>, Vector<f64>)> {
+        let mut class_of: BTreeMap<PointOrd<f64>, usize> = BTreeMap::new();
+        let mut reps: Vec<&Point<f64>> = Vec::new();
+        let classes: Vec<usize> = self
+            .vertices
+            .iter()
+            .map(|v| {
+                *class_of.entry(lattice.reduce(v)).or_insert_with(|| {
+                    reps.push(v);
+                    reps.len() - 1
+                })
+            })
+            .collect();
+
+        let mut edge_classes = BTreeSet::new();
+        for (a, b) in self.edge_pairs() {
+            let (v, w) = (&self.vertices[a], &self.vertices[b]);
+            edge_classes.insert((classes[a], PointOrd::new(w - v)));
+            edge_classes.insert((classes[b], PointOrd::new(v - w)));
+        }
+
+        let mut symmetries = Vec::new();
+        for a in lattice.automorphisms() {
+            let translation = reps.iter().find_map(|&target| {
+                let b = target - &a * reps[0];
+                let images: Vec<usize> = reps
+                    .iter()
+                    .map(|&p| class_of.get(&lattice.reduce(&(&a * p + &b))).copied())
+                    .collect::<Option<_>>()?;
+
+                // The classes must be permuted, and so must the edges.
+                if images.iter().collect::<HashSet<_>>().len() != reps.len() {
+                    return None;
+                }
+                edge_classes
+                    .iter()
+                    .all(|(c, d)| edge_classes.contains(&(images[*c], PointOrd::new(&a * d.matrix()))))
+                    .then_some(b)
+            });
+
+            if let Some(b) = translation {
+                symmetries.push((a, b));
+            }
+        }
+
+        symmetries
+    }
+
+    /// Finds the periods of a patch of a tiling, and the symmetries of the
+    /// whole tiling modulo translations whenever the periods span the whole
+    /// space.
+    pub fn periodicity(&self) -> Periodicity {
+        let basis = self.periods();
+
+        // Nothing is periodic in zero dimensions, and there's no lattice to
+        // find the symmetries modulo.
+        if basis.is_empty() {
+            return Periodicity::default();
+        }
+
+        let symmetries = if basis.len() == self.dim_or() {
+            Lattice::new(&basis).map_or_else(Vec::new, |lattice| self.symmetries_mod(&lattice))
+        } else {
+            Vec::new()
+        };
+
+        Periodicity { basis, symmetries }
+    }
+
+    /// Computes the symmetries of a patch of a tiling that map the patch onto
+    /// itself, along with a list of vertex mappings. These are the elements of
+    /// the affine group of the tiling modulo translations that fix the patch.
+    ///
+    /// A symmetry of a finite set of points fixes their center, so these are
+    /// found as the linear symmetries about it. The matrices returned are the
+    /// linear parts of the symmetries.
+    pub fn patch_symmetry_group(&self) -> Option<SymmetryGroup> {
+        let mut moved = self.clone();
+        if let Some(center) = self.gravicenter() {
+            for v in moved.vertices.iter_mut() {
+                *v -= &center;
+            }
+        }
+
+        moved.get_symmetry_group()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{file::FromFile, Polytope};
+
+    use vec_like::*;
+
+    /// Builds an `n` by `n` patch of a tiling by parallelograms with given
+    /// sides, with a corner at a given point.
+    fn grid(n: usize, a: [f64; 2], b: [f64; 2], corner: [f64; 2]) -> Concrete {
+        let mut off = format!("2OFF {} {}\n", (n + 1) * (n + 1), n * n);
+        for i in 0..=n {
+            for j in 0..=n {
+                let (i, j) = (i as f64, j as f64);
+                off.push_str(&format!(
+                    "{} {}\n",
+                    corner[0] + i * a[0] + j * b[0],
+                    corner[1] + i * a[1] + j * b[1]
+                ));
+            }
+        }
+
+        let idx = |i: usize, j: usize| i * (n + 1) + j;
+        for i in 0..n {
+            for j in 0..n {
+                off.push_str(&format!(
+                    "4 {} {} {} {}\n",
+                    idx(i, j),
+                    idx(i + 1, j),
+                    idx(i + 1, j + 1),
+                    idx(i, j + 1)
+                ));
+            }
+        }
+
+        Concrete::from_off(&off).unwrap()
+    }
+
+    /// Finds the periods of some patches.
+    #[test]
+    fn periods() {
+        let square = grid(4, [1.0, 0.0], [0.0, 1.0], [0.3, -1.2]);
+        let periods = square.periods();
+        assert_eq!(periods.len(), 2);
+        assert!(periods.iter().all(|t| (t.norm() - 1.0).abs() < f64::EPS));
+
+        assert!(Concrete::polygon(5).periods().is_empty());
+        assert_eq!(Concrete::polygon(5).periodicity().to_string(), "not periodic");
+    }
+
+    /// A point and the nullitope aren't periodic.
+    #[test]
+    fn point() {
+        assert!(Concrete::point().periodicity().basis.is_empty());
+        assert!(Concrete::nullitope().periodicity().symmetries.is_empty());
+        assert_eq!(Concrete::point().periodicity().to_string(), "not periodic");
+    }
+
+    /// Names the wallpaper groups of some tilings by parallelograms.
+    #[test]
+    fn wallpaper() {
+        let name = |a: [f64; 2], b: [f64; 2]| grid(4, a, b, [0.5, 0.25]).periodicity().wallpaper_group();
+
+        assert_eq!(name([1.0, 0.0], [0.0, 1.0]), Some("p4m"));
+        assert_eq!(name([2.0, 0.0], [0.0, 1.0]), Some("pmm"));
+        assert_eq!(name([1.0, 0.0], [0.3, 1.1]), Some("p2"));
+        assert_eq!(name([1.0, 0.5], [1.0, -0.5]), Some("cmm"));
+    }
+
+    /// Finds the symmetries of a patch that isn't centered at the origin.
+    #[test]
+    fn patch() {
+        let square = grid(3, [1.0, 0.0], [0.0, 1.0], [5.0, 2.0]);
+        let (group, vertex_map) = square.patch_symmetry_group().unwrap();
+        assert_eq!(group.count(), 8);
+        assert_eq!(vertex_map.len(), 8);
+    }
+}
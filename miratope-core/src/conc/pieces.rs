@@ -0,0 +1,579 @@
+//! Splits the faces of a self-intersecting polyhedron along the lines where
+//! they cross each other, and keeps the pieces on its outer surface. This is
+//! what a physical model of the polyhedron looks like.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::{AbstractBuilder, Ranked, SubelementList, Subelements},
+    float::Float,
+    geometry::{Point, PointOrd, Subspace},
+};
+
+use nalgebra::{Matrix3, Vector3};
+use partitions::{partition_vec, PartitionVec};
+use vec_like::*;
+
+/// A convex polygon in 3D space, given by its vertices in order.
+pub(super) type Polygon = Vec<Vector3<f64>>;
+
+/// Directions in which rays are cast to test whether a point is enclosed by
+/// the polyhedron. They're chosen so as to not be parallel to anything
+/// reasonable.
+const RAY_DIRECTIONS: [[f64; 3]; 3] = [
+    [0.573_462_8, 0.613_093_2, 0.543_362_1],
+    [-0.681_243_7, 0.271_987_3, -0.679_637_6],
+    [0.143_571_9, -0.901_325_4, 0.408_662_2],
+];
+
+/// Converts a point into a 3D vector.
+pub(super) fn vector3(p: &Point<f64>) -> Vector3<f64> {
+    Vector3::new(p[0], p[1], p[2])
+}
+
+/// Splits a convex polygon by a plane with a given normal and offset. Returns
+/// the parts in front of and behind the plane, if they're not degenerate.
+pub(crate) fn split_polygon(
+    polygon: &[Vector3<f64>],
+    normal: &Vector3<f64>,
+    offset: f64,
+) -> (Option<Polygon>, Option<Polygon>) {
+    let dist: Vec<f64> = polygon.iter().map(|p| normal.dot(p) - offset).collect();
+
+    if dist.iter().all(|&d| d > -f64::EPS) {
+        return (Some(polygon.to_vec()), None);
+    }
+    if dist.iter().all(|&d| d < f64::EPS) {
+        return (None, Some(polygon.to_vec()));
+    }
+
+    let mut front = Vec::new();
+    let mut back = Vec::new();
+    let n = polygon.len();
+
+    for i in 0..n {
+        let j = (i + 1) % n;
+        let (p, dp, dq) = (&polygon[i], dist[i], dist[j]);
+
+        if dp >= -f64::EPS {
+            front.push(*p);
+        }
+        if dp <= f64::EPS {
+            back.push(*p);
+        }
+
+        // The edge crosses the plane.
+        if (dp > f64::EPS && dq < -f64::EPS) || (dp < -f64::EPS && dq > f64::EPS) {
+            let t = dp / (dp - dq);
+            let x = p + (polygon[j] - p) * t;
+            front.push(x);
+            back.push(x);
+        }
+    }
+
+    let valid = |polygon: Polygon| (polygon.len() >= 3).then_some(polygon);
+    (valid(front), valid(back))
+}
+
+/// Returns the distance along a ray at which it hits a triangle, if it does.
+pub(crate) fn ray_triangle(
+    origin: &Vector3<f64>,
+    dir: &Vector3<f64>,
+    triangle: &[Vector3<f64>; 3],
+) -> Option<f64> {
+    let e1 = triangle[1] - triangle[0];
+    let e2 = triangle[2] - triangle[0];
+    let p = dir.cross(&e2);
+    let det = e1.dot(&p);
+    if det.abs() < f64::EPS {
+        return None;
+    }
+
+    let s = origin - triangle[0];
+    let u = s.dot(&p) / det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(&e1);
+    let v = dir.dot(&q) / det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = e2.dot(&q) / det;
+    (t > 0.0).then_some(t)
+}
+
+/// Returns the interval of parameters along a direction covered by the
+/// intersection of a triangle with a plane, if it's not empty.
+fn plane_interval(
+    triangle: &[Vector3<f64>; 3],
+    normal: &Vector3<f64>,
+    offset: f64,
+    dir: &Vector3<f64>,
+) -> Option<(f64, f64)> {
+    let dist = triangle.map(|p| normal.dot(&p) - offset);
+    let mut params = Vec::new();
+
+    for i in 0..3 {
+        let j = (i + 1) % 3;
+        if dist[i].abs() < f64::EPS {
+            params.push(dir.dot(&triangle[i]));
+        } else if dist[i] * dist[j] < 0.0 && dist[j].abs() >= f64::EPS {
+            let t = dist[i] / (dist[i] - dist[j]);
+            params.push(dir.dot(&(triangle[i] + (triangle[j] - triangle[i]) * t)));
+        }
+    }
+
+    let min = params.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = params.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    (max - min > f64::EPS).then_some((min, max))
+}
+
+/// Merges a list of intervals into disjoint ones, in increasing order.
+fn merge_intervals(mut intervals: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    intervals.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+    let mut merged: Vec<(f64, f64)> = Vec::new();
+
+    for (min, max) in intervals {
+        match merged.last_mut() {
+            Some(last) if min <= last.1 + f64::EPS => last.1 = last.1.max(max),
+            _ => merged.push((min, max)),
+        }
+    }
+
+    merged
+}
+
+impl Concrete {
+    /// Returns the segments along which the faces of a polyhedron cross each
+    /// other. These are the visually apparent edges of star polyhedra that
+    /// aren't edges of the polytope. Faces sharing an edge aren't checked
+    /// against each other. Returns `None` if the polytope isn't a polyhedron
+    /// in 3D space.
+    pub fn face_intersections(&self) -> Option<Vec<[Point<f64>; 2]>> {
+        if self.rank() != 4 || self.dim() != Some(3) {
+            return None;
+        }
+
+        let face_triangles = self.face_triangles();
+        let planes: Vec<Option<(Vector3<f64>, f64)>> = (0..self.el_count(3))
+            .map(|idx| self.face_plane(idx))
+            .collect();
+
+        let mut segments = Vec::new();
+        for f in 0..planes.len() {
+            let (nf, of) = match planes[f] {
+                Some(plane) => plane,
+                None => continue,
+            };
+
+            for g in f + 1..planes.len() {
+                let (ng, og) = match planes[g] {
+                    Some(plane) => plane,
+                    None => continue,
+                };
+
+                // Parallel faces don't cross.
+                let dir = nf.cross(&ng);
+                if dir.norm() < f64::EPS {
+                    continue;
+                }
+                let dir = dir.normalize();
+
+                // Faces sharing an edge only meet along it.
+                if self[(3, f)].subs.iter().any(|e| self[(3, g)].subs.contains(e)) {
+                    continue;
+                }
+
+                let intervals = |face: usize, normal: &Vector3<f64>, offset: f64| {
+                    merge_intervals(
+                        face_triangles[face]
+                            .iter()
+                            .filter_map(|t| plane_interval(t, normal, offset, &dir))
+                            .collect(),
+                    )
+                };
+                let f_intervals = intervals(f, &ng, og);
+                let g_intervals = intervals(g, &nf, of);
+                if f_intervals.is_empty() || g_intervals.is_empty() {
+                    continue;
+                }
+
+                // A point on the line where both planes meet.
+                let base = match Matrix3::from_rows(&[nf.transpose(), ng.transpose(), dir.transpose()])
+                    .try_inverse()
+                {
+                    Some(inv) => inv * Vector3::new(of, og, 0.0),
+                    None => continue,
+                };
+                let point = |t: f64| Point::from_column_slice((base + dir * t).as_slice());
+
+                // Intersects both lists of intervals.
+                let (mut i, mut j) = (0, 0);
+                while i < f_intervals.len() && j < g_intervals.len() {
+                    let min = f_intervals[i].0.max(g_intervals[j].0);
+                    let max = f_intervals[i].1.min(g_intervals[j].1);
+                    if max - min > f64::EPS {
+                        segments.push([point(min), point(max)]);
+                    }
+
+                    if f_intervals[i].1 < g_intervals[j].1 {
+                        i += 1;
+                    } else {
+                        j += 1;
+                    }
+                }
+            }
+        }
+
+        Some(segments)
+    }
+
+    /// Returns the unit normal and offset of the plane of a face, or `None` if
+    /// the face is degenerate.
+    pub(super) fn face_plane(&self, idx: usize) -> Option<(Vector3<f64>, f64)> {
+        let vertices = self.abs.element_vertices(3, idx)?;
+        let subspace = Subspace::from_points(vertices.iter().map(|&v| &self.vertices[v]));
+        if subspace.rank() != 2 {
+            return None;
+        }
+
+        let normal = vector3(&subspace.basis[0])
+            .cross(&vector3(&subspace.basis[1]))
+            .normalize();
+        Some((normal, normal.dot(&vector3(&subspace.offset))))
+    }
+
+    /// Returns the triangles of the triangulation of the polytope's faces, as
+    /// 3D vectors, grouped by face.
+    fn face_triangles(&self) -> Vec<Vec<[Vector3<f64>; 3]>> {
+        let triangulation = self.triangulation();
+        let mut triangles = vec![Vec::new(); self.el_count(3)];
+
+        for triangle in triangulation.iter() {
+            triangles[triangle.face]
+                .push(triangle.vertices.map(|v| vector3(triangulation.vertex(self, v))));
+        }
+
+        triangles
+    }
+
+    /// Splits the faces of a polyhedron along their mutual intersections, and
+    /// builds the polyhedron made out of the pieces on its outer surface. A
+    /// point is considered to be enclosed by the polyhedron when rays cast
+    /// from it both ways along a few generic directions all hit a face, and a
+    /// piece is on the outer surface when exactly one of its sides is enclosed.
+    ///
+    /// The pieces coming from the same face are merged back together whenever
+    /// they share an edge. Returns `None` if the polytope isn't a polyhedron
+    /// in 3D space.
+    ///
+    /// The result is always a closed surface, but it might not be a valid
+    /// polytope in degenerate cases, like when two pieces of the surface touch
+    /// along an edge.
+    pub fn outer_shell(&self) -> Option<Self> {
+        if self.rank() != 4 || self.dim() != Some(3) {
+            return None;
+        }
+
+        let face_triangles = self.face_triangles();
+        let all_triangles: Vec<_> = face_triangles.iter().flatten().copied().collect();
+        let scale = self
+            .vertices
+            .iter()
+            .map(|v| v.norm())
+            .fold(1.0, f64::max);
+
+        // The planes of the faces, or `None` for degenerate faces.
+        let planes: Vec<Option<(Vector3<f64>, f64)>> = (0..self.el_count(3))
+            .map(|idx| self.face_plane(idx))
+            .collect();
+
+        // Whether a face has vertices strictly on both sides of a plane.
+        let straddles = |face: usize, (normal, offset): &(Vector3<f64>, f64)| {
+            let (mut front, mut back) = (false, false);
+            for d in face_triangles[face].iter().flatten().map(|p| normal.dot(p) - offset) {
+                front |= d > f64::EPS;
+                back |= d < -f64::EPS;
+            }
+            front && back
+        };
+
+        // Whether a point is enclosed by the polyhedron. We cast rays both ways
+        // along every direction, as a point in a notch of a star polyhedron
+        // might only be able to escape through one side.
+        let enclosed = |p: &Vector3<f64>| {
+            RAY_DIRECTIONS
+                .iter()
+                .flat_map(|&dir| [Vector3::from(dir), -Vector3::from(dir)])
+                .all(|dir| {
+                    all_triangles
+                        .iter()
+                        .any(|triangle| ray_triangle(p, &dir, triangle).is_some())
+                })
+        };
+
+        // The pieces on the outer surface, together with the faces they come
+        // from and whether they're oriented along their face's normal.
+        let mut pieces = Vec::new();
+        let mut centers = BTreeSet::new();
+
+        for (face, plane) in planes.iter().enumerate() {
+            let plane = match plane {
+                Some(plane) => plane,
+                None => continue,
+            };
+            let normal = &plane.0;
+
+            let mut face_pieces: Vec<Polygon> =
+                face_triangles[face].iter().map(|t| t.to_vec()).collect();
+
+            // Cuts the face by every plane that crosses it.
+            for (other, other_plane) in planes.iter().enumerate() {
+                let other_plane = match other_plane {
+                    Some(other_plane) if other != face => other_plane,
+                    _ => continue,
+                };
+
+                if !straddles(other, plane) || !straddles(face, other_plane) {
+                    continue;
+                }
+
+                let mut cut_pieces = Vec::with_capacity(face_pieces.len());
+                for piece in &face_pieces {
+                    let (front, back) = split_polygon(piece, &other_plane.0, other_plane.1);
+                    cut_pieces.extend(front);
+                    cut_pieces.extend(back);
+                }
+                face_pieces = cut_pieces;
+            }
+
+            for mut piece in face_pieces {
+                let center = piece.iter().sum::<Vector3<f64>>() / piece.len() as f64;
+                let offset = normal * (scale * 1e-6);
+
+                // Coplanar faces might give the same piece twice.
+                let center_ord = PointOrd::new(Point::from_column_slice(center.as_slice()));
+                if centers.contains(&center_ord) {
+                    continue;
+                }
+
+                let front = enclosed(&(center + offset));
+                let back = enclosed(&(center - offset));
+                if front == back {
+                    continue;
+                }
+
+                // We orient the piece so that its normal points outwards.
+                let along_normal = back;
+                if piece_normal(&piece).dot(normal) < 0.0 {
+                    piece.reverse();
+                }
+                if !along_normal {
+                    piece.reverse();
+                }
+
+                centers.insert(center_ord);
+                pieces.push((piece, face, along_normal));
+            }
+        }
+
+        Some(Self::from_pieces(pieces))
+    }
+
+    /// Builds a polyhedron out of oriented convex pieces, welding their
+    /// vertices, splitting edges at T-junctions, and merging the pieces that
+    /// come from the same face and share an edge.
+    pub(super) fn from_pieces(pieces: Vec<(Polygon, usize, bool)>) -> Self {
+        // Welds together the vertices of the pieces.
+        let mut vertex_idx = BTreeMap::new();
+        let mut vertices = Vec::new();
+        let mut cycles: Vec<Vec<usize>> = pieces
+            .iter()
+            .map(|(piece, _, _)| {
+                let mut cycle: Vec<usize> = piece
+                    .iter()
+                    .map(|p| {
+                        let p = Point::from_column_slice(p.as_slice());
+                        *vertex_idx.entry(PointOrd::new(p.clone())).or_insert_with(|| {
+                            vertices.push(p);
+                            vertices.len() - 1
+                        })
+                    })
+                    .collect();
+                cycle.dedup();
+                while cycle.len() > 1 && cycle.first() == cycle.last() {
+                    cycle.pop();
+                }
+                cycle
+            })
+            .collect();
+
+        // Splits the edges of every piece at the vertices lying on them.
+        for cycle in &mut cycles {
+            let mut new_cycle = Vec::with_capacity(cycle.len());
+            for i in 0..cycle.len() {
+                let (a, b) = (cycle[i], cycle[(i + 1) % cycle.len()]);
+                let (pa, pb) = (&vertices[a], &vertices[b]);
+                let dir = pb - pa;
+                let len_sq = dir.norm_squared();
+
+                let mut between: Vec<(f64, usize)> = (0..vertices.len())
+                    .filter(|&v| v != a && v != b)
+                    .filter_map(|v| {
+                        let t = (&vertices[v] - pa).dot(&dir) / len_sq;
+                        (t > 0.0
+                            && t < 1.0
+                            && (pa + &dir * t - &vertices[v]).norm() < f64::EPS)
+                            .then_some((t, v))
+                    })
+                    .collect();
+                between.sort_unstable_by(|x, y| x.0.total_cmp(&y.0));
+
+                new_cycle.push(a);
+                new_cycle.extend(between.into_iter().map(|(_, v)| v));
+            }
+            *cycle = new_cycle;
+        }
+
+        // Pieces from the same face with the same orientation that share an
+        // edge get merged. Their shared edges are removed.
+        let mut directed = HashMap::new();
+        for (piece, cycle) in cycles.iter().enumerate() {
+            for i in 0..cycle.len() {
+                directed.insert((cycle[i], cycle[(i + 1) % cycle.len()]), piece);
+            }
+        }
+
+        let mut components: PartitionVec<()> = partition_vec![(); cycles.len()];
+        let same_group = |p: usize, q: usize| pieces[p].1 == pieces[q].1 && pieces[p].2 == pieces[q].2;
+        let mut interior = BTreeSet::new();
+
+        for (&(a, b), &piece) in &directed {
+            if let Some(&other) = directed.get(&(b, a))
+                && same_group(piece, other)
+            {
+                components.union(piece, other);
+                interior.insert((a.min(b), a.max(b)));
+            }
+        }
+
+        // Builds the edges and faces.
+        let mut edge_idx = HashMap::new();
+        let mut edges = SubelementList::new();
+        let mut faces = SubelementList::new();
+
+        for component in components.all_sets() {
+            let mut face = BTreeSet::new();
+
+            for (piece, _) in component {
+                let cycle = &cycles[piece];
+                for i in 0..cycle.len() {
+                    let (a, b) = (cycle[i], cycle[(i + 1) % cycle.len()]);
+                    let key = (a.min(b), a.max(b));
+                    if interior.contains(&key) {
+                        continue;
+                    }
+
+                    let idx = *edge_idx.entry(key).or_insert_with(|| {
+                        edges.push(Subelements::from(vec![key.0, key.1]));
+                        edges.len() - 1
+                    });
+                    face.insert(idx);
+                }
+            }
+
+            if !face.is_empty() {
+                faces.push(face.into_iter().collect());
+            }
+        }
+
+        // Removes the vertices that ended up inside of merged faces.
+        let mut used = vec![None; vertices.len()];
+        let mut used_vertices = Vec::new();
+        for edge in edges.iter_mut() {
+            for v in edge.iter_mut() {
+                *v = *used[*v].get_or_insert_with(|| {
+                    used_vertices.push(vertices[*v].clone());
+                    used_vertices.len() - 1
+                });
+            }
+        }
+
+        let mut builder = AbstractBuilder::new();
+        builder.push(SubelementList::min());
+        builder.push(SubelementList::vertices(used_vertices.len()));
+        let face_count = faces.len();
+        builder.push(edges);
+        builder.push(faces);
+        builder.push(SubelementList::max(face_count));
+
+        // Safety: every edge has two vertices, and every face consists of the
+        // edges of a union of pieces. The result might still fail to be
+        // dyadic in degenerate cases, which the documentation warns about.
+        unsafe { Self::new(used_vertices, builder.build()) }
+    }
+}
+
+/// Returns a (non-normalized) normal vector of a polygon.
+fn piece_normal(polygon: &[Vector3<f64>]) -> Vector3<f64> {
+    let mut normal = Vector3::zeros();
+    for i in 0..polygon.len() {
+        normal += polygon[i].cross(&polygon[(i + 1) % polygon.len()]);
+    }
+    normal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test, Polytope};
+
+    /// The outer shell of a cube is the cube itself.
+    #[test]
+    fn cube() {
+        test(&Concrete::cube().outer_shell().unwrap(), [1, 8, 12, 6, 1]);
+    }
+
+    /// The outer shell of the stella octangula consists of 24 triangles.
+    #[test]
+    fn stella_octangula() {
+        let mut stella = Concrete::tetrahedron();
+        let mut dual = stella.clone();
+        for v in &mut dual.vertices {
+            *v = -&*v;
+        }
+        stella.comp_append(dual);
+
+        test(&stella.outer_shell().unwrap(), [1, 14, 36, 24, 1]);
+    }
+
+    /// A convex polyhedron has no face intersections.
+    #[test]
+    fn cube_intersections() {
+        assert!(Concrete::cube().face_intersections().unwrap().is_empty());
+    }
+
+    /// The faces of the stella octangula cross along the edges of an
+    /// octahedron.
+    #[test]
+    fn stella_octangula_intersections() {
+        let mut stella = Concrete::tetrahedron();
+        let mut dual = stella.clone();
+        for v in &mut dual.vertices {
+            *v = -&*v;
+        }
+        stella.comp_append(dual);
+
+        assert_eq!(stella.face_intersections().unwrap().len(), 12);
+    }
+
+    /// Only 3D polyhedra have outer shells.
+    #[test]
+    fn tesseract() {
+        assert!(Concrete::hypercube(5).outer_shell().is_none());
+    }
+}
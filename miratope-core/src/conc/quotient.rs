@@ -0,0 +1,175 @@
+//! Builds the quotient of a polytope by a group of its symmetries, like the
+//! hemi-polytopes, which identify the opposite elements of centrally
+//! symmetric polytopes.
+
+use std::fmt::Display;
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::{AbstractError, Ranked},
+    float::Float,
+    group::vertex_map::VertexMap,
+    Polytope,
+};
+
+use vec_like::*;
+
+/// Any error encountered while building a quotient.
+#[derive(Clone, Copy, Debug)]
+pub enum QuotientError {
+    /// Some permutation of the vertices doesn't come from a symmetry of the
+    /// polytope.
+    Automorphism {
+        /// The index of the permutation in the vertex map.
+        idx: usize,
+    },
+
+    /// The quotient isn't a valid polytope.
+    Abstract(AbstractError),
+}
+
+impl Display for QuotientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Automorphism { idx } => {
+                write!(f, "vertex permutation {} doesn't extend to the elements", idx)
+            }
+            Self::Abstract(err) => write!(f, "the quotient isn't a polytope: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for QuotientError {}
+
+impl From<AbstractError> for QuotientError {
+    fn from(err: AbstractError) -> Self {
+        Self::Abstract(err)
+    }
+}
+
+/// The result of building a quotient.
+pub type QuotientResult<T> = Result<T, QuotientError>;
+
+impl Concrete {
+    /// Returns the quotient of the polytope by a group of its symmetries,
+    /// given by its vertex map. See
+    /// [`Abstract::quotient_and_orbits`](crate::abs::Abstract::quotient_and_orbits).
+    ///
+    /// Each vertex of the quotient is placed at the first vertex of its
+    /// orbit. Quotients like the hemicube don't live in Euclidean space, so
+    /// the result is rarely a faithful realization.
+    pub fn quotient(&self, vertex_map: &VertexMap) -> QuotientResult<Self> {
+        let identifications = vertex_map
+            .iter()
+            .enumerate()
+            .map(|(idx, row)| self.abs.element_map(row).ok_or(QuotientError::Automorphism { idx }))
+            .collect::<QuotientResult<Vec<_>>>()?;
+
+        let (abs, orbits) = self.abs.quotient_and_orbits(&identifications)?;
+        let mut vertices = Vec::with_capacity(abs.vertex_count());
+        for (idx, &orbit) in orbits[1].iter().enumerate() {
+            if orbit == vertices.len() {
+                vertices.push(self.vertices[idx].clone());
+            }
+        }
+
+        Ok(Self::new(vertices, abs))
+    }
+
+    /// Returns the quotient of the polytope by the central inversion through
+    /// its gravicenter, or `None` if that isn't a symmetry or the quotient
+    /// isn't a polytope.
+    pub fn central_quotient(&self) -> Option<Self> {
+        let center = self.gravicenter()?;
+        let inversion = self
+            .vertices
+            .iter()
+            .map(|v| {
+                let image = &center * 2.0 - v;
+                self.vertices.iter().position(|w| (w - &image).norm() < f64::EPS)
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut vertex_map = VertexMap::identity(self.vertex_count());
+        vertex_map.push(inversion);
+        self.quotient(&vertex_map).ok()
+    }
+
+    /// Returns whether the polytope is the orientable double cover of a
+    /// non-orientable polytope, namely its
+    /// [central quotient](Self::central_quotient), like the cube is of the
+    /// hemicube.
+    pub fn is_double_cover(&self) -> bool {
+        let Some(quotient) = self.central_quotient() else {
+            return false;
+        };
+
+        let mut poly = self.clone();
+        poly.element_sort();
+        poly.orientable() && !quotient.orientable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::FromFile;
+
+    /// Returns the vertex map of the group generated by the central
+    /// inversion of a polytope centered at the origin.
+    fn central_inversion(poly: &Concrete) -> VertexMap {
+        let inversion = poly
+            .vertices
+            .iter()
+            .map(|v| poly.vertices.iter().position(|w| (v + w).norm() < 1e-9).unwrap())
+            .collect();
+
+        let mut vertex_map = VertexMap::identity(poly.vertex_count());
+        vertex_map.push(inversion);
+        vertex_map
+    }
+
+    /// Builds the hemicube and the hemi-dodecahedron, whose vertices are the
+    /// first vertices of their orbits.
+    #[test]
+    fn hemi() {
+        let cube = Concrete::cube();
+        let vertex_map = central_inversion(&cube);
+        let hemicube = cube.quotient(&vertex_map).unwrap();
+        assert_eq!(hemicube.el_count_iter().collect::<Vec<_>>(), [1, 4, 6, 3, 1]);
+
+        let firsts = (0..8).filter(|&idx| vertex_map[1][idx] > idx);
+        for (v, idx) in hemicube.vertices.iter().zip(firsts) {
+            assert_eq!(v, &cube.vertices[idx]);
+        }
+
+        let doe = Concrete::from_off(include_str!("../../../lib/3D/uniform/Regular/doe.off")).unwrap();
+        let hemi_doe = doe.quotient(&central_inversion(&doe)).unwrap();
+        assert_eq!(hemi_doe.el_count_iter().collect::<Vec<_>>(), [1, 10, 15, 6, 1]);
+    }
+
+    /// The cube and the octahedron cover their hemi-polytopes, but the
+    /// tetrahedron and the hemicube itself don't cover anything.
+    #[test]
+    fn double_cover() {
+        let cube = Concrete::cube();
+        assert!(cube.is_double_cover());
+        assert!(Concrete::orthoplex(4).is_double_cover());
+        assert!(!Concrete::simplex(4).is_double_cover());
+
+        assert!(!cube.central_quotient().unwrap().is_double_cover());
+    }
+
+    /// The tetrahedron has no central inversion, and identifying any two of
+    /// its vertices doesn't give a polytope.
+    #[test]
+    fn invalid() {
+        let tet = Concrete::simplex(4);
+        let mut vertex_map = VertexMap::identity(4);
+        vertex_map.push(vec![1, 0, 3, 2]);
+        assert!(matches!(tet.quotient(&vertex_map), Err(QuotientError::Abstract(_))));
+
+        vertex_map.push(vec![0, 0, 1, 2]);
+        assert!(matches!(tet.quotient(&vertex_map), Err(QuotientError::Automorphism { idx: 2 })));
+    }
+}
@@ -0,0 +1,153 @@
+//! Generates random polytopes, mostly to use as stress tests for the other
+//! algorithms. Every generator takes a seed, so that a polytope that breaks
+//! something can be built again.
+//!
+//! The hull of random points on a sphere is simplicial, while the hull of a
+//! union of random orbits under a group has lots of symmetry, so between them
+//! they cover the two extremes most algorithms care about.
+
+use std::collections::BTreeSet;
+
+use super::Concrete;
+use crate::{
+    geometry::{Matrix, Point, PointOrd},
+    group::Group,
+};
+
+/// A tiny xorshift generator, so that random polytopes are reproducible.
+pub(crate) struct XorShift(u64);
+
+impl XorShift {
+    /// Initializes the generator from a seed. A seed of zero is replaced by
+    /// one, as the generator would otherwise only return zeros.
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    /// Returns a pseudorandom number between -1 and 1.
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+    }
+
+    /// Returns a point uniformly distributed on the unit sphere of a given
+    /// dimension, by normalizing a random point in the unit ball.
+    fn next_sphere_point(&mut self, dim: usize) -> Point<f64> {
+        loop {
+            let p = Point::from_fn(dim, |_, _| self.next_f64());
+            let norm = p.norm();
+
+            // We skip points outside the ball, which would bias the
+            // distribution towards the corners of the cube, and points too
+            // close to the center to normalize.
+            if norm <= 1.0 && norm > 1e-3 {
+                return p / norm;
+            }
+        }
+    }
+}
+
+impl Concrete {
+    /// Returns a given number of random points on the unit sphere. The unit
+    /// sphere of dimension 0 has no points, so we return none.
+    pub fn random_sphere_points(count: usize, dim: usize, seed: u64) -> Vec<Point<f64>> {
+        if dim == 0 {
+            return Vec::new();
+        }
+
+        let mut rng = XorShift::new(seed);
+        (0..count).map(|_| rng.next_sphere_point(dim)).collect()
+    }
+
+    /// Builds the convex hull of a given number of random points on the unit
+    /// sphere. If there are at most as many points as the dimension, they
+    /// span a simplex of a lower rank. Returns `None` if there are no points.
+    pub fn random_hull(count: usize, dim: usize, seed: u64) -> Option<Self> {
+        Self::convex_hull(&Self::random_sphere_points(count, dim, seed))
+    }
+
+    /// Returns the union of the orbits of a given number of random points on
+    /// the unit sphere under a group of matrices, without repeats.
+    pub fn random_orbits<I: Iterator<Item = Matrix<f64>>>(
+        group: Group<I>,
+        count: usize,
+        seed: u64,
+    ) -> Vec<Point<f64>> {
+        let matrices: Vec<Matrix<f64>> = group.collect();
+        let dim = matrices.first().map_or(0, |m| m.nrows());
+
+        let mut points = BTreeSet::new();
+        for p in Self::random_sphere_points(count, dim, seed) {
+            for m in &matrices {
+                points.insert(PointOrd::new(m * &p));
+            }
+        }
+
+        points.into_iter().map(|p| p.0).collect()
+    }
+
+    /// Builds the convex hull of the union of the orbits of a given number of
+    /// random points on the unit sphere under a group of matrices. Returns
+    /// `None` if there are no points.
+    pub fn random_symmetric_hull<I: Iterator<Item = Matrix<f64>>>(
+        group: Group<I>,
+        count: usize,
+        seed: u64,
+    ) -> Option<Self> {
+        Self::convex_hull(&Self::random_orbits(group, count, seed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abs::Ranked, float::Float, test};
+
+    /// Random points lie on the unit sphere, and the same seed gives the same
+    /// points.
+    #[test]
+    fn sphere_points() {
+        let points = Concrete::random_sphere_points(50, 4, 7);
+        assert_eq!(points.len(), 50);
+        for p in &points {
+            assert!((p.norm() - 1.0).abs() < f64::EPS);
+        }
+
+        assert_eq!(points, Concrete::random_sphere_points(50, 4, 7));
+        assert_ne!(points, Concrete::random_sphere_points(50, 4, 8));
+    }
+
+    /// The hull of random points is simplicial, and has every point as a
+    /// vertex.
+    #[test]
+    fn hull() {
+        for seed in 1..5 {
+            let poly = Concrete::random_hull(20, 3, seed).unwrap();
+            test(&poly, [1, 20, 54, 36, 1]);
+        }
+
+        let poly = Concrete::random_hull(12, 4, 1).unwrap();
+        assert_eq!(poly.vertex_count(), 12);
+        assert_eq!(poly.rank(), 5);
+
+        // Too few points to span the space.
+        test(&Concrete::random_hull(3, 3, 1).unwrap(), [1, 3, 3, 1]);
+        assert!(Concrete::random_hull(0, 3, 1).is_none());
+    }
+
+    /// The orbit of a generic point under the symmetry of the cube has the
+    /// elements of the great rhombicuboctahedron.
+    #[test]
+    fn orbits() {
+        let points = Concrete::random_orbits(Group::hypercube(3), 1, 1);
+        assert_eq!(points.len(), 48);
+
+        let poly = Concrete::random_symmetric_hull(Group::hypercube(3), 1, 1).unwrap();
+        test(&poly, [1, 48, 72, 26, 1]);
+
+        let points = Concrete::random_orbits(Group::simplex(3), 2, 1);
+        assert_eq!(points.len(), 48);
+    }
+}
@@ -0,0 +1,163 @@
+//! Quick constructions that combine a polytope with its point reflection
+//! through the origin, or with its mirror image.
+
+use super::{Concrete, ConcretePolytope};
+use crate::{geometry::Point, Polytope};
+
+impl Concrete {
+    /// Reflects the polytope through the origin in place.
+    pub fn point_reflect_mut(&mut self) {
+        for v in &mut self.vertices {
+            v.neg_mut();
+        }
+    }
+
+    /// Returns the compound of the polytope and its reflection through the
+    /// origin.
+    pub fn reflection_compound(&self) -> Self {
+        let mut reflection = self.clone();
+        reflection.point_reflect_mut();
+
+        let mut compound = self.clone();
+        compound.comp_append(reflection);
+        compound
+    }
+
+    /// Reflects the polytope in place through the hyperplane orthogonal to the
+    /// first coordinate axis, turning it into its enantiomorph.
+    pub fn enantiomorph_mut(&mut self) {
+        for v in &mut self.vertices {
+            if let Some(c) = v.get_mut(0) {
+                *c = -*c;
+            }
+        }
+    }
+
+    /// Returns the enantiomorph of the polytope, i.e. its mirror image. This
+    /// is only congruent to the polytope when it isn't chiral.
+    pub fn enantiomorph(&self) -> Self {
+        let mut enantiomorph = self.clone();
+        enantiomorph.enantiomorph_mut();
+        enantiomorph
+    }
+
+    /// Returns the compound of the polytope and its enantiomorph.
+    pub fn enantiomorph_compound(&self) -> Self {
+        let mut compound = self.clone();
+        compound.comp_append(self.enantiomorph());
+        compound
+    }
+
+    /// Builds a prism of a given height whose bottom base is the polytope, and
+    /// whose top base is its reflection through the origin. The lateral
+    /// elements stay flat, since every element is parallel to its reflection.
+    /// Note that a point reflection is only a mirror image in an odd number of
+    /// dimensions, and is a rotation otherwise.
+    ///
+    /// Every lateral facet is symmetric under the reflection through the
+    /// center, so it passes through it, and the prism has no dual.
+    pub fn reflection_prism_with(&self, height: f64) -> Self {
+        let mut prism = self.prism_with(height);
+        let dim = self.dim_or();
+
+        for v in &mut prism.vertices {
+            if v[dim] > 0.0 {
+                v.rows_mut(0, dim).neg_mut();
+            }
+        }
+
+        prism
+    }
+
+    /// Builds a reflection prism of unit height.
+    pub fn reflection_prism(&self) -> Self {
+        self.reflection_prism_with(1.0)
+    }
+
+    /// Builds a tegum of a given height between the polytope and its
+    /// reflection through the origin. Both share the two apices, which lie on
+    /// a new axis at either side of them, so that the result has one more rank
+    /// than the polytope. Centrally symmetric polytopes coincide with their
+    /// reflections, and give two coinciding tegums.
+    pub fn reflection_tegum_with(&self, height: f64) -> Self {
+        let dim = self.dim_or();
+        let apex = |h| {
+            let mut apex = Point::zeros(dim + 1);
+            apex[dim] = h;
+            apex
+        };
+
+        self.reflection_compound()
+            .tegum_with(apex(height / 2.0), apex(-height / 2.0))
+    }
+
+    /// Builds a reflection tegum of unit height.
+    pub fn reflection_tegum(&self) -> Self {
+        self.reflection_tegum_with(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abs::Ranked, float::Float, test};
+
+    /// The compound of a tetrahedron and its reflection is a stella
+    /// octangula.
+    #[test]
+    fn compound() {
+        test(
+            &Concrete::tetrahedron().reflection_compound(),
+            [1, 8, 12, 8, 1],
+        );
+    }
+
+    /// The enantiomorph of a polytope is its mirror image, and its compound
+    /// with it has twice the elements.
+    #[test]
+    fn enantiomorph() {
+        let tet = Concrete::tetrahedron();
+        let enantiomorph = tet.enantiomorph();
+
+        for (v, w) in tet.vertices.iter().zip(&enantiomorph.vertices) {
+            assert_eq!(v[0], -w[0]);
+            assert_eq!(v.rows(1, 2), w.rows(1, 2));
+        }
+
+        test(&tet.enantiomorph_compound(), [1, 8, 12, 8, 1]);
+    }
+
+    /// The top base of a reflection prism is the reflected bottom base.
+    #[test]
+    fn prism() {
+        let prism = Concrete::tetrahedron().reflection_prism_with(2.0);
+        test(&prism, [1, 8, 16, 14, 6, 1]);
+
+        for v in &prism.vertices {
+            let w = -v;
+            assert!(prism.vertices.iter().any(|u| (u - &w).norm() < f64::EPS));
+        }
+    }
+
+    /// The tegum of a triangle and its reflection joins both triangles to the
+    /// same two apices, in one more dimension. This isn't the duotegum of the
+    /// triangle with itself.
+    #[test]
+    fn tegum() {
+        let triangle = Concrete::polygon(3);
+        let tegum = triangle.reflection_tegum_with(2.0);
+        test(&tegum, [1, 8, 18, 12, 1]);
+        assert_eq!(tegum.dim(), Some(3));
+        assert_eq!(tegum.rank(), triangle.rank() + 1);
+        assert_ne!(tegum.rank(), triangle.duotegum(&triangle).rank());
+
+        for v in &triangle.vertices {
+            let w = (-v).push(0.0);
+            assert!(tegum.vertices.iter().any(|u| (u - &w).norm() < f64::EPS));
+        }
+        for h in [-1.0, 1.0] {
+            let apex = Point::from_vec(vec![0.0, 0.0, h]);
+            assert!(tegum.vertices.iter().any(|u| (u - &apex).norm() < f64::EPS));
+        }
+    }
+}
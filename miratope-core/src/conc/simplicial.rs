@@ -0,0 +1,197 @@
+//! Decomposes the interior of a polytope into simplices.
+
+use std::collections::HashSet;
+
+use super::{Concrete, ConcretePolytope};
+use crate::{
+    abs::{
+        flag::{FlagChanges, FlagEvent, OrientedFlagIter},
+        Ranked,
+    },
+    geometry::{Matrix, Point, Subspace},
+    Polytope,
+};
+
+/// A simplex in the decomposition of a polytope, together with the sign with
+/// which its volume counts towards the volume of the polytope.
+#[derive(Clone, Debug)]
+pub struct Simplex {
+    /// The vertices of the simplex.
+    pub vertices: Vec<Point<f64>>,
+
+    /// Either `1.0` or `-1.0`. Simplices with a negative sign must be
+    /// subtracted from the rest.
+    pub sign: f64,
+}
+
+impl Simplex {
+    /// Returns the determinant of the edge vectors from the first vertex.
+    fn determinant(&self) -> f64 {
+        let n = self.vertices.len() - 1;
+        let v0 = &self.vertices[0];
+
+        Matrix::from_iterator(
+            n,
+            n,
+            self.vertices[1..].iter().flat_map(|v| (v - v0).iter().copied().collect::<Vec<_>>()),
+        )
+        .determinant()
+    }
+
+    /// Returns the volume of the simplex, counted with its sign. This is
+    /// nonnegative for every simplex of a convex polytope.
+    pub fn signed_volume(&self) -> f64 {
+        self.sign * self.determinant() / f64::from(crate::factorial(self.vertices.len() - 1))
+    }
+
+    /// Returns the centroid of the simplex.
+    pub fn centroid(&self) -> Point<f64> {
+        self.vertices.iter().sum::<Point<f64>>() / self.vertices.len() as f64
+    }
+}
+
+impl Concrete {
+    /// Decomposes the interior of a polytope into simplices, one for every
+    /// flag. Each simplex has as vertices the gravicenters of the elements of
+    /// its flag, together with the gravicenter of the whole polytope.
+    ///
+    /// For convex polytopes, this is the barycentric subdivision, and every
+    /// simplex has a nonnegative signed volume. For non-convex polytopes, the
+    /// simplices may overlap, but their signed volumes still add up to the
+    /// volume of the polytope.
+    ///
+    /// Returns `None` if the polytope isn't orientable, or if it doesn't span
+    /// a space of its own rank. Polytopes embedded in more dimensions than
+    /// their rank must be flattened beforehand.
+    pub fn simplexes(&self) -> Option<Vec<Simplex>> {
+        let rank = self.rank();
+        if rank < 2
+            || self.dim() != Some(rank - 1)
+            || Subspace::from_points(self.vertices.iter()).rank() != rank - 1
+        {
+            return None;
+        }
+
+        let mut poly = self.clone();
+        poly.element_sort();
+
+        // The gravicenters of all elements. The nullitope has none, so we
+        // leave its rank empty.
+        let centers: Vec<Vec<Point<f64>>> = (0..rank)
+            .map(|r| {
+                if r == 0 {
+                    return Vec::new();
+                }
+
+                (0..poly.el_count(r))
+                    .map(|idx| {
+                        let vertices = poly.abs.element_vertices(r, idx).unwrap();
+                        vertices.iter().map(|&v| &poly.vertices[v]).sum::<Point<f64>>()
+                            / vertices.len() as f64
+                    })
+                    .collect()
+            })
+            .collect();
+        let apex = poly.gravicenter().unwrap();
+
+        let mut simplexes = Vec::new();
+        let mut all_flags = HashSet::new();
+
+        for flag in poly.flags() {
+            // If this flag forms a new component of the polytope, we iterate
+            // over the oriented flags in this component.
+            if all_flags.contains(&flag) {
+                continue;
+            }
+
+            let start = simplexes.len();
+            let mut component_volume = 0.0;
+
+            for flag_event in
+                OrientedFlagIter::with_flags(&poly.abs, FlagChanges::all(rank), flag.into())
+            {
+                if let FlagEvent::Flag(oriented_flag) = flag_event {
+                    all_flags.insert(oriented_flag.flag.clone());
+
+                    let mut vertices = vec![apex.clone()];
+                    for r in (1..rank).rev() {
+                        vertices.push(centers[r][oriented_flag[r]].clone());
+                    }
+
+                    let simplex = Simplex {
+                        vertices,
+                        sign: oriented_flag.orientation.sign(),
+                    };
+                    component_volume += simplex.signed_volume();
+                    simplexes.push(simplex);
+                }
+                // A non-orientable polytope can't be decomposed.
+                else {
+                    return None;
+                }
+            }
+
+            // We orient every component so that it has positive volume.
+            if component_volume < 0.0 {
+                for simplex in &mut simplexes[start..] {
+                    simplex.sign = -simplex.sign;
+                }
+            }
+        }
+
+        Some(simplexes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    /// Checks that the simplices of a polytope add up to its volume.
+    fn test_volume(poly: &Concrete, flags: usize) {
+        let simplexes = poly.simplexes().unwrap();
+        assert_eq!(simplexes.len(), flags);
+
+        let volume: f64 = simplexes.iter().map(Simplex::signed_volume).sum();
+        assert_abs_diff_eq!(volume, poly.clone().volume_mut().unwrap(), epsilon = 1e-9);
+        assert!(simplexes.iter().all(|s| s.signed_volume() > -1e-9));
+    }
+
+    #[test]
+    fn square() {
+        test_volume(&Concrete::polygon(4), 8);
+    }
+
+    #[test]
+    fn cube() {
+        test_volume(&Concrete::cube(), 48);
+    }
+
+    #[test]
+    fn tesseract() {
+        test_volume(&Concrete::hypercube(5), 384);
+    }
+
+    /// A flat polytope can't be decomposed.
+    #[test]
+    fn flat() {
+        let mut cube = Concrete::cube();
+        for v in &mut cube.vertices {
+            v[2] = 0.0;
+        }
+        assert!(cube.simplexes().is_none());
+    }
+
+    /// A polytope in more dimensions than its rank can't be decomposed until
+    /// it's flattened.
+    #[test]
+    fn padded() {
+        let mut square = Concrete::polygon(4);
+        square.pad_dim(3);
+        assert!(square.simplexes().is_none());
+
+        square.flatten();
+        test_volume(&square, 8);
+    }
+}
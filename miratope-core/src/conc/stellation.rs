@@ -0,0 +1,502 @@
+//! Computes the [stellations](https://en.wikipedia.org/wiki/Stellation) of a
+//! polyhedron. The planes of its faces split space into convex cells, which
+//! make up its stellation diagram, and every stellation is the solid made out
+//! of some set of the bounded cells.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use super::{
+    pieces::{vector3, Polygon},
+    Concrete, ConcretePolytope,
+};
+use crate::{
+    abs::Ranked,
+    float::Float,
+    geometry::{Matrix, Point, PointOrd},
+    group::Group,
+};
+
+use itertools::Itertools;
+use nalgebra::{Matrix3, Vector3};
+
+/// A plane, given by its unit normal and its offset from the origin.
+type Plane = (Vector3<f64>, f64);
+
+/// Which side of each plane of a stellation diagram a cell lies on. A `true`
+/// entry means that the cell is in front of the plane, i.e. on the side its
+/// normal points to.
+type Signs = Vec<bool>;
+
+/// A bounded cell of a [`StellationDiagram`].
+#[derive(Clone, Debug)]
+pub struct StellationCell {
+    /// Which side of each plane the cell lies on.
+    signs: Signs,
+
+    /// The faces of the cell, given by the index of their plane and the
+    /// indices of their vertices. These are in counterclockwise order when
+    /// seen from outside the cell.
+    faces: Vec<(usize, Vec<usize>)>,
+
+    /// The number of planes separating the cell from the core.
+    layer: usize,
+
+    /// The average of the vertices of the cell.
+    center: Point<f64>,
+}
+
+impl StellationCell {
+    /// Returns the faces of the cell, given by the index of their plane and
+    /// the indices of their vertices in counterclockwise order when seen from
+    /// outside the cell.
+    pub fn faces(&self) -> &[(usize, Vec<usize>)] {
+        &self.faces
+    }
+
+    /// Returns the number of planes separating the cell from the core. The
+    /// core itself is in layer 0.
+    pub fn layer(&self) -> usize {
+        self.layer
+    }
+
+    /// Returns the average of the vertices of the cell.
+    pub fn center(&self) -> &Point<f64> {
+        &self.center
+    }
+
+    /// Returns whether the cell lies in front of a given plane.
+    pub fn in_front_of(&self, plane: usize) -> bool {
+        self.signs[plane]
+    }
+}
+
+/// The stellation diagram of a polyhedron, i.e. the bounded cells into which
+/// the planes of its faces split space.
+///
+/// The cells are found by starting from the core, the cell containing the
+/// center of the polyhedron, and crossing the faces of every cell found. This
+/// finds every bounded cell that can be reached from the core without going
+/// through an unbounded one, which in practice is all of them.
+#[derive(Clone, Debug)]
+pub struct StellationDiagram {
+    /// The planes of the faces, oriented so that the center of the polyhedron
+    /// is behind them.
+    planes: Vec<Plane>,
+
+    /// The points where three or more planes meet.
+    vertices: Vec<Vector3<f64>>,
+
+    /// The bounded cells, sorted by layer. The first one is the core.
+    cells: Vec<StellationCell>,
+
+    /// The index of every cell from its signs.
+    cell_idx: HashMap<Signs, usize>,
+}
+
+/// Computes the cell on a given side of every plane, if it's bounded.
+struct CellBuilder<'a> {
+    /// The planes of the diagram.
+    planes: &'a [Plane],
+
+    /// The vertices of the diagram.
+    vertices: &'a [Vector3<f64>],
+
+    /// The signed distance from each vertex to each plane.
+    distances: Vec<Vec<f64>>,
+}
+
+impl<'a> CellBuilder<'a> {
+    /// Initializes a new cell builder.
+    fn new(planes: &'a [Plane], vertices: &'a [Vector3<f64>]) -> Self {
+        let distances = vertices
+            .iter()
+            .map(|v| {
+                planes
+                    .iter()
+                    .map(|(normal, offset)| normal.dot(v) - offset)
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            planes,
+            vertices,
+            distances,
+        }
+    }
+
+    /// Builds the cell with the given signs. Returns `None` if it's empty or
+    /// unbounded.
+    fn build(&self, signs: Signs) -> Option<StellationCell> {
+        let cell_vertices: Vec<usize> = (0..self.vertices.len())
+            .filter(|&v| {
+                self.distances[v].iter().zip(&signs).all(|(&d, &front)| {
+                    if front {
+                        d > -f64::EPS
+                    } else {
+                        d < f64::EPS
+                    }
+                })
+            })
+            .collect();
+        if cell_vertices.len() < 4 {
+            return None;
+        }
+
+        let mut faces = Vec::new();
+        for (plane, (normal, _)) in self.planes.iter().enumerate() {
+            let face: Vec<usize> = cell_vertices
+                .iter()
+                .copied()
+                .filter(|&v| self.distances[v][plane].abs() < f64::EPS)
+                .collect();
+            if face.len() < 3 {
+                continue;
+            }
+
+            // Sorts the vertices by their angle around the center of the face,
+            // as seen from outside the cell.
+            let outward = if signs[plane] { -normal } else { *normal };
+            let center =
+                face.iter().map(|&v| self.vertices[v]).sum::<Vector3<f64>>() / face.len() as f64;
+            let u = face
+                .iter()
+                .map(|&v| self.vertices[v] - center)
+                .max_by(|a, b| a.norm().total_cmp(&b.norm()))
+                .unwrap()
+                .normalize();
+            let w = outward.cross(&u);
+
+            let mut angles: Vec<(f64, usize)> = face
+                .into_iter()
+                .map(|v| {
+                    let p = self.vertices[v] - center;
+                    (p.dot(&w).atan2(p.dot(&u)), v)
+                })
+                .collect();
+            angles.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+            let cycle: Vec<usize> = angles.into_iter().map(|(_, v)| v).collect();
+
+            // Planes touching the cell along an edge don't give a face.
+            let area: f64 = (0..cycle.len())
+                .map(|i| {
+                    let p = self.vertices[cycle[i]] - center;
+                    let q = self.vertices[cycle[(i + 1) % cycle.len()]] - center;
+                    p.cross(&q).dot(&outward)
+                })
+                .sum();
+            if area > f64::EPS {
+                faces.push((plane, cycle));
+            }
+        }
+
+        // The faces of a bounded cell close up, so every edge is in exactly
+        // two of them.
+        let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+        for (_, cycle) in &faces {
+            for i in 0..cycle.len() {
+                let (a, b) = (cycle[i], cycle[(i + 1) % cycle.len()]);
+                *edges.entry((a.min(b), a.max(b))).or_default() += 1;
+            }
+        }
+        if faces.len() < 4 || edges.values().any(|&count| count != 2) {
+            return None;
+        }
+
+        let center = cell_vertices
+            .iter()
+            .map(|&v| self.vertices[v])
+            .sum::<Vector3<f64>>()
+            / cell_vertices.len() as f64;
+
+        Some(StellationCell {
+            layer: signs.iter().filter(|&&front| front).count(),
+            signs,
+            faces,
+            center: Point::from_column_slice(center.as_slice()),
+        })
+    }
+}
+
+impl StellationDiagram {
+    /// Computes the stellation diagram of a polyhedron. Returns `None` if the
+    /// polytope isn't a polyhedron in 3D space, or if the cell containing its
+    /// center isn't bounded.
+    pub fn new(poly: &Concrete) -> Option<Self> {
+        if poly.rank() != 4 || poly.dim() != Some(3) {
+            return None;
+        }
+        let center = vector3(&poly.gravicenter()?);
+
+        // The planes of the faces, oriented so that the center is behind
+        // them. Coplanar faces give a single plane.
+        let mut planes: Vec<Plane> = Vec::new();
+        let mut found = BTreeSet::new();
+        for idx in 0..poly.el_count(3) {
+            let (mut normal, mut offset) = match poly.face_plane(idx) {
+                Some(plane) => plane,
+                None => continue,
+            };
+            if normal.dot(&center) - offset > 0.0 {
+                normal = -normal;
+                offset = -offset;
+            }
+
+            let key = PointOrd::new(Point::from_vec(vec![normal.x, normal.y, normal.z, offset]));
+            if found.insert(key) {
+                planes.push((normal, offset));
+            }
+        }
+
+        // The points where three planes meet.
+        let mut vertex_idx = BTreeMap::new();
+        let mut vertices = Vec::new();
+        for (i, j, k) in (0..planes.len()).tuple_combinations() {
+            let mat = Matrix3::from_rows(&[
+                planes[i].0.transpose(),
+                planes[j].0.transpose(),
+                planes[k].0.transpose(),
+            ]);
+            if mat.determinant().abs() < f64::EPS {
+                continue;
+            }
+
+            if let Some(inv) = mat.try_inverse() {
+                let v = inv * Vector3::new(planes[i].1, planes[j].1, planes[k].1);
+                vertex_idx
+                    .entry(PointOrd::new(Point::from_column_slice(v.as_slice())))
+                    .or_insert_with(|| {
+                        vertices.push(v);
+                        vertices.len() - 1
+                    });
+            }
+        }
+
+        // Finds the cells, layer by layer, starting from the core.
+        let builder = CellBuilder::new(&planes, &vertices);
+        let mut cells = vec![builder.build(vec![false; planes.len()])?];
+        let mut unbounded = HashSet::new();
+        let mut cell_idx = HashMap::new();
+        cell_idx.insert(cells[0].signs.clone(), 0);
+
+        let mut idx = 0;
+        while let Some(cell) = cells.get(idx) {
+            let neighbors: Vec<Signs> = cell
+                .faces
+                .iter()
+                .map(|&(plane, _)| {
+                    let mut signs = cell.signs.clone();
+                    signs[plane] = !signs[plane];
+                    signs
+                })
+                .collect();
+
+            for signs in neighbors {
+                if cell_idx.contains_key(&signs) || unbounded.contains(&signs) {
+                    continue;
+                }
+
+                match builder.build(signs.clone()) {
+                    Some(neighbor) => {
+                        cell_idx.insert(signs, cells.len());
+                        cells.push(neighbor);
+                    }
+                    None => {
+                        unbounded.insert(signs);
+                    }
+                }
+            }
+
+            idx += 1;
+        }
+
+        Some(Self {
+            planes,
+            vertices,
+            cells,
+            cell_idx,
+        })
+    }
+
+    /// Returns the number of planes of the diagram.
+    pub fn plane_count(&self) -> usize {
+        self.planes.len()
+    }
+
+    /// Returns the points where three or more planes meet.
+    pub fn vertices(&self) -> &[Vector3<f64>] {
+        &self.vertices
+    }
+
+    /// Returns the bounded cells of the diagram, sorted by layer. The first
+    /// one is the core.
+    pub fn cells(&self) -> &[StellationCell] {
+        &self.cells
+    }
+
+    /// Returns the number of layers of the diagram.
+    pub fn layer_count(&self) -> usize {
+        self.cells.last().map_or(0, |cell| cell.layer + 1)
+    }
+
+    /// Returns the indices of the cells in each layer.
+    pub fn layers(&self) -> Vec<Vec<usize>> {
+        let mut layers = vec![Vec::new(); self.layer_count()];
+        for (idx, cell) in self.cells.iter().enumerate() {
+            layers[cell.layer].push(idx);
+        }
+        layers
+    }
+
+    /// Returns the index of the cell on the other side of a face of a given
+    /// cell, if it's bounded.
+    pub fn neighbor(&self, cell: usize, plane: usize) -> Option<usize> {
+        let mut signs = self.cells[cell].signs.clone();
+        signs[plane] = !signs[plane];
+        self.cell_idx.get(&signs).copied()
+    }
+
+    /// Splits the cells into orbits under a symmetry group of the polyhedron.
+    /// Cells that aren't mapped onto any other cell, which only happens if the
+    /// group isn't a symmetry group of the polyhedron, get their own orbits.
+    /// Each orbit is sorted, and orbits are sorted by their first cell.
+    pub fn cell_orbits<I: Iterator<Item = Matrix<f64>>>(&self, group: Group<I>) -> Vec<Vec<usize>> {
+        let group: Vec<_> = group.collect();
+        let centers: BTreeMap<_, _> = self
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| (PointOrd::new(cell.center.clone()), idx))
+            .collect();
+
+        let mut orbit_of = vec![None; self.cells.len()];
+        let mut orbits = Vec::new();
+        for (idx, cell) in self.cells.iter().enumerate() {
+            if orbit_of[idx].is_some() {
+                continue;
+            }
+
+            let mut orbit = BTreeSet::from([idx]);
+            for mat in &group {
+                if let Some(&image) = centers.get(&PointOrd::new(mat * &cell.center)) {
+                    orbit.insert(image);
+                }
+            }
+
+            for &image in &orbit {
+                orbit_of[image] = Some(orbits.len());
+            }
+            orbits.push(orbit.into_iter().collect());
+        }
+
+        orbits
+    }
+
+    /// Builds the stellation made out of a set of cells. Its faces are the
+    /// pieces of the planes that separate a chosen cell from one that isn't.
+    /// Pieces on the same plane that share an edge are merged into a single
+    /// face. Returns `None` if no cells are chosen.
+    ///
+    /// Like [`Concrete::outer_shell`], the result is the surface of a solid,
+    /// so the faces of stellations like the great stellated dodecahedron
+    /// come out as the visible parts of their star polygons.
+    pub fn stellation(&self, cells: &[usize]) -> Option<Concrete> {
+        let mut chosen = vec![false; self.cells.len()];
+        for &idx in cells {
+            chosen[idx] = true;
+        }
+        if !chosen.contains(&true) {
+            return None;
+        }
+
+        let mut pieces = Vec::new();
+        for (idx, cell) in self.cells.iter().enumerate() {
+            if !chosen[idx] {
+                continue;
+            }
+
+            for (plane, cycle) in &cell.faces {
+                if self
+                    .neighbor(idx, *plane)
+                    .is_some_and(|other| chosen[other])
+                {
+                    continue;
+                }
+
+                let piece: Polygon = cycle.iter().map(|&v| self.vertices[v]).collect();
+                pieces.push((piece, *plane, !cell.signs[*plane]));
+            }
+        }
+
+        Some(Concrete::from_pieces(pieces))
+    }
+}
+
+impl Concrete {
+    /// Computes the stellation diagram of a polyhedron. See
+    /// [`StellationDiagram::new`].
+    pub fn stellation_diagram(&self) -> Option<StellationDiagram> {
+        StellationDiagram::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{file::FromFile, test, Polytope};
+
+    /// Returns the number of cells in each layer of a diagram.
+    fn layer_sizes(diagram: &StellationDiagram) -> Vec<usize> {
+        diagram.layers().iter().map(Vec::len).collect()
+    }
+
+    /// A cube can't be stellated.
+    #[test]
+    fn cube() {
+        let diagram = Concrete::hypercube(4).stellation_diagram().unwrap();
+        assert_eq!(diagram.plane_count(), 6);
+        assert_eq!(layer_sizes(&diagram), [1]);
+        test(&diagram.stellation(&[0]).unwrap(), [1, 8, 12, 6, 1]);
+    }
+
+    /// The only stellation of the octahedron is the stella octangula.
+    #[test]
+    fn octahedron() {
+        let diagram = Concrete::orthoplex(4).stellation_diagram().unwrap();
+        assert_eq!(layer_sizes(&diagram), [1, 8]);
+        test(&diagram.stellation(&[0]).unwrap(), [1, 6, 12, 8, 1]);
+
+        let all: Vec<_> = (0..diagram.cells().len()).collect();
+        test(&diagram.stellation(&all).unwrap(), [1, 14, 36, 24, 1]);
+        assert!(diagram.stellation(&[]).is_none());
+    }
+
+    /// The dodecahedron has three stellations, one for each layer.
+    #[test]
+    fn dodecahedron() {
+        let mut doe =
+            Concrete::from_off(include_str!("../../../lib/3D/uniform/Regular/doe.off")).unwrap();
+        let diagram = doe.stellation_diagram().unwrap();
+        assert_eq!(layer_sizes(&diagram), [1, 12, 30, 20]);
+
+        let (group, _) = doe.get_symmetry_group().unwrap();
+        assert_eq!(diagram.cell_orbits(group).len(), 4);
+
+        // Each partial union of layers gives a closed surface.
+        for layer_count in 1..=4 {
+            let cells: Vec<_> = diagram.layers()[..layer_count].concat();
+            let stellation = diagram.stellation(&cells).unwrap();
+            assert!(stellation.abs.ranks().is_valid().is_ok());
+
+            let euler = stellation.el_count(1) as isize - stellation.el_count(2) as isize
+                + stellation.el_count(3) as isize;
+            assert_eq!(euler, 2);
+        }
+    }
+
+    /// Only 3D polyhedra have stellation diagrams.
+    #[test]
+    fn tesseract() {
+        assert!(Concrete::hypercube(5).stellation_diagram().is_none());
+    }
+}
@@ -1,20 +1,36 @@
 //! The code used to get the symmetry of a polytope and do operations based on that.
 
-use std::{collections::{BTreeMap, HashSet}, vec, iter::FromIterator};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    hash::{Hash, Hasher},
+    iter::FromIterator,
+    vec,
+};
 
 use crate::{
     abs::{Ranked, flag::{FlagIter, Flag}},
+    cancel::{Cancelled, CancellationToken},
     conc::Concrete,
     float::Float,
-    group::Group,
+    group::{classify::GroupClass, vertex_map::VertexMap, Group},
     geometry::{Matrix, Point, PointOrd, Subspace},
     Polytope,
 };
 
+use partitions::{partition_vec, PartitionVec};
 use vec_like::*;
 
 use super::ConcretePolytope;
 
+#[cfg(feature = "bevy")]
+use bevy::prelude::Resource;
+
+/// A symmetry group of a polytope, together with its vertex map.
+pub type SymmetryGroup = (Group<vec::IntoIter<Matrix<f64>>>, VertexMap);
+
+/// The number of symmetry groups a [`SymmetryCache`] holds on to.
+const SYMMETRY_CACHE_SIZE: usize = 8;
+
 impl Flag {
     /// Outputs a sequence of vertices obtained from applying a fixed sequence of flag changes to a flag.
     /// Used for computing the elements of a symmetry group. 
@@ -37,9 +53,79 @@ impl Flag {
     }
 }
 
+/// An orbit of elements of a polytope under a symmetry group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ElementOrbit {
+    /// The index of a representative element of the orbit.
+    pub example: usize,
+
+    /// The number of elements in the orbit.
+    pub count: usize,
+}
+
+/// The kinds of elements the symmetries of a polytope act transitively on,
+/// as found by [`Concrete::transitivity`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Transitivity {
+    /// Whether the polytope is vertex-transitive, or isogonal.
+    pub vertex: bool,
+
+    /// Whether the polytope is edge-transitive, or isotoxal.
+    pub edge: bool,
+
+    /// Whether the polytope is facet-transitive, or isotopic.
+    pub facet: bool,
+
+    /// Whether the polytope is flag-transitive, that is, regular.
+    pub regular: bool,
+
+    /// Whether the polytope is uniform, that is, a regular polygon, or
+    /// vertex-transitive with uniform facets.
+    pub uniform: bool,
+}
+
+impl Transitivity {
+    /// Returns whether the polytope is isotoxal, that is, edge-transitive.
+    pub fn isotoxal(&self) -> bool {
+        self.edge
+    }
+
+    /// Returns whether the polytope is noble, that is, both vertex-transitive
+    /// and facet-transitive.
+    pub fn noble(&self) -> bool {
+        self.vertex && self.facet
+    }
+}
+
 impl Concrete {
     /// Computes the symmetry group of a polytope, along with a list of vertex mappings.
-    pub fn get_symmetry_group(&mut self) -> Option<(Group<vec::IntoIter<Matrix<f64>>>, Vec<Vec<usize>>)> {
+    pub fn get_symmetry_group(&mut self) -> Option<SymmetryGroup> {
+        self.get_symmetry_group_with(&CancellationToken::new()).ok().flatten()
+    }
+
+    /// Computes the symmetry group of a polytope, along with a list of vertex
+    /// mappings. Stops early if the token is cancelled.
+    pub fn get_symmetry_group_with(
+        &mut self,
+        cancel: &CancellationToken,
+    ) -> Result<Option<SymmetryGroup>, Cancelled> {
+        // The flag algorithm below needs a basis of the space made out of the
+        // vertices of a flag.
+        if self.rank() < 3 || self.dim() != Some(self.rank() - 1) {
+            return self.graph_symmetry_group_with(cancel);
+        }
+
+        // It also assumes that any flag can be reached from any other, and
+        // that no two vertices coincide.
+        let mut sorted = self.clone();
+        sorted.element_sort();
+        if sorted.abs.is_compound() {
+            return match self.compound_symmetry_group_with(cancel)? {
+                Some(group) => Ok(Some(group)),
+                None => self.graph_symmetry_group_with(cancel),
+            };
+        }
+
         let mut fixed = self.clone(); // We'll relabel the facets if needed so the first facet isn't hemi.
 
         let mut facet_idx = 0;
@@ -54,9 +140,9 @@ impl Concrete {
                 facet_idx += 1;
             }
 
+            // All facets pass through the origin.
             if facet_idx == self.el_count(self.rank()-1) {
-                println!("Symmetry calculation failed. All facets pass through the origin.");
-                return None
+                return self.graph_symmetry_group_with(cancel);
             }
 
             if facet_idx != 0 {
@@ -84,7 +170,7 @@ impl Concrete {
             vertices_pointord.push(PointOrd::new(v.clone()));
         }
         let vertices = BTreeMap::from_iter((vertices_pointord).into_iter().zip(0..));
-        let mut vertex_map: Vec<Vec<usize>> = Vec::new();
+        let mut vertex_map = VertexMap::new();
 
         // Sets of elements' vertex sets.
         let elements = Vec::<HashSet<Vec<usize>>>::from_iter(
@@ -99,11 +185,15 @@ impl Concrete {
 
         let base_flag = fixed.first_flag();
         let base_basis = base_flag.clone().vertex_sequence(&fixed);
-        let base_basis_inverse = base_basis.clone().try_inverse().unwrap();
+        let Some(base_basis_inverse) = base_basis.try_inverse() else {
+            return self.graph_symmetry_group_with(cancel);
+        };
 
         let mut group = Vec::<Matrix<f64>>::new();
 
         'a: for flag in flag_iter {
+            cancel.check()?;
+
             if flag
                 .iter()
                 .enumerate()
@@ -146,16 +236,31 @@ impl Concrete {
             }
         }
 
+        // Not even the identity was found, which happens when some vertices
+        // coincide.
+        if group.is_empty() {
+            return self.compound_symmetry_group_with(cancel);
+        }
+
         unsafe {
-            Some((Group::new(&self.rank()-1, group.into_iter()), vertex_map))
+            Ok(Some((Group::new(&self.rank()-1, group.into_iter()), vertex_map)))
         }
     }
 
     /// Computes the rotation subgroup of a polytope, along with a list of vertex mappings.
-    pub fn get_rotation_group(&mut self) -> Option<(Group<vec::IntoIter<Matrix<f64>>>, Vec<Vec<usize>>)> {
-        if let Some((full_group, full_vertex_map)) = self.get_symmetry_group() {
+    pub fn get_rotation_group(&mut self) -> Option<SymmetryGroup> {
+        self.get_rotation_group_with(&CancellationToken::new()).ok().flatten()
+    }
+
+    /// Computes the rotation subgroup of a polytope, along with a list of
+    /// vertex mappings. Stops early if the token is cancelled.
+    pub fn get_rotation_group_with(
+        &mut self,
+        cancel: &CancellationToken,
+    ) -> Result<Option<SymmetryGroup>, Cancelled> {
+        if let Some((full_group, full_vertex_map)) = self.get_symmetry_group_with(cancel)? {
             let mut rotation_group = Vec::new();
-            let mut vertex_map = Vec::new();
+            let mut vertex_map = VertexMap::new();
     
             for (idx, el) in full_group.enumerate() {
                 if el.determinant() > 0. {
@@ -165,24 +270,155 @@ impl Concrete {
             }
     
             unsafe {
-                Some((Group::new(&self.rank()-1, rotation_group.into_iter()), vertex_map))
+                Ok(Some((Group::new(&self.rank()-1, rotation_group.into_iter()), vertex_map)))
             }
         }
         else {
-            None
+            Ok(None)
+        }
+    }
+
+    /// Returns whether the polytope is chiral, i.e. whether all of its
+    /// symmetries are rotations, so that its symmetry group and its rotation
+    /// group have the same order. Points and the nullitope, whose only
+    /// symmetry is the identity, aren't chiral. Returns `None` if the symmetry
+    /// group can't be computed.
+    pub fn is_chiral(&mut self) -> Option<bool> {
+        if self.dim().unwrap_or(0) == 0 {
+            return Some(false);
         }
+
+        let (mut group, _) = self.get_symmetry_group()?;
+        Some(group.all(|m| m.determinant() > 0.0))
+    }
+
+    /// Computes the stabilizer of an element under the symmetry group of the
+    /// polytope, along with its vertex map.
+    pub fn element_stabilizer(&mut self, rank: usize, idx: usize) -> Option<SymmetryGroup> {
+        let element = self.abs.element_vertices(rank, idx)?;
+        let (group, vertex_map) = self.get_symmetry_group()?;
+        Some(group.stabilizer_of_element(&vertex_map, &element))
+    }
+
+    /// Splits the elements of a given rank into orbits under a symmetry group,
+    /// given by its vertex map. Elements are identified by their vertex sets.
+    pub fn element_orbits(&self, vertex_map: &VertexMap, rank: usize) -> Vec<ElementOrbit> {
+        let mut orbits: Vec<ElementOrbit> = Vec::new();
+
+        for (idx, orbit) in self.element_orbit_indices(vertex_map, rank).into_iter().enumerate() {
+            if orbit == orbits.len() {
+                orbits.push(ElementOrbit { example: idx, count: 0 });
+            }
+            orbits[orbit].count += 1;
+        }
+
+        orbits
+    }
+
+    /// Returns the index of the orbit of every element of a given rank under a
+    /// symmetry group, given by its vertex map. Orbits are numbered in the
+    /// order their first elements appear.
+    pub fn element_orbit_indices(&self, vertex_map: &VertexMap, rank: usize) -> Vec<usize> {
+        let el_count = self.el_count(rank);
+        let mut el_vertices = Vec::with_capacity(el_count);
+        let mut index_of = HashMap::new();
+
+        for idx in 0..el_count {
+            let mut vertices = self.abs.element_vertices(rank, idx).unwrap();
+            vertices.sort_unstable();
+            index_of.entry(vertices.clone()).or_insert(idx);
+            el_vertices.push(vertices);
+        }
+
+        let mut orbit_of = vec![None; el_count];
+        let mut orbit_count = 0;
+
+        for idx in 0..el_count {
+            if orbit_of[idx].is_some() {
+                continue;
+            }
+
+            // We found a new orbit of elements.
+            for image in vertex_map.orbit_of_set(&el_vertices[idx]) {
+                if let Some(&other) = index_of.get(&image) {
+                    orbit_of[other].get_or_insert(orbit_count);
+                }
+            }
+
+            // Elements sharing their vertex set with another one aren't found
+            // by the lookup, so they get their own orbit.
+            orbit_of[idx].get_or_insert(orbit_count);
+            orbit_count += 1;
+        }
+
+        orbit_of.into_iter().map(Option::unwrap).collect()
+    }
+
+    /// Determines which kinds of elements the symmetries of the polytope act
+    /// transitively on, and whether it's regular or uniform. Returns `None` if
+    /// the symmetry group of the polytope, or that of one of its facets,
+    /// couldn't be computed.
+    ///
+    /// Elements of different types can't be in the same orbit, so we only
+    /// look for orbits at the ranks with a single element type. A polytope is
+    /// regular whenever its symmetries act transitively on its flags, which
+    /// happens when there's as many of them as flags. Uniformity is checked
+    /// recursively on a facet of each orbit.
+    pub fn transitivity(&self) -> Option<Transitivity> {
+        let rank = self.rank();
+
+        // Symmetries are computed about the origin, and extra dimensions
+        // would give extra reflections.
+        let mut poly = self.clone();
+        poly.flatten();
+        poly.recenter();
+        poly.element_sort();
+
+        let types = poly.element_types();
+        let (_, vertex_map) = poly.get_symmetry_group()?;
+        let transitive = |r: usize| {
+            r >= rank
+                || poly.el_count(r) <= 1
+                || (types[r].len() == 1 && poly.element_orbits(&vertex_map, r).len() == 1)
+        };
+
+        let vertex = transitive(1);
+        let regular = vertex_map.len() == poly.flags().count();
+        let uniform = if rank <= 2 {
+            true
+        } else if rank == 3 {
+            regular
+        } else {
+            vertex && {
+                let mut uniform = true;
+                for orbit in poly.element_orbits(&vertex_map, rank - 1) {
+                    if !poly.element(rank - 1, orbit.example)?.transitivity()?.uniform {
+                        uniform = false;
+                        break;
+                    }
+                }
+                uniform
+            }
+        };
+
+        Some(Transitivity {
+            vertex,
+            edge: transitive(2),
+            facet: rank >= 1 && transitive(rank - 1),
+            regular,
+            uniform,
+        })
     }
 
-    /// Fills in the vertex map.
-    /// A vertex map is an array of (group element, vertex index) with values being the index of the vertex after applying the transformation.
-    pub fn get_vertex_map(&mut self, group: Group<vec::IntoIter<Matrix<f64>>>) -> Vec<Vec<usize>> {
+    /// Computes the vertex map of a symmetry group of the polytope.
+    pub fn get_vertex_map(&mut self, group: Group<vec::IntoIter<Matrix<f64>>>) -> VertexMap {
         let mut vertices = Vec::<PointOrd<f64>>::new();
         for v in &self.vertices {
             vertices.push(PointOrd::new(v.clone()));
         }
         let vertices = BTreeMap::from_iter((vertices).into_iter().zip(0..));
 
-        let mut vertex_map: Vec<Vec<usize>> = Vec::new();
+        let mut vertex_map = VertexMap::new();
 
         for isometry in group {
             let mut vertex_map_row = Vec::<usize>::new();
@@ -203,12 +439,688 @@ impl Concrete {
     }
 }
 
+impl Concrete {
+    /// Returns whether a polytope might have symmetries besides those of a
+    /// given group, which must act on its vertices as the vertex map says.
+    ///
+    /// Every symmetry is determined by where it sends some base flag, and the
+    /// flags it can be sent to split into orbits under the group. If a flag in
+    /// any orbit besides that of the base flag is the image of a symmetry, so
+    /// is every other flag in it. This way, only one flag of each orbit has to
+    /// be checked.
+    fn has_other_symmetries(&self, vertex_map: &VertexMap) -> bool {
+        let rank = self.rank();
+        let mut sorted = self.clone();
+        sorted.element_sort();
+        let (_, types_map_back) = sorted.element_types_common();
+
+        // The sorted vertex sets of the elements of each rank. We can't tell
+        // elements apart if two of them share a vertex set.
+        let mut el_vertices = vec![Vec::new(); rank];
+        let mut index_of = vec![HashMap::new(); rank];
+        for r in 1..rank {
+            for idx in 0..sorted.el_count(r) {
+                let mut vertices = sorted.abs.element_vertices(r, idx).unwrap();
+                vertices.sort_unstable();
+                if index_of[r].insert(vertices.clone(), idx).is_some() {
+                    return true;
+                }
+                el_vertices[r].push(vertices);
+            }
+        }
+
+        // The image of a flag under the symmetry with a given vertex map row.
+        let image = |flag: &Flag, row: &[usize]| -> Option<Flag> {
+            let mut image = flag.clone();
+            for r in 1..rank {
+                let mut vertices: Vec<usize> =
+                    el_vertices[r][flag[r]].iter().map(|&v| row[v]).collect();
+                vertices.sort_unstable();
+                image[r] = *index_of[r].get(&vertices)?;
+            }
+            Some(image)
+        };
+
+        let vertices: BTreeMap<PointOrd<f64>, usize> = self
+            .vertices
+            .iter()
+            .cloned()
+            .map(PointOrd::new)
+            .zip(0..)
+            .collect();
+
+        // Whether an isometry maps the vertices and elements onto themselves.
+        let is_symmetry = |isometry: &Matrix<f64>| {
+            let row: Option<Vec<usize>> = self
+                .vertices
+                .iter()
+                .map(|v| vertices.get(&PointOrd::new(isometry * v)).copied())
+                .collect();
+
+            row.is_some_and(|row| {
+                (2..rank).all(|r| {
+                    el_vertices[r].iter().all(|el| {
+                        let mut image: Vec<usize> = el.iter().map(|&v| row[v]).collect();
+                        image.sort_unstable();
+                        index_of[r].contains_key(&image)
+                    })
+                })
+            })
+        };
+
+        let flags: Vec<Flag> = FlagIter::new(&sorted.abs).collect();
+        let Some((base_flag, base_inverse)) = flags
+            .iter()
+            .find_map(|flag| Some((flag, flag.clone().vertex_sequence(&sorted).try_inverse()?)))
+        else {
+            return true;
+        };
+
+        // Adds the orbit of a flag to the flags we've found.
+        let add_orbit = |found: &mut HashSet<Flag>, flag: &Flag| {
+            for row in vertex_map {
+                match image(flag, row) {
+                    Some(image) => found.insert(image),
+                    None => return false,
+                };
+            }
+            true
+        };
+
+        let mut found = HashSet::new();
+        if !add_orbit(&mut found, base_flag) {
+            return true;
+        }
+
+        for flag in &flags {
+            if found.contains(flag)
+                || (0..=rank).any(|r| types_map_back[r][flag[r]] != types_map_back[r][base_flag[r]])
+            {
+                continue;
+            }
+
+            // This is the first flag we find in a new orbit.
+            let isometry = flag.clone().vertex_sequence(&sorted) * &base_inverse;
+            if is_symmetry(&isometry) || !add_orbit(&mut found, flag) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Builds the symmetry group of a product of two polytopes, such as a
+    /// duoprism, duotegum, duopyramid, or duocomb, from the symmetry groups of
+    /// its factors. When both factors are the same polytope, the symmetries
+    /// that swap them are added.
+    ///
+    /// Returns `None` if some of these transformations aren't symmetries of
+    /// the product, like when the factors are offset from the origin, or if
+    /// the product might have other symmetries, like the tesseract as the
+    /// duoprism of two squares. The symmetry group has to be computed from
+    /// scratch in these cases.
+    pub fn product_symmetry_group(
+        &self,
+        (p, p_group): (&Concrete, Group<vec::IntoIter<Matrix<f64>>>),
+        (q, q_group): (&Concrete, Group<vec::IntoIter<Matrix<f64>>>),
+    ) -> Option<SymmetryGroup> {
+        let dim = self.dim()?;
+        let (p_dim, q_dim) = (p.dim()?, q.dim()?);
+        if dim + 1 != self.rank() || p_dim == 0 || q_dim == 0 || p_dim + q_dim > dim {
+            return None;
+        }
+
+        // The groups must act on the spaces of the factors.
+        let p_group = p_group.cache();
+        let q_group = q_group.cache();
+        if p_group.clone().any(|mat| mat.nrows() != p_dim)
+            || q_group.clone().any(|mat| mat.nrows() != q_dim)
+        {
+            return None;
+        }
+
+        let vertices: BTreeMap<PointOrd<f64>, usize> = self
+            .vertices
+            .iter()
+            .cloned()
+            .map(PointOrd::new)
+            .zip(0..)
+            .collect();
+
+        // Returns how a transformation permutes the vertices, if it does.
+        let vertex_map_row = |isometry: &Matrix<f64>| -> Option<Vec<usize>> {
+            self.vertices
+                .iter()
+                .map(|v| vertices.get(&PointOrd::new(isometry * v)).copied())
+                .collect()
+        };
+
+        let extra = dim - p_dim - q_dim;
+        let product = p_group.direct_product(q_group).pad(extra);
+
+        // The factors can only be swapped if they're the same.
+        let same = |p: &Concrete, q: &Concrete| {
+            let set = |poly: &Concrete| -> BTreeSet<PointOrd<f64>> {
+                poly.vertices.iter().cloned().map(PointOrd::new).collect()
+            };
+            p_dim == q_dim && set(p) == set(q)
+        };
+
+        // Swapping the factors of a duopyramid also flips its height.
+        let swap = same(p, q).then(|| {
+            [1.0, -1.0].into_iter().find_map(|sign| {
+                let swap = Matrix::from_fn(dim, dim, |i, j| {
+                    if i < 2 * p_dim {
+                        ((i + p_dim) % (2 * p_dim) == j) as u8 as f64
+                    } else if i == j {
+                        sign
+                    } else {
+                        0.0
+                    }
+                });
+                vertex_map_row(&swap).map(|_| swap)
+            })
+        });
+
+        let group: Vec<_> = match swap.flatten() {
+            // Safety: the swap normalizes the direct product of two copies of
+            // the same group, so this is their semidirect product.
+            Some(swap) => unsafe { product.matrix_product(Group::two(dim, swap)) }.collect(),
+            None => product.collect(),
+        };
+
+        let mut vertex_map = VertexMap::new();
+        for isometry in &group {
+            vertex_map.push(vertex_map_row(isometry)?);
+        }
+
+        if self.has_other_symmetries(&vertex_map) {
+            return None;
+        }
+
+        // Safety: we built either a direct or a semidirect product of groups.
+        Some((unsafe { Group::new(dim, group.into_iter()) }, vertex_map))
+    }
+}
+
+/// A component of a compound polytope, i.e. a set of flags that can be reached
+/// from one another through flag changes.
+struct Component {
+    /// The flags in the component.
+    flags: Vec<Flag>,
+
+    /// The sorted indices of the elements of each rank in the component. The
+    /// list for the nullitope is left empty.
+    elements: Vec<Vec<usize>>,
+}
+
+/// The elements of a component of a polytope, as the sorted lists of the
+/// points their vertices lie on, for every rank from the vertices up to the
+/// facets. Two components with the same key are the same geometrically.
+type ComponentKey = Vec<Vec<Vec<usize>>>;
+
+impl Concrete {
+    /// Splits the flags of a polytope into components. The elements must be
+    /// sorted.
+    fn components(&self) -> Vec<Component> {
+        let rank = self.rank();
+        let flags: Vec<Flag> = FlagIter::new(&self.abs).collect();
+        let index_of: HashMap<&Flag, usize> = flags.iter().zip(0..).collect();
+
+        let mut partition: PartitionVec<()> = partition_vec![(); flags.len()];
+        for (idx, flag) in flags.iter().enumerate() {
+            for r in 1..rank {
+                partition.union(idx, index_of[&flag.change(&self.abs, r)]);
+            }
+        }
+
+        partition
+            .all_sets()
+            .map(|set| {
+                let flags: Vec<Flag> = set.map(|(idx, _)| flags[idx].clone()).collect();
+                let mut elements = vec![Vec::new(); rank];
+                for (r, elements) in elements.iter_mut().enumerate().skip(1) {
+                    let set: BTreeSet<usize> = flags.iter().map(|flag| flag[r]).collect();
+                    *elements = set.into_iter().collect();
+                }
+
+                Component { flags, elements }
+            })
+            .collect()
+    }
+
+    /// Computes the symmetry group of a polytope that might be a compound, or
+    /// that might have coincident vertices, along with a list of vertex
+    /// mappings.
+    ///
+    /// Any symmetry sends the first component onto one of the components
+    /// congruent to it. We find the symmetries of the first component, and an
+    /// isometry taking it onto each other component. Composing these gives
+    /// every candidate, and we keep those that permute the components. The
+    /// identity is always kept, so the group is never empty.
+    ///
+    /// Components are compared by the points their elements lie on, rather
+    /// than by the indices of their vertices, so that coincident components
+    /// are handled.
+    fn compound_symmetry_group_with(&self, cancel: &CancellationToken) -> Result<Option<SymmetryGroup>, Cancelled> {
+        let rank = self.rank();
+        let Some(dim) = self.dim() else {
+            return Ok(None);
+        };
+        if dim + 1 != rank {
+            return Ok(None);
+        }
+
+        let mut sorted = self.clone();
+        sorted.element_sort();
+        let components = sorted.components();
+
+        // Coincident vertices are identified with the same point.
+        let mut point_ids = BTreeMap::new();
+        let vertex_points: Vec<usize> = self
+            .vertices
+            .iter()
+            .map(|v| {
+                let len = point_ids.len();
+                *point_ids.entry(PointOrd::new(v.clone())).or_insert(len)
+            })
+            .collect();
+
+        let el_vertices: Vec<Vec<Vec<usize>>> = (0..rank)
+            .map(|r| (0..sorted.el_count(r)).map(|idx| sorted.abs.element_vertices(r, idx).unwrap()).collect())
+            .collect();
+
+        // The key of a component, after its vertices are sent to some points.
+        let key = |component: &Component, points: &[Option<usize>]| -> Option<ComponentKey> {
+            (1..rank)
+                .map(|r| {
+                    let mut elements = component.elements[r]
+                        .iter()
+                        .map(|&idx| {
+                            let mut el: Vec<usize> =
+                                el_vertices[r][idx].iter().map(|&v| points[v]).collect::<Option<_>>()?;
+                            el.sort_unstable();
+                            Some(el)
+                        })
+                        .collect::<Option<Vec<_>>>()?;
+                    elements.sort_unstable();
+                    Some(elements)
+                })
+                .collect()
+        };
+
+        // The points the vertices are sent to by an isometry.
+        let images = |isometry: &Matrix<f64>| -> Vec<Option<usize>> {
+            self.vertices
+                .iter()
+                .map(|v| point_ids.get(&PointOrd::new(isometry * v)).copied())
+                .collect()
+        };
+
+        let identity: Vec<Option<usize>> = vertex_points.iter().copied().map(Some).collect();
+        let keys: Vec<ComponentKey> = components.iter().map(|c| key(c, &identity).unwrap()).collect();
+        let mut by_key: BTreeMap<&ComponentKey, Vec<usize>> = BTreeMap::new();
+        for (idx, key) in keys.iter().enumerate() {
+            by_key.entry(key).or_default().push(idx);
+        }
+
+        // The flag of the first component every isometry is measured from.
+        let first = &components[0];
+        let Some(base_inverse) = first
+            .flags
+            .iter()
+            .find_map(|flag| flag.clone().vertex_sequence(&sorted).try_inverse())
+        else {
+            return Ok(None);
+        };
+        let isometry = |flag: &Flag| flag.clone().vertex_sequence(&sorted) * &base_inverse;
+
+        // The isometries taking the first component onto another one, found
+        // by trying every flag the base flag could be sent to.
+        let onto = |target: usize| -> Result<Vec<Matrix<f64>>, Cancelled> {
+            let mut isometries = Vec::new();
+            for flag in &components[target].flags {
+                cancel.check()?;
+                let isometry = isometry(flag);
+                if key(first, &images(&isometry)).as_ref() == Some(&keys[target]) {
+                    isometries.push(isometry);
+                }
+            }
+            Ok(isometries)
+        };
+
+        let first_group = onto(0)?;
+        let mut candidates = Vec::new();
+        for indices in by_key.values() {
+            // Coincident components would give the same isometries.
+            if let Some(translation) = onto(indices[0])?.into_iter().next() {
+                candidates.extend(first_group.iter().map(|g| &translation * g));
+            }
+        }
+
+        // The index of each vertex of every component, by the point it lies on.
+        let component_vertices: Vec<HashMap<usize, usize>> = components
+            .iter()
+            .map(|c| c.elements[1].iter().map(|&v| (vertex_points[v], v)).collect())
+            .collect();
+
+        let mut group = Vec::new();
+        let mut vertex_map = VertexMap::new();
+        'candidates: for isometry in candidates {
+            cancel.check()?;
+            let images = images(&isometry);
+
+            // Every component must be sent onto another one. Coincident
+            // components are matched in order, so that this is an action.
+            let mut used: HashMap<&ComponentKey, usize> = HashMap::new();
+            let mut component_map = Vec::with_capacity(components.len());
+            for component in &components {
+                let Some(image) = key(component, &images) else {
+                    continue 'candidates;
+                };
+                let Some((key, indices)) = by_key.get_key_value(&image) else {
+                    continue 'candidates;
+                };
+                let count = used.entry(*key).or_default();
+                let Some(&target) = indices.get(*count) else {
+                    continue 'candidates;
+                };
+                component_map.push(target);
+                *count += 1;
+            }
+
+            let mut row: Vec<usize> = (0..self.vertices.len()).collect();
+            let mut found = vec![false; row.len()];
+            for (component, &target) in components.iter().zip(&component_map) {
+                for &v in &component.elements[1] {
+                    if !found[v] {
+                        // The image key matched, so the point is there.
+                        row[v] = component_vertices[target][&images[v].unwrap()];
+                        found[v] = true;
+                    }
+                }
+            }
+
+            group.push(isometry);
+            vertex_map.push(row);
+        }
+
+        // Safety: these are all of the isometries that map the polytope onto
+        // itself.
+        Ok(Some((unsafe { Group::new(dim, group.into_iter()) }, vertex_map)))
+    }
+
+    /// Computes the symmetry group of a polytope from the automorphisms of the
+    /// graph of inner products between its vertices, along with a list of
+    /// vertex mappings. This is slower than the flag algorithm, but makes no
+    /// assumptions on the polytope besides its vertices being distinct, so
+    /// it's used whenever the former fails.
+    ///
+    /// An isometry fixing the origin preserves the inner products between the
+    /// vertices, and is determined by where it sends a basis of the space they
+    /// span. We try every way to send a basis made out of vertices to other
+    /// vertices with the same inner products, and keep the isometries that
+    /// send every element onto another. The directions perpendicular to every
+    /// vertex are either fixed or reversed, as otherwise the group would be
+    /// infinite.
+    fn graph_symmetry_group_with(&self, cancel: &CancellationToken) -> Result<Option<SymmetryGroup>, Cancelled> {
+        let dim = self.dim_or();
+        let vertices = &self.vertices;
+        let index_of: BTreeMap<PointOrd<f64>, usize> =
+            vertices.iter().map(|v| PointOrd::new(v.clone())).zip(0..).collect();
+        if index_of.len() != vertices.len() {
+            return Ok(None);
+        }
+
+        // A basis of the span of the vertices, chosen among them.
+        let mut basis: Vec<usize> = Vec::new();
+        for (idx, v) in vertices.iter().enumerate() {
+            if basis.len() == dim {
+                break;
+            }
+
+            let mut columns: Vec<_> = basis.iter().map(|&b| vertices[b].clone()).collect();
+            columns.push(v.clone());
+            if Matrix::from_columns(&columns).rank(f64::EPS) == columns.len() {
+                basis.push(idx);
+            }
+        }
+
+        // The projection onto the span of the basis is B(BᵀB)⁻¹Bᵀ, so that an
+        // isometry sending B to C is C(BᵀB)⁻¹Bᵀ on the span.
+        let identity = Matrix::identity(dim, dim);
+        let (pseudo_inverse, perpendicular) = if basis.is_empty() {
+            (None, identity.clone())
+        } else {
+            let b = Matrix::from_columns(&basis.iter().map(|&idx| vertices[idx].clone()).collect::<Vec<_>>());
+            let Some(gram_inverse) = (b.transpose() * &b).try_inverse() else {
+                return Ok(None);
+            };
+            let pseudo_inverse = gram_inverse * b.transpose();
+            let perpendicular = &identity - &b * &pseudo_inverse;
+            (Some(pseudo_inverse), perpendicular)
+        };
+        let flips: &[f64] = if basis.len() < dim { &[1.0, -1.0] } else { &[1.0] };
+
+        // Sets of elements' vertex sets.
+        let elements: Vec<HashSet<Vec<usize>>> = (0..self.rank())
+            .map(|r| {
+                (0..self.el_count(r))
+                    .map(|idx| {
+                        let mut vec = self.abs.element_vertices(r, idx).unwrap();
+                        vec.sort_unstable();
+                        vec
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let dot = |i: usize, j: usize| vertices[i].dot(&vertices[j]);
+        let matches = |c: usize, images: &[usize]| {
+            let b = basis[images.len()];
+            (dot(c, c) - dot(b, b)).abs() < f64::EPS
+                && images.iter().zip(&basis).all(|(&i, &a)| (dot(c, i) - dot(b, a)).abs() < f64::EPS)
+        };
+
+        let mut group = Vec::new();
+        let mut vertex_map = VertexMap::new();
+        let mut images: Vec<usize> = Vec::with_capacity(basis.len());
+        let mut next = 0;
+
+        // Walks through every way to send the basis to vertices with the same
+        // inner products.
+        loop {
+            cancel.check()?;
+
+            if images.len() < basis.len() {
+                if let Some(c) = (next..vertices.len()).find(|&c| matches(c, &images)) {
+                    images.push(c);
+                    next = 0;
+                    continue;
+                }
+            } else {
+                let on_span = match &pseudo_inverse {
+                    Some(pseudo_inverse) => {
+                        Matrix::from_columns(&images.iter().map(|&idx| vertices[idx].clone()).collect::<Vec<_>>())
+                            * pseudo_inverse
+                    }
+                    None => Matrix::zeros(dim, dim),
+                };
+
+                let row: Option<Vec<usize>> = vertices
+                    .iter()
+                    .map(|v| index_of.get(&PointOrd::new(&on_span * v)).copied())
+                    .collect();
+                let is_symmetry = row.as_ref().is_some_and(|row| {
+                    (2..self.rank()).all(|r| {
+                        elements[r].iter().all(|el| {
+                            let mut image: Vec<usize> = el.iter().map(|&v| row[v]).collect();
+                            image.sort_unstable();
+                            elements[r].contains(&image)
+                        })
+                    })
+                });
+
+                if is_symmetry {
+                    let row = row.unwrap();
+                    for &sign in flips {
+                        group.push(&on_span + &perpendicular * sign);
+                        vertex_map.push(row.clone());
+                    }
+                }
+            }
+
+            // Backtracks to the last basis vertex with untried images.
+            match images.pop() {
+                Some(c) => next = c + 1,
+                None => break,
+            }
+        }
+
+        if group.is_empty() {
+            return Ok(None);
+        }
+
+        // Safety: these are all of the isometries that map the polytope onto
+        // itself, up to the perpendicular directions.
+        Ok(Some((unsafe { Group::new(dim, group.into_iter()) }, vertex_map)))
+    }
+}
+
+/// The symmetry group of a polytope stored in a [`SymmetryCache`].
+#[derive(Clone)]
+struct CacheEntry {
+    /// The hash of the polytope.
+    hash: u64,
+
+    /// The elements of the symmetry group.
+    group: Vec<Matrix<f64>>,
+
+    /// The vertex map of the symmetry group.
+    vertex_map: VertexMap,
+
+    /// The classification of the symmetry group, once it's been found.
+    class: Option<GroupClass>,
+}
+
+/// Stores the symmetry groups of the last few polytopes they were computed
+/// for, so that they don't have to be found again. Polytopes are told apart
+/// by a hash of their vertices and elements.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "bevy", derive(Resource))]
+pub struct SymmetryCache {
+    /// The symmetry groups of the polytopes, from the most to the least
+    /// recent.
+    entries: VecDeque<CacheEntry>,
+}
+
+impl SymmetryCache {
+    /// Initializes an empty cache.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Hashes the vertices and elements of a polytope.
+    fn hash(poly: &Concrete) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for v in &poly.vertices {
+            for c in v.iter() {
+                c.to_bits().hash(&mut hasher);
+            }
+        }
+        for r in 0..=poly.rank() {
+            for el in poly[r].iter() {
+                el.subs.hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Returns the symmetry group of a polytope, if it's in the cache.
+    pub fn get(&self, poly: &Concrete) -> Option<SymmetryGroup> {
+        let hash = Self::hash(poly);
+        let entry = self.entries.iter().find(|entry| entry.hash == hash)?;
+        let dim = entry.group.first()?.nrows();
+
+        // Safety: only symmetry groups are put into the cache.
+        Some((
+            unsafe { Group::new(dim, entry.group.clone().into_iter()) },
+            entry.vertex_map.clone(),
+        ))
+    }
+
+    /// Puts the symmetry group of a polytope into the cache, forgetting the
+    /// least recent one if it's full.
+    pub fn insert(&mut self, poly: &Concrete, (group, vertex_map): SymmetryGroup) {
+        let hash = Self::hash(poly);
+        self.entries.retain(|entry| entry.hash != hash);
+        self.entries.push_front(CacheEntry {
+            hash,
+            group: group.collect(),
+            vertex_map,
+            class: None,
+        });
+        self.entries.truncate(SYMMETRY_CACHE_SIZE);
+    }
+
+    /// Returns the symmetry group of a polytope, computing it and putting it
+    /// into the cache if it's not there already.
+    pub fn symmetry_group(&mut self, poly: &Concrete) -> Option<SymmetryGroup> {
+        if let Some(group) = self.get(poly) {
+            return Some(group);
+        }
+
+        let group = poly.clone().get_symmetry_group()?;
+        self.insert(poly, group.clone());
+        Some(group)
+    }
+
+    /// Returns the classification of the symmetry group of a polytope, like
+    /// `H3` or `A2×A1`, computing the group if needed. The classification is
+    /// stored along with the group. See [`GroupClass`].
+    pub fn classify(&mut self, poly: &Concrete) -> Option<GroupClass> {
+        let (group, _) = self.symmetry_group(poly)?;
+        let hash = Self::hash(poly);
+        let entry = self.entries.iter_mut().find(|entry| entry.hash == hash)?;
+        Some(entry.class.get_or_insert_with(|| group.classify()).clone())
+    }
+
+    /// Returns the stabilizer of an element of a polytope under its symmetry
+    /// group, along with its vertex map. The symmetry group is taken from the
+    /// cache whenever possible.
+    pub fn element_stabilizer(&mut self, poly: &Concrete, rank: usize, idx: usize) -> Option<SymmetryGroup> {
+        let element = poly.abs.element_vertices(rank, idx)?;
+        let (group, vertex_map) = self.symmetry_group(poly)?;
+        Some(group.stabilizer_of_element(&vertex_map, &element))
+    }
+
+    /// Puts the symmetry group of a product of two polytopes into the cache,
+    /// building it from the symmetry groups of the factors when possible. See
+    /// [`Concrete::product_symmetry_group`]. Returns whether this succeeded.
+    pub fn insert_product(&mut self, product: &Concrete, p: &Concrete, q: &Concrete) -> bool {
+        let factor_groups = self
+            .symmetry_group(p)
+            .zip(self.symmetry_group(q))
+            .and_then(|((p_group, _), (q_group, _))| {
+                product.product_symmetry_group((p, p_group), (q, q_group))
+            });
+
+        match factor_groups {
+            Some(group) => {
+                self.insert(product, group);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
 /// A set of vertices.
 pub struct Vertices(pub Vec<Point<f64>>);
 
 impl Vertices {
     /// Uses the provided symmetry group on the vertices, also outputs the new vertex map.
-    pub fn copy_by_symmetry(&self, group: Group<vec::IntoIter<Matrix<f64>>>) -> (Self, Vec<Vec<usize>>) {
+    pub fn copy_by_symmetry(&self, group: Group<vec::IntoIter<Matrix<f64>>>) -> (Self, VertexMap) {
         let mut vertices = BTreeMap::<PointOrd<f64>, usize>::new();
         let mut vertices_vec = Vec::new();
         let mut c = 0;
@@ -226,7 +1138,7 @@ impl Vertices {
             }
         }
 
-        let mut vertex_map: Vec<Vec<usize>> = Vec::new();
+        let mut vertex_map = VertexMap::new();
 
         for isometry in group {
             let mut vertex_map_row = Vec::<usize>::new();
@@ -249,4 +1161,174 @@ impl Vertices {
             vertex_map,
         )
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::FromFile;
+
+    /// Returns the order of the symmetry group of a product, as built from its
+    /// factors.
+    fn product_order(product: &Concrete, p: &Concrete, q: &Concrete) -> Option<usize> {
+        let mut cache = SymmetryCache::new();
+        cache
+            .insert_product(product, p, q)
+            .then(|| cache.get(product).unwrap().0.count())
+    }
+
+    /// Builds the symmetries of some duoprisms and duotegums.
+    #[test]
+    fn products() {
+        let triangle = Concrete::polygon(3);
+        let pentagon = Concrete::polygon(5);
+
+        assert_eq!(product_order(&triangle.duoprism(&pentagon), &triangle, &pentagon), Some(60));
+        assert_eq!(product_order(&triangle.duoprism(&triangle), &triangle, &triangle), Some(72));
+        assert_eq!(product_order(&triangle.duotegum(&triangle), &triangle, &triangle), Some(72));
+    }
+
+    /// Finds the symmetries of polytopes the flag algorithm can't handle.
+    #[test]
+    fn fallback() {
+        let order = |mut poly: Concrete| poly.get_symmetry_group().unwrap().0.count();
+        assert_eq!(order(Concrete::point()), 1);
+        assert_eq!(order(Concrete::dyad()), 2);
+
+        // A square in 3D can also be reflected through its plane.
+        let mut square = Concrete::polygon(4);
+        for v in square.vertices.iter_mut() {
+            *v = v.push(0.0);
+        }
+        assert_eq!(order(square), 16);
+
+        // Both algorithms agree when they can both be used.
+        let cube = Concrete::cube();
+        let (group, vertex_map) = cube.graph_symmetry_group_with(&CancellationToken::new()).unwrap().unwrap();
+        assert_eq!(group.count(), 48);
+        assert_eq!(vertex_map.len(), 48);
+    }
+
+    /// Finds the symmetries of some compounds, including one whose components
+    /// coincide.
+    #[test]
+    fn compounds() {
+        let compound = |p: Concrete, q: Concrete| {
+            let mut compound = p;
+            compound.comp_append(q);
+            compound
+        };
+        let order = |mut poly: Concrete| {
+            let (group, vertex_map) = poly.get_symmetry_group().unwrap();
+            for row in vertex_map.iter() {
+                assert_eq!(row.iter().collect::<BTreeSet<_>>().len(), poly.vertices.len());
+            }
+            group.count()
+        };
+
+        assert_eq!(order(compound(Concrete::polygon(6), Concrete::polygon(6))), 12);
+
+        let mut big = Concrete::polygon(6);
+        big.scale(2.0);
+        assert_eq!(order(compound(Concrete::polygon(6), big)), 12);
+
+        let mut inverted = Concrete::simplex(4);
+        inverted.scale(-1.0);
+        assert_eq!(order(compound(Concrete::simplex(4), inverted)), 48);
+    }
+
+    /// A hexagon shaped like a pinwheel is chiral, as is its enantiomorph,
+    /// while a regular hexagon isn't.
+    #[test]
+    fn chirality() {
+        let vertices = (0..6)
+            .map(|k| {
+                let angle = (k / 2) as f64 * f64::TAU / 3.0 + (k % 2) as f64 * f64::TAU / 12.0;
+                let radius = (k % 2 + 1) as f64;
+                Point::from_vec(vec![radius * angle.cos(), radius * angle.sin()])
+            })
+            .collect();
+        let mut pinwheel = Concrete::new(vertices, crate::abs::Abstract::polygon(6));
+
+        assert_eq!(pinwheel.is_chiral(), Some(true));
+        assert_eq!(pinwheel.enantiomorph().is_chiral(), Some(true));
+        assert_eq!(Concrete::polygon(6).is_chiral(), Some(false));
+    }
+
+    /// Neither a point nor a dyad is chiral.
+    #[test]
+    fn chirality_low_rank() {
+        assert_eq!(Concrete::point().is_chiral(), Some(false));
+        assert_eq!(Concrete::dyad().is_chiral(), Some(false));
+    }
+
+    /// Finds the stabilizers of the elements of a cube, and checks that the
+    /// symmetry group is only computed once.
+    #[test]
+    fn stabilizers() {
+        let cube = Concrete::hypercube(4);
+        let mut cache = SymmetryCache::new();
+
+        for (rank, order) in [(1, 6), (2, 4), (3, 8), (4, 48)] {
+            let (group, vertex_map) = cache.element_stabilizer(&cube, rank, 0).unwrap();
+            assert_eq!(group.count(), order);
+            assert_eq!(vertex_map.len(), order);
+        }
+
+        assert_eq!(cache.entries.len(), 1);
+        assert!(cache.element_stabilizer(&cube, 3, 6).is_none());
+    }
+
+    /// Classifies the symmetry groups of a few polytopes, and stores the
+    /// classification.
+    #[test]
+    fn classify() {
+        let mut cache = SymmetryCache::new();
+        let prism = Concrete::polygon(3).duoprism(&Concrete::dyad());
+        assert_eq!(cache.classify(&prism).unwrap().to_string(), "A2×A1");
+        assert!(cache.entries[0].class.is_some());
+
+        assert_eq!(cache.classify(&Concrete::hypercube(5)).unwrap().to_string(), "B4");
+    }
+
+    /// Tells apart regular, uniform, and noble polytopes.
+    #[test]
+    fn transitivity() {
+        let cube = Concrete::cube().transitivity().unwrap();
+        assert!(cube.regular && cube.uniform && cube.noble() && cube.isotoxal());
+
+        // The truncated tetrahedron is uniform, but neither noble nor
+        // isotoxal.
+        let tut = Concrete::from_off(include_str!("../../../lib/3D/uniform/Truncate/tut.off"))
+            .unwrap()
+            .transitivity()
+            .unwrap();
+        assert!(tut.vertex && tut.uniform);
+        assert!(!tut.regular && !tut.edge && !tut.noble());
+
+        // A rectangle is isogonal, but not uniform.
+        let mut rectangle = Concrete::dyad().duoprism(&Concrete::dyad());
+        rectangle.vertices.iter_mut().for_each(|v| v[0] *= 2.0);
+        let rectangle = rectangle.transitivity().unwrap();
+        assert!(rectangle.vertex && !rectangle.edge && !rectangle.uniform);
+
+        // The rhombic dodecahedron is isotopic and isotoxal, but not isogonal.
+        let rhombic = Concrete::from_off(include_str!("../../../lib/3D/uniform/Quasiregular/co.off"))
+            .unwrap()
+            .try_dual()
+            .unwrap()
+            .transitivity()
+            .unwrap();
+        assert!(rhombic.facet && rhombic.edge && !rhombic.vertex && !rhombic.uniform);
+    }
+
+    /// The square duoprism is a tesseract, which has more symmetries than the
+    /// product of those of its factors.
+    #[test]
+    fn tesseract() {
+        let square = Concrete::polygon(4);
+        let tesseract = square.duoprism(&square);
+        assert_eq!(product_order(&tesseract, &square, &square), None);
+        assert_eq!(tesseract.clone().get_symmetry_group().unwrap().0.count(), 384);
+    }
+}
@@ -0,0 +1,596 @@
+//! Periodic tilings and honeycombs, stored as their quotient by a lattice of
+//! translations.
+//!
+//! A [`Tiling`] keeps a single copy of each of its elements up to
+//! translation. Each element refers to its subelements by their indices,
+//! along with the translations that take the stored copies of these to the
+//! ones it contains. Translations are written as integer combinations of the
+//! periods of the tiling. Operations like duals and truncations work directly
+//! on this quotient, and finite patches of the tiling are built by putting
+//! together as many copies of its elements as needed.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::Concrete;
+use crate::{
+    abs::{AbstractBuilder, SubelementList, Subelements},
+    float::Float,
+    geometry::{Point, Vector},
+};
+
+use approx::abs_diff_eq;
+use vec_like::*;
+
+/// A translation of a tiling, as the integer multiples of each of its periods
+/// that add up to it.
+pub type Offset = Vec<i64>;
+
+/// A copy of an element of a tiling, as the index of the stored copy and the
+/// translation that takes it there.
+pub type ElementCopy = (usize, Offset);
+
+/// Adds two translations.
+fn add(a: &[i64], b: &[i64]) -> Offset {
+    a.iter().zip(b).map(|(x, y)| x + y).collect()
+}
+
+/// Subtracts a translation from another.
+fn sub(a: &[i64], b: &[i64]) -> Offset {
+    a.iter().zip(b).map(|(x, y)| x - y).collect()
+}
+
+/// Returns every translation with `k` coordinates between `-radius` and
+/// `radius`.
+fn offsets(k: usize, radius: i64) -> Vec<Offset> {
+    let mut offsets = vec![Vec::new()];
+    for _ in 0..k {
+        offsets = offsets
+            .into_iter()
+            .flat_map(|offset| {
+                (-radius..=radius).map(move |x| {
+                    let mut offset = offset.clone();
+                    offset.push(x);
+                    offset
+                })
+            })
+            .collect();
+    }
+    offsets
+}
+
+/// The elements of some rank containing, or contained in, a copy of an
+/// element, at every other rank.
+type Closure = Vec<HashSet<ElementCopy>>;
+
+/// A chain of elements of a tiling, indexed by their ranks. Chains are only
+/// stored up to translation, with their highest element at the stored copy.
+type Chain = BTreeMap<usize, ElementCopy>;
+
+/// Translates every element of a chain so that its highest one becomes the
+/// stored copy, and returns the translation that undoes this.
+fn normalize(chain: &Chain) -> (Chain, Offset) {
+    let shift = chain.values().next_back().map(|(_, offset)| offset.clone()).unwrap_or_default();
+    let chain = chain
+        .iter()
+        .map(|(&rank, (idx, offset))| (rank, (*idx, sub(offset, &shift))))
+        .collect();
+    (chain, shift)
+}
+
+/// A periodic tiling or honeycomb, stored as its quotient by its lattice of
+/// translations. The ranks follow those of polytopes, so that the vertices
+/// have rank 1 and the tiles have rank `self.rank() - 1`.
+#[derive(Clone, Debug)]
+pub struct Tiling {
+    /// The vertices, one for each class under translation.
+    pub vertices: Vec<Point<f64>>,
+
+    /// The periods, whose integer combinations are the translations of the
+    /// tiling.
+    pub periods: Vec<Vector<f64>>,
+
+    /// The elements from the edges up to the tiles, one for each class under
+    /// translation. Each element is the list of the copies of the elements
+    /// of the rank below that it contains.
+    ranks: Vec<Vec<Vec<ElementCopy>>>,
+}
+
+impl Tiling {
+    /// Builds a tiling from its vertices, its periods, and its elements from
+    /// the edges up to the tiles.
+    ///
+    /// # Panics
+    /// Panics if some element refers to a subelement that doesn't exist, or
+    /// if some translation doesn't have one coordinate for each period.
+    pub fn new(vertices: Vec<Point<f64>>, periods: Vec<Vector<f64>>, ranks: Vec<Vec<Vec<ElementCopy>>>) -> Self {
+        let mut sub_count = vertices.len();
+        for elements in &ranks {
+            for (idx, offset) in elements.iter().flatten() {
+                assert!(*idx < sub_count, "subelement {} out of bounds", idx);
+                assert_eq!(offset.len(), periods.len(), "translation with the wrong number of coordinates");
+            }
+            sub_count = elements.len();
+        }
+
+        Self {
+            vertices,
+            periods,
+            ranks,
+        }
+    }
+
+    /// Returns the rank of the tiling, which is that of its patches.
+    pub fn rank(&self) -> usize {
+        self.ranks.len() + 2
+    }
+
+    /// Returns the number of elements of a given rank, up to translation.
+    pub fn el_count(&self, rank: usize) -> usize {
+        match rank {
+            0 => 0,
+            1 => self.vertices.len(),
+            _ => self.ranks.get(rank - 2).map_or(0, Vec::len),
+        }
+    }
+
+    /// Returns the copies of the subelements of an element of rank at least 2.
+    pub fn subs(&self, rank: usize, idx: usize) -> &[ElementCopy] {
+        &self.ranks[rank - 2][idx]
+    }
+
+    /// Returns the translation with some coordinates.
+    pub fn translation(&self, offset: &[i64]) -> Vector<f64> {
+        let dim = self.vertices.first().map_or(0, |v| v.len());
+        self.periods
+            .iter()
+            .zip(offset)
+            .fold(Vector::zeros(dim), |acc, (period, &k)| acc + period * k as f64)
+    }
+
+    /// Returns the average of the vertices of every element, computed like
+    /// [`ConcretePolytope::avg_vertex_map`](super::ConcretePolytope::avg_vertex_map).
+    /// The list at index `r` holds the elements of rank `r + 1`.
+    pub fn centers(&self) -> Vec<Vec<Point<f64>>> {
+        let mut centers = vec![self.vertices.clone()];
+
+        for elements in &self.ranks {
+            let below = centers.last().unwrap();
+            let next = elements
+                .iter()
+                .map(|subs| {
+                    subs.iter()
+                        .map(|(idx, offset)| &below[*idx] + self.translation(offset))
+                        .sum::<Point<f64>>()
+                        / f64::usize(subs.len())
+                })
+                .collect();
+            centers.push(next);
+        }
+
+        centers
+    }
+
+    /// Returns the copies of the superelements of the stored copy of every
+    /// element below the tiles. The list at index `r` holds the elements of
+    /// rank `r + 1`.
+    fn sups(&self) -> Vec<Vec<Vec<ElementCopy>>> {
+        let mut sups: Vec<Vec<Vec<ElementCopy>>> = (1..self.rank() - 1)
+            .map(|rank| vec![Vec::new(); self.el_count(rank)])
+            .collect();
+
+        for (r, elements) in self.ranks.iter().enumerate() {
+            for (idx, subs) in elements.iter().enumerate() {
+                for (sub, offset) in subs {
+                    sups[r][*sub].push((idx, offset.iter().map(|x| -x).collect()));
+                }
+            }
+        }
+
+        sups
+    }
+
+    /// Builds a finite patch of the tiling, made out of the copies of its
+    /// vertices that are translated by at most `radius` times each period,
+    /// and of every element whose subelements are all there.
+    pub fn patch(&self, radius: usize) -> Concrete {
+        let k = self.periods.len();
+
+        // An element might be stored far away from its vertices, so we look
+        // for copies of it in a larger box.
+        let reach = self
+            .ranks
+            .iter()
+            .flatten()
+            .flatten()
+            .flat_map(|(_, offset)| offset.iter().map(|x| x.abs()))
+            .max()
+            .unwrap_or(0);
+        let inner = radius as i64;
+        let outer = inner + reach * self.ranks.len() as i64;
+
+        // The index in the patch of each copy of an element of the last rank
+        // we built.
+        let mut index = HashMap::new();
+        let mut vertices = Vec::new();
+        for offset in offsets(k, inner) {
+            let translation = self.translation(&offset);
+            for (idx, vertex) in self.vertices.iter().enumerate() {
+                index.insert((idx, offset.clone()), vertices.len());
+                vertices.push(vertex + &translation);
+            }
+        }
+
+        let mut builder = AbstractBuilder::new();
+        builder.push_min();
+        builder.push_vertices(vertices.len());
+
+        for elements in &self.ranks {
+            let mut next = HashMap::new();
+            let mut list = SubelementList::new();
+
+            for offset in offsets(k, outer) {
+                for (idx, subs) in elements.iter().enumerate() {
+                    let subs: Option<Vec<usize>> = subs
+                        .iter()
+                        .map(|(sub, sub_offset)| index.get(&(*sub, add(sub_offset, &offset))).copied())
+                        .collect();
+
+                    if let Some(mut subs) = subs {
+                        subs.sort_unstable();
+                        subs.dedup();
+                        next.insert((idx, offset.clone()), list.len());
+                        list.push(Subelements::from(subs));
+                    }
+                }
+            }
+
+            builder.push(list);
+            index = next;
+        }
+
+        builder.push_max();
+
+        // Safety: the patch is built like the tiling it comes from, except
+        // for its boundary. Like the patches read from OFF files, it isn't a
+        // polytope in general.
+        Concrete::new(vertices, unsafe { builder.build() })
+    }
+
+    /// Returns the dual of the tiling, whose vertices are at the centers of
+    /// the tiles, as given by [`Self::centers`].
+    pub fn dual(&self) -> Self {
+        let centers = self.centers();
+        let vertices = centers.last().cloned().unwrap_or_default();
+
+        // The elements of each rank become the elements of the opposite rank,
+        // and they contain the elements that contained them.
+        let mut ranks = self.sups();
+        ranks.reverse();
+
+        Self::new(vertices, self.periods.clone(), ranks)
+    }
+
+    /// Returns the closure of each element under taking subelements or
+    /// superelements, depending on the lists passed, at every rank.
+    fn closures(&self, lists: &[Vec<Vec<ElementCopy>>], up: bool) -> Vec<Vec<Closure>> {
+        let rank = self.rank();
+        let mut closures: Vec<Vec<Closure>> = (1..rank)
+            .map(|r| vec![vec![HashSet::new(); rank]; self.el_count(r)])
+            .collect();
+
+        // We go from the elements whose closures are already known.
+        let ranks: Vec<usize> = if up { (1..rank).rev().collect() } else { (1..rank).collect() };
+        for &r in &ranks {
+            for idx in 0..self.el_count(r) {
+                let mut closure = vec![HashSet::new(); rank];
+                closure[r].insert((idx, vec![0; self.periods.len()]));
+
+                let next_rank = if up { r + 1 } else { r - 1 };
+                let neighbors = if up {
+                    lists.get(r - 1).map_or(&[][..], |list| &list[idx][..])
+                } else if r >= 2 {
+                    &lists[r - 2][idx][..]
+                } else {
+                    &[]
+                };
+
+                for (next, offset) in neighbors {
+                    for (s, elements) in closures[next_rank - 1][*next].iter().enumerate() {
+                        for (el, el_offset) in elements {
+                            closure[s].insert((*el, add(el_offset, offset)));
+                        }
+                    }
+                }
+
+                closures[r - 1][idx] = closure;
+            }
+        }
+
+        closures
+    }
+
+    /// Returns whether a copy of an element contains another, given the
+    /// closures of the elements under taking subelements.
+    fn contains(down: &[Vec<Closure>], hi: (usize, &ElementCopy), lo: (usize, &ElementCopy)) -> bool {
+        let (hi_rank, (hi_idx, hi_offset)) = hi;
+        let (lo_rank, (lo_idx, lo_offset)) = lo;
+        down[hi_rank - 1][*hi_idx][lo_rank].contains(&(*lo_idx, sub(lo_offset, hi_offset)))
+    }
+
+    /// Returns every chain that extends a given one with an element of each
+    /// of some ranks.
+    fn extensions(&self, chain: Chain, ranks: &[usize], down: &[Vec<Closure>], up: &[Vec<Closure>]) -> Vec<Chain> {
+        let Some((&rank, rest)) = ranks.split_first() else {
+            return vec![chain];
+        };
+
+        let higher = chain.range(rank + 1..).next();
+        let lower = chain.range(..rank).next_back();
+
+        // The candidates are found from the nearest element above, or below
+        // if there's none above, and must be incident to the nearest element
+        // on the other side.
+        let candidates: Vec<ElementCopy> = match (higher, lower) {
+            (Some((&r, (idx, offset))), _) => down[r - 1][*idx][rank]
+                .iter()
+                .map(|(el, el_offset)| (*el, add(el_offset, offset)))
+                .filter(|el| lower.is_none_or(|(&lo_rank, lo)| Self::contains(down, (rank, el), (lo_rank, lo))))
+                .collect(),
+            (None, Some((&r, (idx, offset)))) => up[r - 1][*idx][rank]
+                .iter()
+                .map(|(el, el_offset)| (*el, add(el_offset, offset)))
+                .collect(),
+            (None, None) => (0..self.el_count(rank))
+                .map(|idx| (idx, vec![0; self.periods.len()]))
+                .collect(),
+        };
+
+        candidates
+            .into_iter()
+            .flat_map(|el| {
+                let mut chain = chain.clone();
+                chain.insert(rank, el);
+                self.extensions(chain, rest, down, up)
+            })
+            .collect()
+    }
+
+    /// Returns an arbitrary truncate of the tiling, with the same arguments as
+    /// [`ConcretePolytope::truncate_with`](super::ConcretePolytope::truncate_with).
+    /// The node `r` stands for the elements of rank `r + 1`, and each vertex
+    /// is placed at the sum of the centers of the elements of the ringed
+    /// ranks it comes from, weighted by the depths.
+    ///
+    /// Each face of the truncate comes from a set of nodes whose connected
+    /// components all have a ringed node, and from a chain of elements of
+    /// the ranks that neither are in the set nor are unringed and away from
+    /// it.
+    pub fn truncate_with(&self, truncate_type: Vec<usize>, depth: Vec<f64>) -> Self {
+        let nodes = self.rank() - 1;
+        let mut ringed = vec![false; nodes];
+        for &node in &truncate_type {
+            ringed[node] = true;
+        }
+
+        // Whether a set of nodes is the generator of a face.
+        let is_active = |set: &[bool]| {
+            let mut component_ringed = false;
+            for node in 0..nodes {
+                if set[node] {
+                    component_ringed |= ringed[node];
+                    if node + 1 == nodes || !set[node + 1] {
+                        if !component_ringed {
+                            return false;
+                        }
+                        component_ringed = false;
+                    }
+                }
+            }
+            true
+        };
+
+        // The ranks of the chains giving the faces generated by a set.
+        let chain_ranks = |set: &[bool]| -> Vec<usize> {
+            (0..nodes)
+                .filter(|&node| {
+                    let near = set[node] || (node > 0 && set[node - 1]) || (node + 1 < nodes && set[node + 1]);
+                    !set[node] && (ringed[node] || near)
+                })
+                .map(|node| node + 1)
+                .rev()
+                .collect()
+        };
+
+        let down = self.closures(&self.ranks, false);
+        let up = self.closures(&self.sups(), true);
+
+        // The generating sets of the faces of each rank.
+        let mut sets: Vec<Vec<Vec<bool>>> = vec![Vec::new(); nodes];
+        for mask in 0..(1usize << nodes) {
+            let set: Vec<bool> = (0..nodes).map(|node| mask & (1 << node) != 0).collect();
+            let size = set.iter().filter(|&&b| b).count();
+            if size < nodes && is_active(&set) {
+                sets[size].push(set);
+            }
+        }
+
+        // The faces of each generating set, and their indices.
+        let mut faces: HashMap<Vec<bool>, Vec<Chain>> = HashMap::new();
+        let mut indices: HashMap<(Vec<bool>, Chain), usize> = HashMap::new();
+        for level in &sets {
+            let mut count = 0;
+            for set in level {
+                let chains = self.extensions(Chain::new(), &chain_ranks(set), &down, &up);
+                for chain in &chains {
+                    indices.insert((set.clone(), chain.clone()), count);
+                    count += 1;
+                }
+                faces.insert(set.clone(), chains);
+            }
+        }
+
+        let centers = self.centers();
+        let vertices = sets[0]
+            .iter()
+            .flat_map(|set| &faces[set])
+            .map(|chain| {
+                chain
+                    .iter()
+                    .map(|(&rank, (idx, offset))| {
+                        (&centers[rank - 1][*idx] + self.translation(offset)) * depth[rank - 1]
+                    })
+                    .sum()
+            })
+            .collect();
+
+        let mut ranks = Vec::new();
+        for level in sets.iter().take(nodes).skip(1) {
+            let mut elements = Vec::new();
+
+            for set in level {
+                let own_ranks = chain_ranks(set);
+                for chain in &faces[set] {
+                    let mut subs = Vec::new();
+
+                    // The subfaces come from the sets with one node less,
+                    // and from the chains compatible with this one.
+                    for node in (0..nodes).filter(|&node| set[node]) {
+                        let mut sub_set = set.clone();
+                        sub_set[node] = false;
+                        if !is_active(&sub_set) {
+                            continue;
+                        }
+
+                        let sub_ranks = chain_ranks(&sub_set);
+                        let missing: Vec<usize> =
+                            sub_ranks.iter().copied().filter(|r| !own_ranks.contains(r)).collect();
+
+                        for extension in self.extensions(chain.clone(), &missing, &down, &up) {
+                            let sub_chain: Chain =
+                                extension.into_iter().filter(|(r, _)| sub_ranks.contains(r)).collect();
+                            let (sub_chain, shift) = normalize(&sub_chain);
+                            let sub = (indices[&(sub_set.clone(), sub_chain)], shift);
+                            if !subs.contains(&sub) {
+                                subs.push(sub);
+                            }
+                        }
+                    }
+
+                    elements.push(subs);
+                }
+            }
+
+            ranks.push(elements);
+        }
+
+        Self::new(vertices, self.periods.clone(), ranks)
+    }
+
+    /// Cuts off every vertex of the tiling, like
+    /// [`ConcretePolytope::truncate`](super::ConcretePolytope::truncate). A
+    /// depth of 1 gives the rectification.
+    pub fn truncate(&self, depth: f64) -> Self {
+        let mut depths = vec![0.0; self.rank() - 1];
+        if abs_diff_eq!(depth, 1.0, epsilon = f64::EPS) {
+            depths[1] = 1.0;
+            self.truncate_with(vec![1], depths)
+        } else {
+            depths[0] = 1.0 - depth;
+            depths[1] = depth;
+            self.truncate_with(vec![0, 1], depths)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abs::Ranked, conc::ConcretePolytope};
+
+    /// The square tiling, with a single vertex.
+    fn square() -> Tiling {
+        Tiling::new(
+            vec![Point::from_vec(vec![0.0, 0.0])],
+            vec![Vector::from_vec(vec![1.0, 0.0]), Vector::from_vec(vec![0.0, 1.0])],
+            vec![
+                vec![vec![(0, vec![0, 0]), (0, vec![1, 0])], vec![(0, vec![0, 0]), (0, vec![0, 1])]],
+                vec![vec![(0, vec![0, 0]), (1, vec![1, 0]), (0, vec![0, 1]), (1, vec![0, 0])]],
+            ],
+        )
+    }
+
+    /// The triangular tiling, with a single vertex.
+    fn triangular() -> Tiling {
+        let h = 3f64.sqrt() / 2.0;
+        Tiling::new(
+            vec![Point::from_vec(vec![0.0, 0.0])],
+            vec![Vector::from_vec(vec![1.0, 0.0]), Vector::from_vec(vec![0.5, h])],
+            vec![
+                vec![
+                    vec![(0, vec![0, 0]), (0, vec![1, 0])],
+                    vec![(0, vec![0, 0]), (0, vec![0, 1])],
+                    vec![(0, vec![1, 0]), (0, vec![0, 1])],
+                ],
+                vec![
+                    vec![(0, vec![0, 0]), (1, vec![0, 0]), (2, vec![0, 0])],
+                    vec![(0, vec![0, 1]), (1, vec![1, 0]), (2, vec![0, 0])],
+                ],
+            ],
+        )
+    }
+
+    /// Returns the element counts of a tiling, up to translation.
+    fn counts(tiling: &Tiling) -> Vec<usize> {
+        (1..tiling.rank()).map(|r| tiling.el_count(r)).collect()
+    }
+
+    /// Patches have as many copies as asked for.
+    #[test]
+    fn patch() {
+        let patch = square().patch(1);
+        assert_eq!(patch.vertex_count(), 9);
+        assert_eq!(patch.edge_count(), 12);
+        assert_eq!(patch.el_count(3), 4);
+        assert!(patch.is_equilateral_with(1.0));
+
+        let patch = triangular().patch(2);
+        assert_eq!(patch.vertex_count(), 25);
+        assert!(patch.is_equilateral_with(1.0));
+    }
+
+    /// The square tiling is self-dual, and the triangular tiling is dual to
+    /// the hexagonal one.
+    #[test]
+    fn dual() {
+        let dual = square().dual();
+        assert_eq!(counts(&dual), [1, 2, 1]);
+        assert!(dual.patch(2).is_equilateral_with(1.0));
+
+        let hexagonal = triangular().dual();
+        assert_eq!(counts(&hexagonal), [2, 3, 1]);
+        assert!(hexagonal.patch(2).is_equilateral_with(1.0 / 3f64.sqrt()));
+        assert_eq!(counts(&hexagonal.dual()), [1, 3, 2]);
+    }
+
+    /// Truncates and rectifies the square and triangular tilings.
+    #[test]
+    fn truncate() {
+        let truncated = square().truncate(2.0 - 2f64.sqrt());
+        assert_eq!(counts(&truncated), [4, 6, 2]);
+        assert!(truncated.patch(2).is_equilateral());
+
+        let rectified = square().truncate(1.0);
+        assert_eq!(counts(&rectified), [2, 4, 2]);
+        assert!(rectified.patch(2).is_equilateral_with(0.5f64.sqrt()));
+
+        // The rectified triangular tiling is the trihexagonal tiling.
+        let trihexagonal = triangular().truncate(1.0);
+        assert_eq!(counts(&trihexagonal), [3, 6, 3]);
+        assert!(trihexagonal.patch(2).is_equilateral_with(0.5));
+
+        // The hexagonal tiling, truncated from the triangular one.
+        let hexagonal = triangular().truncate_with(vec![0, 1], vec![1.0 / 3.0, 2.0 / 3.0, 0.0]);
+        assert_eq!(counts(&hexagonal), [6, 9, 3]);
+        assert!(hexagonal.patch(2).is_equilateral_with(1.0 / 3.0));
+    }
+}
@@ -0,0 +1,336 @@
+//! Triangulates the faces of a polytope. This is the triangulation used to
+//! render polytopes, and it can be used for anything else that needs the raw
+//! triangles, like volume computations or exporting to other formats.
+
+use std::collections::HashMap;
+
+use super::{
+    cycle::{Cycle, CycleList},
+    Concrete, ConcretePolytope,
+};
+use crate::{
+    abs::{ElementList, Ranked},
+    float::Float,
+    geometry::{Point, Subspace},
+    Polytope,
+};
+
+use lyon::{math::point, path::Path, tessellation::*};
+use vec_like::*;
+
+/// Attempts to turn the cycle into a 2D path, which can then be given to
+/// the tessellator. Uses the specified vertex list to grab the coordinates
+/// of the vertices on the path.
+///
+/// If the cycle isn't 2D, we return `None`.
+pub fn path(cycle: &Cycle, vertices: &[Point<f64>]) -> Option<Path> {
+    let mut builder = Path::builder();
+    let cycle_iter = cycle.iter().map(|&idx| &vertices[idx]);
+
+    // We don't bother with any polygons that aren't in 2D space.
+    let s = Subspace::from_points_with(cycle_iter.clone(), 2)?;
+    if s.rank() != 2 {
+        return None
+    }
+
+    let mut flat_points = cycle_iter.map(|p| s.flatten(p));
+
+    let path_point = |v: &Point<f64>| point(v[0] as f32, v[1] as f32);
+
+    // We build a path from the polygon.
+    let v = flat_points.next().unwrap();
+    builder.begin(path_point(&v));
+
+    for v in flat_points {
+        builder.line_to(path_point(&v));
+    }
+
+    builder.end(true);
+
+    Some(builder.build())
+}
+
+/// A triangle in a [`Triangulation`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Triangle {
+    /// The indices of the vertices of the triangle. Indices past the vertices
+    /// of the polytope refer to the extra vertices of the triangulation.
+    pub vertices: [usize; 3],
+
+    /// The index of the face of the polytope the triangle belongs to.
+    pub face: usize,
+}
+
+/// Represents a triangulation of the faces of a [`Concrete`]. It stores the
+/// vertex indices that make up the triangulation of the polytope, as well as
+/// the extra vertices that may be needed to represent it.
+///
+/// The triangles of orientable polyhedra are wound consistently, and
+/// counterclockwise as seen from the outside in 3D.
+pub struct Triangulation {
+    /// Extra vertices that might be needed for the triangulation.
+    pub extra_vertices: Vec<Point<f64>>,
+
+    /// Indices of the vertices that make up the triangles.
+    pub triangles: Vec<u32>,
+
+    /// The index of the face each triangle belongs to.
+    pub faces: Vec<usize>,
+}
+
+impl Triangulation {
+    /// Creates a new triangulation from a polytope.
+    pub fn new(polytope: &Concrete) -> Self {
+        let mut extra_vertices = Vec::new();
+        let mut triangles = Vec::new();
+        let mut faces_of = Vec::new();
+        let empty_els = ElementList::new();
+
+        // Either returns a reference to the element list of a given rank, or
+        // returns a reference to an empty element list.
+        let elements_or = |r| polytope.get_element_list(r).unwrap_or(&empty_els);
+
+        let edges = elements_or(2);
+        let faces = elements_or(3);
+
+        let concrete_vertex_len = polytope.vertices.len() as u32;
+
+        // We render each face separately.
+        for (face_idx, face) in faces.iter().enumerate() {
+            // We tesselate this path.
+            let cycles = CycleList::from_edges(face.subs.iter().map(|&i| &edges[i].subs));
+            for cycle in cycles {
+                if let Some(path) = path(&cycle, &polytope.vertices) {
+                    let mut geometry: VertexBuffers<_, u32> = VertexBuffers::new();
+
+                    // Configures all of the options of the tessellator.
+                    FillTessellator::new()
+                        .tessellate_with_ids(
+                            path.id_iter(),
+                            &path,
+                            None,
+                            &FillOptions::with_fill_rule(Default::default(), FillRule::NonZero)
+                                .with_tolerance(f64::EPS as f32),
+                            &mut BuffersBuilder::new(&mut geometry, |vertex: FillVertex<'_>| {
+                                vertex.sources().next().unwrap()
+                            }),
+                        )
+                        .unwrap();
+
+                    // Maps EndpointIds to the indices in the original vertex list.
+                    let mut id_to_idx = Vec::new();
+                    for idx in cycle {
+                        id_to_idx.push(idx);
+                    }
+
+                    // We map the output vertices to the original ones, and add any
+                    // extra vertices that may be needed.
+                    let mut vertex_hash = HashMap::new();
+
+                    for (new_id, vertex_source) in geometry.vertices.into_iter().enumerate() {
+                        let new_id = new_id as u32;
+
+                        match vertex_source {
+                            // This is one of the concrete vertices of the polytope.
+                            VertexSource::Endpoint { id } => {
+                                vertex_hash.insert(new_id, id_to_idx[id.to_usize()] as u32);
+                            }
+
+                            // This is a new vertex that has been added to the tesselation.
+                            VertexSource::Edge { from, to, t } => {
+                                let from = &polytope.vertices[id_to_idx[from.to_usize()]];
+                                let to = &polytope.vertices[id_to_idx[to.to_usize()]];
+
+                                let t = t as f64;
+                                let p = from * (1.0 - t) + to * t;
+
+                                vertex_hash
+                                    .insert(new_id, concrete_vertex_len + extra_vertices.len() as u32);
+
+                                extra_vertices.push(p);
+                            }
+                        }
+                    }
+
+                    // Add all of the new indices we've found onto the triangle vector.
+                    for new_idx in geometry
+                        .indices
+                        .iter()
+                        .map(|idx| *vertex_hash.get(idx).unwrap())
+                    {
+                        triangles.push(new_idx);
+                    }
+
+                    // Every triangle of this cycle belongs to the same face.
+                    faces_of.resize(triangles.len() / 3, face_idx);
+                }
+            }
+        }
+
+        let mut triangulation = Self {
+            extra_vertices,
+            triangles,
+            faces: faces_of,
+        };
+        triangulation.orient(polytope);
+        triangulation
+    }
+
+    /// Winds the triangles of a polyhedron according to a consistent
+    /// orientation of its faces, if it has one. In 3D, every component is then
+    /// turned so that the normals of its triangles point outwards, i.e. so
+    /// that it has positive volume.
+    fn orient(&mut self, polytope: &Concrete) {
+        if polytope.rank() != 4 {
+            return;
+        }
+
+        let orientation = if polytope.abs.sorted() {
+            polytope.abs.face_orientation()
+        } else {
+            let mut abs = polytope.abs.clone();
+            abs.element_sort();
+            abs.face_orientation()
+        };
+        let Some(orientation) = orientation else {
+            return;
+        };
+
+        // The plane of every face, together with the signed area of the face
+        // within it when its edges are taken in order.
+        let planes: Vec<_> = orientation
+            .edges
+            .iter()
+            .map(|edges| {
+                let vertex = |idx: usize| &polytope.vertices[idx];
+                let subspace =
+                    Subspace::from_points_with(edges.iter().map(|edge| vertex(edge[0])), 2)?;
+                if subspace.rank() != 2 {
+                    return None;
+                }
+
+                let area: f64 = edges
+                    .iter()
+                    .map(|edge| {
+                        let p = subspace.flatten(vertex(edge[0]));
+                        let q = subspace.flatten(vertex(edge[1]));
+                        p[0] * q[1] - p[1] * q[0]
+                    })
+                    .sum();
+                (area.abs() > f64::EPS).then_some((subspace, area))
+            })
+            .collect();
+
+        let mut flip = vec![false; self.len()];
+        for (idx, triangle) in self.iter().enumerate() {
+            if let Some((subspace, area)) = &planes[triangle.face] {
+                let [p, q, r] = triangle
+                    .vertices
+                    .map(|v| subspace.flatten(self.vertex(polytope, v)));
+                let (u, v) = (q - &p, r - &p);
+                flip[idx] = (u[0] * v[1] - u[1] * v[0]) * area < 0.0;
+            }
+        }
+
+        // Only in 3D do components have an inside and an outside.
+        if polytope.dim() == Some(3) {
+            let component_count = orientation.components.iter().max().map_or(0, |&c| c + 1);
+            let mut volumes = vec![0.0; component_count];
+
+            for (triangle, &flip) in self.iter().zip(&flip) {
+                let [p, q, r] = triangle.vertices.map(|v| self.vertex(polytope, v));
+                let det = p[0] * (q[1] * r[2] - q[2] * r[1]) - p[1] * (q[0] * r[2] - q[2] * r[0])
+                    + p[2] * (q[0] * r[1] - q[1] * r[0]);
+                volumes[orientation.components[triangle.face]] += if flip { -det } else { det };
+            }
+
+            for (triangle, flip) in self.iter().zip(&mut flip) {
+                if volumes[orientation.components[triangle.face]] < -f64::EPS {
+                    *flip = !*flip;
+                }
+            }
+        }
+
+        for (triangle, flip) in self.triangles.chunks_exact_mut(3).zip(flip) {
+            if flip {
+                triangle.swap(1, 2);
+            }
+        }
+    }
+
+    /// Returns the number of triangles in the triangulation.
+    pub fn len(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// Returns whether the triangulation has no triangles.
+    pub fn is_empty(&self) -> bool {
+        self.faces.is_empty()
+    }
+
+    /// Iterates over the triangles of the triangulation, along with the faces
+    /// they belong to.
+    pub fn iter(&self) -> impl Iterator<Item = Triangle> + '_ {
+        self.triangles
+            .chunks_exact(3)
+            .zip(&self.faces)
+            .map(|(t, &face)| Triangle {
+                vertices: [t[0] as usize, t[1] as usize, t[2] as usize],
+                face,
+            })
+    }
+
+    /// Returns the coordinates of a vertex of the triangulation, given the
+    /// polytope it was built from.
+    pub fn vertex<'a>(&'a self, polytope: &'a Concrete, idx: usize) -> &'a Point<f64> {
+        polytope
+            .vertices
+            .get(idx)
+            .unwrap_or_else(|| &self.extra_vertices[idx - polytope.vertices.len()])
+    }
+}
+
+impl Concrete {
+    /// Triangulates the faces of the polytope.
+    pub fn triangulation(&self) -> Triangulation {
+        Triangulation::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+
+    /// Triangulates a cube.
+    #[test]
+    fn cube() {
+        let cube = Concrete::cube();
+        let triangulation = cube.triangulation();
+
+        assert_eq!(triangulation.len(), 12);
+        assert!(triangulation.extra_vertices.is_empty());
+        for face in 0..6 {
+            assert_eq!(triangulation.iter().filter(|t| t.face == face).count(), 2);
+        }
+    }
+
+    /// Checks that the triangles of a cube point outwards.
+    #[test]
+    fn cube_orientation() {
+        let cube = Concrete::cube();
+        let triangulation = cube.triangulation();
+
+        for triangle in triangulation.iter() {
+            let [p, q, r] = triangle.vertices.map(|v| triangulation.vertex(&cube, v));
+            let (u, v) = (q - p, r - p);
+            let normal = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            let center = (p + q + r) / 3.0;
+            assert!((0..3).map(|i| normal[i] * center[i]).sum::<f64>() > 0.0);
+        }
+    }
+}
@@ -0,0 +1,591 @@
+//! Builds [Wythoffians](https://polytope.miraheze.org/wiki/Wythoffian) from
+//! their Coxeter diagrams, together with their whole face lattices.
+//!
+//! The vertices of a Wythoffian are the orbit of the generator point under its
+//! Coxeter group. Each of its faces is the orbit of the generator under the
+//! subgroup generated by some set of mirrors, moved around by the group. This
+//! lets us find every face by only working with the permutations the mirrors
+//! induce on the vertices.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use super::Concrete;
+use crate::{
+    abs::{Abstract, AbstractBuilder, SubelementList, Subelements},
+    cox::{
+        cd::{Cd, CdError, Node},
+        CoxError, CoxType,
+    },
+    float::Float,
+    geometry::{Point, Subspace},
+    group::kd_tree::KdTree,
+    Polytope,
+};
+
+use vec_like::*;
+
+/// The largest number of vertices of a Wythoffian we attempt to build.
+///
+/// Diagrams with fractional edges can describe dense groups even when their
+/// mirrors fit on a sphere, and this keeps us from trying to list infinitely
+/// many vertices.
+pub const MAX_VERTICES: usize = 1 << 20;
+
+/// The largest number of nodes of a diagram we build a Wythoffian from.
+///
+/// Every set of nodes is checked for whether it generates a face, and the
+/// number of faces can itself grow exponentially with the number of nodes, as
+/// it does for simplices. Building the 16-simplex already takes several
+/// seconds, and every further node roughly quadruples that.
+pub const MAX_NODES: usize = 16;
+
+/// Any error encountered while building a Wythoffian.
+#[derive(Clone, Copy, Debug)]
+pub enum WythoffError {
+    /// The diagram couldn't be parsed, or its group is infinite.
+    Cox(CoxError),
+
+    /// The polytope has more than [`MAX_VERTICES`] vertices. This happens in
+    /// particular when the group of the diagram is dense.
+    TooManyVertices,
+
+    /// The diagram has more than [`MAX_NODES`] nodes.
+    TooManyNodes,
+
+    /// The snub nodes of the diagram don't describe an alternation. This
+    /// happens when a snub node and a non-snub node are joined by an edge with
+    /// an odd numerator, or when the alternated faces don't form a polytope.
+    Alternation,
+}
+
+impl From<CoxError> for WythoffError {
+    fn from(err: CoxError) -> Self {
+        Self::Cox(err)
+    }
+}
+
+impl From<CdError> for WythoffError {
+    fn from(err: CdError) -> Self {
+        Self::Cox(err.into())
+    }
+}
+
+impl Display for WythoffError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cox(err) => write!(f, "{}", err),
+            Self::TooManyVertices => write!(
+                f,
+                "the polytope has more than {} vertices, or its group is dense",
+                MAX_VERTICES
+            ),
+            Self::TooManyNodes => write!(f, "diagrams can have at most {} nodes", MAX_NODES),
+            Self::Alternation => write!(f, "the snub nodes don't describe an alternation"),
+        }
+    }
+}
+
+impl std::error::Error for WythoffError {}
+
+/// The result of building a Wythoffian.
+pub type WythoffResult<T> = Result<T, WythoffError>;
+
+/// The elements of a given rank of a polytope, each of them given by a bitmask
+/// of the mirrors that generate it and its sorted vertex indices.
+type Elements = Vec<(u64, Vec<usize>)>;
+
+/// Returns whether every entry of a sorted slice is in another sorted slice.
+fn is_subset(sub: &[usize], set: &[usize]) -> bool {
+    sub.iter().all(|v| set.binary_search(v).is_ok())
+}
+
+/// Builds the face lattice of a polytope from its vertex count and the vertex
+/// sets of its elements of each rank, starting from the edges.
+///
+/// An element is a subelement of another one of the next rank when its
+/// vertices are among theirs, and its mirrors are among theirs. The second
+/// condition tells apart elements that happen to share vertices in star
+/// polytopes.
+fn build_abstract(vertex_count: usize, elements: &[Elements]) -> AbstractBuilder {
+    let mut builder = AbstractBuilder::with_rank_capacity(elements.len() + 2);
+    builder.push_min();
+    builder.push_vertices(vertex_count);
+
+    if let Some(edges) = elements.first() {
+        let mut subelements = SubelementList::with_capacity(edges.len());
+        for (_, edge) in edges {
+            subelements.push(Subelements::from(edge.clone()));
+        }
+        builder.push(subelements);
+    }
+
+    for pair in elements.windows(2) {
+        let (lower, upper) = (&pair[0], &pair[1]);
+
+        // The elements of the lower rank by their first vertex, and by their
+        // first two vertices. Every subelement of an element has these among
+        // the element's vertices, so this way we find each candidate once.
+        let mut by_first = vec![Vec::new(); vertex_count];
+        let mut by_pair = HashMap::new();
+        for (idx, (_, el)) in lower.iter().enumerate() {
+            by_first[el[0]].push(idx);
+            by_pair.entry((el[0], el[1])).or_insert_with(Vec::new).push(idx);
+        }
+
+        let mut subelements = SubelementList::with_capacity(upper.len());
+        for (mask, el) in upper {
+            let is_sub = |&idx: &usize| {
+                let (sub_mask, sub) = &lower[idx];
+                sub_mask & !mask == 0 && is_subset(sub, el)
+            };
+
+            // Looking up every pair of vertices is faster for small elements
+            // whose vertices are in many others, as in the Gosset polytopes.
+            let pair_count = el.len() * (el.len() - 1) / 2;
+            let first_count: usize = el.iter().map(|&v| by_first[v].len()).sum();
+
+            let mut subs: Vec<usize> = if pair_count < first_count {
+                el.iter()
+                    .enumerate()
+                    .flat_map(|(i, &a)| el[i + 1..].iter().map(move |&b| (a, b)))
+                    .filter_map(|key| by_pair.get(&key))
+                    .flatten()
+                    .copied()
+                    .filter(is_sub)
+                    .collect()
+            } else {
+                el.iter()
+                    .flat_map(|&v| &by_first[v])
+                    .copied()
+                    .filter(is_sub)
+                    .collect()
+            };
+            subs.sort_unstable();
+            subelements.push(Subelements::from(subs));
+        }
+        builder.push(subelements);
+    }
+
+    builder.push_max();
+    builder
+}
+
+/// The orbit of the generator point of a Wythoffian, together with the
+/// structure of its diagram.
+struct Wythoff {
+    /// The number of nodes of the diagram.
+    dim: usize,
+
+    /// The vertices of the polytope. The first one is the generator.
+    vertices: Vec<Point<f64>>,
+
+    /// For each mirror, the index of the reflection of each vertex.
+    perms: Vec<Vec<usize>>,
+
+    /// For each node, the bitmask of the nodes joined to it by an edge.
+    adjacent: Vec<u64>,
+
+    /// The bitmask of the ringed and snub nodes.
+    rings: u64,
+
+    /// The bitmask of the snub nodes.
+    snubs: u64,
+}
+
+impl Wythoff {
+    /// Finds the vertices of the Wythoffian described by a diagram.
+    fn new(cd: &Cd) -> WythoffResult<Self> {
+        let dim = cd.dim();
+        if dim > MAX_NODES {
+            return Err(WythoffError::TooManyNodes);
+        }
+
+        let cox = cd.cox();
+        match cox.cox_type() {
+            CoxType::Spherical => {}
+            CoxType::Affine => return Err(CoxError::Affine.into()),
+            CoxType::Hyperbolic => return Err(CoxError::Hyperbolic.into()),
+        }
+        let normals = cox.normals().ok_or(CoxError::Affine)?;
+        let generator = cd.generator().ok_or(CoxError::Affine)?;
+
+        let mut adjacent = vec![0; dim];
+        let mut rings = 0;
+        let mut snubs = 0;
+        for (i, node) in cd.node_iter().enumerate() {
+            for j in 0..dim {
+                if i != j && (cox[(i, j)] - 2.0).fabs() > f64::EPS {
+                    adjacent[i] |= 1 << j;
+                }
+            }
+
+            match node {
+                Node::Unringed => {}
+                Node::Ringed(_) => rings |= 1 << i,
+                Node::Snub(_) => {
+                    rings |= 1 << i;
+                    snubs |= 1 << i;
+                }
+            }
+        }
+
+        // Reflects every vertex found through every mirror.
+        let mut tree = KdTree::new();
+        tree.insert(generator.clone());
+        let mut vertices = vec![generator];
+        let mut perms = vec![Vec::new(); dim];
+
+        let mut idx = 0;
+        while idx < vertices.len() {
+            for (i, n) in normals.column_iter().enumerate() {
+                let p = &vertices[idx];
+                let q = p - n * (2.0 * p.dot(&n));
+
+                let q_idx = match tree.find(&q) {
+                    Some(q_idx) => q_idx,
+                    None => {
+                        if vertices.len() == MAX_VERTICES {
+                            return Err(WythoffError::TooManyVertices);
+                        }
+
+                        tree.insert(q.clone());
+                        vertices.push(q);
+                        vertices.len() - 1
+                    }
+                };
+                perms[i].push(q_idx);
+            }
+
+            idx += 1;
+        }
+
+        Ok(Self {
+            dim,
+            vertices,
+            perms,
+            adjacent,
+            rings,
+            snubs,
+        })
+    }
+
+    /// Returns the connected component of the diagram induced by a set of
+    /// nodes that contains a given node.
+    fn component(&self, node: usize, mask: u64) -> u64 {
+        let mut component = 1 << node;
+        loop {
+            let next = (0..self.dim)
+                .filter(|&i| component & (1 << i) != 0)
+                .fold(component, |next, i| next | (self.adjacent[i] & mask));
+
+            if next == component {
+                return component;
+            }
+            component = next;
+        }
+    }
+
+    /// Returns whether every connected component of a set of nodes has a
+    /// ringed node. These are exactly the sets of mirrors that generate the
+    /// faces of the polytope from its generator.
+    fn is_active(&self, mask: u64) -> bool {
+        let mut left = mask;
+
+        while left != 0 {
+            let component = self.component(left.trailing_zeros() as usize, mask);
+            if component & self.rings == 0 {
+                return false;
+            }
+            left &= !component;
+        }
+
+        true
+    }
+
+    /// Returns the rank of the polytope, i.e. the number of nodes in the
+    /// components of the diagram with a ringed node.
+    fn rank(&self) -> usize {
+        (0..self.dim)
+            .filter(|&i| self.component(i, u64::MAX) & self.rings != 0)
+            .count()
+    }
+
+    /// Returns the sorted vertices of the face of the generator with a given
+    /// set of mirrors, i.e. the orbit of the generator under them.
+    fn base_face(&self, mask: u64) -> Vec<usize> {
+        let mut face = vec![0];
+        let mut found = HashSet::from([0]);
+
+        let mut idx = 0;
+        while let Some(&v) = face.get(idx) {
+            for (i, perm) in self.perms.iter().enumerate() {
+                if mask & (1 << i) != 0 && found.insert(perm[v]) {
+                    face.push(perm[v]);
+                }
+            }
+            idx += 1;
+        }
+
+        face.sort_unstable();
+        face
+    }
+
+    /// Returns the elements of the polytope from the edges up to the facets.
+    fn elements(&self, rank: usize) -> Vec<Elements> {
+        let mut elements = vec![Vec::new(); rank.saturating_sub(1)];
+
+        for mask in 1..1u64 << self.dim {
+            let el_rank = mask.count_ones() as usize;
+            if el_rank >= rank || !self.is_active(mask) {
+                continue;
+            }
+
+            // Moves the face of the generator around.
+            let base = self.base_face(mask);
+            let mut faces = vec![base.clone()];
+            let mut found = HashSet::from([base]);
+
+            let mut idx = 0;
+            while let Some(face) = faces.get(idx).cloned() {
+                for perm in &self.perms {
+                    let mut image: Vec<usize> = face.iter().map(|&v| perm[v]).collect();
+                    image.sort_unstable();
+
+                    if found.insert(image.clone()) {
+                        faces.push(image);
+                    }
+                }
+                idx += 1;
+            }
+
+            elements[el_rank - 1].extend(faces.into_iter().map(|face| (mask, face)));
+        }
+
+        elements
+    }
+
+    /// Alternates a Wythoffian, removing every other vertex as determined by
+    /// its snub nodes. Returns the remaining vertices and the vertex sets of
+    /// the elements of the alternated polytope, from the edges up to the
+    /// facets.
+    ///
+    /// The elements of the alternated polytope are the alternated elements of
+    /// the original, together with the vertex figures of the removed vertices
+    /// in each of their elements. Those that degenerate into lower ranks are
+    /// sorted by the rank of their span.
+    fn alternate(&self, elements: &[Elements]) -> WythoffResult<(Vec<Point<f64>>, Vec<Elements>)> {
+        // Whether each vertex is removed. Reflecting through a snub mirror
+        // switches this.
+        let mut removed = vec![None; self.vertices.len()];
+        removed[0] = Some(false);
+        for v in 0..self.vertices.len() {
+            let parity = removed[v].expect("vertices are found in order");
+            for (i, perm) in self.perms.iter().enumerate() {
+                let new_parity = parity ^ (self.snubs & (1 << i) != 0);
+                let old_parity = removed[perm[v]];
+                match old_parity {
+                    None => removed[perm[v]] = Some(new_parity),
+                    Some(old_parity) if old_parity != new_parity => {
+                        return Err(WythoffError::Alternation);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        // The indices of the remaining vertices.
+        let mut new_idx = vec![None; self.vertices.len()];
+        let mut vertices = Vec::new();
+        for (v, p) in self.vertices.iter().enumerate() {
+            if removed[v] == Some(false) {
+                new_idx[v] = Some(vertices.len());
+                vertices.push(p.clone());
+            }
+        }
+
+        // The remaining neighbors of each removed vertex.
+        let mut neighbors = vec![Vec::new(); self.vertices.len()];
+        for (_, edge) in elements.first().into_iter().flatten() {
+            let (a, b) = (edge[0], edge[1]);
+            if new_idx[a].is_none() && new_idx[b].is_some() {
+                neighbors[a].push(b);
+            } else if new_idx[b].is_none() && new_idx[a].is_some() {
+                neighbors[b].push(a);
+            }
+        }
+
+        let full_rank = Subspace::from_points(vertices.iter()).rank();
+        let mut new_elements = vec![Vec::new(); full_rank.saturating_sub(1)];
+        let mut found = HashSet::new();
+
+        // Adds an element with some vertices, unless they span a point or the
+        // whole polytope.
+        let mut add = |el: &mut dyn Iterator<Item = usize>| {
+            let mut el: Vec<usize> = el.filter_map(|v| new_idx[v]).collect();
+            if el.len() < 2 {
+                return;
+            }
+            el.sort_unstable();
+
+            let rank = Subspace::from_points(el.iter().map(|&v| &vertices[v])).rank();
+            if rank < full_rank && found.insert(el.clone()) {
+                new_elements[rank - 1].push((0, el));
+            }
+        };
+
+        let all: Vec<usize> = (0..self.vertices.len()).collect();
+        for el in elements.iter().flatten().map(|(_, el)| el).chain([&all]) {
+            add(&mut el.iter().copied());
+
+            for &v in el {
+                if new_idx[v].is_none() {
+                    let mut figure = neighbors[v]
+                        .iter()
+                        .copied()
+                        .filter(|u| el.binary_search(u).is_ok());
+                    add(&mut figure);
+                }
+            }
+        }
+
+        Ok((vertices, new_elements))
+    }
+}
+
+impl Concrete {
+    /// Builds the Wythoffian described by a Coxeter diagram, with the
+    /// generator point at the position given by the values of its nodes.
+    ///
+    /// If the diagram has snub nodes, the Wythoffian with these nodes ringed is
+    /// alternated. Nodes in components of the diagram without any ringed node
+    /// don't add to the rank of the polytope.
+    pub fn wythoff(cd: &Cd) -> WythoffResult<Self> {
+        let wythoff = Wythoff::new(cd)?;
+        let rank = wythoff.rank();
+        if rank == 0 {
+            return Ok(Self::new(wythoff.vertices, Abstract::point()));
+        }
+
+        let elements = wythoff.elements(rank);
+        if wythoff.snubs == 0 {
+            let builder = build_abstract(wythoff.vertices.len(), &elements);
+
+            // Safety: the faces of a Wythoffian form a polytope.
+            return Ok(Self::new(wythoff.vertices, unsafe { builder.build() }));
+        }
+
+        let (vertices, elements) = wythoff.alternate(&elements)?;
+        if vertices.len() == 1 {
+            return Ok(Self::new(vertices, Abstract::point()));
+        }
+
+        let builder = build_abstract(vertices.len(), &elements);
+        if builder.ranks().is_valid().is_err() {
+            return Err(WythoffError::Alternation);
+        }
+
+        // Safety: we just checked that the alternation is a polytope.
+        Ok(Self::new(vertices, unsafe { builder.build() }))
+    }
+
+    /// Parses a Coxeter diagram and builds the Wythoffian it describes. See
+    /// [`Self::wythoff`].
+    pub fn parse_wythoff(input: &str) -> WythoffResult<Self> {
+        Self::wythoff(&Cd::parse(input)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{conc::ConcretePolytope, test};
+
+    /// Builds a Wythoffian and checks its element counts.
+    fn wythoff<I: IntoIterator<Item = usize>>(input: &str, element_counts: I) -> Concrete {
+        let poly = Concrete::parse_wythoff(input).unwrap();
+        test(&poly, element_counts);
+        poly
+    }
+
+    /// The regular polytopes have unit edges.
+    #[test]
+    fn regular() {
+        for poly in [
+            wythoff("x3o3o", [1, 4, 6, 4, 1]),
+            wythoff("x4o3o", [1, 8, 12, 6, 1]),
+            wythoff("x5o3o", [1, 20, 30, 12, 1]),
+            wythoff("x3o3o3o", [1, 5, 10, 10, 5, 1]),
+            wythoff("x3o4o3o", [1, 24, 96, 96, 24, 1]),
+        ] {
+            assert!(poly.is_equilateral_with(1.0));
+        }
+    }
+
+    /// Tests some truncates.
+    #[test]
+    fn truncates() {
+        wythoff("x3x", [1, 6, 6, 1]);
+        wythoff("o4x3o", [1, 12, 24, 14, 1]);
+        wythoff("x4x3x", [1, 48, 72, 26, 1]);
+        wythoff("x3x3x3x", [1, 120, 240, 150, 30, 1]);
+    }
+
+    /// Tests some polytopes with branching diagrams.
+    #[test]
+    fn branching() {
+        wythoff("x3o3o *b3o", [1, 8, 24, 32, 16, 1]);
+        wythoff("x3x3o3o *b3o", [1, 160, 560, 640, 280, 42, 1]);
+        wythoff("x3o3o3o3o *c3o", [1, 27, 216, 720, 1080, 648, 99, 1]);
+    }
+
+    /// Tests some star polytopes.
+    #[test]
+    fn stars() {
+        wythoff("x5/2o", [1, 5, 5, 1]);
+        wythoff("x5/2o5o", [1, 12, 30, 12, 1]);
+        wythoff("o5/2x5o", [1, 30, 60, 24, 1]);
+    }
+
+    /// Disconnected diagrams give products, and unringed components don't add
+    /// to the rank.
+    #[test]
+    fn products() {
+        wythoff("x x", [1, 4, 4, 1]);
+        wythoff("x3x x", [1, 12, 18, 8, 1]);
+        wythoff("x3o o", [1, 3, 3, 1]);
+        wythoff("o", [1, 1]);
+    }
+
+    /// Tests some alternations.
+    #[test]
+    fn snubs() {
+        wythoff("s4o3o", [1, 4, 6, 4, 1]);
+        wythoff("s3s3s", [1, 12, 30, 20, 1]);
+        wythoff("s3s4s", [1, 24, 60, 38, 1]);
+        wythoff("s2s", [1, 2, 1]);
+    }
+
+    /// Infinite groups and invalid alternations give errors.
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            Concrete::parse_wythoff("x4o4o"),
+            Err(WythoffError::Cox(CoxError::Affine))
+        ));
+        assert!(matches!(
+            Concrete::parse_wythoff("x3o7o"),
+            Err(WythoffError::Cox(CoxError::Hyperbolic))
+        ));
+        assert!(matches!(
+            Concrete::parse_wythoff("s3o3o"),
+            Err(WythoffError::Alternation)
+        ));
+        assert!(matches!(
+            Concrete::parse_wythoff(&format!("x{}", "3o".repeat(MAX_NODES))),
+            Err(WythoffError::TooManyNodes)
+        ));
+    }
+}
@@ -0,0 +1,243 @@
+//! Builds [zonotopes](https://en.wikipedia.org/wiki/Zonohedron#Zonotopes),
+//! the Minkowski sums of segments, together with their face lattices.
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::Concrete;
+use crate::{
+    abs::{Abstract, AbstractBuilder, SubelementList, Subelements},
+    float::Float,
+    geometry::{Point, Subspace, Vector},
+    Polytope,
+};
+
+use itertools::Itertools;
+use vec_like::*;
+
+/// The sign of the dot product of a linear functional with each generator of a
+/// zonotope. The face of the zonotope where the functional is maximized is the
+/// sum of the generators with a positive sign, minus those with a negative
+/// sign, plus the segments of those with a zero sign, all halved.
+type SignVector = Vec<i8>;
+
+/// Returns the sign vector of a linear functional.
+fn sign_vector(functional: &Vector<f64>, generators: &[Vector<f64>]) -> SignVector {
+    generators
+        .iter()
+        .map(|g| {
+            let dot = functional.dot(g);
+            if dot.abs() < f64::EPS {
+                0
+            } else if dot > 0.0 {
+                1
+            } else {
+                -1
+            }
+        })
+        .collect()
+}
+
+/// Composes two sign vectors, taking the sign of the first vector except
+/// where it's zero. This corresponds to maximizing the first functional, and
+/// then the second one within that face.
+fn compose(s: &[i8], t: &[i8]) -> SignVector {
+    s.iter()
+        .zip(t)
+        .map(|(&a, &b)| if a == 0 { b } else { a })
+        .collect()
+}
+
+/// Returns whether the face with one sign vector is contained in the face with
+/// another.
+fn is_subface(sub: &[i8], face: &[i8]) -> bool {
+    sub.iter().zip(face).all(|(&a, &b)| b == 0 || a == b)
+}
+
+/// Returns the rank of the span of some vectors.
+fn span_rank<'a, I: Iterator<Item = &'a Vector<f64>>>(dim: usize, vectors: I) -> usize {
+    let mut subspace = Subspace::new(Point::zeros(dim));
+    for v in vectors {
+        subspace.add(v);
+    }
+    subspace.rank()
+}
+
+impl Concrete {
+    /// Builds the zonotope generated by a set of vectors, i.e. the Minkowski
+    /// sum of the segments from `-v / 2` to `v / 2` for each vector `v`. The
+    /// result is centered at the origin, and every generator is the direction
+    /// of some of its edges.
+    ///
+    /// Unlike the hull of the sums of the generators, this keeps the actual
+    /// faces of the zonotope. Parallel generators give longer edges, and the
+    /// result lives in the span of the generators, so it can be lower rank
+    /// than the number of coordinates.
+    ///
+    /// # Panics
+    /// Panics if the generators don't all have the same number of coordinates.
+    pub fn zonotope(generators: &[Vector<f64>]) -> Self {
+        let dim = generators.first().map_or(0, |g| g.len());
+        assert!(
+            generators.iter().all(|g| g.len() == dim),
+            "generators must have the same number of coordinates"
+        );
+
+        let generators: Vec<Vector<f64>> = generators
+            .iter()
+            .filter(|g| g.norm() > f64::EPS)
+            .cloned()
+            .collect();
+
+        // We work within the span of the generators.
+        let mut span = Subspace::new(Point::zeros(dim));
+        for g in &generators {
+            span.add(g);
+        }
+        let rank = span.rank();
+        if rank == 0 {
+            return Self::new(vec![Point::zeros(dim)], Abstract::point());
+        }
+
+        let flat: Vec<Vector<f64>> = generators.iter().map(|g| span.flatten(g)).collect();
+
+        // The facets come from the hyperplanes spanned by the generators. Their
+        // sign vectors are the cocircuits of the generators.
+        let mut cocircuits: BTreeSet<SignVector> = BTreeSet::new();
+        for combination in (0..flat.len()).combinations(rank - 1) {
+            let mut hyperplane = Subspace::new(Point::zeros(rank));
+            for &idx in &combination {
+                hyperplane.add(&flat[idx]);
+            }
+            if hyperplane.rank() != rank - 1 {
+                continue;
+            }
+
+            let normal = (0..rank)
+                .find_map(|i| hyperplane.normal(&Vector::from_fn(rank, |j, _| (i == j) as u8 as f64)))
+                .unwrap();
+            let sign = sign_vector(&normal, &flat);
+            cocircuits.insert(sign.iter().map(|&x| -x).collect());
+            cocircuits.insert(sign);
+        }
+
+        // Every face is a composition of facets.
+        let zero = vec![0; flat.len()];
+        let mut faces: Vec<SignVector> = vec![zero.clone()];
+        let mut found: BTreeSet<SignVector> = BTreeSet::from([zero]);
+        let mut idx = 0;
+        while let Some(face) = faces.get(idx).cloned() {
+            for cocircuit in &cocircuits {
+                let new_face = compose(&face, cocircuit);
+                if found.insert(new_face.clone()) {
+                    faces.push(new_face);
+                }
+            }
+            idx += 1;
+        }
+
+        // Sorts the faces by rank, leaving out the whole zonotope.
+        let mut by_rank: Vec<Vec<SignVector>> = vec![Vec::new(); rank + 1];
+        for face in faces {
+            let zeros = flat.iter().zip(&face).filter(|(_, s)| **s == 0);
+            let face_rank = span_rank(rank, zeros.map(|(g, _)| g)) + 1;
+            if face_rank <= rank {
+                by_rank[face_rank].push(face);
+            }
+        }
+
+        let vertices = by_rank[1]
+            .iter()
+            .map(|sign| {
+                generators
+                    .iter()
+                    .zip(sign)
+                    .fold(Point::zeros(dim), |v, (g, &s)| v + g * (s as f64 / 2.0))
+            })
+            .collect();
+
+        let mut builder = AbstractBuilder::with_rank_capacity(rank + 1);
+        builder.push_min();
+        builder.push_vertices(by_rank[1].len());
+        for pair in by_rank[1..].windows(2) {
+            let (lower, upper) = (&pair[0], &pair[1]);
+            let indices: HashMap<&SignVector, usize> =
+                lower.iter().enumerate().map(|(idx, s)| (s, idx)).collect();
+
+            let mut subelements = SubelementList::with_capacity(upper.len());
+            for face in upper {
+                let subs: Vec<usize> = lower
+                    .iter()
+                    .filter(|sub| is_subface(sub, face))
+                    .map(|sub| indices[sub])
+                    .collect();
+                subelements.push(Subelements::from(subs));
+            }
+            builder.push(subelements);
+        }
+        builder.push_max();
+
+        // Safety: the faces of a zonotope form a polytope.
+        Self::new(vertices, unsafe { builder.build() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+    use nalgebra::dvector;
+
+    /// Three orthogonal vectors generate a cube.
+    #[test]
+    fn cube() {
+        let zonotope = Concrete::zonotope(&[
+            dvector![1.0, 0.0, 0.0],
+            dvector![0.0, 1.0, 0.0],
+            dvector![0.0, 0.0, 1.0],
+        ]);
+        test(&zonotope, [1, 8, 12, 6, 1]);
+        assert!(zonotope.vertices.iter().all(|v| v.iter().all(|c| (c.abs() - 0.5).abs() < f64::EPS)));
+    }
+
+    /// Three vectors at 120° generate a hexagon.
+    #[test]
+    fn hexagon() {
+        let generators: Vec<_> = (0..3)
+            .map(|k| {
+                let angle = k as f64 * f64::TAU / 3.0;
+                dvector![angle.cos(), angle.sin()]
+            })
+            .collect();
+        test(&Concrete::zonotope(&generators), [1, 6, 6, 1]);
+    }
+
+    /// The four diagonals of a cube generate a rhombic dodecahedron.
+    #[test]
+    fn rhombic_dodecahedron() {
+        test(
+            &Concrete::zonotope(&[
+                dvector![1.0, 1.0, 1.0],
+                dvector![1.0, -1.0, -1.0],
+                dvector![-1.0, 1.0, -1.0],
+                dvector![-1.0, -1.0, 1.0],
+            ]),
+            [1, 14, 24, 12, 1],
+        );
+    }
+
+    /// Parallel and zero generators don't add any faces, and the zonotope
+    /// lives in the span of the generators.
+    #[test]
+    fn degenerate() {
+        test(
+            &Concrete::zonotope(&[
+                dvector![1.0, 0.0, 0.0],
+                dvector![2.0, 0.0, 0.0],
+                dvector![0.0, 1.0, 0.0],
+                dvector![0.0, 0.0, 0.0],
+            ]),
+            [1, 4, 4, 1],
+        );
+        test(&Concrete::zonotope(&[]), [1, 1]);
+    }
+}
@@ -121,6 +121,9 @@ pub enum Node {
 }
 
 impl Node {
+    /// Every character that [`Self::from_char`] reads as a node.
+    pub const SYMBOLS: &'static str = "osvxqfhkuwFeQdVUAXB";
+
     /// Returns twice the distance from the generator point to the hyperplane
     /// corresponding to this node.
     pub fn value(&self) -> f64 {
@@ -175,6 +178,19 @@ impl Node {
         }))
     }
 
+    /// Converts a node back into the character that represents it in
+    /// [`Self::from_char`], if there is one. Every snub node is written as
+    /// `s`.
+    pub fn to_char(&self) -> Option<char> {
+        match self {
+            Self::Unringed => Some('o'),
+            Self::Snub(_) => Some('s'),
+            Self::Ringed(x) => Self::SYMBOLS.chars().find(|&c| {
+                matches!(Self::from_char(c), Some(Self::Ringed(y)) if (x - y).fabs() < f64::EPS)
+            }),
+        }
+    }
+
     /// Attempts to convert a character into a [`Node`]. Returns a
     /// [`CdError::InvalidSymbol`] if it fails.
     pub fn from_char_or(c: char, pos: usize) -> CdResult<Self> {
@@ -230,6 +246,16 @@ impl Edge {
     pub fn eq_two(&self) -> bool {
         self.num == self.den * 2
     }
+
+    /// Writes the edge as it's read in inline ASCII notation, like `3` or
+    /// `5/2`.
+    pub fn inline(&self) -> String {
+        if self.den == 1 {
+            self.num.to_string()
+        } else {
+            format!("{}/{}", self.num, self.den)
+        }
+    }
 }
 
 impl Display for Edge {
@@ -398,6 +424,57 @@ impl Cd {
         Vector::from_iterator(self.dim(), self.node_iter().map(|node| node.value()))
     }
 
+    /// Returns the edge between two nodes, if any.
+    pub fn edge(&self, a: usize, b: usize) -> Option<Edge> {
+        self.0
+            .find_edge(NodeIndex::new(a), NodeIndex::new(b))
+            .map(|idx| self.0[idx])
+    }
+
+    /// Writes the Coxeter diagram in inline ASCII notation, so that
+    /// [`Self::parse`] reads back the same diagram. Nodes are written in
+    /// order, and edges between nodes that aren't consecutive are written
+    /// with virtual nodes. Returns `None` if this needs a virtual node past
+    /// `*z`.
+    pub fn inline(&self) -> Option<String> {
+        let mut inline = String::new();
+
+        for (idx, node) in self.node_iter().enumerate() {
+            if idx != 0 {
+                match self.edge(idx - 1, idx) {
+                    Some(edge) => inline.push_str(&edge.inline()),
+                    None => inline.push(' '),
+                }
+            }
+
+            match node.to_char() {
+                Some(c) => inline.push(c),
+                None => inline.push_str(&format!("({})", node.value())),
+            }
+        }
+
+        for edge in self.raw_edges() {
+            let a = edge.source().index().min(edge.target().index());
+            let b = edge.source().index().max(edge.target().index());
+
+            if b != a + 1 {
+                if b >= 26 {
+                    return None;
+                }
+
+                let virtual_node = |idx: usize| char::from(b'a' + idx as u8);
+                inline.push_str(&format!(
+                    " *{}{}*{}",
+                    virtual_node(a),
+                    edge.weight.inline(),
+                    virtual_node(b)
+                ));
+            }
+        }
+
+        Some(inline)
+    }
+
     /// Returns whether a CD is minimal, i.e. whether every connected component
     /// has at least one ringed node.
     pub fn minimal(&self) -> bool {
@@ -446,13 +523,15 @@ impl Cd {
     }
 
     /// Returns a point in the position specified by the Coxeter diagram,
-    /// using the set of mirrors generated by [`Cox::normals`].    
+    /// using the set of mirrors generated by [`Cox::normals`]. Its distance to
+    /// the mirror of each node is half the node's value.
     pub fn generator(&self) -> Option<Point<f64>> {
-        let mut vector = self.node_vector();
+        let mut vector = self.node_vector() / 2.0;
 
         self.cox()
             .normals()?
-            .solve_upper_triangular_mut(&mut vector)
+            .transpose()
+            .solve_lower_triangular_mut(&mut vector)
             .then(|| vector)
     }
 }
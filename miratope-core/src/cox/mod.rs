@@ -4,19 +4,94 @@ pub mod cd;
 pub mod parse;
 
 use std::{
+    fmt::Display,
     iter,
     ops::{Index, IndexMut},
 };
 
 use crate::float::Float;
 use crate::group::Group;
-use crate::{geometry::Matrix, group::GenIter};
+use crate::{
+    geometry::{Matrix, Point},
+    group::{cox_iter::CoxIter, weyl_iter::WeylIter, GenIter},
+};
 
 use nalgebra::dmatrix;
 
 use crate::geometry::VectorView;
 
-use self::cd::{Cd, CdResult};
+use self::cd::{Cd, CdError, CdResult};
+
+/// The kind of space in which the mirrors of a Coxeter diagram fit, which
+/// determines whether its group is finite.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoxType {
+    /// The mirrors fit on a sphere. If every edge of the diagram is an
+    /// integer, the group is finite.
+    Spherical,
+
+    /// The mirrors fit in Euclidean space, and the group is the symmetry group
+    /// of a Euclidean tiling.
+    Affine,
+
+    /// The mirrors don't fit in spherical or Euclidean space. This includes the
+    /// symmetry groups of hyperbolic tilings.
+    Hyperbolic,
+}
+
+impl Display for CoxType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Spherical => write!(f, "spherical"),
+            Self::Affine => write!(f, "affine"),
+            Self::Hyperbolic => write!(f, "hyperbolic"),
+        }
+    }
+}
+
+/// Any error encountered while trying to generate the group of a Coxeter
+/// diagram.
+#[derive(Clone, Copy, Debug)]
+pub enum CoxError {
+    /// The diagram couldn't be parsed.
+    Cd(CdError),
+
+    /// The group is infinite, since it's the symmetry group of a Euclidean
+    /// tiling. Its elements can't be listed, but it can still describe a
+    /// tiling.
+    Affine,
+
+    /// The group is infinite, and its mirrors don't even fit in Euclidean
+    /// space.
+    Hyperbolic,
+}
+
+impl From<CdError> for CoxError {
+    fn from(err: CdError) -> Self {
+        Self::Cd(err)
+    }
+}
+
+impl Display for CoxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cd(err) => write!(f, "{}", err),
+            Self::Affine => write!(
+                f,
+                "the diagram is affine, so its group is infinite and describes a Euclidean tiling"
+            ),
+            Self::Hyperbolic => write!(
+                f,
+                "the diagram is hyperbolic, so its group is infinite and describes a hyperbolic tiling"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CoxError {}
+
+/// The result of trying to generate the group of a Coxeter diagram.
+pub type CoxResult<T> = Result<T, CoxError>;
 
 /// Represents a [Coxeter matrix](https://en.wikipedia.org/wiki/Coxeter_matrix),
 /// which itself represents a [`Cd`]. This representation makes many
@@ -53,6 +128,11 @@ impl Cox<f64> {
         self.0.nrows()
     }
 
+    /// Returns a reference to the Coxeter matrix.
+    pub fn matrix(&self) -> &Matrix<f64> {
+        &self.0
+    }
+
     /// Links together two nodes with a given edge.
     pub fn link(&mut self, i: usize, j: usize, edge: f64) {
         self[(i, j)] = edge;
@@ -140,8 +220,43 @@ impl Cox<f64> {
         )
     }
 
+    /// Returns the [Gram matrix](https://en.wikipedia.org/wiki/Gram_matrix) of
+    /// the unit normals of the mirrors, with the sign convention that makes
+    /// them point out of the fundamental domain. Its (i, j) entry is
+    /// `-cos(π / m)`, where `m` is the (i, j) entry of the Coxeter matrix.
+    pub fn gram(&self) -> Matrix<f64> {
+        Matrix::from_fn(self.dim(), self.dim(), |i, j| {
+            if i == j {
+                1.0
+            } else {
+                -(f64::PI / self[(i, j)]).fcos()
+            }
+        })
+    }
+
+    /// Returns the kind of space in which the mirrors of the diagram fit. This
+    /// is determined by the eigenvalues of the [Gram matrix](Self::gram): the
+    /// mirrors fit on a sphere when they're all positive, and in Euclidean
+    /// space when they're all non-negative.
+    pub fn cox_type(&self) -> CoxType {
+        if self.dim() == 0 {
+            return CoxType::Spherical;
+        }
+
+        let min = self.gram().symmetric_eigenvalues().min();
+        if min > f64::EPS {
+            CoxType::Spherical
+        } else if min > -f64::EPS {
+            CoxType::Affine
+        } else {
+            CoxType::Hyperbolic
+        }
+    }
+
     /// Returns an upper triangular matrix whose columns are unit normal vectors
-    /// for the hyperplanes described by the Coxeter matrix.
+    /// for the hyperplanes described by the Coxeter matrix. Their dot products
+    /// are the entries of the [Gram matrix](Self::gram), so that any two of
+    /// them make an obtuse angle, and they bound a fundamental domain.
     pub fn normals(&self) -> Option<Matrix<f64>> {
         let dim = self.dim();
         let mut mat = Matrix::zeros(dim, dim);
@@ -154,7 +269,7 @@ impl Cox<f64> {
             for (j, n_j) in prev_gens.column_iter().enumerate() {
                 // All other entries in the dot product between columns are zero.
                 let dot = n_i.rows_range(0..=j).dot(&n_j.rows_range(0..=j));
-                n_i[j] = ((f64::PI / self[(i, j)]).fcos() - dot) / n_j[j];
+                n_i[j] = (-(f64::PI / self[(i, j)]).fcos() - dot) / n_j[j];
             }
 
             // If the vector doesn't fit in spherical space.
@@ -169,8 +284,71 @@ impl Cox<f64> {
         Some(mat)
     }
 
-    /// Returns an iterator over the elements of the Coxeter group.
-    pub fn gen_iter(&self) -> Option<GenIter<Matrix<f64>>> {
+    /// Returns the [Cartan matrix](https://en.wikipedia.org/wiki/Cartan_matrix)
+    /// of the diagram, together with the lengths of the simple roots, if its
+    /// group is crystallographic. The simple roots point along the
+    /// [normals](Self::normals), and the (i, j) entry of the Cartan matrix is
+    /// 2(αᵢ · αⱼ) / (αⱼ · αⱼ), for the simple roots αᵢ.
+    ///
+    /// This is only possible when every edge of the diagram is 2, 3, 4 or 6,
+    /// and the lengths of the roots can be chosen consistently around every
+    /// cycle of the diagram.
+    pub fn cartan(&self) -> Option<(Matrix<i64>, Vec<f64>)> {
+        let dim = self.dim();
+
+        // The ratio between the lengths of the roots joined by an edge.
+        let ratio = |m: f64| {
+            [(2.0, 1.0), (3.0, 1.0), (4.0, 2f64.fsqrt()), (6.0, 3f64.fsqrt())]
+                .into_iter()
+                .find(|&(n, _)| (m - n).abs() < f64::EPS)
+                .map(|(_, ratio)| ratio)
+        };
+
+        // Assigns the lengths of the roots by walking through the diagram.
+        let mut lengths: Vec<Option<f64>> = vec![None; dim];
+        for start in 0..dim {
+            if lengths[start].is_some() {
+                continue;
+            }
+
+            lengths[start] = Some(1.0);
+            let mut stack = vec![start];
+            while let Some(i) = stack.pop() {
+                for j in 0..dim {
+                    if j == i || lengths[j].is_some() || (self[(i, j)] - 2.0).abs() < f64::EPS {
+                        continue;
+                    }
+
+                    lengths[j] = Some(lengths[i]? * ratio(self[(i, j)])?);
+                    stack.push(j);
+                }
+            }
+        }
+        let lengths: Vec<f64> = lengths.into_iter().collect::<Option<_>>()?;
+
+        // Every entry must be an integer, which also checks the edges we
+        // didn't walk through.
+        let gram = self.gram();
+        let mut cartan = Matrix::zeros(dim, dim);
+        for i in 0..dim {
+            for j in 0..dim {
+                ratio(self[(i, j)]).or((i == j).then_some(1.0))?;
+                let entry = 2.0 * gram[(i, j)] * lengths[i] / lengths[j];
+                let rounded = entry.round();
+                if (entry - rounded).abs() > f64::EPS {
+                    return None;
+                }
+
+                cartan[(i, j)] = rounded as i64;
+            }
+        }
+
+        Some((cartan, lengths))
+    }
+
+    /// Returns the reflections through the hyperplanes described by the
+    /// Coxeter matrix, along with the unit normals of these hyperplanes.
+    fn reflections(&self) -> Option<(Vec<Matrix<f64>>, Matrix<f64>)> {
         let normals = self.normals()?;
         let dim = normals.nrows();
 
@@ -187,14 +365,174 @@ impl Cox<f64> {
             mat
         };
 
-        Some(GenIter::new(
-            dim,
-            normals.column_iter().map(refl_mat).collect(),
-        ))
+        Some((normals.column_iter().map(refl_mat).collect(), normals))
+    }
+
+    /// Returns an iterator over the elements of the Coxeter group.
+    pub fn gen_iter(&self) -> Option<GenIter<Matrix<f64>>> {
+        let (gens, _) = self.reflections()?;
+        Some(GenIter::new(self.dim(), gens))
+    }
+
+    /// Returns an iterator that streams the elements of the Coxeter group,
+    /// without storing all of them. This is what makes big groups like E7 and
+    /// E8 feasible to iterate over. Crystallographic groups are generated
+    /// exactly, see [`WeylIter`].
+    pub fn cox_iter(&self) -> Option<CoxIter> {
+        let (gens, normals) = self.reflections()?;
+
+        if let Some(weyl) = self.weyl_iter() {
+            return Some(CoxIter::from_weyl(gens, weyl));
+        }
+
+        // A point inside of the fundamental domain, at unit distance from
+        // every mirror.
+        let point: Point<f64> = normals.transpose().try_inverse()? * Point::from_element(self.dim(), 1.0);
+
+        // Safety: the generators are reflections, and the point is generic.
+        Some(unsafe { CoxIter::new(self.dim(), gens, point) })
+    }
+
+    /// Returns an iterator that streams the elements of the Coxeter group
+    /// using exact integer arithmetic, if it's crystallographic and finite.
+    pub fn weyl_iter(&self) -> Option<WeylIter> {
+        let (cartan, lengths) = self.cartan()?;
+
+        // The fundamental weights are dual to the simple roots, up to a
+        // common factor.
+        let normals = self.normals()?;
+        let basis = normals.transpose().try_inverse()? * Matrix::from_diagonal(&Point::from_vec(lengths));
+
+        // Safety: the Cartan matrix comes from a spherical diagram, since it
+        // has normals.
+        unsafe { WeylIter::new(&cartan, basis) }
     }
 
     /// Returns the associated Coxeter [`Group`].
-    pub fn group(&self) -> Option<Group<GenIter<Matrix<f64>>>> {
-        self.gen_iter().map(Into::into)
+    pub fn group(&self) -> Option<Group<CoxIter>> {
+        self.cox_iter().map(Into::into)
+    }
+
+    /// Returns the associated Coxeter [`Group`], or an error explaining why
+    /// it's infinite. Unlike [`Self::group`], this tells affine diagrams,
+    /// which describe Euclidean tilings, apart from hyperbolic ones.
+    pub fn try_group(&self) -> CoxResult<Group<CoxIter>> {
+        match self.cox_type() {
+            // Diagrams right at the edge of being affine might still not
+            // have any normals.
+            CoxType::Spherical => self.group().ok_or(CoxError::Affine),
+            CoxType::Affine => Err(CoxError::Affine),
+            CoxType::Hyperbolic => Err(CoxError::Hyperbolic),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Classifies a few diagrams of each type.
+    #[test]
+    fn cox_type() {
+        for (input, cox_type) in [
+            ("o3o3o", CoxType::Spherical),
+            ("o5o3o3o", CoxType::Spherical),
+            ("o3o3o *b3o", CoxType::Spherical),
+            ("o5/2o5o", CoxType::Spherical),
+            ("o o", CoxType::Spherical),
+            ("o4o3o4o", CoxType::Affine),
+            ("o3o6o", CoxType::Affine),
+            ("o3o3o3o3*a", CoxType::Affine),
+            ("o3o6o o3o", CoxType::Affine),
+            ("o3o7o", CoxType::Hyperbolic),
+            ("o5o3o5o", CoxType::Hyperbolic),
+            ("o3o6o o3o7o", CoxType::Hyperbolic),
+        ] {
+            assert_eq!(Cox::parse(input).unwrap().cox_type(), cox_type, "{}", input);
+        }
+    }
+
+    /// Finds the Cartan matrices of a few crystallographic diagrams.
+    #[test]
+    fn cartan() {
+        let (cartan, _) = Cox::parse("o3o4o").unwrap().cartan().unwrap();
+        assert_eq!(cartan, dmatrix![2, -1, 0; -1, 2, -1; 0, -2, 2]);
+
+        let (cartan, lengths) = Cox::parse("o6o").unwrap().cartan().unwrap();
+        assert_eq!(cartan, dmatrix![2, -1; -3, 2]);
+        assert!((lengths[1] / lengths[0] - 3f64.fsqrt()).abs() < f64::EPS);
+
+        let (cartan, _) = Cox::e(6).cartan().unwrap();
+        assert_eq!(cartan, cartan.transpose());
+        assert_eq!(cartan.iter().filter(|&&x| x == -1).count(), 10);
+
+        for input in ["o5o3o", "o5/2o", "o8o"] {
+            assert!(Cox::parse(input).unwrap().cartan().is_none(), "{}", input);
+        }
+    }
+
+    /// The exact and the floating point generation of a crystallographic
+    /// group give the same elements.
+    #[test]
+    fn weyl_iter() {
+        let cox = Cox::parse("o3o4o3o").unwrap();
+        let exact: Vec<_> = cox.weyl_iter().unwrap().collect();
+        assert_eq!(exact.len(), 1152);
+
+        let (gens, _) = cox.reflections().unwrap();
+        let group = Group::from(GenIter::new(4, gens)).cache();
+        for el in &exact {
+            assert!(group.clone().any(|m| (m - el).norm() < f64::EPS));
+        }
+    }
+
+    /// The circumradii of a few uniform polytopes with unit edges.
+    #[test]
+    fn circumradius() {
+        for (input, radius) in [
+            ("x3o3o", 6f64.fsqrt() / 4.0),
+            ("x4o3o", 3f64.fsqrt() / 2.0),
+            ("o3x3o", 2f64.fsqrt() / 2.0),
+            ("x3x3o", 22f64.fsqrt() / 4.0),
+            ("x5o3o", (15f64.fsqrt() + 3f64.fsqrt()) / 4.0),
+            ("x3o3o3o", 0.4f64.fsqrt()),
+            ("x4o3o3o", 1.0),
+            ("x3o3o *b3o", 2f64.fsqrt() / 2.0),
+            ("x3o3o3o4o", 0.5f64.fsqrt()),
+        ] {
+            let found = Cd::parse(input).unwrap().circumradius().unwrap();
+            assert!((found - radius).abs() < f64::EPS, "{}: {} != {}", input, found, radius);
+        }
+    }
+
+    /// The generator of a diagram is at half the value of each node from its
+    /// mirror, on the side the normals point to.
+    #[test]
+    fn generator() {
+        for input in ["x4o3o", "x3o3o3o", "x3x3o", "o3x5x"] {
+            let cd = Cd::parse(input).unwrap();
+            let normals = cd.cox().normals().unwrap();
+            let generator = cd.generator().unwrap();
+            let distances = normals.transpose() * generator;
+
+            for (distance, node) in distances.iter().zip(cd.node_vector().iter()) {
+                assert!((distance - node / 2.0).abs() < f64::EPS, "{}", input);
+            }
+        }
+    }
+
+    /// Only spherical diagrams give groups.
+    #[test]
+    fn try_group() {
+        assert_eq!(Cox::parse("o4o3o").unwrap().try_group().unwrap().count(), 48);
+        assert!(matches!(
+            Cox::parse("o4o4o").unwrap().try_group(),
+            Err(CoxError::Affine)
+        ));
+        assert!(matches!(
+            Cox::parse("o4o5o").unwrap().try_group(),
+            Err(CoxError::Hyperbolic)
+        ));
+        assert!(matches!(Group::try_parse("o4o("), Err(CoxError::Cd(_))));
     }
 }
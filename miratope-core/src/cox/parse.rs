@@ -148,7 +148,7 @@ impl<'a> CdBuilder<'a> {
     /// By the time this method is called, we've already skipped the opening
     /// parenthesis.
     fn parse_node(&mut self) -> CdResult<Node> {
-        let (init_idx, _) = self.peek().expect("Node can't be empty!");
+        let (init_idx, _) = self.peek_or()?;
         let mut end_idx = init_idx;
 
         // We read the number until we find the closing parenthesis.
@@ -475,6 +475,40 @@ mod tests {
         )
     }
 
+    #[test]
+    /// Tests that diagrams written in inline notation parse back to
+    /// themselves.
+    fn inline_round_trip() {
+        for diagram in [
+            "x3o3o",
+            "x5/2o5o",
+            "x x",
+            "s3s4s",
+            "(1.5)4o3q",
+            "x3o3o3o3*a",
+            "*a4*b3*c3*-aooxx",
+            "o3o3o *b3o",
+        ] {
+            let cd = Cd::parse(diagram).unwrap();
+            let inline = cd.inline().unwrap();
+            let parsed = Cd::parse(&inline).unwrap();
+
+            assert_eq!(parsed.nodes(), cd.nodes(), "Node mismatch in {}!", inline);
+            assert_eq!(
+                parsed.cox(),
+                cd.cox(),
+                "Coxeter matrix mismatch in {}!",
+                inline
+            );
+        }
+
+        assert_eq!(Cd::parse("x3o3o").unwrap().inline().unwrap(), "x3o3o");
+        assert_eq!(
+            Cd::parse("x3o3o3o3*a").unwrap().inline().unwrap(),
+            "x3o3o3o *a3*d"
+        );
+    }
+
     #[test]
     /// Tests that CDs with spaces parse properly.
     fn spaces() {
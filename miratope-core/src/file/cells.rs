@@ -0,0 +1,110 @@
+//! Exports every facet of a polytope into its own OFF file, which is handy for
+//! building physical models of the cells of a polychoron.
+
+use std::path::Path;
+
+use super::off::{OffOptions, OffSaveError};
+use crate::{abs::Ranked, conc::Concrete, Polytope};
+
+/// Replaces the characters that aren't allowed in file names on some platform,
+/// like path separators, with underscores. Trailing dots and spaces, which
+/// Windows strips, are removed too.
+fn sanitize_file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    name.trim_end_matches(['.', ' ']).to_string()
+}
+
+impl Concrete {
+    /// Returns the index of the orbit of every facet under the symmetry group
+    /// of the polytope. If the symmetry group can't be computed, facets are
+    /// instead grouped by their [element types](Self::element_types).
+    pub fn facet_orbits(&self) -> Vec<usize> {
+        let rank = self.rank();
+        if rank == 0 {
+            return Vec::new();
+        }
+
+        match self.clone().get_symmetry_group() {
+            Some((_, vertex_map)) => self.element_orbit_indices(&vertex_map, rank - 1),
+            None => self.types_of_elements()[rank - 1].clone(),
+        }
+    }
+
+    /// Writes every facet of the polytope into its own OFF file in a given
+    /// directory, which is created if it doesn't exist. Files are named after
+    /// the polytope, the orbit of the facet, and its index within the orbit.
+    /// Characters that can't go in a file name are replaced by underscores.
+    /// Returns the number of files written.
+    pub fn facets_to_dir<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        name: &str,
+        opt: OffOptions,
+    ) -> Result<usize, OffSaveError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let name = sanitize_file_name(name);
+
+        let orbits = self.facet_orbits();
+        let mut orbit_counts = vec![0; orbits.iter().max().map_or(0, |&max| max + 1)];
+
+        for (idx, &orbit) in orbits.iter().enumerate() {
+            orbit_counts[orbit] += 1;
+            let facet = self.facet(idx).unwrap();
+            let file_name = format!("{} - orbit {} - facet {}.off", name, orbit + 1, orbit_counts[orbit]);
+            facet.to_path(dir.join(file_name), opt.clone())?;
+        }
+
+        Ok(orbits.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// All cells of a tesseract are in the same orbit.
+    #[test]
+    fn tesseract() {
+        let orbits = Concrete::hypercube(5).facet_orbits();
+        assert_eq!(orbits.len(), 8);
+        assert!(orbits.iter().all(|&orbit| orbit == 0));
+    }
+
+    /// Writes the cells of a tesseract.
+    #[test]
+    fn write() {
+        let dir = std::env::temp_dir().join("miratope-cells-test");
+        let count = Concrete::hypercube(5)
+            .facets_to_dir(&dir, "tes", Default::default())
+            .unwrap();
+
+        assert_eq!(count, 8);
+        assert!(dir.join("tes - orbit 1 - facet 8.off").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// Path separators and other reserved characters are replaced.
+    #[test]
+    fn sanitize() {
+        assert_eq!(sanitize_file_name("5/2 star"), "5_2 star");
+        assert_eq!(sanitize_file_name("a\\b:c*d?e\"f<g>h|i"), "a_b_c_d_e_f_g_h_i");
+        assert_eq!(sanitize_file_name("tes. "), "tes");
+
+        let dir = std::env::temp_dir().join("miratope-cells-sanitize-test");
+        Concrete::hypercube(5)
+            .facets_to_dir(&dir, "../tes", Default::default())
+            .unwrap();
+
+        assert!(dir.join(".._tes - orbit 1 - facet 1.off").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,19 +1,30 @@
-//! Contains the code that opens a GGB file and parses it into a polytope.
+//! Contains the code that opens a GGB file and parses it into a polytope, as
+//! well as the code that writes a polytope into a GGB file.
 
-// This code is unfinished.
+// The reading code is unfinished.
 #![allow(dead_code)]
 #![allow(clippy::collapsible_match)]
 
-use std::io::Result as IoResult;
+use std::{
+    fmt::Write as FmtWrite,
+    fs::File,
+    io::{Error as IoError, Result as IoResult, Write},
+    path::Path,
+};
 
-use crate::{conc::Concrete, geometry::Point};
+use crate::{
+    abs::Ranked,
+    conc::{cycle::CycleList, Concrete, ConcretePolytope},
+    geometry::Point,
+};
 
 use nalgebra::dvector;
 use xml::{
     attribute::OwnedAttribute,
     reader::{EventReader, XmlEvent},
 };
-use zip::result::ZipError;
+use vec_like::*;
+use zip::{result::ZipError, write::SimpleFileOptions, ZipWriter};
 
 type Events<'a> = xml::reader::Events<&'a [u8]>;
 
@@ -140,6 +151,13 @@ pub enum GgbError {
 
     /// Some number could not be parsed.
     ParseError,
+
+    /// The polytope can't be represented in GeoGebra, as it's more than
+    /// 3-dimensional.
+    TooManyDimensions,
+
+    /// Some generic I/O error occured.
+    IoError(IoError),
 }
 
 impl std::fmt::Display for GgbError {
@@ -151,6 +169,8 @@ impl std::fmt::Display for GgbError {
             Self::InvalidGgb => write!(f, "invalid GGB"),
             Self::ZipError(err) => write!(f, "ZIP error: {}", err),
             Self::ParseError => write!(f, "parse error"),
+            Self::TooManyDimensions => write!(f, "GGB files only support up to 3 dimensions"),
+            Self::IoError(err) => write!(f, "IO error: {}", err),
         }
     }
 }
@@ -161,7 +181,13 @@ impl From<ZipError> for GgbError {
     }
 }
 
-/// The result of trying to read a GGB file.
+impl From<IoError> for GgbError {
+    fn from(err: IoError) -> Self {
+        Self::IoError(err)
+    }
+}
+
+/// The result of trying to read or write a GGB file.
 pub type GgbResult<T> = Result<T, GgbError>;
 
 impl std::error::Error for GgbError {}
@@ -255,3 +281,142 @@ pub(super) fn parse_xml(xml: &str) -> GgbResult<Concrete> {
         }
     }
 }
+
+/// Writes a point into a GeoGebra construction.
+fn write_point(xml: &mut String, label: &str, point: &Point<f64>) {
+    let coord = |i: usize| point.get(i).copied().unwrap_or(0.0);
+
+    writeln!(xml, "<element type=\"point3d\" label=\"{}\">", label).unwrap();
+    writeln!(xml, "\t<show object=\"true\" label=\"false\"/>").unwrap();
+    writeln!(
+        xml,
+        "\t<coords x=\"{}\" y=\"{}\" z=\"{}\" w=\"1\"/>",
+        coord(0),
+        coord(1),
+        coord(2)
+    )
+    .unwrap();
+    writeln!(xml, "</element>").unwrap();
+}
+
+/// Writes a command with some given inputs and a single output into a
+/// GeoGebra construction, followed by the element it outputs.
+fn write_command(xml: &mut String, name: &str, inputs: &[String], output: &str, el_type: &str) {
+    writeln!(xml, "<command name=\"{}\">", name).unwrap();
+    write!(xml, "\t<input").unwrap();
+    for (i, input) in inputs.iter().enumerate() {
+        write!(xml, " a{}=\"{}\"", i, input).unwrap();
+    }
+    writeln!(xml, "/>").unwrap();
+    writeln!(xml, "\t<output a0=\"{}\"/>", output).unwrap();
+    writeln!(xml, "</command>").unwrap();
+
+    writeln!(xml, "<element type=\"{}\" label=\"{}\">", el_type, output).unwrap();
+    writeln!(xml, "\t<show object=\"true\" label=\"false\"/>").unwrap();
+    writeln!(xml, "</element>").unwrap();
+}
+
+impl Concrete {
+    /// Builds the `geogebra.xml` file of a GGB file for a polytope of at most
+    /// three dimensions. Vertices, edges and faces become points, segments and
+    /// polygons, respectively.
+    pub fn to_ggb_xml(&self) -> GgbResult<String> {
+        if self.dim_or() > 3 {
+            return Err(GgbError::TooManyDimensions);
+        }
+
+        let vertex_label = |idx: usize| format!("V_{{{}}}", idx + 1);
+        let mut xml = String::new();
+
+        writeln!(xml, "<?xml version=\"1.0\" encoding=\"utf-8\"?>").unwrap();
+        writeln!(xml, "<geogebra format=\"5.0\" app=\"classic\" subApp=\"3D\">").unwrap();
+        writeln!(xml, "<construction title=\"\" author=\"\" date=\"\">").unwrap();
+
+        for (idx, v) in self.vertices.iter().enumerate() {
+            write_point(&mut xml, &vertex_label(idx), v);
+        }
+
+        if let Some(edges) = self.get_element_list(2) {
+            for (idx, edge) in edges.iter().enumerate() {
+                write_command(
+                    &mut xml,
+                    "Segment",
+                    &[vertex_label(edge.subs[0]), vertex_label(edge.subs[1])],
+                    &format!("e_{{{}}}", idx + 1),
+                    "segment3d",
+                );
+            }
+        }
+
+        // Faces made out of more than one cycle become several polygons.
+        if self.rank() >= 3 {
+            let mut polygon_idx = 0;
+
+            for face in &self[3] {
+                for cycle in CycleList::from_edges(face.subs.iter().map(|&i| &self[(2, i)].subs)) {
+                    polygon_idx += 1;
+                    write_command(
+                        &mut xml,
+                        "Polygon",
+                        &cycle.iter().map(|&v| vertex_label(v)).collect::<Vec<_>>(),
+                        &format!("f_{{{}}}", polygon_idx),
+                        "polygon3d",
+                    );
+                }
+            }
+        }
+
+        writeln!(xml, "</construction>").unwrap();
+        writeln!(xml, "</geogebra>").unwrap();
+
+        Ok(xml)
+    }
+
+    /// Writes a polytope of at most three dimensions into a GGB file in a
+    /// specified file path.
+    pub fn to_ggb_path<P: AsRef<Path>>(&self, fp: P) -> GgbResult<()> {
+        let xml = self.to_ggb_xml()?;
+        let mut zip = ZipWriter::new(File::create(fp)?);
+
+        zip.start_file("geogebra.xml", SimpleFileOptions::default())?;
+        zip.write_all(xml.as_bytes())?;
+        zip.finish()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+
+    /// Checks that every element of a cube gets written.
+    #[test]
+    fn cube() {
+        let xml = Concrete::cube().to_ggb_xml().unwrap();
+
+        assert_eq!(xml.matches("type=\"point3d\"").count(), 8);
+        assert_eq!(xml.matches("type=\"segment3d\"").count(), 12);
+        assert_eq!(xml.matches("type=\"polygon3d\"").count(), 6);
+    }
+
+    /// Checks that a polygon gets written as a single face.
+    #[test]
+    fn pentagon() {
+        let xml = Concrete::polygon(5).to_ggb_xml().unwrap();
+
+        assert_eq!(xml.matches("type=\"point3d\"").count(), 5);
+        assert_eq!(xml.matches("type=\"segment3d\"").count(), 5);
+        assert_eq!(xml.matches("type=\"polygon3d\"").count(), 1);
+    }
+
+    /// A tesseract can't be written.
+    #[test]
+    fn tesseract() {
+        assert!(matches!(
+            Concrete::hypercube(5).to_ggb_xml(),
+            Err(GgbError::TooManyDimensions)
+        ));
+    }
+}
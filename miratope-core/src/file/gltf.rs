@@ -0,0 +1,275 @@
+//! Contains the code that writes a polytope into a binary
+//! [glTF 2.0](https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.html) file,
+//! so that it can be shared and viewed on the web.
+
+use std::{fmt::Write, io::Result as IoResult, path::Path};
+
+use crate::{abs::Ranked, conc::Concrete, geometry::Point};
+
+use vec_like::*;
+
+/// The magic number at the start of every GLB file, `glTF` in ASCII.
+const GLB_MAGIC: u32 = 0x4654_6C67;
+
+/// The type of the JSON chunk of a GLB file, `JSON` in ASCII.
+const JSON_CHUNK: u32 = 0x4E4F_534A;
+
+/// The type of the binary chunk of a GLB file, `BIN\0` in ASCII.
+const BIN_CHUNK: u32 = 0x004E_4942;
+
+/// The options for writing a glTF file.
+#[derive(Clone, Copy, Debug)]
+pub struct GltfOptions {
+    /// The color of the faces, as linear RGBA.
+    pub face_color: [f32; 4],
+
+    /// The color of the edges, as linear RGBA.
+    pub edge_color: [f32; 4],
+
+    /// The transform of the polytope, as a 4×4 matrix in column-major order.
+    pub transform: [f32; 16],
+}
+
+impl Default for GltfOptions {
+    fn default() -> Self {
+        Self {
+            face_color: [0.8, 0.8, 0.8, 1.0],
+            edge_color: [0.0, 0.0, 0.0, 1.0],
+            transform: [
+                1.0, 0.0, 0.0, 0.0, //
+                0.0, 1.0, 0.0, 0.0, //
+                0.0, 0.0, 1.0, 0.0, //
+                0.0, 0.0, 0.0, 1.0,
+            ],
+        }
+    }
+}
+
+/// Returns the first three coordinates of a point, padding with zeros.
+fn coords(p: &Point<f64>) -> [f32; 3] {
+    [0, 1, 2].map(|i| p.get(i).copied().unwrap_or_default() as f32)
+}
+
+/// Writes a list of numbers as a JSON array.
+fn json_array(values: &[f32]) -> String {
+    let values: Vec<String> = values.iter().map(f32::to_string).collect();
+    format!("[{}]", values.join(","))
+}
+
+/// Writes a material with a single color. Transparent colors get blended.
+fn json_material(name: &str, color: [f32; 4]) -> String {
+    let alpha_mode = if color[3] < 1.0 { "BLEND" } else { "OPAQUE" };
+    format!(
+        "{{\"name\":\"{}\",\"pbrMetallicRoughness\":{{\"baseColorFactor\":{},\"metallicFactor\":0,\"roughnessFactor\":1}},\"alphaMode\":\"{}\",\"doubleSided\":true}}",
+        name,
+        json_array(&color),
+        alpha_mode
+    )
+}
+
+/// Pads a buffer to a multiple of four bytes.
+fn pad(buf: &mut Vec<u8>, byte: u8) {
+    while !buf.len().is_multiple_of(4) {
+        buf.push(byte);
+    }
+}
+
+impl Concrete {
+    /// Writes a binary glTF file of the polytope. The triangulated faces and
+    /// the edges are written as two primitives of the same mesh, each with its
+    /// own material, on a single node with the given transform.
+    ///
+    /// Only the first three coordinates of each vertex are used, so polytopes
+    /// in more dimensions should be projected beforehand.
+    pub fn to_glb(&self, options: &GltfOptions) -> Vec<u8> {
+        let triangulation = if self.rank() >= 4 {
+            Some(self.triangulation())
+        } else {
+            None
+        };
+
+        // The vertices of the triangulation come after those of the polytope,
+        // so both primitives can share them.
+        let mut positions: Vec<[f32; 3]> = self.vertices.iter().map(coords).collect();
+        if let Some(triangulation) = &triangulation {
+            positions.extend(triangulation.extra_vertices.iter().map(coords));
+        }
+
+        let triangles: Vec<u32> = triangulation
+            .map(|triangulation| triangulation.triangles)
+            .unwrap_or_default();
+        let edges: Vec<u32> = if self.rank() >= 3 {
+            self[2]
+                .iter()
+                .flat_map(|edge| [edge.subs[0] as u32, edge.subs[1] as u32])
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        // The binary buffer: positions, then triangle indices, then edge
+        // indices. Everything's four bytes wide, so no padding is needed.
+        let mut bin = Vec::new();
+        for p in &positions {
+            for c in p {
+                bin.extend_from_slice(&c.to_le_bytes());
+            }
+        }
+        for idx in triangles.iter().chain(&edges) {
+            bin.extend_from_slice(&idx.to_le_bytes());
+        }
+
+        let position_len = positions.len() * 12;
+        let triangle_len = triangles.len() * 4;
+        let edge_len = edges.len() * 4;
+
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for p in &positions {
+            for i in 0..3 {
+                min[i] = min[i].min(p[i]);
+                max[i] = max[i].max(p[i]);
+            }
+        }
+
+        // Empty accessors aren't allowed, so we only write what's there.
+        let mut buffer_views = Vec::new();
+        let mut accessors = Vec::new();
+        let mut primitives = Vec::new();
+
+        if !positions.is_empty() {
+            buffer_views.push(format!(
+                "{{\"buffer\":0,\"byteOffset\":0,\"byteLength\":{},\"target\":34962}}",
+                position_len
+            ));
+            accessors.push(format!(
+                "{{\"bufferView\":0,\"componentType\":5126,\"count\":{},\"type\":\"VEC3\",\"min\":{},\"max\":{}}}",
+                positions.len(),
+                json_array(&min),
+                json_array(&max)
+            ));
+
+            for (offset, len, count, material, mode) in [
+                (position_len, triangle_len, triangles.len(), 0, 4),
+                (position_len + triangle_len, edge_len, edges.len(), 1, 1),
+            ] {
+                if count == 0 {
+                    continue;
+                }
+
+                buffer_views.push(format!(
+                    "{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":34963}}",
+                    offset, len
+                ));
+                accessors.push(format!(
+                    "{{\"bufferView\":{},\"componentType\":5125,\"count\":{},\"type\":\"SCALAR\"}}",
+                    buffer_views.len() - 1,
+                    count
+                ));
+                primitives.push(format!(
+                    "{{\"attributes\":{{\"POSITION\":0}},\"indices\":{},\"material\":{},\"mode\":{}}}",
+                    accessors.len() - 1,
+                    material,
+                    mode
+                ));
+            }
+        }
+
+        let mut json = String::new();
+        write!(
+            json,
+            "{{\"asset\":{{\"version\":\"2.0\",\"generator\":\"Miratope\"}},\"scene\":0,\"scenes\":[{{\"nodes\":[0]}}]"
+        )
+        .unwrap();
+
+        if primitives.is_empty() {
+            write!(json, ",\"nodes\":[{{\"matrix\":{}}}]", json_array(&options.transform)).unwrap();
+        } else {
+            write!(
+                json,
+                ",\"nodes\":[{{\"mesh\":0,\"matrix\":{}}}]",
+                json_array(&options.transform)
+            )
+            .unwrap();
+            write!(json, ",\"meshes\":[{{\"primitives\":[{}]}}]", primitives.join(",")).unwrap();
+            write!(
+                json,
+                ",\"materials\":[{},{}]",
+                json_material("Faces", options.face_color),
+                json_material("Edges", options.edge_color)
+            )
+            .unwrap();
+            write!(json, ",\"accessors\":[{}]", accessors.join(",")).unwrap();
+            write!(json, ",\"bufferViews\":[{}]", buffer_views.join(",")).unwrap();
+            write!(json, ",\"buffers\":[{{\"byteLength\":{}}}]", bin.len()).unwrap();
+        }
+        write!(json, "}}").unwrap();
+
+        let mut json = json.into_bytes();
+        pad(&mut json, b' ');
+        pad(&mut bin, 0);
+
+        let mut glb = Vec::new();
+        let mut total = 12 + 8 + json.len();
+        if !primitives.is_empty() {
+            total += 8 + bin.len();
+        }
+
+        glb.extend_from_slice(&GLB_MAGIC.to_le_bytes());
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(total as u32).to_le_bytes());
+
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&JSON_CHUNK.to_le_bytes());
+        glb.extend_from_slice(&json);
+
+        if !primitives.is_empty() {
+            glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+            glb.extend_from_slice(&BIN_CHUNK.to_le_bytes());
+            glb.extend_from_slice(&bin);
+        }
+
+        glb
+    }
+
+    /// Writes a binary glTF file of the polytope in a specified file path.
+    pub fn to_glb_path<P: AsRef<Path>>(&self, fp: P, options: &GltfOptions) -> IoResult<()> {
+        std::fs::write(fp, self.to_glb(options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+
+    /// Reads a little-endian integer at some position of a buffer.
+    fn read_u32(buf: &[u8], idx: usize) -> u32 {
+        u32::from_le_bytes(buf[idx..idx + 4].try_into().unwrap())
+    }
+
+    /// Checks the layout of the file written for a cube.
+    #[test]
+    fn cube() {
+        let glb = Concrete::cube().to_glb(&Default::default());
+
+        assert_eq!(read_u32(&glb, 0), GLB_MAGIC);
+        assert_eq!(read_u32(&glb, 4), 2);
+        assert_eq!(read_u32(&glb, 8) as usize, glb.len());
+        assert_eq!(glb.len() % 4, 0);
+
+        let json_len = read_u32(&glb, 12) as usize;
+        assert_eq!(read_u32(&glb, 16), JSON_CHUNK);
+        let json = std::str::from_utf8(&glb[20..20 + json_len]).unwrap();
+
+        // 8 vertices, 12 triangles and 12 edges.
+        assert!(json.contains("\"count\":8"));
+        assert!(json.contains("\"count\":36"));
+        assert!(json.contains("\"count\":24"));
+        assert_eq!(json.matches("\"mode\"").count(), 2);
+
+        let bin_len = read_u32(&glb, 20 + json_len) as usize;
+        assert_eq!(read_u32(&glb, 24 + json_len), BIN_CHUNK);
+        assert_eq!(bin_len, 8 * 12 + 36 * 4 + 24 * 4);
+    }
+}
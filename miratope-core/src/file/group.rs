@@ -0,0 +1,275 @@
+//! The code that exports the symmetry group of a polytope, so that it can be
+//! studied in a computer algebra system.
+//!
+//! The group is given by a few generators, as permutations of the vertices
+//! for [GAP](https://www.gap-system.org/) and
+//! [Magma](http://magma.maths.usyd.edu.au/magma/), or as matrices.
+
+use std::{fmt::Display, fmt::Write, io::Error as IoError, path::Path, vec};
+
+use crate::{
+    conc::Concrete,
+    geometry::Matrix,
+    group::{stab_chain::StabChain, vertex_map::VertexMap, Group},
+};
+
+use vec_like::*;
+
+/// The formats a symmetry group can be written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupFormat {
+    /// A GAP permutation group, acting on the vertices numbered from 1.
+    Gap,
+
+    /// A Magma permutation group, acting on the vertices numbered from 1.
+    Magma,
+
+    /// Comma-separated values, with one line per row of each generating
+    /// matrix.
+    Csv,
+
+    /// A JSON array, with one object per generator, containing both its
+    /// matrix and its permutation of the vertices.
+    Json,
+}
+
+impl GroupFormat {
+    /// Guesses the format from the extension of a file path.
+    pub fn from_path<P: AsRef<Path>>(fp: P) -> Option<Self> {
+        match fp.as_ref().extension()?.to_str()? {
+            "g" | "gap" => Some(Self::Gap),
+            "m" | "mag" | "magma" => Some(Self::Magma),
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// An error when exporting the symmetry group of a polytope.
+#[derive(Debug)]
+pub enum GroupExportError {
+    /// The symmetry group of the polytope couldn't be computed.
+    Symmetry,
+
+    /// There was a problem saving the file.
+    IoError(IoError),
+}
+
+impl From<IoError> for GroupExportError {
+    fn from(err: IoError) -> Self {
+        Self::IoError(err)
+    }
+}
+
+impl Display for GroupExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Symmetry => write!(f, "the symmetry group could not be computed"),
+            Self::IoError(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for GroupExportError {}
+
+/// The result of trying to export the symmetry group of a polytope.
+pub type GroupExportResult<T> = Result<T, GroupExportError>;
+
+/// Returns the indices of some rows of a vertex map that generate the same
+/// group. A row is picked whenever it's not in the group generated by the
+/// previous ones, so there are at most as many as the length of a chain of
+/// subgroups.
+pub fn generator_rows(vertex_map: &VertexMap) -> Vec<usize> {
+    let mut chain = StabChain::trivial(vertex_map.vertex_count());
+    (0..vertex_map.len())
+        .filter(|&idx| chain.insert(&vertex_map[idx]))
+        .collect()
+}
+
+/// Writes a permutation in cycle notation, with the points numbered from 1.
+fn write_cycles(out: &mut String, perm: &[usize]) {
+    let mut seen = vec![false; perm.len()];
+    let mut empty = true;
+
+    for start in 0..perm.len() {
+        if seen[start] || perm[start] == start {
+            continue;
+        }
+
+        let mut cycle = Vec::new();
+        let mut point = start;
+        while !seen[point] {
+            seen[point] = true;
+            cycle.push((point + 1).to_string());
+            point = perm[point];
+        }
+
+        let _ = write!(out, "({})", cycle.join(","));
+        empty = false;
+    }
+
+    if empty {
+        out.push_str("()");
+    }
+}
+
+/// Writes a matrix as a JSON array of its rows.
+fn json_matrix(mat: &Matrix<f64>) -> String {
+    let rows: Vec<String> = mat
+        .row_iter()
+        .map(|row| {
+            let entries: Vec<String> = row.iter().map(f64::to_string).collect();
+            format!("[{}]", entries.join(", "))
+        })
+        .collect();
+    format!("[{}]", rows.join(", "))
+}
+
+/// Writes a symmetry group given by its matrices, along with the vertex map
+/// whose rows correspond to them, in some format. Only the generators from
+/// [`generator_rows`] are written.
+pub fn group_to_string(
+    group: Group<vec::IntoIter<Matrix<f64>>>,
+    vertex_map: &VertexMap,
+    format: GroupFormat,
+) -> String {
+    let matrices: Vec<Matrix<f64>> = group.collect();
+    let gens = generator_rows(vertex_map);
+    let order = vertex_map.len();
+    let degree = vertex_map.vertex_count();
+    let mut out = String::new();
+
+    // Writing into a string can't fail, so we ignore the results.
+    match format {
+        GroupFormat::Gap | GroupFormat::Magma => {
+            let mut perms = Vec::new();
+            for &idx in &gens {
+                let mut perm = String::new();
+                write_cycles(&mut perm, &vertex_map[idx]);
+                perms.push(perm);
+            }
+
+            // The trivial group needs a generator.
+            if perms.is_empty() {
+                perms.push("()".to_string());
+            }
+
+            if format == GroupFormat::Gap {
+                let _ = writeln!(out, "# Symmetry group of order {} acting on {} vertices.", order, degree);
+                let _ = writeln!(out, "G := Group({});", perms.join(", "));
+            } else {
+                let _ = writeln!(out, "// Symmetry group of order {} acting on {} vertices.", order, degree);
+                let _ = writeln!(out, "G := PermutationGroup<{} | {}>;", degree, perms.join(", "));
+            }
+        }
+
+        GroupFormat::Csv => {
+            let dim = matrices.first().map_or(0, Matrix::nrows);
+            let _ = write!(out, "generator,row");
+            for i in 0..dim {
+                let _ = write!(out, ",x{}", i);
+            }
+            out.push('\n');
+
+            for (generator, &idx) in gens.iter().enumerate() {
+                for (row_idx, row) in matrices[idx].row_iter().enumerate() {
+                    let _ = write!(out, "{},{}", generator, row_idx);
+                    for x in row.iter() {
+                        let _ = write!(out, ",{}", x);
+                    }
+                    out.push('\n');
+                }
+            }
+        }
+
+        GroupFormat::Json => {
+            out.push_str("[\n");
+            for (generator, &idx) in gens.iter().enumerate() {
+                let perm: Vec<String> = vertex_map[idx].iter().map(usize::to_string).collect();
+                let _ = write!(
+                    out,
+                    "  {{\"matrix\": {}, \"permutation\": [{}]}}",
+                    json_matrix(&matrices[idx]),
+                    perm.join(", ")
+                );
+                out.push_str(if generator + 1 == gens.len() { "\n" } else { ",\n" });
+            }
+            out.push_str("]\n");
+        }
+    }
+
+    out
+}
+
+impl Concrete {
+    /// Writes the symmetry group of the polytope in some format. See
+    /// [`group_to_string`].
+    pub fn symmetry_to_string(&mut self, format: GroupFormat) -> GroupExportResult<String> {
+        let (group, vertex_map) = self.get_symmetry_group().ok_or(GroupExportError::Symmetry)?;
+        Ok(group_to_string(group, &vertex_map, format))
+    }
+
+    /// Writes the symmetry group of a polytope in a specified file path. The
+    /// format is chosen from the extension, defaulting to GAP.
+    pub fn symmetry_to_path<P: AsRef<Path>>(&mut self, fp: P) -> GroupExportResult<()> {
+        let format = GroupFormat::from_path(&fp).unwrap_or(GroupFormat::Gap);
+        std::fs::write(fp, self.symmetry_to_string(format)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+
+    /// Checks the generators of the symmetry group of a cube.
+    #[test]
+    fn cube() {
+        let mut cube = Concrete::cube();
+        let (_, vertex_map) = cube.get_symmetry_group().unwrap();
+
+        // The generators give back the whole group.
+        let gens = generator_rows(&vertex_map);
+        let chain = StabChain::new(vertex_map.vertex_count(), gens.iter().map(|&idx| vertex_map[idx].as_slice()));
+        assert_eq!(chain.order(), 48);
+
+        let gap = cube.symmetry_to_string(GroupFormat::Gap).unwrap();
+        assert!(gap.contains("order 48 acting on 8 vertices"));
+        assert!(gap.contains("G := Group(("));
+
+        let magma = cube.symmetry_to_string(GroupFormat::Magma).unwrap();
+        assert!(magma.contains("G := PermutationGroup<8 | "));
+
+        let csv = cube.symmetry_to_string(GroupFormat::Csv).unwrap();
+        assert_eq!(csv.lines().next(), Some("generator,row,x0,x1,x2"));
+        assert_eq!(csv.lines().count(), 1 + 3 * gens.len());
+
+        let json = cube.symmetry_to_string(GroupFormat::Json).unwrap();
+        assert_eq!(json.matches("\"matrix\"").count(), gens.len());
+    }
+
+    /// Checks the cycle notation of a few permutations.
+    #[test]
+    fn cycles() {
+        let cycles = |perm: &[usize]| {
+            let mut out = String::new();
+            write_cycles(&mut out, perm);
+            out
+        };
+
+        assert_eq!(cycles(&[0, 1, 2]), "()");
+        assert_eq!(cycles(&[1, 2, 0, 3]), "(1,2,3)");
+        assert_eq!(cycles(&[1, 0, 3, 2]), "(1,2)(3,4)");
+    }
+
+    /// Checks that the format is read from the extension.
+    #[test]
+    fn format() {
+        assert_eq!(GroupFormat::from_path("a.g"), Some(GroupFormat::Gap));
+        assert_eq!(GroupFormat::from_path("a.magma"), Some(GroupFormat::Magma));
+        assert_eq!(GroupFormat::from_path("a/b.json"), Some(GroupFormat::Json));
+        assert_eq!(GroupFormat::from_path("a.off"), None);
+    }
+}
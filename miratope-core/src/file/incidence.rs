@@ -0,0 +1,406 @@
+//! Contains the code that reads an abstract polytope from a plain-text
+//! description of its incidences, so that polytopes without an OFF
+//! representation, such as those from the
+//! [Atlas of Small Regular Polytopes](https://www.abstract-polytopes.com/atlas/),
+//! can be loaded.
+//!
+//! Two formats are supported. Empty lines and anything after a `#` are
+//! ignored in both.
+//!
+//! # Element lists
+//! The header `INC` is followed by the dimension of the polytope, i.e. `3` for
+//! polyhedra. The next line holds the number of vertices. Then, for edges,
+//! faces, and so on up to facets, comes a line with the number of elements,
+//! followed by a line for each of them with the indices of its subelements.
+//!
+//! ```txt
+//! INC 2
+//! # Vertices
+//! 3
+//! # Edges
+//! 3
+//! 0 1
+//! 1 2
+//! 2 0
+//! ```
+//!
+//! # Flags
+//! The header `FLAGS` is followed by the dimension of the polytope. Each line
+//! afterwards holds a flag, given by the indices of its vertex, edge, and so on
+//! up to its facet. Elements are incident whenever they share a flag.
+//!
+//! ```txt
+//! FLAGS 1
+//! 0
+//! 1
+//! ```
+
+use std::{collections::BTreeSet, fmt::Display, str::FromStr};
+
+use crate::abs::{Abstract, AbstractBuilder, AbstractError, SubelementList, Subelements};
+
+use vec_like::*;
+
+/// Any error encountered while parsing an incidence file.
+#[derive(Clone, Copy, Debug)]
+pub enum IncidenceError {
+    /// The file is empty.
+    Empty,
+
+    /// The file doesn't start with `INC` or `FLAGS` and a dimension.
+    Header,
+
+    /// The file ended before all elements were read.
+    UnexpectedEnding,
+
+    /// Could not parse a number on a given line.
+    Parsing(usize),
+
+    /// Some element on a given line refers to an element that doesn't exist.
+    Index(usize),
+
+    /// Some element on a given line has the same subelement twice.
+    Duplicate(usize),
+
+    /// The number of elements on a given line is larger than what the rest of
+    /// the file could hold.
+    Count(usize),
+
+    /// The flag on a given line doesn't have an element of every rank.
+    FlagLength(usize),
+
+    /// The elements don't form a valid abstract polytope.
+    Invalid(AbstractError),
+}
+
+impl Display for IncidenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "file is empty"),
+            Self::Header => write!(f, "no \"INC\" or \"FLAGS\" header with a dimension"),
+            Self::UnexpectedEnding => write!(f, "file ended unexpectedly"),
+            Self::Parsing(line) => write!(f, "could not parse number on line {}", line),
+            Self::Index(line) => write!(f, "invalid element index on line {}", line),
+            Self::Duplicate(line) => write!(f, "repeated subelement on line {}", line),
+            Self::Count(line) => write!(f, "element count on line {} is too large for the file", line),
+            Self::FlagLength(line) => write!(f, "flag on line {} has the wrong length", line),
+            Self::Invalid(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for IncidenceError {}
+
+/// The result of parsing an incidence file.
+pub type IncidenceResult<T> = Result<T, IncidenceError>;
+
+/// Returns the lines of a file that aren't empty or comments, together with
+/// their line numbers, starting from 1.
+fn lines(src: &str) -> impl Iterator<Item = (usize, &str)> {
+    src.lines().enumerate().filter_map(|(idx, line)| {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        (!line.is_empty()).then_some((idx + 1, line))
+    })
+}
+
+/// Parses all the numbers on a line.
+fn numbers<T: FromStr>(line: usize, src: &str) -> IncidenceResult<Vec<T>> {
+    src.split_whitespace()
+        .map(|num| num.parse().map_err(|_| IncidenceError::Parsing(line)))
+        .collect()
+}
+
+/// Parses a line with a single number.
+fn number<T: FromStr>(line: usize, src: &str) -> IncidenceResult<T> {
+    let mut nums = numbers(line, src)?;
+    if nums.len() == 1 {
+        Ok(nums.pop().unwrap())
+    } else {
+        Err(IncidenceError::Parsing(line))
+    }
+}
+
+impl Abstract {
+    /// Reads an abstract polytope from an incidence file, given either by the
+    /// subelements of each element or by a list of flags. See the
+    /// [module documentation](self) for the formats.
+    pub fn from_incidence(src: &str) -> IncidenceResult<Self> {
+        let mut lines = lines(src);
+        let (_, header) = lines.next().ok_or(IncidenceError::Empty)?;
+
+        let mut header = header.split_whitespace();
+        let kind = header.next().ok_or(IncidenceError::Header)?;
+        let dim: usize = header
+            .next()
+            .and_then(|dim| dim.parse().ok())
+            .ok_or(IncidenceError::Header)?;
+        if header.next().is_some() {
+            return Err(IncidenceError::Header);
+        }
+
+        let subelements = match kind {
+            "INC" => Self::parse_element_lists(lines, dim)?,
+            "FLAGS" => Self::parse_flags(lines, dim)?,
+            _ => return Err(IncidenceError::Header),
+        };
+
+        // We make sure that we're not building something invalid.
+        let mut builder = AbstractBuilder::with_rank_capacity(dim + 1);
+        builder.push_min();
+        builder.push_vertices(subelements.0);
+        for subs in subelements.1 {
+            builder.push(subs);
+        }
+        builder.push_max();
+
+        builder.build_checked().map_err(IncidenceError::Invalid)
+    }
+
+    /// Reads the number of vertices and the subelements of every element from
+    /// edges up to facets.
+    fn parse_element_lists<'a>(
+        lines: impl Iterator<Item = (usize, &'a str)>,
+        dim: usize,
+    ) -> IncidenceResult<(usize, Vec<SubelementList>)> {
+        let lines: Vec<_> = lines.collect();
+        let mut lines = lines.as_slice();
+        let mut next = || match lines.split_first() {
+            Some((&first, rest)) => {
+                lines = rest;
+                Ok((first, rest))
+            }
+            None => Err(IncidenceError::UnexpectedEnding),
+        };
+
+        // Since the counts come from the file, we check that the rest of it
+        // can actually hold that many elements before we allocate anything
+        // for them. Every vertex of a polytope with edges shows up as a
+        // subelement somewhere, and every other element takes up a line.
+        let ((line, src), rest) = next()?;
+        let vertex_count = number(line, src)?;
+        let tokens: usize = rest.iter().map(|(_, src)| src.split_whitespace().count()).sum();
+        if vertex_count > tokens.max(2) {
+            return Err(IncidenceError::Count(line));
+        }
+
+        let mut prev_count = vertex_count;
+        let mut subelements = Vec::new();
+
+        for _ in 2..=dim {
+            let ((line, src), rest) = next()?;
+            let count = number(line, src)?;
+            if count > rest.len() {
+                return Err(IncidenceError::Count(line));
+            }
+
+            let mut els = SubelementList::with_capacity(count);
+            for _ in 0..count {
+                let ((line, src), _) = next()?;
+                let mut subs: Vec<usize> = numbers(line, src)?;
+                if subs.iter().any(|&sub| sub >= prev_count) {
+                    return Err(IncidenceError::Index(line));
+                }
+
+                subs.sort_unstable();
+                if subs.windows(2).any(|pair| pair[0] == pair[1]) {
+                    return Err(IncidenceError::Duplicate(line));
+                }
+
+                els.push(Subelements::from(subs));
+            }
+
+            subelements.push(els);
+            prev_count = count;
+        }
+
+        match next() {
+            Ok(((line, _), _)) => Err(IncidenceError::Parsing(line)),
+            Err(_) => Ok((vertex_count, subelements)),
+        }
+    }
+
+    /// Reads a list of flags, and finds the subelements of every element from
+    /// edges up to facets.
+    fn parse_flags<'a>(
+        lines: impl Iterator<Item = (usize, &'a str)>,
+        dim: usize,
+    ) -> IncidenceResult<(usize, Vec<SubelementList>)> {
+        // The subelements of each element of each rank, from edges onwards.
+        let mut incidences: Vec<Vec<BTreeSet<usize>>> = vec![Vec::new(); dim.saturating_sub(1)];
+        let mut vertex_count = 0;
+
+        // Every element shows up in some flag, so no index can be larger than
+        // the number of flags. Checking this keeps us from allocating space for
+        // elements that can't exist.
+        let lines: Vec<_> = lines.collect();
+        let flag_count = lines.len();
+
+        for (line, src) in lines {
+            let flag: Vec<usize> = numbers(line, src)?;
+            if flag.len() != dim {
+                return Err(IncidenceError::FlagLength(line));
+            }
+            if flag.iter().any(|&el| el >= flag_count) {
+                return Err(IncidenceError::Index(line));
+            }
+
+            if let Some(&vertex) = flag.first() {
+                vertex_count = vertex_count.max(vertex + 1);
+            }
+
+            for (els, pair) in incidences.iter_mut().zip(flag.windows(2)) {
+                let (sub, el) = (pair[0], pair[1]);
+                if els.len() <= el {
+                    els.resize(el + 1, BTreeSet::new());
+                }
+                els[el].insert(sub);
+            }
+        }
+
+        if vertex_count == 0 && dim != 0 {
+            return Err(IncidenceError::UnexpectedEnding);
+        }
+
+        // Elements that show up in no flag are caught when validating.
+        let subelements = incidences
+            .into_iter()
+            .map(|els| {
+                els.into_iter()
+                    .map(|subs| Subelements::from(subs.into_iter().collect::<Vec<_>>()))
+                    .collect::<Vec<_>>()
+                    .into()
+            })
+            .collect();
+
+        // The point has a single vertex, and no flags besides the trivial one.
+        Ok((if dim == 0 { 1 } else { vertex_count }, subelements))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abs::Ranked, Polytope};
+
+    /// Checks that a polytope has the expected element counts.
+    fn test(poly: &Abstract, el_counts: &[usize]) {
+        assert_eq!(poly.el_count_iter().collect::<Vec<_>>(), el_counts);
+    }
+
+    /// Reads a triangle from its element lists.
+    #[test]
+    fn triangle() {
+        let src = "INC 2\n# Vertices\n3\n\n# Edges\n3\n0 1\n1 2\n2 0\n";
+        test(&Abstract::from_incidence(src).unwrap(), &[1, 3, 3, 1]);
+    }
+
+    /// Reads the hemicube, which has no OFF representation, from its element
+    /// lists.
+    #[test]
+    fn hemicube() {
+        let src = "INC 3
+            4
+            6
+            0 1
+            0 2
+            0 3
+            1 2
+            1 3
+            2 3
+            3
+            0 3 5 2
+            0 4 5 1
+            1 3 4 2";
+        let hemicube = Abstract::from_incidence(src).unwrap();
+        test(&hemicube, &[1, 4, 6, 3, 1]);
+        assert!(!hemicube.orientable());
+    }
+
+    /// Reads a square from its flags.
+    #[test]
+    fn square_flags() {
+        let src = "FLAGS 2
+            0 0
+            1 0
+            1 1
+            2 1
+            2 2
+            3 2
+            3 3
+            0 3";
+        test(&Abstract::from_incidence(src).unwrap(), &[1, 4, 4, 1]);
+    }
+
+    /// Rejects things that aren't polytopes.
+    #[test]
+    fn invalid() {
+        assert!(matches!(
+            Abstract::from_incidence("INC 2\n3\n1\n0 1 2"),
+            Err(IncidenceError::Invalid(_))
+        ));
+        assert!(matches!(
+            Abstract::from_incidence("INC 2\n3\n3\n0 1\n1 2\n2 3"),
+            Err(IncidenceError::Index(6))
+        ));
+        assert!(matches!(
+            Abstract::from_incidence("OFF 2"),
+            Err(IncidenceError::Header)
+        ));
+    }
+
+    /// Rejects element counts that the file can't hold, without trying to
+    /// allocate space for them.
+    #[test]
+    fn huge_count() {
+        assert!(matches!(
+            Abstract::from_incidence("INC 2\n3\n99999999999999999\n0 1"),
+            Err(IncidenceError::Count(3))
+        ));
+        assert!(matches!(
+            Abstract::from_incidence("INC 2\n99999999999999999\n1\n0 1"),
+            Err(IncidenceError::Count(2))
+        ));
+    }
+
+    /// Rejects flags with indices larger than any element could have.
+    #[test]
+    fn huge_flag_index() {
+        assert!(matches!(
+            Abstract::from_incidence("FLAGS 2\n0 99999999999999999\n1 0"),
+            Err(IncidenceError::Index(2))
+        ));
+    }
+
+    /// Rejects a dyad with the wrong number of vertices.
+    #[test]
+    fn dyad_vertices() {
+        test(&Abstract::from_incidence("INC 1\n2").unwrap(), &[1, 2, 1]);
+        assert!(matches!(
+            Abstract::from_incidence("INC 1\n5"),
+            Err(IncidenceError::Count(2))
+        ));
+        assert!(matches!(
+            Abstract::from_incidence("INC 1\n1"),
+            Err(IncidenceError::Invalid(_))
+        ));
+    }
+
+    /// Rejects an edge with a repeated vertex.
+    #[test]
+    fn duplicate() {
+        assert!(matches!(
+            Abstract::from_incidence("INC 2\n1\n1\n0 0"),
+            Err(IncidenceError::Duplicate(4))
+        ));
+    }
+
+    /// Rejects a polygon with a single edge, whose maximal element isn't
+    /// dyadic.
+    #[test]
+    fn single_edge() {
+        assert!(matches!(
+            Abstract::from_incidence("INC 2\n2\n1\n0 1"),
+            Err(IncidenceError::Invalid(AbstractError::Dyadic { .. }))
+        ));
+    }
+}
@@ -1,13 +1,25 @@
 //! Reading from and writing to files in various different formats.
 
+pub mod cells;
 pub mod ggb;
+pub mod gltf;
+pub mod group;
+pub mod incidence;
+pub mod moff;
 pub mod off;
+pub mod orbits;
+pub mod povray;
+pub mod recipe;
+pub mod tiling;
 
 use self::{
     ggb::{GgbError, GgbResult},
+    incidence::{IncidenceError, IncidenceResult},
+    moff::{MoffError, MoffResult},
     off::{OffParseResult, OffReader},
+    recipe::{Recipe, RecipeError, RecipeResult},
 };
-use crate::conc::Concrete;
+use crate::{abs::Abstract, conc::Concrete};
 
 use off::OffParseError;
 use zip::result::ZipError;
@@ -24,6 +36,15 @@ pub enum FileError<'a> {
     /// An error while reading a GGB file.
     GgbError(GgbError),
 
+    /// An error while reading an incidence file.
+    IncidenceError(IncidenceError),
+
+    /// An error while reading a MOFF file.
+    MoffError(MoffError),
+
+    /// An error while reading a coordinate recipe.
+    RecipeError(RecipeError),
+
     /// Some generic I/O error occured.
     IoError(IoError),
 
@@ -43,6 +64,9 @@ impl<'a> Display for FileError<'a> {
         match self {
             Self::OffError(err) => write!(f, "OFF error: {}", err),
             Self::GgbError(err) => write!(f, "GGB error: {}", err),
+            Self::IncidenceError(err) => write!(f, "incidence error: {}", err),
+            Self::MoffError(err) => write!(f, "MOFF error: {}", err),
+            Self::RecipeError(err) => write!(f, "recipe error: {}", err),
             Self::IoError(err) => write!(f, "IO error: {}", err),
             Self::ZipError(err) => write!(f, "ZIP error while opening GGB: {}", err),
             Self::InvalidFile(err) => write!(f, "invalid file: {}", err),
@@ -67,6 +91,27 @@ impl<'a> From<GgbError> for FileError<'a> {
     }
 }
 
+/// [`IncidenceError`] is a type of [`FileError`].
+impl<'a> From<IncidenceError> for FileError<'a> {
+    fn from(err: IncidenceError) -> Self {
+        Self::IncidenceError(err)
+    }
+}
+
+/// [`MoffError`] is a type of [`FileError`].
+impl<'a> From<MoffError> for FileError<'a> {
+    fn from(err: MoffError) -> Self {
+        Self::MoffError(err)
+    }
+}
+
+/// [`RecipeError`] is a type of [`FileError`].
+impl<'a> From<RecipeError> for FileError<'a> {
+    fn from(err: RecipeError) -> Self {
+        Self::RecipeError(err)
+    }
+}
+
 /// [`FromUtf8Error`] is a type of [`FileError`].
 impl<'a> From<FromUtf8Error> for FileError<'a> {
     fn from(err: FromUtf8Error) -> Self {
@@ -103,6 +148,17 @@ pub trait FromFile: Sized {
     /// 3D.
     fn from_ggb(file: File) -> GgbResult<Self>;
 
+    /// Reads an incidence file, which describes an abstract polytope by its
+    /// element lists or its flags.
+    fn from_incidence(src: &str) -> IncidenceResult<Self>;
+
+    /// Reads a MOFF file, the compressed binary version of an OFF file.
+    fn from_moff(bytes: &[u8]) -> MoffResult<Self>;
+
+    /// Reads a coordinate recipe, and builds the convex hull of the vertices
+    /// it describes.
+    fn from_recipe(src: &str) -> RecipeResult<Self>;
+
     /// Loads a polytope from a file path.
     fn from_path<U: AsRef<std::path::Path>>(fp: &U) -> FileResult<'_, Self> {
         use std::{ffi::OsStr, fs};
@@ -123,6 +179,21 @@ pub trait FromFile: Sized {
             // Reads the file as a GGB file.
             "ggb" => Ok(Self::from_ggb(File::open(fp)?)?),
 
+            // Reads the file as a MOFF file.
+            "moff" => Ok(Self::from_moff(&fs::read(fp)?)?),
+
+            // Reads the file as an incidence file.
+            "inc" => match String::from_utf8(fs::read(fp)?) {
+                Ok(src) => Ok(Self::from_incidence(&src)?),
+                Err(err) => Err(err.into()),
+            },
+
+            // Reads the file as a coordinate recipe.
+            "recipe" => match String::from_utf8(fs::read(fp)?) {
+                Ok(src) => Ok(Self::from_recipe(&src)?),
+                Err(err) => Err(err.into()),
+            },
+
             // Could not recognize the file extension.
             ext => Err(FileError::InvalidExtension(ext)),
         }
@@ -151,6 +222,20 @@ impl FromFile for Concrete {
             Err(GgbError::InvalidGgb)
         }
     }
+
+    fn from_moff(bytes: &[u8]) -> MoffResult<Self> {
+        moff::parse_moff(bytes)
+    }
+
+    fn from_recipe(src: &str) -> RecipeResult<Self> {
+        Recipe::parse(src)?.build()
+    }
+
+    /// Reads an incidence file. Since these carry no coordinates, the
+    /// polytope gets an embedding of its skeleton.
+    fn from_incidence(src: &str) -> IncidenceResult<Self> {
+        Ok(Concrete::from_abstract(Abstract::from_incidence(src)?))
+    }
 }
 
 /// A position in a file.
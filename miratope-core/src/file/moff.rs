@@ -0,0 +1,367 @@
+//! Contains the code that reads and writes MOFF files, a compact binary
+//! version of the OFF format, meant for polytopes so large that their OFF
+//! files become unwieldy, like facetings of big uniform polytopes.
+//!
+//! A file consists of the magic bytes `MOFF`, a version byte, and a
+//! [zstd](https://facebook.github.io/zstd/)-compressed payload. The payload
+//! stores the rank of the polytope and the number of coordinates of its
+//! vertices, then the vertex coordinates as little-endian `f64`s, and finally,
+//! for every rank from edges up to facets, the number of elements followed by
+//! the subelement list of each. Every integer is written as an unsigned
+//! LEB128 varint, and the subelements of each element are sorted and written
+//! as the differences between consecutive indices.
+//!
+//! Unlike OFF files, these store every element explicitly, so any polytope
+//! can be written, skew or compound faces included.
+
+use std::{fmt::Display, io::Error as IoError, path::Path};
+
+use crate::{
+    abs::{AbstractBuilder, Ranked, SubelementList, Subelements},
+    conc::{Concrete, ConcretePolytope},
+    geometry::Point,
+    Polytope,
+};
+
+use vec_like::*;
+
+/// The magic bytes at the start of every MOFF file.
+const MAGIC: &[u8; 4] = b"MOFF";
+
+/// The version of the format we write.
+const VERSION: u8 = 1;
+
+/// The zstd compression level. Higher levels barely make these files smaller,
+/// but take much longer to write.
+const LEVEL: i32 = 9;
+
+/// Any error encountered while reading a MOFF file.
+#[derive(Debug)]
+pub enum MoffError {
+    /// The file doesn't start with the magic bytes.
+    MagicWord,
+
+    /// The file was written with a version of the format we don't know.
+    Version(u8),
+
+    /// The payload couldn't be decompressed.
+    IoError(IoError),
+
+    /// The payload ended unexpectedly.
+    UnexpectedEnding,
+
+    /// Some count is larger than the rest of the payload could hold.
+    Count,
+
+    /// Some element of a given rank refers to an element that doesn't exist.
+    Index {
+        /// The rank of the element at fault.
+        rank: usize,
+    },
+
+    /// Some element of a given rank lists the same subelement twice.
+    Duplicate {
+        /// The rank of the element at fault.
+        rank: usize,
+    },
+}
+
+impl Display for MoffError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MagicWord => write!(f, "no \"MOFF\" detected"),
+            Self::Version(version) => write!(f, "unknown version {}", version),
+            Self::IoError(err) => write!(f, "could not decompress: {}", err),
+            Self::UnexpectedEnding => write!(f, "file ended unexpectedly"),
+            Self::Count => write!(f, "count too large for the file"),
+            Self::Index { rank } => write!(f, "invalid subelement index at rank {}", rank),
+            Self::Duplicate { rank } => write!(f, "duplicate subelement index at rank {}", rank),
+        }
+    }
+}
+
+impl std::error::Error for MoffError {}
+
+impl From<IoError> for MoffError {
+    fn from(err: IoError) -> Self {
+        Self::IoError(err)
+    }
+}
+
+/// The result of reading a MOFF file.
+pub type MoffResult<T> = Result<T, MoffError>;
+
+/// Appends an integer to a buffer as a varint.
+fn write_varint(buf: &mut Vec<u8>, mut n: usize) {
+    while n >= 0x80 {
+        buf.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    buf.push(n as u8);
+}
+
+/// Reads through the decompressed payload of a MOFF file.
+struct Reader<'a> {
+    /// The remaining bytes.
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Reads a given number of bytes.
+    fn take(&mut self, len: usize) -> MoffResult<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(MoffError::UnexpectedEnding);
+        }
+
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    /// Reads a varint.
+    fn varint(&mut self) -> MoffResult<usize> {
+        let mut n = 0;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.take(1)?[0];
+            n |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+
+        Err(MoffError::UnexpectedEnding)
+    }
+
+    /// Reads the number of items in a list, each taking up at least `size`
+    /// bytes. Since the count comes from the file, we check that the rest of
+    /// the payload can actually hold that many items before we allocate
+    /// anything for them.
+    fn count(&mut self, size: usize) -> MoffResult<usize> {
+        let count = self.varint()?;
+        if count.saturating_mul(size) > self.bytes.len() {
+            Err(MoffError::Count)
+        } else {
+            Ok(count)
+        }
+    }
+
+    /// Reads a little-endian float.
+    fn f64(&mut self) -> MoffResult<f64> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+impl Concrete {
+    /// Writes the polytope into a MOFF file.
+    pub fn to_moff(&self) -> Result<Vec<u8>, IoError> {
+        let mut poly = self.clone();
+        poly.element_sort();
+
+        let rank = poly.rank();
+        let dim = poly.dim_or();
+        let mut payload = Vec::new();
+        write_varint(&mut payload, rank);
+        write_varint(&mut payload, dim);
+
+        write_varint(&mut payload, poly.vertices.len());
+        for v in &poly.vertices {
+            for &c in v.iter() {
+                payload.extend_from_slice(&c.to_le_bytes());
+            }
+        }
+
+        for r in 2..rank {
+            write_varint(&mut payload, poly.el_count(r));
+            for el in &poly[r] {
+                write_varint(&mut payload, el.subs.len());
+
+                let mut prev = 0;
+                for &sub in &el.subs {
+                    write_varint(&mut payload, sub - prev);
+                    prev = sub;
+                }
+            }
+        }
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend(zstd::stream::encode_all(payload.as_slice(), LEVEL)?);
+        Ok(bytes)
+    }
+
+    /// Writes the polytope into a MOFF file in a specified file path.
+    pub fn to_moff_path<P: AsRef<Path>>(&self, fp: P) -> Result<(), IoError> {
+        std::fs::write(fp, self.to_moff()?)
+    }
+}
+
+/// Reads a polytope from a MOFF file.
+pub fn parse_moff(bytes: &[u8]) -> MoffResult<Concrete> {
+    let payload = bytes.strip_prefix(MAGIC).ok_or(MoffError::MagicWord)?;
+    let (&version, payload) = payload.split_first().ok_or(MoffError::UnexpectedEnding)?;
+    if version != VERSION {
+        return Err(MoffError::Version(version));
+    }
+
+    let payload = zstd::stream::decode_all(payload)?;
+    let mut reader = Reader { bytes: &payload };
+    let rank = reader.varint()?;
+    let dim = reader.varint()?;
+
+    if rank == 0 {
+        return Ok(Concrete::nullitope());
+    }
+
+    // Every vertex of a polytope in 0D space is the origin, so there's no
+    // room for more than one of them.
+    let vertex_count = reader.count(dim.saturating_mul(8))?;
+    if dim == 0 && vertex_count > 1 {
+        return Err(MoffError::Count);
+    }
+
+    let mut vertices = Vec::with_capacity(vertex_count);
+    for _ in 0..vertex_count {
+        let mut v = Vec::with_capacity(dim);
+        for _ in 0..dim {
+            v.push(reader.f64()?);
+        }
+        vertices.push(Point::from_vec(v));
+    }
+
+    if rank == 1 {
+        let mut point = Concrete::point();
+        point.vertices = vertices;
+        return Ok(point);
+    }
+
+    let mut builder = AbstractBuilder::with_rank_capacity(rank);
+    builder.push_min();
+    builder.push_vertices(vertex_count);
+
+    let mut prev_count = vertex_count;
+    for r in 2..rank {
+        // Every element stores at least its number of subelements.
+        let count = reader.count(1)?;
+        let mut els = SubelementList::with_capacity(count);
+
+        for _ in 0..count {
+            let len = reader.count(1)?;
+            let mut subs = Subelements::with_capacity(len);
+
+            let mut sub: usize = 0;
+            for i in 0..len {
+                // Only the first subelement may be written as a difference of
+                // zero, as the rest are strictly increasing.
+                let delta = reader.varint()?;
+                if i != 0 && delta == 0 {
+                    return Err(MoffError::Duplicate { rank: r });
+                }
+
+                sub = sub.saturating_add(delta);
+                if sub >= prev_count {
+                    return Err(MoffError::Index { rank: r });
+                }
+                subs.push(sub);
+            }
+
+            els.push(subs);
+        }
+
+        builder.push(els);
+        prev_count = count;
+    }
+    builder.push_max();
+
+    // Safety: the file was written from a valid polytope. Like with OFF
+    // files, we only check that the indices make sense.
+    let mut poly = Concrete::new(vertices, unsafe { builder.build() });
+    poly.element_sort();
+    Ok(poly)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+
+    /// Checks that a polytope survives being written and read back.
+    fn round_trip(poly: &Concrete) -> Concrete {
+        let back = parse_moff(&poly.to_moff().unwrap()).unwrap();
+        assert_eq!(back.vertices, poly.vertices);
+        back
+    }
+
+    /// Writes and reads a few polytopes.
+    #[test]
+    fn polytopes() {
+        test(&round_trip(&Concrete::nullitope()), [1]);
+        test(&round_trip(&Concrete::point()), [1, 1]);
+        test(&round_trip(&Concrete::polygon(7)), [1, 7, 7, 1]);
+        test(&round_trip(&Concrete::cube()), [1, 8, 12, 6, 1]);
+        test(&round_trip(&Concrete::cube().prism()), [1, 16, 32, 24, 8, 1]);
+    }
+
+    /// Skew polytopes keep their coordinates.
+    #[test]
+    fn skew() {
+        let duocomb = Concrete::polygon(3).duocomb(&Concrete::polygon(4));
+        round_trip(&duocomb);
+    }
+
+    /// Invalid files should fail.
+    #[test]
+    fn invalid() {
+        assert!(matches!(parse_moff(b"OFF"), Err(MoffError::MagicWord)));
+        assert!(matches!(parse_moff(b"MOFF\x07"), Err(MoffError::Version(7))));
+    }
+
+    /// Compresses a raw payload into a MOFF file.
+    fn moff(payload: &[u8]) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend(zstd::stream::encode_all(payload, LEVEL).unwrap());
+        bytes
+    }
+
+    /// Counts larger than the file could hold should fail before anything is
+    /// allocated for them.
+    #[test]
+    fn huge_counts() {
+        let mut payload = Vec::new();
+        write_varint(&mut payload, 3);
+        write_varint(&mut payload, 2);
+        write_varint(&mut payload, usize::MAX >> 4);
+        assert!(matches!(parse_moff(&moff(&payload)), Err(MoffError::Count)));
+
+        let mut payload = Vec::new();
+        write_varint(&mut payload, 3);
+        write_varint(&mut payload, usize::MAX >> 4);
+        write_varint(&mut payload, 1);
+        payload.extend(0.0f64.to_le_bytes());
+        assert!(matches!(parse_moff(&moff(&payload)), Err(MoffError::Count)));
+
+        let mut payload = Vec::new();
+        write_varint(&mut payload, 3);
+        write_varint(&mut payload, 0);
+        write_varint(&mut payload, 1);
+        write_varint(&mut payload, usize::MAX >> 4);
+        assert!(matches!(parse_moff(&moff(&payload)), Err(MoffError::Count)));
+    }
+
+    /// Elements can't list a subelement twice.
+    #[test]
+    fn duplicates() {
+        let mut payload = Vec::new();
+        write_varint(&mut payload, 3);
+        write_varint(&mut payload, 0);
+        write_varint(&mut payload, 1);
+        write_varint(&mut payload, 1);
+        write_varint(&mut payload, 2);
+        write_varint(&mut payload, 0);
+        write_varint(&mut payload, 0);
+        assert!(matches!(
+            parse_moff(&moff(&payload)),
+            Err(MoffError::Duplicate { rank: 2 })
+        ));
+    }
+}
@@ -1,5 +1,17 @@
 //! The code that opens an OFF file and parses it into a polytope.
 //! Also the code that writes an OFF file of a polytope.
+//!
+//! An OFF file for a polytope of rank `r` starts with the header `{r - 1}OFF`,
+//! or just `OFF` for polyhedra. It's followed by the number of vertices, faces,
+//! edges, and then the number of elements of every other rank up to the
+//! facets. Next come the coordinates of the vertices, then the faces, each
+//! written as the cycle of its vertices, and then the elements of every other
+//! rank. Each of these is written as the number of its subelements, followed
+//! by their indices in the list right before it. Edges aren't written, since
+//! they can be read off the faces.
+//!
+//! Polygons are the exception, since their single face is written as the
+//! cycles of its components instead.
 
 use std::{collections::{HashMap, HashSet}, fmt::Display, io::Error as IoError, path::Path, str::FromStr};
 
@@ -7,7 +19,7 @@ use super::Position;
 
 use crate::{
     abs::{AbstractBuilder, Ranked, SubelementList, Subelements},
-    conc::{cycle::CycleList, Concrete, element_types::EL_NAMES},
+    conc::{cycle::CycleList, Concrete, ConcretePolytope, element_types::EL_NAMES},
     geometry::Point,
     Polytope, COMPONENTS
 };
@@ -21,6 +33,15 @@ const HEADER: &str = concat!(
     " (https://github.com/galoomba1/miratope-rs)"
 );
 
+/// The tag of the comment that marks an OFF file for a skew polytope, i.e. one
+/// whose vertices have more coordinates than its rank would suggest. It's
+/// followed by the rank of the polytope, written as in the OFF header, and the
+/// number of coordinates of each vertex.
+///
+/// Since it's a comment, this doesn't change how the rest of the file is read,
+/// other than the number of coordinates.
+const SKEW: &str = "#SKEW";
+
 /// Any error encountered while parsing an OFF file.
 #[derive(Clone, Copy, Debug)]
 pub enum OffParseError {
@@ -38,6 +59,12 @@ pub enum OffParseError {
 
     /// Didn't find the OFF magic word.
     MagicWord(Position),
+
+    /// The skew header is malformed, or doesn't match the rank of the file.
+    Skew(Position),
+
+    /// An element refers to a subelement that doesn't exist.
+    Index(Position),
 }
 
 impl Display for OffParseError {
@@ -48,6 +75,8 @@ impl Display for OffParseError {
             Self::Parsing(pos) => write!(f, "could not parse number at {}", pos),
             Self::Rank(pos) => write!(f, "could not read rank at {}", pos),
             Self::MagicWord(pos) => write!(f, "no \"OFF\" detected at {}", pos),
+            Self::Skew(pos) => write!(f, "invalid {} header at {}", SKEW, pos),
+            Self::Index(pos) => write!(f, "subelement index out of range at {}", pos),
         }
     }
 }
@@ -243,11 +272,60 @@ impl<'a> OffReader<'a> {
         self.iter.next()
     }
 
+    /// Reads the index of a subelement, and checks that it's smaller than the
+    /// number of elements of the previous rank.
+    fn parse_index(&mut self, count: usize) -> OffParseResult<usize> {
+        let token = self
+            .next()
+            .ok_or(OffParseError::UnexpectedEnding(self.iter.position))?;
+        let idx = token.parse()?;
+
+        if idx < count {
+            Ok(idx)
+        } else {
+            Err(OffParseError::Index(token.pos))
+        }
+    }
+
     /// Reads the first token from the OFF file, returns the polytope's rank.
     fn rank(&mut self) -> OffParseResult<usize> {
         self.next().ok_or(OffParseError::Empty)?.rank()
     }
 
+    /// Reads the skew header from the comments before the OFF header, if any.
+    /// Returns the position of the header, the rank it specifies, and the
+    /// number of coordinates of each vertex.
+    fn skew(&self) -> OffParseResult<Option<(Position, usize, usize)>> {
+        for (row, line) in self.src().lines().enumerate() {
+            let line = line.trim();
+            if !line.is_empty() && !line.starts_with('#') {
+                break;
+            }
+
+            if let Some(rest) = line.strip_prefix(SKEW) {
+                let pos = Position {
+                    row: row as u32,
+                    column: 0,
+                };
+
+                let nums: Vec<isize> = rest
+                    .split_whitespace()
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| OffParseError::Skew(pos))?;
+
+                return match nums[..] {
+                    [rank, dim] if rank >= -1 && dim >= 0 => {
+                        Ok(Some((pos, (rank + 1) as usize, dim as usize)))
+                    }
+                    _ => Err(OffParseError::Skew(pos)),
+                };
+            }
+        }
+
+        Ok(None)
+    }
+
     /// Gets the number of elements from the OFF file from rank 1 up to rank
     /// max(3, dim - 1). For the purposes of the OFF format, the 2-elements of
     /// a polygon are taken to be its components.
@@ -304,6 +382,7 @@ impl<'a> OffReader<'a> {
     fn parse_edges_and_faces(
         &mut self,
         rank: usize,
+        num_vertices: usize,
         num_edges: usize,
         num_faces: usize,
     ) -> OffParseResult<(SubelementList, SubelementList)> {
@@ -319,7 +398,7 @@ impl<'a> OffReader<'a> {
 
             // Reads all vertices of the face.
             for _ in 0..face_sub_num {
-                face_verts.push(self.iter.parse_next()?);
+                face_verts.push(self.parse_index(num_vertices)?);
             }
 
             // We add the first vertex to the end for simplicity.
@@ -371,8 +450,9 @@ impl<'a> OffReader<'a> {
         Ok((edges, faces))
     }
 
-    /// Parses the next set of d-elements from the OFF file.
-    fn parse_els(&mut self, num_el: usize) -> OffParseResult<SubelementList> {
+    /// Parses the next set of d-elements from the OFF file, given the number
+    /// of (d - 1)-elements.
+    fn parse_els(&mut self, num_el: usize, num_subs: usize) -> OffParseResult<SubelementList> {
         let mut els_subs = SubelementList::with_capacity(num_el);
 
         // Adds every d-element to the element list.
@@ -382,7 +462,7 @@ impl<'a> OffReader<'a> {
 
             // Reads all sub-elements of the d-element.
             for _ in 0..el_sub_num {
-                subs.push(self.iter.parse_next()?);
+                subs.push(self.parse_index(num_subs)?);
             }
 
             els_subs.push(subs);
@@ -408,19 +488,32 @@ impl<'a> OffReader<'a> {
 
     /// Builds a concrete polytope from the OFF reader.
     pub fn build(mut self) -> OffParseResult<Concrete> {
-        // Reads the rank of the polytope.
+        // Reads the rank of the polytope, and the dimension of its vertices.
+        let skew = self.skew()?;
         let rank = self.rank()?;
+        let dim = match skew {
+            Some((_, skew_rank, dim)) if skew_rank == rank => dim,
+            Some((pos, _, _)) => return Err(OffParseError::Skew(pos)),
+            None => rank.saturating_sub(1),
+        };
 
-        // Deals with dumb degenerate cases.
+        // Deals with dumb degenerate cases. Skew points store their
+        // coordinates right after the header.
         match rank {
             0 => return Ok(Concrete::nullitope()),
-            1 => return Ok(Concrete::point()),
+            1 => {
+                let mut point = Concrete::point();
+                if skew.is_some() {
+                    point.vertices = self.parse_vertices(1, dim)?;
+                }
+                return Ok(point);
+            }
             _ => {}
         }
 
         // Reads the element numbers and vertices.
         let num_elems = self.el_nums(rank)?;
-        let vertices = self.parse_vertices(num_elems[0], rank - 1)?;
+        let vertices = self.parse_vertices(num_elems[0], dim)?;
 
         // Adds nullitope and vertices.
         self.abs.reserve(rank + 2);
@@ -428,15 +521,20 @@ impl<'a> OffReader<'a> {
         self.abs.push_vertices(vertices.len());
 
         // Reads edges and faces.
+        let mut num_subs = vertices.len();
         if rank >= 3 {
-            let (edges, faces) = self.parse_edges_and_faces(rank, num_elems[1], num_elems[2])?;
+            let (edges, faces) =
+                self.parse_edges_and_faces(rank, num_subs, num_elems[1], num_elems[2])?;
+            num_subs = faces.len();
             self.abs.push(edges);
             self.abs.push(faces);
         }
 
-        // Adds all higher elements.
+        // Adds all higher elements. Each of them refers to the elements of the
+        // rank right below by their order in the file.
         for &num_el in num_elems.iter().take(rank - 1).skip(3) {
-            let subelements = self.parse_els(num_el)?;
+            let subelements = self.parse_els(num_el, num_subs)?;
+            num_subs = subelements.len();
             self.abs.push(subelements);
         }
 
@@ -480,16 +578,49 @@ impl Concrete {
     }
 }*/
 
+/// The line endings used when writing a file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Lines end with `\n`.
+    #[default]
+    Unix,
+
+    /// Lines end with `\r\n`.
+    Windows,
+}
+
 /// A set of options to be used when saving the OFF file.
-#[derive(Clone, Copy)]
+#[derive(Clone, Debug)]
 pub struct OffOptions {
     /// Whether the OFF file should have comments specifying each face type.
     pub comments: bool,
+
+    /// The number of decimal places written for each coordinate. If `None`,
+    /// coordinates are written with as many places as needed to read them
+    /// back exactly.
+    pub precision: Option<usize>,
+
+    /// Whether to sort the vertices and elements in a canonical order, so that
+    /// files for the same polytope can be compared with a diff.
+    pub sort: bool,
+
+    /// Some lines describing where the polytope comes from, written as a
+    /// comment at the top of the file.
+    pub provenance: Option<String>,
+
+    /// The line endings of the file.
+    pub line_ending: LineEnding,
 }
 
 impl Default for OffOptions {
     fn default() -> Self {
-        OffOptions { comments: true }
+        OffOptions {
+            comments: true,
+            precision: None,
+            sort: false,
+            provenance: None,
+            line_ending: LineEnding::Unix,
+        }
     }
 }
 
@@ -544,6 +675,9 @@ pub struct OffWriter<'a> {
 
     /// Options for the text output.
     options: OffOptions,
+
+    /// The index in the file of each element of the last rank written.
+    labels: Vec<usize>,
 }
 
 impl<'a> OffWriter<'a> {
@@ -554,6 +688,7 @@ impl<'a> OffWriter<'a> {
             off: String::new(),
             poly,
             options,
+            labels: Vec::new(),
         }
     }
 
@@ -587,6 +722,20 @@ impl<'a> OffWriter<'a> {
         self.push_str(data.to_string())
     }
 
+    /// Returns whether the polytope is skew, i.e. whether its vertices have
+    /// more coordinates than its rank would suggest.
+    fn skew(&self) -> bool {
+        let rank = self.rank();
+        rank >= 1 && self.poly.dim().is_some_and(|dim| dim + 1 != rank)
+    }
+
+    /// Writes the skew header, which must go before the OFF format header.
+    fn write_skew(&mut self) {
+        let rank = self.rank() as isize - 1;
+        let dim = self.poly.dim_or();
+        self.push_str(format!("{} {} {}\n", SKEW, rank, dim));
+    }
+
     /// Writes the OFF format header.
     fn write_rank(&mut self) {
         let rank = self.rank();
@@ -680,6 +829,78 @@ impl<'a> OffWriter<'a> {
         self.push('\n');
     }
 
+    /// Formats a coordinate with the precision in the options.
+    fn coordinate(&self, c: f64) -> String {
+        match self.options.precision {
+            Some(precision) => {
+                let formatted = format!("{:.*}", precision, c);
+
+                // Negative numbers that round to zero are written as zero.
+                match formatted.strip_prefix('-') {
+                    Some(abs) if abs.chars().all(|ch| ch == '0' || ch == '.') => abs.to_string(),
+                    _ => formatted,
+                }
+            }
+            None => c.to_string(),
+        }
+    }
+
+    /// Returns the order in which some lines should be written, given the keys
+    /// they're sorted by, together with the index in the file of each line.
+    /// Unless we're sorting, lines keep their original order.
+    fn order<T: Ord>(&self, keys: &[T]) -> (Vec<usize>, Vec<usize>) {
+        let mut order: Vec<usize> = (0..keys.len()).collect();
+        if self.options.sort {
+            order.sort_by(|&i, &j| keys[i].cmp(&keys[j]));
+        }
+
+        let mut labels = vec![0; keys.len()];
+        for (new, &old) in order.iter().enumerate() {
+            labels[old] = new;
+        }
+
+        (order, labels)
+    }
+
+    /// Relabels the vertices of a cycle according to their order in the file.
+    /// When sorting, the cycle is also rotated to start at its least vertex,
+    /// and traversed towards its least neighbor.
+    fn relabel_cycle(&self, cycle: Vec<usize>) -> Vec<usize> {
+        let mut cycle: Vec<usize> = cycle.into_iter().map(|v| self.labels[v]).collect();
+        if self.options.sort {
+            if let Some(min) = cycle.iter().enumerate().min_by_key(|&(_, v)| v).map(|(i, _)| i) {
+                cycle.rotate_left(min);
+            }
+            if cycle.len() > 2 && cycle[cycle.len() - 1] < cycle[1] {
+                cycle[1..].reverse();
+            }
+        }
+        cycle
+    }
+
+    /// Writes a list of lines, each consisting of a list of indices preceded
+    /// by their count, in a given order.
+    fn write_index_lines(&mut self, lines: &[Vec<usize>], order: &[usize]) {
+        for &idx in order {
+            self.push_to_str(lines[idx].len());
+            for &i in &lines[idx] {
+                self.push(' ');
+                self.push_to_str(i);
+            }
+            self.push('\n');
+        }
+    }
+
+    /// Writes the coordinates of a vertex in a single line.
+    fn write_coordinates(&mut self, idx: usize) {
+        for i in 0..self.poly.vertices[idx].len() {
+            let c = self.coordinate(self.poly.vertices[idx][i]);
+            self.push_str(c);
+            self.push(' ');
+        }
+        self.push('\n');
+    }
+
     /// Writes the vertices of a polytope into an OFF file.
     fn write_vertices(&mut self) {
         // # Vertices
@@ -689,14 +910,21 @@ impl<'a> OffWriter<'a> {
             self.push('\n');
         }
 
+        // Vertices are sorted by their coordinates, up to rounding errors.
+        let keys: Vec<Vec<i64>> = self
+            .poly
+            .vertices
+            .iter()
+            .map(|v| v.iter().map(|&c| ((c * 1e9).round() + 0.0) as i64).collect())
+            .collect();
+        let (order, labels) = self.order(&keys);
+
         // Adds the coordinates.
-        for v in &self.poly.vertices {
-            for c in v {
-                self.push_to_str(c);
-                self.push(' ');
-            }
-            self.push('\n');
+        for idx in order {
+            self.write_coordinates(idx);
         }
+
+        self.labels = labels;
     }
 
     /// Gets and writes the faces of a polytope into an OFF file.
@@ -714,19 +942,15 @@ impl<'a> OffWriter<'a> {
             self.push('\n');
         }
 
-        // Writes the components in the polygonal case.
-        if rank == 3 {
-            for component in CycleList::from_edges(self.poly[2].iter().map(|edge| &edge.subs)) {
-                self.push_to_str(component.len());
-                for vert in component {
-                    self.push(' ');
-                    self.push_to_str(vert);
-                }
-                self.push('\n');
-            }
+        // Gets the components in the polygonal case, and the faces otherwise.
+        let cycles: Vec<Vec<usize>> = if rank == 3 {
+            CycleList::from_edges(self.poly[2].iter().map(|edge| &edge.subs))
+                .into_iter()
+                .map(|component| self.relabel_cycle(component.into_iter().collect()))
+                .collect()
         } else {
+            let mut faces = Vec::with_capacity(self.el_count(3));
             for (idx, face) in self.poly[3].iter().enumerate() {
-                self.push_to_str(face.subs.len());
                 let mut cycles =
                     CycleList::from_edges(face.subs.iter().map(|&i| &self.poly[(2, i)].subs));
 
@@ -734,13 +958,14 @@ impl<'a> OffWriter<'a> {
                     return Err(OffWriteError::CompoundFace { idx });
                 }
 
-                for v in cycles.swap_remove(0) {
-                    self.push(' ');
-                    self.push_to_str(v);
-                }
-                self.push('\n');
+                faces.push(self.relabel_cycle(cycles.swap_remove(0).into_iter().collect()));
             }
-        }
+            faces
+        };
+
+        let (order, labels) = self.order(&cycles);
+        self.write_index_lines(&cycles, &order);
+        self.labels = labels;
 
         Ok(())
     }
@@ -754,18 +979,21 @@ impl<'a> OffWriter<'a> {
             self.push('\n');
         }
 
-        // Adds the elements' indices.
-        for el in &self.poly[rank] {
-            let subs = &el.subs;
-            self.push_to_str(subs.len());
-
-            for &sub in subs {
-                self.push(' ');
-                self.push_to_str(sub);
-            }
+        // Gets the elements' indices, as written in the file.
+        let els: Vec<Vec<usize>> = self.poly[rank]
+            .iter()
+            .map(|el| {
+                let mut subs: Vec<usize> = el.subs.iter().map(|&sub| self.labels[sub]).collect();
+                if self.options.sort {
+                    subs.sort_unstable();
+                }
+                subs
+            })
+            .collect();
 
-            self.push('\n');
-        }
+        let (order, labels) = self.order(&els);
+        self.write_index_lines(&els, &order);
+        self.labels = labels;
     }
 
     /// Consumes the OFF writer, returns the actual OFF file as a `String`.
@@ -785,12 +1013,29 @@ impl<'a> OffWriter<'a> {
             self.push('\n');
         }
 
+        // Where the polytope comes from.
+        if let Some(provenance) = self.options.provenance.clone() {
+            for line in provenance.lines() {
+                self.push_str("# ");
+                self.push_str(line);
+                self.push('\n');
+            }
+        }
+
         // Writes header.
+        let skew = self.skew();
+        if skew {
+            self.write_skew();
+        }
         self.write_rank();
 
-        // If we have a nullitope or point on our hands, that is all.
+        // If we have a nullitope or point on our hands, that is all, unless
+        // the point has coordinates.
         if rank < 2 {
-            return Ok(self.off);
+            if skew {
+                self.write_coordinates(0);
+            }
+            return Ok(self.finish());
         }
 
         // Adds the element counts.
@@ -809,7 +1054,15 @@ impl<'a> OffWriter<'a> {
             self.write_els(r);
         }
 
-        Ok(self.off)
+        Ok(self.finish())
+    }
+
+    /// Returns the OFF file with the line endings in the options.
+    fn finish(self) -> String {
+        match self.options.line_ending {
+            LineEnding::Unix => self.off,
+            LineEnding::Windows => self.off.replace('\n', "\r\n"),
+        }
     }
 }
 
@@ -942,6 +1195,167 @@ mod tests {
         test_off!("comments", [1, 4, 6, 4, 1])
     }
 
+    /// Checks that sorted OFF files don't depend on the order of the vertices
+    /// and faces.
+    #[test]
+    fn sorted() {
+        // Every face goes on its own line, since anything after it is ignored.
+        let a = Concrete::from_off(
+            "OFF 4 4 6 1 1 1 1 -1 -1 -1 1 -1 -1 -1 1\n\
+            3 0 1 2\n3 0 3 1\n3 0 2 3\n3 1 3 2",
+        )
+        .unwrap();
+        let b = Concrete::from_off(
+            "OFF 4 4 6 -1 -1 1 -1 1 -1 1 -1 -1 1 1 1\n\
+            3 2 0 1\n3 3 1 0\n3 3 2 1\n3 3 0 2",
+        )
+        .unwrap();
+
+        let options = OffOptions {
+            sort: true,
+            ..Default::default()
+        };
+        assert_eq!(a.to_off(options.clone()).unwrap(), b.to_off(options).unwrap());
+    }
+
+    /// Checks the precision, provenance and line ending options.
+    #[test]
+    fn options() {
+        let poly = Concrete::from_off(include_str!("tet.off")).unwrap();
+        let off = poly
+            .to_off(OffOptions {
+                precision: Some(3),
+                provenance: Some("Tetrahedron\nFrom a test".to_string()),
+                line_ending: LineEnding::Windows,
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert!(off.contains("# From a test\r\n"));
+        assert!(!off.replace("\r\n", "").contains('\n'));
+        let coordinate_lines = off
+            .lines()
+            .filter(|line| !line.starts_with('#') && line.contains('.'));
+        for line in coordinate_lines {
+            assert!(line.split_whitespace().all(|c| c.split('.').nth(1).unwrap().len() == 3));
+        }
+        test(&Concrete::from_off(&off).unwrap(), [1, 4, 6, 4, 1]);
+    }
+
+    /// Checks that skew polytopes keep all of their coordinates.
+    #[test]
+    fn skew() {
+        let square = Concrete::from_off(
+            "#SKEW 2 3\n2OFF\n4 1\n1 0 1\n0 1 -1\n-1 0 1\n0 -1 -1\n4 0 1 2 3",
+        )
+        .unwrap();
+        assert_eq!(square.dim(), Some(3));
+        test(&square, [1, 4, 4, 1]);
+
+        let off = square.to_off(Default::default()).unwrap();
+        assert!(off.contains("#SKEW 2 3\n"));
+        assert_eq!(Concrete::from_off(&off).unwrap().vertices, square.vertices);
+
+        let mut point = Concrete::point();
+        point.vertices = vec![vec![1.0, 2.0].into()];
+        let off = point.to_off(Default::default()).unwrap();
+        assert_eq!(Concrete::from_off(&off).unwrap().vertices, point.vertices);
+
+        // Polytopes that aren't skew are written as usual.
+        let tet = Concrete::from_off(include_str!("tet.off")).unwrap();
+        assert!(!tet.to_off(Default::default()).unwrap().contains(SKEW));
+    }
+
+    /// Returns the vertices of every element of each rank, as sorted lists of
+    /// the given labels of the vertices, so that they don't depend on the
+    /// order in which elements are stored.
+    fn vertex_sets(poly: &Concrete, labels: &[usize]) -> Vec<Vec<Vec<usize>>> {
+        (1..poly.rank())
+            .map(|r| {
+                let mut sets: Vec<Vec<usize>> = (0..poly.el_count(r))
+                    .map(|idx| {
+                        let mut set: Vec<usize> = poly
+                            .abs
+                            .element_vertices(r, idx)
+                            .unwrap()
+                            .into_iter()
+                            .map(|v| labels[v])
+                            .collect();
+                        set.sort_unstable();
+                        set
+                    })
+                    .collect();
+                sets.sort();
+                sets
+            })
+            .collect()
+    }
+
+    /// Checks that a polytope can be written and read back with the same
+    /// element counts, and with every element having the same vertices as
+    /// before, whether the file is sorted or not.
+    fn round_trip<I: IntoIterator<Item = usize> + Clone>(poly: &Concrete, element_counts: I) {
+        test(poly, element_counts.clone());
+        let identity: Vec<usize> = (0..poly.vertices.len()).collect();
+        let original = vertex_sets(poly, &identity);
+
+        for sort in [false, true] {
+            let off = poly
+                .to_off(OffOptions {
+                    sort,
+                    ..Default::default()
+                })
+                .unwrap();
+            let reloaded = Concrete::from_off(&off).unwrap();
+            test(&reloaded, element_counts.clone());
+
+            // Sorting might have moved the vertices around.
+            let labels: Vec<usize> = reloaded
+                .vertices
+                .iter()
+                .map(|v| poly.vertices.iter().position(|w| w == v).unwrap())
+                .collect();
+            assert_eq!(vertex_sets(&reloaded, &labels), original);
+        }
+    }
+
+    /// Round-trips products of rank 5.
+    #[test]
+    fn rank_5() {
+        let triangle = Concrete::polygon(3);
+        let square = Concrete::polygon(4);
+        round_trip(&triangle.duoprism(&square), [1, 12, 24, 19, 7, 1]);
+        round_trip(&triangle.duotegum(&square), [1, 7, 19, 24, 12, 1]);
+    }
+
+    /// Round-trips products of rank 6.
+    #[test]
+    fn rank_6() {
+        let triangle = Concrete::polygon(3);
+        let square = Concrete::polygon(4);
+        round_trip(&triangle.duopyramid(&square), [1, 7, 19, 26, 19, 7, 1]);
+        round_trip(&Concrete::hypercube(6), [1, 32, 80, 80, 40, 10, 1]);
+    }
+
+    /// Round-trips a product of rank 7.
+    #[test]
+    fn rank_7() {
+        let triangle = Concrete::polygon(3);
+        round_trip(
+            &Concrete::multiprism([&triangle, &triangle, &triangle]),
+            [1, 27, 81, 108, 81, 36, 9, 1],
+        );
+    }
+
+    /// Round-trips a product of rank 8.
+    #[test]
+    fn rank_8() {
+        round_trip(
+            &Concrete::hypercube(8),
+            [1, 128, 448, 672, 560, 280, 84, 14, 1],
+        );
+    }
+
     /// Attempts to parse an OFF file, unwraps it.
     fn unwrap_off(src: &str) {
         Concrete::from_off(src).unwrap();
@@ -968,6 +1382,20 @@ mod tests {
         unwrap_off("# comment\n   foo bar")
     }
 
+    /// A skew header that doesn't match the rank should fail.
+    #[test]
+    #[should_panic(expected = "Skew(Position { row: 0, column: 0 })")]
+    fn skew_rank() {
+        unwrap_off("#SKEW 3 4\n2OFF")
+    }
+
+    /// A file with an element referring to a missing subelement should fail.
+    #[test]
+    #[should_panic(expected = "Index(Position { row: 0, column: 27 })")]
+    fn index() {
+        unwrap_off("2OFF 3 1 0 0 1 0 0 1 3 0 1 3")
+    }
+
     /// A file with some invalid token should fail.
     #[test]
     #[should_panic(expected = "Parsing(Position { row: 1, column: 3 })")]
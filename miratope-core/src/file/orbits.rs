@@ -0,0 +1,227 @@
+//! The code that exports one representative of each orbit of edges or faces
+//! of a polytope, together with its multiplicity.
+
+use std::{fmt::Display, fmt::Write, io::Error as IoError, path::Path};
+
+use crate::{
+    abs::Ranked,
+    conc::{cycle::CycleList, Concrete, ConcretePolytope},
+    geometry::Point,
+};
+
+use vec_like::*;
+
+/// The formats an orbit table can be written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrbitFormat {
+    /// Comma-separated values, with one line per vertex of each
+    /// representative.
+    Csv,
+
+    /// A JSON array, with one object per orbit.
+    Json,
+}
+
+impl OrbitFormat {
+    /// Guesses the format from the extension of a file path.
+    pub fn from_path<P: AsRef<Path>>(fp: P) -> Option<Self> {
+        match fp.as_ref().extension()?.to_str()? {
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// An error when exporting the orbits of a polytope.
+#[derive(Debug)]
+pub enum OrbitExportError {
+    /// The symmetry group of the polytope couldn't be computed.
+    Symmetry,
+
+    /// There was a problem saving the file.
+    IoError(IoError),
+}
+
+impl From<IoError> for OrbitExportError {
+    fn from(err: IoError) -> Self {
+        Self::IoError(err)
+    }
+}
+
+impl Display for OrbitExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Symmetry => write!(f, "the symmetry group could not be computed"),
+            Self::IoError(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for OrbitExportError {}
+
+/// The result of trying to export the orbits of a polytope.
+pub type OrbitExportResult<T> = Result<T, OrbitExportError>;
+
+/// A representative of an orbit of elements.
+struct OrbitRow {
+    /// The rank of the elements in the orbit.
+    rank: usize,
+
+    /// The index of the representative element.
+    example: usize,
+
+    /// The number of elements in the orbit.
+    count: usize,
+
+    /// The coordinates of the vertices of the representative. The vertices of
+    /// faces are given in cyclic order whenever possible.
+    vertices: Vec<Point<f64>>,
+}
+
+impl Concrete {
+    /// Returns the indices of the vertices of an element. Faces consisting of
+    /// a single cycle have their vertices listed in cyclic order.
+    fn orbit_vertices(&self, rank: usize, idx: usize) -> Vec<usize> {
+        if rank == 3 {
+            let mut cycles = CycleList::from_edges(
+                self[(3, idx)].subs.iter().map(|&i| &self[(2, i)].subs),
+            );
+
+            if cycles.len() == 1 {
+                return cycles.pop().unwrap().into();
+            }
+        }
+
+        self.abs.element_vertices(rank, idx).unwrap()
+    }
+
+    /// Computes one representative of each orbit of elements of the given
+    /// ranks under the full symmetry group.
+    fn orbit_rows(&mut self, ranks: &[usize]) -> OrbitExportResult<Vec<OrbitRow>> {
+        let (_, vertex_map) = self.get_symmetry_group().ok_or(OrbitExportError::Symmetry)?;
+        let mut rows = Vec::new();
+
+        for &rank in ranks {
+            if rank >= self.rank() {
+                continue;
+            }
+
+            for orbit in self.element_orbits(&vertex_map, rank) {
+                rows.push(OrbitRow {
+                    rank,
+                    example: orbit.example,
+                    count: orbit.count,
+                    vertices: self
+                        .orbit_vertices(rank, orbit.example)
+                        .into_iter()
+                        .map(|v| self.vertices[v].clone())
+                        .collect(),
+                });
+            }
+        }
+
+        Ok(rows)
+    }
+
+    /// Writes one representative of each orbit of elements of the given ranks,
+    /// along with the coordinates of its vertices and the size of its orbit.
+    pub fn orbits_to_string(
+        &mut self,
+        ranks: &[usize],
+        format: OrbitFormat,
+    ) -> OrbitExportResult<String> {
+        let rows = self.orbit_rows(ranks)?;
+        let mut out = String::new();
+
+        // Writing into a string can't fail, so we ignore the results.
+        match format {
+            OrbitFormat::Csv => {
+                let _ = write!(out, "rank,orbit,element,count,vertex");
+                for i in 0..self.dim_or() {
+                    let _ = write!(out, ",x{}", i);
+                }
+                out.push('\n');
+
+                for (orbit, row) in rows.iter().enumerate() {
+                    for (vertex, point) in row.vertices.iter().enumerate() {
+                        let _ = write!(
+                            out,
+                            "{},{},{},{},{}",
+                            row.rank, orbit, row.example, row.count, vertex
+                        );
+                        for x in point {
+                            let _ = write!(out, ",{}", x);
+                        }
+                        out.push('\n');
+                    }
+                }
+            }
+
+            OrbitFormat::Json => {
+                out.push_str("[\n");
+                for (orbit, row) in rows.iter().enumerate() {
+                    let vertices: Vec<String> = row
+                        .vertices
+                        .iter()
+                        .map(|point| {
+                            let coords: Vec<String> = point.iter().map(f64::to_string).collect();
+                            format!("[{}]", coords.join(", "))
+                        })
+                        .collect();
+
+                    let _ = write!(
+                        out,
+                        "  {{\"rank\": {}, \"element\": {}, \"count\": {}, \"vertices\": [{}]}}",
+                        row.rank,
+                        row.example,
+                        row.count,
+                        vertices.join(", ")
+                    );
+                    out.push_str(if orbit + 1 == rows.len() { "\n" } else { ",\n" });
+                }
+                out.push_str("]\n");
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Writes the edge and face orbits of a polytope in a specified file path.
+    /// The format is chosen from the extension, defaulting to CSV.
+    pub fn orbits_to_path<P: AsRef<Path>>(&mut self, fp: P) -> OrbitExportResult<()> {
+        let format = OrbitFormat::from_path(&fp).unwrap_or(OrbitFormat::Csv);
+        std::fs::write(fp, self.orbits_to_string(&[2, 3], format)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+
+    /// Checks the orbits of a cube.
+    #[test]
+    fn cube() {
+        let mut cube = Concrete::cube();
+
+        let csv = cube.orbits_to_string(&[2, 3], OrbitFormat::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "rank,orbit,element,count,vertex,x0,x1,x2");
+        assert_eq!(lines.len(), 1 + 2 + 4);
+        assert!(lines[1].starts_with("2,0,0,12,0,"));
+        assert!(lines[3].starts_with("3,1,0,6,0,"));
+
+        let json = cube.orbits_to_string(&[2, 3], OrbitFormat::Json).unwrap();
+        assert_eq!(json.matches("\"rank\"").count(), 2);
+    }
+
+    /// Checks that the format is read from the extension.
+    #[test]
+    fn format() {
+        assert_eq!(OrbitFormat::from_path("a.csv"), Some(OrbitFormat::Csv));
+        assert_eq!(OrbitFormat::from_path("a/b.json"), Some(OrbitFormat::Json));
+        assert_eq!(OrbitFormat::from_path("a.off"), None);
+    }
+}
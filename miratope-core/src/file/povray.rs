@@ -0,0 +1,241 @@
+//! Contains the code that writes a polytope into a
+//! [POV-Ray](http://www.povray.org/) scene, so that it can be rendered
+//! externally.
+
+use std::{fmt::Write, io::Result as IoResult, path::Path};
+
+use crate::{abs::Ranked, conc::Concrete, geometry::Point};
+
+use vec_like::*;
+
+/// The pose of the camera in a POV-Ray scene. Coordinates are given in a
+/// right-handed system, and get converted into the left-handed system of
+/// POV-Ray when written.
+#[derive(Clone, Copy, Debug)]
+pub struct PovCamera {
+    /// The position of the camera.
+    pub location: [f64; 3],
+
+    /// The point the camera looks at.
+    pub look_at: [f64; 3],
+
+    /// The direction that's up on the screen.
+    pub sky: [f64; 3],
+
+    /// The vertical field of view, in radians.
+    pub vertical_fov: f64,
+}
+
+impl Default for PovCamera {
+    fn default() -> Self {
+        Self {
+            location: [0.0, 0.0, 5.0],
+            look_at: [0.0; 3],
+            sky: [0.0, 1.0, 0.0],
+            vertical_fov: std::f64::consts::FRAC_PI_4,
+        }
+    }
+}
+
+/// The options for writing a POV-Ray scene.
+#[derive(Clone, Copy, Debug)]
+pub struct PovOptions {
+    /// The pose of the camera.
+    pub camera: PovCamera,
+
+    /// The ratio between the width and the height of the image.
+    pub aspect_ratio: f64,
+
+    /// The radius of the cylinders drawn for each edge. Edges aren't drawn if
+    /// this is zero.
+    pub edge_radius: f64,
+
+    /// The radius of the spheres drawn for each vertex. Vertices aren't drawn
+    /// if this is zero.
+    pub vertex_radius: f64,
+
+    /// The transparency of the faces, between 0 and 1.
+    pub face_transparency: f64,
+}
+
+impl Default for PovOptions {
+    fn default() -> Self {
+        Self {
+            camera: Default::default(),
+            aspect_ratio: 4.0 / 3.0,
+            edge_radius: 0.02,
+            vertex_radius: 0.04,
+            face_transparency: 0.0,
+        }
+    }
+}
+
+/// Writes a vector in POV-Ray syntax, flipping the z-axis to go from a
+/// right-handed into a left-handed coordinate system.
+fn pov_vector(v: [f64; 3]) -> String {
+    format!("<{}, {}, {}>", v[0], v[1], -v[2])
+}
+
+/// Returns the first three coordinates of a point, padding with zeros.
+fn coords(p: &Point<f64>) -> [f64; 3] {
+    [0, 1, 2].map(|i| p.get(i).copied().unwrap_or_default())
+}
+
+/// Returns a color for faces with a given number of edges, so that faces of
+/// the same kind get the same color.
+fn face_color(edges: usize) -> [f64; 3] {
+    // Walks around the hue circle by the golden angle.
+    let hue = (edges as f64 * 0.618_033_988_75).fract() * 6.0;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+
+    let (r, g, b) = match hue as usize {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+
+    // Pastel colors look better under lighting.
+    [r, g, b].map(|c| 0.35 + 0.65 * c)
+}
+
+impl Concrete {
+    /// Writes a POV-Ray scene of the polytope, with a camera, a light at the
+    /// camera, one colored mesh per face, and cylinders and spheres for the
+    /// edges and vertices.
+    ///
+    /// Only the first three coordinates of each vertex are used, so polytopes
+    /// in more dimensions should be projected beforehand.
+    pub fn to_pov(&self, options: &PovOptions) -> String {
+        let camera = &options.camera;
+        let mut pov = String::new();
+
+        writeln!(pov, "// Generated by Miratope.").unwrap();
+        writeln!(pov, "#version 3.7;").unwrap();
+        writeln!(pov, "global_settings {{ assumed_gamma 1.0 }}").unwrap();
+        writeln!(pov, "background {{ color rgb <1, 1, 1> }}").unwrap();
+        writeln!(pov).unwrap();
+
+        // POV-Ray takes a horizontal field of view.
+        let angle = 2.0
+            * ((camera.vertical_fov / 2.0).tan() * options.aspect_ratio)
+                .atan()
+                .to_degrees();
+
+        writeln!(pov, "camera {{").unwrap();
+        writeln!(pov, "\tlocation {}", pov_vector(camera.location)).unwrap();
+        writeln!(pov, "\tsky {}", pov_vector(camera.sky)).unwrap();
+        writeln!(pov, "\tright x * {}", options.aspect_ratio).unwrap();
+        writeln!(pov, "\tangle {}", angle).unwrap();
+        writeln!(pov, "\tlook_at {}", pov_vector(camera.look_at)).unwrap();
+        writeln!(pov, "}}").unwrap();
+        writeln!(pov).unwrap();
+
+        writeln!(
+            pov,
+            "light_source {{ {} color rgb <1, 1, 1> }}",
+            pov_vector(camera.location)
+        )
+        .unwrap();
+        writeln!(pov).unwrap();
+
+        // Each face is a separate mesh, so that it can have its own color.
+        if self.rank() >= 4 {
+            let triangulation = self.triangulation();
+            let mut triangles = vec![Vec::new(); self.el_count(3)];
+            for triangle in triangulation.iter() {
+                triangles[triangle.face].push(triangle.vertices);
+            }
+
+            for (face, triangles) in triangles.into_iter().enumerate() {
+                if triangles.is_empty() {
+                    continue;
+                }
+
+                writeln!(pov, "mesh {{").unwrap();
+                for vertices in triangles {
+                    let [a, b, c] =
+                        vertices.map(|v| pov_vector(coords(triangulation.vertex(self, v))));
+                    writeln!(pov, "\ttriangle {{ {}, {}, {} }}", a, b, c).unwrap();
+                }
+
+                let [r, g, b] = face_color(self[(3, face)].subs.len());
+                writeln!(
+                    pov,
+                    "\ttexture {{ pigment {{ color rgbt <{}, {}, {}, {}> }} finish {{ phong 0.3 }} }}",
+                    r, g, b, options.face_transparency
+                )
+                .unwrap();
+                writeln!(pov, "}}").unwrap();
+            }
+
+            writeln!(pov).unwrap();
+        }
+
+        let edge_texture = "texture { pigment { color rgb <0.1, 0.1, 0.1> } }";
+
+        if options.edge_radius > 0.0 && self.rank() >= 3 {
+            writeln!(pov, "union {{").unwrap();
+            for edge in &self[2] {
+                let v0 = coords(&self.vertices[edge.subs[0]]);
+                let v1 = coords(&self.vertices[edge.subs[1]]);
+
+                // POV-Ray refuses to draw degenerate cylinders.
+                if v0 != v1 {
+                    writeln!(
+                        pov,
+                        "\tcylinder {{ {}, {}, {} }}",
+                        pov_vector(v0),
+                        pov_vector(v1),
+                        options.edge_radius
+                    )
+                    .unwrap();
+                }
+            }
+            writeln!(pov, "\t{}", edge_texture).unwrap();
+            writeln!(pov, "}}").unwrap();
+            writeln!(pov).unwrap();
+        }
+
+        if options.vertex_radius > 0.0 && !self.vertices.is_empty() {
+            writeln!(pov, "union {{").unwrap();
+            for v in &self.vertices {
+                writeln!(
+                    pov,
+                    "\tsphere {{ {}, {} }}",
+                    pov_vector(coords(v)),
+                    options.vertex_radius
+                )
+                .unwrap();
+            }
+            writeln!(pov, "\t{}", edge_texture).unwrap();
+            writeln!(pov, "}}").unwrap();
+        }
+
+        pov
+    }
+
+    /// Writes a POV-Ray scene of the polytope in a specified file path.
+    pub fn to_pov_path<P: AsRef<Path>>(&self, fp: P, options: &PovOptions) -> IoResult<()> {
+        std::fs::write(fp, self.to_pov(options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+
+    /// Checks that every element of a cube gets drawn.
+    #[test]
+    fn cube() {
+        let pov = Concrete::cube().to_pov(&Default::default());
+
+        assert_eq!(pov.matches("mesh {").count(), 6);
+        assert_eq!(pov.matches("triangle {").count(), 12);
+        assert_eq!(pov.matches("cylinder {").count(), 12);
+        assert_eq!(pov.matches("sphere {").count(), 8);
+    }
+}
@@ -0,0 +1,530 @@
+//! Contains the code that reads a coordinate recipe, which describes a convex
+//! polytope by representatives of its vertex orbits under some symmetry, as
+//! coordinates are often given on the
+//! [Polytope Wiki](https://polytope.miraheze.org/). The polytope is built as
+//! the convex hull of the vertices, and its element counts can be checked
+//! against those the recipe expects.
+//!
+//! Empty lines and anything after a `#` are ignored. Every other line has a
+//! key and a value, separated by a colon:
+//!
+//! * `name`: the name of the polytope.
+//! * `counts`: the expected number of vertices, edges, and so on up to facets.
+//! * `group`: the symmetry used for the points that don't name their own.
+//! * `point`: a representative of a vertex orbit, given by its coordinates
+//!   separated by commas. It can be followed by a symmetry, as in
+//!   `point E: phi, 1, 1/phi, 0`.
+//!
+//! Coordinates can be arithmetic expressions, with the constants `phi` and
+//! `sqrt(x)`. The symmetries act on the coordinates, and are named by a letter,
+//! optionally followed by the number of coordinates:
+//!
+//! * `I`: only the point itself.
+//! * `A`: all permutations.
+//! * `B`: all permutations and changes of sign.
+//! * `D`: all permutations and even changes of sign.
+//! * `E`: even permutations and all changes of sign.
+//! * `S`: all changes of sign.
+//!
+//! ```txt
+//! # The rectified tesseract.
+//! name: Rectified tesseract
+//! counts: 32 96 88 24
+//! point B4: 0, 1, 1, 1
+//! ```
+
+use std::{collections::BTreeSet, fmt::Display};
+
+use crate::{
+    abs::Ranked,
+    conc::Concrete,
+    float::Float,
+    geometry::{Point, PointOrd},
+};
+
+/// Any error encountered while reading or checking a coordinate recipe.
+#[derive(Clone, Debug)]
+pub enum RecipeError {
+    /// The line with a given number doesn't have a known key.
+    Key(usize),
+
+    /// Could not parse a number on a given line.
+    Parsing(usize),
+
+    /// The symmetry on a given line doesn't exist, or acts on a different
+    /// number of coordinates than the point.
+    Group(usize),
+
+    /// The point on a given line has a different number of coordinates than
+    /// the previous ones.
+    Dimension(usize),
+
+    /// The recipe has no points.
+    NoPoints,
+
+    /// The polytope doesn't have the expected element counts.
+    Counts {
+        /// The element counts in the recipe.
+        expected: Vec<usize>,
+
+        /// The element counts of the polytope.
+        found: Vec<usize>,
+    },
+}
+
+impl Display for RecipeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Key(line) => write!(f, "unknown key on line {}", line),
+            Self::Parsing(line) => write!(f, "could not parse number on line {}", line),
+            Self::Group(line) => write!(f, "invalid symmetry on line {}", line),
+            Self::Dimension(line) => write!(f, "point on line {} has the wrong dimension", line),
+            Self::NoPoints => write!(f, "recipe has no points"),
+            Self::Counts { expected, found } => write!(
+                f,
+                "expected element counts {:?}, found {:?}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RecipeError {}
+
+/// The result of reading or checking a coordinate recipe.
+pub type RecipeResult<T> = Result<T, RecipeError>;
+
+/// Which permutations or changes of sign a symmetry applies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Changes {
+    /// Only the identity.
+    None,
+
+    /// Only the even ones.
+    Even,
+
+    /// All of them.
+    All,
+}
+
+impl Changes {
+    /// Returns whether a change of a given parity is applied.
+    fn allows(self, odd: bool) -> bool {
+        match self {
+            Self::None => false,
+            Self::Even => !odd,
+            Self::All => true,
+        }
+    }
+}
+
+/// A symmetry that acts on points by permuting and changing the signs of
+/// their coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Symmetry {
+    /// The permutations of the coordinates.
+    pub permutations: Changes,
+
+    /// The changes of sign of the coordinates.
+    pub signs: Changes,
+
+    /// The number of coordinates the symmetry acts on, if it's specified.
+    pub dim: Option<usize>,
+}
+
+impl Symmetry {
+    /// Parses a symmetry from its name, like `B` or `B4`.
+    fn parse(name: &str) -> Option<Self> {
+        let mut chars = name.chars();
+        let (permutations, signs) = match chars.next()? {
+            'I' => (Changes::None, Changes::None),
+            'A' => (Changes::All, Changes::None),
+            'B' => (Changes::All, Changes::All),
+            'D' => (Changes::All, Changes::Even),
+            'E' => (Changes::Even, Changes::All),
+            'S' => (Changes::None, Changes::All),
+            _ => return None,
+        };
+
+        let dim = chars.as_str();
+        Some(Self {
+            permutations,
+            signs,
+            dim: if dim.is_empty() {
+                None
+            } else {
+                Some(dim.parse().ok()?)
+            },
+        })
+    }
+
+    /// Returns the images of a point under the symmetry, without repeats.
+    pub fn orbit(&self, point: &Point<f64>) -> Vec<Point<f64>> {
+        let dim = point.len();
+        let mut orbit = BTreeSet::new();
+
+        for (perm, odd_perm) in permutations(dim) {
+            if !(self.permutations.allows(odd_perm)
+                || perm.iter().enumerate().all(|(i, &j)| i == j))
+            {
+                continue;
+            }
+
+            for signs in 0..1u64 << dim {
+                if signs != 0 && !self.signs.allows(signs.count_ones() % 2 == 1) {
+                    continue;
+                }
+
+                orbit.insert(PointOrd::new(Point::from_iterator(
+                    dim,
+                    perm.iter().enumerate().map(|(idx, &coord)| {
+                        if signs & (1 << idx) == 0 {
+                            point[coord]
+                        } else {
+                            -point[coord]
+                        }
+                    }),
+                )));
+            }
+        }
+
+        orbit.into_iter().map(|p| p.0).collect()
+    }
+}
+
+/// Returns every permutation of a given number of elements, together with
+/// whether it's odd.
+fn permutations(n: usize) -> Vec<(Vec<usize>, bool)> {
+    if n == 0 {
+        return vec![(Vec::new(), false)];
+    }
+
+    // Inserts the last element at every position of the smaller permutations.
+    let mut perms = Vec::new();
+    for (perm, odd) in permutations(n - 1) {
+        for pos in 0..n {
+            let mut new_perm = perm.clone();
+            new_perm.insert(pos, n - 1);
+            perms.push((new_perm, odd ^ ((n - 1 - pos) % 2 == 1)));
+        }
+    }
+    perms
+}
+
+/// A parser for arithmetic expressions with the constants `phi` and
+/// `sqrt(x)`.
+struct Expr {
+    /// The characters of the expression, without whitespace.
+    chars: Vec<char>,
+
+    /// The position of the next character to read.
+    pos: usize,
+}
+
+impl Expr {
+    /// Evaluates an expression.
+    fn eval(src: &str) -> Option<f64> {
+        let mut expr = Self {
+            chars: src.chars().filter(|c| !c.is_whitespace()).collect(),
+            pos: 0,
+        };
+
+        let value = expr.sum()?;
+        (expr.pos == expr.chars.len()).then_some(value)
+    }
+
+    /// Reads a given string if it comes next.
+    fn eat(&mut self, s: &str) -> bool {
+        let len = s.chars().count();
+        let matches = self.chars.len() >= self.pos + len
+            && self.chars[self.pos..self.pos + len]
+                .iter()
+                .copied()
+                .eq(s.chars());
+
+        if matches {
+            self.pos += len;
+        }
+        matches
+    }
+
+    /// Reads a sum or difference of products.
+    fn sum(&mut self) -> Option<f64> {
+        let mut value = self.product()?;
+        loop {
+            if self.eat("+") {
+                value += self.product()?;
+            } else if self.eat("-") {
+                value -= self.product()?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    /// Reads a product or quotient of factors.
+    fn product(&mut self) -> Option<f64> {
+        let mut value = self.factor()?;
+        loop {
+            if self.eat("*") {
+                value *= self.factor()?;
+            } else if self.eat("/") {
+                value /= self.factor()?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    /// Reads a number, a constant, or a parenthesized expression.
+    fn factor(&mut self) -> Option<f64> {
+        if self.eat("-") {
+            return self.factor().map(|x| -x);
+        }
+
+        if self.eat("phi") {
+            return Some((f64::SQRT_5 + f64::ONE) / f64::TWO);
+        }
+
+        let sqrt = self.eat("sqrt");
+        if self.eat("(") {
+            let value = self.sum()?;
+            if !self.eat(")") {
+                return None;
+            }
+            return Some(if sqrt { value.sqrt() } else { value });
+        } else if sqrt {
+            return None;
+        }
+
+        let start = self.pos;
+        while self
+            .chars
+            .get(self.pos)
+            .is_some_and(|c| c.is_ascii_digit() || *c == '.')
+        {
+            self.pos += 1;
+        }
+
+        self.chars[start..self.pos]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .ok()
+    }
+}
+
+/// A coordinate recipe. See the [module documentation](self) for the format.
+#[derive(Clone, Debug, Default)]
+pub struct Recipe {
+    /// The name of the polytope.
+    pub name: Option<String>,
+
+    /// The expected number of vertices, edges, and so on up to facets.
+    pub counts: Option<Vec<usize>>,
+
+    /// The representatives of each vertex orbit, with their symmetries.
+    pub points: Vec<(Symmetry, Point<f64>)>,
+}
+
+impl Recipe {
+    /// Reads a coordinate recipe.
+    pub fn parse(src: &str) -> RecipeResult<Self> {
+        let mut recipe = Self::default();
+        let mut group = None;
+
+        for (idx, line) in src.lines().enumerate() {
+            let line_num = idx + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line.split_once(':').ok_or(RecipeError::Key(line_num))?;
+            let value = value.trim();
+            let mut key = key.split_whitespace();
+
+            match key.next() {
+                Some("name") => recipe.name = Some(value.to_string()),
+                Some("counts") => {
+                    let counts = value
+                        .split_whitespace()
+                        .map(|count| count.parse().map_err(|_| RecipeError::Parsing(line_num)))
+                        .collect::<RecipeResult<_>>()?;
+                    recipe.counts = Some(counts);
+                }
+                Some("group") => {
+                    group = Some(Symmetry::parse(value).ok_or(RecipeError::Group(line_num))?);
+                }
+                Some("point") => {
+                    let symmetry = match key.next() {
+                        Some(name) => Symmetry::parse(name),
+                        None => group,
+                    }
+                    .ok_or(RecipeError::Group(line_num))?;
+
+                    let coords = value
+                        .split(',')
+                        .map(|coord| Expr::eval(coord).ok_or(RecipeError::Parsing(line_num)))
+                        .collect::<RecipeResult<Vec<_>>>()?;
+                    let point = Point::from_vec(coords);
+
+                    if symmetry.dim.is_some_and(|dim| dim != point.len()) {
+                        return Err(RecipeError::Group(line_num));
+                    }
+                    if recipe
+                        .points
+                        .first()
+                        .is_some_and(|(_, p)| p.len() != point.len())
+                    {
+                        return Err(RecipeError::Dimension(line_num));
+                    }
+
+                    recipe.points.push((symmetry, point));
+                }
+                _ => return Err(RecipeError::Key(line_num)),
+            }
+
+            if key.next().is_some() {
+                return Err(RecipeError::Key(line_num));
+            }
+        }
+
+        Ok(recipe)
+    }
+
+    /// Returns the vertices of the polytope, i.e. the orbits of every point.
+    pub fn vertices(&self) -> Vec<Point<f64>> {
+        self.points
+            .iter()
+            .flat_map(|(symmetry, point)| symmetry.orbit(point))
+            .collect()
+    }
+
+    /// Builds the polytope, as the convex hull of its vertices.
+    pub fn build(&self) -> RecipeResult<Concrete> {
+        Concrete::convex_hull(&self.vertices()).ok_or(RecipeError::NoPoints)
+    }
+
+    /// Checks that a polytope has the element counts of the recipe, if it
+    /// lists them.
+    pub fn check(&self, poly: &Concrete) -> RecipeResult<()> {
+        let Some(expected) = &self.counts else {
+            return Ok(());
+        };
+
+        let found: Vec<usize> = (1..poly.rank()).map(|r| poly.el_count(r)).collect();
+        if found == *expected {
+            Ok(())
+        } else {
+            Err(RecipeError::Counts {
+                expected: expected.clone(),
+                found,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Evaluates some coordinates.
+    #[test]
+    fn expressions() {
+        let phi = (5f64.sqrt() + 1.0) / 2.0;
+        for (src, value) in [
+            ("1", 1.0),
+            ("-0.5", -0.5),
+            ("1/2", 0.5),
+            ("1 + 2*3", 7.0),
+            ("(1+2)*3", 9.0),
+            ("phi", phi),
+            ("1/(2*phi)", 1.0 / (2.0 * phi)),
+            ("sqrt(2)", 2f64.sqrt()),
+            ("-sqrt(1+3)", -2.0),
+        ] {
+            assert!((Expr::eval(src).unwrap() - value).abs() < 1e-12, "{}", src);
+        }
+
+        for src in ["", "1+", "(1", "sqrt2", "phi phi", "x"] {
+            assert!(Expr::eval(src).is_none(), "{}", src);
+        }
+    }
+
+    /// Checks the sizes of the orbits of some points.
+    #[test]
+    fn orbits() {
+        let point = Point::from_vec(vec![1.0, 2.0, 3.0]);
+        for (name, size) in [
+            ("I", 1),
+            ("A", 6),
+            ("B", 48),
+            ("D", 24),
+            ("E", 24),
+            ("S", 8),
+        ] {
+            let symmetry = Symmetry::parse(name).unwrap();
+            assert_eq!(symmetry.orbit(&point).len(), size, "{}", name);
+        }
+
+        // Changes of sign of zero don't give new points.
+        let point = Point::from_vec(vec![0.0, 1.0, 1.0, 1.0]);
+        assert_eq!(Symmetry::parse("B4").unwrap().orbit(&point).len(), 32);
+    }
+
+    /// Builds some polychora and checks their element counts.
+    #[test]
+    fn polychora() {
+        for src in [
+            "name: Rectified tesseract\ncounts: 32 96 88 24\npoint B4: 0, 1, 1, 1",
+            "# The 24-cell.\ngroup: B\ncounts: 24 96 96 24\npoint: 1, 1, 0, 0",
+            "name: Hexacosichoron
+            counts: 120 720 1200 600
+            group: B4
+            point: 1/2, 1/2, 1/2, 1/2
+            point: 0, 0, 0, 1
+            point E4: phi/2, 1/2, 1/(2*phi), 0",
+        ] {
+            let recipe = Recipe::parse(src).unwrap();
+            let poly = recipe.build().unwrap();
+            recipe.check(&poly).unwrap();
+        }
+    }
+
+    /// Checks that wrong counts and recipes are caught.
+    #[test]
+    fn errors() {
+        let recipe = Recipe::parse("counts: 8 12 6 1\npoint S: 1, 1, 1").unwrap();
+        let poly = recipe.build().unwrap();
+        assert!(matches!(
+            recipe.check(&poly),
+            Err(RecipeError::Counts { .. })
+        ));
+
+        assert!(matches!(
+            Recipe::parse("points: 1"),
+            Err(RecipeError::Key(1))
+        ));
+        assert!(matches!(
+            Recipe::parse("point: 1"),
+            Err(RecipeError::Group(1))
+        ));
+        assert!(matches!(
+            Recipe::parse("point B3: 1, 1"),
+            Err(RecipeError::Group(1))
+        ));
+        assert!(matches!(
+            Recipe::parse("point A: 1, x"),
+            Err(RecipeError::Parsing(1))
+        ));
+        assert!(matches!(
+            Recipe::parse("group: B\npoint: 1, 1\npoint: 1, 1, 1"),
+            Err(RecipeError::Dimension(3))
+        ));
+        assert!(matches!(
+            Recipe::default().build(),
+            Err(RecipeError::NoPoints)
+        ));
+    }
+}
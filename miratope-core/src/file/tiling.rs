@@ -0,0 +1,316 @@
+//! Contains the code that reads and writes TOFF files, a variant of the OFF
+//! format for periodic tilings and honeycombs.
+//!
+//! A TOFF file starts with the magic word `nTOFF`, where `n` is the dimension
+//! of the tiles. Then come the number of periods and their coordinates, the
+//! number of elements of each rank up to translation, from the vertices up to
+//! the tiles, and the coordinates of the vertices. Finally, every element from
+//! the edges up is written as its number of subelements, followed by the
+//! index of each of them and the coordinates of the translation that takes it
+//! into place. Like in OFF files, anything after a `#` is a comment.
+//!
+//! For instance, this is the square tiling:
+//!
+//! ```txt
+//! 2TOFF
+//! 2
+//! 1 0
+//! 0 1
+//! 1 2 1
+//! 0 0
+//! 2  0 0 0  0 1 0
+//! 2  0 0 0  0 0 1
+//! 4  0 0 0  1 1 0  0 0 1  1 0 0
+//! ```
+
+use std::{fmt::Display, io::Error as IoError, path::Path};
+
+use crate::{
+    conc::{
+        element_types::EL_NAMES,
+        tiling::{ElementCopy, Tiling},
+    },
+    geometry::{Point, Vector},
+};
+
+/// Any error encountered while reading a TOFF file.
+#[derive(Clone, Copy, Debug)]
+pub enum TilingParseError {
+    /// Empty file.
+    Empty,
+
+    /// Didn't find the TOFF magic word.
+    MagicWord {
+        /// The line of the error.
+        line: usize,
+    },
+
+    /// The file ended unexpectedly.
+    UnexpectedEnding,
+
+    /// Could not parse a number.
+    Parsing {
+        /// The line of the error.
+        line: usize,
+    },
+
+    /// An element refers to a subelement that doesn't exist.
+    Index {
+        /// The line of the error.
+        line: usize,
+    },
+}
+
+impl Display for TilingParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "file is empty."),
+            Self::MagicWord { line } => write!(f, "no \"TOFF\" detected at line {}", line),
+            Self::UnexpectedEnding => write!(f, "file ended unexpectedly"),
+            Self::Parsing { line } => write!(f, "could not parse number at line {}", line),
+            Self::Index { line } => write!(f, "subelement index out of range at line {}", line),
+        }
+    }
+}
+
+impl std::error::Error for TilingParseError {}
+
+/// The result of reading a TOFF file.
+pub type TilingParseResult<T> = Result<T, TilingParseError>;
+
+/// Any error encountered while loading a TOFF file.
+#[derive(Debug)]
+pub enum TilingLoadError {
+    /// The file couldn't be parsed.
+    Parse(TilingParseError),
+
+    /// The file couldn't be read.
+    IoError(IoError),
+}
+
+impl Display for TilingLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(err) => write!(f, "TOFF error: {}", err),
+            Self::IoError(err) => write!(f, "IO error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for TilingLoadError {}
+
+impl From<TilingParseError> for TilingLoadError {
+    fn from(err: TilingParseError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<IoError> for TilingLoadError {
+    fn from(err: IoError) -> Self {
+        Self::IoError(err)
+    }
+}
+
+/// Reads through the tokens of a TOFF file, skipping comments.
+struct Reader<'a> {
+    /// The tokens of the file, along with the lines they're on.
+    tokens: std::vec::IntoIter<(usize, &'a str)>,
+}
+
+impl<'a> Reader<'a> {
+    /// Splits a file into its tokens.
+    fn new(src: &'a str) -> Self {
+        let tokens: Vec<_> = src
+            .lines()
+            .enumerate()
+            .flat_map(|(idx, line)| {
+                let line_src = line.split('#').next().unwrap_or_default();
+                line_src.split_whitespace().map(move |token| (idx + 1, token))
+            })
+            .collect();
+
+        Self {
+            tokens: tokens.into_iter(),
+        }
+    }
+
+    /// Reads the next token.
+    fn next(&mut self) -> TilingParseResult<(usize, &'a str)> {
+        self.tokens.next().ok_or(TilingParseError::UnexpectedEnding)
+    }
+
+    /// Parses the next token.
+    fn parse<T: std::str::FromStr>(&mut self) -> TilingParseResult<T> {
+        let (line, token) = self.next()?;
+        token.parse().map_err(|_| TilingParseError::Parsing { line })
+    }
+
+    /// Reads a list of `len` coordinates.
+    fn coordinates(&mut self, len: usize) -> TilingParseResult<Vec<f64>> {
+        (0..len).map(|_| self.parse()).collect()
+    }
+
+    /// Reads an element with subelements among the first `count` of the rank
+    /// below, in a tiling with `k` periods.
+    fn element(&mut self, count: usize, k: usize) -> TilingParseResult<Vec<ElementCopy>> {
+        let len = self.parse()?;
+        let mut subs = Vec::with_capacity(len);
+
+        for _ in 0..len {
+            let (line, token) = self.next()?;
+            let idx: usize = token.parse().map_err(|_| TilingParseError::Parsing { line })?;
+            if idx >= count {
+                return Err(TilingParseError::Index { line });
+            }
+
+            let offset = (0..k).map(|_| self.parse()).collect::<TilingParseResult<_>>()?;
+            subs.push((idx, offset));
+        }
+
+        Ok(subs)
+    }
+}
+
+impl Tiling {
+    /// Reads a tiling from a TOFF file.
+    pub fn from_toff(src: &str) -> TilingParseResult<Self> {
+        let mut reader = Reader::new(src);
+
+        let (line, magic) = reader.next().map_err(|_| TilingParseError::Empty)?;
+        let dim: usize = magic
+            .strip_suffix("TOFF")
+            .and_then(|dim| dim.parse().ok())
+            .ok_or(TilingParseError::MagicWord { line })?;
+
+        let k = reader.parse()?;
+        let periods = (0..k)
+            .map(|_| reader.coordinates(dim).map(Vector::from_vec))
+            .collect::<TilingParseResult<_>>()?;
+
+        let counts = (0..dim + 1)
+            .map(|_| reader.parse())
+            .collect::<TilingParseResult<Vec<usize>>>()?;
+
+        let vertices = (0..counts[0])
+            .map(|_| reader.coordinates(dim).map(Point::from_vec))
+            .collect::<TilingParseResult<_>>()?;
+
+        let mut ranks = Vec::with_capacity(dim);
+        for window in counts.windows(2) {
+            let elements = (0..window[1])
+                .map(|_| reader.element(window[0], k))
+                .collect::<TilingParseResult<_>>()?;
+            ranks.push(elements);
+        }
+
+        Ok(Self::new(vertices, periods, ranks))
+    }
+
+    /// Loads a tiling from a TOFF file.
+    pub fn from_path<P: AsRef<Path>>(fp: P) -> Result<Self, TilingLoadError> {
+        Ok(Self::from_toff(&std::fs::read_to_string(fp)?)?)
+    }
+
+    /// Converts a tiling into a TOFF file.
+    pub fn to_toff(&self) -> String {
+        let rank = self.rank();
+        let coordinates = |v: &[f64]| v.iter().map(f64::to_string).collect::<Vec<_>>().join(" ");
+        let mut toff = format!("{}TOFF\n\n# Periods\n{}\n", rank - 2, self.periods.len());
+        for period in &self.periods {
+            toff += &coordinates(period.as_slice());
+            toff.push('\n');
+        }
+
+        let names: Vec<_> = (1..rank).map(|r| EL_NAMES.get(r).copied().unwrap_or("Elements")).collect();
+        let counts: Vec<_> = (1..rank).map(|r| self.el_count(r).to_string()).collect();
+        toff += &format!("\n# {}\n{}\n", names.join(", "), counts.join(" "));
+
+        toff += "\n# Vertices\n";
+        for vertex in &self.vertices {
+            toff += &coordinates(vertex.as_slice());
+            toff.push('\n');
+        }
+
+        for (r, name) in names.iter().enumerate().skip(1) {
+            toff += &format!("\n# {}\n", name);
+            for idx in 0..self.el_count(r + 1) {
+                let subs = self.subs(r + 1, idx);
+                toff += &subs.len().to_string();
+                for (sub, offset) in subs {
+                    toff += &format!("  {}", sub);
+                    for x in offset {
+                        toff += &format!(" {}", x);
+                    }
+                }
+                toff.push('\n');
+            }
+        }
+
+        toff
+    }
+
+    /// Writes a tiling's TOFF file in a specified file path.
+    pub fn to_path<P: AsRef<Path>>(&self, fp: P) -> Result<(), IoError> {
+        std::fs::write(fp, self.to_toff())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conc::ConcretePolytope;
+
+    /// The square tiling, as written in the module docs.
+    const SQUARE: &str = "2TOFF
+        2
+        1 0
+        0 1
+        1 2 1
+        0 0 # The only vertex.
+        2  0 0 0  0 1 0
+        2  0 0 0  0 0 1
+        4  0 0 0  1 1 0  0 0 1  1 0 0";
+
+    /// Returns the element counts of a tiling, up to translation.
+    fn counts(tiling: &Tiling) -> Vec<usize> {
+        (1..tiling.rank()).map(|r| tiling.el_count(r)).collect()
+    }
+
+    /// Reads the square tiling.
+    #[test]
+    fn square() {
+        let square = Tiling::from_toff(SQUARE).unwrap();
+        assert_eq!(counts(&square), [1, 2, 1]);
+        assert!(square.patch(1).is_equilateral_with(1.0));
+    }
+
+    /// Writing a tiling and reading it back gives the same tiling.
+    #[test]
+    fn round_trip() {
+        let tiling = Tiling::from_toff(SQUARE).unwrap().truncate(1.0).dual();
+        let read = Tiling::from_toff(&tiling.to_toff()).unwrap();
+
+        assert_eq!(counts(&read), counts(&tiling));
+        assert_eq!(read.vertices, tiling.vertices);
+        for rank in 2..tiling.rank() {
+            for idx in 0..tiling.el_count(rank) {
+                assert_eq!(read.subs(rank, idx), tiling.subs(rank, idx));
+            }
+        }
+    }
+
+    /// Invalid files give errors rather than panicking.
+    #[test]
+    fn invalid() {
+        assert!(matches!(Tiling::from_toff("# nothing"), Err(TilingParseError::Empty)));
+        assert!(matches!(Tiling::from_toff("2OFF"), Err(TilingParseError::MagicWord { line: 1 })));
+        assert!(matches!(
+            Tiling::from_toff(&SQUARE.replace("2  0 0 0  0 1 0", "2  0 0 0  1 1 0")),
+            Err(TilingParseError::Index { line: 7 })
+        ));
+        assert!(matches!(
+            Tiling::from_toff(SQUARE.trim_end_matches(" 1 0 0")),
+            Err(TilingParseError::UnexpectedEnding)
+        ));
+    }
+}
@@ -1,19 +1,23 @@
 //! Defines a [`Float`] trait, which allows Miratope to be generic over `f32` or
-//! `f64`.
+//! `f64`, as well as over arbitrary precision floats when the `big-float`
+//! feature is enabled.
+
+use nalgebra::ComplexField;
 
 /// A trait containing the constants associated to each floating point type.
 ///
-/// This trait is only meant to be implemented for `f32` and `f64`.
+/// This trait is implemented for `f32` and `f64`, and for
+/// [`BigFloat`](crate::big_float::BigFloat) when the `big-float` feature is
+/// enabled. Since the latter isn't `Copy`, generic code has to clone its
+/// floats explicitly.
 pub trait Float:
     'static
     + nalgebra::Scalar
     + nalgebra::RealField
-    + ordered_float::Float
     + Default
     + std::fmt::Display
     + std::str::FromStr
     + serde::Serialize
-    + Copy
 {
     /// A default epsilon value for comparing values close to `1.0`. Used in
     /// general floating point operations that would return zero given infinite
@@ -61,32 +65,35 @@ pub trait Float:
 
     /// Takes the square root of a float.
     fn fsqrt(self) -> Self {
-        <Self as ordered_float::Float>::sqrt(self)
+        <Self as ComplexField>::sqrt(self)
     }
 
     /// Takes the absolute value of a float.
     fn fabs(self) -> Self {
-        <Self as ordered_float::Float>::abs(self)
+        <Self as ComplexField>::abs(self)
     }
 
     /// Takes the sine of a float.
     fn fsin(self) -> Self {
-        <Self as ordered_float::Float>::sin(self)
+        <Self as ComplexField>::sin(self)
     }
 
     /// Takes the cosine of a float.
     fn fcos(self) -> Self {
-        <Self as ordered_float::Float>::cos(self)
+        <Self as ComplexField>::cos(self)
     }
 
     /// Takes the sine and cosine of a float.
     fn fsin_cos(self) -> (Self, Self) {
-        <Self as ordered_float::Float>::sin_cos(self)
+        <Self as ComplexField>::sin_cos(self)
     }
 
     /// Makes a float from a `f64`.
     fn f64(f: f64) -> Self;
 
+    /// Rounds a float to the nearest `f64`.
+    fn to_f64(&self) -> f64;
+
     /// Makes a float from a `usize`.
     fn usize(u: usize) -> Self;
 
@@ -114,6 +121,10 @@ impl Float for f32 {
         f as Self
     }
 
+    fn to_f64(&self) -> f64 {
+        *self as f64
+    }
+
     fn usize(u: usize) -> Self {
         u as Self
     }
@@ -143,6 +154,10 @@ impl Float for f64 {
         f
     }
 
+    fn to_f64(&self) -> f64 {
+        *self
+    }
+
     fn usize(u: usize) -> Self {
         u as Self
     }
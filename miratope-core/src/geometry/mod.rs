@@ -1,5 +1,7 @@
 //! Contains structs and methods to faciliate geometry in *n*-dimensional space.
 
+pub mod predicates;
+
 /// A point in *n*-dimensional space.
 pub type Point<T> = nalgebra::DVector<T>;
 
@@ -23,6 +25,7 @@ use crate::{
 };
 
 use approx::{abs_diff_eq, abs_diff_ne};
+use predicates::Sign;
 use nalgebra::{allocator::Allocator, DefaultAllocator, Dim, Dyn, OMatrix, U1};
 use vec_like::VecLike;
 
@@ -45,13 +48,13 @@ impl<T: Float> Hypersphere<T> {
     /// Returns the radius of the hypersphere, or `NaN` if its squared radius is
     /// negative.
     pub fn radius(&self) -> T {
-        ordered_float::Float::sqrt(self.squared_radius)
+        self.squared_radius.clone().fsqrt()
     }
 
     /// Constructs a hypersphere with a given dimension and radius,
     /// centered at the origin.
     pub fn with_radius(center: Point<T>, radius: T) -> Self {
-        Self::with_squared_radius(center, radius * radius)
+        Self::with_squared_radius(center, radius.clone() * radius)
     }
 
     /// Constructs a hypersphere with a given dimension and squared radius,
@@ -81,7 +84,7 @@ impl<T: Float> Hypersphere<T> {
         }
 
         q /= s;
-        q *= self.squared_radius;
+        q *= self.squared_radius.clone();
         *p = q + &self.center;
         true
     }
@@ -181,9 +184,11 @@ impl<T: Float> Subspace<T> {
     /// # Todo:
     /// Implement the [Gram-Schmidt process](https://en.wikipedia.org/wiki/Gram%E2%80%93Schmidt_process#Numerical_stability).
     pub fn add(&mut self, p: &Point<T>) -> Option<&Point<T>> {
-        let mut v = p - self.project(p);
+        let v = p - self.project(p);
 
-        if v.normalize_mut() > T::EPS {
+        // The norm is checked before normalizing, so that we never divide by
+        // zero, which not every float type allows.
+        if let Some(v) = v.try_normalize(T::EPS) {
             self.basis.push(v);
             self.basis.last()
         } else {
@@ -327,23 +332,30 @@ pub struct Hyperplane<T: Float> {
 
     /// The normal vector of the hyperplane.
     normal: Vector<T>,
+
+    /// The dot product of the normal with any point on the hyperplane.
+    pos: T,
 }
 
 impl<T: Float> Hyperplane<T> {
     /// Generates an oriented hyperplane from its normal vector.
     pub fn new(normal: Vector<T>, pos: T) -> Self {
         let rank = normal.len();
-        let mut subspace = Subspace::new(&normal * pos);
+        let mut subspace = Subspace::new(&normal * pos.clone());
         let mut e = Vector::zeros(rank);
 
         for i in 0..rank {
             e[i] = T::ONE;
-            e += &normal * (pos - e.dot(&normal));
+            e += &normal * (pos.clone() - e.dot(&normal));
             subspace.add(&e);
             e[i] = T::ZERO;
         }
 
-        Self { subspace, normal }
+        Self {
+            subspace,
+            normal,
+            pos,
+        }
     }
 
     /// Projects a point onto the hyperplane.
@@ -367,17 +379,35 @@ impl<T: Float> Hyperplane<T> {
     pub fn is_outer(&self, p: &Point<T>) -> bool {
         abs_diff_eq!(self.distance(p), T::ZERO, epsilon = T::EPS)
     }
+}
+
+impl Hyperplane<f64> {
+    /// Returns on which side of the hyperplane a point lies, or zero if it's at
+    /// most some distance away from it. Points on the side the normal points
+    /// to are positive. See [`predicates::side`].
+    pub fn side(&self, p: &Point<f64>, eps: f64) -> Sign {
+        predicates::side(p, &self.normal, self.pos, eps)
+    }
 
     /// Returns the intersection of itself and a line segment, or `None` if it
     /// doesn't exist.
-    pub fn intersect(&self, line: Segment<'_, T>) -> Option<Point<T>> {
-        let d0 = self.distance(line.0);
-        let d1 = self.distance(line.1);
-
-        // This right here is some really sensitive code. If we screw up
-        // handling the edge cases, cross-sections through elements will crash.
-        (abs_diff_ne!(d0, d1, epsilon = T::EPS) && (d0 < -T::EPS) != (d1 < -T::EPS))
-            .then(|| line.at(d1 / (d1 - d0)))
+    ///
+    /// This right here is some really sensitive code. If we screw up handling
+    /// the edge cases, cross-sections through elements will crash. Segments
+    /// only intersect the hyperplane if one endpoint is on its negative side,
+    /// and the other isn't. When the latter is on the hyperplane, we return it
+    /// unchanged, so that all segments through it agree on the intersection.
+    pub fn intersect(&self, line: Segment<'_, f64>) -> Option<Point<f64>> {
+        match (self.side(line.0, f64::EPS), self.side(line.1, f64::EPS)) {
+            (Sign::Zero, Sign::Negative) => Some(line.0.clone()),
+            (Sign::Negative, Sign::Zero) => Some(line.1.clone()),
+            (Sign::Negative, Sign::Positive) | (Sign::Positive, Sign::Negative) => {
+                let d0 = self.distance(line.0);
+                let d1 = self.distance(line.1);
+                Some(line.at(d1 / (d1 - d0)))
+            }
+            _ => None,
+        }
     }
 }
 
@@ -388,7 +418,7 @@ impl<'a, T: Float> Segment<'a, T> {
     /// Returns the point at a certain position along the line. If `t` is
     /// between 0 and 1, the point will be contained on the line segment.
     pub fn at(&self, t: T) -> Point<T> {
-        self.0 * t + self.1 * (T::ONE - t)
+        self.0 * t.clone() + self.1 * (T::ONE - t)
     }
 }
 
@@ -0,0 +1,578 @@
+//! Robust geometric predicates, which decide on which side of a hyperplane or
+//! a hypersphere a point lies.
+//!
+//! Every predicate first evaluates a determinant or a dot product in floating
+//! point arithmetic, together with a bound on its rounding error. Only when the
+//! bound doesn't settle the answer is it evaluated again exactly, using the
+//! floating point expansions of
+//! [Shewchuk](https://people.eecs.berkeley.edu/~jrs/papers/robustr.pdf). The
+//! answer is thus always the one exact arithmetic would give on the input
+//! coordinates, no matter how they were rounded along the way.
+//!
+//! Most coordinates Miratope deals with are roundings of irrational numbers, so
+//! points that should lie on a hyperplane seldom do exactly. Many predicates
+//! therefore take a tolerance, and only return a nonzero sign for points
+//! farther away than it. This comparison is made exactly as well.
+
+use std::ops::{Mul, Neg};
+
+use super::{Matrix, Point, Vector};
+
+/// The sign of a real number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sign {
+    /// The number is negative.
+    Negative,
+
+    /// The number is zero.
+    Zero,
+
+    /// The number is positive.
+    Positive,
+}
+
+impl Sign {
+    /// Returns the sign of a float. `NaN` is taken to be zero.
+    pub fn of(x: f64) -> Self {
+        if x > 0.0 {
+            Self::Positive
+        } else if x < 0.0 {
+            Self::Negative
+        } else {
+            Self::Zero
+        }
+    }
+
+    /// Returns whether the sign is zero.
+    pub fn is_zero(self) -> bool {
+        self == Self::Zero
+    }
+}
+
+impl Neg for Sign {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        match self {
+            Self::Negative => Self::Positive,
+            Self::Zero => Self::Zero,
+            Self::Positive => Self::Negative,
+        }
+    }
+}
+
+impl Mul for Sign {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        match self {
+            Self::Negative => -other,
+            Self::Zero => Self::Zero,
+            Self::Positive => other,
+        }
+    }
+}
+
+/// Returns the sum of two floats, and the rounding error made in computing it.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let bv = x - a;
+    let av = x - bv;
+    (x, (a - av) + (b - bv))
+}
+
+/// Returns the sum of two floats, and the rounding error made in computing it,
+/// assuming that the first one is the largest in absolute value.
+fn fast_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    (x, b - (x - a))
+}
+
+/// Returns the product of two floats, and the rounding error made in computing
+/// it.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    (x, a.mul_add(b, -x))
+}
+
+/// A number represented exactly as a sum of nonzero floats of increasing
+/// magnitude, none of whose bits overlap. The last one is thus the largest,
+/// and has the sign of the whole sum. Zero is represented by a single zero.
+#[derive(Clone, Debug)]
+struct Expansion(Vec<f64>);
+
+impl Expansion {
+    /// Returns the expansion of a single float.
+    fn new(x: f64) -> Self {
+        Self(vec![x])
+    }
+
+    /// Returns the exact squared norm of a point.
+    fn norm_squared(p: &Point<f64>) -> Self {
+        let mut e = Self::new(0.0);
+        for &x in p.iter() {
+            let (hi, lo) = two_product(x, x);
+            e = e.grow(lo).grow(hi);
+        }
+
+        e
+    }
+
+    /// Returns a float that approximates the expansion.
+    fn estimate(&self) -> f64 {
+        self.0.iter().sum()
+    }
+
+    /// Returns the sign of the expansion.
+    fn sign(&self) -> Sign {
+        Sign::of(*self.0.last().unwrap())
+    }
+
+    /// Returns whether the expansion is zero.
+    fn is_zero(&self) -> bool {
+        self.sign().is_zero()
+    }
+
+    /// Appends a component to the expansion, unless it's a zero that isn't
+    /// needed.
+    fn push_nonzero(&mut self, x: f64) {
+        if x != 0.0 {
+            self.0.push(x);
+        }
+    }
+
+    /// Pushes a zero if the expansion has no components.
+    fn finish(mut self) -> Self {
+        if self.0.is_empty() {
+            self.0.push(0.0);
+        }
+
+        self
+    }
+
+    /// Returns the exact sum of the expansion and a float.
+    fn grow(&self, b: f64) -> Self {
+        let mut h = Self(Vec::with_capacity(self.0.len() + 1));
+        let mut q = b;
+        for &e in &self.0 {
+            let (sum, err) = two_sum(q, e);
+            h.push_nonzero(err);
+            q = sum;
+        }
+
+        h.push_nonzero(q);
+        h.finish()
+    }
+
+    /// Returns the exact sum of two expansions.
+    fn plus(&self, other: &Self) -> Self {
+        other.0.iter().fold(self.clone(), |e, &b| e.grow(b))
+    }
+
+    /// Returns the exact product of the expansion and a float.
+    fn scale(&self, b: f64) -> Self {
+        let mut h = Self(Vec::with_capacity(2 * self.0.len()));
+        let (mut q, err) = two_product(self.0[0], b);
+        h.push_nonzero(err);
+
+        for &e in &self.0[1..] {
+            let (product, err) = two_product(e, b);
+            let (sum, err2) = two_sum(q, err);
+            h.push_nonzero(err2);
+            let (sum, err3) = fast_two_sum(product, sum);
+            h.push_nonzero(err3);
+            q = sum;
+        }
+
+        h.push_nonzero(q);
+        h.finish()
+    }
+
+    /// Returns the exact product of two expansions.
+    fn times(&self, other: &Self) -> Self {
+        other
+            .0
+            .iter()
+            .map(|&b| self.scale(b))
+            .reduce(|e, f| e.plus(&f))
+            .unwrap()
+    }
+
+    /// Returns the negation of the expansion.
+    fn negated(&self) -> Self {
+        Self(self.0.iter().map(|x| -x).collect())
+    }
+}
+
+/// Returns whether some number exceeds a tolerance in absolute value, and if
+/// so, its sign. The number is first approximated by a float with a given
+/// bound on its error, and only computed exactly if that doesn't settle it.
+fn classify(approx: f64, bound: f64, tolerance: f64, exact: impl FnOnce() -> Expansion) -> Sign {
+    if approx - bound > tolerance {
+        Sign::Positive
+    } else if approx + bound < -tolerance {
+        Sign::Negative
+    } else if approx.abs() + bound <= tolerance {
+        Sign::Zero
+    } else {
+        let exact = exact();
+        if exact.grow(-tolerance).sign() == Sign::Positive {
+            Sign::Positive
+        } else if exact.grow(tolerance).sign() == Sign::Negative {
+            Sign::Negative
+        } else {
+            Sign::Zero
+        }
+    }
+}
+
+/// Returns the sign of the determinant of a square matrix, or zero if its
+/// absolute value is at most a given tolerance.
+///
+/// The determinant is expanded along the rows, remembering the minors on every
+/// subset of columns. This takes exponential time in the size of the matrix,
+/// but unlike elimination, it never needs to divide, which makes it exact when
+/// done on expansions.
+fn det_sign(matrix: &[Vec<Expansion>], tolerance: f64) -> Sign {
+    let n = matrix.len();
+    let full = (1usize << n) - 1;
+
+    // Whether the column of a row goes before an odd number of columns of the
+    // previous rows, which makes the term change sign.
+    let odd = |mask: usize, col: usize| (mask >> col).count_ones() % 2 == 1;
+
+    // The determinant in floating point arithmetic, and the permanent of the
+    // absolute values of the entries, which bounds every term.
+    let approx: Vec<Vec<f64>> = matrix
+        .iter()
+        .map(|row| row.iter().map(Expansion::estimate).collect())
+        .collect();
+    let mut det = vec![0.0; full + 1];
+    let mut perm = vec![0.0; full + 1];
+    det[0] = 1.0;
+    perm[0] = 1.0;
+
+    for mask in 1..=full {
+        let row = mask.count_ones() as usize - 1;
+        for col in (0..n).filter(|&col| mask & (1 << col) != 0) {
+            let prev = mask ^ (1 << col);
+            let a = approx[row][col];
+            if odd(prev, col) {
+                det[mask] -= det[prev] * a;
+            } else {
+                det[mask] += det[prev] * a;
+            }
+            perm[mask] += perm[prev] * a.abs();
+        }
+    }
+
+    // Every term goes through a product of n entries, each of them possibly
+    // rounded, and at most n sums at each of the n steps.
+    let bound = (n * n + 4 * n + 4) as f64 * f64::EPSILON * perm[full];
+
+    classify(det[full], bound, tolerance, || {
+        let mut minors = vec![None; full + 1];
+        minors[0] = Some(Expansion::new(1.0));
+
+        for mask in 1..=full {
+            let row = mask.count_ones() as usize - 1;
+            let mut minor = Expansion::new(0.0);
+            for col in (0..n).filter(|&col| mask & (1 << col) != 0) {
+                let prev = mask ^ (1 << col);
+                let entry = &matrix[row][col];
+                let sub: &Expansion = minors[prev].as_ref().unwrap();
+                if entry.is_zero() || sub.is_zero() {
+                    continue;
+                }
+
+                let term = sub.times(entry);
+                minor = minor.plus(&if odd(prev, col) { term.negated() } else { term });
+            }
+            minors[mask] = Some(minor);
+        }
+
+        minors[full].take().unwrap()
+    })
+}
+
+/// Returns the row of a point in the matrices of the predicates, which is a one
+/// followed by its coordinates, and optionally its squared norm.
+fn lift(p: &Point<f64>, norm: bool) -> Vec<Expansion> {
+    let mut row = Vec::with_capacity(p.len() + 2);
+    row.push(Expansion::new(1.0));
+    row.extend(p.iter().map(|&x| Expansion::new(x)));
+    if norm {
+        row.push(Expansion::norm_squared(p));
+    }
+
+    row
+}
+
+/// Returns the orientation of *d* + 1 points in *d*-dimensional space. This is
+/// the sign of the determinant whose rows are the differences between the last
+/// *d* points and the first. In the plane, it's positive when the points go
+/// counterclockwise. It's zero exactly when the points are affinely dependent.
+pub fn orient(points: &[&Point<f64>]) -> Sign {
+    orient_eps(points, 0.0)
+}
+
+/// Returns the orientation of *d* + 1 points in *d*-dimensional space, as in
+/// [`orient`], or zero if the last point is at most some distance away from
+/// the hyperplane through the others.
+///
+/// If the other points are affinely dependent, this is always zero.
+pub fn orient_eps(points: &[&Point<f64>], eps: f64) -> Sign {
+    let (last, base) = points.split_last().expect("no points to orient");
+    debug_assert_eq!(last.len() + 1, points.len(), "wrong number of points");
+
+    // The determinant is the distance from the last point to the hyperplane,
+    // times the volume of the parallelotope spanned by the others.
+    let tolerance = if eps == 0.0 {
+        0.0
+    } else if base.len() == 1 {
+        eps
+    } else {
+        let edges = Matrix::from_columns(
+            &base
+                .iter()
+                .skip(1)
+                .map(|&p| p - base[0])
+                .collect::<Vec<Vector<f64>>>(),
+        );
+        eps * (edges.transpose() * &edges).determinant().max(0.0).sqrt()
+    };
+
+    let matrix: Vec<_> = points.iter().map(|p| lift(p, false)).collect();
+    det_sign(&matrix, tolerance)
+}
+
+/// Returns whether *d* + 2 points in *d*-dimensional space have the last one
+/// inside the hypersphere through the others. This is positive when it's
+/// inside, negative when it's outside, and zero when it's on it, no matter
+/// the orientation of the others. It's also zero when the others are affinely
+/// dependent, so that there's no such hypersphere.
+pub fn in_sphere(points: &[&Point<f64>]) -> Sign {
+    let (last, simplex) = points.split_last().expect("no points to test");
+    debug_assert_eq!(last.len() + 2, points.len(), "wrong number of points");
+
+    // The determinant grows with the squared distance from the center, times
+    // the orientation of the simplex.
+    let matrix: Vec<_> = points.iter().map(|p| lift(p, true)).collect();
+    -(det_sign(&matrix, 0.0) * orient(simplex))
+}
+
+/// Returns on which side of the hyperplane of points *x* with *x* · `normal` =
+/// `pos` a point lies, or zero if its distance to it is at most some value.
+/// The normal is assumed to be a unit vector.
+pub fn side(p: &Point<f64>, normal: &Vector<f64>, pos: f64, eps: f64) -> Sign {
+    let mut approx = -pos;
+    let mut magnitude = pos.abs();
+    for (x, y) in p.iter().zip(normal.iter()) {
+        approx += x * y;
+        magnitude += (x * y).abs();
+    }
+
+    let bound = (2 * p.len() + 2) as f64 * f64::EPSILON * magnitude;
+    classify(approx, bound, eps, || {
+        let mut dot = Expansion::new(-pos);
+        for (&x, &y) in p.iter().zip(normal.iter()) {
+            let (hi, lo) = two_product(x, y);
+            dot = dot.grow(lo).grow(hi);
+        }
+
+        dot
+    })
+}
+
+/// Returns the coordinates along which some points vary the most, as many as
+/// the rank of the flat through them. These are found by Gaussian elimination
+/// with complete pivoting.
+fn pivot_coordinates(base: &[&Point<f64>]) -> Vec<usize> {
+    let dim = base[0].len();
+    let mut rows: Vec<Vector<f64>> = base[1..].iter().map(|&p| p - base[0]).collect();
+    let mut coords = Vec::with_capacity(rows.len());
+
+    for i in 0..rows.len() {
+        let (r, c) = (i..rows.len())
+            .flat_map(|r| (0..dim).map(move |c| (r, c)))
+            .max_by(|&(r1, c1), &(r2, c2)| rows[r1][c1].abs().total_cmp(&rows[r2][c2].abs()))
+            .unwrap();
+        rows.swap(i, r);
+        coords.push(c);
+
+        let pivot = rows[i].clone();
+        for row in &mut rows[i + 1..] {
+            let factor = row[c] / pivot[c];
+            *row -= &pivot * factor;
+        }
+    }
+
+    coords
+}
+
+/// Returns whether a point lies on the flat through some affinely independent
+/// points, up to roughly some distance. For three points, this tests whether
+/// the four points are coplanar.
+///
+/// The flat is the graph of an affine map on the coordinates along which the
+/// points vary the most. We test whether the point lies on it by projecting
+/// onto these together with every other coordinate, and using [`orient_eps`].
+pub fn coplanar(base: &[&Point<f64>], p: &Point<f64>, eps: f64) -> bool {
+    let coords = pivot_coordinates(base);
+    let restrict = |q: &Point<f64>, j: usize| {
+        Point::from_iterator(
+            coords.len() + 1,
+            coords.iter().chain(std::iter::once(&j)).map(|&c| q[c]),
+        )
+    };
+
+    (0..p.len()).filter(|j| !coords.contains(j)).all(|j| {
+        let points: Vec<Point<f64>> = base
+            .iter()
+            .map(|&q| restrict(q, j))
+            .chain(std::iter::once(restrict(p, j)))
+            .collect();
+        orient_eps(&points.iter().collect::<Vec<_>>(), eps).is_zero()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::float::Float;
+
+    use nalgebra::dvector;
+
+    /// Orients triangles in the plane.
+    #[test]
+    fn orient_triangles() {
+        let a = dvector![0.0, 0.0];
+        let b = dvector![1.0, 0.0];
+        let c = dvector![0.0, 1.0];
+        assert_eq!(orient(&[&a, &b, &c]), Sign::Positive);
+        assert_eq!(orient(&[&a, &c, &b]), Sign::Negative);
+        assert_eq!(orient(&[&a, &b, &dvector![3.0, 0.0]]), Sign::Zero);
+    }
+
+    /// Orients points very close to a line, where floating point arithmetic
+    /// gets the answer wrong.
+    #[test]
+    fn orient_near_line() {
+        let a = dvector![12.0, 12.0];
+        let b = dvector![24.0, 24.0];
+        let ulp = f64::EPSILON / 2.0;
+
+        for i in 0..32 {
+            for j in 0..32 {
+                let p = dvector![0.5 + i as f64 * ulp, 0.5 + j as f64 * ulp];
+                assert_eq!(orient(&[&a, &b, &p]), Sign::of((j - i) as f64));
+            }
+        }
+    }
+
+    /// Orients points in space against a tolerance.
+    #[test]
+    fn orient_tolerance() {
+        let a = dvector![0.0, 0.0, 0.0];
+        let b = dvector![10.0, 0.0, 0.0];
+        let c = dvector![0.0, 10.0, 0.0];
+        let near = dvector![3.0, 3.0, f64::EPS / 2.0];
+        let far = dvector![3.0, 3.0, 2.0 * f64::EPS];
+
+        assert_eq!(orient(&[&a, &b, &c, &near]), Sign::Positive);
+        assert_eq!(orient_eps(&[&a, &b, &c, &near], f64::EPS), Sign::Zero);
+        assert_eq!(orient_eps(&[&a, &b, &c, &far], f64::EPS), Sign::Positive);
+        assert_eq!(orient_eps(&[&b, &a, &c, &far], f64::EPS), Sign::Negative);
+    }
+
+    /// Tests points against circles and spheres.
+    #[test]
+    fn in_spheres() {
+        let a = dvector![1.0, 0.0];
+        let b = dvector![0.0, 1.0];
+        let c = dvector![-1.0, 0.0];
+        for (p, sign) in [
+            (dvector![0.0, 0.0], Sign::Positive),
+            (dvector![2.0, 0.0], Sign::Negative),
+            (dvector![0.0, -1.0], Sign::Zero),
+        ] {
+            assert_eq!(in_sphere(&[&a, &b, &c, &p]), sign);
+            assert_eq!(in_sphere(&[&c, &b, &a, &p]), sign);
+        }
+
+        let simplex = [
+            dvector![1.0, 0.0, 0.0],
+            dvector![0.0, 1.0, 0.0],
+            dvector![0.0, 0.0, 1.0],
+            dvector![-1.0, 0.0, 0.0],
+        ];
+        let test = |p: Point<f64>| {
+            let mut points: Vec<_> = simplex.iter().collect();
+            points.push(&p);
+            in_sphere(&points)
+        };
+        assert_eq!(test(dvector![0.1, 0.2, 0.3]), Sign::Positive);
+        assert_eq!(test(dvector![0.0, -1.0, 0.0]), Sign::Zero);
+        assert_eq!(test(dvector![0.0, 0.0, -1.5]), Sign::Negative);
+    }
+
+    /// Tests points against a hyperplane.
+    #[test]
+    fn sides() {
+        let normal = dvector![0.0, 0.0, 1.0];
+        assert_eq!(
+            side(&dvector![5.0, 3.0, 4.0], &normal, 4.0, 0.0),
+            Sign::Zero
+        );
+        assert_eq!(
+            side(&dvector![5.0, 3.0, 4.1], &normal, 4.0, 0.0),
+            Sign::Positive
+        );
+        assert_eq!(
+            side(&dvector![5.0, 3.0, 3.9], &normal, 4.0, 0.0),
+            Sign::Negative
+        );
+        assert_eq!(
+            side(&dvector![5.0, 3.0, 4.1], &normal, 4.0, 0.2),
+            Sign::Zero
+        );
+
+        // The rounded normal doesn't go exactly through the point.
+        let normal = dvector![0.0, 0.6, 0.8];
+        assert_eq!(
+            side(&dvector![5.0, 3.0, 4.0], &normal, 5.0, f64::EPS),
+            Sign::Zero
+        );
+    }
+
+    /// Tests points against planes in three and four dimensions.
+    #[test]
+    fn coplanarity() {
+        let a = dvector![1.0, 0.0, 0.0];
+        let b = dvector![0.0, 1.0, 0.0];
+        let c = dvector![0.0, 0.0, 1.0];
+        let third = 1.0 / 3.0;
+        assert!(coplanar(
+            &[&a, &b, &c],
+            &dvector![third, third, third],
+            f64::EPS
+        ));
+        assert!(!coplanar(&[&a, &b, &c], &dvector![0.0, 0.0, 0.0], f64::EPS));
+
+        let a = dvector![1.0, 2.0, 0.0, 0.0];
+        let b = dvector![3.0, 2.0, 1.0, 1.0];
+        let c = dvector![1.0, 5.0, 0.0, 0.0];
+        assert!(coplanar(&[&a, &b, &c], &dvector![5.0, 7.0, 2.0, 2.0], 0.0));
+        assert!(!coplanar(
+            &[&a, &b, &c],
+            &dvector![5.0, 7.0, 2.0, 2.5],
+            f64::EPS
+        ));
+        assert!(coplanar(&[&a, &b], &dvector![7.0, 2.0, 3.0, 3.0], 0.0));
+        assert!(!coplanar(
+            &[&a, &b],
+            &dvector![7.0, 3.0, 3.0, 3.0],
+            f64::EPS
+        ));
+    }
+}
@@ -0,0 +1,346 @@
+//! Names finite groups of orthogonal matrices, such as the symmetry groups of
+//! polytopes.
+//!
+//! Every such group contains a subgroup generated by the reflections in it,
+//! which is a product of irreducible Coxeter groups. These are told apart by
+//! their rank, their number of reflections, and whether their mirrors only
+//! meet at angles of 90° and 60°. The group is then named after its reflection
+//! subgroup whenever it doesn't have any other elements.
+
+use std::fmt::Display;
+
+use crate::{
+    float::Float,
+    geometry::{Matrix, Vector},
+};
+
+use super::Group;
+
+/// An irreducible finite Coxeter group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CoxeterType {
+    /// The symmetry group of the simplex of a given rank.
+    A(usize),
+
+    /// The symmetry group of the hypercube of a given rank.
+    B(usize),
+
+    /// The symmetry group of the demihypercube of a given rank.
+    D(usize),
+
+    /// One of the exceptional groups E6, E7 and E8.
+    E(usize),
+
+    /// The symmetry group of the 24-cell.
+    F4,
+
+    /// The symmetry group of the dodecahedron or of the 120-cell.
+    H(usize),
+
+    /// The symmetry group of a polygon with a given number of sides.
+    I2(usize),
+}
+
+impl CoxeterType {
+    /// Identifies an irreducible Coxeter group from its rank, its number of
+    /// reflections, and whether it's simply-laced, i.e. whether its mirrors
+    /// only meet at angles of 90° and 60°. Returns `None` if there's no such
+    /// group.
+    pub fn from_reflections(rank: usize, count: usize, simply_laced: bool) -> Option<Self> {
+        use CoxeterType::*;
+
+        Some(match (rank, count, simply_laced) {
+            (1, 1, _) => A(1),
+            (2, 3, _) => A(2),
+            (2, 4, _) => B(2),
+            (2, m, _) => I2(m),
+            (3, 15, false) => H(3),
+            (4, 24, false) => F4,
+            (4, 60, false) => H(4),
+            (6, 36, true) => E(6),
+            (7, 63, true) => E(7),
+            (8, 120, true) => E(8),
+            (n, count, true) if count == n * (n + 1) / 2 => A(n),
+            (n, count, true) if count == n * (n - 1) => D(n),
+            (n, count, false) if count == n * n => B(n),
+            _ => return None,
+        })
+    }
+
+    /// Returns the rank of the group, i.e. its number of generators.
+    pub fn rank(self) -> usize {
+        match self {
+            Self::A(n) | Self::B(n) | Self::D(n) | Self::E(n) | Self::H(n) => n,
+            Self::F4 => 4,
+            Self::I2(_) => 2,
+        }
+    }
+
+    /// Returns the number of elements of the group.
+    pub fn order(self) -> usize {
+        let factorial = |n: usize| (1..=n).product::<usize>();
+
+        match self {
+            Self::A(n) => factorial(n + 1),
+            Self::B(n) => (1 << n) * factorial(n),
+            Self::D(n) => (1 << (n - 1)) * factorial(n),
+            Self::E(6) => 51_840,
+            Self::E(7) => 2_903_040,
+            Self::E(_) => 696_729_600,
+            Self::F4 => 1152,
+            Self::H(3) => 120,
+            Self::H(_) => 14_400,
+            Self::I2(m) => 2 * m,
+        }
+    }
+}
+
+impl Display for CoxeterType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::A(n) => write!(f, "A{}", n),
+            Self::B(n) => write!(f, "B{}", n),
+            Self::D(n) => write!(f, "D{}", n),
+            Self::E(n) => write!(f, "E{}", n),
+            Self::F4 => write!(f, "F4"),
+            Self::H(n) => write!(f, "H{}", n),
+            Self::I2(6) => write!(f, "G2"),
+            Self::I2(m) => write!(f, "I2({})", m),
+        }
+    }
+}
+
+/// Writes a product of Coxeter groups, like `A2×A1`.
+fn write_product(f: &mut std::fmt::Formatter<'_>, factors: &[CoxeterType]) -> std::fmt::Result {
+    for (idx, factor) in factors.iter().enumerate() {
+        if idx != 0 {
+            write!(f, "×")?;
+        }
+        write!(f, "{}", factor)?;
+    }
+
+    Ok(())
+}
+
+/// The classification of a finite group of orthogonal matrices.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GroupClass {
+    /// The group is generated by reflections, and is the product of some
+    /// irreducible Coxeter groups. The trivial group is the empty product.
+    Coxeter(Vec<CoxeterType>),
+
+    /// The group is the rotation subgroup of the product of some irreducible
+    /// Coxeter groups, which contains the central inversion.
+    Rotations(Vec<CoxeterType>),
+
+    /// The group is cyclic with a given order, and isn't generated by
+    /// reflections.
+    Cyclic(usize),
+
+    /// Any other group.
+    Other {
+        /// The number of elements of the group.
+        order: usize,
+
+        /// The irreducible Coxeter groups whose product is the subgroup
+        /// generated by the reflections in the group.
+        reflections: Vec<CoxeterType>,
+    },
+}
+
+impl Display for GroupClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Coxeter(factors) if factors.is_empty() => write!(f, "trivial"),
+            Self::Coxeter(factors) => write_product(f, factors),
+            Self::Rotations(factors) if factors.len() == 1 => write!(f, "{}+", factors[0]),
+            Self::Rotations(factors) => {
+                write!(f, "(")?;
+                write_product(f, factors)?;
+                write!(f, ")+")
+            }
+            Self::Cyclic(n) => write!(f, "cyclic C{}", n),
+            Self::Other { order, reflections } if reflections.is_empty() => {
+                write!(f, "order {}", order)
+            }
+            Self::Other { order, reflections } => {
+                write!(f, "order {}, with reflections ", order)?;
+                write_product(f, reflections)
+            }
+        }
+    }
+}
+
+/// Returns the unit normals of the mirrors of the reflections in a list of
+/// orthogonal matrices, up to sign.
+fn mirror_normals(elements: &[Matrix<f64>]) -> Vec<Vector<f64>> {
+    let dim = elements.first().map_or(0, Matrix::nrows);
+
+    elements
+        .iter()
+        .filter(|m| (m.trace() - (dim as f64 - 2.0)).abs() < f64::EPS && m.determinant() < 0.0)
+        .filter_map(|m| {
+            // A reflection is I - 2nnᵀ, so any nonzero column of I - m points
+            // along the normal.
+            let diff = Matrix::identity(dim, dim) - m;
+            let col = diff.column_iter().max_by(|c, d| c.norm().total_cmp(&d.norm()))?;
+            Some(col / col.norm())
+        })
+        .collect()
+}
+
+/// Splits a reflection group, given by the normals of its mirrors, into its
+/// irreducible components. Two mirrors are in the same component if they're
+/// joined by a chain of mirrors that aren't perpendicular.
+fn components(normals: &[Vector<f64>]) -> Option<Vec<CoxeterType>> {
+    let n = normals.len();
+    let dot = |i: usize, j: usize| normals[i].dot(&normals[j]);
+    let mut component = vec![None; n];
+    let mut types = Vec::new();
+
+    for start in 0..n {
+        if component[start].is_some() {
+            continue;
+        }
+
+        let idx = types.len();
+        component[start] = Some(idx);
+        let mut stack = vec![start];
+        let mut members = Vec::new();
+
+        while let Some(i) = stack.pop() {
+            members.push(i);
+            for (j, c) in component.iter_mut().enumerate() {
+                if c.is_none() && dot(i, j).abs() > f64::EPS {
+                    *c = Some(idx);
+                    stack.push(j);
+                }
+            }
+        }
+
+        // The mirrors meet at 90° or 60° exactly when the squared cosines of
+        // their angles are 0 or 1/4.
+        let simply_laced = members.iter().all(|&i| {
+            members.iter().all(|&j| {
+                let cos_sq = dot(i, j).powi(2);
+                i == j || cos_sq.abs() < f64::EPS || (cos_sq - 0.25).abs() < f64::EPS
+            })
+        });
+
+        let span = Matrix::from_columns(&members.iter().map(|&i| normals[i].clone()).collect::<Vec<_>>());
+        let rank = span.rank(f64::EPS);
+        types.push(CoxeterType::from_reflections(rank, members.len(), simply_laced)?);
+    }
+
+    types.sort_unstable_by(|a, b| b.rank().cmp(&a.rank()).then(a.cmp(b)));
+    Some(types)
+}
+
+/// Returns the order of an orthogonal matrix, as long as it's at most some
+/// value.
+fn matrix_order(m: &Matrix<f64>, max: usize) -> Option<usize> {
+    let dim = m.nrows();
+    let id = Matrix::identity(dim, dim);
+    let mut power = m.clone();
+
+    for order in 1..=max {
+        if (&power - &id).norm() < f64::EPS {
+            return Some(order);
+        }
+        power *= m;
+    }
+
+    None
+}
+
+/// Classifies a list of orthogonal matrices forming a group.
+fn classify_elements(elements: &[Matrix<f64>]) -> GroupClass {
+    let order = elements.len();
+    let dim = elements.first().map_or(0, Matrix::nrows);
+    let reflections = match components(&mirror_normals(elements)) {
+        Some(reflections) => reflections,
+        None => {
+            return GroupClass::Other {
+                order,
+                reflections: Vec::new(),
+            }
+        }
+    };
+
+    let reflection_order: usize = reflections.iter().map(|t| t.order()).product();
+    if reflection_order == order {
+        return GroupClass::Coxeter(reflections);
+    }
+
+    if reflections.is_empty() && elements.iter().any(|m| matrix_order(m, order) == Some(order)) {
+        return GroupClass::Cyclic(order);
+    }
+
+    // In odd dimensions, a group of rotations without the central inversion
+    // is the rotation subgroup of the group we get by adding it. This might
+    // be a reflection group.
+    let id = Matrix::identity(dim, dim);
+    let is_central_inv = |m: &Matrix<f64>| (m + &id).norm() < f64::EPS;
+    if dim % 2 == 1
+        && elements.iter().all(|m| m.determinant() > 0.0)
+        && !elements.iter().any(is_central_inv)
+    {
+        let extended: Vec<Matrix<f64>> = elements.iter().flat_map(|m| [m.clone(), -m]).collect();
+        if let GroupClass::Coxeter(factors) = classify_elements(&extended) {
+            return GroupClass::Rotations(factors);
+        }
+    }
+
+    GroupClass::Other { order, reflections }
+}
+
+impl<I: Iterator<Item = Matrix<f64>>> Group<I> {
+    /// Classifies the group, assuming it consists of orthogonal matrices. See
+    /// [`GroupClass`].
+    pub fn classify(self) -> GroupClass {
+        classify_elements(&self.collect::<Vec<_>>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use CoxeterType::*;
+
+    /// Classifies the groups of a few polytopes.
+    #[test]
+    fn coxeter() {
+        assert_eq!(Group::simplex(3).classify(), GroupClass::Coxeter(vec![A(3)]));
+        assert_eq!(Group::hypercube(4).classify(), GroupClass::Coxeter(vec![B(4)]));
+        assert_eq!(Group::pentagonal(3).classify(), GroupClass::Coxeter(vec![H(3)]));
+        assert_eq!(Group::demihypercube(4).classify(), GroupClass::Coxeter(vec![D(4)]));
+        assert_eq!(
+            Group::parse("o3o4o3o").unwrap().unwrap().classify(),
+            GroupClass::Coxeter(vec![F4])
+        );
+        assert_eq!(Group::trivial(3).classify().to_string(), "trivial");
+    }
+
+    /// Classifies some products of groups.
+    #[test]
+    fn products() {
+        let class = Group::parse("o3o o").unwrap().unwrap().classify();
+        assert_eq!(class, GroupClass::Coxeter(vec![A(2), A(1)]));
+        assert_eq!(class.to_string(), "A2×A1");
+
+        let class = Group::dihedral_2(5).prism().classify();
+        assert_eq!(class.to_string(), "I2(5)×A1");
+    }
+
+    /// Classifies some groups without reflections.
+    #[test]
+    fn chiral() {
+        assert_eq!(Group::cyclic(5).classify().to_string(), "cyclic C5");
+        assert_eq!(
+            Group::hypercube(3).rotations().classify(),
+            GroupClass::Rotations(vec![B(3)])
+        );
+        assert_eq!(Group::pentagonal(3).rotations().classify().to_string(), "H3+");
+        assert_eq!(Group::simplex(3).rotations().classify().to_string(), "order 12");
+    }
+}
@@ -0,0 +1,91 @@
+//! Contains the code that streams the elements of a finite Coxeter group.
+
+use crate::geometry::{Matrix, Point};
+
+use super::{kd_tree::KdTree, layer_iter::LayerIter, weyl_iter::WeylIter, Group};
+
+/// An iterator over the elements of a finite group
+/// [generated](https://en.wikipedia.org/wiki/Generator_(mathematics)) by
+/// reflections, such as a [Coxeter group](https://en.wikipedia.org/wiki/Coxeter_group).
+///
+/// The elements are built in layers by a [`LayerIter`]. They're told apart by
+/// where they send a generic point, i.e. a point in no mirror, which is much
+/// cheaper than comparing whole matrices. These images are stored in
+/// [`KdTree`]s. Crystallographic groups are instead generated exactly by a
+/// [`WeylIter`].
+#[derive(Clone)]
+pub struct CoxIter {
+    /// The number of dimensions the group acts on.
+    dim: usize,
+
+    /// A point that isn't fixed by any element of the group besides the
+    /// identity.
+    point: Point<f64>,
+
+    /// The elements of the group, built in layers.
+    layers: LayerIter<Matrix<f64>, KdTree>,
+
+    /// The iterator that generates the group exactly, if it's
+    /// crystallographic.
+    exact: Option<WeylIter>,
+}
+
+impl CoxIter {
+    /// Initializes a new iterator from a set of reflections, and a point that
+    /// doesn't lie on any mirror of the group they generate.
+    ///
+    /// # Safety
+    /// The generators must be reflections generating a finite group, and the
+    /// point must be generic. Otherwise, the iterator might either not stop or
+    /// skip elements.
+    pub unsafe fn new(dim: usize, gens: Vec<Matrix<f64>>, point: Point<f64>) -> Self {
+        Self {
+            dim,
+            point,
+            layers: LayerIter::new(gens, Matrix::identity(dim, dim)),
+            exact: None,
+        }
+    }
+
+    /// Initializes a new iterator from a set of reflections, and an iterator
+    /// over the Weyl group they generate, which is used to build the elements
+    /// instead.
+    pub fn from_weyl(gens: Vec<Matrix<f64>>, weyl: WeylIter) -> Self {
+        let dim = weyl.dim();
+
+        // Safety: the point is never used.
+        let mut iter = unsafe { Self::new(dim, gens, Point::zeros(dim)) };
+        iter.exact = Some(weyl);
+        iter
+    }
+
+    /// Returns the number of dimensions the group acts on.
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    /// Returns the reflections that generate the group.
+    pub fn gens(&self) -> &[Matrix<f64>] {
+        self.layers.gens()
+    }
+}
+
+impl Iterator for CoxIter {
+    type Item = Matrix<f64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(exact) = &mut self.exact {
+            return exact.next();
+        }
+
+        let point = &self.point;
+        self.layers.next_with(|el| el * point)
+    }
+}
+
+impl From<CoxIter> for Group<CoxIter> {
+    fn from(iter: CoxIter) -> Self {
+        // Safety: the elements of a CoxIter always form a group.
+        unsafe { Self::new(iter.dim, iter) }
+    }
+}
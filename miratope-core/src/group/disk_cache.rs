@@ -0,0 +1,329 @@
+//! Contains the code that stores the elements of Coxeter groups on disk, so
+//! that big groups like E7 or E8 only ever need to be generated once.
+//!
+//! Each group is stored in its own file, named after an
+//! [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) hash of its Coxeter
+//! matrix, which unlike the standard library's hashers is stable across
+//! releases. A file consists of the magic bytes `MGRP`, a version byte, and a
+//! [zstd](https://facebook.github.io/zstd/)-compressed payload. The payload
+//! stores the dimension of the group and its Coxeter matrix, which guards
+//! against hash collisions, followed by the entries of every element as
+//! little-endian `f64`s, in column-major order.
+
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter, Read, Result as IoResult, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    cox::{Cox, CoxError, CoxResult, CoxType},
+    geometry::Matrix,
+};
+
+use super::{cox_iter::CoxIter, Group};
+
+#[cfg(feature = "bevy")]
+use bevy::prelude::Resource;
+
+/// The magic bytes at the start of every group file.
+const MAGIC: &[u8; 4] = b"MGRP";
+
+/// The version of the format we write.
+const VERSION: u8 = 1;
+
+/// The zstd compression level. Group elements repeat the same few entries over
+/// and over, so even fast levels compress them well.
+const LEVEL: i32 = 3;
+
+/// The offset basis of the 64-bit FNV-1a hash.
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// The prime of the 64-bit FNV-1a hash.
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Hashes some bytes with the 64-bit FNV-1a hash.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(hash, |hash, &b| (hash ^ u64::from(b)).wrapping_mul(FNV_PRIME))
+}
+
+/// A directory where the elements of Coxeter groups are stored.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "bevy", derive(Resource))]
+pub struct GroupCache {
+    /// The directory the files are stored in.
+    dir: PathBuf,
+}
+
+/// Reads a little-endian `f64`. Returns `None` at the end of the file.
+fn read_f64(reader: &mut impl Read) -> Option<f64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes).ok()?;
+    Some(f64::from_le_bytes(bytes))
+}
+
+/// Reads a matrix of a given size, in column-major order.
+fn read_matrix(reader: &mut impl Read, dim: usize) -> Option<Matrix<f64>> {
+    let entries = (0..dim * dim)
+        .map(|_| read_f64(reader))
+        .collect::<Option<Vec<_>>>()?;
+    Some(Matrix::from_vec(dim, dim, entries))
+}
+
+/// Writes a matrix in column-major order.
+fn write_matrix(writer: &mut impl Write, matrix: &Matrix<f64>) -> IoResult<()> {
+    for x in matrix.iter() {
+        writer.write_all(&x.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+impl GroupCache {
+    /// Initializes a cache in a given directory. The directory is created
+    /// once the first group is stored.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the directory of the cache.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the path of the file for a Coxeter group.
+    pub fn path(&self, cox: &Cox<f64>) -> PathBuf {
+        let mut hash = fnv1a(FNV_OFFSET, &(cox.dim() as u64).to_le_bytes());
+        for x in cox.matrix().iter() {
+            hash = fnv1a(hash, &x.to_le_bytes());
+        }
+
+        self.dir.join(format!("{:016x}.mgrp", hash))
+    }
+
+    /// Opens the file for a Coxeter group, if it exists and was written for
+    /// that same group. Returns a reader positioned at its first element.
+    fn open(&self, cox: &Cox<f64>) -> Option<zstd::stream::read::Decoder<'static, BufReader<File>>> {
+        let mut file = File::open(self.path(cox)).ok()?;
+        let mut header = [0; 5];
+        file.read_exact(&mut header).ok()?;
+        if &header[..4] != MAGIC || header[4] != VERSION {
+            return None;
+        }
+
+        let mut reader = zstd::stream::read::Decoder::new(file).ok()?;
+        let mut dim = [0; 8];
+        reader.read_exact(&mut dim).ok()?;
+        let dim = u64::from_le_bytes(dim) as usize;
+
+        (dim == cox.dim() && read_matrix(&mut reader, dim)? == *cox.matrix()).then_some(reader)
+    }
+
+    /// Returns the Coxeter group of a given Coxeter matrix, or `None` if it's
+    /// not finite. Its elements are read from the cache if they're there.
+    /// Otherwise, they're generated, and written to the cache as they are.
+    pub fn group(&self, cox: &Cox<f64>) -> Option<Group<CachedCoxIter>> {
+        let dim = cox.dim();
+        let iter = match self.open(cox) {
+            Some(reader) => CachedCoxIter::Read { dim, reader },
+            None => {
+                let iter = Box::new(cox.cox_iter()?);
+                CachedCoxIter::Write {
+                    writer: self.create(cox).ok(),
+                    iter,
+                }
+            }
+        };
+
+        // Safety: both variants return the elements of the Coxeter group.
+        Some(unsafe { Group::new(dim, iter) })
+    }
+
+    /// Returns the Coxeter group of a given Coxeter matrix, or an error
+    /// explaining why it's infinite. See [`Cox::try_group`].
+    pub fn try_group(&self, cox: &Cox<f64>) -> CoxResult<Group<CachedCoxIter>> {
+        match cox.cox_type() {
+            CoxType::Spherical => self.group(cox).ok_or(CoxError::Affine),
+            CoxType::Affine => Err(CoxError::Affine),
+            CoxType::Hyperbolic => Err(CoxError::Hyperbolic),
+        }
+    }
+
+    /// Parses a diagram and returns its Coxeter group through the cache, or an
+    /// error explaining why the diagram doesn't describe a finite group.
+    pub fn try_parse(&self, input: &str) -> CoxResult<Group<CachedCoxIter>> {
+        self.try_group(&Cox::parse(input)?)
+    }
+
+    /// Creates a temporary file for a Coxeter group, and writes its header.
+    fn create(&self, cox: &Cox<f64>) -> IoResult<CacheWriter> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(cox);
+        let tmp_path = path.with_extension("tmp");
+
+        let mut file = BufWriter::new(File::create(&tmp_path)?);
+        file.write_all(MAGIC)?;
+        file.write_all(&[VERSION])?;
+
+        let mut encoder = zstd::stream::write::Encoder::new(file, LEVEL)?;
+        encoder.write_all(&(cox.dim() as u64).to_le_bytes())?;
+        write_matrix(&mut encoder, cox.matrix())?;
+
+        Ok(CacheWriter {
+            encoder: Some(encoder),
+            tmp_path,
+            path,
+        })
+    }
+
+    /// Removes the files of every group from the cache.
+    pub fn clear(&self) -> IoResult<()> {
+        if !self.dir.exists() {
+            return Ok(());
+        }
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "mgrp" || ext == "tmp") {
+                fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the elements of a group into a temporary file, which only takes the
+/// place of the actual cache file once every element has been written. This
+/// way, a group that's only partially iterated over is never cached.
+pub struct CacheWriter {
+    /// The compressed stream, or `None` once it's finished.
+    encoder: Option<zstd::stream::write::Encoder<'static, BufWriter<File>>>,
+
+    /// The path of the temporary file.
+    tmp_path: PathBuf,
+
+    /// The path of the cache file.
+    path: PathBuf,
+}
+
+impl CacheWriter {
+    /// Writes an element of the group.
+    fn write(&mut self, matrix: &Matrix<f64>) -> IoResult<()> {
+        match &mut self.encoder {
+            Some(encoder) => write_matrix(encoder, matrix),
+            None => Ok(()),
+        }
+    }
+
+    /// Finishes writing the file and moves it into place.
+    fn finish(&mut self) -> IoResult<()> {
+        if let Some(encoder) = self.encoder.take() {
+            encoder.finish()?.flush()?;
+            fs::rename(&self.tmp_path, &self.path)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for CacheWriter {
+    fn drop(&mut self) {
+        if self.encoder.take().is_some() {
+            let _ = fs::remove_file(&self.tmp_path);
+        }
+    }
+}
+
+/// An iterator over the elements of a Coxeter group, which are either read
+/// from a [`GroupCache`] or generated and written into it.
+pub enum CachedCoxIter {
+    /// The elements are read from a file.
+    Read {
+        /// The number of dimensions the group acts on.
+        dim: usize,
+
+        /// The reader for the elements.
+        reader: zstd::stream::read::Decoder<'static, BufReader<File>>,
+    },
+
+    /// The elements are generated, and written into a file as they are.
+    Write {
+        /// The iterator generating the elements. It's boxed, as it's much
+        /// larger than the reader.
+        iter: Box<CoxIter>,
+
+        /// The writer for the elements, or `None` if the file couldn't be
+        /// written.
+        writer: Option<CacheWriter>,
+    },
+}
+
+impl Iterator for CachedCoxIter {
+    type Item = Matrix<f64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Read { dim, reader } => read_matrix(reader, *dim),
+            Self::Write { iter, writer } => {
+                let next = iter.next();
+
+                // If anything goes wrong, we just stop caching.
+                if let Some(w) = writer {
+                    let res = match &next {
+                        Some(matrix) => w.write(matrix),
+                        None => w.finish(),
+                    };
+
+                    if res.is_err() {
+                        *writer = None;
+                    }
+                }
+
+                next
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::MatrixOrd;
+    use std::collections::BTreeSet;
+
+    /// Generates a group into the cache, and reads the same elements back.
+    #[test]
+    fn h3() {
+        let cache = GroupCache::new(std::env::temp_dir().join("miratope-group-cache-test"));
+        let cox = Cox::h(3);
+        fs::remove_file(cache.path(&cox)).ok();
+
+        let generated: BTreeSet<_> = cache.group(&cox).unwrap().map(MatrixOrd::new).collect();
+        assert_eq!(generated.len(), 120);
+        assert!(cache.path(&cox).exists());
+
+        let read: Vec<_> = cache.group(&cox).unwrap().map(MatrixOrd::new).collect();
+        assert_eq!(read.len(), 120);
+        assert!(read.into_iter().all(|m| generated.contains(&m)));
+
+        // A group that's only partially iterated over isn't cached.
+        let cox = Cox::b(3);
+        fs::remove_file(cache.path(&cox)).ok();
+        assert_eq!(cache.group(&cox).unwrap().take(10).count(), 10);
+        assert!(!cache.path(&cox).exists());
+    }
+
+    /// The file names don't depend on the Rust release.
+    #[test]
+    fn stable_path() {
+        let cache = GroupCache::new(std::env::temp_dir().join("miratope-group-cache-test"));
+        assert_eq!(fnv1a(FNV_OFFSET, b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(cache.path(&Cox::a(1)), cache.path(&Cox::a(1)));
+        assert_ne!(cache.path(&Cox::a(2)), cache.path(&Cox::b(2)));
+        assert!(cache.try_parse("o3o3o *b3o").is_ok());
+        assert!(matches!(cache.try_parse("o4o4o"), Err(CoxError::Affine)));
+    }
+}
@@ -0,0 +1,161 @@
+//! Contains a [k-d tree](https://en.wikipedia.org/wiki/K-d_tree) of points,
+//! used to quickly tell whether a point has been found before up to some small
+//! tolerance.
+
+use crate::{float::Float, geometry::Point};
+
+/// A node of a [`KdTree`].
+#[derive(Clone, Debug)]
+struct Node {
+    /// The point stored in the node.
+    point: Point<f64>,
+
+    /// The indices of the nodes with a smaller and with a larger coordinate
+    /// along the splitting axis, respectively.
+    children: [Option<usize>; 2],
+}
+
+/// A [k-d tree](https://en.wikipedia.org/wiki/K-d_tree) of points, which
+/// considers two points equal whenever all of their coordinates differ by less
+/// than [`Float::EPS`].
+///
+/// Unlike a `BTreeSet` of [`PointOrd`](crate::geometry::PointOrd)s, whose
+/// fuzzy lexicographic order can file two equal points in different places,
+/// every point close to a query point gets checked.
+///
+/// The tree isn't rebalanced, so it works best when the points are inserted in
+/// no particular order.
+#[derive(Clone, Debug, Default)]
+pub struct KdTree {
+    /// The nodes of the tree. The root is the first one.
+    nodes: Vec<Node>,
+}
+
+impl KdTree {
+    /// Initializes an empty tree.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of points in the tree.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns whether the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Removes every point from the tree, keeping its allocated memory.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+
+    /// Returns whether the tree contains a point equal to a given one, up to
+    /// the tolerance.
+    pub fn contains(&self, point: &Point<f64>) -> bool {
+        self.find(point).is_some()
+    }
+
+    /// Returns the index of a point in the tree equal to a given one, up to the
+    /// tolerance. Points are indexed in the order they were inserted.
+    pub fn find(&self, point: &Point<f64>) -> Option<usize> {
+        let dim = point.len();
+        if self.nodes.is_empty() || dim == 0 {
+            return (!self.nodes.is_empty()).then_some(0);
+        }
+
+        let mut stack = vec![(0, 0)];
+        while let Some((idx, axis)) = stack.pop() {
+            let node = &self.nodes[idx];
+            if node
+                .point
+                .iter()
+                .zip(point.iter())
+                .all(|(x, y)| (x - y).abs() < f64::EPS)
+            {
+                return Some(idx);
+            }
+
+            // A close point could be on either side of the splitting plane.
+            let diff = point[axis] - node.point[axis];
+            let next_axis = (axis + 1) % dim;
+            if let Some(child) = node.children[0].filter(|_| diff < f64::EPS) {
+                stack.push((child, next_axis));
+            }
+            if let Some(child) = node.children[1].filter(|_| diff > -f64::EPS) {
+                stack.push((child, next_axis));
+            }
+        }
+
+        None
+    }
+
+    /// Inserts a point into the tree, unless it already contains an equal one.
+    /// Returns whether the point was inserted.
+    pub fn insert(&mut self, point: Point<f64>) -> bool {
+        if self.contains(&point) {
+            return false;
+        }
+
+        let new_idx = self.nodes.len();
+        let dim = point.len();
+
+        if new_idx != 0 {
+            let mut idx = 0;
+            let mut axis = 0;
+
+            loop {
+                let side = (point[axis] >= self.nodes[idx].point[axis]) as usize;
+                match self.nodes[idx].children[side] {
+                    Some(child) => {
+                        idx = child;
+                        axis = (axis + 1) % dim;
+                    }
+                    None => {
+                        self.nodes[idx].children[side] = Some(new_idx);
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.nodes.push(Node {
+            point,
+            children: [None, None],
+        });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Inserts a grid of points, and finds them again after perturbing them.
+    #[test]
+    fn grid() {
+        let mut tree = KdTree::new();
+        let points: Vec<Point<f64>> = (0..1000)
+            .map(|k| {
+                // Scrambles the order of the points, so the tree stays shallow.
+                let k = (k * 379) % 1000;
+                Point::from_vec(vec![(k % 10) as f64, (k / 10 % 10) as f64, (k / 100) as f64])
+            })
+            .collect();
+
+        for p in &points {
+            assert!(tree.insert(p.clone()));
+        }
+        assert_eq!(tree.len(), 1000);
+
+        for (idx, p) in points.iter().enumerate() {
+            let q = p.add_scalar(f64::EPS / 2.0);
+            assert_eq!(tree.find(&q), Some(idx));
+            assert!(!tree.insert(q));
+            assert!(!tree.contains(&p.add_scalar(0.5)));
+        }
+        assert_eq!(tree.len(), 1000);
+    }
+}
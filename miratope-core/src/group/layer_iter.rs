@@ -0,0 +1,180 @@
+//! Contains the code that streams the elements of a finite group generated by
+//! reflections in layers, by their length as words in the generators.
+
+use std::{collections::HashSet, hash::Hash, mem, ops::Mul};
+
+use super::kd_tree::KdTree;
+use crate::geometry::Point;
+
+/// A set of images of a point under some group elements, used to tell these
+/// elements apart.
+pub trait ImageSet: Clone + Default {
+    /// The type of the images.
+    type Image;
+
+    /// Returns whether the set contains an image.
+    fn contains(&self, image: &Self::Image) -> bool;
+
+    /// Inserts an image into the set, unless it already contains it. Returns
+    /// whether the image was inserted.
+    fn insert(&mut self, image: Self::Image) -> bool;
+
+    /// Removes every image from the set.
+    fn clear(&mut self);
+}
+
+/// Images are compared up to [`Float::EPS`](crate::float::Float::EPS).
+impl ImageSet for KdTree {
+    type Image = Point<f64>;
+
+    fn contains(&self, image: &Point<f64>) -> bool {
+        KdTree::contains(self, image)
+    }
+
+    fn insert(&mut self, image: Point<f64>) -> bool {
+        KdTree::insert(self, image)
+    }
+
+    fn clear(&mut self) {
+        KdTree::clear(self)
+    }
+}
+
+/// Images are compared exactly.
+impl<T: Clone + Eq + Hash> ImageSet for HashSet<T> {
+    type Image = T;
+
+    fn contains(&self, image: &T) -> bool {
+        HashSet::contains(self, image)
+    }
+
+    fn insert(&mut self, image: T) -> bool {
+        HashSet::insert(self, image)
+    }
+
+    fn clear(&mut self) {
+        HashSet::clear(self)
+    }
+}
+
+/// An iterator over the elements of a finite group
+/// [generated](https://en.wikipedia.org/wiki/Generator_(mathematics)) by
+/// reflections, such as a [Coxeter group](https://en.wikipedia.org/wiki/Coxeter_group),
+/// generic over the type of the elements and of the sets of their images.
+///
+/// The elements are built in layers, by their length as words in the
+/// generators. Since every reflection flips the sign of the determinant,
+/// multiplying an element by a generator always takes it to the previous or to
+/// the next layer. This means we only ever need to remember three layers at a
+/// time, rather than the whole group, which is what makes groups like E7 and
+/// E8 feasible to iterate over.
+///
+/// Elements are told apart by where they send a point in no mirror, which is
+/// much cheaper than comparing them directly. The image of an element is
+/// passed to [`Self::next_with`] by whoever wraps this iterator.
+#[derive(Clone)]
+pub struct LayerIter<T, S> {
+    /// The reflections that generate the group.
+    gens: Vec<T>,
+
+    /// The identity element.
+    identity: T,
+
+    /// The images of the point under the elements of the previous layer.
+    prev_images: S,
+
+    /// The images of the point under the elements of the current layer.
+    cur_images: S,
+
+    /// The images of the point under the elements of the next layer found so
+    /// far.
+    next_images: S,
+
+    /// The elements of the current layer.
+    layer: Vec<T>,
+
+    /// The elements of the next layer found so far.
+    next_layer: Vec<T>,
+
+    /// The index of the element in the current layer being multiplied.
+    el_idx: usize,
+
+    /// The index of the generator it's being multiplied by.
+    gen_idx: usize,
+
+    /// Whether the identity has been returned.
+    started: bool,
+}
+
+impl<T: Clone, S: ImageSet> LayerIter<T, S>
+where
+    for<'a> &'a T: Mul<&'a T, Output = T>,
+{
+    /// Initializes a new iterator from a set of reflections and the identity.
+    pub fn new(gens: Vec<T>, identity: T) -> Self {
+        Self {
+            gens,
+            identity,
+            prev_images: S::default(),
+            cur_images: S::default(),
+            next_images: S::default(),
+            layer: Vec::new(),
+            next_layer: Vec::new(),
+            el_idx: 0,
+            gen_idx: 0,
+            started: false,
+        }
+    }
+
+    /// Returns the reflections that generate the group.
+    pub fn gens(&self) -> &[T] {
+        &self.gens
+    }
+
+    /// Moves on to the next layer. Returns `false` if it's empty.
+    fn advance_layer(&mut self) -> bool {
+        mem::swap(&mut self.prev_images, &mut self.cur_images);
+        mem::swap(&mut self.cur_images, &mut self.next_images);
+        self.next_images.clear();
+
+        self.layer = mem::take(&mut self.next_layer);
+        self.el_idx = 0;
+        self.gen_idx = 0;
+        !self.layer.is_empty()
+    }
+
+    /// Returns the next element of the group, telling the elements apart by
+    /// the images given by a function.
+    pub fn next_with<F: FnMut(&T) -> S::Image>(&mut self, mut image: F) -> Option<T> {
+        // The identity is the only element of length zero.
+        if !self.started {
+            self.started = true;
+            self.cur_images.insert(image(&self.identity));
+            self.layer.push(self.identity.clone());
+            return Some(self.identity.clone());
+        }
+
+        if self.gens.is_empty() {
+            return None;
+        }
+
+        loop {
+            if self.el_idx == self.layer.len() && !self.advance_layer() {
+                return None;
+            }
+
+            let new_el = &self.layer[self.el_idx] * &self.gens[self.gen_idx];
+            self.gen_idx += 1;
+            if self.gen_idx == self.gens.len() {
+                self.gen_idx = 0;
+                self.el_idx += 1;
+            }
+
+            let new_image = image(&new_el);
+            if !self.prev_images.contains(&new_image) && self.next_images.insert(new_image) {
+                self.next_layer.push(new_el.clone());
+                return Some(new_el);
+            }
+        }
+    }
+}
@@ -1,10 +1,20 @@
 //! Contains methods to generate many symmetry groups.
 
+pub mod classify;
+pub mod cox_iter;
 pub mod cyclic;
+pub mod disk_cache;
 pub mod gen_iter;
 pub mod group_item;
+pub mod kd_tree;
+pub mod layer_iter;
 pub mod pairs;
 pub mod permutation;
+pub mod quaternion;
+pub mod stab_chain;
+pub mod subgroups;
+pub mod vertex_map;
+pub mod weyl_iter;
 
 pub use gen_iter::*;
 
@@ -15,23 +25,27 @@ use std::{
 };
 
 use crate::{
-    cox::{cd::CdResult, Cox},
+    cox::{cd::CdResult, Cox, CoxResult},
     float::Float,
-    geometry::Matrix,
+    geometry::{Matrix, Point},
 };
 
 use self::{
+    cox_iter::CoxIter,
     cyclic::Cyclic,
     group_item::GroupItem,
     pairs::{AsPair, PairMap},
     permutation::{PermutationIter, SPermutation},
+    vertex_map::VertexMap,
 };
 
+use itertools::Itertools;
 use nalgebra::{
     allocator::Allocator, dmatrix, Const, DefaultAllocator, Quaternion, Rotation, UnitQuaternion,
 };
 
 use unchecked_unwrap::UncheckedUnwrap;
+use vec_like::*;
 
 /// The type of the dimension associated to an iterator.
 type Dim<I> = <<I as Iterator>::Item as GroupItem>::Dim;
@@ -143,14 +157,20 @@ impl<T: Float> Group<Cyclic<Matrix<T>>> {
     /// Builds the cyclic group generated by a 2π / n rotation.
     pub fn cyclic(n: u32) -> Self {
         let (s, c) = (T::TAU / T::u32(n)).fsin_cos();
-        Self::cyclic_gen(2, dmatrix![c, -s; s, c])
+        Self::cyclic_gen(2, dmatrix![c.clone(), -s.clone(); s.clone(), c])
     }
 }
 
-impl Group<GenIter<Matrix<f64>>> {
+impl Group<CoxIter> {
     /// Parses a diagram and turns it into a Coxeter group.
     pub fn parse(input: &str) -> CdResult<Option<Self>> {
-        GenIter::parse(input).map(|gens| gens.map(Into::into))
+        Cox::parse(input).map(|cox| cox.group())
+    }
+
+    /// Parses a diagram and turns it into a group, or returns an error
+    /// explaining why the diagram doesn't describe a finite group.
+    pub fn try_parse(input: &str) -> CoxResult<Self> {
+        Cox::parse(input)?.try_group()
     }
 
     /// Returns the A(n) group.
@@ -410,75 +430,114 @@ impl<T: Float, I: Iterator<Item = Matrix<T>>> Group<I> {
         unsafe { self.iso(2 * dim, move |mat| direct_sum(&mat, &f(&mat))) }
     }
 
-    /*
-    /// Generates the [wreath product](https://en.wikipedia.org/wiki/Wreath_product)
-    /// of two symmetry groups.
-    pub fn wreath<D: nalgebra::Dim, J: Iterator<Item = Permutation<D>>>(self, h: Group<J>) -> Self
-    where
-        J::Item: GroupItem,
-    {
-        let h = h.elements();
-        let g_dim = g.dim;
-        let dim = g_dim * h.len();
+    /// Builds the [wreath product](https://en.wikipedia.org/wiki/Wreath_product)
+    /// of a group with the symmetric group on `n` letters. This acts on `n`
+    /// orthogonal copies of the space of the group, by applying an element of
+    /// the group to each of them and then permuting them. It's the symmetry
+    /// group of the product of `n` equal polytopes, like the duoprism of two
+    /// equal polygons.
+    ///
+    /// # Panics
+    /// This method will panic if `n` is zero.
+    pub fn wreath(self, n: usize) -> Group<MatrixProductIter<T>> {
+        assert!(n >= 1);
+        let g_dim = self.dim;
+        let dim = g_dim * n;
+
+        // The direct product of the group with itself n times.
+        let g: Vec<_> = self.collect();
+        let mut product = g.clone();
+        for _ in 1..n {
+            product = product
+                .iter()
+                .flat_map(|a| g.iter().map(move |b| direct_sum(a, b)))
+                .collect();
+        }
+
+        // The matrices that permute the copies of the space.
+        let permutations: Vec<Matrix<T>> = (0..n)
+            .permutations(n)
+            .map(|perm| {
+                Matrix::from_fn(dim, dim, |i, j| {
+                    if perm[i / g_dim] == j / g_dim && i % g_dim == j % g_dim {
+                        T::ONE
+                    } else {
+                        T::ZERO
+                    }
+                })
+            })
+            .collect();
+
+        // Safety: the permutations normalize the direct product, and only
+        // intersect it at the identity, so this is a semidirect product.
+        unsafe { Group::new(dim, (product, permutations).into_pairs().map(|a, b| b * a)) }
+    }
 
-        // Indexes each element in h.
-        let mut h_indices = BTreeMap::new();
+    /// Builds the symmetry group of a prism, given the symmetry group of its
+    /// base. This is the direct product with the reflection along the new
+    /// axis.
+    pub fn prism(self) -> Group<impl Iterator<Item = Matrix<T>>> {
+        self.direct_product(Group::central_inv(1))
+    }
+
+    /// Extends a group by central inversion. Unlike
+    /// [`with_central_inv`](Self::with_central_inv), this checks whether
+    /// central inversion is in the group already, in which case the group is
+    /// returned unchanged.
+    pub fn extend_with_central_inversion(self) -> Group<vec::IntoIter<Matrix<T>>> {
+        let dim = self.dim;
+        let mut elements: Vec<_> = self.collect();
+        let inv = -Matrix::identity(dim, dim);
 
-        for (i, h_el) in h.iter().enumerate() {
-            h_indices.insert(MatrixOrd::new(h_el.clone()), i);
+        if !elements.iter().any(|mat| (mat - &inv).norm() < T::EPS) {
+            let negated: Vec<_> = elements.iter().map(|mat| -mat).collect();
+            elements.extend(negated);
         }
 
-        // Converts h into a permutation group.
-        let mut permutations = Vec::with_capacity(h.len());
+        // Safety: central inversion commutes with everything, so either it's
+        // already in the group, or we get the direct product with ±I.
+        unsafe { Group::new(dim, elements.into_iter()) }
+    }
+}
 
-        for h_el_1 in &h {
-            let mut perm = Vec::with_capacity(h.len());
+impl Group<vec::IntoIter<Matrix<f64>>> {
+    /// Returns the subgroup of the elements that fix a given point.
+    pub fn stabilizer_of_point(self, point: &Point<f64>) -> Self {
+        // Safety: the stabilizer of a point is always a subgroup.
+        unsafe { self.sub(|mat| (mat * point - point).norm() < f64::EPS) }.cache()
+    }
 
-            for h_el_2 in &h {
-                perm.push(
-                    *h_indices
-                        .get(&MatrixOrd::new(h_el_1 * h_el_2))
-                        .expect("h is not a valid group!"),
-                );
-            }
+    /// Returns the subgroup of the elements that map the vertices of an
+    /// element onto themselves, together with its vertex map. The rows of the
+    /// vertex map must correspond to the elements of the group, in order.
+    ///
+    /// A group doesn't know about the polytope it acts on, so the element is
+    /// given by its vertices rather than by its rank and index. To find the
+    /// stabilizer of an element of a polytope by its rank and index, use
+    /// [`Concrete::element_stabilizer`](crate::conc::Concrete::element_stabilizer),
+    /// or [`SymmetryCache::element_stabilizer`](crate::conc::symmetry::SymmetryCache::element_stabilizer)
+    /// to reuse a symmetry group that was already computed.
+    pub fn stabilizer_of_element(self, vertex_map: &VertexMap, element: &[usize]) -> (Self, VertexMap) {
+        let dim = self.dim;
+        let mut sorted = element.to_vec();
+        sorted.sort_unstable();
 
-            permutations.push(perm);
-        }
+        let mut stabilizer = Vec::new();
+        let mut stabilizer_map = VertexMap::new();
 
-        // Computes the direct product of g with itself |h| times.
-        let g_prod = vec![&g; h.len() - 1]
-            .into_iter()
-            .cloned()
-            .fold(g.clone(), Group::direct_product);
-
-        Self::new(
-            dim,
-            g_prod
-                .map(move |g_el| {
-                    let mut matrices = Vec::new();
-
-                    for perm in &permutations {
-                        let mut new_el = Matrix::zeros(dim, dim);
-
-                        // Permutes the blocks on the diagonal of g_el.
-                        for (i, &j) in perm.iter().enumerate() {
-                            for x in 0..g_dim {
-                                for y in 0..g_dim {
-                                    new_el[(i * g_dim + x, j * g_dim + y)] =
-                                        g_el[(i * g_dim + x, i * g_dim + y)];
-                                }
-                            }
-                        }
-
-                        matrices.push(new_el);
-                    }
+        for (mat, row) in self.zip(vertex_map) {
+            let mut image: Vec<usize> = element.iter().map(|&v| row[v]).collect();
+            image.sort_unstable();
 
-                    matrices.into_iter()
-                })
-                .flatten(),
-        )
+            if image == sorted {
+                stabilizer.push(mat);
+                stabilizer_map.push(row.clone());
+            }
+        }
+
+        // Safety: the stabilizer of a set is always a subgroup.
+        (unsafe { Group::new(dim, stabilizer.into_iter()) }, stabilizer_map)
     }
-    */
 }
 
 /// Converts a matrix into a unit quaternion.
@@ -496,14 +555,14 @@ fn mat_from_quats<T: Float>(q: &Quaternion<T>, r: &Quaternion<T>) -> Matrix<T> {
         4,
         // q, q * i, q * j, q * k.
         IntoIterator::into_iter([
-            *q,
-            [q.w, q.k, -q.j, -q.i].into(),
-            [-q.k, q.w, q.i, -q.j].into(),
-            [q.j, -q.i, q.w, -q.k].into(),
+            q.clone(),
+            [q.w.clone(), q.k.clone(), -q.j.clone(), -q.i.clone()].into(),
+            [-q.k.clone(), q.w.clone(), q.i.clone(), -q.j.clone()].into(),
+            [q.j.clone(), -q.i.clone(), q.w.clone(), -q.k.clone()].into(),
         ])
         .map(|q| {
-            let arr = (q * r).coords.data.0[0];
-            IntoIterator::into_iter([arr[3], arr[0], arr[1], arr[2]])
+            let [[i, j, k, w]] = (q * r).coords.data.0;
+            IntoIterator::into_iter([w, i, j, k])
         })
         .flatten(),
     )
@@ -518,12 +577,12 @@ fn direct_sum<T: Float>(mat1: &Matrix<T>, mat2: &Matrix<T>) -> Matrix<T> {
     Matrix::from_fn(dim, dim, |i, j| {
         if i < dim1 {
             if j < dim1 {
-                mat1[(i, j)]
+                mat1[(i, j)].clone()
             } else {
                 T::ZERO
             }
         } else if j >= dim1 {
-            mat2[(i - dim1, j - dim1)]
+            mat2[(i - dim1, j - dim1)].clone()
         } else {
             T::ZERO
         }
@@ -563,7 +622,7 @@ mod tests {
     }
 
     /// Parses a CD and unwraps it.
-    fn parse_unwrap(input: &str) -> Group<GenIter<Matrix<f64>>> {
+    fn parse_unwrap(input: &str) -> Group<CoxIter> {
         Group::parse(input).unwrap().unwrap()
     }
 
@@ -683,6 +742,14 @@ mod tests {
         test(parse_unwrap("o3o3o3o3o *c3o"), 51840, 25920, "E6");
     }
 
+    /// Tests the F4 and G2 symmetry groups, which are generated exactly even
+    /// though their mirrors meet at angles with irrational cosines.
+    #[test]
+    fn f4_g2() {
+        test(parse_unwrap("o3o4o3o"), 1152, 576, "F4");
+        test(parse_unwrap("o6o"), 12, 6, "G2");
+    }
+
     #[test]
     fn pairs() {
         assert_eq!(
@@ -700,7 +767,7 @@ mod tests {
     #[cfg(not(debug_assertions))]
     fn e7() {
         test(
-            Group::parse_unwrap("o3o3o3o3o3o *c3o"),
+            parse_unwrap("o3o3o3o3o3o *c3o"),
             2903040,
             1451520,
             "E7",
@@ -715,16 +782,35 @@ mod tests {
         test(g, 576, 288, "A3×A3");
     }
 
-    /* #[test]
-    /// Tests the wreath product of A3 with A1.
+    #[test]
+    /// Tests the wreath product of A3 with the symmetric group on 2 letters.
     fn a3_wr_a1() {
-        test(
-            Group::wreath(Group::a(3), Group::a(1)),
-            1152,
-            576,
-            "A3 ≀ A1",
-        );
-    }*/
+        test(parse_unwrap("o3o3o").wreath(2), 1152, 576, "A3 ≀ A1");
+    }
+
+    #[test]
+    /// Tests the symmetries of the square duoprism, which is the tesseract,
+    /// and of the triangular duoprism.
+    fn duoprisms() {
+        test(Group::dihedral_2(4).wreath(2), 128, 64, "I2(4) ≀ A1");
+        test(Group::dihedral_2(3).wreath(2), 72, 36, "I2(3) ≀ A1");
+        test(Group::dihedral_2(3).wreath(3), 1296, 648, "I2(3) ≀ A2");
+    }
+
+    #[test]
+    /// Tests the symmetries of some prisms.
+    fn prisms() {
+        test(Group::dihedral_2(5).prism(), 20, 10, "I2(5) × A1");
+        test(parse_unwrap("o3o3o").prism(), 48, 24, "A3 × A1");
+    }
+
+    #[test]
+    /// Tests extending groups by central inversion.
+    fn central_inversion() {
+        test(parse_unwrap("o3o3o").extend_with_central_inversion(), 48, 24, "±A3");
+        test(Group::hypercube(3).extend_with_central_inversion(), 48, 24, "BC3");
+        test(Group::cyclic(3).extend_with_central_inversion(), 6, 6, "±I2(3)⁺");
+    }
 
     #[test]
     /// Tests out some step prisms.
@@ -742,4 +828,30 @@ mod tests {
             }
         }
     }
+
+    /// Tests the stabilizers of some points and sets of vertices.
+    #[test]
+    fn stabilizers() {
+        let inv = Group::central_inv(3).cache();
+        assert_eq!(inv.clone().stabilizer_of_point(&Point::zeros(3)).count(), 2);
+        assert_eq!(inv.stabilizer_of_point(&nalgebra::dvector![1.0, 0.0, 0.0]).count(), 1);
+
+        // The rotations of a square, and how they permute its vertices.
+        let rotations = Group::cyclic(4).cache();
+        let vertex_map: VertexMap = vec![
+            vec![0, 1, 2, 3],
+            vec![1, 2, 3, 0],
+            vec![2, 3, 0, 1],
+            vec![3, 0, 1, 2],
+        ]
+        .into();
+
+        let (group, map) = rotations.clone().stabilizer_of_element(&vertex_map, &[0, 2]);
+        assert_eq!(group.count(), 2);
+        assert_eq!(map.len(), 2);
+
+        let (group, map) = rotations.stabilizer_of_element(&vertex_map, &[0, 1]);
+        assert_eq!(group.count(), 1);
+        assert_eq!(map.len(), 1);
+    }
 }
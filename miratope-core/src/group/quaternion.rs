@@ -0,0 +1,395 @@
+//! Contains the representation of 4D rotations as pairs of unit quaternions.
+//!
+//! Every rotation of 4D space can be written as `x ↦ l * x * r` for some unit
+//! quaternions `l` and `r`, where we identify the point `(w, x, y, z)` with the
+//! quaternion `w + xi + yj + zk`. The pair is unique up to negating both
+//! quaternions. Composing rotations this way takes two quaternion products
+//! rather than a 4 × 4 matrix product, and it's how the swirlchoron groups are
+//! built in the first place.
+
+use std::{cmp::Ordering, collections::BTreeSet, vec};
+
+use super::{
+    group_item::{GroupItem, Wrapper},
+    mat_from_quats, mat_to_quat, Group,
+};
+use crate::{
+    float::Float,
+    geometry::{Matrix, MatrixOrd},
+};
+
+use approx::{abs_diff_eq, abs_diff_ne};
+use nalgebra::Quaternion;
+
+/// Returns the components of a quaternion, real part first.
+fn components<T: Float + Copy>(q: &Quaternion<T>) -> [T; 4] {
+    [q.w, q.i, q.j, q.k]
+}
+
+/// Returns the quaternion `1`, `i`, `j`, or `k`.
+fn basis<T: Float + Copy>(idx: usize) -> Quaternion<T> {
+    let mut q = Quaternion::new(T::ZERO, T::ZERO, T::ZERO, T::ZERO);
+    match idx {
+        0 => q.w = T::ONE,
+        1 => q.i = T::ONE,
+        2 => q.j = T::ONE,
+        _ => q.k = T::ONE,
+    }
+    q
+}
+
+/// A rotation of 4D space, stored as the pair of unit quaternions `(l, r)` so
+/// that the rotation sends `x` to `l * x * r`.
+///
+/// Since `(l, r)` and `(-l, -r)` give the same rotation, we always store the
+/// pair whose first component that isn't zero is positive. This way, equal
+/// rotations have (approximately) equal pairs.
+#[derive(Clone, Copy, Debug)]
+pub struct QuatPair<T: Float + Copy> {
+    /// The quaternion that multiplies on the left.
+    left: Quaternion<T>,
+
+    /// The quaternion that multiplies on the right.
+    right: Quaternion<T>,
+}
+
+impl<T: Float + Copy> QuatPair<T> {
+    /// Builds the rotation `x ↦ left * x * right`. Both quaternions should
+    /// have unit norm.
+    pub fn new(left: Quaternion<T>, right: Quaternion<T>) -> Self {
+        let mut pair = Self { left, right };
+        pair.normalize_sign();
+        pair
+    }
+
+    /// Returns the identity rotation.
+    pub fn identity() -> Self {
+        Self::new(Quaternion::identity(), Quaternion::identity())
+    }
+
+    /// Returns the quaternion that multiplies on the left.
+    pub fn left(&self) -> &Quaternion<T> {
+        &self.left
+    }
+
+    /// Returns the quaternion that multiplies on the right.
+    pub fn right(&self) -> &Quaternion<T> {
+        &self.right
+    }
+
+    /// Iterates over the eight components of the pair, left quaternion first.
+    pub fn iter(&self) -> impl Iterator<Item = T> {
+        components(&self.left)
+            .into_iter()
+            .chain(components(&self.right))
+    }
+
+    /// Negates both quaternions if needed, so that the first component that
+    /// isn't zero is positive.
+    fn normalize_sign(&mut self) {
+        if self
+            .iter()
+            .find(|x| x.fabs() > T::EPS)
+            .is_some_and(|x| x < T::ZERO)
+        {
+            self.left = -self.left;
+            self.right = -self.right;
+        }
+    }
+
+    /// Applies the rotation to a quaternion.
+    pub fn apply(&self, x: &Quaternion<T>) -> Quaternion<T> {
+        self.left * x * self.right
+    }
+
+    /// Returns the 4 × 4 matrix of the rotation, in the coordinates
+    /// `(w, x, y, z)`.
+    pub fn to_matrix(&self) -> Matrix<T> {
+        mat_from_quats(&self.left, &self.right)
+    }
+
+    /// Decomposes a 4D rotation matrix into a pair of unit quaternions.
+    /// Returns `None` if the matrix isn't a 4D rotation.
+    ///
+    /// The products `x ↦ e * x * f`, where `e` and `f` range over `1`, `i`,
+    /// `j`, and `k`, form an orthogonal basis of the 4 × 4 matrices. The
+    /// coefficients of the matrix of `x ↦ l * x * r` in this basis are the
+    /// products of the components of `l` and `r`, from which we can read off
+    /// both quaternions.
+    pub fn from_matrix(mat: &Matrix<T>) -> Option<Self> {
+        if mat.shape() != (4, 4) {
+            return None;
+        }
+
+        // The outer product of l and r.
+        let outer = Matrix::from_fn(4, 4, |p, q| {
+            mat.dot(&mat_from_quats(&basis(p), &basis(q))) / T::FOUR
+        });
+
+        // The column with the largest norm is the most precise multiple of l.
+        let col = (0..4)
+            .map(|q| outer.column(q))
+            .max_by(|a, b| a.norm().partial_cmp(&b.norm()).unwrap_or(Ordering::Equal))?;
+        let norm = col.norm();
+        if norm < T::EPS {
+            return None;
+        }
+        let l = col / norm;
+        let r = outer.tr_mul(&l);
+
+        let pair = Self::new(
+            Quaternion::new(l[0], l[1], l[2], l[3]),
+            Quaternion::new(r[0], r[1], r[2], r[3]),
+        );
+
+        // Reflections and non-orthogonal matrices don't decompose this way.
+        (MatrixOrd::new(pair.to_matrix()) == MatrixOrd::new(mat.clone())).then_some(pair)
+    }
+}
+
+impl<T: Float + Copy> GroupItem for QuatPair<T> {
+    type Dim = ();
+    type FuzzyOrd = QuatPairOrd<T>;
+
+    fn id(_: ()) -> Self {
+        Self::identity()
+    }
+
+    fn inv(&self) -> Self {
+        Self::new(self.left.conjugate(), self.right.conjugate())
+    }
+
+    /// Composes two rotations, applying `rhs` first, just as with matrices.
+    fn mul(&self, rhs: &Self) -> Self {
+        Self::new(self.left * rhs.left, rhs.right * self.right)
+    }
+
+    fn mul_assign(&mut self, rhs: &Self) {
+        *self = self.mul(rhs);
+    }
+}
+
+/// A wrapper around a [`QuatPair`] that compares the components of its
+/// quaternions lexicographically, considering any two that differ by less than
+/// [`Float::EPS`] as equal.
+#[derive(Clone, Copy, Debug)]
+#[repr(transparent)]
+pub struct QuatPairOrd<T: Float + Copy>(pub QuatPair<T>);
+
+unsafe impl<T: Float + Copy> Wrapper<QuatPair<T>> for QuatPairOrd<T> {
+    fn from_inner(inner: QuatPair<T>) -> Self {
+        Self(inner)
+    }
+
+    fn into_inner(self) -> QuatPair<T> {
+        self.0
+    }
+
+    fn as_inner(&self) -> &QuatPair<T> {
+        &self.0
+    }
+}
+
+impl<T: Float + Copy> PartialEq for QuatPairOrd<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .all(|(x, y)| abs_diff_eq!(x, y, epsilon = T::EPS))
+    }
+}
+
+/// Equality on `QuatPairOrds` should be an equality relation, as long as the
+/// distance between the pairs you're comparing is "small".
+impl<T: Float + Copy> Eq for QuatPairOrd<T> {}
+
+impl<T: Float + Copy> PartialOrd for QuatPairOrd<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Float + Copy> Ord for QuatPairOrd<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        for (x, y) in self.0.iter().zip(other.0.iter()) {
+            if abs_diff_ne!(x, y, epsilon = T::EPS) {
+                return x.partial_cmp(&y).unwrap_or(Ordering::Equal);
+            }
+        }
+
+        Ordering::Equal
+    }
+}
+
+impl<T: Float + Copy, I: Iterator<Item = Matrix<T>>> Group<I> {
+    /// Converts a group of 4D rotations into a group of quaternion pairs.
+    ///
+    /// # Panics
+    /// This method will panic if the group isn't a group of 4D rotations.
+    pub fn quat_pairs(self) -> Group<impl Iterator<Item = QuatPair<T>>> {
+        assert_eq!(self.dim, 4, "quaternion pairs only represent 4D rotations");
+
+        // Safety: composing pairs corresponds to multiplying matrices.
+        unsafe {
+            self.iso((), |mat| {
+                QuatPair::from_matrix(&mat).expect("group element is not a rotation")
+            })
+        }
+    }
+
+    /// Returns the group of unit quaternions that represent the rotations of
+    /// a 3D rotation group, i.e. its preimage in the
+    /// [binary polyhedral groups](https://en.wikipedia.org/wiki/Binary_polyhedral_group).
+    /// Every rotation is represented by two opposite quaternions.
+    ///
+    /// # Panics
+    /// This method will panic if the group is not 3-dimensional.
+    ///
+    /// # Safety
+    /// The group must be a rotation group.
+    pub unsafe fn binary(self) -> Group<vec::IntoIter<Quaternion<T>>> {
+        assert_eq!(self.dim, 3);
+
+        let quats: Vec<_> = self
+            .flat_map(|mat| {
+                let q = *mat_to_quat(&mat).quaternion();
+                [q, -q]
+            })
+            .collect();
+
+        unsafe { Group::new((), quats.into_iter()) }
+    }
+}
+
+impl<T: Float + Copy, I: Iterator<Item = QuatPair<T>>> Group<I> {
+    /// Converts a group of quaternion pairs into a group of 4D rotation
+    /// matrices.
+    pub fn matrices(self) -> Group<impl Iterator<Item = Matrix<T>>> {
+        // Safety: composing pairs corresponds to multiplying matrices.
+        unsafe { self.iso(4, |pair| pair.to_matrix()) }
+    }
+}
+
+impl<T: Float + Copy> Group<vec::IntoIter<QuatPair<T>>> {
+    /// Builds the group of rotations `x ↦ l * x * r`, where `l` and `r` range
+    /// over two groups of unit quaternions. Applied to the
+    /// [`binary`](Group::binary) groups of two 3D rotation groups, this gives
+    /// the same group as [`swirl`](Group::swirl), in the notation ±\[L × R\]
+    /// of "On Quaternions and Octonions" by John H. Conway and Derek A. Smith.
+    pub fn quat_product<I: Iterator<Item = Quaternion<T>>, J: Iterator<Item = Quaternion<T>>>(
+        left: Group<I>,
+        right: Group<J>,
+    ) -> Self {
+        let right: Vec<_> = right.collect();
+        let mut found = BTreeSet::new();
+        let mut elements = Vec::new();
+
+        for l in left {
+            for r in &right {
+                let pair = QuatPair::new(l, *r);
+                if found.insert(QuatPairOrd(pair)) {
+                    elements.push(pair);
+                }
+            }
+        }
+
+        // Safety: the map (l, r) ↦ (x ↦ l * x * r) is a homomorphism from the
+        // product of the left group and the opposite of the right group, so
+        // its image is a group.
+        unsafe { Group::new((), elements.into_iter()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cox::Cox, group::GenIter};
+
+    /// Some unit quaternions in general position.
+    fn quats() -> Vec<Quaternion<f64>> {
+        [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.5, 0.5, 0.5, 0.5],
+            [0.3, -0.1, 0.7, 0.2],
+            [-0.4, 0.2, -0.3, 0.9],
+            [0.0, 0.0, -1.0, 0.0],
+        ]
+        .into_iter()
+        .map(|[w, i, j, k]| Quaternion::new(w, i, j, k).normalize())
+        .collect()
+    }
+
+    /// Checks that converting pairs into matrices and back gives the same
+    /// pairs, and that composing pairs composes their matrices.
+    #[test]
+    fn matrices() {
+        let quats = quats();
+        let pairs: Vec<_> = quats
+            .iter()
+            .flat_map(|l| quats.iter().map(move |r| QuatPair::new(*l, *r)))
+            .collect();
+
+        for a in &pairs {
+            let mat = a.to_matrix();
+            assert!((mat.determinant() - 1.0).abs() < f64::EPS);
+            assert!(GroupItem::eq(&QuatPair::from_matrix(&mat).unwrap(), a));
+
+            let x = Quaternion::new(0.1, 0.2, 0.3, 0.4);
+            let y = &mat * nalgebra::dvector![x.w, x.i, x.j, x.k];
+            let z = a.apply(&x);
+            assert!((y - nalgebra::dvector![z.w, z.i, z.j, z.k]).norm() < f64::EPS);
+
+            for b in &pairs {
+                let prod = MatrixOrd::new(GroupItem::mul(a, b).to_matrix());
+                assert!(prod == MatrixOrd::new(&mat * b.to_matrix()));
+            }
+
+            assert!(GroupItem::eq(
+                &GroupItem::mul(a, &a.inv()),
+                &QuatPair::identity()
+            ));
+        }
+
+        // Opposite pairs are the same rotation.
+        let (l, r) = (quats[2], quats[3]);
+        assert!(GroupItem::eq(&QuatPair::new(l, r), &QuatPair::new(-l, -r)));
+
+        // Reflections aren't rotations.
+        let reflection: Matrix<f64> = Group::reflection_at(4, 0).nth(1).unwrap();
+        assert!(QuatPair::from_matrix(&reflection).is_none());
+    }
+
+    /// Checks that the quaternion product of binary groups is the swirl group.
+    #[test]
+    fn swirl() {
+        let t = || Group::simplex(3).rotations();
+        let swirl = unsafe { t().swirl(t()) }.quat_pairs();
+        let mut product = unsafe { Group::quat_product(t().binary(), t().binary()) };
+
+        let swirl: BTreeSet<_> = swirl.map(QuatPairOrd).collect();
+        assert_eq!(swirl.len(), 288);
+        assert_eq!(product.clone().count(), 288);
+        assert!(product.all(|pair| swirl.contains(&QuatPairOrd(pair))));
+    }
+
+    /// Generates the rotations of the 600-cell from quaternion pairs.
+    #[test]
+    fn h4() {
+        // The rotations are generated by the products of pairs of reflections.
+        let cox_iter = Cox::h(4).cox_iter().unwrap();
+        let refls = cox_iter.gens();
+        let gens = refls[1..]
+            .iter()
+            .map(|refl| QuatPair::from_matrix(&(&refls[0] * refl)).unwrap())
+            .collect();
+
+        let rotations: BTreeSet<_> = Group::from(GenIter::new((), gens))
+            .matrices()
+            .map(MatrixOrd::new)
+            .collect();
+        assert_eq!(rotations.len(), 7200);
+
+        let mut expected = Group::parse("o5o3o3o").unwrap().unwrap().rotations();
+        assert!(expected.all(|mat| rotations.contains(&MatrixOrd::new(mat))));
+    }
+}
@@ -0,0 +1,488 @@
+//! Contains the [`StabChain`] type, which represents a permutation group by a
+//! [stabilizer chain](https://en.wikipedia.org/wiki/Schreier%E2%80%93Sims_algorithm),
+//! so that orbits, the group order and membership can be computed without
+//! storing every element of the group.
+
+use std::collections::HashSet;
+
+use super::vertex_map::VertexMap;
+
+use vec_like::*;
+
+/// A strong generator of a [`StabChain`].
+#[derive(Clone, Debug)]
+struct Generator {
+    /// The permutation, as the image of every point.
+    perm: Vec<usize>,
+
+    /// The inverse permutation.
+    inv: Vec<usize>,
+
+    /// The level at which the generator was added. The generator fixes the
+    /// base points of all previous levels.
+    level: usize,
+}
+
+/// A group of permutations on some points, represented by a base and strong
+/// generating set, found through the
+/// [Schreier–Sims algorithm](https://en.wikipedia.org/wiki/Schreier%E2%80%93Sims_algorithm).
+///
+/// The chain has a base point for each level. The group at each level is
+/// generated by the strong generators added at that level or any later one,
+/// and it's the stabilizer of the base point of the previous level within the
+/// group of the previous level. The group at the last level is trivial.
+///
+/// The orbit of each base point is stored as a Schreier vector, i.e. for every
+/// point in the orbit, the generator that was used to reach it. This takes
+/// space proportional to the number of points times the number of levels,
+/// however big the group is.
+#[derive(Clone, Debug)]
+pub struct StabChain {
+    /// The number of points the group acts on.
+    point_count: usize,
+
+    /// The base point of each level.
+    base: Vec<usize>,
+
+    /// The strong generators.
+    gens: Vec<Generator>,
+
+    /// For each level and each point in the orbit of its base point, the
+    /// index of the generator used to reach it. The base point itself stores
+    /// `Some(usize::MAX)`.
+    schreier: Vec<Vec<Option<usize>>>,
+}
+
+impl StabChain {
+    /// Returns the trivial group acting on a given number of points.
+    pub fn trivial(point_count: usize) -> Self {
+        Self {
+            point_count,
+            base: Vec::new(),
+            gens: Vec::new(),
+            schreier: Vec::new(),
+        }
+    }
+
+    /// Builds the group generated by some permutations.
+    ///
+    /// # Panics
+    /// Panics if some permutation doesn't act on the given number of points.
+    pub fn new<'a, I: IntoIterator<Item = &'a [usize]>>(point_count: usize, gens: I) -> Self {
+        let mut chain = Self::trivial(point_count);
+        for g in gens {
+            chain.insert(g);
+        }
+        chain
+    }
+
+    /// Builds a stabilizer chain for the group given by a vertex map. Rows are
+    /// only added until the chain has as many elements as the vertex map.
+    pub fn from_vertex_map(vertex_map: &VertexMap) -> Self {
+        let mut chain = Self::trivial(vertex_map.vertex_count());
+
+        for row in vertex_map {
+            if chain.order() >= vertex_map.len() as u128 {
+                break;
+            }
+            chain.insert(row);
+        }
+
+        chain
+    }
+
+    /// Returns the number of points the group acts on.
+    pub fn point_count(&self) -> usize {
+        self.point_count
+    }
+
+    /// Returns the base points of the chain.
+    pub fn base(&self) -> &[usize] {
+        &self.base
+    }
+
+    /// Returns an iterator over the strong generators of the group.
+    pub fn generators(&self) -> impl Iterator<Item = &[usize]> {
+        self.gens.iter().map(|g| g.perm.as_slice())
+    }
+
+    /// Returns the generators of the group at a given level.
+    fn level_gens(&self, level: usize) -> impl Iterator<Item = (usize, &Generator)> {
+        self.gens
+            .iter()
+            .enumerate()
+            .filter(move |(_, g)| g.level >= level)
+    }
+
+    /// Returns the size of the orbit of the base point at a given level.
+    fn orbit_len(&self, level: usize) -> usize {
+        self.schreier[level].iter().filter(|x| x.is_some()).count()
+    }
+
+    /// Returns the order of the group.
+    pub fn order(&self) -> u128 {
+        (0..self.base.len())
+            .map(|level| self.orbit_len(level) as u128)
+            .product()
+    }
+
+    /// Returns the element of the group at a given level that maps its base
+    /// point to a point in its orbit.
+    fn transversal(&self, level: usize, mut point: usize) -> Vec<usize> {
+        let mut word = Vec::new();
+        while point != self.base[level] {
+            let g = &self.gens[self.schreier[level][point].expect("point must be in the orbit")];
+            word.push(&g.perm);
+            point = g.inv[point];
+        }
+
+        word.into_iter()
+            .rev()
+            .fold((0..self.point_count).collect::<Vec<_>>(), |u, g| {
+                VertexMap::compose_rows(&u, g)
+            })
+    }
+
+    /// Sifts a permutation through the chain from a given level, dividing it
+    /// by the transversal elements of each level. Returns what's left, and the
+    /// level at which the sifting stopped.
+    fn sift(&self, perm: &[usize], start: usize) -> (Vec<usize>, usize) {
+        let mut perm = perm.to_vec();
+
+        for level in start..self.base.len() {
+            let mut point = perm[self.base[level]];
+            if self.schreier[level][point].is_none() {
+                return (perm, level);
+            }
+
+            // Walks back to the base point.
+            while point != self.base[level] {
+                let g = &self.gens[self.schreier[level][point].unwrap()];
+                perm = VertexMap::compose_rows(&perm, &g.inv);
+                point = g.inv[point];
+            }
+        }
+
+        (perm, self.base.len())
+    }
+
+    /// Returns whether a permutation is the identity.
+    fn is_identity(perm: &[usize]) -> bool {
+        perm.iter().enumerate().all(|(i, &j)| i == j)
+    }
+
+    /// Returns whether the group contains a permutation.
+    pub fn contains(&self, perm: &[usize]) -> bool {
+        perm.len() == self.point_count && Self::is_identity(&self.sift(perm, 0).0)
+    }
+
+    /// Adds a permutation to the generators of the group, unless it's already
+    /// in it. Returns whether the group grew.
+    ///
+    /// # Panics
+    /// Panics if the permutation doesn't act on the right number of points.
+    pub fn insert(&mut self, perm: &[usize]) -> bool {
+        assert_eq!(
+            perm.len(),
+            self.point_count,
+            "permutation has the wrong length"
+        );
+
+        let (residue, level) = self.sift(perm, 0);
+        if Self::is_identity(&residue) {
+            false
+        } else {
+            self.add_gen(residue, level);
+            true
+        }
+    }
+
+    /// Recomputes the Schreier vector of a given level.
+    fn update_orbit(&mut self, level: usize) {
+        let base = self.base[level];
+        let mut schreier = vec![None; self.point_count];
+        schreier[base] = Some(usize::MAX);
+
+        let mut orbit = vec![base];
+        let mut idx = 0;
+        while let Some(&point) = orbit.get(idx) {
+            for (g_idx, g) in self.level_gens(level) {
+                let image = g.perm[point];
+                if schreier[image].is_none() {
+                    schreier[image] = Some(g_idx);
+                    orbit.push(image);
+                }
+            }
+            idx += 1;
+        }
+
+        self.schreier[level] = schreier;
+    }
+
+    /// Adds a strong generator at a given level, which fixes the base points
+    /// of all previous levels. The levels up to this one are then checked with
+    /// their Schreier generators, which gives a valid chain again.
+    fn add_gen(&mut self, perm: Vec<usize>, level: usize) {
+        if level == self.base.len() {
+            let moved = (0..self.point_count)
+                .find(|&i| perm[i] != i)
+                .expect("the identity can't be a new generator");
+            self.base.push(moved);
+            self.schreier.push(Vec::new());
+        }
+
+        self.gens.push(Generator {
+            inv: VertexMap::invert_row(&perm),
+            perm,
+            level,
+        });
+
+        for level in (0..=level).rev() {
+            self.update_orbit(level);
+
+            // Every Schreier generator must be in the next level.
+            let orbit: Vec<usize> = (0..self.point_count)
+                .filter(|&p| self.schreier[level][p].is_some())
+                .collect();
+            let gens: Vec<usize> = self.level_gens(level).map(|(idx, _)| idx).collect();
+
+            for &point in &orbit {
+                let u = self.transversal(level, point);
+                for &g in &gens {
+                    let image = self.gens[g].perm[point];
+                    let schreier_gen = VertexMap::compose_rows(
+                        &VertexMap::compose_rows(&u, &self.gens[g].perm),
+                        &VertexMap::invert_row(&self.transversal(level, image)),
+                    );
+
+                    let (residue, new_level) = self.sift(&schreier_gen, level + 1);
+                    if !Self::is_identity(&residue) {
+                        // This call leaves the whole chain valid.
+                        self.add_gen(residue, new_level);
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the orbit of a point, starting from the point itself.
+    pub fn orbit(&self, point: usize) -> Vec<usize> {
+        let mut orbit = vec![point];
+        let mut found = vec![false; self.point_count];
+        found[point] = true;
+
+        let mut idx = 0;
+        while let Some(&p) = orbit.get(idx) {
+            for g in &self.gens {
+                let image = g.perm[p];
+                if !found[image] {
+                    found[image] = true;
+                    orbit.push(image);
+                }
+            }
+            idx += 1;
+        }
+
+        orbit
+    }
+
+    /// Splits the points into orbits. The orbits are sorted by their smallest
+    /// point, which comes first in each of them.
+    pub fn orbits(&self) -> Vec<Vec<usize>> {
+        let mut orbits = Vec::new();
+        let mut checked = vec![false; self.point_count];
+
+        for p in 0..self.point_count {
+            if !checked[p] {
+                let orbit = self.orbit(p);
+                for &q in &orbit {
+                    checked[q] = true;
+                }
+                orbits.push(orbit);
+            }
+        }
+
+        orbits
+    }
+
+    /// Returns the distinct images of a set of points, each of them sorted,
+    /// starting from the set itself.
+    pub fn orbit_of_set(&self, set: &[usize]) -> Vec<Vec<usize>> {
+        let mut sorted = set.to_vec();
+        sorted.sort_unstable();
+
+        let mut orbit = vec![sorted.clone()];
+        let mut found = HashSet::from([sorted]);
+
+        let mut idx = 0;
+        while let Some(set) = orbit.get(idx).cloned() {
+            for g in &self.gens {
+                let mut image: Vec<usize> = set.iter().map(|&p| g.perm[p]).collect();
+                image.sort_unstable();
+
+                if found.insert(image.clone()) {
+                    orbit.push(image);
+                }
+            }
+            idx += 1;
+        }
+
+        orbit
+    }
+
+    /// Returns the order of the stabilizer of a set of points, by the
+    /// orbit-stabilizer theorem.
+    pub fn stabilizer_order(&self, set: &[usize]) -> u128 {
+        self.order() / self.orbit_of_set(set).len() as u128
+    }
+
+    /// Returns an iterator over the elements of the group. Each of them is
+    /// built when it's needed, so only the transversals are stored.
+    pub fn elements(&self) -> StabChainIter {
+        StabChainIter {
+            transversals: (0..self.base.len())
+                .map(|level| {
+                    (0..self.point_count)
+                        .filter(|&p| self.schreier[level][p].is_some())
+                        .map(|p| self.transversal(level, p))
+                        .collect()
+                })
+                .collect(),
+            indices: Some(vec![0; self.base.len()]),
+            point_count: self.point_count,
+        }
+    }
+}
+
+/// An iterator over the elements of a [`StabChain`].
+pub struct StabChainIter {
+    /// The transversal elements of each level.
+    transversals: Vec<Vec<Vec<usize>>>,
+
+    /// The index of the transversal element of each level in the next element,
+    /// or `None` if we've gone through all of them.
+    indices: Option<Vec<usize>>,
+
+    /// The number of points the group acts on.
+    point_count: usize,
+}
+
+impl Iterator for StabChainIter {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let indices = self.indices.as_mut()?;
+
+        // Every element is a product of transversal elements, from the last
+        // level to the first.
+        let element = indices.iter().enumerate().rev().fold(
+            (0..self.point_count).collect::<Vec<_>>(),
+            |g, (level, &idx)| VertexMap::compose_rows(&g, &self.transversals[level][idx]),
+        );
+
+        // Moves on to the next indices.
+        let mut level = 0;
+        loop {
+            if level == indices.len() {
+                self.indices = None;
+                break;
+            }
+
+            indices[level] += 1;
+            if indices[level] < self.transversals[level].len() {
+                break;
+            }
+            indices[level] = 0;
+            level += 1;
+        }
+
+        Some(element)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A transposition swapping two points.
+    fn swap(n: usize, i: usize, j: usize) -> Vec<usize> {
+        let mut perm: Vec<usize> = (0..n).collect();
+        perm.swap(i, j);
+        perm
+    }
+
+    /// Returns the orbits of a group, each of them sorted.
+    fn sorted_orbits(chain: &StabChain) -> Vec<Vec<usize>> {
+        let mut orbits = chain.orbits();
+        for orbit in &mut orbits {
+            orbit.sort_unstable();
+        }
+        orbits
+    }
+
+    /// A cycle through all points.
+    fn cycle(n: usize) -> Vec<usize> {
+        (0..n).map(|i| (i + 1) % n).collect()
+    }
+
+    /// The symmetric group on `n` points has order `n!`.
+    #[test]
+    fn symmetric() {
+        for n in 1..8 {
+            let gens = [swap(n, 0, 1.min(n - 1)), cycle(n)];
+            let chain = StabChain::new(n, gens.iter().map(Vec::as_slice));
+            assert_eq!(chain.order(), (1..=n as u128).product());
+            assert_eq!(chain.orbits().len(), 1);
+        }
+    }
+
+    /// The alternating group doesn't contain transpositions.
+    #[test]
+    fn alternating() {
+        let gens = [vec![1, 2, 0, 3, 4], vec![1, 2, 3, 4, 0]];
+        let chain = StabChain::new(5, gens.iter().map(Vec::as_slice));
+        assert_eq!(chain.order(), 60);
+        assert!(!chain.contains(&swap(5, 0, 1)));
+        assert!(chain.contains(&[1, 0, 3, 2, 4]));
+
+        let elements: HashSet<Vec<usize>> = chain.elements().collect();
+        assert_eq!(elements.len(), 60);
+        assert!(elements.iter().all(|g| chain.contains(g)));
+    }
+
+    /// The symmetries of a square agree with its vertex map.
+    #[test]
+    fn square() {
+        let map: VertexMap = vec![
+            vec![0, 1, 2, 3],
+            vec![1, 2, 3, 0],
+            vec![2, 3, 0, 1],
+            vec![3, 0, 1, 2],
+            vec![0, 3, 2, 1],
+            vec![1, 0, 3, 2],
+            vec![2, 1, 0, 3],
+            vec![3, 2, 1, 0],
+        ]
+        .into();
+        let chain = StabChain::from_vertex_map(&map);
+
+        assert_eq!(chain.order(), 8);
+        assert!(map.iter().all(|row| chain.contains(row)));
+        assert!(!chain.contains(&[0, 2, 1, 3]));
+        assert_eq!(sorted_orbits(&chain), vec![vec![0, 1, 2, 3]]);
+        assert_eq!(chain.orbit_of_set(&[0, 2]).len(), 2);
+        assert_eq!(chain.stabilizer_order(&[0, 1]), 2);
+    }
+
+    /// Two disjoint cycles give two orbits.
+    #[test]
+    fn orbits() {
+        let gens = [vec![1, 2, 0, 3, 4], vec![0, 1, 2, 4, 3]];
+        let chain = StabChain::new(5, gens.iter().map(Vec::as_slice));
+        assert_eq!(chain.order(), 6);
+        assert_eq!(sorted_orbits(&chain), vec![vec![0, 1, 2], vec![3, 4]]);
+        assert_eq!(StabChain::trivial(2).order(), 1);
+        assert_eq!(StabChain::trivial(2).elements().count(), 1);
+    }
+}
@@ -0,0 +1,401 @@
+//! Contains the [`GroupTable`] type, which stores the multiplication table of
+//! a finite group so that its conjugacy classes and its subgroups can be
+//! found.
+
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+use super::{group_item::Wrapper, Group, GroupItem};
+
+/// A conjugacy class of subgroups of a [`GroupTable`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubgroupClass {
+    /// The indices of the elements of a representative of the class, sorted.
+    pub elements: Vec<usize>,
+
+    /// The indices of some elements that generate the representative.
+    pub generators: Vec<usize>,
+
+    /// The number of subgroups conjugate to the representative, including
+    /// itself.
+    pub conjugates: usize,
+}
+
+impl SubgroupClass {
+    /// Returns the number of elements of each subgroup in the class.
+    pub fn order(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns whether the subgroups in the class are normal, i.e. whether
+    /// the class has a single subgroup.
+    pub fn is_normal(&self) -> bool {
+        self.conjugates == 1
+    }
+}
+
+/// A finite group, stored as the list of its elements together with its
+/// multiplication table. Elements are referred to by their index in the list,
+/// which is the order in which the group returned them.
+///
+/// As the table has an entry for each pair of elements, this is only meant for
+/// groups with at most a few thousand elements.
+pub struct GroupTable<T: GroupItem> {
+    /// The "dimension" of the elements.
+    dim: T::Dim,
+
+    /// The elements of the group.
+    elements: Vec<T>,
+
+    /// The index of the product of every pair of elements, row by row.
+    table: Vec<usize>,
+
+    /// The index of the inverse of every element.
+    inverses: Vec<usize>,
+
+    /// The index of the identity.
+    identity: usize,
+
+    /// The indices of some elements that generate the group.
+    generators: Vec<usize>,
+}
+
+impl<T: GroupItem + Clone> GroupTable<T> {
+    /// Builds the multiplication table of a group.
+    ///
+    /// # Panics
+    /// Panics if the elements of the group aren't closed under multiplication
+    /// or don't contain the identity, which can't happen for a valid group.
+    pub fn new<I: Iterator<Item = T>>(group: Group<I>) -> Self {
+        let dim = group.dim;
+        let elements: Vec<T> = group.collect();
+        let n = elements.len();
+
+        let mut indices = BTreeMap::new();
+        for (i, el) in elements.iter().enumerate() {
+            indices.entry(T::FuzzyOrd::from_inner(el.clone())).or_insert(i);
+        }
+        let index_of = |el: T| -> usize {
+            *indices
+                .get(&T::FuzzyOrd::from_inner(el))
+                .expect("the elements don't form a group")
+        };
+
+        let mut table = Vec::with_capacity(n * n);
+        for a in &elements {
+            for b in &elements {
+                table.push(index_of(a.mul(b)));
+            }
+        }
+
+        let identity = index_of(T::id(dim));
+        let inverses = (0..n)
+            .map(|i| {
+                (0..n)
+                    .find(|&j| table[i * n + j] == identity)
+                    .expect("the elements don't form a group")
+            })
+            .collect();
+
+        let mut group = Self {
+            dim,
+            elements,
+            table,
+            inverses,
+            identity,
+            generators: Vec::new(),
+        };
+
+        // Adds any element outside of the subgroup generated so far, until
+        // the whole group is generated. Each generator at least doubles the
+        // order of the subgroup, so there are few of them.
+        let mut generated = group.closure(&[]);
+        for i in 0..n {
+            if generated.binary_search(&i).is_err() {
+                group.generators.push(i);
+                generated = group.closure(&group.generators);
+            }
+        }
+
+        group
+    }
+
+    /// Returns the number of elements of the group.
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns whether the group has no elements, which can only happen if it
+    /// wasn't a valid group.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns the elements of the group.
+    pub fn elements(&self) -> &[T] {
+        &self.elements
+    }
+
+    /// Returns the index of the identity.
+    pub fn identity(&self) -> usize {
+        self.identity
+    }
+
+    /// Returns the indices of some elements that generate the group.
+    pub fn generators(&self) -> &[usize] {
+        &self.generators
+    }
+
+    /// Returns the index of the product of two elements.
+    pub fn mul(&self, i: usize, j: usize) -> usize {
+        self.table[i * self.len() + j]
+    }
+
+    /// Returns the index of the inverse of an element.
+    pub fn inv(&self, i: usize) -> usize {
+        self.inverses[i]
+    }
+
+    /// Returns the index of the conjugate `by · i · by⁻¹` of an element.
+    pub fn conjugate(&self, i: usize, by: usize) -> usize {
+        self.mul(self.mul(by, i), self.inv(by))
+    }
+
+    /// Returns the conjugate of a sorted set of elements, sorted.
+    fn conjugate_set(&self, set: &[usize], by: usize) -> Vec<usize> {
+        let mut conjugate: Vec<usize> = set.iter().map(|&i| self.conjugate(i, by)).collect();
+        conjugate.sort_unstable();
+        conjugate
+    }
+
+    /// Returns the sorted indices of the elements of the subgroup generated by
+    /// some elements.
+    pub fn closure(&self, generators: &[usize]) -> Vec<usize> {
+        let mut found = vec![false; self.len()];
+        found[self.identity] = true;
+        let mut queue = VecDeque::from([self.identity]);
+
+        while let Some(i) = queue.pop_front() {
+            for &g in generators {
+                let j = self.mul(i, g);
+                if !found[j] {
+                    found[j] = true;
+                    queue.push_back(j);
+                }
+            }
+        }
+
+        (0..self.len()).filter(|&i| found[i]).collect()
+    }
+
+    /// Returns the orbit of an item under conjugation by the elements of the
+    /// group. As conjugation by a product is conjugation by each factor, it's
+    /// enough to conjugate by the generators.
+    fn conjugation_orbit<U: Clone + Eq + Hash>(
+        &self,
+        item: U,
+        conjugate: impl Fn(&U, usize) -> U,
+    ) -> Vec<U> {
+        let mut orbit = vec![item.clone()];
+        let mut found = HashSet::from([item]);
+        let mut idx = 0;
+
+        while idx < orbit.len() {
+            for &g in &self.generators {
+                let new = conjugate(&orbit[idx], g);
+                if found.insert(new.clone()) {
+                    orbit.push(new);
+                }
+            }
+            idx += 1;
+        }
+
+        orbit
+    }
+
+    /// Returns the conjugacy classes of the group, as the sorted indices of
+    /// their elements. The classes are in the order of their first elements.
+    pub fn conjugacy_classes(&self) -> Vec<Vec<usize>> {
+        let mut found = vec![false; self.len()];
+        let mut classes = Vec::new();
+
+        for i in 0..self.len() {
+            if found[i] {
+                continue;
+            }
+
+            let mut class = self.conjugation_orbit(i, |&j, g| self.conjugate(j, g));
+            for &j in &class {
+                found[j] = true;
+            }
+            class.sort_unstable();
+            classes.push(class);
+        }
+
+        classes
+    }
+
+    /// Returns the subgroups of the group up to conjugacy, from the largest
+    /// to the smallest. If a maximum is given, the search stops after finding
+    /// that many classes, and the larger subgroups might be missing.
+    ///
+    /// Every subgroup is generated by a subgroup with one less generator and
+    /// one more element. Since conjugating both gives a conjugate subgroup, we
+    /// only need to add elements to a representative of each class found so
+    /// far, starting from the trivial group. Adding any element of a coset of
+    /// the representative gives the same subgroup, so we add one per coset.
+    pub fn subgroup_classes(&self, max: Option<usize>) -> Vec<SubgroupClass> {
+        let n = self.len();
+        let mut classes = Vec::new();
+        let mut known = HashSet::new();
+
+        let mut add_class = |classes: &mut Vec<SubgroupClass>, elements: Vec<usize>, generators| {
+            if known.contains(&elements) {
+                return;
+            }
+
+            let conjugates = self.conjugation_orbit(elements.clone(), |set, g| self.conjugate_set(set, g));
+            for conjugate in &conjugates {
+                known.insert(conjugate.clone());
+            }
+
+            classes.push(SubgroupClass {
+                elements,
+                generators,
+                conjugates: conjugates.len(),
+            });
+        };
+
+        add_class(&mut classes, vec![self.identity], Vec::new());
+
+        let mut idx = 0;
+        while idx < classes.len() {
+            let SubgroupClass { elements, generators, .. } = classes[idx].clone();
+            let mut in_coset = vec![false; n];
+
+            for g in 0..n {
+                if max.is_some_and(|max| classes.len() >= max) {
+                    break;
+                }
+                if in_coset[g] {
+                    continue;
+                }
+                for &h in &elements {
+                    in_coset[self.mul(h, g)] = true;
+                }
+
+                let mut new_generators = generators.clone();
+                new_generators.push(g);
+                add_class(&mut classes, self.closure(&new_generators), new_generators);
+            }
+
+            idx += 1;
+        }
+
+        classes.sort_by(|a, b| b.order().cmp(&a.order()).then(a.conjugates.cmp(&b.conjugates)));
+        classes
+    }
+
+    /// Returns the subgroup with the given elements.
+    ///
+    /// # Safety
+    /// The elements must form a subgroup, like those returned by
+    /// [`Self::closure`] or [`Self::subgroup_classes`].
+    pub unsafe fn subgroup(&self, elements: &[usize]) -> Group<std::vec::IntoIter<T>> {
+        let elements: Vec<T> = elements.iter().map(|&i| self.elements[i].clone()).collect();
+        unsafe { Group::new(self.dim, elements.into_iter()) }
+    }
+}
+
+impl<I: Iterator> Group<I>
+where
+    I::Item: GroupItem + Clone,
+{
+    /// Builds the multiplication table of the group. See [`GroupTable`].
+    pub fn table(self) -> GroupTable<I::Item> {
+        GroupTable::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{geometry::Matrix, group::classify::GroupClass};
+
+    /// Returns the sizes of the conjugacy classes of a group, sorted.
+    fn class_sizes<T: GroupItem + Clone>(table: &GroupTable<T>) -> Vec<usize> {
+        let mut sizes: Vec<usize> = table.conjugacy_classes().iter().map(Vec::len).collect();
+        sizes.sort_unstable();
+        sizes
+    }
+
+    /// Checks the multiplication table of the symmetry group of the
+    /// tetrahedron.
+    #[test]
+    fn table() {
+        let table = Group::simplex(3).table();
+        assert_eq!(table.len(), 24);
+
+        let id = table.identity();
+        for i in 0..table.len() {
+            assert_eq!(table.mul(i, id), i);
+            assert_eq!(table.mul(i, table.inv(i)), id);
+        }
+
+        assert_eq!(table.closure(table.generators()).len(), 24);
+        assert_eq!(table.closure(&[]), vec![id]);
+    }
+
+    /// Computes the conjugacy classes of a few groups.
+    #[test]
+    fn conjugacy_classes() {
+        // The symmetric group on four elements.
+        let table = Group::simplex(3).table();
+        assert_eq!(class_sizes(&table), vec![1, 3, 6, 6, 8]);
+
+        // The symmetry group of the cube is the above times ±I.
+        let table = Group::hypercube(3).table();
+        assert_eq!(class_sizes(&table), vec![1, 1, 3, 3, 6, 6, 6, 6, 8, 8]);
+
+        // Every cyclic group is abelian.
+        let table: GroupTable<Matrix<f64>> = Group::cyclic(7).table();
+        assert_eq!(class_sizes(&table), vec![1; 7]);
+    }
+
+    /// Enumerates the subgroups of a few groups up to conjugacy.
+    #[test]
+    fn subgroup_classes() {
+        // The symmetric group on four elements has 30 subgroups in 11
+        // classes.
+        let table = Group::simplex(3).table();
+        let classes = table.subgroup_classes(None);
+        assert_eq!(classes.len(), 11);
+        assert_eq!(classes.iter().map(|class| class.conjugates).sum::<usize>(), 30);
+        assert_eq!(classes[0].order(), 24);
+        assert_eq!(classes.last().unwrap().order(), 1);
+
+        // The alternating subgroup is the chiral tetrahedral group.
+        let rotations = classes.iter().find(|class| class.order() == 12).unwrap();
+        assert!(rotations.is_normal());
+        let class = unsafe { table.subgroup(&rotations.elements) }.classify();
+        assert_eq!(class, GroupClass::Other { order: 12, reflections: Vec::new() });
+
+        // Every subgroup of a cyclic group is normal, and there's one for each
+        // divisor.
+        let table: GroupTable<Matrix<f64>> = Group::cyclic(12).table();
+        let classes = table.subgroup_classes(None);
+        let orders: Vec<usize> = classes.iter().map(SubgroupClass::order).collect();
+        assert_eq!(orders, vec![12, 6, 4, 3, 2, 1]);
+        assert!(classes.iter().all(SubgroupClass::is_normal));
+
+        // The symmetry group of the cube has 98 subgroups in 33 classes.
+        let classes = Group::hypercube(3).table().subgroup_classes(None);
+        assert_eq!(classes.len(), 33);
+        assert_eq!(classes.iter().map(|class| class.conjugates).sum::<usize>(), 98);
+
+        // The search can be cut short.
+        assert_eq!(Group::hypercube(3).table().subgroup_classes(Some(5)).len(), 5);
+    }
+}
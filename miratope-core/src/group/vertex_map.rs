@@ -0,0 +1,212 @@
+//! Contains the [`VertexMap`] type, which represents a symmetry group by the
+//! way it permutes the vertices of a polytope.
+
+use std::collections::{HashMap, HashSet};
+
+use vec_like::*;
+
+/// A group, represented by the way each of its elements permutes the vertices
+/// of a polytope. Each row corresponds to a group element, and stores the index
+/// of the image of every vertex under it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VertexMap(Vec<Vec<usize>>);
+impl_veclike!(VertexMap, Item = Vec<usize>);
+
+impl VertexMap {
+    /// Returns the trivial group acting on a given number of vertices.
+    pub fn identity(vertex_count: usize) -> Self {
+        Self(vec![(0..vertex_count).collect()])
+    }
+
+    /// Returns the number of vertices the group acts on.
+    pub fn vertex_count(&self) -> usize {
+        self.0.first().map_or(0, Vec::len)
+    }
+
+    /// Returns the permutation that results from applying `first`, then
+    /// `second`.
+    pub fn compose_rows(first: &[usize], second: &[usize]) -> Vec<usize> {
+        first.iter().map(|&v| second[v]).collect()
+    }
+
+    /// Returns the inverse of a permutation.
+    pub fn invert_row(row: &[usize]) -> Vec<usize> {
+        let mut inverse = vec![0; row.len()];
+        for (idx, &v) in row.iter().enumerate() {
+            inverse[v] = idx;
+        }
+        inverse
+    }
+
+    /// Returns the index of the row equal to a given permutation, if any.
+    pub fn position(&self, row: &[usize]) -> Option<usize> {
+        self.iter().position(|r| r == row)
+    }
+
+    /// Returns the index of the element that results from applying the element
+    /// at index `i`, then the one at index `j`. Returns `None` if it's not in
+    /// the vertex map.
+    pub fn compose(&self, i: usize, j: usize) -> Option<usize> {
+        self.position(&Self::compose_rows(&self[i], &self[j]))
+    }
+
+    /// Returns the index of the inverse of the element at index `i`. Returns
+    /// `None` if it's not in the vertex map.
+    pub fn inverse(&self, i: usize) -> Option<usize> {
+        self.position(&Self::invert_row(&self[i]))
+    }
+
+    /// Returns whether the rows form a group, that is, whether they contain the
+    /// identity and are closed under composition and inversion.
+    pub fn is_group(&self) -> bool {
+        let rows: HashSet<&[usize]> = self.iter().map(Vec::as_slice).collect();
+
+        let identity: Vec<usize> = (0..self.vertex_count()).collect();
+        if !rows.contains(identity.as_slice()) {
+            return false;
+        }
+
+        self.iter().all(|a| {
+            rows.contains(Self::invert_row(a).as_slice())
+                && self
+                    .iter()
+                    .all(|b| rows.contains(Self::compose_rows(a, b).as_slice()))
+        })
+    }
+
+    /// Returns the orbit of a vertex, in the order in which the rows reach each
+    /// vertex.
+    pub fn orbit(&self, vertex: usize) -> Vec<usize> {
+        let mut seen = HashSet::new();
+        self.iter()
+            .map(|row| row[vertex])
+            .filter(|v| seen.insert(*v))
+            .collect()
+    }
+
+    /// Splits the vertices into orbits. The orbits are sorted by their smallest
+    /// vertex, which comes first in each of them.
+    pub fn orbits(&self) -> Vec<Vec<usize>> {
+        let mut orbits = Vec::new();
+        let mut checked = vec![false; self.vertex_count()];
+
+        for v in 0..self.vertex_count() {
+            if !checked[v] {
+                // We found a new orbit of vertices.
+                let mut orbit = Vec::new();
+                for row in self {
+                    let c = row[v];
+                    if !checked[c] {
+                        orbit.push(c);
+                        checked[c] = true;
+                    }
+                }
+                orbits.push(orbit);
+            }
+        }
+
+        orbits
+    }
+
+    /// Returns the distinct images of a set of vertices, each of them sorted.
+    pub fn orbit_of_set(&self, set: &[usize]) -> Vec<Vec<usize>> {
+        let mut seen = HashSet::new();
+        let mut orbit = Vec::new();
+
+        for row in self {
+            let mut image: Vec<usize> = set.iter().map(|&v| row[v]).collect();
+            image.sort_unstable();
+
+            if seen.insert(image.clone()) {
+                orbit.push(image);
+            }
+        }
+
+        orbit
+    }
+
+    /// Returns the elements that map a set of vertices onto itself.
+    pub fn stabilizer(&self, set: &[usize]) -> Self {
+        let mut sorted = set.to_vec();
+        sorted.sort_unstable();
+
+        Self(
+            self.iter()
+                .filter(|row| {
+                    let mut image: Vec<usize> = set.iter().map(|&v| row[v]).collect();
+                    image.sort_unstable();
+                    image == sorted
+                })
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Returns the elements that map a set of vertices onto itself, as
+    /// permutations of the set. The vertex `set[i]` is relabeled as `i`.
+    pub fn local_stabilizer(&self, set: &[usize]) -> Self {
+        let to_local: HashMap<usize, usize> =
+            set.iter().enumerate().map(|(idx, &v)| (v, idx)).collect();
+
+        Self(
+            self.stabilizer(set)
+                .into_iter()
+                .map(|row| set.iter().map(|&v| to_local[&row[v]]).collect())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The symmetries of a square, with vertices labeled in cyclic order.
+    fn square() -> VertexMap {
+        vec![
+            vec![0, 1, 2, 3],
+            vec![1, 2, 3, 0],
+            vec![2, 3, 0, 1],
+            vec![3, 0, 1, 2],
+            vec![0, 3, 2, 1],
+            vec![1, 0, 3, 2],
+            vec![2, 1, 0, 3],
+            vec![3, 2, 1, 0],
+        ]
+        .into()
+    }
+
+    #[test]
+    fn group() {
+        let square = square();
+        assert!(square.is_group());
+        assert_eq!(square.compose(1, 1), Some(2));
+        assert_eq!(square.inverse(1), Some(3));
+        assert_eq!(square.inverse(4), Some(4));
+
+        let rotations: VertexMap = square.iter().take(2).cloned().collect::<Vec<_>>().into();
+        assert!(!rotations.is_group());
+    }
+
+    #[test]
+    fn orbits() {
+        let square = square();
+        assert_eq!(square.orbits(), vec![vec![0, 1, 2, 3]]);
+        assert_eq!(square.orbit(2), vec![2, 3, 0, 1]);
+        assert_eq!(square.orbit_of_set(&[0, 1]).len(), 4);
+        assert_eq!(square.orbit_of_set(&[0, 2]).len(), 2);
+        assert_eq!(VertexMap::identity(3).orbits(), vec![vec![0], vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn stabilizers() {
+        let square = square();
+        assert_eq!(square.stabilizer(&[0]).len(), 2);
+        assert_eq!(square.stabilizer(&[0, 2]).len(), 4);
+
+        let edge = square.local_stabilizer(&[1, 2]);
+        assert_eq!(edge.len(), 2);
+        assert!(edge.is_group());
+        assert!(edge.contains(&vec![1, 0]));
+    }
+}
@@ -0,0 +1,103 @@
+//! Contains the code that streams the elements of a finite Weyl group using
+//! exact integer arithmetic.
+
+use std::collections::HashSet;
+
+use crate::geometry::{Matrix, Point};
+
+use super::{layer_iter::LayerIter, Group};
+
+/// An iterator over the elements of a finite
+/// [Weyl group](https://en.wikipedia.org/wiki/Weyl_group), that is, of a
+/// crystallographic Coxeter group, such as E6, E7 or E8.
+///
+/// The reflections of such a group act on the basis of fundamental weights as
+/// integer matrices, read off from the
+/// [Cartan matrix](https://en.wikipedia.org/wiki/Cartan_matrix). We build the
+/// elements as products of these, and only convert them into floating point
+/// matrices as they're returned. Unlike in a [`CoxIter`](super::cox_iter::CoxIter),
+/// elements are thus told apart exactly, and rounding errors don't pile up
+/// along long words.
+///
+/// Elements are built in layers by a [`LayerIter`]. They're told apart by
+/// where they send the sum of the fundamental weights, which lies in no
+/// mirror.
+#[derive(Clone)]
+pub struct WeylIter {
+    /// The matrix whose columns are the fundamental weights, up to a common
+    /// factor.
+    basis: Matrix<f64>,
+
+    /// The inverse of the basis matrix.
+    basis_inv: Matrix<f64>,
+
+    /// The elements of the group in the basis of fundamental weights, built in
+    /// layers.
+    layers: LayerIter<Matrix<i64>, HashSet<Point<i64>>>,
+}
+
+impl WeylIter {
+    /// Initializes a new iterator from a Cartan matrix, whose (i, j) entry is
+    /// 2(αᵢ · αⱼ) / (αⱼ · αⱼ) for the simple roots αᵢ, and a matrix whose
+    /// columns are the fundamental weights. Returns `None` if the latter isn't
+    /// invertible.
+    ///
+    /// # Safety
+    /// The Cartan matrix must be that of a finite Weyl group. Otherwise, the
+    /// iterator might not stop.
+    pub unsafe fn new(cartan: &Matrix<i64>, basis: Matrix<f64>) -> Option<Self> {
+        let dim = cartan.nrows();
+        let basis_inv = basis.clone().try_inverse()?;
+
+        // The reflection through the ith mirror takes a weight with
+        // coordinates y to y - yᵢ αᵢ.
+        let gens = (0..dim)
+            .map(|i| {
+                let mut refl = Matrix::identity(dim, dim);
+                for j in 0..dim {
+                    refl[(j, i)] -= cartan[(i, j)];
+                }
+                refl
+            })
+            .collect();
+
+        Some(Self {
+            basis,
+            basis_inv,
+            layers: LayerIter::new(gens, Matrix::identity(dim, dim)),
+        })
+    }
+
+    /// Returns the number of dimensions the group acts on.
+    pub fn dim(&self) -> usize {
+        self.basis.nrows()
+    }
+
+    /// Converts an element in the basis of fundamental weights into a matrix
+    /// in the usual basis.
+    fn to_matrix(&self, el: &Matrix<i64>) -> Matrix<f64> {
+        &self.basis * el.map(|x| x as f64) * &self.basis_inv
+    }
+
+    /// Returns the image of the sum of the fundamental weights under an
+    /// element.
+    fn image(el: &Matrix<i64>) -> Point<i64> {
+        el.column_sum()
+    }
+}
+
+impl Iterator for WeylIter {
+    type Item = Matrix<f64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let el = self.layers.next_with(Self::image)?;
+        Some(self.to_matrix(&el))
+    }
+}
+
+impl From<WeylIter> for Group<WeylIter> {
+    fn from(iter: WeylIter) -> Self {
+        // Safety: the elements of a WeylIter always form a group.
+        unsafe { Self::new(iter.dim(), iter) }
+    }
+}
@@ -24,6 +24,9 @@
 //! a look at the [`miratope`](https://crates.io/crates/miratope) crate instead.
 
 pub mod abs;
+#[cfg(feature = "big-float")]
+pub mod big_float;
+pub mod cancel;
 pub mod conc;
 pub mod cox;
 pub mod file;
@@ -35,7 +38,7 @@ use std::{collections::HashSet, error::Error, iter, ops::IndexMut};
 
 use abs::{
     flag::{Flag, FlagIter, OrientedFlag, OrientedFlagIter},
-    ranked::Ranks,
+    ranked::{ElementHash, Ranks},
     Abstract, Element, ElementList, ElementMap, Ranked,
 };
 
@@ -279,6 +282,15 @@ pub trait Polytope:
         hi_rank: usize,
         hi_idx: usize,
     ) -> Result<Option<Self>, Self::DualError> {
+        // The element re-indexes its subelements, so we need to find where the
+        // lower element ended up.
+        let lo_idx = match ElementHash::new(self.abs(), hi_rank, hi_idx)
+            .and_then(|hash| hash.index(lo_rank, lo_idx))
+        {
+            Some(lo_idx) => lo_idx,
+            None => return Ok(None),
+        };
+
         if let Some(el) = self.element(hi_rank, hi_idx) {
             el.element_fig(lo_rank, lo_idx)
         } else {
@@ -324,12 +336,17 @@ pub trait Polytope:
     }
 
     /// Returns the indices of the vertices of a Petrie polygon in cyclic
-    /// order, or `None` if it self-intersects.
+    /// order, or `None` if it self-intersects. Polytopes of rank less than 3
+    /// have no Petrie polygons.
     ///
     /// # Panics
     /// Panics if the polytope is not sorted.
     fn petrie_polygon_vertices(&self, flag: Flag) -> Option<Vec<usize>> {
         let rank = self.rank();
+        if rank < 3 {
+            return None;
+        }
+
         let mut new_flag = flag.clone();
         let first_vertex = flag[1];
         let mut vertices = Vec::new();
@@ -367,6 +384,15 @@ pub trait Polytope:
     /// `None` if this Petrie polygon is invalid.
     fn petrie_polygon_with(&mut self, flag: Flag) -> Option<Self>;
 
+    /// Builds the Petrie polygon through the [first flag](Self::first_flag)
+    /// of the polytope. Returns `None` if this Petrie polygon is invalid, or if
+    /// the polytope has rank less than 3.
+    fn petrie_polygon(&mut self) -> Option<Self> {
+        self.element_sort();
+        let flag = self.first_flag();
+        self.petrie_polygon_with(flag)
+    }
+
     /// Returns the first [`Flag`] of a polytope. This is the flag built when we
     /// start at the maximal element and repeatedly take the first subelement.
     fn first_flag(&self) -> Flag {
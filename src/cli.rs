@@ -0,0 +1,684 @@
+//! Runs Miratope without a window. A polytope is loaded from a file, a pipeline
+//! of operations is applied to it, and the results are written to OFF files.
+//! This lets batch jobs be scripted on machines without a display.
+
+use std::{path::PathBuf, str::FromStr};
+
+use crate::Concrete;
+use miratope_core::{
+    abs::Ranked,
+    conc::{
+        faceting::{
+            FacetFilter, FacetingOptions as CoreFacetingOptions, GroupEnum, Precision,
+            ProgressReporter,
+        },
+        ConcretePolytope,
+    },
+    file::{recipe::Recipe, FromFile},
+    Polytope,
+};
+
+/// The flag that makes Miratope run without a window.
+pub const HEADLESS_FLAG: &str = "--headless";
+
+/// How to use the headless mode.
+const USAGE: &str = "\
+Usage: miratope --headless <INPUT> [OPERATION]... [-o <OUTPUT>] [--orbits <ORBITS>]
+
+Loads a polytope from an OFF, MOFF or GGB file, applies the operations in
+order, and writes the results as OFF files, or as compressed MOFF files if
+the output ends in .moff. If an operation returns several polytopes, like a
+faceting, the rest of the pipeline is applied to each of them, and they're
+written to numbered files. Without an output, the OFF files are printed.
+
+With --orbits, one edge and one face of every orbit under the symmetry group
+of each result is also written, as CSV, or as JSON if the path ends in .json.
+
+The input can also be a coordinate recipe (.recipe), whose vertices are
+wrapped in their convex hull. If the recipe lists element counts, only the
+results that match them are kept.
+
+Operations:
+  dual, petrial, petrie-polygon, pyramid, prism, tegum, antiprism, ditope,
+  hosotope, recenter, unit-edge
+  truncate:<RANKS>    Uniform truncation with the given ringed ranks,
+                      separated by commas, e.g. truncate:0,1
+  facet[:<OPTIONS>]   Enumerates the facetings. Options are separated by
+                      commas, and can be any of:
+                        edge=<LEN>, min-edge=<LEN>, max-edge=<LEN>,
+                        any-edge, min-inradius=<R>, max-inradius=<R>,
+                        noble=<N>, max-per-hyperplane=<N>, chiral,
+                        exclude-hemis, only-below-vertex, uniform,
+                        compounds, min-facet-radius=<R>,
+                        max-facet-radius=<R>, min-facet-vertices=<N>,
+                        max-facet-vertices=<N>, facet-counts=<COUNTS>,
+                        precision=<BITS>
+                      The facet counts are the element counts of every
+                      facet from the vertices up, separated by slashes,
+                      e.g. facet-counts=4/6/4
+                      The precision is the number of bits of the floats
+                      the faceting is computed with, either 64, or 128 or
+                      256 if built with the big-float feature, e.g.
+                      precision=128";
+
+/// The options of a faceting, which mirror those of the faceting window.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FacetingOptions {
+    /// Whether to only use the rotation group.
+    chiral: bool,
+
+    /// Whether to facet with every edge length in turn.
+    any_single_edge_length: bool,
+
+    /// The minimum edge length.
+    min_edge_length: Option<f64>,
+
+    /// The maximum edge length.
+    max_edge_length: Option<f64>,
+
+    /// The minimum inradius of the facets.
+    min_inradius: Option<f64>,
+
+    /// The maximum inradius of the facets.
+    max_inradius: Option<f64>,
+
+    /// Whether to exclude planes passing through the origin.
+    exclude_hemis: bool,
+
+    /// Whether to only consider hyperplanes perpendicular to a vertex.
+    only_below_vertex: bool,
+
+    /// The maximum number of facet types.
+    noble: Option<usize>,
+
+    /// The maximum number of facets generated in each hyperplane.
+    max_per_hyperplane: Option<usize>,
+
+    /// Whether to only use uniform or semiuniform elements.
+    uniform: bool,
+
+    /// Whether to include trivial compounds.
+    compounds: bool,
+
+    /// The bounds on the candidate facets.
+    facet_filter: FacetFilter,
+
+    /// The floats the faceting is computed with.
+    precision: Precision,
+}
+
+impl Default for FacetingOptions {
+    fn default() -> Self {
+        Self {
+            chiral: false,
+            any_single_edge_length: false,
+            min_edge_length: Some(1.0),
+            max_edge_length: Some(1.0),
+            min_inradius: None,
+            max_inradius: None,
+            exclude_hemis: false,
+            only_below_vertex: false,
+            noble: None,
+            max_per_hyperplane: None,
+            uniform: false,
+            compounds: false,
+            facet_filter: FacetFilter::default(),
+            precision: Precision::default(),
+        }
+    }
+}
+
+impl FromStr for FacetingOptions {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        /// Parses the value of an option.
+        fn parse<T: FromStr>(key: &str, value: Option<&str>) -> Result<T, String> {
+            let value = value.ok_or_else(|| format!("faceting option {} needs a value", key))?;
+            value
+                .parse()
+                .map_err(|_| format!("invalid value {} for faceting option {}", value, key))
+        }
+
+        let mut options = Self::default();
+
+        for option in s.split(',').filter(|option| !option.is_empty()) {
+            let (key, value) = match option.split_once('=') {
+                Some((key, value)) => (key, Some(value)),
+                None => (option, None),
+            };
+
+            match key {
+                "edge" => {
+                    let len = parse(key, value)?;
+                    options.min_edge_length = Some(len);
+                    options.max_edge_length = Some(len);
+                }
+                "min-edge" => options.min_edge_length = Some(parse(key, value)?),
+                "max-edge" => options.max_edge_length = Some(parse(key, value)?),
+                "any-edge" => {
+                    options.any_single_edge_length = true;
+                    options.min_edge_length = None;
+                    options.max_edge_length = None;
+                }
+                "min-inradius" => options.min_inradius = Some(parse(key, value)?),
+                "max-inradius" => options.max_inradius = Some(parse(key, value)?),
+                "noble" => options.noble = Some(parse(key, value)?),
+                "max-per-hyperplane" => options.max_per_hyperplane = Some(parse(key, value)?),
+                "chiral" => options.chiral = true,
+                "exclude-hemis" => options.exclude_hemis = true,
+                "only-below-vertex" => options.only_below_vertex = true,
+                "uniform" => options.uniform = true,
+                "compounds" => options.compounds = true,
+                "min-facet-radius" => {
+                    options.facet_filter.min_circumradius = Some(parse(key, value)?)
+                }
+                "max-facet-radius" => {
+                    options.facet_filter.max_circumradius = Some(parse(key, value)?)
+                }
+                "min-facet-vertices" => {
+                    options.facet_filter.min_vertices = Some(parse(key, value)?)
+                }
+                "max-facet-vertices" => {
+                    options.facet_filter.max_vertices = Some(parse(key, value)?)
+                }
+                "facet-counts" => {
+                    let counts = parse::<String>(key, value)?
+                        .split('/')
+                        .map(|count| parse(key, Some(count)))
+                        .collect::<Result<_, _>>()?;
+                    options.facet_filter.element_counts = Some(counts);
+                }
+                "precision" => {
+                    options.precision = match parse::<u32>(key, value)? {
+                        64 => Precision::Double,
+                        #[cfg(feature = "big-float")]
+                        128 => Precision::Quadruple,
+                        #[cfg(feature = "big-float")]
+                        256 => Precision::Octuple,
+                        bits => return Err(format!("unsupported precision of {} bits", bits)),
+                    }
+                }
+                _ => return Err(format!("unknown faceting option {}", key)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// An operation in the pipeline.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+    /// Takes the dual with respect to the unit sphere.
+    Dual,
+
+    /// Takes the Petrial.
+    Petrial,
+
+    /// Builds the Petrie polygon through the first flag.
+    PetriePolygon,
+
+    /// Builds a pyramid.
+    Pyramid,
+
+    /// Builds a prism.
+    Prism,
+
+    /// Builds a tegum.
+    Tegum,
+
+    /// Builds an antiprism.
+    Antiprism,
+
+    /// Builds the ditope.
+    Ditope,
+
+    /// Builds the hosotope.
+    Hosotope,
+
+    /// Moves the gravicenter to the origin.
+    Recenter,
+
+    /// Scales the polytope so that its first edge has unit length.
+    UnitEdge,
+
+    /// Truncates uniformly, with the given ringed ranks.
+    Truncate(Vec<usize>),
+
+    /// Enumerates the facetings.
+    Facet(FacetingOptions),
+}
+
+impl FromStr for Operation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, args) = match s.split_once(':') {
+            Some((name, args)) => (name, Some(args)),
+            None => (s, None),
+        };
+
+        Ok(match (name, args) {
+            ("dual", None) => Self::Dual,
+            ("petrial", None) => Self::Petrial,
+            ("petrie-polygon", None) => Self::PetriePolygon,
+            ("pyramid", None) => Self::Pyramid,
+            ("prism", None) => Self::Prism,
+            ("tegum", None) => Self::Tegum,
+            ("antiprism", None) => Self::Antiprism,
+            ("ditope", None) => Self::Ditope,
+            ("hosotope", None) => Self::Hosotope,
+            ("recenter", None) => Self::Recenter,
+            ("unit-edge", None) => Self::UnitEdge,
+            ("truncate", Some(ranks)) => Self::Truncate(
+                ranks
+                    .split(',')
+                    .map(|rank| rank.parse().map_err(|_| format!("invalid rank {}", rank)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            ("facet", args) => Self::Facet(args.unwrap_or_default().parse()?),
+            _ => return Err(format!("unknown operation {}", s)),
+        })
+    }
+}
+
+impl Operation {
+    /// Applies the operation to a named polytope, returning the named results.
+    fn apply(&self, mut poly: Concrete, name: &str) -> Result<Vec<(Concrete, String)>, String> {
+        let named = |prefix: &str| format!("{} of {}", prefix, name);
+
+        let res = match self {
+            Self::Dual => {
+                poly.try_dual_mut().map_err(|err| format!("dual failed: {}", err))?;
+                (poly, named("Dual"))
+            }
+            Self::Petrial => {
+                if !poly.petrial_mut() {
+                    return Err("petrial failed".to_string());
+                }
+                (poly, named("Petrial"))
+            }
+            Self::PetriePolygon => (
+                poly.petrie_polygon()
+                    .ok_or_else(|| "petrie polygon failed".to_string())?,
+                named("Petrie polygon"),
+            ),
+            Self::Pyramid => (poly.pyramid(), named("Pyramid")),
+            Self::Prism => (poly.prism(), named("Prism")),
+            Self::Tegum => (poly.tegum(), named("Tegum")),
+            Self::Antiprism => (
+                poly.try_antiprism()
+                    .map_err(|err| format!("antiprism failed: {}", err))?,
+                named("Antiprism"),
+            ),
+            Self::Ditope => {
+                poly.ditope_mut();
+                (poly, named("Ditope"))
+            }
+            Self::Hosotope => {
+                poly.hosotope_mut();
+                (poly, named("Hosotope"))
+            }
+            Self::Recenter => {
+                poly.recenter();
+                (poly, name.to_string())
+            }
+            Self::UnitEdge => {
+                let len = poly
+                    .edge_len(0)
+                    .ok_or_else(|| "the polytope has no edges".to_string())?;
+                poly.scale(1.0 / len);
+                (poly, name.to_string())
+            }
+            Self::Truncate(ranks) => {
+                if let Some(&rank) = ranks.iter().find(|&&rank| rank + 1 >= poly.rank()) {
+                    return Err(format!("cannot ring rank {} of a rank {} polytope", rank, poly.rank()));
+                }
+                (poly.uniform_truncate(ranks.clone()), named("Truncated"))
+            }
+            Self::Facet(options) => return Ok(facet(poly, name, options)),
+        };
+
+        Ok(vec![res])
+    }
+}
+
+/// Enumerates the facetings of a polytope. Progress is reported on the
+/// standard error, so that it doesn't get mixed with printed OFF files.
+fn facet(mut poly: Concrete, name: &str, options: &FacetingOptions) -> Vec<(Concrete, String)> {
+    let mut progress = ProgressReporter::callback(|report| eprintln!("{}", report.message.trim()));
+    let vertices = poly.vertices.clone();
+
+    let core_options = CoreFacetingOptions {
+        any_single_edge_length: options.any_single_edge_length,
+        min_edge_length: options.min_edge_length,
+        max_edge_length: options.max_edge_length,
+        min_inradius: options.min_inradius,
+        max_inradius: options.max_inradius,
+        exclude_hemis: options.exclude_hemis,
+        only_below_vertex: options.only_below_vertex,
+        noble: options.noble,
+        max_per_hyperplane: options.max_per_hyperplane,
+        facet_filter: options.facet_filter.clone(),
+        uniform: options.uniform,
+        include_compounds: options.compounds,
+        mark_fissary: true,
+        label_facets: true,
+        save: true,
+        precision: options.precision,
+        ..Default::default()
+    };
+
+    poly.faceting(vertices, GroupEnum::Chiral(options.chiral), &core_options, &mut progress)
+    .into_iter()
+    .enumerate()
+    .map(|(idx, (faceting, label))| {
+        let label = label.unwrap_or_else(|| format!("Faceting {}", idx + 1));
+        (faceting, format!("{} of {}", label, name))
+    })
+    .collect()
+}
+
+/// The parsed command line of the headless mode.
+#[derive(Debug, PartialEq)]
+struct Args {
+    /// The file to load.
+    input: PathBuf,
+
+    /// The operations to apply, in order.
+    operations: Vec<Operation>,
+
+    /// The file to write to, if any.
+    output: Option<PathBuf>,
+
+    /// The file to write the edge and face orbits to, if any.
+    orbits: Option<PathBuf>,
+}
+
+impl Args {
+    /// Parses the arguments following the headless flag.
+    fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut input = None;
+        let mut operations = Vec::new();
+        let mut output = None;
+        let mut orbits = None;
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-o" | "--output" => {
+                    output = Some(args.next().ok_or("missing output path")?.into());
+                }
+                "--orbits" => {
+                    orbits = Some(args.next().ok_or("missing orbits path")?.into());
+                }
+                _ if input.is_none() => input = Some(arg.into()),
+                _ => operations.push(arg.parse()?),
+            }
+        }
+
+        Ok(Self {
+            input: input.ok_or("missing input file")?,
+            operations,
+            output,
+            orbits,
+        })
+    }
+}
+
+/// Returns the path to write the result with a given index to. If there's
+/// more than one result, they're numbered.
+fn output_path(output: &std::path::Path, idx: usize, count: usize) -> PathBuf {
+    numbered_path(output, idx, count, "off")
+}
+
+/// Returns the path to write the file with a given index to, numbering them if
+/// there's more than one.
+fn numbered_path(output: &std::path::Path, idx: usize, count: usize, default_ext: &str) -> PathBuf {
+    if count == 1 {
+        return output.to_path_buf();
+    }
+
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = output.extension().map_or(default_ext.into(), |ext| ext.to_string_lossy());
+    output.with_file_name(format!("{}_{}.{}", stem, idx + 1, ext))
+}
+
+/// Runs the headless mode with the arguments following the headless flag.
+pub fn run<I: IntoIterator<Item = String>>(args: I) -> Result<(), String> {
+    let args: Vec<String> = args.into_iter().collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let args = Args::parse(args).map_err(|err| format!("{}\n\n{}", err, USAGE))?;
+    let mut name = args
+        .input
+        .file_stem()
+        .map_or_else(|| "polytope".to_string(), |stem| stem.to_string_lossy().into_owned());
+
+    // Recipes are kept around to check the results against.
+    let recipe = if args.input.extension().is_some_and(|ext| ext == "recipe") {
+        let src = std::fs::read_to_string(&args.input).map_err(|err| err.to_string())?;
+        let recipe = Recipe::parse(&src).map_err(|err| format!("recipe error: {}", err))?;
+        if let Some(recipe_name) = &recipe.name {
+            name = recipe_name.clone();
+        }
+        Some(recipe)
+    } else {
+        None
+    };
+
+    let poly = match &recipe {
+        Some(recipe) => recipe
+            .build()
+            .map_err(|err| format!("recipe error: {}", err))?,
+        None => Concrete::from_path(&args.input).map_err(|err| err.to_string())?,
+    };
+
+    let mut polies = vec![(poly, name)];
+    for operation in &args.operations {
+        let mut results = Vec::new();
+        for (poly, name) in polies {
+            results.extend(operation.apply(poly, &name)?);
+        }
+        polies = results;
+    }
+
+    eprintln!("{} polytope(s) built.", polies.len());
+
+    // Only keeps the polytopes with the element counts of the recipe.
+    if let Some(recipe) = recipe.as_ref().filter(|recipe| recipe.counts.is_some()) {
+        polies.retain(|(poly, name)| match recipe.check(poly) {
+            Ok(()) => {
+                eprintln!("{} matches the expected element counts.", name);
+                true
+            }
+            Err(err) => {
+                eprintln!("{} doesn't match: {}.", name, err);
+                false
+            }
+        });
+
+        if polies.is_empty() {
+            return Err("no polytope matches the expected element counts".to_string());
+        }
+    }
+    for (idx, (poly, name)) in polies.iter().enumerate() {
+        if let Some(orbits) = &args.orbits {
+            let path = numbered_path(orbits, idx, polies.len(), "csv");
+            poly.clone()
+                .orbits_to_path(&path)
+                .map_err(|err| format!("could not write the orbits to {}: {}", path.display(), err))?;
+            eprintln!("Wrote the orbits of {} to {}.", name, path.display());
+        }
+
+        match &args.output {
+            Some(output) => {
+                let path = output_path(output, idx, polies.len());
+                let result = if path.extension().is_some_and(|ext| ext == "moff") {
+                    poly.to_moff_path(&path).map_err(|err| err.to_string())
+                } else {
+                    poly.to_path(&path, Default::default()).map_err(|err| err.to_string())
+                };
+                result.map_err(|err| format!("could not write {}: {}", path.display(), err))?;
+                eprintln!("Wrote {} to {}.", name, path.display());
+            }
+            None => {
+                let off = poly
+                    .to_off(Default::default())
+                    .map_err(|err| format!("could not write {}: {}", name, err))?;
+                println!("# {}\n{}", name, off);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses a command line, given as a string.
+    fn parse(args: &str) -> Result<Args, String> {
+        Args::parse(args.split_whitespace().map(String::from))
+    }
+
+    /// Parses a pipeline with an output.
+    #[test]
+    fn pipeline() {
+        assert_eq!(
+            parse("cube.off dual truncate:0,1 -o out.off").unwrap(),
+            Args {
+                input: "cube.off".into(),
+                operations: vec![Operation::Dual, Operation::Truncate(vec![0, 1])],
+                output: Some("out.off".into()),
+                orbits: None,
+            }
+        );
+
+        // The output can go anywhere, and no operations are needed.
+        assert_eq!(
+            parse("--output out.moff cube.off").unwrap(),
+            Args {
+                input: "cube.off".into(),
+                operations: Vec::new(),
+                output: Some("out.moff".into()),
+                orbits: None,
+            }
+        );
+
+        // The orbits can be written along with the polytope.
+        assert_eq!(
+            parse("cube.off --orbits orbits.json").unwrap(),
+            Args {
+                input: "cube.off".into(),
+                operations: Vec::new(),
+                output: None,
+                orbits: Some("orbits.json".into()),
+            }
+        );
+    }
+
+    /// Command lines without an input or an output path fail.
+    #[test]
+    fn missing_args() {
+        assert!(parse("").is_err());
+        assert!(parse("-o out.off").is_err());
+        assert!(parse("cube.off dual -o").is_err());
+        assert!(parse("cube.off --orbits").is_err());
+    }
+
+    /// Parses every operation without arguments.
+    #[test]
+    fn operations() {
+        for (name, operation) in [
+            ("dual", Operation::Dual),
+            ("petrial", Operation::Petrial),
+            ("petrie-polygon", Operation::PetriePolygon),
+            ("pyramid", Operation::Pyramid),
+            ("prism", Operation::Prism),
+            ("tegum", Operation::Tegum),
+            ("antiprism", Operation::Antiprism),
+            ("ditope", Operation::Ditope),
+            ("hosotope", Operation::Hosotope),
+            ("recenter", Operation::Recenter),
+            ("unit-edge", Operation::UnitEdge),
+            ("facet", Operation::Facet(FacetingOptions::default())),
+        ] {
+            assert_eq!(name.parse::<Operation>().unwrap(), operation);
+        }
+    }
+
+    /// Unknown operations, and known ones with the wrong arguments, fail.
+    #[test]
+    fn unknown_operations() {
+        assert!("stellate".parse::<Operation>().is_err());
+        assert!("dual:1".parse::<Operation>().is_err());
+        assert!("truncate".parse::<Operation>().is_err());
+        assert!("truncate:0,x".parse::<Operation>().is_err());
+        assert!(parse("cube.off dual stellate").is_err());
+    }
+
+    /// Parses faceting options.
+    #[test]
+    fn faceting_options() {
+        let options: FacetingOptions = "edge=2,noble=3,chiral,facet-counts=4/6/4".parse().unwrap();
+        assert_eq!(options.min_edge_length, Some(2.0));
+        assert_eq!(options.max_edge_length, Some(2.0));
+        assert_eq!(options.noble, Some(3));
+        assert!(options.chiral);
+        assert_eq!(options.facet_filter.element_counts, Some(vec![4, 6, 4]));
+
+        let options: FacetingOptions = "any-edge,max-facet-vertices=12".parse().unwrap();
+        assert!(options.any_single_edge_length);
+        assert_eq!(options.min_edge_length, None);
+        assert_eq!(options.facet_filter.max_vertices, Some(12));
+
+        let options: FacetingOptions = "precision=64".parse().unwrap();
+        assert_eq!(options.precision, Precision::Double);
+
+        #[cfg(feature = "big-float")]
+        {
+            let options: FacetingOptions = "precision=128".parse().unwrap();
+            assert_eq!(options.precision, Precision::Quadruple);
+        }
+
+        assert_eq!("".parse::<FacetingOptions>().unwrap(), FacetingOptions::default());
+    }
+
+    /// Bad faceting options fail.
+    #[test]
+    fn bad_faceting_options() {
+        for options in [
+            "edge",
+            "edge=one",
+            "noble=-1",
+            "noble=1.5",
+            "max-per-hyperplane=",
+            "facet-counts=4/x/4",
+            "precision=100",
+            "hemis",
+        ] {
+            assert!(options.parse::<FacetingOptions>().is_err(), "{}", options);
+        }
+    }
+
+    /// Missing input files fail.
+    #[test]
+    fn missing_file() {
+        let input = std::env::temp_dir().join("miratope_missing_file.off");
+        assert!(run([input.to_string_lossy().into_owned()]).is_err());
+    }
+
+    /// Several results get numbered files.
+    #[test]
+    fn output_paths() {
+        let output = std::path::Path::new("dir/out.moff");
+        assert_eq!(output_path(output, 0, 1), output);
+        assert_eq!(output_path(output, 1, 3), std::path::Path::new("dir/out_2.moff"));
+    }
+}
@@ -11,7 +11,7 @@
 
 //! A tool for building and visualizing polytopes. Still in alpha development.
 
-use bevy::prelude::*;
+use bevy::{camera::visibility::RenderLayers, prelude::*};
 use bevy_egui::EguiPlugin;
 use bevy_render::{
     batching::gpu_preprocessing::{GpuPreprocessingMode, GpuPreprocessingSupport},
@@ -21,11 +21,14 @@ use miratope_core::file::FromFile;
 
 use ui::{
     camera::{CameraInputEvent, ProjectionType},
+    compare::COMPARE_LAYER,
+    stats::StatsCache,
     MiratopePlugins,
 };
 
 use crate::mesh::Renderable;
 
+mod cli;
 mod mesh;
 mod ui;
 
@@ -59,9 +62,19 @@ type Hyperplane = miratope_core::geometry::Hyperplane<f64>;
 /// The default epsilon value throughout the application.
 const EPS: Float = <Float as miratope_core::float::Float>::EPS;
 
-/// Loads all of the necessary systems for the application to run.
+/// Loads all of the necessary systems for the application to run, or runs
+/// the headless mode if asked to.
 fn main() {
     unsafe { std::env::set_var("RUST_BACKTRACE", "full"); }
+
+    let mut args = std::env::args().skip(1).peekable();
+    if args.next_if(|arg| arg == cli::HEADLESS_FLAG).is_some() {
+        if let Err(err) = cli::run(args) {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
     
     let mut app = App::new();
     app
@@ -119,7 +132,7 @@ fn setup(
     commands
         // Mesh
         .spawn((
-            Mesh3d(meshes.add(poly.mesh(ProjectionType::Perspective))),
+            Mesh3d(meshes.add(poly.mesh(ProjectionType::Perspective, false))),
             MeshMaterial3d(mesh_material),
             Transform::default(),
             Visibility::Visible,
@@ -134,7 +147,7 @@ fn setup(
             ));
         })
         // Polytope
-        .insert(poly);
+        .insert((poly, StatsCache::default()));
 
     // Camera anchor
     commands
@@ -147,18 +160,22 @@ fn setup(
                 Msaa::Sample4,
 
             ));
-            // Light sources
+            // Light sources, which also light the side by side view.
+            let layers = RenderLayers::from_layers(&[0, COMPARE_LAYER]);
             cb.spawn((
                 Transform::from_translation(Vec3::new(-5., 5., 5.)),
                 PointLight::default(),
+                layers.clone(),
             ));
             cb.spawn((
                 Transform::from_translation(Vec3::new(5., 5., 5.)),
                 PointLight::default(),
+                layers.clone(),
             ));
             cb.spawn((
                 Transform::from_translation(Vec3::new(0., 5., -5.)),
                 PointLight::default(),
+                layers,
             ));
         });
 }
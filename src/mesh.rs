@@ -1,158 +1,36 @@
 //! Contains the methods that take a polytope and turn it into a mesh.
 
-use std::collections::HashMap;
+use std::{fmt::Write, io, path::Path};
 
 use crate::ui::camera::ProjectionType;
-use crate::{Concrete, Float, Point, EPS};
+use crate::{Concrete, Point, EPS};
 
 use bevy::{
-    prelude::Mesh,
-    mesh::{Indices, PrimitiveTopology},
+    color::{Color, ColorToComponents, LinearRgba},
+    prelude::{Mesh, Vec3},
+    mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
 };
 use bevy::asset::RenderAssetUsages;
-use lyon::{math::point, path::Path, tessellation::*};
-use miratope_core::conc::cycle::{Cycle, CycleList};
 use miratope_core::{
-    abs::{ElementList, Ranked},
+    abs::Ranked,
     conc::ConcretePolytope,
-    geometry::{Subspace, Vector},
+    geometry::Vector,
 };
 
+use serde::{Deserialize, Serialize};
 use vec_like::*;
 
-/// Attempts to turn the cycle into a 2D path, which can then be given to
-/// the tessellator. Uses the specified vertex list to grab the coordinates
-/// of the vertices on the path.
-///
-/// If the cycle isn't 2D, we return `None`.
-pub fn path(cycle: &Cycle, vertices: &[Point]) -> Option<Path> {
-    let mut builder = Path::builder();
-    let cycle_iter = cycle.iter().map(|&idx| &vertices[idx]);
-
-    // We don't bother with any polygons that aren't in 2D space.
-    let s = Subspace::from_points_with(cycle_iter.clone(), 2)?;
-    if s.rank() != 2 {
-        return None
-    }
-
-    let mut flat_points = cycle_iter.map(|p| s.flatten(&p));
-
-    let path_point = |v: &Point| point(v[0] as f32, v[1] as f32);
-
-    // We build a path from the polygon.
-    let v = flat_points.next().unwrap();
-    builder.begin(path_point(&v));
-
-    for v in flat_points {
-        builder.line_to(path_point(&v));
-    }
-
-    builder.end(true);
-
-    Some(builder.build())
-}
-
-/// Represents a triangulation of the faces of a [`Concrete`]. It stores the
-/// vertex indices that make up the triangulation of the polytope, as well as
-/// the extra vertices that may be needed to represent it.
-struct Triangulation {
-    /// Extra vertices that might be needed for the triangulation.
-    extra_vertices: Vec<Point>,
-
-    /// Indices of the vertices that make up the triangles.
-    triangles: Vec<u32>,
-}
-
-impl Triangulation {
-    /// Creates a new triangulation from a polytope.
-    fn new(polytope: &Concrete) -> Self {
-        let mut extra_vertices = Vec::new();
-        let mut triangles = Vec::new();
-        let empty_els = ElementList::new();
-
-        // Either returns a reference to the element list of a given rank, or
-        // returns a reference to an empty element list.
-        let elements_or = |r| polytope.get_element_list(r).unwrap_or(&empty_els);
-
-        let edges = elements_or(2);
-        let faces = elements_or(3);
-
-        let concrete_vertex_len = polytope.vertices.len() as u32;
-
-        // We render each face separately.
-        for face in faces {
-            // We tesselate this path.
-            let cycles = CycleList::from_edges(face.subs.iter().map(|&i| &edges[i].subs));
-            for cycle in cycles {
-                if let Some(path) = path(&cycle, &polytope.vertices) {
-                    let mut geometry: VertexBuffers<_, u32> = VertexBuffers::new();
-
-                    // Configures all of the options of the tessellator.
-                    FillTessellator::new()
-                        .tessellate_with_ids(
-                            path.id_iter(),
-                            &path,
-                            None,
-                            &FillOptions::with_fill_rule(Default::default(), FillRule::NonZero)
-                                .with_tolerance(EPS as f32),
-                            &mut BuffersBuilder::new(&mut geometry, |vertex: FillVertex<'_>| {
-                                vertex.sources().next().unwrap()
-                            }),
-                        )
-                        .unwrap();
-
-                    // Maps EndpointIds to the indices in the original vertex list.
-                    let mut id_to_idx = Vec::new();
-                    for idx in cycle {
-                        id_to_idx.push(idx);
-                    }
-
-                    // We map the output vertices to the original ones, and add any
-                    // extra vertices that may be needed.
-                    let mut vertex_hash = HashMap::new();
-
-                    for (new_id, vertex_source) in geometry.vertices.into_iter().enumerate() {
-                        let new_id = new_id as u32;
-
-                        match vertex_source {
-                            // This is one of the concrete vertices of the polytope.
-                            VertexSource::Endpoint { id } => {
-                                vertex_hash.insert(new_id, id_to_idx[id.to_usize()] as u32);
-                            }
-
-                            // This is a new vertex that has been added to the tesselation.
-                            VertexSource::Edge { from, to, t } => {
-                                let from = &polytope.vertices[id_to_idx[from.to_usize()]];
-                                let to = &polytope.vertices[id_to_idx[to.to_usize()]];
-
-                                let t = t as Float;
-                                let p = from * (1.0 - t) + to * t;
-
-                                vertex_hash
-                                    .insert(new_id, concrete_vertex_len + extra_vertices.len() as u32);
-
-                                extra_vertices.push(p);
-                            }
-                        }
-                    }
-
-                    // Add all of the new indices we've found onto the triangle vector.
-                    for new_idx in geometry
-                        .indices
-                        .iter()
-                        .map(|idx| *vertex_hash.get(idx).unwrap())
-                    {
-                        triangles.push(new_idx);
-                    }
-                }
-            }
-        }
-
-        Self {
-            extra_vertices,
-            triangles,
-        }
-    }
+/// How the normals of a mesh are computed, which determines how its faces are
+/// shaded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NormalMode {
+    /// Every face gets a single normal, perpendicular to it.
+    #[default]
+    Flat,
+
+    /// Every vertex gets a normal pointing away from the origin. This only
+    /// looks right for polytopes that are star-shaped around the origin.
+    Radial,
 }
 
 /// Generates normals from a set of vertices by just projecting radially from
@@ -172,6 +50,88 @@ fn normals(vertices: &[[f32; 3]]) -> Vec<[f32; 3]> {
         .collect()
 }
 
+/// Gives every face of a mesh with duplicated vertices a single normal, up to
+/// sign. The triangles of a face might be wound either way, so their normals
+/// are flipped to agree with each other before being added up, and the result
+/// is flipped back to agree with the winding of each triangle, which is what
+/// two-sided lighting and culling go by. Since orientable polyhedra come with
+/// their triangles wound outwards, their normals end up pointing outwards.
+fn flat_normals(mesh: &mut Mesh, faces: &[usize]) {
+    let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        return;
+    };
+
+    let triangle_normals: Vec<Vec3> = positions
+        .chunks_exact(3)
+        .map(|triangle| {
+            let [p0, p1, p2] = [0, 1, 2].map(|i| Vec3::from_array(triangle[i]));
+            (p1 - p0).cross(p2 - p0)
+        })
+        .collect();
+
+    let face_count = faces.iter().max().map_or(0, |&face| face + 1);
+    let mut face_normals = vec![Vec3::ZERO; face_count];
+    for (&normal, &face) in triangle_normals.iter().zip(faces) {
+        let sum = &mut face_normals[face];
+        if sum.dot(normal) < 0.0 {
+            *sum -= normal;
+        } else {
+            *sum += normal;
+        }
+    }
+
+    let normals: Vec<[f32; 3]> = triangle_normals
+        .iter()
+        .zip(faces)
+        .flat_map(|(&normal, &face)| {
+            let face_normal = face_normals[face].normalize_or_zero();
+            let normal = if face_normal.dot(normal) < 0.0 { -face_normal } else { face_normal };
+            [normal.to_array(); 3]
+        })
+        .collect();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+}
+
+/// Returns a distinct color for every index, for coloring classes of elements.
+pub fn class_color(idx: usize) -> Color {
+    // Walks around the hue circle by the golden angle.
+    Color::hsl((idx as f32 * 137.507_77) % 360.0, 0.75, 0.55)
+}
+
+/// Adds some extra segments to a wireframe, given by pairs of consecutive
+/// points. If the wireframe is colored, the segments are drawn in white.
+pub fn add_segments(mesh: &mut Mesh, points: Vec<[f32; 3]>) {
+    let start = mesh.count_vertices() as u32;
+    let count = points.len();
+
+    let mut indices: Vec<u32> = mesh.indices().map(|indices| indices.iter().map(|i| i as u32).collect()).unwrap_or_default();
+    indices.extend(start..start + count as u32);
+
+    if let Some(VertexAttributeValues::Float32x3(normals)) = mesh.attribute_mut(Mesh::ATTRIBUTE_NORMAL) {
+        normals.extend(self::normals(&points));
+    }
+    if let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute_mut(Mesh::ATTRIBUTE_UV_0) {
+        uvs.extend(std::iter::repeat_n([0.0; 2], count));
+    }
+    if let Some(VertexAttributeValues::Float32x4(colors)) = mesh.attribute_mut(Mesh::ATTRIBUTE_COLOR) {
+        colors.extend(std::iter::repeat_n([1.0; 4], count));
+    }
+    if let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION) {
+        positions.extend(points);
+    }
+
+    mesh.insert_indices(Indices::U32(indices));
+}
+
+/// The number of segments each edge is split into in the stereographic
+/// projection, so that it renders as an arc.
+const ARC_SEGMENTS: usize = 16;
+
+/// The least distance from the projection pole to a point on the sphere, as a
+/// fraction of its radius. Points closer to the pole would be sent arbitrarily
+/// far away.
+const POLE_CUTOFF: f64 = 0.01;
+
 /// Returns an empty mesh.
 fn empty_mesh() -> Mesh {
     Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::default())
@@ -182,7 +142,7 @@ fn empty_mesh() -> Mesh {
 }
 
 /// Gets the coordinates of the vertices, after projecting down into 3D.
-fn vertex_coords<'a, I: Iterator<Item = &'a Point>>(
+pub fn vertex_coords<'a, I: Iterator<Item = &'a Point>>(
     poly: &Concrete,
     vertices: I,
     projection_type: ProjectionType,
@@ -196,6 +156,29 @@ fn vertex_coords<'a, I: Iterator<Item = &'a Point>>(
     if projection_type.is_orthogonal() || dim <= 3 {
         vertices.map(|p| [0, 1, 2].map(|i| coord(p, i) as f32)).collect()
     }
+    // We project onto the sphere through the farthest vertex, and then
+    // stereographically from its point on the last axis, once per dimension.
+    else if projection_type.is_stereographic() {
+        let radius = poly.vertices.iter().map(|v| v.norm()).fold(EPS, f64::max);
+
+        vertices
+            .map(|p| {
+                let mut p = p.clone();
+                while p.len() > 3 {
+                    let norm = p.norm();
+                    if norm > EPS {
+                        p *= radius / norm;
+                    }
+
+                    let last = p.len() - 1;
+                    let factor = radius / (radius - p[last]).max(radius * POLE_CUTOFF);
+                    p = p.rows(0, last) * factor;
+                }
+
+                [0, 1, 2].map(|i| coord(&p, i) as f32)
+            })
+            .collect()
+    }
     // Else, we project it down.
     else {
         // Distance from the projection planes.
@@ -215,10 +198,76 @@ fn vertex_coords<'a, I: Iterator<Item = &'a Point>>(
     }
 }
 
+/// Builds the wireframe of a polytope in the stereographic projection. Every
+/// edge is split into [`ARC_SEGMENTS`] segments before being projected, so
+/// that it renders as an arc. Faces aren't subdivided, so they still render
+/// as flat triangles between the projected vertices.
+fn arc_wireframe(poly: &Concrete, edge_colors: Option<&[Color]>) -> Mesh {
+    let Some(edges) = poly.get_element_list(2) else {
+        return empty_mesh();
+    };
+
+    let mut points = Vec::with_capacity(edges.len() * (ARC_SEGMENTS + 1));
+    let mut indices = Vec::with_capacity(edges.len() * ARC_SEGMENTS * 2);
+    for edge in edges {
+        let [p0, p1] = [edge.subs[0], edge.subs[1]].map(|v| &poly.vertices[v]);
+        let start = points.len() as u32;
+
+        points.extend((0..=ARC_SEGMENTS).map(|k| p0 + (p1 - p0) * (k as f64 / ARC_SEGMENTS as f64)));
+        indices.extend((start..start + ARC_SEGMENTS as u32).flat_map(|i| [i, i + 1]));
+    }
+
+    let vertices = vertex_coords(poly, points.iter(), ProjectionType::Stereographic);
+    let vertex_count = vertices.len();
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals(&vertices))
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vertices)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0; 2]; vertex_count])
+        .with_inserted_indices(Indices::U32(indices));
+
+    if let Some(edge_colors) = edge_colors {
+        let colors: Vec<[f32; 4]> = edge_colors
+            .iter()
+            .flat_map(|&color| [LinearRgba::from(color).to_f32_array(); ARC_SEGMENTS + 1])
+            .collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+
+    mesh
+}
+
+/// How a single face of a mesh is drawn.
+#[derive(Clone, Copy, PartialEq)]
+pub enum FaceStyle {
+    /// The face is drawn in the mesh color.
+    Default,
+
+    /// The face is drawn in the mesh color, tinted by another.
+    Colored(Color),
+
+    /// The face isn't drawn.
+    Hidden,
+}
+
 /// A trait for a polytope for which we can build a mesh.
 pub trait Renderable: ConcretePolytope {
-    /// Builds the mesh of a polytope.
-    fn mesh(&self, projection_type: ProjectionType) -> Mesh {
+    /// Builds the mesh of a polytope. Faces lying on the same plane as others
+    /// can be slightly offset, so that they don't flicker.
+    fn mesh(&self, projection_type: ProjectionType, offset_coplanar: bool) -> Mesh {
+        self.styled_mesh(projection_type, offset_coplanar, NormalMode::default(), |_| {
+            FaceStyle::Default
+        })
+    }
+
+    /// Builds the mesh of a polytope, drawing each face in the style given by
+    /// its index, and with normals computed in the given way.
+    fn styled_mesh(
+        &self,
+        projection_type: ProjectionType,
+        offset_coplanar: bool,
+        normal_mode: NormalMode,
+        style: impl Fn(usize) -> FaceStyle,
+    ) -> Mesh {
         // If there's no vertices, returns an empty mesh.
         if self.vertex_count() == 0 {
             return empty_mesh();
@@ -226,7 +275,7 @@ pub trait Renderable: ConcretePolytope {
 
         // Triangulates the polytope's faces, projects the vertices of both the
         // polytope and the triangulation.
-        let triangulation = Triangulation::new(self.con());
+        let triangulation = self.con().triangulation();
         let vertices = vertex_coords(
             self.con(),
             self.vertices()
@@ -242,7 +291,69 @@ pub trait Renderable: ConcretePolytope {
             .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vertices)
             .with_inserted_indices(Indices::U32(triangulation.triangles));
         mesh.duplicate_vertices();
-        mesh.compute_flat_normals();
+
+        if offset_coplanar {
+            let layers = self.con().coplanar_face_layers();
+
+            if let Some(VertexAttributeValues::Float32x3(positions)) =
+                mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+            {
+                // The offset is proportional to the size of the polytope.
+                let size = positions
+                    .iter()
+                    .flatten()
+                    .fold(0.0f32, |size, c| size.max(c.abs()));
+                let delta = size * 1e-3;
+
+                // After duplicating vertices, every triangle has its own
+                // three consecutive vertices.
+                for (triangle, &face) in positions.chunks_exact_mut(3).zip(&triangulation.faces) {
+                    let layer = layers[face];
+                    if layer == 0 {
+                        continue;
+                    }
+
+                    let [p0, p1, p2] = [0, 1, 2].map(|i| Vec3::from_array(triangle[i]));
+                    let offset = (p1 - p0).cross(p2 - p0).normalize_or_zero() * delta * layer as f32;
+
+                    for p in triangle {
+                        *p = (Vec3::from_array(*p) + offset).to_array();
+                    }
+                }
+            }
+        }
+
+        // Hidden triangles are collapsed into a point, colored ones get their
+        // color on each of their vertices.
+        let styles: Vec<FaceStyle> = triangulation.faces.iter().map(|&face| style(face)).collect();
+        if styles.iter().any(|&style| style != FaceStyle::Default) {
+            if let Some(VertexAttributeValues::Float32x3(positions)) =
+                mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+            {
+                for (triangle, &style) in positions.chunks_exact_mut(3).zip(&styles) {
+                    if style == FaceStyle::Hidden {
+                        let p0 = triangle[0];
+                        triangle.fill(p0);
+                    }
+                }
+            }
+
+            let colors: Vec<[f32; 4]> = styles
+                .iter()
+                .flat_map(|&style| {
+                    let color = match style {
+                        FaceStyle::Colored(color) => LinearRgba::from(color).to_f32_array(),
+                        _ => [1.0; 4],
+                    };
+                    [color; 3]
+                })
+                .collect();
+            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        }
+
+        if normal_mode == NormalMode::Flat {
+            flat_normals(&mut mesh, &triangulation.faces);
+        }
         mesh
     }
 
@@ -255,6 +366,11 @@ pub trait Renderable: ConcretePolytope {
             return empty_mesh();
         }
 
+        // Edges are drawn as arcs in the stereographic projection.
+        if projection_type.is_stereographic() && self.dim_or() > 3 && self.edge_count() > 0 {
+            return arc_wireframe(self.con(), None);
+        }
+
         let edge_count = self.edge_count();
 
         // We add a single vertex so that Miratope doesn't crash.
@@ -283,6 +399,94 @@ pub trait Renderable: ConcretePolytope {
             .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0; 2]; vertex_count])
             .with_inserted_indices(Indices::U16(indices))
     }
+
+    /// Builds the wireframe of a polytope, giving each edge its own color.
+    /// Vertices are duplicated for each edge, so that colors don't bleed into
+    /// each other.
+    fn colored_wireframe(&self, projection_type: ProjectionType, edge_colors: &[Color]) -> Mesh {
+        let edges = match self.get_element_list(2) {
+            Some(edges) if !edges.is_empty() => edges,
+            _ => return self.wireframe(projection_type),
+        };
+
+        if projection_type.is_stereographic() && self.dim_or() > 3 {
+            return arc_wireframe(self.con(), Some(edge_colors));
+        }
+
+        let vertices = vertex_coords(
+            self.con(),
+            edges.iter().flat_map(|edge| [edge.subs[0], edge.subs[1]]).map(|v| &self.vertices()[v]),
+            projection_type,
+        );
+        let colors: Vec<[f32; 4]> = edge_colors
+            .iter()
+            .flat_map(|&color| [LinearRgba::from(color).to_f32_array(); 2])
+            .collect();
+        let vertex_count = vertices.len();
+
+        Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::default())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals(&vertices))
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vertices)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0; 2]; vertex_count])
+            .with_inserted_indices(Indices::U32((0..vertex_count as u32).collect()))
+    }
+
+    /// Writes the triangulated faces of a polytope as a Wavefront OBJ file,
+    /// projected the same way as on screen. Every triangle gets its own
+    /// normal, and the triangles of each face are put into their own group.
+    fn to_obj(&self, projection_type: ProjectionType) -> String {
+        let triangulation = self.con().triangulation();
+        let vertices = vertex_coords(
+            self.con(),
+            self.vertices()
+                .iter()
+                .chain(triangulation.extra_vertices.iter()),
+            projection_type,
+        );
+
+        let mut obj = String::from("# Exported from Miratope\n");
+        for [x, y, z] in &vertices {
+            writeln!(obj, "v {} {} {}", x, y, z).unwrap();
+        }
+
+        for triangle in triangulation.triangles.chunks_exact(3) {
+            let [p0, p1, p2] = [0, 1, 2].map(|i| Vec3::from_array(vertices[triangle[i] as usize]));
+            let n = (p1 - p0).cross(p2 - p0).normalize_or_zero();
+            writeln!(obj, "vn {} {} {}", n.x, n.y, n.z).unwrap();
+        }
+
+        // OBJ indices start at 1.
+        let mut last_face = None;
+        for (idx, (triangle, &face)) in triangulation
+            .triangles
+            .chunks_exact(3)
+            .zip(&triangulation.faces)
+            .enumerate()
+        {
+            if last_face != Some(face) {
+                writeln!(obj, "g face{}", face).unwrap();
+                last_face = Some(face);
+            }
+
+            writeln!(
+                obj,
+                "f {}//{n} {}//{n} {}//{n}",
+                triangle[0] + 1,
+                triangle[1] + 1,
+                triangle[2] + 1,
+                n = idx + 1
+            ).unwrap();
+        }
+
+        obj
+    }
+
+    /// Writes the triangulated faces of a polytope into an OBJ file in a
+    /// specified file path.
+    fn export_obj<P: AsRef<Path>>(&self, fp: P, projection_type: ProjectionType) -> io::Result<()> {
+        std::fs::write(fp, self.to_obj(projection_type))
+    }
 }
 
 impl<U: ConcretePolytope> Renderable for U {}
@@ -0,0 +1,275 @@
+//! Draws annotations over the viewport, like labels on elements, arrows
+//! between them, and a scale bar, so that figures can be made directly from
+//! screenshots.
+
+use super::{camera::ProjectionType, compare::CompareCamera, window::ShowWindows};
+use crate::{Concrete, mesh::vertex_coords};
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+/// The length of the ticks at the ends of the scale bar, in pixels.
+const TICK_LENGTH: f32 = 6.0;
+
+/// The plugin in charge of the annotations.
+pub struct AnnotationPlugin;
+
+impl Plugin for AnnotationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Annotations>()
+            .add_systems(EguiPrimaryContextPass, show_annotations.in_set(ShowWindows));
+    }
+}
+
+/// An element of the polytope that an annotation is attached to. Annotations
+/// are drawn at the average of the element's vertices.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct Anchor {
+    /// The dimension of the element, so that vertices have dimension 0.
+    pub dim: usize,
+
+    /// The index of the element.
+    pub idx: usize,
+}
+
+impl Anchor {
+    /// Returns where the element is drawn, or `None` if it doesn't exist.
+    fn position(self, poly: &Concrete, coords: &[[f32; 3]]) -> Option<Vec3> {
+        let vertices = poly.abs.element_vertices(self.dim + 1, self.idx)?;
+        (!vertices.is_empty()).then(|| {
+            vertices
+                .iter()
+                .map(|&v| Vec3::from_array(coords[v]))
+                .sum::<Vec3>()
+                / vertices.len() as f32
+        })
+    }
+
+    /// Shows the widgets to pick the element.
+    fn show(&mut self, ui: &mut egui::Ui) {
+        ui.add(
+            egui::DragValue::new(&mut self.dim)
+                .speed(0.05)
+                .prefix("dim "),
+        );
+        ui.add(egui::DragValue::new(&mut self.idx).speed(0.1).prefix("#"));
+    }
+}
+
+/// A text label next to an element.
+#[derive(Clone, Default)]
+pub struct TextLabel {
+    /// The element the label is attached to.
+    pub anchor: Anchor,
+
+    /// The text on the label.
+    pub text: String,
+}
+
+/// An arrow from one element to another.
+#[derive(Clone, Default)]
+pub struct Arrow {
+    /// The element the arrow starts at.
+    pub from: Anchor,
+
+    /// The element the arrow points to.
+    pub to: Anchor,
+}
+
+/// The annotations drawn over the viewport. They refer to elements by their
+/// indices, so those that don't exist in the loaded polytope are skipped.
+#[derive(Resource)]
+pub struct Annotations {
+    /// Whether the window to edit the annotations is open.
+    open: bool,
+
+    /// The text labels.
+    pub labels: Vec<TextLabel>,
+
+    /// The arrows.
+    pub arrows: Vec<Arrow>,
+
+    /// Whether to show a bar with the length of a unit edge.
+    pub scale_bar: bool,
+
+    /// The color of the annotations.
+    pub color: egui::Color32,
+}
+
+impl Default for Annotations {
+    fn default() -> Self {
+        Self {
+            open: false,
+            labels: Vec::new(),
+            arrows: Vec::new(),
+            scale_bar: false,
+            color: egui::Color32::WHITE,
+        }
+    }
+}
+
+impl Annotations {
+    /// Opens the window.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// Returns whether there's nothing to draw.
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty() && self.arrows.is_empty() && !self.scale_bar
+    }
+
+    /// Shows the widgets to edit the annotations.
+    fn build(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.scale_bar, "Scale bar of unit length");
+        ui.horizontal(|ui| {
+            ui.label("Color:");
+            ui.color_edit_button_srgba(&mut self.color);
+        });
+
+        ui.separator();
+        ui.label("Labels");
+
+        let mut removed = None;
+        for (idx, label) in self.labels.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button("−").clicked() {
+                    removed = Some(idx);
+                }
+                label.anchor.show(ui);
+                ui.add(egui::TextEdit::singleline(&mut label.text).desired_width(120.0));
+            });
+        }
+        if let Some(idx) = removed {
+            self.labels.remove(idx);
+        }
+
+        if ui.button("Add label").clicked() {
+            self.labels.push(Default::default());
+        }
+
+        ui.separator();
+        ui.label("Arrows");
+
+        let mut removed = None;
+        for (idx, arrow) in self.arrows.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button("−").clicked() {
+                    removed = Some(idx);
+                }
+                arrow.from.show(ui);
+                ui.label("→");
+                arrow.to.show(ui);
+            });
+        }
+        if let Some(idx) = removed {
+            self.arrows.remove(idx);
+        }
+
+        if ui.button("Add arrow").clicked() {
+            self.arrows.push(Default::default());
+        }
+
+        ui.separator();
+        if ui.button("Clear all").clicked() {
+            self.labels.clear();
+            self.arrows.clear();
+            self.scale_bar = false;
+        }
+    }
+
+    /// Draws the annotations over the viewport.
+    fn paint(
+        &self,
+        painter: &egui::Painter,
+        poly: &Concrete,
+        projection: ProjectionType,
+        (camera, camera_gtf): (&Camera, &GlobalTransform),
+    ) {
+        let coords = vertex_coords(poly, poly.vertices.iter(), projection);
+        let screen = |anchor: Anchor| {
+            let pos = camera
+                .world_to_viewport(camera_gtf, anchor.position(poly, &coords)?)
+                .ok()?;
+            Some(egui::pos2(pos.x, pos.y))
+        };
+        let stroke = egui::Stroke::new(1.5, self.color);
+        let font = egui::FontId::proportional(16.0);
+
+        for arrow in &self.arrows {
+            if let (Some(from), Some(to)) = (screen(arrow.from), screen(arrow.to)) {
+                painter.arrow(from, to - from, stroke);
+            }
+        }
+
+        for label in &self.labels {
+            if let Some(pos) = screen(label.anchor) {
+                painter.circle_filled(pos, 2.5, self.color);
+                painter.text(
+                    pos + egui::vec2(5.0, -5.0),
+                    egui::Align2::LEFT_BOTTOM,
+                    &label.text,
+                    font.clone(),
+                    self.color,
+                );
+            }
+        }
+
+        // The scale bar measures a unit length at the origin, perpendicular to
+        // the camera.
+        if self.scale_bar {
+            let ends = (
+                camera.world_to_viewport(camera_gtf, Vec3::ZERO),
+                camera.world_to_viewport(camera_gtf, *camera_gtf.right()),
+            );
+            if let (Ok(a), Ok(b), Some(rect)) = (ends.0, ends.1, camera.logical_viewport_rect()) {
+                let start = egui::pos2(rect.min.x + 30.0, rect.max.y - 30.0);
+                let end = start + egui::vec2(a.distance(b), 0.0);
+                let tick = egui::vec2(0.0, TICK_LENGTH);
+
+                painter.line_segment([start, end], stroke);
+                painter.line_segment([start - tick, start + tick], stroke);
+                painter.line_segment([end - tick, end + tick], stroke);
+                painter.text(
+                    egui::pos2((start.x + end.x) / 2.0, start.y - TICK_LENGTH),
+                    egui::Align2::CENTER_BOTTOM,
+                    "1",
+                    font,
+                    self.color,
+                );
+            }
+        }
+    }
+}
+
+/// Shows the window to edit the annotations, and draws them over the viewport.
+pub fn show_annotations(
+    mut egui_ctx: EguiContexts<'_, '_>,
+    mut annotations: ResMut<'_, Annotations>,
+    query: Query<'_, '_, &Concrete>,
+    cameras: Query<'_, '_, (&Camera, &GlobalTransform), Without<CompareCamera>>,
+    projection: Res<'_, ProjectionType>,
+) -> Result {
+    let ctx = egui_ctx.ctx_mut()?;
+
+    if annotations.open {
+        let mut open = true;
+        egui::Window::new("Annotations")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| annotations.build(ui));
+        annotations.open = open;
+    }
+
+    // Annotations are drawn under every window, but over the polytope.
+    if let (false, Some(poly), Some(camera)) = (
+        annotations.is_empty(),
+        query.iter().next(),
+        cameras.iter().next(),
+    ) {
+        let painter = ctx.layer_painter(egui::LayerId::background());
+        annotations.paint(&painter, poly, *projection, camera);
+    }
+
+    Ok(())
+}
@@ -6,11 +6,22 @@ use bevy::{
     input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel},
     math::EulerRot,
     prelude::*,
-    camera::Camera,
+    camera::{Camera, ScalingMode},
 };
 use bevy::window::PrimaryWindow;
 use bevy_egui::{egui::Context, EguiContexts};
-use crate::ui::library::show_library;
+use miratope_core::conc::ConcretePolytope;
+use crate::{
+    mesh::vertex_coords,
+    ui::{compare::CompareCamera, library::show_library, selection::SelectionMode, top_panel::SectionState},
+    Concrete,
+};
+
+/// The vertical field of view of the camera in perspective mode.
+pub const FOV: f32 = std::f32::consts::FRAC_PI_4;
+
+/// How much empty space is left around a polytope when fitting it to view.
+const FIT_MARGIN: f32 = 1.1;
 
 /// The plugin handling all camera input.
 pub struct InputPlugin;
@@ -19,10 +30,12 @@ impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
         app.add_message::<CameraInputEvent>()
             .init_resource::<ProjectionType>()
+            .init_resource::<OrthoScale>()
             // We register inputs after the library has been shown, so that we
             // know whether mouse input should register.
             .add_systems(Update, add_cam_input_events.after(show_library))
-            .add_systems(Update, update_cameras_and_anchors);
+            .add_systems(Update, update_cameras_and_anchors)
+            .add_systems(PostUpdate, update_projections);
     }
 }
 
@@ -33,6 +46,10 @@ pub enum ProjectionType {
 
     /// We're projecting from a point.
     Perspective,
+
+    /// We're projecting radially onto the circumsphere, and then
+    /// stereographically from a point on it, so that edges become arcs.
+    Stereographic,
 }
 
 impl Default for ProjectionType {
@@ -42,11 +59,13 @@ impl Default for ProjectionType {
 }
 
 impl ProjectionType {
-    /// Flips the projection type.
+    /// Flips the projection type between orthogonal and perspective. The
+    /// stereographic projection is viewed in perspective, so it flips into
+    /// an orthogonal one.
     pub fn flip(&mut self) {
         match self {
             Self::Orthogonal => *self = Self::Perspective,
-            Self::Perspective => *self = Self::Orthogonal,
+            Self::Perspective | Self::Stereographic => *self = Self::Orthogonal,
         }
     }
 
@@ -54,6 +73,47 @@ impl ProjectionType {
     pub fn is_orthogonal(&self) -> bool {
         matches!(self, Self::Orthogonal)
     }
+
+    /// Returns whether the projection type is `Stereographic`.
+    pub fn is_stereographic(&self) -> bool {
+        matches!(self, Self::Stereographic)
+    }
+
+    /// Returns the camera projection corresponding to this projection type.
+    pub fn projection(&self, ortho_scale: OrthoScale) -> Projection {
+        match self {
+            Self::Orthogonal => Projection::Orthographic(OrthographicProjection {
+                // The visible region is `2 * scale` units tall.
+                scaling_mode: ScalingMode::FixedVertical { viewport_height: 2.0 },
+                scale: ortho_scale.0,
+                ..OrthographicProjection::default_3d()
+            }),
+            Self::Perspective | Self::Stereographic => Projection::Perspective(PerspectiveProjection {
+                fov: FOV,
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+/// The zoom of the camera in orthogonal projection mode, independent of the
+/// distance from the camera to its anchor. This is half the height of the
+/// visible region, in world units.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct OrthoScale(pub f32);
+
+impl Default for OrthoScale {
+    fn default() -> Self {
+        Self(2.0)
+    }
+}
+
+impl OrthoScale {
+    /// The minimum allowed scale.
+    pub const MIN: f32 = 0.001;
+
+    /// The maximum allowed scale.
+    pub const MAX: f32 = 1000.0;
 }
 
 /// An input event for the camera.
@@ -77,6 +137,16 @@ pub enum CameraInputEvent {
     /// The zoom tapers with distance: closer in zooms slow, etc.
     Zoom(f32),
 
+    /// Moves and zooms the camera so that a sphere with a given center and
+    /// radius fits in view.
+    Fit {
+        /// The center of the sphere.
+        center: Vec3,
+
+        /// The radius of the sphere.
+        radius: f32,
+    },
+
     /// Resets the camera to its default state.
     Reset,
 }
@@ -119,10 +189,62 @@ impl CameraInputEvent {
         anchor_tf.rotate_local(Quat::from_euler(EulerRot::YXZ, 0., 0., roll));
     }
 
-    /// Zooms into the camera.
-    fn zoom(zoom: f32, cam_tf: &mut Transform) {
-        cam_tf.translation.z += zoom * cam_tf.translation.length();
-        cam_tf.translation.z = cam_tf.translation.z.max(0.05).min(400.);
+    /// Zooms into the camera. In orthogonal mode, this changes the scale of
+    /// the projection instead of moving the camera.
+    fn zoom(zoom: f32, cam_tf: &mut Transform, projection: ProjectionType, ortho_scale: &mut OrthoScale) {
+        if projection.is_orthogonal() {
+            ortho_scale.0 = (ortho_scale.0 * zoom.exp()).clamp(OrthoScale::MIN, OrthoScale::MAX);
+        } else {
+            cam_tf.translation.z += zoom * cam_tf.translation.length();
+            cam_tf.translation.z = cam_tf.translation.z.max(0.05).min(400.);
+        }
+    }
+
+    /// Frames a sphere with a given center and radius, both in perspective and
+    /// orthogonal mode.
+    fn fit(center: Vec3, radius: f32, anchor_tf: &mut Transform, cam_tf: &mut Transform, ortho_scale: &mut OrthoScale) {
+        let radius = radius.max(0.01) * FIT_MARGIN;
+
+        anchor_tf.translation = center;
+        *cam_tf = Transform::from_translation(Vec3::new(0., 0., (radius / (FOV / 2.).sin()).min(400.)));
+        ortho_scale.0 = radius.clamp(OrthoScale::MIN, OrthoScale::MAX);
+    }
+
+    /// Returns the event that fits a polytope into view, as it would be
+    /// rendered with a given projection type.
+    ///
+    /// We frame the polytope's circumsphere whenever the projection preserves
+    /// it. Otherwise, or if the polytope has no circumsphere, we frame the
+    /// bounding sphere of its projected vertices.
+    pub fn fit_polytope(poly: &Concrete, projection: ProjectionType) -> Option<Self> {
+        if poly.vertices.is_empty() {
+            return None;
+        }
+
+        if projection.is_orthogonal() || poly.dim_or() <= 3 {
+            if let Some(sphere) = poly.circumsphere() {
+                let coord = |i: usize| sphere.center.get(i).copied().unwrap_or_default() as f32;
+
+                return Some(Self::Fit {
+                    center: Vec3::new(coord(0), coord(1), coord(2)),
+                    radius: sphere.radius() as f32,
+                });
+            }
+        }
+
+        let coords: Vec<_> = vertex_coords(poly, poly.vertices.iter(), projection)
+            .into_iter()
+            .map(Vec3::from)
+            .collect();
+
+        let (min, max) = coords.iter().fold(
+            (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+            |(min, max), &v| (min.min(v), max.max(v)),
+        );
+        let center = (min + max) / 2.;
+        let radius = coords.iter().map(|v| v.distance(center)).fold(0., f32::max);
+
+        Some(Self::Fit { center, radius })
     }
 
     /// Resets the camera to the default position.
@@ -138,13 +260,19 @@ impl CameraInputEvent {
         anchor_tf: &mut Transform,
         cam_tf: &mut Transform,
         cam_gtf: &GlobalTransform,
+        projection: ProjectionType,
+        ortho_scale: &mut OrthoScale,
     ) {
         match *self {
             Self::RotateAnchor(vec) => Self::rotate(vec, anchor_tf),
             Self::Translate(vec) => Self::translate(vec, anchor_tf, cam_gtf),
             Self::Roll(roll) => Self::roll(roll, anchor_tf),
-            Self::Zoom(zoom) => Self::zoom(zoom, cam_tf),
-            Self::Reset => Self::reset(anchor_tf, cam_tf),
+            Self::Zoom(zoom) => Self::zoom(zoom, cam_tf, projection, ortho_scale),
+            Self::Fit { center, radius } => Self::fit(center, radius, anchor_tf, cam_tf, ortho_scale),
+            Self::Reset => {
+                Self::reset(anchor_tf, cam_tf);
+                *ortho_scale = Default::default();
+            }
         }
     }
 
@@ -192,15 +320,19 @@ impl CameraInputEvent {
         (real_scale, scale)
     }
 
-    /// Processes camera events coming from the mouse buttons.
+    /// Processes camera events coming from the mouse buttons. When selecting
+    /// faces, the left button is left for the selection.
     fn cam_events_from_mouse(
         mouse_button: &ButtonInput<MouseButton>,
         mut mouse_move: MessageReader<'_, '_, MouseMotion>,
         height: f32,
         real_scale: f32,
+        selecting: bool,
         cam_inputs: &mut MessageWriter<'_, Self>,
     ) {
-        if mouse_button.pressed(MouseButton::Left) || mouse_button.pressed(MouseButton::Right) {
+        if (!selecting && mouse_button.pressed(MouseButton::Left))
+            || mouse_button.pressed(MouseButton::Right)
+        {
             for &MouseMotion { mut delta } in mouse_move.read() {
                 delta.x /= height;
                 delta.y /= height;
@@ -237,6 +369,10 @@ fn add_cam_input_events(
     mut window_query: Query<'_, '_, &Window, With<PrimaryWindow>>,
     mut cam_inputs: MessageWriter<'_, CameraInputEvent>,
     mut egui_ctx: EguiContexts<'_, '_>,
+    polies: Query<'_, '_, &Concrete>,
+    projection: Res<'_, ProjectionType>,
+    selection_mode: Res<'_, SelectionMode>,
+    section_state: Res<'_, SectionState>,
 ) -> Result {
     let height = {
         let primary_win = window_query.single_mut().expect("There is no primary window");
@@ -248,6 +384,17 @@ fn add_cam_input_events(
     let (real_scale, scale) =
         CameraInputEvent::cam_events_from_kb(&time, &keyboard, cam_inputs, ctx);
 
+    // Fits the polytope into view.
+    if !ctx.wants_keyboard_input() && keyboard.just_pressed(KeyCode::KeyZ) {
+        if let Some(event) = polies
+            .iter()
+            .next()
+            .and_then(|poly| CameraInputEvent::fit_polytope(poly, *projection))
+        {
+            cam_inputs.write(event);
+        }
+    }
+
     // Omit any events if the UI will process them instead.
     if !ctx.wants_pointer_input() {
         CameraInputEvent::cam_events_from_mouse(
@@ -255,15 +402,24 @@ fn add_cam_input_events(
             mouse_move,
             height,
             real_scale,
+            selection_mode.0,
             cam_inputs,
         );
-        CameraInputEvent::cam_events_from_wheel(mouse_wheel, scale, cam_inputs);
+
+        // The wheel moves the cross-section instead when it follows the
+        // camera, unless Alt is held.
+        let alt = keyboard.pressed(KeyCode::AltLeft) || keyboard.pressed(KeyCode::AltRight);
+        if alt || !section_state.follows_camera() {
+            CameraInputEvent::cam_events_from_wheel(mouse_wheel, scale, cam_inputs);
+        }
     };
     Ok(())
 }
 
 fn update_cameras_and_anchors(
     mut events: MessageReader<'_, '_, CameraInputEvent>,
+    projection: Res<'_, ProjectionType>,
+    mut ortho_scale: ResMut<'_, OrthoScale>,
     q: Query<
         '_,
         '_,
@@ -273,6 +429,7 @@ fn update_cameras_and_anchors(
             Option<&ChildOf>,
             Option<&Camera>,
         ),
+        Without<CompareCamera>,
     >,
 ) {
     // SAFETY: see the remark below.
@@ -283,10 +440,36 @@ fn update_cameras_and_anchors(
                 // shouldn't ever happen on purpose)
                 let mut anchor_tf =
                     unsafe { q.get_unchecked(child_of.parent()).unwrap().0 };
+                // We only write to the scale when it actually changes, so as
+                // not to trigger change detection every frame.
+                let mut new_scale = *ortho_scale;
                 for event in events.read() {
-                    event.update_camera_and_anchor(&mut anchor_tf, &mut cam_tf, cam_gtf);
+                    event.update_camera_and_anchor(
+                        &mut anchor_tf,
+                        &mut cam_tf,
+                        cam_gtf,
+                        *projection,
+                        &mut new_scale,
+                    );
+                }
+                if new_scale != *ortho_scale {
+                    *ortho_scale = new_scale;
                 }
             }
         }
     }
 }
+
+/// Updates the camera projections whenever the projection type or the
+/// orthogonal scale change.
+fn update_projections(
+    projection_type: Res<'_, ProjectionType>,
+    ortho_scale: Res<'_, OrthoScale>,
+    mut cams: Query<'_, '_, &mut Projection, With<Camera>>,
+) {
+    if projection_type.is_changed() || ortho_scale.is_changed() {
+        for mut projection in cams.iter_mut() {
+            *projection = projection_type.projection(*ortho_scale);
+        }
+    }
+}
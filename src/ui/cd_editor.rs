@@ -0,0 +1,388 @@
+//! A widget to build Coxeter diagrams by clicking on their nodes, rather than
+//! by writing them in inline notation.
+
+use miratope_core::cox::cd::{Cd, CdResult, Edge, Node};
+
+use bevy_egui::egui::{self, pos2, vec2, Align2, FontId, Pos2, Sense, Shape, Stroke, Ui};
+
+/// The horizontal distance between two consecutive nodes.
+const NODE_SPACING: f32 = 48.0;
+
+/// The radius of a node.
+const NODE_RADIUS: f32 = 6.0;
+
+/// The radius of the ring around a ringed node.
+const RING_RADIUS: f32 = 10.0;
+
+/// How far a click can be from a node to hit it.
+const HIT_RADIUS: f32 = 14.0;
+
+/// How high an edge between two nodes that aren't consecutive is drawn, per
+/// node in between them.
+const ARC_HEIGHT: f32 = 12.0;
+
+/// An edge in the editor, between two nodes with `a < b`.
+#[derive(Clone, Copy)]
+struct EditorEdge {
+    /// The index of the first node.
+    a: usize,
+
+    /// The index of the second node.
+    b: usize,
+
+    /// The numerator of the edge.
+    num: u32,
+
+    /// The denominator of the edge.
+    den: u32,
+}
+
+impl EditorEdge {
+    /// Returns the edge as a Coxeter diagram edge.
+    fn edge(&self) -> CdResult<Edge> {
+        Edge::rational(self.num, self.den, 0)
+    }
+}
+
+/// The state of the Coxeter diagram editor.
+#[derive(Default)]
+pub struct CdEditor {
+    /// The nodes of the diagram, in order.
+    nodes: Vec<Node>,
+
+    /// The edges of the diagram, sorted by their nodes.
+    edges: Vec<EditorEdge>,
+
+    /// The node that was clicked last, if any. Clicking on another node joins
+    /// them with an edge.
+    selected: Option<usize>,
+}
+
+impl CdEditor {
+    /// Loads a Coxeter diagram into the editor.
+    pub fn from_cd(cd: &Cd) -> Self {
+        let mut edges: Vec<EditorEdge> = cd
+            .raw_edges()
+            .iter()
+            .map(|edge| {
+                let (a, b) = (edge.source().index(), edge.target().index());
+                EditorEdge {
+                    a: a.min(b),
+                    b: a.max(b),
+                    num: edge.weight.num,
+                    den: edge.weight.den,
+                }
+            })
+            .collect();
+        edges.sort_unstable_by_key(|edge| (edge.a, edge.b));
+
+        Self {
+            nodes: cd.nodes(),
+            edges,
+            selected: None,
+        }
+    }
+
+    /// Builds the Coxeter diagram in the editor.
+    pub fn cd(&self) -> CdResult<Cd> {
+        let mut cd = Cd::new();
+        let indices: Vec<_> = self.nodes.iter().map(|&node| cd.add_node(node)).collect();
+
+        for edge in &self.edges {
+            cd.add_edge(indices[edge.a], indices[edge.b], edge.edge()?)?;
+        }
+
+        Ok(cd)
+    }
+
+    /// Returns the position of the edge between two nodes, if any.
+    fn edge_position(&self, a: usize, b: usize) -> Option<usize> {
+        let (a, b) = (a.min(b), a.max(b));
+        self.edges
+            .iter()
+            .position(|edge| edge.a == a && edge.b == b)
+    }
+
+    /// Adds an edge with a value of 3 between two nodes, or removes the edge
+    /// between them if there's one already.
+    fn toggle_edge(&mut self, a: usize, b: usize) {
+        match self.edge_position(a, b) {
+            Some(idx) => {
+                self.edges.remove(idx);
+            }
+            None => {
+                self.edges.push(EditorEdge {
+                    a: a.min(b),
+                    b: a.max(b),
+                    num: 3,
+                    den: 1,
+                });
+                self.edges.sort_unstable_by_key(|edge| (edge.a, edge.b));
+            }
+        }
+    }
+
+    /// Adds an unringed node at the end, joined to the previous last node.
+    fn push_node(&mut self) {
+        self.nodes.push(Node::Unringed);
+
+        let len = self.nodes.len();
+        if len >= 2 {
+            self.toggle_edge(len - 2, len - 1);
+        }
+    }
+
+    /// Removes a node and every edge through it.
+    fn remove_node(&mut self, idx: usize) {
+        self.nodes.remove(idx);
+        self.edges.retain(|edge| edge.a != idx && edge.b != idx);
+
+        for edge in &mut self.edges {
+            if edge.a > idx {
+                edge.a -= 1;
+            }
+            if edge.b > idx {
+                edge.b -= 1;
+            }
+        }
+
+        self.selected = None;
+    }
+
+    /// Cycles a node between unringed, ringed and snub.
+    fn cycle_node(&mut self, idx: usize) {
+        let node = &mut self.nodes[idx];
+        *node = match *node {
+            Node::Unringed => Node::ringed(1.0),
+            Node::Ringed(_) => Node::snub(1.0),
+            Node::Snub(_) => Node::Unringed,
+        };
+    }
+
+    /// The label for a node in the drop-down.
+    fn node_label(node: Node) -> String {
+        match (node, node.to_char()) {
+            (Node::Ringed(x), Some(c)) if c != 'x' => format!("{} ({:.3})", c, x),
+            (_, Some(c)) => c.to_string(),
+            (_, None) => format!("({})", node.value()),
+        }
+    }
+
+    /// Returns the point where a node is drawn.
+    fn node_pos(origin: Pos2, idx: usize) -> Pos2 {
+        origin + vec2(idx as f32 * NODE_SPACING, 0.0)
+    }
+
+    /// Draws the diagram and lets the user click on it. Returns whether the
+    /// diagram changed.
+    fn show_canvas(&mut self, ui: &mut Ui) -> bool {
+        let max_arc = self
+            .edges
+            .iter()
+            .map(|edge| edge.b - edge.a - 1)
+            .max()
+            .unwrap_or(0) as f32
+            * ARC_HEIGHT;
+        let width = (self.nodes.len().max(1) as f32 * NODE_SPACING).max(ui.available_width());
+        let height = max_arc + 3.0 * NODE_SPACING / 2.0;
+
+        let (response, painter) = ui.allocate_painter(vec2(width, height), Sense::click());
+        let rect = response.rect;
+        let origin = pos2(
+            rect.left() + NODE_SPACING / 2.0,
+            rect.bottom() - NODE_SPACING / 2.0,
+        );
+
+        let visuals = ui.visuals();
+        let color = visuals.text_color();
+        let stroke = Stroke::new(1.5, color);
+        let font = FontId::proportional(13.0);
+
+        // Draws the edges, as arcs if their nodes aren't consecutive.
+        for edge in &self.edges {
+            let (p, q) = (
+                Self::node_pos(origin, edge.a),
+                Self::node_pos(origin, edge.b),
+            );
+            let rise = (edge.b - edge.a - 1) as f32 * ARC_HEIGHT;
+            let control = pos2((p.x + q.x) / 2.0, p.y - 2.0 * rise);
+
+            let points: Vec<Pos2> = (0..=16)
+                .map(|i| {
+                    let t = i as f32 / 16.0;
+                    let s = 1.0 - t;
+                    (s * s * p.to_vec2() + 2.0 * s * t * control.to_vec2() + t * t * q.to_vec2())
+                        .to_pos2()
+                })
+                .collect();
+            painter.add(Shape::line(points, stroke));
+
+            // Edges with a value of 3 are left unmarked.
+            if (edge.num, edge.den) != (3, 1) {
+                painter.text(
+                    pos2(control.x, p.y - rise - 10.0),
+                    Align2::CENTER_CENTER,
+                    edge.edge().map(|e| e.inline()).unwrap_or_default(),
+                    font.clone(),
+                    color,
+                );
+            }
+        }
+
+        // Draws the nodes.
+        for (idx, &node) in self.nodes.iter().enumerate() {
+            let pos = Self::node_pos(origin, idx);
+
+            if self.selected == Some(idx) {
+                painter.circle_filled(pos, HIT_RADIUS, visuals.selection.bg_fill);
+            }
+
+            match node {
+                Node::Snub(_) => {
+                    painter.circle(pos, NODE_RADIUS, visuals.extreme_bg_color, stroke);
+                }
+                _ => {
+                    painter.circle_filled(pos, NODE_RADIUS, color);
+                }
+            }
+
+            if node != Node::Unringed {
+                painter.circle_stroke(pos, RING_RADIUS, stroke);
+            }
+
+            // Node lengths other than the unit are written below.
+            if let Node::Ringed(x) = node
+                && (x - 1.0).abs() > f64::EPSILON
+            {
+                let label = node
+                    .to_char()
+                    .map_or_else(|| format!("{:.3}", x), String::from);
+                painter.text(
+                    pos + vec2(0.0, RING_RADIUS + 8.0),
+                    Align2::CENTER_CENTER,
+                    label,
+                    font.clone(),
+                    color,
+                );
+            }
+        }
+
+        // Finds which node was clicked, if any.
+        let hit = |pos: Option<Pos2>| {
+            let pos = pos?;
+            (0..self.nodes.len())
+                .find(|&idx| Self::node_pos(origin, idx).distance(pos) < HIT_RADIUS)
+        };
+
+        if response.secondary_clicked() {
+            if let Some(idx) = hit(response.interact_pointer_pos()) {
+                self.cycle_node(idx);
+                return true;
+            }
+        } else if response.clicked() {
+            match (self.selected, hit(response.interact_pointer_pos())) {
+                (Some(selected), Some(idx)) if selected != idx => {
+                    self.toggle_edge(selected, idx);
+                    self.selected = None;
+                    return true;
+                }
+                (Some(_), Some(_)) | (_, None) => self.selected = None,
+                (None, Some(idx)) => self.selected = Some(idx),
+            }
+        }
+
+        false
+    }
+
+    /// Shows the editor. Returns whether the diagram changed.
+    pub fn show(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = false;
+
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            changed |= self.show_canvas(ui);
+        });
+        ui.small("Click two nodes to join or split them. Right-click a node to ring it.");
+
+        // The marking of the selected node.
+        if let Some(idx) = self.selected {
+            ui.horizontal(|ui| {
+                let node = self.nodes[idx];
+                egui::ComboBox::from_label(format!("Node {}", idx + 1))
+                    .selected_text(Self::node_label(node))
+                    .show_ui(ui, |ui| {
+                        for c in Node::SYMBOLS.chars() {
+                            let new_node = Node::from_char(c).expect("node symbols are valid");
+                            if ui
+                                .selectable_label(
+                                    node.to_char() == Some(c),
+                                    Self::node_label(new_node),
+                                )
+                                .clicked()
+                            {
+                                self.nodes[idx] = new_node;
+                                changed = true;
+                            }
+                        }
+                    });
+
+                if ui.button("Remove").clicked() {
+                    self.remove_node(idx);
+                    changed = true;
+                }
+            });
+        }
+
+        // The values of the edges.
+        if !self.edges.is_empty() {
+            ui.collapsing("Edges", |ui| {
+                let mut remove = None;
+
+                for (idx, edge) in self.edges.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} – {}:", edge.a + 1, edge.b + 1));
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut edge.num)
+                                    .speed(0.03)
+                                    .range(2..=u32::MAX),
+                            )
+                            .changed();
+                        ui.label("/");
+                        let max_den = edge.num - 1;
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut edge.den)
+                                    .speed(0.03)
+                                    .range(1..=max_den),
+                            )
+                            .changed();
+                        edge.den = edge.den.min(max_den);
+
+                        if ui.button("✖").clicked() {
+                            remove = Some(idx);
+                        }
+                    });
+                }
+
+                if let Some(idx) = remove {
+                    self.edges.remove(idx);
+                    changed = true;
+                }
+            });
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Add node").clicked() {
+                self.push_node();
+                changed = true;
+            }
+
+            if ui.button("Clear").clicked() {
+                *self = Default::default();
+                changed = true;
+            }
+        });
+
+        changed
+    }
+}
@@ -0,0 +1,265 @@
+//! Shows a polytope from memory side by side with the current one, in two
+//! viewports that share the same camera. The edges that differ between both
+//! polytopes are highlighted on the stored one.
+
+use super::{
+    camera::{OrthoScale, ProjectionType},
+    window::ShowWindows,
+};
+use crate::{
+    mesh::{add_segments, vertex_coords, Renderable},
+    Concrete,
+};
+use miratope_core::{abs::Ranked, conc::compare::EdgeDiff};
+
+use bevy::{
+    camera::{visibility::RenderLayers, Viewport},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+/// The render layer the compared polytope is drawn on, so that only the
+/// second camera sees it.
+pub const COMPARE_LAYER: usize = 1;
+
+/// The color of the edges both polytopes share.
+const SHARED_COLOR: Color = Color::srgb(0.6, 0.6, 0.6);
+
+/// The color of the edges only the stored polytope has. The edges only the
+/// current polytope has are drawn in white.
+const ADDED_COLOR: Color = Color::srgb(1.0, 0.55, 0.1);
+
+/// The plugin in charge of the side by side view.
+pub struct ComparePlugin;
+
+impl Plugin for ComparePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CompareView>()
+            .add_systems(Update, update_viewports)
+            .add_systems(PostUpdate, update_compare)
+            .add_systems(EguiPrimaryContextPass, show_compare.in_set(ShowWindows));
+    }
+}
+
+/// The state of the side by side view.
+#[derive(Default, Resource)]
+pub struct CompareView {
+    /// The polytope shown next to the current one, along with its name.
+    pub slot: Option<(Concrete, String)>,
+
+    /// The edges that differ from the current polytope to the shown one.
+    diff: EdgeDiff,
+}
+
+impl CompareView {
+    /// Sets the polytope to show next to the current one.
+    pub fn set(&mut self, poly: Concrete, name: String) {
+        self.slot = Some((poly, name));
+    }
+
+    /// Closes the side by side view.
+    pub fn clear(&mut self) {
+        self.slot = None;
+    }
+}
+
+/// Marks the camera showing the compared polytope.
+#[derive(Component)]
+pub struct CompareCamera;
+
+/// Marks the entities drawing the compared polytope.
+#[derive(Component)]
+pub struct CompareMesh;
+
+/// Rebuilds the compared polytope whenever it, the current polytope, or the
+/// projection change.
+pub fn update_compare(
+    mut commands: Commands<'_, '_>,
+    mut meshes: ResMut<'_, Assets<Mesh>>,
+    mut materials: ResMut<'_, Assets<StandardMaterial>>,
+    entities: Query<'_, '_, Entity, (With<CompareMesh>, Without<ChildOf>)>,
+    polies: Query<'_, '_, Ref<'_, Concrete>>,
+    mut compare: ResMut<'_, CompareView>,
+    projection: Res<'_, ProjectionType>,
+) {
+    let Some(current) = polies.iter().next() else {
+        return;
+    };
+    if !compare.is_changed() && !projection.is_changed() && !current.is_changed() {
+        return;
+    }
+
+    // Despawning the parent also despawns its wireframe.
+    for entity in entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let compare = compare.bypass_change_detection();
+    let Some((poly, _)) = &compare.slot else {
+        compare.diff = EdgeDiff::default();
+        return;
+    };
+
+    let diff = current.edge_diff(poly);
+    let mut colors = vec![SHARED_COLOR; poly.edge_count()];
+    for &idx in &diff.added {
+        colors[idx] = ADDED_COLOR;
+    }
+
+    // The missing edges are projected like the compared polytope, so that
+    // they line up with it.
+    let mut wireframe = poly.colored_wireframe(*projection, &colors);
+    let missing = diff.removed.iter().flat_map(|&idx| {
+        let subs = &current[(2, idx)].subs;
+        [&current.vertices[subs[0]], &current.vertices[subs[1]]]
+    });
+    add_segments(&mut wireframe, vertex_coords(poly, missing, *projection));
+
+    commands
+        .spawn((
+            Mesh3d(meshes.add(poly.mesh(*projection, false))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::WHITE,
+                double_sided: true,
+                cull_mode: None,
+                ..Default::default()
+            })),
+            Transform::default(),
+            Visibility::Visible,
+            RenderLayers::layer(COMPARE_LAYER),
+            CompareMesh,
+        ))
+        .with_children(|cb| {
+            cb.spawn((
+                Mesh3d(meshes.add(wireframe)),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: Color::WHITE,
+                    unlit: true,
+                    ..Default::default()
+                })),
+                Transform::default(),
+                Visibility::Visible,
+                RenderLayers::layer(COMPARE_LAYER),
+                CompareMesh,
+            ));
+        });
+
+    compare.diff = diff;
+}
+
+/// Splits the window between the main camera and the compare camera while
+/// the side by side view is open, spawning or despawning the latter as
+/// needed.
+pub fn update_viewports(
+    mut commands: Commands<'_, '_>,
+    compare: Res<'_, CompareView>,
+    window_query: Query<'_, '_, &Window, With<PrimaryWindow>>,
+    mut main_cams: Query<'_, '_, (Entity, &mut Camera), (With<Camera3d>, Without<CompareCamera>)>,
+    mut compare_cams: Query<'_, '_, (Entity, &mut Camera), With<CompareCamera>>,
+    (projection, ortho_scale): (Res<'_, ProjectionType>, Res<'_, OrthoScale>),
+) {
+    let Some((main_entity, mut main_cam)) = main_cams.iter_mut().next() else {
+        return;
+    };
+
+    // Restores the main camera once the view is closed. We only write to the
+    // cameras when something changes, so as not to trigger change detection
+    // every frame.
+    if compare.slot.is_none() {
+        for (entity, _) in compare_cams.iter() {
+            commands.entity(entity).despawn();
+        }
+        if main_cam.viewport.is_some() {
+            main_cam.viewport = None;
+            main_cam.clear_color = ClearColorConfig::Default;
+        }
+        return;
+    }
+
+    let Ok(window) = window_query.single() else {
+        return;
+    };
+    let size = window.physical_size();
+    if size.x < 2 || size.y == 0 {
+        return;
+    }
+
+    let half = size.x / 2;
+    let left = (UVec2::ZERO, UVec2::new(half, size.y));
+    let right = (UVec2::new(half, 0), UVec2::new(size.x - half, size.y));
+    let viewport = |(physical_position, physical_size): (UVec2, UVec2)| Viewport {
+        physical_position,
+        physical_size,
+        ..Default::default()
+    };
+    let differs = |cam: &Camera, (position, size): (UVec2, UVec2)| {
+        cam.viewport
+            .as_ref()
+            .is_none_or(|v| v.physical_position != position || v.physical_size != size)
+    };
+
+    // The compare camera renders first and clears the whole window, so the
+    // main camera mustn't clear it again.
+    if differs(&main_cam, left) {
+        main_cam.viewport = Some(viewport(left));
+        main_cam.clear_color = ClearColorConfig::None;
+    }
+
+    match compare_cams.iter_mut().next() {
+        Some((_, mut cam)) => {
+            if differs(&cam, right) {
+                cam.viewport = Some(viewport(right));
+            }
+        }
+
+        // The camera is a child of the main one, so that they move together.
+        None => {
+            commands.entity(main_entity).with_children(|cb| {
+                cb.spawn((
+                    Camera3d::default(),
+                    Camera {
+                        order: -1,
+                        viewport: Some(viewport(right)),
+                        ..Default::default()
+                    },
+                    projection.projection(*ortho_scale),
+                    Transform::default(),
+                    Msaa::Sample4,
+                    RenderLayers::layer(COMPARE_LAYER),
+                    CompareCamera,
+                ));
+            });
+        }
+    }
+}
+
+/// Shows the window describing the side by side view.
+pub fn show_compare(mut egui_ctx: EguiContexts<'_, '_>, mut compare: ResMut<'_, CompareView>) -> Result {
+    let Some((_, name)) = &compare.slot else {
+        return Ok(());
+    };
+
+    let diff = &compare.diff;
+    let mut close = false;
+
+    egui::Window::new("Side by side")
+        .resizable(false)
+        .show(egui_ctx.ctx_mut()?, |ui| {
+            ui.label(format!("Right: {}", name));
+            if diff.is_empty() {
+                ui.label("Both polytopes have the same edges.");
+            } else {
+                ui.label(format!("{} edges only on the right (orange)", diff.added.len()));
+                ui.label(format!("{} edges only on the left (white)", diff.removed.len()));
+            }
+
+            close = ui.button("Close").clicked();
+        });
+
+    if close {
+        compare.clear();
+    }
+
+    Ok(())
+}
@@ -10,8 +10,13 @@ use std::{
 use bevy::{app::AppExit, prelude::*};
 use bevy_egui::{egui, EguiContexts};
 use directories::ProjectDirs;
+use miratope_core::{
+    conc::ConcretePolytope,
+    file::off::{LineEnding, OffOptions},
+    group::disk_cache::GroupCache,
+};
 use serde::{Deserialize, Serialize};
-use crate::ui::CurrentVisuals;
+use crate::{mesh::NormalMode, ui::CurrentVisuals, Concrete};
 
 /// The default path in which we look for the Miratope library.
 const DEFAULT_PATH: &str = "./lib";
@@ -41,8 +46,17 @@ impl Plugin for ConfigPlugin {
             .insert_resource(config.background_color.clear_color())
             .insert_resource(config.mesh_color)
             .insert_resource(config.wf_color)
+            .insert_resource(config.wf_color_mode)
+            .insert_resource(config.offset_coplanar)
+            .insert_resource(config.show_intersections)
+            .insert_resource(config.preview_operations)
+            .insert_resource(config.show_performance)
+            .insert_resource(config.render_settings)
             .insert_resource(CurrentVisuals(config.light_mode.visuals()))
             .insert_resource(config.slots_per_page)
+            .insert_resource(config.load_settings)
+            .insert_resource(config.save_settings)
+            .insert_resource(Config::group_cache())
             .add_systems(Update, update_visuals)
             .add_systems(Last, save_config);
     }
@@ -116,6 +130,63 @@ impl Default for WfColor {
     }
 }
 
+/// How to color the edges of the wireframe.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Resource)]
+pub enum WfColorMode {
+    /// Every edge gets the wireframe color.
+    #[default]
+    Single,
+
+    /// Edges get a color for each orbit under the symmetry group.
+    Orbit,
+
+    /// Edges get a color for each distinct length.
+    Length,
+}
+
+/// Whether to slightly offset faces lying on the same plane when rendering,
+/// so that compounds don't flicker.
+#[derive(Clone, Copy, Default, Serialize, Deserialize, Resource)]
+pub struct OffsetCoplanarFaces(pub bool);
+
+/// Whether to draw the segments along which faces cross each other, in
+/// addition to the edges.
+#[derive(Clone, Copy, Default, Serialize, Deserialize, Resource)]
+pub struct ShowIntersections(pub bool);
+
+/// Whether destructive operations show a preview of their result that has to
+/// be applied, instead of changing the polytope right away.
+#[derive(Clone, Copy, Default, Serialize, Deserialize, Resource)]
+pub struct PreviewOperations(pub bool);
+
+/// Whether to show a HUD with the frame time and the duration of the last
+/// operations.
+#[derive(Clone, Copy, Default, Serialize, Deserialize, Resource)]
+pub struct ShowPerformance(pub bool);
+
+/// How the faces of the polytope are lit and culled.
+#[derive(Clone, Copy, Serialize, Deserialize, Resource)]
+pub struct RenderSettings {
+    /// Whether the back of each face is lit as if it were the front.
+    pub two_sided_lighting: bool,
+
+    /// Whether the back of each face is hidden.
+    pub backface_culling: bool,
+
+    /// How the normals of the mesh are computed.
+    pub normals: NormalMode,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            two_sided_lighting: true,
+            backface_culling: false,
+            normals: NormalMode::Flat,
+        }
+    }
+}
+
 /// Whether light mode is turned on or off.
 #[derive(Default, Serialize, Deserialize)]
 pub struct LightMode(bool);
@@ -142,6 +213,180 @@ impl Default for SlotsPerPage {
     }
 }
 
+/// How to recenter a polytope when it's loaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AutoRecenter {
+    /// Leave the polytope where it is.
+    None,
+
+    /// Move the gravicenter to the origin.
+    Gravicenter,
+
+    /// Move the circumcenter to the origin, if it exists.
+    Circumcenter,
+}
+
+impl Default for AutoRecenter {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+/// How to rescale a polytope when it's loaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AutoRescale {
+    /// Leave the polytope at its size.
+    None,
+
+    /// Scale to unit circumradius, if the circumsphere exists.
+    UnitCircumradius,
+
+    /// Scale so that the first edge has unit length.
+    UnitEdge,
+}
+
+impl Default for AutoRescale {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+/// The transformations applied to every polytope loaded from a file or from
+/// the library.
+#[derive(Clone, Copy, Serialize, Deserialize, Resource)]
+pub struct LoadSettings {
+    /// How to recenter the polytope.
+    pub recenter: AutoRecenter,
+
+    /// How to rescale the polytope.
+    pub rescale: AutoRescale,
+
+    /// Whether to discard the coordinates of the polytope and only keep its
+    /// abstract structure.
+    #[serde(default)]
+    pub as_abstract: bool,
+
+    /// Whether to weld together the vertices closer than the weld tolerance.
+    #[serde(default)]
+    pub weld: bool,
+
+    /// The distance under which two vertices are considered the same.
+    #[serde(default = "default_weld_tolerance")]
+    pub weld_tolerance: f64,
+}
+
+/// The default distance under which vertices get welded.
+fn default_weld_tolerance() -> f64 {
+    1e-6
+}
+
+impl Default for LoadSettings {
+    fn default() -> Self {
+        Self {
+            recenter: Default::default(),
+            rescale: Default::default(),
+            as_abstract: false,
+            weld: false,
+            weld_tolerance: default_weld_tolerance(),
+        }
+    }
+}
+
+impl LoadSettings {
+    /// Applies the load settings to a freshly loaded polytope. We recenter
+    /// before rescaling, so that the center stays at the origin.
+    pub fn apply(&self, poly: &mut Concrete) {
+        // Polytopes without meaningful coordinates get a skeleton embedding.
+        if self.as_abstract || poly.is_degenerate() {
+            *poly = Concrete::from_abstract(poly.abs.clone());
+            return;
+        }
+
+        // Near-duplicate vertices break symmetry detection, among others.
+        if self.weld {
+            let (welded, merged) = poly.weld_vertices(self.weld_tolerance);
+            if merged > 0 {
+                println!("Welded {} vertices.", merged);
+                *poly = welded;
+            }
+        }
+
+        match self.recenter {
+            AutoRecenter::None => {}
+            AutoRecenter::Gravicenter => poly.recenter(),
+            AutoRecenter::Circumcenter => match poly.circumsphere() {
+                Some(sphere) => poly.recenter_with(&sphere.center),
+                None => println!("The polytope has no circumsphere, it won't be recentered."),
+            },
+        }
+
+        match self.rescale {
+            AutoRescale::None => {}
+            AutoRescale::UnitCircumradius => match poly.circumsphere() {
+                Some(sphere) if sphere.radius() > 0.0 => poly.scale(1.0 / sphere.radius()),
+                _ => println!("The polytope has no circumsphere, it won't be rescaled."),
+            },
+            AutoRescale::UnitEdge => match poly.edge_len(0) {
+                Some(len) if len > 0.0 => poly.scale(1.0 / len),
+                _ => println!("The polytope has no edges, it won't be rescaled."),
+            },
+        }
+    }
+}
+
+/// The options used when saving OFF files.
+#[derive(Clone, Copy, Serialize, Deserialize, Resource)]
+#[serde(default)]
+pub struct SaveSettings {
+    /// Whether to write comments naming each section.
+    pub comments: bool,
+
+    /// Whether to round the coordinates to a fixed precision.
+    pub round: bool,
+
+    /// The number of decimal places to round to.
+    pub precision: usize,
+
+    /// Whether to sort vertices and elements in a canonical order.
+    pub sort: bool,
+
+    /// Whether to write the name of the polytope at the top of the file.
+    pub provenance: bool,
+
+    /// Whether to use Windows line endings.
+    pub windows_line_endings: bool,
+}
+
+impl Default for SaveSettings {
+    fn default() -> Self {
+        Self {
+            comments: true,
+            round: false,
+            precision: 10,
+            sort: false,
+            provenance: true,
+            windows_line_endings: false,
+        }
+    }
+}
+
+impl SaveSettings {
+    /// Returns the options to save a polytope with a given name.
+    pub fn off_options(&self, name: &str) -> OffOptions {
+        OffOptions {
+            comments: self.comments,
+            precision: self.round.then_some(self.precision),
+            sort: self.sort,
+            provenance: self.provenance.then(|| name.to_string()),
+            line_ending: if self.windows_line_endings {
+                LineEnding::Windows
+            } else {
+                LineEnding::Unix
+            },
+        }
+    }
+}
+
 /// Updates the application appearance whenever the visuals are changed. This
 /// occurs at application startup and whenever the user toggles light/dark mode.
 fn update_visuals(mut egui_ctx: EguiContexts<'_, '_>, visuals: Res<'_, CurrentVisuals>) -> Result {
@@ -165,11 +410,43 @@ pub struct Config {
     /// The wireframe color of the polytope.
     pub wf_color: WfColor,
 
+    /// How to color the edges of the wireframe.
+    #[serde(default)]
+    pub wf_color_mode: WfColorMode,
+
+    /// Whether to offset coplanar faces when rendering.
+    #[serde(default)]
+    pub offset_coplanar: OffsetCoplanarFaces,
+
+    /// Whether to draw face intersections in the wireframe.
+    #[serde(default)]
+    pub show_intersections: ShowIntersections,
+
+    /// Whether to preview destructive operations before applying them.
+    #[serde(default)]
+    pub preview_operations: PreviewOperations,
+
+    /// Whether to show the performance HUD.
+    #[serde(default)]
+    pub show_performance: ShowPerformance,
+
+    /// How the faces of the polytope are lit and culled.
+    #[serde(default)]
+    pub render_settings: RenderSettings,
+
     /// Whether light mode is enabled.
     pub light_mode: LightMode,
 
     /// Number of memory slots per page.
     pub slots_per_page: SlotsPerPage,
+
+    /// The transformations applied to loaded polytopes.
+    #[serde(default)]
+    pub load_settings: LoadSettings,
+
+    /// The options used when saving OFF files.
+    #[serde(default)]
+    pub save_settings: SaveSettings,
 }
 
 impl Config {
@@ -182,6 +459,22 @@ impl Config {
         }
     }
 
+    /// Returns the path to the cache directory in Miratope, where expensive
+    /// computations like the elements of big symmetry groups are kept between
+    /// sessions.
+    pub fn cache_dir() -> PathBuf {
+        if let Some(proj_dir) = ProjectDirs::from("rs", "Miratope", "Miratope") {
+            proj_dir.cache_dir().to_owned()
+        } else {
+            PathBuf::new()
+        }
+    }
+
+    /// Returns the cache where the elements of Coxeter groups are stored.
+    pub fn group_cache() -> GroupCache {
+        GroupCache::new(Self::cache_dir().join("groups"))
+    }
+
     /// Attempts to read the configuration from a given path.
     pub fn from_path<T: AsRef<OsStr>>(config_path: T) -> Option<Self> {
         ron::from_str(&fs::read_to_string(config_path.as_ref()).ok()?).ok()
@@ -242,8 +535,16 @@ fn save_config(
     background_color: Res<'_, ClearColor>,
     mesh_color: Res<'_, MeshColor>,
     wf_color: Res<'_, WfColor>,
+    wf_color_mode: Res<'_, WfColorMode>,
+    offset_coplanar: Res<'_, OffsetCoplanarFaces>,
+    show_intersections: Res<'_, ShowIntersections>,
+    preview_operations: Res<'_, PreviewOperations>,
+    show_performance: Res<'_, ShowPerformance>,
+    render_settings: Res<'_, RenderSettings>,
     visuals: Res<'_, CurrentVisuals>,
     slots_per_page: Res<'_, SlotsPerPage>,
+    load_settings: Res<'_, LoadSettings>,
+    save_settings: Res<'_, SaveSettings>,
 ) {
     // If the application is being exited:
     if exit.read().next().is_some() {
@@ -251,8 +552,16 @@ fn save_config(
             background_color: BgColor::new(background_color.as_ref()),
             mesh_color: mesh_color.clone(),
             wf_color: wf_color.clone(),
+            wf_color_mode: *wf_color_mode,
+            offset_coplanar: *offset_coplanar,
+            show_intersections: *show_intersections,
+            preview_operations: *preview_operations,
+            show_performance: *show_performance,
+            render_settings: *render_settings,
             light_mode: LightMode(!visuals.0.dark_mode),
             slots_per_page: slots_per_page.clone(),
+            load_settings: *load_settings,
+            save_settings: *save_settings,
         };
 
         config.save(&config_path.0);
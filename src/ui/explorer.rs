@@ -0,0 +1,343 @@
+//! Lets the user browse the sections of the loaded polytope, by picking a lower
+//! and an upper element and moving them along the edges of its lattice.
+
+use super::{
+    faceting::{facet_under_stabilizer, FacetingJob},
+    main_window::PolyName,
+    memory::Memory,
+    preview::Preview,
+    window::{FacetingSettings, ShowWindows},
+};
+use crate::Concrete;
+use miratope_core::{
+    abs::{ranked::ElementHash, Ranked},
+    conc::{symmetry::SymmetryCache, ConcretePolytope},
+    Polytope,
+};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+use vec_like::VecLike;
+
+/// The plugin in charge of the section explorer.
+pub struct ExplorerPlugin;
+
+impl Plugin for ExplorerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SectionExplorer>()
+            .add_systems(Update, reset_explorer)
+            .add_systems(EguiPrimaryContextPass, show_explorer.in_set(ShowWindows));
+    }
+}
+
+/// An element of the polytope, given by its rank and its index.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct ElementRef {
+    /// The rank of the element.
+    pub rank: usize,
+
+    /// The index of the element within its rank.
+    pub idx: usize,
+}
+
+impl ElementRef {
+    /// Initializes a new element reference.
+    pub fn new(rank: usize, idx: usize) -> Self {
+        Self { rank, idx }
+    }
+}
+
+/// The state of the section explorer, which shows the section between two
+/// elements of the loaded polytope.
+#[derive(Default, Resource)]
+pub struct SectionExplorer {
+    /// Whether the window is open.
+    pub open: bool,
+
+    /// The lower element of the section.
+    lo: ElementRef,
+
+    /// The upper element of the section.
+    hi: ElementRef,
+
+    /// The element counts of the section, or `None` inside if the lower
+    /// element isn't under the upper one. These are cached until either
+    /// element changes.
+    counts: Option<Option<Vec<usize>>>,
+}
+
+impl SectionExplorer {
+    /// Opens the window on the section between the minimal and the maximal
+    /// element, that is, on the whole polytope.
+    pub fn open(&mut self, poly: &Concrete) {
+        self.open = true;
+        self.reset(poly);
+    }
+
+    /// Sets the elements back to the minimal and the maximal element.
+    fn reset(&mut self, poly: &Concrete) {
+        self.lo = ElementRef::new(0, 0);
+        self.hi = ElementRef::new(poly.rank(), 0);
+        self.counts = None;
+    }
+
+    /// Sets the lower element.
+    fn set_lo(&mut self, lo: ElementRef) {
+        if self.lo != lo {
+            self.lo = lo;
+            self.counts = None;
+        }
+    }
+
+    /// Sets the upper element.
+    fn set_hi(&mut self, hi: ElementRef) {
+        if self.hi != hi {
+            self.hi = hi;
+            self.counts = None;
+        }
+    }
+
+    /// Returns the element counts of the section, or `None` if the elements
+    /// don't define one. Only the abstract section is built, so that this is
+    /// cheap enough to show every frame.
+    fn counts(&mut self, poly: &Concrete) -> Option<&[usize]> {
+        let (lo, hi) = (self.lo, self.hi);
+        self.counts
+            .get_or_insert_with(|| {
+                let section = poly.abs.section(lo.rank, lo.idx, hi.rank, hi.idx).ok()??;
+                Some((0..=section.rank()).map(|r| section.el_count(r)).collect())
+            })
+            .as_deref()
+    }
+
+    /// Returns the section as a concrete polytope, flattened and centered at
+    /// the origin, or `None` if it can't be computed.
+    fn section(&self, poly: &Concrete) -> Option<Concrete> {
+        let (lo, hi) = (self.lo, self.hi);
+        match poly.section(lo.rank, lo.idx, hi.rank, hi.idx) {
+            Ok(Some(mut section)) => {
+                section.flatten();
+                section.recenter();
+                Some(section)
+            }
+            Ok(None) => {
+                eprintln!("Section failed: the lower element isn't under the upper one.");
+                None
+            }
+            Err(err) => {
+                eprintln!("Section failed: {}", err);
+                None
+            }
+        }
+    }
+}
+
+/// Returns whether an element is a subelement of another, or the same element.
+fn is_under(poly: &Concrete, lo: ElementRef, hi: ElementRef) -> bool {
+    ElementHash::new(&poly.abs, hi.rank, hi.idx)
+        .and_then(|hash| hash.index(lo.rank, lo.idx))
+        .is_some()
+}
+
+/// Returns the name of the elements of a given rank.
+fn rank_name(rank: usize, poly_rank: usize) -> String {
+    if rank == 0 {
+        "Nullitope".to_string()
+    } else if rank == poly_rank {
+        "Body".to_string()
+    } else {
+        format!("{}-element", rank - 1)
+    }
+}
+
+/// Shows a picker for an element of the polytope. The index is clamped
+/// whenever the rank changes.
+fn element_picker(ui: &mut egui::Ui, label: &str, el: &mut ElementRef, poly: &Concrete) {
+    let rank = poly.rank();
+    ui.horizontal(|ui| {
+        ui.label(label);
+        ui.add(egui::DragValue::new(&mut el.rank).range(0..=rank).prefix("rank "));
+
+        let count = poly.el_count(el.rank);
+        el.idx = el.idx.min(count.saturating_sub(1));
+        ui.add_enabled(
+            count > 1,
+            egui::DragValue::new(&mut el.idx).range(0..=count.saturating_sub(1)).prefix("#"),
+        );
+        ui.label(format!("({} of {})", rank_name(el.rank, rank), count));
+    });
+}
+
+/// Shows a row of buttons for some elements adjacent to a given one, and
+/// returns the one that was clicked.
+fn neighbors(ui: &mut egui::Ui, label: &str, rank: usize, elements: &[usize]) -> Option<ElementRef> {
+    let mut clicked = None;
+    ui.horizontal_wrapped(|ui| {
+        ui.label(label);
+        if elements.is_empty() {
+            ui.label("none");
+        }
+
+        for &idx in elements {
+            if ui.button(format!("#{}", idx)).clicked() {
+                clicked = Some(ElementRef::new(rank, idx));
+            }
+        }
+    });
+    clicked
+}
+
+/// Shows the section explorer, and loads, stores or previews the section the
+/// user picks. Facetings can also be started under the stabilizer of either
+/// element.
+pub fn show_explorer(
+    mut egui_ctx: EguiContexts<'_, '_>,
+    mut explorer: ResMut<'_, SectionExplorer>,
+    mut query: Query<'_, '_, &mut Concrete>,
+    mut poly_name: ResMut<'_, PolyName>,
+    mut memory: ResMut<'_, Memory>,
+    mut preview: ResMut<'_, Preview>,
+    (mut faceting_job, faceting_settings, mut symmetry): (
+        ResMut<'_, FacetingJob>,
+        Res<'_, FacetingSettings>,
+        ResMut<'_, SymmetryCache>,
+    ),
+) -> Result {
+    if !explorer.open {
+        return Ok(());
+    }
+
+    let Some(poly) = query.iter().next() else {
+        return Ok(());
+    };
+
+    let mut is_open = true;
+    let mut load = false;
+    let mut save = false;
+    let mut show_preview = false;
+    let mut stabilizer = None;
+
+    egui::Window::new("Section explorer")
+        .open(&mut is_open)
+        .resizable(false)
+        .show(egui_ctx.ctx_mut()?, |ui| {
+            let (mut lo, mut hi) = (explorer.lo, explorer.hi);
+            element_picker(ui, "Lower:", &mut lo, poly);
+            element_picker(ui, "Upper:", &mut hi, poly);
+            explorer.set_lo(lo);
+            explorer.set_hi(hi);
+
+            ui.separator();
+
+            let valid = match explorer.counts(poly) {
+                Some(counts) => {
+                    let counts: Vec<String> = counts.iter().map(usize::to_string).collect();
+                    ui.label(format!("Section of rank {}: [{}]", counts.len() - 1, counts.join(", ")));
+                    true
+                }
+                None => {
+                    ui.label("The lower element isn't under the upper one.");
+                    false
+                }
+            };
+
+            ui.separator();
+
+            // The adjacent elements. Moving the lower element down or the
+            // upper element up always gives a section, but the other moves
+            // only list the elements that keep one.
+            let (lo, hi) = (explorer.lo, explorer.hi);
+            if lo.rank > 0 {
+                let subs: Vec<usize> = poly.abs[(lo.rank, lo.idx)].subs.iter().copied().collect();
+                if let Some(el) = neighbors(ui, "Lower down:", lo.rank - 1, &subs) {
+                    explorer.set_lo(el);
+                }
+            }
+
+            if valid && lo.rank < hi.rank {
+                let sups: Vec<usize> = poly.abs[(lo.rank, lo.idx)]
+                    .sups
+                    .iter()
+                    .copied()
+                    .filter(|&idx| is_under(poly, ElementRef::new(lo.rank + 1, idx), hi))
+                    .collect();
+                if let Some(el) = neighbors(ui, "Lower up:", lo.rank + 1, &sups) {
+                    explorer.set_lo(el);
+                }
+
+                let subs: Vec<usize> = poly.abs[(hi.rank, hi.idx)]
+                    .subs
+                    .iter()
+                    .copied()
+                    .filter(|&idx| is_under(poly, lo, ElementRef::new(hi.rank - 1, idx)))
+                    .collect();
+                if let Some(el) = neighbors(ui, "Upper down:", hi.rank - 1, &subs) {
+                    explorer.set_hi(el);
+                }
+            }
+
+            if hi.rank < poly.rank() {
+                let sups: Vec<usize> = poly.abs[(hi.rank, hi.idx)].sups.iter().copied().collect();
+                if let Some(el) = neighbors(ui, "Upper up:", hi.rank + 1, &sups) {
+                    explorer.set_hi(el);
+                }
+            }
+
+            ui.separator();
+
+            ui.add_enabled_ui(valid, |ui| {
+                ui.horizontal(|ui| {
+                    show_preview = ui.button("Preview").clicked();
+                    load = ui.button("Load").clicked();
+                    save = ui.button("Save to memory").clicked();
+                });
+            });
+
+            ui.separator();
+
+            // Facets the polytope under the symmetries that fix an element.
+            ui.add_enabled_ui(!faceting_job.is_running(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Facet under the stabilizer of:");
+                    if ui.button("Lower").clicked() {
+                        stabilizer = Some(explorer.lo);
+                    }
+                    if ui.button("Upper").clicked() {
+                        stabilizer = Some(explorer.hi);
+                    }
+                });
+            });
+        });
+
+    if let Some(el) = stabilizer {
+        facet_under_stabilizer(&mut faceting_job, poly, &mut symmetry, (el.rank, el.idx), &faceting_settings);
+    }
+
+    if show_preview || load || save {
+        if let Some(section) = explorer.section(poly) {
+            let name = format!("Section of {}", poly_name.0);
+            if show_preview {
+                preview.set(section, name);
+            } else if save {
+                memory.push((section, Some(name)));
+            } else if let Some(mut p) = query.iter_mut().next() {
+                *p = section;
+                poly_name.0 = name;
+            }
+        }
+    }
+
+    if !is_open {
+        explorer.open = false;
+    }
+
+    Ok(())
+}
+
+/// Sets the elements back to the whole polytope whenever it changes, since the
+/// indices no longer refer to the same elements.
+fn reset_explorer(polies: Query<'_, '_, &Concrete, Changed<Concrete>>, mut explorer: ResMut<'_, SectionExplorer>) {
+    if let Some(poly) = polies.iter().next() {
+        explorer.reset(poly);
+    }
+}
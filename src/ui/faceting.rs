@@ -0,0 +1,217 @@
+//! Runs facetings in the background, and shows how far along they are.
+
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use super::{
+    memory::Memory,
+    perf::{timed, PerfStats},
+    window::{FacetingSettings, ShowWindows},
+};
+use crate::{Concrete, Point};
+use miratope_core::{
+    cancel::CancellationToken,
+    conc::{
+        faceting::{FacetingProgress, GroupEnum, ProgressReporter},
+        fingerprint::FingerprintDb,
+        symmetry::SymmetryCache,
+    },
+};
+
+use bevy::prelude::*;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+/// The plugin in charge of background facetings.
+pub struct FacetingPlugin;
+
+impl Plugin for FacetingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FacetingJob>()
+            .add_systems(EguiPrimaryContextPass, show_faceting_progress.in_set(ShowWindows));
+    }
+}
+
+/// A faceting running in the background.
+pub struct RunningFaceting {
+    /// The task computing the facetings, and how long it took.
+    task: Task<(Vec<(Concrete, Option<String>)>, Duration)>,
+
+    /// The last progress report of the faceting.
+    progress: Arc<Mutex<Option<FacetingProgress>>>,
+
+    /// The token that stops the faceting.
+    cancel: CancellationToken,
+}
+
+/// The faceting currently running in the background, if any.
+#[derive(Default, Resource)]
+pub struct FacetingJob(Option<RunningFaceting>);
+
+impl FacetingJob {
+    /// Returns whether a faceting is running.
+    pub fn is_running(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Starts a faceting in the background. The function is given a reporter
+    /// that it should pass on to [`Concrete::faceting`].
+    pub fn start(
+        &mut self,
+        faceting: impl FnOnce(&mut ProgressReporter) -> Vec<(Concrete, Option<String>)> + Send + 'static,
+    ) {
+        let progress = Arc::new(Mutex::new(None));
+        let sink = Arc::clone(&progress);
+        let cancel = CancellationToken::new();
+        let mut reporter = ProgressReporter::callback(move |report| {
+            *sink.lock().unwrap() = Some(report.clone());
+        })
+        .with_cancellation(cancel.clone());
+
+        let task = AsyncComputeTaskPool::get().spawn(async move { timed(|| faceting(&mut reporter)) });
+        self.0 = Some(RunningFaceting { task, progress, cancel });
+    }
+
+    /// Starts enumerating the facetings of a polytope in the background, on
+    /// some vertices and under some symmetry group, with the given settings.
+    pub fn start_with_settings(
+        &mut self,
+        poly: &Concrete,
+        vertices: Vec<Point>,
+        group: GroupEnum,
+        settings: &FacetingSettings,
+    ) {
+        // The faceting runs in the background, on its own copy of the settings.
+        let mut poly = poly.clone();
+        let settings = settings.clone();
+        self.start(move |progress| {
+            let mut facetings = poly.faceting(vertices, group, &settings.options(), progress);
+
+            if settings.recognize {
+                name_known(&mut facetings, &settings.known_path);
+            }
+            facetings
+        });
+    }
+}
+
+/// Appends the names of the known polytopes among the results of a faceting
+/// to their labels. These are looked up in the shipped database, and in the
+/// database or folder of OFF files at a path, if any.
+fn name_known(facetings: &mut [(Concrete, Option<String>)], path: &str) {
+    let mut db = FingerprintDb::builtin();
+    if !path.is_empty() {
+        let path = Path::new(path);
+        let result = if path.is_dir() { db.load_dir(path) } else { db.load(path) };
+        if let Err(err) = result {
+            eprintln!("Loading known polytopes failed: {}", err);
+        }
+    }
+
+    let mut known = 0;
+    for (poly, label) in facetings.iter_mut() {
+        if let Some(name) = db.identify(poly) {
+            *label = Some(match label.take() {
+                Some(label) => format!("{} = {}", label, name),
+                None => name,
+            });
+            known += 1;
+        }
+    }
+
+    if !facetings.is_empty() {
+        println!("{} of {} results are known polytopes.", known, facetings.len());
+    }
+}
+
+/// Starts enumerating the facetings of a polytope under the stabilizer of one
+/// of its elements, which is found from its cached symmetry group.
+pub fn facet_under_stabilizer(
+    job: &mut FacetingJob,
+    poly: &Concrete,
+    symmetry: &mut SymmetryCache,
+    (rank, idx): (usize, usize),
+    settings: &FacetingSettings,
+) {
+    match symmetry.element_stabilizer(poly, rank, idx) {
+        Some((_, vertex_map)) => {
+            job.start_with_settings(poly, poly.vertices.clone(), GroupEnum::VertexMap(vertex_map), settings)
+        }
+        None => eprintln!("Faceting failed: the stabilizer could not be computed."),
+    }
+}
+
+/// Shows the progress of the running faceting, and stores its results in
+/// memory once it finishes.
+pub fn show_faceting_progress(
+    mut egui_ctx: EguiContexts<'_, '_>,
+    mut job: ResMut<'_, FacetingJob>,
+    mut memory: ResMut<'_, Memory>,
+    mut perf: ResMut<'_, PerfStats>,
+) -> Result {
+    if job.0.as_ref().is_some_and(|running| running.task.is_finished()) {
+        let running = job.0.take().unwrap();
+        let (facetings, duration) = block_on(running.task);
+        perf.record("Faceting", duration);
+        for faceting in facetings {
+            memory.push(faceting);
+        }
+    }
+
+    let Some(running) = &job.0 else {
+        return Ok(());
+    };
+
+    let progress = running.progress.lock().unwrap().clone();
+    let mut cancel = false;
+
+    egui::Window::new("Faceting progress")
+        .resizable(false)
+        .show(egui_ctx.ctx_mut()?, |ui| {
+            match progress {
+                Some(progress) => {
+                    ui.heading(progress.phase.to_string());
+
+                    match progress.fraction() {
+                        Some(fraction) => {
+                            ui.add(egui::ProgressBar::new(fraction).show_percentage());
+                        }
+                        None => {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label(format!("{} done", progress.done));
+                            });
+                        }
+                    }
+
+                    ui.label(progress.message.trim());
+
+                    let mut times = format!("Elapsed: {}s", progress.elapsed.as_secs());
+                    if let Some(eta) = progress.eta() {
+                        times.push_str(&format!(", about {}s left", eta.as_secs()));
+                    }
+                    ui.label(times);
+                }
+                None => {
+                    ui.spinner();
+                }
+            }
+
+            ui.separator();
+
+            cancel = ui.button("Cancel").clicked();
+        });
+
+    // The faceting stops at its next check, and whatever it found is
+    // discarded.
+    if cancel {
+        if let Some(running) = job.0.take() {
+            running.cancel.cancel();
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,251 @@
+//! Shows the vertex coordinates of the polytope in frames adapted to its
+//! symmetry, and rotates it into them. Also rotates the polytope to rest on
+//! one of its facets.
+
+use super::{main_window::PolyName, selection::FaceSelection, window::ShowWindows};
+use crate::Concrete;
+use miratope_core::{
+    abs::Ranked,
+    conc::{
+        frame::{frame_with_axis, RotationAxis},
+        ConcretePolytope,
+    },
+    geometry::Matrix,
+};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+/// The coordinate axis that points up in the viewport.
+const UP_AXIS: usize = 1;
+
+/// The plugin in charge of the coordinate frame window.
+pub struct FramePlugin;
+
+impl Plugin for FramePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CoordinateFrame>()
+            .add_systems(EguiPrimaryContextPass, show_coordinate_frame.in_set(ShowWindows));
+    }
+}
+
+/// The frames in which the coordinates can be shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FrameChoice {
+    /// The usual coordinate axes.
+    #[default]
+    Cartesian,
+
+    /// The principal axes of inertia, in increasing order of their moments.
+    Principal,
+
+    /// A rotation axis as the z-axis, given by its index.
+    Axis(usize),
+}
+
+/// The state of the coordinate frame window.
+#[derive(Default, Resource)]
+pub struct CoordinateFrame {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The frame the coordinates are shown in.
+    choice: FrameChoice,
+
+    /// The rotation axes of the polytope, once computed.
+    axes: Option<Vec<RotationAxis>>,
+
+    /// The frame matrix, once computed. This is `Some(None)` if the frame
+    /// doesn't exist for this polytope.
+    frame: Option<Option<Matrix<f64>>>,
+
+    /// The facet to rest the polytope on.
+    facet: usize,
+}
+
+impl CoordinateFrame {
+    /// Opens the window.
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// Forgets everything computed from the polytope.
+    fn invalidate(&mut self) {
+        self.axes = None;
+        self.frame = None;
+        if matches!(self.choice, FrameChoice::Axis(_)) {
+            self.choice = FrameChoice::Cartesian;
+        }
+    }
+
+    /// Computes the frame matrix, given by an orthonormal basis as columns.
+    fn compute_frame(&self, poly: &Concrete) -> Option<Matrix<f64>> {
+        let dim = poly.dim()?;
+
+        match self.choice {
+            FrameChoice::Cartesian => Some(Matrix::identity(dim, dim)),
+            FrameChoice::Principal => Some(poly.mass_properties()?.principal_axes().1),
+            FrameChoice::Axis(idx) => {
+                let axes = self.axes.as_ref()?;
+                let main = axes.get(idx)?;
+                let secondary = axes
+                    .iter()
+                    .find(|other| other.axis.dot(&main.axis).abs() < 1e-6)
+                    .map(|other| &other.axis);
+                Some(frame_with_axis(&main.axis, secondary))
+            }
+        }
+    }
+}
+
+/// Shows the coordinate frame window.
+pub fn show_coordinate_frame(
+    mut egui_ctx: EguiContexts<'_, '_>,
+    mut state: ResMut<'_, CoordinateFrame>,
+    mut query: Query<'_, '_, &mut Concrete>,
+    mut poly_name: ResMut<'_, PolyName>,
+    selection: Res<'_, FaceSelection>,
+) -> Result {
+    let Some(mut poly) = query.iter_mut().next() else {
+        return Ok(());
+    };
+
+    if poly.is_changed() {
+        state.invalidate();
+    }
+
+    if !state.open {
+        return Ok(());
+    }
+
+    let mut open = true;
+    let mut rotate = None;
+    let mut standard = false;
+    let mut find_axes = false;
+    let mut rest = false;
+
+    egui::Window::new("Coordinate frame")
+        .open(&mut open)
+        .default_width(320.0)
+        .show(egui_ctx.ctx_mut()?, |ui| {
+            let state = &mut *state;
+            let old_choice = state.choice;
+
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut state.choice, FrameChoice::Cartesian, "Cartesian");
+                ui.radio_value(&mut state.choice, FrameChoice::Principal, "Principal axes");
+            });
+
+            // Rotation axes are only found on demand, since they need the
+            // symmetry group.
+            match &state.axes {
+                None => {
+                    find_axes = ui
+                        .add_enabled(poly.dim() == Some(3), egui::Button::new("Find rotation axes"))
+                        .clicked();
+                }
+                Some(axes) if axes.is_empty() => {
+                    ui.label("No rotation axes found.");
+                }
+                Some(axes) => {
+                    let labels: Vec<String> = axes
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, axis)| format!("Axis {} ({}-fold) as z", idx + 1, axis.order))
+                        .collect();
+
+                    ui.horizontal_wrapped(|ui| {
+                        for (idx, label) in labels.iter().enumerate() {
+                            ui.radio_value(&mut state.choice, FrameChoice::Axis(idx), label);
+                        }
+                    });
+                }
+            }
+
+            if state.choice != old_choice {
+                state.frame = None;
+            }
+
+            if state.frame.is_none() {
+                state.frame = Some(state.compute_frame(&poly));
+            }
+
+            ui.separator();
+
+            match state.frame.as_ref().and_then(Option::as_ref) {
+                Some(frame) => {
+                    egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        for (idx, v) in poly.frame_coordinates(frame).iter().enumerate() {
+                            let coords: Vec<String> = v.iter().map(|c| format!("{:.6}", c)).collect();
+                            ui.monospace(format!("{}: ({})", idx, coords.join(", ")));
+                        }
+                    });
+
+                    ui.separator();
+
+                    if ui.button("Rotate into frame").clicked() {
+                        rotate = Some(frame.clone());
+                    }
+                }
+                None => {
+                    ui.label("This frame isn't defined for the polytope.");
+                }
+            }
+
+            standard = ui.button("Standard orientation").clicked();
+
+            ui.separator();
+
+            // Rests the polytope on a facet, so that it's at the bottom of
+            // the viewport.
+            let facet_count = poly.el_count(poly.rank().saturating_sub(1));
+            ui.add_enabled_ui(poly.rank() >= 2 && facet_count > 0, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut state.facet)
+                            .range(0..=facet_count.saturating_sub(1))
+                            .prefix("Facet "),
+                    );
+
+                    // In polyhedra, the facets are the faces that can be
+                    // picked in the viewport.
+                    let picked = match selection.selected.iter().collect::<Vec<_>>()[..] {
+                        [&face] if poly.rank() == 4 => Some(face),
+                        _ => None,
+                    };
+                    if ui
+                        .add_enabled(picked.is_some(), egui::Button::new("Use selected face"))
+                        .clicked()
+                    {
+                        state.facet = picked.unwrap_or_default();
+                    }
+                });
+
+                rest = ui.button("Rest on facet").clicked();
+            });
+        });
+
+    if find_axes {
+        state.axes = Some(poly.rotation_axes().unwrap_or_default());
+    }
+
+    if let Some(frame) = rotate {
+        poly.rotate_into_frame(&frame);
+        poly_name.0 = format!("Rotated {}", poly_name.0);
+    } else if rest {
+        if poly.rest_on_facet(state.facet, UP_AXIS) {
+            poly_name.0 = format!("Rotated {}", poly_name.0);
+        } else {
+            println!("The facet doesn't span a hyperplane.");
+        }
+    } else if standard {
+        if poly.standard_orientation() {
+            poly_name.0 = format!("Rotated {}", poly_name.0);
+        } else {
+            println!("The polytope has no symmetry axes or volume.");
+        }
+    }
+
+    state.open = open;
+    Ok(())
+}
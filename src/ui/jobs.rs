@@ -0,0 +1,302 @@
+//! Runs symmetry group computations and products in the background, so that
+//! they can be cancelled when they take too long.
+
+use std::time::Duration;
+
+use super::{
+    config::WfColorMode,
+    main_window::PolyName,
+    perf::{timed, PerfStats},
+    window::ShowWindows,
+};
+use crate::{Concrete, Float, Point};
+use miratope_core::{
+    abs::product::ProductType,
+    cancel::{CancellationToken, Cancelled},
+    conc::{
+        symmetry::{SymmetryCache, SymmetryGroup},
+        ConcretePolytope,
+    },
+};
+
+use bevy::prelude::*;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+/// The plugin in charge of background jobs.
+pub struct JobPlugin;
+
+impl Plugin for JobPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BackgroundJob>()
+            .add_systems(EguiPrimaryContextPass, show_job_progress.in_set(ShowWindows));
+    }
+}
+
+/// What to print about the symmetry group of a polytope once it's found.
+#[derive(Clone, Copy)]
+pub enum SymmetryReport {
+    /// The order and classification of the symmetry group.
+    Order,
+
+    /// The order of the rotation group.
+    RotationOrder,
+
+    /// Whether the polytope is chiral.
+    Chirality,
+}
+
+impl SymmetryReport {
+    /// Prints the report for a polytope, if its symmetry group is in the cache.
+    fn print(self, poly: &Concrete, symmetry: &mut SymmetryCache) {
+        let Some((group, _)) = symmetry.get(poly) else {
+            println!("The symmetry group could not be computed.");
+            return;
+        };
+
+        match self {
+            Self::Order => {
+                let order = group.count();
+                match symmetry.classify(poly) {
+                    Some(class) => println!("Symmetry order {} ({})", order, class),
+                    None => println!("Symmetry order {}", order),
+                }
+            }
+            Self::RotationOrder => {
+                println!("Rotation symmetry order {}", group.filter(|m| m.determinant() > 0.0).count());
+            }
+            Self::Chirality => {
+                let group: Vec<_> = group.collect();
+                let rotations = group.iter().filter(|m| m.determinant() > 0.0).count();
+                let chiral = if rotations == group.len() { "yes" } else { "no" };
+                println!(
+                    "Chiral: {} (symmetry order {}, rotation symmetry order {}).",
+                    chiral,
+                    group.len(),
+                    rotations
+                );
+            }
+        }
+    }
+}
+
+/// One of the four products of two polytopes, with the offsets of its factors
+/// and its height, as in [`Concrete::try_product_with`].
+pub struct Product {
+    /// The type of product.
+    pub product_type: ProductType,
+
+    /// The offset of each factor.
+    pub offsets: [Point; 2],
+
+    /// The height of a pyramid product.
+    pub height: Float,
+}
+
+impl Product {
+    /// A product of two polytopes at the origin, with unit height.
+    pub fn new(product_type: ProductType, p: &Concrete, q: &Concrete) -> Self {
+        Self {
+            product_type,
+            offsets: [Point::zeros(p.dim_or()), Point::zeros(q.dim_or())],
+            height: 1.0,
+        }
+    }
+}
+
+/// The result of a background job.
+enum JobOutput {
+    /// The symmetry group of a polytope, if it could be computed, and what to
+    /// print about it.
+    Symmetry {
+        poly: Concrete,
+        group: Option<SymmetryGroup>,
+        report: Option<SymmetryReport>,
+    },
+
+    /// A product that replaces the loaded polytope, along with its name and
+    /// its symmetry group, if it could be built from those of its factors.
+    Product {
+        poly: Concrete,
+        name: String,
+        group: Option<SymmetryGroup>,
+    },
+}
+
+/// A job running in the background.
+struct RunningJob {
+    /// The name of the operation.
+    name: &'static str,
+
+    /// The task running the job, and how long it took.
+    task: Task<(Result<JobOutput, Cancelled>, Duration)>,
+
+    /// The token that stops the job.
+    cancel: CancellationToken,
+}
+
+/// The symmetry group computation or product currently running in the
+/// background, if any.
+#[derive(Default, Resource)]
+pub struct BackgroundJob(Option<RunningJob>);
+
+impl BackgroundJob {
+    /// Returns whether a job is running.
+    pub fn is_running(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Starts a job in the background, cancelling the one running before.
+    fn start(
+        &mut self,
+        name: &'static str,
+        job: impl FnOnce(&CancellationToken) -> Result<JobOutput, Cancelled> + Send + 'static,
+    ) {
+        if let Some(running) = self.0.take() {
+            running.cancel.cancel();
+        }
+
+        let cancel = CancellationToken::new();
+        let token = cancel.clone();
+        let task = AsyncComputeTaskPool::get().spawn(async move { timed(|| job(&token)) });
+        self.0 = Some(RunningJob { name, task, cancel });
+    }
+
+    /// Computes the symmetry group of a polytope in the background and puts it
+    /// into the cache, optionally printing a report on it afterwards.
+    fn start_symmetry(&mut self, poly: &Concrete, report: Option<SymmetryReport>) {
+        let poly = poly.clone();
+        self.start("Symmetry group", move |cancel| {
+            let group = poly.clone().get_symmetry_group_with(cancel)?;
+            Ok(JobOutput::Symmetry { poly, group, report })
+        });
+    }
+
+    /// Prints a report on the symmetry group of a polytope. If the group isn't
+    /// in the cache, it's computed in the background first.
+    pub fn report_symmetry(&mut self, poly: &Concrete, symmetry: &mut SymmetryCache, report: SymmetryReport) {
+        if symmetry.get(poly).is_some() {
+            report.print(poly, symmetry);
+        } else {
+            self.start_symmetry(poly, Some(report));
+        }
+    }
+
+    /// Computes the symmetry group of a polytope in the background, unless it's
+    /// already in the cache or another job is running. The wireframe is redrawn
+    /// once it's found, so that it can be colored by orbit.
+    pub fn cache_symmetry(&mut self, poly: &Concrete, symmetry: &SymmetryCache) {
+        if !self.is_running() && symmetry.get(poly).is_none() {
+            self.start_symmetry(poly, None);
+        }
+    }
+
+    /// Builds a product of two polytopes in the background, which replaces the
+    /// loaded polytope once it's done. Its symmetry group is built from those
+    /// of its factors whenever possible.
+    pub fn product(
+        &mut self,
+        name: &'static str,
+        (p, q): (&Concrete, &Concrete),
+        product: Product,
+        poly_name: String,
+        symmetry: &SymmetryCache,
+    ) {
+        let (p_group, q_group) = (symmetry.get(p), symmetry.get(q));
+        let (p, q) = (p.clone(), q.clone());
+
+        self.start(name, move |cancel| {
+            let [p_offset, q_offset] = &product.offsets;
+            let poly = p.try_product_with(&q, product.product_type, p_offset, q_offset, product.height, cancel)?;
+
+            let p_group = match p_group {
+                Some(group) => Some(group),
+                None => p.clone().get_symmetry_group_with(cancel)?,
+            };
+            let q_group = match q_group {
+                Some(group) => Some(group),
+                None => q.clone().get_symmetry_group_with(cancel)?,
+            };
+            let group = p_group
+                .zip(q_group)
+                .and_then(|((p_group, _), (q_group, _))| poly.product_symmetry_group((&p, p_group), (&q, q_group)));
+
+            Ok(JobOutput::Product {
+                poly,
+                name: poly_name,
+                group,
+            })
+        });
+    }
+}
+
+/// Shows that a job is running, with a button to cancel it, and applies its
+/// result once it finishes.
+fn show_job_progress(
+    mut egui_ctx: EguiContexts<'_, '_>,
+    mut job: ResMut<'_, BackgroundJob>,
+    mut query: Query<'_, '_, &mut Concrete>,
+    mut poly_name: ResMut<'_, PolyName>,
+    mut symmetry: ResMut<'_, SymmetryCache>,
+    mut perf: ResMut<'_, PerfStats>,
+    mut wf_color_mode: ResMut<'_, WfColorMode>,
+) -> Result {
+    if job.0.as_ref().is_some_and(|running| running.task.is_finished()) {
+        let running = job.0.take().unwrap();
+        let (output, duration) = block_on(running.task);
+        perf.record(running.name, duration);
+
+        match output {
+            Ok(JobOutput::Symmetry { poly, group, report }) => {
+                if let Some(group) = group {
+                    symmetry.insert(&poly, group);
+                }
+                if let Some(report) = report {
+                    report.print(&poly, &mut symmetry);
+                }
+
+                // Redraws the wireframe with the orbits that were just found.
+                if *wf_color_mode == WfColorMode::Orbit {
+                    wf_color_mode.set_changed();
+                }
+            }
+            Ok(JobOutput::Product { poly, name, group }) => {
+                if let Some(group) = group {
+                    symmetry.insert(&poly, group);
+                }
+                for mut p in query.iter_mut() {
+                    *p = poly.clone();
+                }
+                poly_name.0 = name;
+            }
+            Err(Cancelled) => {}
+        }
+    }
+
+    let Some(running) = &job.0 else {
+        return Ok(());
+    };
+
+    let mut cancel = false;
+    egui::Window::new(format!("{} in progress", running.name))
+        .resizable(false)
+        .show(egui_ctx.ctx_mut()?, |ui| {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(format!("Computing {}...", running.name.to_lowercase()));
+            });
+
+            ui.separator();
+
+            cancel = ui.button("Cancel").clicked();
+        });
+
+    // The job stops at its next check, and whatever it found is discarded.
+    if cancel {
+        if let Some(running) = job.0.take() {
+            running.cancel.cancel();
+        }
+    }
+
+    Ok(())
+}
@@ -4,14 +4,16 @@ use std::{
     ffi::{OsStr, OsString},
     fs, io,
     path::PathBuf,
+    time::{Duration, Instant},
 };
 
-use super::{config::LibPath, main_window::PolyName};
+use super::{config::{LibPath, LoadSettings}, main_window::PolyName, perf::{timed, PerfStats}, watcher::FileWatcher, window::{Window, WythoffWindow, ZonotopeWindow}};
 use crate::Concrete;
-use miratope_core::file::FromFile;
+use miratope_core::{conc::wythoff::WythoffResult, file::FromFile};
 use special::*;
 
 use bevy::prelude::*;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
 use bevy_egui::{egui, egui::Ui, EguiContexts, EguiPrimaryContextPass};
 use serde::{Deserialize, Serialize};
 use crate::ui::top_panel::show_top_panel;
@@ -23,6 +25,8 @@ pub struct LibraryPlugin;
 
 impl Plugin for LibraryPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<Generating>();
+
         // This must run after the Config resource has been added.
         if let Some(library) = Library::new_folder(&LibPath::default()){
 
@@ -35,6 +39,12 @@ impl Plugin for LibraryPlugin {
     }}
 }
 
+/// A special polytope being built in the background, together with its label
+/// and the time at which it started. The task also returns how long the
+/// polytope took to build.
+#[derive(Default, Resource)]
+pub struct Generating(Option<(Task<(WythoffResult<(Concrete, String)>, Duration)>, String, Instant)>);
+
 /// The result of showing the Miratope library in a particular frame.
 pub enum ShowResult {
     /// Nothing happened this frame.
@@ -86,8 +96,9 @@ impl std::ops::BitOrAssign for ShowResult {
 
 /// Represents any of the files or folders that make up the Miratope library.
 ///
-/// The library is internally stored is a tree-like structure. Once a folder
-/// loads, it's (currently) never unloaded.
+/// The library is internally stored is a tree-like structure. Folders are read
+/// in the background the first time they're expanded, so that large libraries
+/// don't block the UI. Once a folder loads, it's (currently) never unloaded.
 #[derive(Serialize, Deserialize, Resource)]
 pub enum Library {
     /// A folder whose contents have not yet been read.
@@ -96,6 +107,16 @@ pub enum Library {
         name: String,
     },
 
+    /// A folder whose contents are being read in the background.
+    #[serde(skip)]
+    ScanningFolder {
+        /// The name of the folder.
+        name: String,
+
+        /// The task reading the contents of the folder.
+        task: Task<io::Result<Vec<Library>>>,
+    },
+
     /// A folder whose contents have been read.
     LoadedFolder {
         /// The name of the folder.
@@ -121,6 +142,7 @@ impl Library {
     pub fn path_name(&self) -> &str {
         match self {
             Library::UnloadedFolder { name, .. }
+            | Library::ScanningFolder { name, .. }
             | Library::LoadedFolder { name, .. }
             | Library::File { name, .. } => name,
             Library::Special(_) => "",
@@ -182,7 +204,7 @@ impl Library {
                 // Adds a new file.
                 else {
                     let ext = path.extension();
-                    if ext == Some(OsStr::new("off")) || ext == Some(OsStr::new("ggb")) {
+                    if ["off", "moff", "ggb", "inc", "recipe"].iter().any(|&e| ext == Some(OsStr::new(e))) {
                         contents.push(Self::new_file(path));
                     }
                 }
@@ -192,17 +214,66 @@ impl Library {
         }
     }
 
+    /// Starts reading the contents of a folder at a given path in the
+    /// background, if it hasn't been read yet.
+    pub fn scan(&mut self, path: PathBuf) {
+        if let Self::UnloadedFolder { name } = self {
+            let task = AsyncComputeTaskPool::get().spawn(async move { Self::folder_contents(path) });
+            *self = Self::ScanningFolder {
+                name: std::mem::take(name),
+                task,
+            };
+        }
+    }
+
+    /// Returns whether any folder in the library is being read.
+    pub fn is_scanning(&self) -> bool {
+        match self {
+            Self::ScanningFolder { .. } => true,
+            Self::LoadedFolder { contents, .. } => contents.iter().any(Self::is_scanning),
+            _ => false,
+        }
+    }
+
     /// Shows the library in a given `Ui`, starting from a given path.
     pub fn show(&mut self, ui: &mut Ui, path: PathBuf) -> ShowResult {
+        // Replaces a folder that's finished reading by its contents.
+        if let Self::ScanningFolder { task, .. } = self
+            && task.is_finished()
+        {
+            let Self::ScanningFolder { name, task } = std::mem::replace(self, Self::UnloadedFolder { name: String::new() })
+            else {
+                unreachable!()
+            };
+
+            let contents = block_on(task).unwrap_or_else(|err| {
+                eprintln!("Library folder {} could not be read: {}", path.display(), err);
+                Vec::new()
+            });
+            *self = Self::LoadedFolder { name, contents };
+        }
+
         match self {
-            // Shows a collapsing drop-down, and loads the folder in case it's clicked.
+            // Shows a collapsing drop-down, and starts reading the folder once
+            // it's expanded.
             Self::UnloadedFolder { name, .. } => {
-                *self = Self::LoadedFolder {
-                    name: name.clone(),
-                    contents: Self::folder_contents(&path).unwrap(),
-                };
+                if ui.collapsing(name.clone(), |_| {}).body_returned.is_some() {
+                    self.scan(path);
+                }
+
+                ShowResult::None
+            }
+
+            // Shows a collapsing drop-down that's still being read.
+            Self::ScanningFolder { name, .. } => {
+                ui.collapsing(name.clone(), |ui| {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Scanning…");
+                    });
+                });
 
-                self.show(ui, path)
+                ShowResult::None
             }
 
             // Shows a drop-down with all of the files and folders.
@@ -249,13 +320,58 @@ pub fn show_library(
     mut poly_name: ResMut<'_, PolyName>,
     mut library: ResMut<'_, Library>,
     lib_path: Res<'_, LibPath>,
+    load_settings: Res<'_, LoadSettings>,
+    mut generating: ResMut<'_, Generating>,
+    mut perf: ResMut<'_, PerfStats>,
+    mut zonotope_window: ResMut<'_, ZonotopeWindow>,
+    mut wythoff_window: ResMut<'_, WythoffWindow>,
+    mut file_watcher: ResMut<'_, FileWatcher>,
 ) -> Result {
-    // Shows the polytope library.
+    // Loads a polytope that finished generating in the background.
+    if generating.0.as_ref().is_some_and(|(task, _, _)| task.is_finished()) {
+        let (task, label, _) = generating.0.take().unwrap();
+        let (result, duration) = block_on(task);
+        perf.record(label, duration);
+
+        match result {
+            Ok((mut a, b)) => {
+                load_settings.apply(&mut a);
+                *query.iter_mut().next().unwrap() = a;
+                poly_name.0 = b;
+            }
+            Err(err) => eprintln!("Wythoffian failed: {}", err),
+        }
+    }
+
+    // Shows the polytope library. The root folder is read right away.
     let library = library.as_mut();
+    library.scan(PathBuf::from(lib_path.as_ref()));
     egui::SidePanel::left("left_panel")
         .default_width(300.0)
         .max_width(450.0)
         .show(egui_ctx.ctx_mut()?, |ui| {
+            // Shows that a polytope is being generated, and for how long.
+            if let Some((_, label, start)) = &generating.0 {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!(
+                        "Generating {}... ({}s)",
+                        label,
+                        start.elapsed().as_secs()
+                    ));
+                });
+                ui.separator();
+            }
+
+            // Shows that the library is being read.
+            if library.is_scanning() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Scanning library…");
+                });
+                ui.separator();
+            }
+
             egui::containers::ScrollArea::vertical().show(ui, |ui| {
                 match library.show(ui, PathBuf::from(lib_path.as_ref())) {
                     // No action needs to be taken.
@@ -263,21 +379,43 @@ pub fn show_library(
 
                     // Loads a selected file.
                     ShowResult::Load(file) => match Concrete::from_path(&file) {
-                        Ok(q) => {
+                        Ok(mut q) => {
+                            load_settings.apply(&mut q);
                             *query.iter_mut().next().unwrap() = q;
                             let path_buf = PathBuf::from(file);
                             let file_name = path_buf.file_name().unwrap().to_str().unwrap();
                             poly_name.0 = file_name[..file_name.len()-4].into();
+                            file_watcher.watch(path_buf);
                         },
                         Err(err) => eprintln!("File open failed: {}", err),
                     },
 
-                    // Loads a special polytope.
-                    ShowResult::Special(special) => {
-                        let (a, b) = special.load();
-                        *query.iter_mut().next().unwrap() = a;
-                        poly_name.0 = b;
+                    // Zonotopes are built from the generators in their dialog.
+                    ShowResult::Special(SpecialLibrary::Zonotope) => zonotope_window.open(),
+
+                    // Wythoffians are built from the diagram in their dialog.
+                    ShowResult::Special(SpecialLibrary::Diagram) => wythoff_window.open(),
+
+                    // Builds an expensive special polytope in the background.
+                    ShowResult::Special(special) if special.is_expensive() => {
+                        if generating.0.is_some() {
+                            println!("Another polytope is already being generated.");
+                        } else {
+                            let label = special.label().to_string();
+                            let task = AsyncComputeTaskPool::get().spawn(async move { timed(|| special.load()) });
+                            generating.0 = Some((task, label, Instant::now()));
+                        }
                     }
+
+                    // Loads a special polytope.
+                    ShowResult::Special(special) => match special.load() {
+                        Ok((mut a, b)) => {
+                            load_settings.apply(&mut a);
+                            *query.iter_mut().next().unwrap() = a;
+                            poly_name.0 = b;
+                        }
+                        Err(err) => eprintln!("Wythoffian failed: {}", err),
+                    },
                 }
             })
         });
@@ -6,7 +6,7 @@ use bevy_egui::egui;
 use serde::{Deserialize, Serialize};
 
 use crate::Concrete;
-use miratope_core::conc::ConcretePolytope;
+use miratope_core::conc::{wythoff::WythoffResult, ConcretePolytope};
 use miratope_core::Polytope;
 
 use super::ShowResult;
@@ -43,6 +43,88 @@ pub enum SpecialLibrary {
 
     /// An orthoplex.
     Orthoplex(isize),
+
+    /// A Wythoffian polytope of a given rank, with the ringed nodes of its
+    /// Coxeter diagram stored as a bitmask.
+    Wythoffian(WythoffFamily, isize, u8),
+
+    /// A zonotope, whose generators are entered in a separate dialog.
+    Zonotope,
+
+    /// A Wythoffian polytope, whose Coxeter diagram is entered in a separate
+    /// dialog.
+    Diagram,
+}
+
+/// The families of Wythoffian polytopes in the special library, each of them
+/// given by the Coxeter diagram of a symmetry group.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum WythoffFamily {
+    /// The family of the simplex, with diagram x3o3o...o.
+    Simplex,
+
+    /// The family of the hypercube, with diagram x4o3o...o.
+    Hypercube,
+
+    /// The family of the demicube, with diagram x3o3o...o *b3o.
+    Demicube,
+
+    /// The family of the Gosset polytopes, with diagram o3o3o3o3o...o *c3o of
+    /// the groups E6, E7 and E8.
+    Gosset,
+}
+
+impl WythoffFamily {
+    /// Returns the smallest rank of a polytope in the family.
+    fn min_rank(self) -> isize {
+        match self {
+            Self::Simplex | Self::Hypercube => 2,
+            Self::Demicube => 4,
+            Self::Gosset => 6,
+        }
+    }
+
+    /// Returns the Coxeter diagram of a polytope in the family, given which of
+    /// its nodes are ringed. If the diagram branches, the last node is the one
+    /// off the main path.
+    fn diagram(self, rings: &[bool]) -> String {
+        let node = |ringed: bool| if ringed { 'x' } else { 'o' };
+        let branch = match self {
+            Self::Simplex | Self::Hypercube => None,
+            Self::Demicube => Some('b'),
+            Self::Gosset => Some('c'),
+        };
+
+        let path = match branch {
+            Some(_) => &rings[..rings.len().saturating_sub(1)],
+            None => rings,
+        };
+
+        let mut diagram = String::new();
+        for (i, &ringed) in path.iter().enumerate() {
+            if i == 1 && self == Self::Hypercube {
+                diagram.push('4');
+            } else if i != 0 {
+                diagram.push('3');
+            }
+
+            diagram.push(node(ringed));
+        }
+
+        if let (Some(branch), Some(&ringed)) = (branch, rings.last()) {
+            diagram.push_str(&format!(" *{}3{}", branch, node(ringed)));
+        }
+
+        diagram
+    }
+}
+
+/// The largest rank of the Wythoffian polytopes in the library.
+const MAX_WYTHOFF_RANK: isize = 8;
+
+/// Returns which of the first `rank` nodes are ringed in a bitmask.
+fn rings(rank: isize, mask: u8) -> Vec<bool> {
+    (0..rank.max(0) as usize).map(|i| mask & (1 << i) != 0).collect()
 }
 
 impl SpecialLibrary {
@@ -57,9 +139,21 @@ impl SpecialLibrary {
             Self::Simplex(_) => "Simplex",
             Self::Hypercube(_) => "Hypercube",
             Self::Orthoplex(_) => "Orthoplex",
+            Self::Wythoffian(WythoffFamily::Simplex, _, _) => "Simplex family",
+            Self::Wythoffian(WythoffFamily::Hypercube, _, _) => "Hypercube family",
+            Self::Wythoffian(WythoffFamily::Demicube, _, _) => "Demicube family",
+            Self::Wythoffian(WythoffFamily::Gosset, _, _) => "Gosset family",
+            Self::Zonotope => "Zonotope...",
+            Self::Diagram => "From diagram...",
         }
     }
 
+    /// Returns whether the polytope can take long enough to build that it
+    /// should be generated in the background.
+    pub fn is_expensive(&self) -> bool {
+        matches!(self, Self::Wythoffian(..))
+    }
+
     /// Shows the special component of the library. Returns the action selected
     /// by the user, if any.
     pub fn show(&mut self, ui: &mut Ui) -> ShowResult {
@@ -180,12 +274,52 @@ impl SpecialLibrary {
                     ShowResult::None
                 }
             }
+
+            // A Wythoffian polytope, given by the ringed nodes of its diagram.
+            Self::Wythoffian(family, rank, mask) => {
+                let family = *family;
+                let clicked = ui.horizontal_wrapped(|ui| {
+                    let clicked = ui.button(text).clicked();
+
+                    ui.label("Rank:");
+                    ui.add(egui::DragValue::new(rank).speed(0.03).range(family.min_rank()..=MAX_WYTHOFF_RANK));
+
+                    // A checkbox for each node of the diagram.
+                    for i in 0..*rank as usize {
+                        let mut ringed = *mask & (1 << i) != 0;
+                        if ui.checkbox(&mut ringed, "").changed() {
+                            *mask ^= 1 << i;
+                        }
+                    }
+
+                    ui.label(family.diagram(&rings(*rank, *mask)));
+
+                    clicked
+                });
+
+                if clicked.inner {
+                    ShowResult::Special(*self)
+                } else {
+                    ShowResult::None
+                }
+            }
+
+            // Opens the dialog for the generators of a zonotope, or for a
+            // Coxeter diagram.
+            Self::Zonotope | Self::Diagram => {
+                if ui.button(text).clicked() {
+                    ShowResult::Special(*self)
+                } else {
+                    ShowResult::None
+                }
+            }
         }
     }
 
-    /// Loads the given special polytope from the library.
-    pub fn load(&self) -> (Concrete, String) {
-        match *self {
+    /// Loads the given special polytope from the library. Only Wythoffians can
+    /// fail to load, when they have too many vertices.
+    pub fn load(&self) -> WythoffResult<(Concrete, String)> {
+        Ok(match *self {
             // Loads a regular star polygon.
             Self::Polygon(n, d) => (
                 Concrete::star_polygon_with_edge(n, d, 1.0),
@@ -265,6 +399,20 @@ impl SpecialLibrary {
                 Concrete::orthoplex((rank + 1) as usize),
                 format!("{}-orthoplex", rank)
             ),
-        }
+
+            // Builds a Wythoffian polytope from its Coxeter diagram.
+            Self::Wythoffian(family, rank, mask) => {
+                let diagram = family.diagram(&rings(rank, mask));
+                (Concrete::parse_wythoff(&diagram)?, diagram)
+            }
+
+            // The library opens a dialog for zonotopes instead, so we just
+            // load the cube, the zonotope of the coordinate axes.
+            Self::Zonotope => (Concrete::hypercube(4), "Zonotope".to_string()),
+
+            // The library opens a dialog for diagrams instead, so we just load
+            // the cube, the Wythoffian of x4o3o.
+            Self::Diagram => (Concrete::hypercube(4), "x4o3o".to_string()),
+        })
     }
 }
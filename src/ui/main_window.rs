@@ -1,15 +1,28 @@
 //! The systems that update the main window.
 
-use super::config::{MeshColor, WfColor};
+use std::time::Instant;
+
+use super::config::{
+    MeshColor, OffsetCoplanarFaces, RenderSettings, ShowIntersections, WfColor, WfColorMode,
+};
+use super::jobs::BackgroundJob;
+use super::perf::PerfStats;
+use super::compare::CompareMesh;
+use super::preview::PreviewMesh;
 use super::right_panel::ElementTypesRes;
+use super::selection::FaceSelection;
 use super::{camera::ProjectionType, top_panel::SectionState};
-use crate::mesh::Renderable;
-use crate::Concrete;
+use crate::mesh::{add_segments, class_color, vertex_coords, Renderable};
+use crate::{Concrete, EPS};
 
 use bevy::prelude::*;
+use bevy::render::render_resource::Face;
 use bevy::window::PrimaryWindow;
 use bevy_egui::EguiContextSettings;
-use miratope_core::abs::Ranked;
+use miratope_core::{
+    abs::Ranked,
+    conc::{symmetry::SymmetryCache, ConcretePolytope},
+};
 
 /// The plugin in charge of the Miratope main window, and of drawing the
 /// polytope onto it.
@@ -20,8 +33,11 @@ impl Plugin for MainWindowPlugin {
         app.add_systems(PreUpdate, update_visible)
             .add_systems(Update, update_scale_factor)
             .add_systems(PostUpdate, update_changed_polytopes)
+            .add_systems(PostUpdate, update_changed_wf_color_mode)
             .add_systems(PostUpdate, update_changed_color)
-            .init_resource::<PolyName>();
+            .init_resource::<PolyName>()
+            .init_resource::<WfLegend>()
+            .init_resource::<SymmetryCache>();
     }
 }
 
@@ -34,10 +50,111 @@ impl Default for PolyName {
     }
 }
 
+/// The colors used in the wireframe, together with what each of them means.
+/// This is empty when every edge has the same color.
+#[derive(Default, Resource)]
+pub struct WfLegend(pub Vec<(Color, String)>);
+
+/// Splits the edges of a polytope into classes according to a coloring mode.
+/// Returns the class of each edge, together with a label for each class.
+///
+/// Orbits are only found if the symmetry group is already in the cache, since
+/// computing it can take a long time. Otherwise, it's computed in the
+/// background, and the edges are left uncolored until it's found.
+fn edge_classes(
+    poly: &Concrete,
+    mode: WfColorMode,
+    symmetry: &SymmetryCache,
+    job: &mut BackgroundJob,
+) -> Option<(Vec<usize>, Vec<String>)> {
+    let edge_count = poly.edge_count();
+    let plural = |count: usize| if count == 1 { "" } else { "s" };
+
+    match mode {
+        WfColorMode::Single => None,
+
+        WfColorMode::Orbit => {
+            let Some((_, vertex_map)) = symmetry.get(poly) else {
+                job.cache_symmetry(poly, symmetry);
+                return None;
+            };
+            let classes = poly.element_orbit_indices(&vertex_map, 2);
+            let mut counts = Vec::new();
+            for &class in &classes {
+                if class == counts.len() {
+                    counts.push(0);
+                }
+                counts[class] += 1;
+            }
+
+            let labels = counts
+                .into_iter()
+                .enumerate()
+                .map(|(i, count)| format!("Orbit {} ({} edge{})", i + 1, count, plural(count)))
+                .collect();
+            Some((classes, labels))
+        }
+
+        WfColorMode::Length => {
+            let lengths: Vec<f64> = (0..edge_count).map(|idx| poly.edge_len(idx).unwrap_or_default()).collect();
+            let mut distinct = lengths.clone();
+            distinct.sort_unstable_by(f64::total_cmp);
+            distinct.dedup_by(|a, b| (*a - *b).abs() < EPS);
+
+            let classes: Vec<usize> = lengths
+                .iter()
+                .map(|len| distinct.partition_point(|other| other + EPS <= *len))
+                .collect();
+
+            let labels = distinct
+                .iter()
+                .enumerate()
+                .map(|(i, len)| {
+                    let count = classes.iter().filter(|&&class| class == i).count();
+                    format!("Length {:.10} ({} edge{})", len, count, plural(count))
+                })
+                .collect();
+            Some((classes, labels))
+        }
+    }
+}
+
+/// Builds the wireframe of a polytope according to the coloring mode, and
+/// updates the legend. Face intersections are optionally added.
+fn wireframe_mesh(
+    poly: &Concrete,
+    projection: ProjectionType,
+    mode: WfColorMode,
+    intersections: bool,
+    legend: &mut WfLegend,
+    symmetry: &SymmetryCache,
+    job: &mut BackgroundJob,
+) -> Mesh {
+    let mut mesh = match edge_classes(poly, mode, symmetry, job) {
+        Some((classes, labels)) => {
+            let colors: Vec<Color> = classes.iter().map(|&class| class_color(class)).collect();
+            legend.0 = labels.into_iter().enumerate().map(|(i, label)| (class_color(i), label)).collect();
+            poly.colored_wireframe(projection, &colors)
+        }
+        None => {
+            legend.0.clear();
+            poly.wireframe(projection)
+        }
+    };
+
+    if intersections {
+        if let Some(segments) = poly.face_intersections() {
+            add_segments(&mut mesh, vertex_coords(poly, segments.iter().flatten(), projection));
+        }
+    }
+
+    mesh
+}
+
 pub fn update_visible(
     keyboard: Res<'_, ButtonInput<KeyCode>>,
     mut polies_vis: Query<'_, '_, &mut Visibility, With<Concrete>>,
-    mut wfs_vis: Query<'_, '_, &mut Visibility, Without<Concrete>>,
+    mut wfs_vis: Query<'_, '_, &mut Visibility, (Without<Concrete>, Without<PreviewMesh>, Without<CompareMesh>)>,
 ) {
     if keyboard.get_pressed().count() == 1 {
         if keyboard.just_pressed(KeyCode::KeyV) {
@@ -80,8 +197,19 @@ pub fn update_changed_polytopes(
     mut section_state: ResMut<'_, SectionState>,
     mut element_types: ResMut<'_, ElementTypesRes>,
     name: Res<'_, PolyName>,
+    (wf_color_mode, mut legend, offset_coplanar, show_intersections, render_settings, symmetry, mut job): (
+        Res<'_, WfColorMode>,
+        ResMut<'_, WfLegend>,
+        Res<'_, OffsetCoplanarFaces>,
+        Res<'_, ShowIntersections>,
+        Res<'_, RenderSettings>,
+        Res<'_, SymmetryCache>,
+        ResMut<'_, BackgroundJob>,
+    ),
 
     orthogonal: Res<'_, ProjectionType>,
+    mut perf: ResMut<'_, PerfStats>,
+    selection: Res<'_, FaceSelection>,
 ) -> Result {
     for (poly, mesh_handle, children) in polies.iter() {
         if cfg!(debug_assertions) {
@@ -94,13 +222,26 @@ pub fn update_changed_polytopes(
             element_types.main_updating = false;
         }
 
-        *meshes.get_mut(&mesh_handle.0).unwrap() = poly.mesh(*orthogonal);
+        let start = Instant::now();
+        *meshes.get_mut(&mesh_handle.0).unwrap() =
+            poly.styled_mesh(*orthogonal, offset_coplanar.0, render_settings.normals, |face| {
+                selection.style(face)
+            });
 
         // Updates all wireframes.
         for child in children.iter() {
             let wf_handle = &wfs.get(child)?.0;
-            *meshes.get_mut(wf_handle).unwrap() = poly.wireframe(*orthogonal);
+            *meshes.get_mut(wf_handle).unwrap() = wireframe_mesh(
+                poly,
+                *orthogonal,
+                *wf_color_mode,
+                show_intersections.0,
+                &mut legend,
+                &symmetry,
+                &mut job,
+            );
         }
+        perf.mesh_build = Some(start.elapsed());
 
         // We reset the cross-section view if we didn't use it to change the polytope.
         if !section_state.is_changed() {
@@ -115,24 +256,66 @@ pub fn update_changed_polytopes(
     Ok(())
 }
 
+/// Rebuilds the wireframes when the way they're colored changes.
+pub fn update_changed_wf_color_mode(
+    mut meshes: ResMut<'_, Assets<Mesh>>,
+    polies: Query<'_, '_, (&Concrete, &Children)>,
+    wfs: Query<'_, '_, &Mesh3d, Without<Concrete>>,
+    wf_color_mode: Res<'_, WfColorMode>,
+    show_intersections: Res<'_, ShowIntersections>,
+    mut legend: ResMut<'_, WfLegend>,
+    symmetry: Res<'_, SymmetryCache>,
+    mut job: ResMut<'_, BackgroundJob>,
+    orthogonal: Res<'_, ProjectionType>,
+) -> Result {
+    if !wf_color_mode.is_changed() || wf_color_mode.is_added() {
+        return Ok(());
+    }
+
+    for (poly, children) in polies.iter() {
+        for child in children.iter() {
+            let wf_handle = &wfs.get(child)?.0;
+            *meshes.get_mut(wf_handle).unwrap() = wireframe_mesh(
+                poly,
+                *orthogonal,
+                *wf_color_mode,
+                show_intersections.0,
+                &mut legend,
+                &symmetry,
+                &mut job,
+            );
+        }
+    }
+    Ok(())
+}
+
 pub fn update_changed_color(
     mut materials: ResMut<'_, Assets<StandardMaterial>>,
     mut polies: Query<'_, '_, &MeshMaterial3d<StandardMaterial>, With<Concrete>>,
-    mut wfs: Query<'_, '_, &MeshMaterial3d<StandardMaterial>, Without<Concrete>>,
+    mut wfs: Query<'_, '_, &MeshMaterial3d<StandardMaterial>, (Without<Concrete>, Without<PreviewMesh>, Without<CompareMesh>)>,
     mesh_color: Res<'_, MeshColor>,
     wf_color: Res<'_, WfColor>,
+    wf_color_mode: Res<'_, WfColorMode>,
+    render_settings: Res<'_, RenderSettings>,
 ) {
     if let Some(material_handle) = polies.iter_mut().next() {
         *materials.get_mut(&material_handle.0).unwrap() = StandardMaterial {
             base_color: Color::from(LinearRgba::from(mesh_color.0)),
-            double_sided: true,
-            cull_mode: None,
+            double_sided: render_settings.two_sided_lighting,
+            cull_mode: render_settings.backface_culling.then_some(Face::Back),
             ..Default::default()
         };
     }
     if let Some(wf_handle) = wfs.iter_mut().next() {
+        // Colored wireframes take their colors from their vertices.
+        let base_color = if *wf_color_mode == WfColorMode::Single {
+            wf_color.0
+        } else {
+            Color::WHITE
+        };
+
         *materials.get_mut(&wf_handle.0).unwrap() = StandardMaterial {
-            base_color: Color::from(LinearRgba::from(wf_color.0)),
+            base_color: Color::from(LinearRgba::from(base_color)),
             double_sided: true,
             cull_mode: None,
             ..Default::default()
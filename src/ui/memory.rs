@@ -1,6 +1,6 @@
 //! Manages the memory tab.
 
-use std::cmp::*;
+use std::{cmp::*, collections::BTreeSet};
 
 use bevy::prelude::{Query, ResMut, Resource, Result};
 use bevy_egui::{egui};
@@ -9,15 +9,57 @@ use crate::{
     ui::config::SlotsPerPage,
     Concrete
 };
+use miratope_core::{abs::Ranked, conc::{compare::VertexSetKey, ConcretePolytope}, Polytope};
 
-use super::main_window::PolyName;
+use super::{compare::CompareView, main_window::PolyName, preview::Preview, top_panel::FileDialogState};
 
 /// Represents the memory slots to store polytopes.
 #[derive(Default, Resource)]
 pub struct Memory {
     pub slots: Vec<Option<(Concrete, Option<String>)>>,
     pub start_page: usize,
-    pub end_page: usize
+    pub end_page: usize,
+
+    /// The slot the current polytope was last compared with, and the report
+    /// of what changed from it.
+    pub comparison: Option<(usize, String)>,
+
+    /// The indices of the slots selected for batch operations.
+    pub selected: BTreeSet<usize>,
+
+    /// The groups of slots last found to share a vertex set, up to isometry.
+    pub shared_vertex_sets: Vec<Vec<usize>>,
+}
+
+/// An operation on every selected memory slot.
+#[derive(Clone, Copy)]
+enum BatchAction {
+    /// Stores the compound of the selected polytopes in a new slot.
+    Compound,
+
+    /// Writes every selected polytope into a folder.
+    Export,
+
+    /// Removes the selected slots.
+    Delete,
+
+    /// Reports the pairs of selected polytopes with no differences.
+    Duplicates,
+}
+
+/// An operation on the groups of slots sharing a vertex set.
+enum SharedAction {
+    /// Hides every group.
+    Dismiss,
+
+    /// Selects the slots in a group.
+    Select(Vec<usize>),
+
+    /// Stores the compound of the polytopes in a group in a new slot.
+    Compound(Vec<usize>),
+
+    /// Reports what changes between the polytopes in a group.
+    Diff(Vec<usize>),
 }
 
 impl std::ops::Index<usize> for Memory {
@@ -49,11 +91,208 @@ impl Memory {
         self.slots.push(Some(a));
     }
 
+    /// Returns the selected polytopes, along with their slot indices and names.
+    fn selection(&self) -> Vec<(usize, &Concrete, String)> {
+        self.selected
+            .iter()
+            .filter_map(|&idx| match self.slots.get(idx)? {
+                Some((poly, label)) => {
+                    Some((idx, poly, label.clone().unwrap_or_else(|| slot_label(idx))))
+                }
+                None => None,
+            })
+            .collect()
+    }
+
+    /// Groups the slots whose polytopes have the same vertex set up to
+    /// isometry, keeping the groups with more than one slot.
+    fn find_shared_vertex_sets(&mut self) {
+        let mut groups: Vec<(VertexSetKey, Vec<usize>)> = Vec::new();
+
+        for (idx, slot) in self.slots.iter().enumerate() {
+            if let Some((poly, _)) = slot {
+                let key = poly.vertex_set_key();
+                match groups.iter_mut().find(|(other, _)| other.matches(&key)) {
+                    Some((_, group)) => group.push(idx),
+                    None => groups.push((key, vec![idx])),
+                }
+            }
+        }
+
+        self.shared_vertex_sets = groups
+            .into_iter()
+            .filter_map(|(_, group)| (group.len() > 1).then_some(group))
+            .collect();
+
+        if self.shared_vertex_sets.is_empty() {
+            println!("No slots share a vertex set.");
+        }
+    }
+
+    /// Reports what changes from the first slot in a group to every other.
+    fn diff_group(&self, group: &[usize]) {
+        let polys: Vec<_> = group
+            .iter()
+            .filter_map(|&idx| Some((idx, &self.slots.get(idx)?.as_ref()?.0)))
+            .collect();
+
+        if let Some(((a, p), rest)) = polys.split_first() {
+            for (b, q) in rest {
+                println!("Changes from slot {} to slot {}:\n{}", a, b, p.diff(q));
+            }
+        }
+    }
+
+    /// Applies an operation to every selected slot.
+    fn apply_batch(&mut self, action: BatchAction, file_dialog_state: &mut ResMut<'_, FileDialogState>) {
+        let selection = self.selection();
+
+        match action {
+            BatchAction::Compound => {
+                let Some((_, first, _)) = selection.first() else {
+                    return;
+                };
+                if selection
+                    .iter()
+                    .any(|(_, p, _)| p.rank() != first.rank() || p.dim() != first.dim())
+                {
+                    println!("The selected polytopes have different ranks or dimensions.");
+                    return;
+                }
+
+                let names: Vec<_> = selection.iter().map(|(_, _, name)| name.as_str()).collect();
+                let label = format!("Compound of {}", names.join(", "));
+                let compound = Concrete::compound(selection.iter().map(|(_, p, _)| (*p).clone()));
+                self.push((compound, Some(label)));
+            }
+
+            BatchAction::Export => {
+                let slots = selection
+                    .into_iter()
+                    .map(|(idx, p, name)| (p.clone(), format!("{} - {}", idx, name)))
+                    .collect();
+                file_dialog_state.export_slots(slots);
+            }
+
+            BatchAction::Delete => {
+                for &idx in self.selected.iter().rev() {
+                    if idx < self.len() {
+                        self.slots.remove(idx);
+                    }
+                }
+
+                // The compared slot moves back by the number of slots removed
+                // before it.
+                self.comparison = self.comparison.take().and_then(|(idx, report)| {
+                    (!self.selected.contains(&idx))
+                        .then(|| (idx - self.selected.range(..idx).count(), report))
+                });
+                self.selected.clear();
+                self.shared_vertex_sets.clear();
+            }
+
+            BatchAction::Duplicates => {
+                let mut found = false;
+                for (i, (a, p, _)) in selection.iter().enumerate() {
+                    for (b, q, _) in &selection[i + 1..] {
+                        if p.diff(q).is_empty() {
+                            println!("Slots {} and {} are duplicates.", a, b);
+                            found = true;
+                        }
+                    }
+                }
+
+                if !found {
+                    println!("No duplicates found among the selected slots.");
+                }
+            }
+        }
+    }
+
+    /// Shows the operations that can be applied to a polytope in memory
+    /// without loading it. Components that get split off are added to
+    /// `new_slots`.
+    fn slot_menu(
+        ui: &mut egui::Ui,
+        poly: &mut Concrete,
+        label: &mut Option<String>,
+        name: &str,
+        file_dialog_state: &mut ResMut<'_, FileDialogState>,
+        new_slots: &mut Vec<(Concrete, Option<String>)>,
+    ) {
+        if ui.button("Dual").clicked() {
+            match poly.try_dual_mut() {
+                Ok(_) => *label = Some(format!("Dual of {}", name)),
+                Err(err) => eprintln!("Dual failed: {}", err),
+            }
+        }
+
+        if ui.button("Petrial").clicked() {
+            if poly.petrial_mut() {
+                *label = Some(format!("Petrial of {}", name));
+            } else {
+                eprintln!("Petrial failed.");
+            }
+        }
+
+        // The Petrie polygon goes in a new slot, since it's usually a skew
+        // polygon that looks nothing like the original.
+        if ui.button("Petrie polygon").clicked() {
+            match poly.petrie_polygon() {
+                Some(q) => new_slots.push((q, Some(format!("Petrie polygon of {}", name)))),
+                None => eprintln!("Petrie polygon failed."),
+            }
+        }
+
+        ui.separator();
+
+        if ui.button("Scale to unit edge length").clicked() {
+            match poly.edge_len(0) {
+                Some(len) => poly.scale(1.0 / len),
+                None => println!("The polytope has no edges."),
+            }
+        }
+
+        // Moves the polytope so that the circumcenter is at the origin.
+        if ui.button("Recenter by circumcenter").clicked() {
+            match poly.circumsphere() {
+                Some(sphere) => poly.recenter_with(&sphere.center),
+                None => println!("The polytope has no circumsphere."),
+            }
+        }
+
+        // Moves the polytope so that the gravicenter is at the origin.
+        if ui.button("Recenter by gravicenter").clicked() {
+            poly.recenter();
+        }
+
+        ui.separator();
+
+        // Stores every component of a compound in its own slot.
+        if ui.button("Split components").clicked() {
+            let components = poly.defiss();
+            if components.len() < 2 {
+                println!("The polytope has a single component.");
+            } else {
+                for (n, component) in components.into_iter().enumerate() {
+                    new_slots.push((component, Some(format!("{} component {}", name, n))));
+                }
+            }
+        }
+
+        if ui.button("Export...").clicked() {
+            file_dialog_state.save_slot(poly.clone(), name.to_string());
+        }
+    }
+
     /// Shows the memory menu in a specified Ui.
     pub fn show(
         &mut self,
         query: &mut Query<'_, '_, &mut Concrete>,
         poly_name: &mut ResMut<'_, PolyName>,
+        preview: &mut ResMut<'_, Preview>,
+        compare_view: &mut ResMut<'_, CompareView>,
+        file_dialog_state: &mut ResMut<'_, FileDialogState>,
         slots_per_page: &mut ResMut<'_, SlotsPerPage>,
         context: &mut Context,
         open: &mut bool
@@ -61,6 +300,10 @@ impl Memory {
         let spp = slots_per_page.0;
         self.start_page = if self.len() < spp {0} else {min(self.start_page, self.len()-spp)};
         self.end_page = min(self.start_page + spp, self.len());
+        let mut dismiss = false;
+        let mut batch = None;
+        let mut find_shared = false;
+        let mut shared_action = None;
         egui::Window::new("Memory")
             .open(open)
             .scroll(true)
@@ -71,11 +314,17 @@ impl Memory {
                 ui.horizontal(|ui| {
                     if ui.button("Clear memory").clicked() {
                         self.slots.clear();
+                        self.selected.clear();
+                        self.shared_vertex_sets.clear();
                     }
         
                     if ui.button("Add slot").clicked() {
                         self.slots.push(None);
                     }
+
+                    if ui.button("Find shared vertex sets").clicked() {
+                        find_shared = true;
+                    }
                     
                     ui.add_space(20.);
                     ui.label("Slots per page:");
@@ -86,7 +335,37 @@ impl Memory {
                     );
                 });
     
+                // Shows the operations on the selected slots.
+                if !self.selected.is_empty() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} selected:", self.selected.len()));
+
+                        if ui.button("Compound").clicked() {
+                            batch = Some(BatchAction::Compound);
+                        }
+
+                        if ui.button("Export...").clicked() {
+                            batch = Some(BatchAction::Export);
+                        }
+
+                        if ui.button("Delete").clicked() {
+                            batch = Some(BatchAction::Delete);
+                        }
+
+                        if ui.button("Find duplicates").clicked() {
+                            batch = Some(BatchAction::Duplicates);
+                        }
+
+                        if ui.button("Deselect").clicked() {
+                            self.selected.clear();
+                        }
+                    });
+                }
+
                 ui.separator();
+
+                let mut comparison = None;
+                let mut new_slots = Vec::new();
     
                 for idx in self.start_page..self.end_page {
                     if idx >= self.len() {continue}
@@ -111,6 +390,15 @@ impl Memory {
                             let mut clear = false;
 
                             ui.horizontal(|ui| {
+                                let mut checked = self.selected.contains(&idx);
+                                if ui.checkbox(&mut checked, "").changed() {
+                                    if checked {
+                                        self.selected.insert(idx);
+                                    } else {
+                                        self.selected.remove(&idx);
+                                    }
+                                }
+
                                 ui.label(format!("{}:", idx));
                                 let name = match label {
                                     None => {
@@ -122,7 +410,12 @@ impl Memory {
                                     }
                                 };
 
-                                ui.label(&name);
+                                // Right-clicking the name shows the operations on the slot.
+                                ui.add(egui::Label::new(&name).sense(egui::Sense::click()))
+                                    .on_hover_text("Right-click for more operations")
+                                    .context_menu(|ui| {
+                                        Self::slot_menu(ui, poly, label, &name, file_dialog_state, &mut new_slots);
+                                    });
 
                                 // Clones a polytope from memory.
                                 if ui.button("Load").clicked() {
@@ -130,6 +423,16 @@ impl Memory {
                                     poly_name.0 = name.clone();
                                 }
 
+                                // Shows the polytope as a ghost over the current one.
+                                if ui.button("Preview").clicked() {
+                                    preview.set(poly.clone(), name.clone());
+                                }
+
+                                // Shows the polytope next to the current one.
+                                if ui.button("Side by side").clicked() {
+                                    compare_view.set(poly.clone(), name.clone());
+                                }
+
                                 // Swaps the current polytope with the one on memory.
                                 if ui.button("Swap").clicked() {
                                     std::mem::swap(query.iter_mut().next().unwrap().as_mut(), poly);
@@ -143,6 +446,13 @@ impl Memory {
                                     *label = Some(poly_name.0.clone());
                                 }
 
+                                // Compares the polytope on memory with the current one.
+                                if ui.button("Compare").clicked() {
+                                    if let Some(p) = query.iter_mut().next() {
+                                        comparison = Some((idx, poly.diff(&p).to_string()));
+                                    }
+                                }
+
                                 // Clears a polytope from memory.
                                 if ui.button("Clear").clicked() {
                                     clear = true;
@@ -151,11 +461,61 @@ impl Memory {
 
                             if clear {
                                 *slot = None;
+                                self.selected.remove(&idx);
                             }
                         }
                     }
                 }
 
+                if comparison.is_some() {
+                    self.comparison = comparison;
+                }
+
+                for slot in new_slots {
+                    self.push(slot);
+                }
+
+                // Shows what changed from the compared slot.
+                if let Some((idx, report)) = &self.comparison {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Changes since slot {}:", idx));
+                        if ui.button("Dismiss").clicked() {
+                            dismiss = true;
+                        }
+                    });
+                    ui.label(report);
+                }
+
+                // Shows the groups of slots on the same vertex set.
+                if !self.shared_vertex_sets.is_empty() {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("Slots sharing a vertex set:");
+                        if ui.button("Dismiss").clicked() {
+                            shared_action = Some(SharedAction::Dismiss);
+                        }
+                    });
+
+                    for group in &self.shared_vertex_sets {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{:?}", group));
+
+                            if ui.button("Select").clicked() {
+                                shared_action = Some(SharedAction::Select(group.clone()));
+                            }
+
+                            if ui.button("Compound").clicked() {
+                                shared_action = Some(SharedAction::Compound(group.clone()));
+                            }
+
+                            if ui.button("Diff").clicked() {
+                                shared_action = Some(SharedAction::Diff(group.clone()));
+                            }
+                        });
+                    }
+                }
+
                 ui.separator();
 
                 ui.horizontal(|ui| {
@@ -173,6 +533,26 @@ impl Memory {
                 });
             });
         });
+
+        if dismiss {
+            self.comparison = None;
+        }
+        if let Some(action) = batch {
+            self.apply_batch(action, file_dialog_state);
+        }
+        if find_shared {
+            self.find_shared_vertex_sets();
+        }
+        match shared_action {
+            Some(SharedAction::Dismiss) => self.shared_vertex_sets.clear(),
+            Some(SharedAction::Select(group)) => self.selected = group.into_iter().collect(),
+            Some(SharedAction::Compound(group)) => {
+                self.selected = group.into_iter().collect();
+                self.apply_batch(BatchAction::Compound, file_dialog_state);
+            }
+            Some(SharedAction::Diff(group)) => self.diff_group(&group),
+            None => {}
+        }
         Ok(())
     }
 }
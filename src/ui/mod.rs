@@ -6,11 +6,26 @@ use bevy::app::PluginGroupBuilder;
 use bevy::prelude::Resource;
 use bevy_egui::egui::{self, Ui, Widget, Visuals};
 
+pub mod annotation;
 pub mod camera;
+pub mod cd_editor;
+pub mod compare;
 pub mod config;
+pub mod explorer;
+pub mod faceting;
+pub mod frame;
+pub mod jobs;
 pub mod library;
 pub mod main_window;
 pub mod memory;
+pub mod perf;
+pub mod preview;
+pub mod selection;
+pub mod stats;
+pub mod stellation;
+pub mod subgroups;
+pub mod tiling;
+pub mod watcher;
 pub mod window;
 pub mod top_panel;
 pub mod right_panel;
@@ -21,13 +36,27 @@ pub struct MiratopePlugins;
 impl bevy::prelude::PluginGroup for MiratopePlugins {
     fn build(self) -> PluginGroupBuilder {
         PluginGroupBuilder::start::<Self>()
+            .add(annotation::AnnotationPlugin)
             .add(camera::InputPlugin)
+            .add(compare::ComparePlugin)
             .add(config::ConfigPlugin)
             .add(window::WindowPlugin)
+            .add(explorer::ExplorerPlugin)
+            .add(faceting::FacetingPlugin)
+            .add(frame::FramePlugin)
+            .add(jobs::JobPlugin)
             .add(library::LibraryPlugin)
             .add(main_window::MainWindowPlugin)
+            .add(preview::PreviewPlugin)
+            .add(selection::SelectionPlugin)
             .add(top_panel::TopPanelPlugin)
             .add(right_panel::RightPanelPlugin)
+            .add(stats::StatsPlugin)
+            .add(stellation::StellationPlugin)
+            .add(subgroups::SubgroupPlugin)
+            .add(tiling::TilingPlugin)
+            .add(watcher::WatcherPlugin)
+            .add(perf::PerfPlugin)
     }
 }
 
@@ -0,0 +1,111 @@
+//! Measures how long rendering and operations take, and shows it on a HUD so
+//! that performance issues can be reported with actual numbers.
+
+use std::time::{Duration, Instant};
+
+use super::{config::ShowPerformance, window::ShowWindows};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+/// The plugin in charge of the performance HUD.
+pub struct PerfPlugin;
+
+impl Plugin for PerfPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PerfStats>()
+            .add_systems(Update, update_frame_time)
+            .add_systems(EguiPrimaryContextPass, show_perf_hud.in_set(ShowWindows));
+    }
+}
+
+/// Runs a function, returning its result together with how long it took.
+/// Background tasks call this from within their thread, and hand the duration
+/// back with their result.
+pub fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let res = f();
+    (res, start.elapsed())
+}
+
+/// How long the last few things took.
+#[derive(Default, Resource)]
+pub struct PerfStats {
+    /// A running average of the frame time, in seconds.
+    frame_time: f32,
+
+    /// How long it took to build the last polytope mesh and wireframe.
+    pub mesh_build: Option<Duration>,
+
+    /// The name and wall-clock duration of the last core operation.
+    pub last_operation: Option<(String, Duration)>,
+}
+
+impl PerfStats {
+    /// Records the duration of an operation.
+    pub fn record(&mut self, name: impl Into<String>, duration: Duration) {
+        self.last_operation = Some((name.into(), duration));
+    }
+
+    /// Runs an operation and records how long it took.
+    pub fn time<T>(&mut self, name: impl Into<String>, f: impl FnOnce() -> T) -> T {
+        let (res, duration) = timed(f);
+        self.record(name, duration);
+        res
+    }
+}
+
+/// Updates the running average of the frame time.
+fn update_frame_time(time: Res<'_, Time>, mut stats: ResMut<'_, PerfStats>) {
+    /// How much each new frame contributes to the average.
+    const SMOOTHING: f32 = 0.05;
+
+    let delta = time.delta_secs();
+    stats.frame_time = if stats.frame_time == 0.0 {
+        delta
+    } else {
+        stats.frame_time + SMOOTHING * (delta - stats.frame_time)
+    };
+}
+
+/// Formats a duration in milliseconds.
+fn millis(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Shows the performance HUD on the lower right corner, if enabled.
+fn show_perf_hud(
+    mut egui_ctx: EguiContexts<'_, '_>,
+    stats: Res<'_, PerfStats>,
+    show: Res<'_, ShowPerformance>,
+) -> Result {
+    if !show.0 {
+        return Ok(());
+    }
+
+    egui::Area::new(egui::Id::new("perf_hud"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -10.0))
+        .interactable(false)
+        .show(egui_ctx.ctx_mut()?, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                let fps = if stats.frame_time > 0.0 { 1.0 / stats.frame_time } else { 0.0 };
+                ui.monospace(format!(
+                    "Frame: {:.1} ms ({:.0} fps)",
+                    stats.frame_time * 1000.0,
+                    fps
+                ));
+
+                ui.monospace(match stats.mesh_build {
+                    Some(duration) => format!("Mesh build: {}", millis(duration)),
+                    None => "Mesh build: -".to_string(),
+                });
+
+                ui.monospace(match &stats.last_operation {
+                    Some((name, duration)) => format!("{}: {}", name, millis(*duration)),
+                    None => "No operations yet".to_string(),
+                });
+            });
+        });
+
+    Ok(())
+}
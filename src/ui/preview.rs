@@ -0,0 +1,130 @@
+//! Previews the result of an operation as a translucent ghost over the current
+//! polytope, so that it can be inspected before being applied.
+
+use super::{camera::ProjectionType, main_window::PolyName, window::ShowWindows};
+use crate::{mesh::Renderable, Concrete};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+/// The plugin in charge of previewing operations.
+pub struct PreviewPlugin;
+
+impl Plugin for PreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Preview>()
+            .add_systems(PostUpdate, update_preview)
+            .add_systems(EguiPrimaryContextPass, show_preview.in_set(ShowWindows));
+    }
+}
+
+/// The candidate result of an operation, together with the name the polytope
+/// will take if it's applied. This is stored away from the main [`Concrete`]
+/// entity, so that it's never mistaken for the loaded polytope.
+#[derive(Default, Resource)]
+pub struct Preview(pub Option<(Concrete, String)>);
+
+impl Preview {
+    /// Sets the polytope to preview, replacing any previous one.
+    pub fn set(&mut self, poly: Concrete, name: String) {
+        self.0 = Some((poly, name));
+    }
+
+    /// Discards the previewed polytope.
+    pub fn clear(&mut self) {
+        self.0 = None;
+    }
+}
+
+/// Marks the entities drawing the previewed polytope.
+#[derive(Component)]
+pub struct PreviewMesh;
+
+/// Returns the translucent material used for previews.
+fn ghost_material(color: Color) -> StandardMaterial {
+    StandardMaterial {
+        base_color: color,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        ..Default::default()
+    }
+}
+
+/// Rebuilds the ghost entities whenever the previewed polytope changes.
+pub fn update_preview(
+    mut commands: Commands<'_, '_>,
+    mut meshes: ResMut<'_, Assets<Mesh>>,
+    mut materials: ResMut<'_, Assets<StandardMaterial>>,
+    ghosts: Query<'_, '_, Entity, (With<PreviewMesh>, Without<ChildOf>)>,
+    preview: Res<'_, Preview>,
+    projection: Res<'_, ProjectionType>,
+) {
+    if !preview.is_changed() && !projection.is_changed() {
+        return;
+    }
+
+    // Despawning the parent also despawns its wireframe.
+    for entity in ghosts.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if let Some((poly, _)) = &preview.0 {
+        commands
+            .spawn((
+                Mesh3d(meshes.add(poly.mesh(*projection, false))),
+                MeshMaterial3d(materials.add(ghost_material(Color::srgba(0.3, 0.9, 0.5, 0.25)))),
+                Transform::default(),
+                Visibility::Visible,
+                PreviewMesh,
+            ))
+            .with_children(|cb| {
+                cb.spawn((
+                    Mesh3d(meshes.add(poly.wireframe(*projection))),
+                    MeshMaterial3d(materials.add(ghost_material(Color::srgba(0.2, 0.7, 0.3, 0.8)))),
+                    Transform::default(),
+                    Visibility::Visible,
+                    PreviewMesh,
+                ));
+            });
+    }
+}
+
+/// Shows the window that applies or discards the previewed polytope.
+pub fn show_preview(
+    mut egui_ctx: EguiContexts<'_, '_>,
+    mut preview: ResMut<'_, Preview>,
+    mut query: Query<'_, '_, &mut Concrete>,
+    mut poly_name: ResMut<'_, PolyName>,
+) -> Result {
+    let Some((_, name)) = &preview.0 else {
+        return Ok(());
+    };
+
+    let mut apply = false;
+    let mut cancel = false;
+
+    egui::Window::new("Preview")
+        .resizable(false)
+        .show(egui_ctx.ctx_mut()?, |ui| {
+            ui.label(name);
+            ui.horizontal(|ui| {
+                apply = ui.button("Apply").clicked();
+                cancel = ui.button("Cancel").clicked();
+            });
+        });
+
+    if apply {
+        if let Some((poly, name)) = preview.0.take() {
+            if let Some(mut p) = query.iter_mut().next() {
+                *p = poly;
+                poly_name.0 = name;
+            }
+        }
+    } else if cancel {
+        preview.clear();
+    }
+
+    Ok(())
+}
@@ -1,31 +1,15 @@
 //! Contains all code related to the right side panel.
 
+use std::sync::Arc;
+
 use crate::Concrete;
 
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
-use miratope_core::{conc::{element_types::{EL_NAMES, EL_SUFFIXES}, ConcretePolytope}, Polytope, abs::Ranked, geometry::{Subspace, Point, Vector}};
+use miratope_core::{abs::topology::Topology, conc::{element_types::{EL_NAMES, EL_SUFFIXES}, symmetry::Transitivity, ConcretePolytope}, Polytope, abs::Ranked, geometry::{Subspace, Point, Vector}};
 use vec_like::VecLike;
 use crate::ui::top_panel::{show_top_panel, SectionDirectionVec};
-use super::{top_panel::{SectionDirection, SectionState}, main_window::PolyName};
-
-#[derive(Clone, Copy, Debug)]
-pub struct ElementTypeWithData {
-    /// The index of the representative for this element type.
-    example: usize,
-
-    /// The number of elements of this type.
-    pub count: usize,
-
-    /// The number of facets.
-    facets: usize,
-
-    /// The number of facets of the figure.
-    fig_facets: usize,
-
-    /// The circumradius of the element, or distance from the origin if it's a vertex.
-    radius: Option<f64>,
-}
+use super::{top_panel::{SectionDirection, SectionState}, main_window::PolyName, stats::{topology_report, ElementTypeWithData, PolyStats, StatsCache}};
 
 #[derive(Clone,Resource)]
 pub struct ElementTypesRes {
@@ -42,9 +26,39 @@ pub struct ElementTypesRes {
     /// The element types.
     pub types: Vec<Vec<ElementTypeWithData>>,
 
+    /// The distinct edge lengths.
+    edge_lengths: Vec<f64>,
+
+    /// The circumradius.
+    circumradius: Option<f64>,
+
+    /// The number of flags.
+    flag_count: usize,
+
+    /// The topological invariants.
+    topology: Option<Topology>,
+
+    /// Whether the polytope is the double cover of its central quotient.
+    double_cover: bool,
+
+    /// The number of facets through the gravicenter.
+    hemi_facet_count: usize,
+
+    /// The volume.
+    hypervolume: Option<f64>,
+
+    /// The total volume of the facets.
+    facet_content: Option<f64>,
+
+    /// The kinds of elements the symmetries act transitively on.
+    transitivity: Option<Transitivity>,
+
     /// The components.
     components: Option<Vec<Concrete>>,
 
+    /// The name of the polytope whose statistics we're waiting for, if any.
+    waiting: Option<String>,
+
     /// Whether the loaded polytope matches `poly` and the buttons should be greyed out.
     pub main: bool,
 
@@ -59,7 +73,17 @@ impl Default for ElementTypesRes {
             poly: Concrete::nullitope(),
             poly_name: "nullitope".to_string(),
             types: Vec::new(),
+            edge_lengths: Vec::new(),
+            circumradius: None,
+            flag_count: 0,
+            topology: None,
+            double_cover: false,
+            hemi_facet_count: 0,
+            hypervolume: None,
+            facet_content: None,
+            transitivity: None,
             components: None,
+            waiting: None,
             main: true,
             main_updating: false,
         }
@@ -67,57 +91,23 @@ impl Default for ElementTypesRes {
 }
 
 impl ElementTypesRes {
-    fn from_poly(&self, poly: Mut<'_, Concrete>, poly_name: String) -> ElementTypesRes {
-        let mut poly = poly.clone();
-        poly.element_sort();
-
-        let plain_types = poly.element_types();
-        let mut types_with_data = Vec::new();
-    
-        for (r, types) in plain_types.clone().into_iter().enumerate() {
-            let rank = poly.rank();
-            if r == rank {
-                break;
-            }
-    
-            let abs = &poly.abs;
-            let dual_abs = &abs.dual();
-            let mut types_with_data_this_rank = Vec::new();
-            
-            for t in types {
-                let idx = t.example;
-    
-                let facets = abs[(r, idx)].subs.len();
-                let fig_facets = dual_abs.element_vertices(rank-r, idx).unwrap().len();
-                let radius = 
-                    if r == 1 {
-                        Some(poly.vertices[idx].norm())
-                    }
-                    else {
-                        if let Some(sphere) = poly.element(r, idx).unwrap().circumsphere() {
-                        Some(sphere.radius())
-                        } else {
-                            None
-                        }
-                    };
-    
-                types_with_data_this_rank.push(ElementTypeWithData {
-                    example: idx,
-                    count: t.count,
-                    facets,
-                    fig_facets,
-                    radius,
-                });
-            }
-            types_with_data.push(types_with_data_this_rank);
-        }
-    
+    fn from_stats(stats: &PolyStats, poly_name: String) -> ElementTypesRes {
         ElementTypesRes {
             active: true,
-            poly: poly.clone(),
+            poly: stats.poly.clone(),
             poly_name,
-            types: types_with_data,
+            types: stats.types.clone(),
+            edge_lengths: stats.edge_lengths.clone(),
+            circumradius: stats.circumradius,
+            flag_count: stats.flag_count,
+            topology: Some(stats.topology.clone()),
+            double_cover: stats.double_cover,
+            hemi_facet_count: stats.hemi_facets.len(),
+            hypervolume: stats.hypervolume,
+            facet_content: stats.facet_content,
+            transitivity: stats.transitivity,
             components: None,
+            waiting: None,
             main: true,
             main_updating: false,
         }
@@ -149,6 +139,7 @@ pub fn show_right_panel(
     // Info about the application state.
     mut egui_ctx: EguiContexts<'_, '_>,
     mut query: Query<'_, '_, &mut Concrete>,
+    mut caches: Query<'_, '_, &mut StatsCache>,
     mut poly_name: ResMut<'_, PolyName>,
 
     // The Miratope resources controlled by the right panel.
@@ -165,10 +156,7 @@ pub fn show_right_panel(
             
             ui.horizontal(|ui| {
                 if ui.add(egui::Button::selectable(!element_types.main, "Generate")).clicked() {
-                    if let Some(p) = query.iter_mut().next() {
-                        element_types.main = true;
-                        *element_types = element_types.from_poly(p, poly_name.0.clone());
-                    }
+                    element_types.waiting = Some(poly_name.0.clone());
                 }
     
                 if ui.add(egui::Button::selectable(!element_types.main,"Load")).clicked() {
@@ -181,9 +169,94 @@ pub fn show_right_panel(
                 }
             });
 
+            // Waits for the statistics to be computed in the background. If
+            // the polytope changes in the meantime, they're requested again.
+            if let Some(name) = element_types.waiting.clone() {
+                if let (Some(p), Some(mut cache)) = (query.iter().next(), caches.iter_mut().next()) {
+                    cache.request(p);
+
+                    if let Some(stats) = cache.get() {
+                        *element_types = ElementTypesRes::from_stats(Arc::as_ref(&stats), name);
+                    } else {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Computing...");
+                        });
+                    }
+                } else {
+                    element_types.waiting = None;
+                }
+            }
+
             ui.separator();
 
             if element_types.active {
+                ui.label(format!("Flags: {}", element_types.flag_count));
+                if let Some(topology) = &element_types.topology {
+                    ui.label(format!(
+                        "Orientable: {}",
+                        if topology.orientable { "yes" } else { "no" }
+                    ));
+                    ui.label(format!("Euler characteristic: {}", topology.euler_characteristic));
+                    if let Some(genus) = topology.genus {
+                        ui.label(format!("Surface: {}", genus));
+                        if let Some(cover) = genus.double_cover() {
+                            ui.label(format!("Orientable double cover: {}", cover));
+                        }
+                    }
+                    if element_types.double_cover {
+                        ui.label("Double cover of its central quotient");
+                    }
+
+                    // Copies the topological invariants as text.
+                    if ui.button("Copy topology").clicked() {
+                        ui.ctx().copy_text(topology_report(topology, element_types.double_cover));
+                    }
+                }
+                if element_types.hemi_facet_count > 0 {
+                    ui.label(format!("Hemi facets: {}", element_types.hemi_facet_count));
+                }
+
+                if let Some(circumradius) = element_types.circumradius {
+                    ui.label(format!("Circumradius: {:.10}", circumradius));
+                }
+
+                if let Some(hypervolume) = element_types.hypervolume {
+                    ui.label(format!("Volume: {:.10}", hypervolume));
+                }
+
+                if let Some(facet_content) = element_types.facet_content {
+                    ui.label(format!("Facet content: {:.10}", facet_content));
+                }
+
+                // The verdicts on how symmetric the polytope is.
+                if let Some(t) = element_types.transitivity {
+                    for (label, verdict) in [
+                        ("Regular", t.regular),
+                        ("Uniform", t.uniform),
+                        ("Noble", t.noble()),
+                        ("Vertex-transitive (isogonal)", t.vertex),
+                        ("Edge-transitive (isotoxal)", t.isotoxal()),
+                        ("Facet-transitive (isotopic)", t.facet),
+                    ] {
+                        ui.label(format!("{}: {}", label, if verdict { "yes" } else { "no" }));
+                    }
+                }
+
+                if !element_types.edge_lengths.is_empty() {
+                    ui.label(format!(
+                        "Edge length{}: {}",
+                        if element_types.edge_lengths.len() == 1 {""} else {"s"},
+                        element_types.edge_lengths
+                            .iter()
+                            .map(|len| format!("{:.10}", len))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ));
+                }
+
+                ui.separator();
+
                 egui::containers::ScrollArea::vertical().show(ui, |ui| {
                     for (r, types) in element_types.types.clone().into_iter().enumerate().skip(1) {
                         let poly = &element_types.poly;
@@ -265,6 +338,11 @@ pub fn show_right_panel(
                                         else {format!("radius {:.10}", radius)}
                                     );
                                 }
+
+                                // Elements below polyhedra are always spheres.
+                                if r >= 4 {
+                                    ui.label(format!("χ = {}", t.euler_characteristic));
+                                }
                             });
                         }
 
@@ -0,0 +1,582 @@
+//! Lets the user pick faces in the viewport, either one by one or by dragging
+//! a box around them, and then extract, hide or color them as a group. Vertices
+//! can also be clicked to get their vertex figures.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{
+    camera::ProjectionType,
+    compare::CompareCamera,
+    config::{OffsetCoplanarFaces, RenderSettings},
+    faceting::{facet_under_stabilizer, FacetingJob},
+    library::show_library,
+    main_window::PolyName,
+    memory::Memory,
+    perf::PerfStats,
+    window::{FacetingSettings, ShowWindows},
+};
+use crate::{
+    mesh::{class_color, vertex_coords, FaceStyle, Renderable},
+    Concrete,
+};
+use miratope_core::{abs::Ranked, conc::{symmetry::SymmetryCache, ConcretePolytope}, Polytope};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+/// How far the mouse has to move, in pixels, for a click to become a drag.
+const DRAG_THRESHOLD: f32 = 4.0;
+
+/// How far a click can be from a vertex, in pixels, to pick it.
+const VERTEX_PICK_RADIUS: f32 = 8.0;
+
+/// The color in which the selected faces are drawn.
+const SELECTED_COLOR: Color = Color::srgb(1.0, 0.6, 0.1);
+
+/// The plugin in charge of selecting faces.
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectionMode>()
+            .init_resource::<FaceSelection>()
+            .init_resource::<VertexFigurePick>()
+            .add_systems(Update, (clear_selection, pick_faces.after(show_library)).chain())
+            .add_systems(PostUpdate, update_selection_mesh)
+            .add_systems(EguiPrimaryContextPass, show_selection.in_set(ShowWindows));
+    }
+}
+
+/// Whether clicking on the viewport selects faces. While it's on, the camera
+/// can only be rotated with the right mouse button.
+#[derive(Clone, Copy, Default, Resource)]
+pub struct SelectionMode(pub bool);
+
+/// Where the vertex figure of a clicked vertex goes.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum FigureTarget {
+    /// The vertex figure replaces the loaded polytope.
+    #[default]
+    Load,
+
+    /// The vertex figure is stored in a new memory slot.
+    Memory,
+}
+
+/// Whether clicking on a vertex gets its vertex figure, instead of selecting
+/// the face under it. Unlike the selection, this is kept when the polytope
+/// changes, so that the figures of many polytopes can be checked in a row.
+#[derive(Clone, Copy, Default, Resource)]
+pub struct VertexFigurePick {
+    /// Whether clicks pick vertices.
+    pub enabled: bool,
+
+    /// Where the vertex figure goes.
+    pub target: FigureTarget,
+}
+
+/// The faces of the loaded polytope that are selected, hidden, or assigned a
+/// color group. Everything is cleared whenever the polytope changes.
+#[derive(Default, Resource)]
+pub struct FaceSelection {
+    /// The selected faces.
+    pub selected: BTreeSet<usize>,
+
+    /// The faces that aren't drawn.
+    pub hidden: BTreeSet<usize>,
+
+    /// The color group of each colored face.
+    pub groups: BTreeMap<usize, usize>,
+
+    /// The number of color groups assigned so far.
+    group_count: usize,
+
+    /// The symmetry orbit of each face, once computed.
+    orbits: Option<Vec<usize>>,
+
+    /// Where the mouse was pressed, if it's being held down.
+    drag_start: Option<Vec2>,
+}
+
+impl FaceSelection {
+    /// Returns whether nothing is selected, hidden or colored.
+    pub fn is_empty(&self) -> bool {
+        self.selected.is_empty()
+            && self.hidden.is_empty()
+            && self.groups.is_empty()
+            && self.orbits.is_none()
+    }
+
+    /// Returns how a face should be drawn.
+    pub fn style(&self, face: usize) -> FaceStyle {
+        if self.hidden.contains(&face) {
+            FaceStyle::Hidden
+        } else if self.selected.contains(&face) {
+            FaceStyle::Colored(SELECTED_COLOR)
+        } else if let Some(&group) = self.groups.get(&face) {
+            FaceStyle::Colored(class_color(group))
+        } else {
+            FaceStyle::Default
+        }
+    }
+
+    /// Puts the selected faces into a new color group.
+    pub fn color_selected(&mut self) {
+        for &face in &self.selected {
+            self.groups.insert(face, self.group_count);
+        }
+        self.group_count += 1;
+        self.selected.clear();
+    }
+
+    /// Returns the symmetry orbit of each face of the polytope. The orbits
+    /// are only computed the first time, and cached until the polytope
+    /// changes. If they can't be computed, every face gets its own orbit.
+    fn orbits(&mut self, poly: &Concrete, symmetry: &mut SymmetryCache, perf: &mut PerfStats) -> &[usize] {
+        self.orbits.get_or_insert_with(|| {
+            perf.time("Face orbits", || match symmetry.symmetry_group(poly) {
+                Some((_, vertex_map)) => poly.element_orbit_indices(&vertex_map, 3),
+                None => {
+                    eprintln!("Symmetry group could not be computed.");
+                    (0..poly.el_count(3)).collect()
+                }
+            })
+        })
+    }
+
+    /// Extends the selection to the full symmetry orbits of the selected
+    /// faces. Hidden faces stay unselected.
+    pub fn select_orbits(&mut self, poly: &Concrete, symmetry: &mut SymmetryCache, perf: &mut PerfStats) {
+        let orbits = self.orbits(poly, symmetry, perf).to_vec();
+        let selected: BTreeSet<usize> = self.selected.iter().map(|&face| orbits[face]).collect();
+        let faces: Vec<usize> = (0..orbits.len())
+            .filter(|&face| selected.contains(&orbits[face]))
+            .collect();
+
+        self.select(faces, false);
+    }
+
+    /// Extends every color group to the full symmetry orbits of its faces.
+    /// When an orbit has faces of different groups, the faces without a color
+    /// take the group of the first colored one.
+    pub fn color_orbits(&mut self, poly: &Concrete, symmetry: &mut SymmetryCache, perf: &mut PerfStats) {
+        let orbits = self.orbits(poly, symmetry, perf).to_vec();
+        let mut orbit_groups = BTreeMap::new();
+        for (&face, &group) in &self.groups {
+            orbit_groups.entry(orbits[face]).or_insert(group);
+        }
+
+        for (face, orbit) in orbits.into_iter().enumerate() {
+            if let Some(&group) = orbit_groups.get(&orbit) {
+                self.groups.entry(face).or_insert(group);
+            }
+        }
+    }
+
+    /// Hides the selected faces.
+    pub fn hide_selected(&mut self) {
+        self.hidden.append(&mut self.selected);
+    }
+
+    /// Selects some faces. Unless adding to the selection, the previous one is
+    /// replaced.
+    fn select(&mut self, faces: impl IntoIterator<Item = usize>, add: bool) {
+        if !add {
+            self.selected.clear();
+        }
+
+        for face in faces {
+            if add && self.selected.contains(&face) {
+                self.selected.remove(&face);
+            } else if !self.hidden.contains(&face) {
+                self.selected.insert(face);
+            }
+        }
+    }
+}
+
+/// Returns the distance along a ray at which it hits a triangle, using the
+/// Möller–Trumbore algorithm.
+fn ray_triangle(ray: Ray3d, [p0, p1, p2]: [Vec3; 3]) -> Option<f32> {
+    let e1 = p1 - p0;
+    let e2 = p2 - p0;
+    let p = ray.direction.cross(e2);
+    let det = e1.dot(p);
+    if det.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let t = ray.origin - p0;
+    let u = t.dot(p) / det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = t.cross(e1);
+    let v = ray.direction.dot(q) / det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let dist = e2.dot(q) / det;
+    (dist > 0.0).then_some(dist)
+}
+
+/// Returns the closest visible face under a point in the viewport.
+fn face_under(
+    poly: &Concrete,
+    selection: &FaceSelection,
+    projection: ProjectionType,
+    camera: (&Camera, &GlobalTransform),
+    pos: Vec2,
+) -> Option<usize> {
+    let ray = camera.0.viewport_to_world(camera.1, pos).ok()?;
+    let triangulation = poly.triangulation();
+    let coords: Vec<Vec3> = vertex_coords(
+        poly,
+        poly.vertices.iter().chain(triangulation.extra_vertices.iter()),
+        projection,
+    )
+    .into_iter()
+    .map(Vec3::from_array)
+    .collect();
+
+    triangulation
+        .triangles
+        .chunks_exact(3)
+        .zip(&triangulation.faces)
+        .filter(|(_, face)| !selection.hidden.contains(face))
+        .filter_map(|(triangle, &face)| {
+            ray_triangle(ray, [0, 1, 2].map(|i| coords[triangle[i] as usize])).map(|dist| (dist, face))
+        })
+        .min_by(|(d0, _), (d1, _)| d0.total_cmp(d1))
+        .map(|(_, face)| face)
+}
+
+/// Returns the vertex closest to a point in the viewport, if it's close enough
+/// to be picked. Of the vertices at the same distance, the closest to the
+/// camera is picked.
+fn vertex_under(
+    poly: &Concrete,
+    projection: ProjectionType,
+    camera: (&Camera, &GlobalTransform),
+    pos: Vec2,
+) -> Option<usize> {
+    let eye = camera.1.translation();
+
+    vertex_coords(poly, poly.vertices.iter(), projection)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(v, coords)| {
+            let coords = Vec3::from_array(coords);
+            let dist = camera.0.world_to_viewport(camera.1, coords).ok()?.distance(pos);
+            (dist <= VERTEX_PICK_RADIUS).then(|| (dist, eye.distance(coords), v))
+        })
+        .min_by(|(d0, e0, _), (d1, e1, _)| d0.total_cmp(d1).then(e0.total_cmp(e1)))
+        .map(|(_, _, v)| v)
+}
+
+/// Returns the vertex figure of a vertex, flattened and centered at the
+/// origin, or `None` if it can't be computed.
+fn vertex_figure(poly: &Concrete, v: usize) -> Option<Concrete> {
+    match poly.element_fig(1, v) {
+        Ok(Some(mut figure)) => {
+            figure.flatten();
+            figure.recenter();
+            Some(figure)
+        }
+        Ok(None) => {
+            eprintln!("Vertex figure failed: no vertex with index {}", v);
+            None
+        }
+        Err(err) => {
+            eprintln!("Vertex figure failed: {}", err);
+            None
+        }
+    }
+}
+
+/// Returns the visible faces whose centers lie within a box in the viewport.
+fn faces_in_box(
+    poly: &Concrete,
+    selection: &FaceSelection,
+    projection: ProjectionType,
+    camera: (&Camera, &GlobalTransform),
+    rect: Rect,
+) -> Vec<usize> {
+    let coords = vertex_coords(poly, poly.vertices.iter(), projection);
+
+    (0..poly.el_count(3))
+        .filter(|face| !selection.hidden.contains(face))
+        .filter(|&face| {
+            let Some(vertices) = poly.abs.element_vertices(3, face) else {
+                return false;
+            };
+            let center = vertices.iter().map(|&v| Vec3::from_array(coords[v])).sum::<Vec3>()
+                / vertices.len() as f32;
+
+            camera
+                .0
+                .world_to_viewport(camera.1, center)
+                .is_ok_and(|pos| rect.contains(pos))
+        })
+        .collect()
+}
+
+/// Clears the selection whenever the polytope changes, since the face indices
+/// no longer refer to the same faces.
+fn clear_selection(polies: Query<'_, '_, (), Changed<Concrete>>, mut selection: ResMut<'_, FaceSelection>) {
+    if !polies.is_empty() && !selection.is_empty() {
+        *selection = FaceSelection::default();
+    }
+}
+
+/// Selects faces with the left mouse button. A click selects the face under the
+/// cursor, while a drag selects every face inside the box. Holding Ctrl or
+/// Shift adds to the selection instead of replacing it.
+///
+/// When vertices are being picked, a click instead gets the vertex figure of
+/// the vertex under the cursor.
+#[allow(clippy::too_many_arguments)]
+fn pick_faces(
+    mode: Res<'_, SelectionMode>,
+    mouse_button: Res<'_, ButtonInput<MouseButton>>,
+    keyboard: Res<'_, ButtonInput<KeyCode>>,
+    window_query: Query<'_, '_, &Window, With<PrimaryWindow>>,
+    cameras: Query<'_, '_, (&Camera, &GlobalTransform), Without<CompareCamera>>,
+    mut polies: Query<'_, '_, &mut Concrete>,
+    projection: Res<'_, ProjectionType>,
+    mut selection: ResMut<'_, FaceSelection>,
+    vertex_pick: Res<'_, VertexFigurePick>,
+    (mut memory, mut poly_name): (ResMut<'_, Memory>, ResMut<'_, PolyName>),
+    mut egui_ctx: EguiContexts<'_, '_>,
+) -> Result {
+    if !mode.0 {
+        return Ok(());
+    }
+
+    let Some(cursor) = window_query.single()?.cursor_position() else {
+        return Ok(());
+    };
+
+    if mouse_button.just_pressed(MouseButton::Left) && !egui_ctx.ctx_mut()?.wants_pointer_input() {
+        selection.drag_start = Some(cursor);
+    }
+
+    if !mouse_button.just_released(MouseButton::Left) {
+        return Ok(());
+    }
+
+    let (Some(start), Some(mut poly), Some(camera)) =
+        (selection.drag_start.take(), polies.iter_mut().next(), cameras.iter().next())
+    else {
+        return Ok(());
+    };
+
+    let add = keyboard.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::ShiftLeft,
+        KeyCode::ShiftRight,
+    ]);
+
+    if start.distance(cursor) < DRAG_THRESHOLD && vertex_pick.enabled {
+        let Some(figure) = vertex_under(&poly, *projection, camera, cursor).and_then(|v| vertex_figure(&poly, v))
+        else {
+            return Ok(());
+        };
+
+        let name = format!("Vertex figure of {}", poly_name.0);
+        match vertex_pick.target {
+            FigureTarget::Load => {
+                *poly = figure;
+                poly_name.0 = name;
+            }
+            FigureTarget::Memory => memory.push((figure, Some(name))),
+        }
+    } else if start.distance(cursor) < DRAG_THRESHOLD {
+        let face = face_under(&poly, &selection, *projection, camera, cursor);
+        selection.select(face, add);
+    } else {
+        let faces = faces_in_box(&poly, &selection, *projection, camera, Rect::from_corners(start, cursor));
+        selection.select(faces, add);
+    }
+
+    Ok(())
+}
+
+/// Rebuilds the mesh when the selection changes. Polytopes that just changed
+/// are skipped, since their mesh is being rebuilt anyways.
+fn update_selection_mesh(
+    mut meshes: ResMut<'_, Assets<Mesh>>,
+    polies: Query<'_, '_, (Ref<'_, Concrete>, &Mesh3d)>,
+    selection: Res<'_, FaceSelection>,
+    projection: Res<'_, ProjectionType>,
+    offset_coplanar: Res<'_, OffsetCoplanarFaces>,
+    render_settings: Res<'_, RenderSettings>,
+) {
+    if !selection.is_changed() || selection.is_added() {
+        return;
+    }
+
+    for (poly, mesh_handle) in polies.iter().filter(|(poly, _)| !poly.is_changed()) {
+        if let Some(mesh) = meshes.get_mut(&mesh_handle.0) {
+            *mesh = poly.styled_mesh(*projection, offset_coplanar.0, render_settings.normals, |face| {
+                selection.style(face)
+            });
+        }
+    }
+}
+
+/// Shows the window with the actions on the selected faces, and the box being
+/// dragged.
+#[allow(clippy::too_many_arguments)]
+fn show_selection(
+    mut egui_ctx: EguiContexts<'_, '_>,
+    mut mode: ResMut<'_, SelectionMode>,
+    mut selection: ResMut<'_, FaceSelection>,
+    query: Query<'_, '_, &Concrete>,
+    mut memory: ResMut<'_, Memory>,
+    poly_name: Res<'_, PolyName>,
+    window_query: Query<'_, '_, &Window, With<PrimaryWindow>>,
+    mut perf: ResMut<'_, PerfStats>,
+    mut symmetry: ResMut<'_, SymmetryCache>,
+    mut vertex_pick: ResMut<'_, VertexFigurePick>,
+    (mut faceting_job, faceting_settings): (ResMut<'_, FacetingJob>, Res<'_, FacetingSettings>),
+) -> Result {
+    if !mode.0 {
+        return Ok(());
+    }
+
+    let ctx = egui_ctx.ctx_mut()?;
+
+    // Draws the selection box.
+    if let (Some(start), Some(cursor)) = (
+        selection.drag_start,
+        window_query.single()?.cursor_position(),
+    ) {
+        if start.distance(cursor) >= DRAG_THRESHOLD {
+            let rect = egui::Rect::from_two_pos(egui::pos2(start.x, start.y), egui::pos2(cursor.x, cursor.y));
+            ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("selection_box")))
+                .rect_stroke(
+                    rect,
+                    0.0,
+                    egui::Stroke::new(1.0, egui::Color32::from_rgb(255, 153, 25)),
+                    egui::StrokeKind::Inside,
+                );
+        }
+    }
+
+    let mut open = true;
+    egui::Window::new("Face selection")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "{} selected, {} hidden",
+                selection.selected.len(),
+                selection.hidden.len()
+            ));
+            ui.label(if vertex_pick.enabled {
+                "Click a vertex for its figure, or drag to select."
+            } else {
+                "Click or drag to select, hold Ctrl or Shift to add."
+            });
+            ui.separator();
+
+            let any_selected = !selection.selected.is_empty();
+            ui.add_enabled_ui(any_selected, |ui| {
+                // Extracts the selected faces as a compound of polygons.
+                if ui.button("Extract to memory").clicked() {
+                    if let Some(poly) = query.iter().next() {
+                        let faces = selection.selected.iter().filter_map(|&face| poly.element(3, face));
+                        memory.push((
+                            Concrete::compound(faces),
+                            Some(format!("{} faces of {}", selection.selected.len(), poly_name.0)),
+                        ));
+                    }
+                }
+
+                if ui.button("Hide").clicked() {
+                    selection.hide_selected();
+                }
+
+                if ui.button("Assign color group").clicked() {
+                    selection.color_selected();
+                }
+
+                if ui.button("Deselect").clicked() {
+                    selection.selected.clear();
+                }
+            });
+
+            ui.separator();
+
+            if ui.add_enabled(!selection.hidden.is_empty(), egui::Button::new("Show hidden")).clicked() {
+                selection.hidden.clear();
+            }
+
+            if ui.add_enabled(!selection.groups.is_empty(), egui::Button::new("Clear colors")).clicked() {
+                selection.groups.clear();
+                selection.group_count = 0;
+            }
+
+            if ui.button("Select all").clicked() {
+                if let Some(poly) = query.iter().next() {
+                    let faces: Vec<usize> = (0..poly.el_count(3)).collect();
+                    selection.select(faces, false);
+                }
+            }
+
+            ui.separator();
+
+            // Paints symmetrically, using the symmetry group of the polytope.
+            ui.add_enabled_ui(any_selected, |ui| {
+                if ui.button("Select orbits").clicked() {
+                    if let Some(poly) = query.iter().next() {
+                        selection.select_orbits(poly, &mut symmetry, &mut perf);
+                    }
+                }
+            });
+
+            if ui
+                .add_enabled(!selection.groups.is_empty(), egui::Button::new("Color orbits"))
+                .clicked()
+            {
+                if let Some(poly) = query.iter().next() {
+                    selection.color_orbits(poly, &mut symmetry, &mut perf);
+                }
+            }
+
+            // Facets the polytope under the symmetries that fix a single face.
+            let single_face = (selection.selected.len() == 1).then(|| *selection.selected.first().unwrap());
+            if ui
+                .add_enabled(
+                    single_face.is_some() && !faceting_job.is_running(),
+                    egui::Button::new("Facet under stabilizer"),
+                )
+                .clicked()
+            {
+                if let (Some(poly), Some(face)) = (query.iter().next(), single_face) {
+                    facet_under_stabilizer(&mut faceting_job, poly, &mut symmetry, (3, face), &faceting_settings);
+                }
+            }
+
+            ui.separator();
+
+            // Gets vertex figures, to check the vertices of a polytope.
+            ui.checkbox(&mut vertex_pick.enabled, "Click vertices for their figures");
+            ui.add_enabled_ui(vertex_pick.enabled, |ui| {
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut vertex_pick.target, FigureTarget::Load, "Load");
+                    ui.radio_value(&mut vertex_pick.target, FigureTarget::Memory, "Store in memory");
+                });
+            });
+        });
+
+    if !open {
+        mode.0 = false;
+    }
+
+    Ok(())
+}
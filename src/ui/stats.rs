@@ -0,0 +1,243 @@
+//! Caches data derived from the loaded polytope, so that it's only computed
+//! when needed, and never twice.
+
+use std::{sync::Arc, time::Duration};
+
+use super::perf::{timed, PerfStats};
+use crate::{Concrete, EPS};
+
+use bevy::{
+    prelude::*,
+    tasks::{block_on, AsyncComputeTaskPool, Task},
+};
+use miratope_core::{
+    abs::{topology::Topology, Ranked},
+    conc::{symmetry::Transitivity, ConcretePolytope},
+    Polytope,
+};
+use vec_like::VecLike;
+
+/// The plugin that keeps the polytope statistics up to date.
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (invalidate_stats, poll_stats).chain());
+    }
+}
+
+/// An element type, together with some data about one of its representatives.
+#[derive(Clone, Copy, Debug)]
+pub struct ElementTypeWithData {
+    /// The index of the representative for this element type.
+    pub example: usize,
+
+    /// The number of elements of this type.
+    pub count: usize,
+
+    /// The number of facets.
+    pub facets: usize,
+
+    /// The number of facets of the figure.
+    pub fig_facets: usize,
+
+    /// The circumradius of the element, or distance from the origin if it's a vertex.
+    pub radius: Option<f64>,
+
+    /// The Euler characteristic of the element.
+    pub euler_characteristic: isize,
+}
+
+/// All of the data we derive from a polytope for the right panel.
+pub struct PolyStats {
+    /// The polytope these statistics are about, with its elements sorted.
+    pub poly: Concrete,
+
+    /// The element types of each rank.
+    pub types: Vec<Vec<ElementTypeWithData>>,
+
+    /// The distinct edge lengths, in increasing order.
+    pub edge_lengths: Vec<f64>,
+
+    /// The circumradius of the polytope, if it has a circumsphere.
+    pub circumradius: Option<f64>,
+
+    /// The number of flags of the polytope.
+    pub flag_count: usize,
+
+    /// The topological invariants of the polytope.
+    pub topology: Topology,
+
+    /// Whether the polytope is the orientable double cover of its central
+    /// quotient.
+    pub double_cover: bool,
+
+    /// The facets whose hyperplanes pass through the gravicenter.
+    pub hemi_facets: Vec<usize>,
+
+    /// The volume of the polytope, if it has one.
+    pub hypervolume: Option<f64>,
+
+    /// The total volume of the facets, if they all have one.
+    pub facet_content: Option<f64>,
+
+    /// The kinds of elements the symmetries act transitively on, if the
+    /// symmetry group could be computed.
+    pub transitivity: Option<Transitivity>,
+}
+
+impl PolyStats {
+    /// Computes the statistics of a polytope. This can be quite slow.
+    pub fn new(mut poly: Concrete) -> Self {
+        poly.element_sort();
+
+        let rank = poly.rank();
+        let dual_abs = poly.abs.dual();
+        let mut types = Vec::new();
+
+        for (r, types_this_rank) in poly.element_types().into_iter().enumerate() {
+            if r == rank {
+                break;
+            }
+
+            let mut types_with_data = Vec::new();
+
+            for t in types_this_rank {
+                let idx = t.example;
+
+                let facets = poly.abs[(r, idx)].subs.len();
+                let fig_facets = dual_abs.element_vertices(rank - r, idx).unwrap().len();
+                let element = poly.element(r, idx).unwrap();
+                let radius = if r == 1 {
+                    Some(poly.vertices[idx].norm())
+                } else {
+                    element.circumsphere().map(|sphere| sphere.radius())
+                };
+                let euler_characteristic = element.abs.euler_characteristic();
+
+                types_with_data.push(ElementTypeWithData {
+                    example: idx,
+                    count: t.count,
+                    facets,
+                    fig_facets,
+                    radius,
+                    euler_characteristic,
+                });
+            }
+
+            types.push(types_with_data);
+        }
+
+        let mut edge_lengths: Vec<_> = (0..poly.edge_count())
+            .filter_map(|idx| poly.edge_len(idx))
+            .collect();
+        edge_lengths.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+        edge_lengths.dedup_by(|a, b| (*a - *b).abs() < EPS);
+
+        let circumradius = poly.circumsphere().map(|sphere| sphere.radius());
+        let flag_count = poly.flags().count();
+        let topology = poly.abs.topology();
+        let double_cover = poly.is_double_cover();
+        let hemi_facets = poly.hemi_facets();
+        let hypervolume = poly.hypervolume();
+        let facet_content = poly.facet_content();
+        let transitivity = poly.transitivity();
+
+        Self {
+            poly,
+            types,
+            edge_lengths,
+            circumradius,
+            flag_count,
+            topology,
+            double_cover,
+            hemi_facets,
+            hypervolume,
+            facet_content,
+            transitivity,
+        }
+    }
+}
+
+/// Writes the topological invariants of a polytope as text, one per line.
+pub fn topology_report(topology: &Topology, double_cover: bool) -> String {
+    format!(
+        "{}Double cover of its central quotient: {}\n",
+        topology,
+        if double_cover { "yes" } else { "no" }
+    )
+}
+
+/// The state of the [`StatsCache`].
+enum CacheState {
+    /// The statistics haven't been computed for the current polytope.
+    Stale,
+
+    /// The statistics are being computed in the background. The task also
+    /// returns how long they took.
+    Pending(Task<(PolyStats, Duration)>),
+
+    /// The statistics are up to date.
+    Ready(Arc<PolyStats>),
+}
+
+/// A component that caches the [`PolyStats`] of the polytope on the same
+/// entity. The statistics are only computed on request, in the background, and
+/// are discarded whenever the polytope changes.
+#[derive(Component)]
+pub struct StatsCache(CacheState);
+
+impl Default for StatsCache {
+    fn default() -> Self {
+        Self(CacheState::Stale)
+    }
+}
+
+impl StatsCache {
+    /// Starts computing the statistics of a polytope in the background, unless
+    /// they're already computed or being computed.
+    pub fn request(&mut self, poly: &Concrete) {
+        if let CacheState::Stale = self.0 {
+            let poly = poly.clone();
+            self.0 = CacheState::Pending(
+                AsyncComputeTaskPool::get().spawn(async move { timed(|| PolyStats::new(poly)) }),
+            );
+        }
+    }
+
+    /// Returns the statistics if they've already been computed.
+    pub fn get(&self) -> Option<Arc<PolyStats>> {
+        match &self.0 {
+            CacheState::Ready(stats) => Some(Arc::clone(stats)),
+            _ => None,
+        }
+    }
+
+    /// Discards the cached statistics. Any computation in progress is
+    /// cancelled.
+    pub fn invalidate(&mut self) {
+        self.0 = CacheState::Stale;
+    }
+}
+
+/// Discards the statistics of any polytope that has changed.
+fn invalidate_stats(mut caches: Query<'_, '_, &mut StatsCache, Changed<Concrete>>) {
+    for mut cache in caches.iter_mut() {
+        cache.invalidate();
+    }
+}
+
+/// Stores the results of any background computation that has finished.
+fn poll_stats(mut caches: Query<'_, '_, &mut StatsCache>, mut perf: ResMut<'_, PerfStats>) {
+    for mut cache in caches.iter_mut() {
+        let finished = matches!(&cache.0, CacheState::Pending(task) if task.is_finished());
+
+        if finished {
+            if let CacheState::Pending(task) = std::mem::replace(&mut cache.0, CacheState::Stale) {
+                let (stats, duration) = block_on(task);
+                perf.record("Statistics", duration);
+                cache.0 = CacheState::Ready(Arc::new(stats));
+            }
+        }
+    }
+}
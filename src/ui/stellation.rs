@@ -0,0 +1,180 @@
+//! Lets the user build stellations of the polyhedron on screen, by picking
+//! cells of its stellation diagram.
+
+use super::{main_window::PolyName, memory::Memory, window::ShowWindows};
+use crate::Concrete;
+use miratope_core::{
+    abs::Ranked,
+    conc::{stellation::StellationDiagram, symmetry::SymmetryCache},
+};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+/// The plugin in charge of the stellation window.
+pub struct StellationPlugin;
+
+impl Plugin for StellationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StellationWindow>()
+            .add_systems(EguiPrimaryContextPass, show_stellation_window.in_set(ShowWindows));
+    }
+}
+
+/// The stellation diagram of a polyhedron, together with the orbits of cells
+/// the user can pick from.
+pub struct OpenDiagram {
+    /// The stellation diagram.
+    diagram: StellationDiagram,
+
+    /// The orbits of cells under the symmetry group of the polyhedron, or the
+    /// single cells if it couldn't be computed.
+    orbits: Vec<Vec<usize>>,
+
+    /// Whether each orbit is picked.
+    picked: Vec<bool>,
+
+    /// The name of the polyhedron.
+    name: String,
+}
+
+/// The window that shows the stellation diagram of a polyhedron, if it's
+/// open.
+#[derive(Default, Resource)]
+pub struct StellationWindow(Option<OpenDiagram>);
+
+impl StellationWindow {
+    /// Computes the stellation diagram of a polyhedron and opens the window.
+    /// Returns `false` if the polytope isn't a 3D polyhedron.
+    pub fn open(&mut self, poly: &Concrete, symmetry: &mut SymmetryCache, name: &str) -> bool {
+        let diagram = match poly.stellation_diagram() {
+            Some(diagram) => diagram,
+            None => return false,
+        };
+
+        let orbits = match symmetry.symmetry_group(poly) {
+            Some((group, _)) => diagram.cell_orbits(group),
+            None => (0..diagram.cells().len()).map(|idx| vec![idx]).collect(),
+        };
+
+        // Only the core is picked at first.
+        let mut picked = vec![false; orbits.len()];
+        picked[0] = true;
+
+        self.0 = Some(OpenDiagram {
+            diagram,
+            orbits,
+            picked,
+            name: name.to_string(),
+        });
+        true
+    }
+
+    /// Returns the name of the stellation that's currently picked.
+    fn stellation_name(open: &OpenDiagram) -> String {
+        let orbits: Vec<String> = (0..open.orbits.len())
+            .filter(|&idx| open.picked[idx])
+            .map(|idx| idx.to_string())
+            .collect();
+        format!("Stellation {{{}}} of {}", orbits.join(", "), open.name)
+    }
+}
+
+/// Shows the stellation window, and builds the stellation the user picks.
+pub fn show_stellation_window(
+    mut egui_ctx: EguiContexts<'_, '_>,
+    mut window: ResMut<'_, StellationWindow>,
+    mut query: Query<'_, '_, &mut Concrete>,
+    mut poly_name: ResMut<'_, PolyName>,
+    mut memory: ResMut<'_, Memory>,
+) -> Result {
+    let Some(open) = &mut window.0 else {
+        return Ok(());
+    };
+
+    let mut is_open = true;
+    let mut load = false;
+    let mut save = false;
+
+    egui::Window::new("Stellation")
+        .open(&mut is_open)
+        .resizable(false)
+        .show(egui_ctx.ctx_mut()?, |ui| {
+            let diagram = &open.diagram;
+            ui.label(format!(
+                "{} planes, {} cells in {} layers",
+                diagram.plane_count(),
+                diagram.cells().len(),
+                diagram.layer_count()
+            ));
+            ui.separator();
+
+            // The orbits, grouped by layer.
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                let mut layer = None;
+                for (idx, orbit) in open.orbits.iter().enumerate() {
+                    let orbit_layer = diagram.cells()[orbit[0]].layer();
+                    if layer != Some(orbit_layer) {
+                        layer = Some(orbit_layer);
+                        ui.label(format!("Layer {}", orbit_layer));
+                    }
+
+                    let cells = if orbit.len() == 1 { "cell" } else { "cells" };
+                    ui.checkbox(
+                        &mut open.picked[idx],
+                        format!("Orbit {}: {} {}", idx, orbit.len(), cells),
+                    );
+                }
+            });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if ui.button("Core only").clicked() {
+                    open.picked.iter_mut().for_each(|picked| *picked = false);
+                    open.picked[0] = true;
+                }
+
+                if ui.button("All").clicked() {
+                    open.picked.iter_mut().for_each(|picked| *picked = true);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                load = ui.button("Load").clicked();
+                save = ui.button("Save to memory").clicked();
+            });
+        });
+
+    if load || save {
+        let cells: Vec<usize> = open
+            .orbits
+            .iter()
+            .zip(&open.picked)
+            .filter(|(_, picked)| **picked)
+            .flat_map(|(orbit, _)| orbit.iter().copied())
+            .collect();
+
+        match open.diagram.stellation(&cells) {
+            Some(stellation) => match stellation.abs.ranks().is_valid() {
+                Ok(()) => {
+                    let name = StellationWindow::stellation_name(open);
+                    if save {
+                        memory.push((stellation, Some(name)));
+                    } else if let Some(mut p) = query.iter_mut().next() {
+                        *p = stellation;
+                        poly_name.0 = name;
+                    }
+                }
+                Err(err) => eprintln!("Stellation failed: {}", err),
+            },
+            None => eprintln!("Stellation failed: no cells were picked."),
+        }
+    }
+
+    if !is_open {
+        window.0 = None;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,203 @@
+//! Lets the user browse the subgroups of the symmetry group of the loaded
+//! polytope up to conjugacy, and facet it or take its quotient under any of
+//! them.
+
+use super::{
+    faceting::FacetingJob,
+    main_window::PolyName,
+    window::{FacetingSettings, ShowWindows},
+};
+use crate::Concrete;
+use miratope_core::{
+    conc::{faceting::GroupEnum, symmetry::SymmetryCache},
+    group::{subgroups::GroupTable, vertex_map::VertexMap},
+};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+use vec_like::VecLike;
+
+/// The largest order of a symmetry group whose subgroups are enumerated, as
+/// its multiplication table has an entry for each pair of elements.
+const MAX_ORDER: usize = 2000;
+
+/// The most subgroup classes that are listed.
+const MAX_CLASSES: usize = 500;
+
+/// The plugin in charge of the subgroup browser.
+pub struct SubgroupPlugin;
+
+impl Plugin for SubgroupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SubgroupBrowser>()
+            .add_systems(Update, reset_subgroups)
+            .add_systems(EguiPrimaryContextPass, show_subgroups.in_set(ShowWindows));
+    }
+}
+
+/// A conjugacy class of subgroups, as shown in the browser.
+struct SubgroupEntry {
+    /// A description of the subgroups, with their order and classification.
+    label: String,
+
+    /// The classification of the subgroups.
+    name: String,
+
+    /// The number of subgroups in the class.
+    conjugates: usize,
+
+    /// The vertex map of a representative of the class.
+    vertex_map: VertexMap,
+}
+
+/// The state of the subgroup browser.
+#[derive(Default, Resource)]
+pub struct SubgroupBrowser {
+    /// Whether the window is open.
+    pub open: bool,
+
+    /// The subgroup classes of the symmetry group of the loaded polytope, or
+    /// the reason they couldn't be found. These are computed when the window
+    /// is opened, and forgotten whenever the polytope changes.
+    entries: Option<Result<Vec<SubgroupEntry>, String>>,
+}
+
+impl SubgroupBrowser {
+    /// Opens the window, forgetting the subgroups of any previous polytope.
+    pub fn open(&mut self) {
+        self.open = true;
+        self.entries = None;
+    }
+
+    /// Returns the subgroup classes of the symmetry group of a polytope,
+    /// computing them if needed.
+    fn entries(&mut self, poly: &Concrete, symmetry: &mut SymmetryCache) -> &Result<Vec<SubgroupEntry>, String> {
+        self.entries.get_or_insert_with(|| {
+            let (group, vertex_map) = symmetry
+                .symmetry_group(poly)
+                .ok_or_else(|| "The symmetry group could not be computed.".to_string())?;
+
+            let group = group.cache();
+            let order = group.clone().count();
+            if order > MAX_ORDER {
+                return Err(format!("The symmetry group has order {}, which is too large.", order));
+            }
+
+            let table = GroupTable::new(group);
+            Ok(table
+                .subgroup_classes(Some(MAX_CLASSES))
+                .into_iter()
+                .map(|class| {
+                    // Safety: the elements of a subgroup class form a group.
+                    let name = unsafe { table.subgroup(&class.elements) }.classify();
+                    let mut sub_map = VertexMap::new();
+                    for &idx in &class.elements {
+                        sub_map.push(vertex_map[idx].clone());
+                    }
+
+                    SubgroupEntry {
+                        label: format!("Order {} ({})", class.order(), name),
+                        name: name.to_string(),
+                        conjugates: class.conjugates,
+                        vertex_map: sub_map,
+                    }
+                })
+                .collect())
+        })
+    }
+}
+
+/// Shows the subgroup browser, and either starts a faceting under the
+/// subgroup the user picks or replaces the polytope by its quotient.
+pub fn show_subgroups(
+    mut egui_ctx: EguiContexts<'_, '_>,
+    mut browser: ResMut<'_, SubgroupBrowser>,
+    mut query: Query<'_, '_, &mut Concrete>,
+    mut poly_name: ResMut<'_, PolyName>,
+    mut symmetry: ResMut<'_, SymmetryCache>,
+    mut faceting_job: ResMut<'_, FacetingJob>,
+    faceting_settings: Res<'_, FacetingSettings>,
+) -> Result {
+    if !browser.open {
+        return Ok(());
+    }
+
+    let Some(mut poly) = query.iter_mut().next() else {
+        return Ok(());
+    };
+
+    let mut is_open = true;
+    let mut facet_under = None;
+    let mut quotient_by = None;
+    let running = faceting_job.is_running();
+
+    egui::Window::new("Subgroups")
+        .open(&mut is_open)
+        .default_height(400.0)
+        .show(egui_ctx.ctx_mut()?, |ui| match browser.entries(&poly, &mut symmetry) {
+            Ok(entries) => {
+                ui.label(format!("{} classes of subgroups, up to conjugacy.", entries.len()));
+                if entries.len() == MAX_CLASSES {
+                    ui.label("The list was cut short, so some larger subgroups might be missing.");
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (idx, entry) in entries.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(!running, egui::Button::new("Facet")).clicked() {
+                                facet_under = Some(idx);
+                            }
+
+                            // Identifies the elements in each orbit of the
+                            // subgroup, like opposite elements for hemis.
+                            if ui.button("Quotient").clicked() {
+                                quotient_by = Some(idx);
+                            }
+
+                            ui.label(&entry.label);
+                            if entry.conjugates > 1 {
+                                ui.label(format!("×{} conjugates", entry.conjugates));
+                            }
+                        });
+                    }
+                });
+            }
+            Err(err) => {
+                ui.label(err.as_str());
+            }
+        });
+
+    if let Some(idx) = facet_under
+        && let Some(Ok(entries)) = &browser.entries
+    {
+        let group = GroupEnum::VertexMap(entries[idx].vertex_map.clone());
+        faceting_job.start_with_settings(&poly, poly.vertices.clone(), group, &faceting_settings);
+    }
+
+    if let Some(idx) = quotient_by
+        && let Some(Ok(entries)) = &browser.entries
+    {
+        let entry = &entries[idx];
+        match poly.quotient(&entry.vertex_map) {
+            Ok(q) => {
+                *poly = q;
+                poly_name.0 = format!("Quotient of {} by {}", poly_name.0, entry.name);
+            }
+            Err(err) => eprintln!("Quotient failed: {}", err),
+        }
+    }
+
+    if !is_open {
+        browser.open = false;
+    }
+
+    Ok(())
+}
+
+/// Forgets the subgroups whenever the polytope changes.
+fn reset_subgroups(polies: Query<'_, '_, &Concrete, Changed<Concrete>>, mut browser: ResMut<'_, SubgroupBrowser>) {
+    if polies.iter().next().is_some() {
+        browser.entries = None;
+    }
+}
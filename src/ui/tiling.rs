@@ -0,0 +1,139 @@
+//! Shows periodic tilings and honeycombs. The current polytope becomes a
+//! patch of the tiling, which is rebuilt with more or fewer copies of its
+//! elements as the camera moves, so that it always fills the view.
+
+use super::{
+    camera::{OrthoScale, ProjectionType, FOV},
+    compare::CompareCamera,
+};
+use crate::Concrete;
+use miratope_core::{conc::tiling::Tiling, Polytope};
+
+use bevy::prelude::*;
+
+/// The most vertices a patch can have, so that honeycombs with many periods
+/// don't take forever to build and render.
+const MAX_VERTICES: usize = 20_000;
+
+/// The plugin in charge of the tilings.
+pub struct TilingPlugin;
+
+impl Plugin for TilingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentTiling>()
+            .add_systems(Update, update_patch);
+    }
+}
+
+/// The tiling the current polytope is a patch of, if any.
+#[derive(Resource)]
+pub struct CurrentTiling {
+    /// The tiling.
+    tiling: Option<Tiling>,
+
+    /// How many times each period the current patch extends for.
+    radius: usize,
+
+    /// The depth of the truncations from the menu.
+    pub depth: f64,
+}
+
+impl Default for CurrentTiling {
+    fn default() -> Self {
+        Self {
+            tiling: None,
+            radius: 0,
+            depth: 1.0 / 3.0,
+        }
+    }
+}
+
+impl CurrentTiling {
+    /// Returns the tiling, if any.
+    pub fn get(&self) -> Option<&Tiling> {
+        self.tiling.as_ref()
+    }
+
+    /// Sets the tiling, so that the current polytope gets replaced by a patch
+    /// of it.
+    pub fn set(&mut self, tiling: Tiling) {
+        self.tiling = Some(tiling);
+        self.radius = 0;
+    }
+}
+
+/// Returns how many times each period a patch of a tiling has to extend for
+/// to cover a ball around the origin.
+fn radius(tiling: &Tiling, extent: f32) -> usize {
+    let shortest = tiling
+        .periods
+        .iter()
+        .map(|period| period.norm())
+        .fold(f64::INFINITY, f64::min);
+
+    let mut radius = if shortest.is_finite() && shortest > 0.0 {
+        (extent as f64 / shortest).ceil().max(1.0) as usize
+    } else {
+        1
+    };
+
+    let copies = |radius: usize| (2 * radius + 1).pow(tiling.periods.len() as u32) * tiling.vertices.len();
+    while radius > 1 && copies(radius) > MAX_VERTICES {
+        radius -= 1;
+    }
+
+    radius
+}
+
+/// Rebuilds the patch whenever the tiling changes, or the camera moves far
+/// enough that the patch no longer fills the view. If the current polytope is
+/// replaced in any other way, the tiling is forgotten.
+fn update_patch(
+    mut current: ResMut<'_, CurrentTiling>,
+    mut polies: Query<'_, '_, &mut Concrete>,
+    cameras: Query<'_, '_, (&Transform, &GlobalTransform), (With<Camera3d>, Without<CompareCamera>)>,
+    (projection, ortho_scale): (Res<'_, ProjectionType>, Res<'_, OrthoScale>),
+) {
+    let Some(mut poly) = polies.iter_mut().next() else {
+        return;
+    };
+
+    // A system never sees its own changes, so this only happens when
+    // something else changed the polytope.
+    if poly.is_changed() && !current.is_changed() {
+        if current.tiling.is_some() {
+            current.tiling = None;
+        }
+        return;
+    }
+
+    let Some(tiling) = &current.tiling else {
+        return;
+    };
+    let Some((cam_tf, cam_gtf)) = cameras.iter().next() else {
+        return;
+    };
+
+    // We cover the ball around the anchor that the camera can see, along with
+    // the origin. Doubling the height covers the width of most windows.
+    let distance = cam_tf.translation.length();
+    let anchor = cam_gtf.translation() + *cam_gtf.forward() * distance;
+    let extent = anchor.length()
+        + 2.0 * if projection.is_orthogonal() {
+            ortho_scale.0
+        } else {
+            distance * (FOV / 2.0).tan()
+        };
+
+    let radius = radius(tiling, extent);
+    if radius == current.radius {
+        return;
+    }
+
+    // Sorting the patch right away keeps other systems from sorting it, which
+    // would make us forget the tiling.
+    let mut patch = tiling.patch(radius);
+    patch.element_sort();
+    *poly = patch;
+    current.radius = radius;
+}
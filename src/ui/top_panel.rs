@@ -1,15 +1,18 @@
 //! Contains all code related to the top bar.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use super::{camera::ProjectionType, memory::Memory, window::{Window, *}, UnitPointWidget, main_window::PolyName, config::{MeshColor, WfColor, SlotsPerPage}, CurrentVisuals};
-use crate::{Concrete, Float, Hyperplane, Point, Vector};
+use super::{annotation::Annotations, camera::{CameraInputEvent, OrthoScale, ProjectionType}, memory::{slot_label, Memory}, window::{Window, *}, UnitPointWidget, main_window::{PolyName, WfLegend}, preview::Preview, compare::CompareView, selection::SelectionMode, faceting::FacetingJob, jobs::{BackgroundJob, SymmetryReport}, stellation::StellationWindow, explorer::SectionExplorer, subgroups::SubgroupBrowser, stats::topology_report, tiling::CurrentTiling, watcher::FileWatcher, frame::CoordinateFrame, config::{AutoRecenter, AutoRescale, LoadSettings, SaveSettings, MeshColor, OffsetCoplanarFaces, PreviewOperations, RenderSettings, ShowIntersections, ShowPerformance, WfColor, WfColorMode, SlotsPerPage}, CurrentVisuals};
+use crate::{mesh::{NormalMode, Renderable}, Concrete, Float, Hyperplane, Point, Vector, EPS};
 
 use bevy::prelude::*;
 use bevy::ecs::change_detection::ResMut;
+use bevy::ecs::system::SystemParam;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::render::view::screenshot::{save_to_disk, Screenshot};
 use bevy_egui::{egui::{self, Ui, MenuBar}, EguiContexts, EguiPrimaryContextPass};
 use bevy_egui::egui::{Visuals};
-use miratope_core::{conc::{ConcretePolytope, faceting::GroupEnum, symmetry::Vertices}, file::FromFile, float::Float as Float2, Polytope, abs::Ranked};
+use miratope_core::{conc::{ConcretePolytope, embedding::EmbeddingOptions, faceting::{FacetingSignature, GroupEnum}, symmetry::{SymmetryCache, Vertices}, tiling::Tiling}, file::{gltf::GltfOptions, group::{group_to_string, GroupFormat}, povray::{PovCamera, PovOptions}, FromFile}, float::Float as Float2, cox::{Cox, CoxError}, group::disk_cache::GroupCache, Polytope, abs::Ranked};
 
 /// The plugin in charge of everything on the top panel.
 pub struct TopPanelPlugin;
@@ -25,6 +28,7 @@ impl Plugin for TopPanelPlugin {
             .init_resource::<ExportMemory>()
             .init_non_send_resource::<FileDialogToken>()
             .add_systems(EguiPrimaryContextPass, file_dialog)
+            .add_systems(Update, slice_with_camera)
             // Windows must be the first thing shown.
             .add_systems(EguiPrimaryContextPass,
                 show_top_panel
@@ -56,6 +60,9 @@ pub enum SectionState {
         /// Whether we're not updating the cross-section.
         lock: bool,
 
+        /// Whether the first slicing hyperplane follows the camera.
+        follow: SectionFollow,
+
         /// Whether to update the polytope. This is a bodge.
         update: bool,
     },
@@ -99,9 +106,16 @@ impl SectionState {
             hyperplane_pos: minmax.clone().into_iter().map(|m| (m.0 + m.1) / 2.0).collect(),
             flatten: true,
             lock: false,
+            follow: SectionFollow::Off,
             update: false,
         }
     }
+
+    /// Returns whether the mouse wheel moves the slicing hyperplane, rather
+    /// than zooming the camera.
+    pub fn follows_camera(&self) -> bool {
+        matches!(self, Self::Active { follow, .. } if *follow != SectionFollow::Off)
+    }
 }
 
 impl Clone for SectionState {
@@ -113,6 +127,7 @@ impl Clone for SectionState {
                 hyperplane_pos,
                 flatten,
                 lock,
+                follow,
                 update,
             } = self{
                 
@@ -123,6 +138,7 @@ impl Clone for SectionState {
                 hyperplane_pos: hyperplane_pos.clone(),
                 flatten: *flatten,
                 lock: *lock,
+                follow: *follow,
                 update: *update,
             }
         }
@@ -138,6 +154,54 @@ impl Default for SectionState {
     }
 }
 
+/// How the first slicing hyperplane of the cross-section view moves along
+/// with the camera. Whenever it does, the mouse wheel and the Page Up and Page
+/// Down keys move it back and forth.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SectionFollow {
+    /// The hyperplane is only moved through the cross-section settings.
+    #[default]
+    Off,
+
+    /// The hyperplane is perpendicular to the direction the camera looks in,
+    /// within the first three coordinates.
+    View,
+
+    /// The hyperplane is perpendicular to a coordinate axis that's projected
+    /// away, such as the fourth one.
+    Axis(usize),
+}
+
+impl SectionFollow {
+    /// Returns the name of the mode, as shown on the cross-section settings.
+    fn label(self) -> String {
+        match self {
+            Self::Off => "Off".to_string(),
+            Self::View => "View direction".to_string(),
+            Self::Axis(axis) => format!("Axis {}", axis + 1),
+        }
+    }
+
+    /// Returns the direction the hyperplane should be perpendicular to, in a
+    /// given dimension, given the direction the camera looks in.
+    fn direction(self, dim: usize, forward: Vec3) -> Option<Vector> {
+        let mut direction = Vector::zeros(dim);
+        match self {
+            Self::Off => return None,
+            Self::View => {
+                for i in 0..dim.min(3) {
+                    direction[i] = forward[i] as Float;
+                }
+            }
+            Self::Axis(axis) => *direction.get_mut(axis)? = 1.0,
+        }
+
+        // The camera might look along the axes we don't have.
+        let norm = direction.norm();
+        (norm > EPS).then(|| direction / norm)
+    }
+}
+
 /// Stores the direction in which the cross-sections are taken.
 pub struct SectionDirection(pub Vector);
 
@@ -202,12 +266,87 @@ impl FileDialogToken {
 
     /// Returns the path given by an open file dialog.
     fn pick_file(&self) -> Option<PathBuf> {
-        Self::new_file_dialog().pick_file()
+        Self::new_file_dialog()
+            .add_filter("MOFF File", &["moff"])
+            .add_filter("Incidence File", &["inc"])
+            .add_filter("Coordinate Recipe", &["recipe"])
+            .add_filter("Tiling", &["toff"])
+            .pick_file()
     }
 
     /// Returns the path given by a save file dialog.
     fn save_file(&self, name: &str) -> Option<PathBuf> {
-        Self::new_file_dialog().set_file_name(name).save_file()
+        Self::new_file_dialog()
+            .add_filter("GGB File", &["ggb"])
+            .add_filter("MOFF File", &["moff"])
+            .add_filter("Tiling", &["toff"])
+            .set_file_name(name)
+            .save_file()
+    }
+
+    /// Returns the path given by a folder dialog.
+    fn pick_folder(&self) -> Option<PathBuf> {
+        rfd::FileDialog::new().pick_folder()
+    }
+
+    /// Returns the path given by a save file dialog for a POV-Ray scene.
+    fn save_pov_file(&self, name: &str) -> Option<PathBuf> {
+        rfd::FileDialog::new()
+            .add_filter("POV-Ray Scene", &["pov"])
+            .set_file_name(name)
+            .save_file()
+    }
+
+    /// Returns the path given by a save file dialog for a glTF scene.
+    fn save_gltf_file(&self, name: &str) -> Option<PathBuf> {
+        rfd::FileDialog::new()
+            .add_filter("glTF Binary", &["glb"])
+            .set_file_name(name)
+            .save_file()
+    }
+
+    /// Returns the path given by a save file dialog for a screenshot.
+    fn save_png_file(&self, name: &str) -> Option<PathBuf> {
+        rfd::FileDialog::new()
+            .add_filter("PNG Image", &["png"])
+            .set_file_name(name)
+            .save_file()
+    }
+
+    /// Returns the path given by a save file dialog for an OBJ mesh.
+    fn save_obj_file(&self, name: &str) -> Option<PathBuf> {
+        rfd::FileDialog::new()
+            .add_filter("Wavefront OBJ", &["obj"])
+            .set_file_name(name)
+            .save_file()
+    }
+
+    /// Returns the path given by a save file dialog for a symmetry group.
+    fn save_group_file(&self, name: &str) -> Option<PathBuf> {
+        rfd::FileDialog::new()
+            .add_filter("GAP File", &["g"])
+            .add_filter("Magma File", &["m"])
+            .add_filter("CSV File", &["csv"])
+            .add_filter("JSON File", &["json"])
+            .set_file_name(name)
+            .save_file()
+    }
+
+    /// Returns the path given by a save file dialog for a table of orbits.
+    fn save_orbits_file(&self, name: &str) -> Option<PathBuf> {
+        rfd::FileDialog::new()
+            .add_filter("CSV File", &["csv"])
+            .add_filter("JSON File", &["json"])
+            .set_file_name(name)
+            .save_file()
+    }
+
+    /// Returns the path given by a save file dialog for a text report.
+    fn save_text_file(&self, name: &str) -> Option<PathBuf> {
+        rfd::FileDialog::new()
+            .add_filter("Text File", &["txt"])
+            .set_file_name(name)
+            .save_file()
     }
 }
 
@@ -221,6 +360,37 @@ enum FileDialogMode {
 
     /// We're showing a file dialog to save a file.
     Save,
+
+    /// We're showing a file dialog to save a polytope from memory.
+    SaveSlot,
+
+    /// We're showing a folder dialog to export many polytopes from memory.
+    ExportSlots,
+
+    /// We're showing a file dialog to export a POV-Ray scene.
+    ExportPov,
+
+    /// We're showing a file dialog to export a glTF scene.
+    ExportGltf,
+
+    /// We're showing a file dialog to export the mesh as an OBJ file.
+    ExportObj,
+
+    /// We're showing a folder dialog to export every facet as a file.
+    ExportFacets,
+
+    /// We're showing a file dialog to export the generators of the symmetry
+    /// group.
+    ExportSymmetry,
+
+    /// We're showing a file dialog to export the topological invariants.
+    ExportTopology,
+
+    /// We're showing a file dialog to export the edge and face orbits.
+    ExportOrbits,
+
+    /// We're showing a file dialog to save a screenshot of the window.
+    Screenshot,
 }
 
 /// The file dialog is disabled by default.
@@ -238,6 +408,10 @@ pub struct FileDialogState {
 
     /// The name of the file to load or save, if any.
     name: Option<String>,
+
+    /// The polytopes to save along with their names, if they aren't the
+    /// current one.
+    slots: Vec<(Concrete, String)>,
 }
 
 impl FileDialogState {
@@ -253,41 +427,177 @@ impl FileDialogState {
         self.name = Some(name);
     }
 
+    /// Changes the file dialog mode to [`FileDialogMode::SaveSlot`], and loads
+    /// the polytope to save and the name of the file.
+    pub fn save_slot(&mut self, poly: Concrete, name: String) {
+        self.mode = FileDialogMode::SaveSlot;
+        self.name = Some(name.clone());
+        self.slots = vec![(poly, name)];
+    }
+
+    /// Changes the file dialog mode to [`FileDialogMode::ExportSlots`], and
+    /// loads the polytopes to save and their file names.
+    pub fn export_slots(&mut self, slots: Vec<(Concrete, String)>) {
+        self.mode = FileDialogMode::ExportSlots;
+        self.name = None;
+        self.slots = slots;
+    }
+
+    /// Changes the file dialog mode to [`FileDialogMode::ExportPov`], and
+    /// loads the name of the file.
+    pub fn export_pov(&mut self, name: String) {
+        self.mode = FileDialogMode::ExportPov;
+        self.name = Some(name);
+    }
+
+    /// Changes the file dialog mode to [`FileDialogMode::ExportGltf`], and
+    /// loads the name of the file.
+    pub fn export_gltf(&mut self, name: String) {
+        self.mode = FileDialogMode::ExportGltf;
+        self.name = Some(name);
+    }
+
+    /// Changes the file dialog mode to [`FileDialogMode::ExportObj`], and
+    /// loads the name of the file.
+    pub fn export_obj(&mut self, name: String) {
+        self.mode = FileDialogMode::ExportObj;
+        self.name = Some(name);
+    }
+
+    /// Changes the file dialog mode to [`FileDialogMode::ExportFacets`], and
+    /// loads the name of the polytope.
+    pub fn export_facets(&mut self, name: String) {
+        self.mode = FileDialogMode::ExportFacets;
+        self.name = Some(name);
+    }
+
+    /// Changes the file dialog mode to [`FileDialogMode::ExportSymmetry`], and
+    /// loads the name of the polytope.
+    pub fn export_symmetry(&mut self, name: String) {
+        self.mode = FileDialogMode::ExportSymmetry;
+        self.name = Some(name);
+    }
+
+    /// Changes the file dialog mode to [`FileDialogMode::ExportTopology`], and
+    /// loads the name of the polytope.
+    pub fn export_topology(&mut self, name: String) {
+        self.mode = FileDialogMode::ExportTopology;
+        self.name = Some(name);
+    }
+
+    /// Changes the file dialog mode to [`FileDialogMode::ExportOrbits`], and
+    /// loads the name of the polytope.
+    pub fn export_orbits(&mut self, name: String) {
+        self.mode = FileDialogMode::ExportOrbits;
+        self.name = Some(name);
+    }
+
+    /// Changes the file dialog mode to [`FileDialogMode::Screenshot`], and
+    /// loads the name of the polytope.
+    pub fn screenshot(&mut self, name: String) {
+        self.mode = FileDialogMode::Screenshot;
+        self.name = Some(name);
+    }
+
     /// Gets the name of the file dialog.
     pub fn unwrap_name(&self) -> &str {
         self.name.as_ref().unwrap()
     }
 }
 
+/// Saves a polytope into a path, in the format given by its extension.
+fn save_path(p: &Concrete, path: &Path, name: &str, save_settings: &SaveSettings) {
+    // GGB files are only written for polytopes in at most 3D.
+    let result = if path.extension().is_some_and(|ext| ext == "ggb") {
+        p.to_ggb_path(path).map_err(|err| err.to_string())
+    } else if path.extension().is_some_and(|ext| ext == "moff") {
+        p.to_moff_path(path).map_err(|err| err.to_string())
+    } else {
+        p.to_path(path, save_settings.off_options(name))
+            .map_err(|err| err.to_string())
+    };
+
+    if let Err(err) = result {
+        eprintln!("File saving failed: {}", err);
+    }
+}
+
 /// The system in charge of showing the file dialog.
 pub fn file_dialog(
     mut query: Query<'_, '_, &mut Concrete>,
     mut name: ResMut<'_, PolyName>,
     file_dialog_state: Res<'_, FileDialogState>,
     file_dialog: NonSend<'_, FileDialogToken>,
+    mut commands: Commands<'_, '_>,
+    (load_settings, save_settings): (Res<'_, LoadSettings>, Res<'_, SaveSettings>),
+    (cameras, projection_type): (Query<'_, '_, &GlobalTransform, With<Camera>>, Res<'_, ProjectionType>),
+    (transforms, mesh_color, wf_color): (Query<'_, '_, &GlobalTransform, With<Concrete>>, Res<'_, MeshColor>, Res<'_, WfColor>),
+    (mut symmetry, mut file_watcher, mut tiling): (ResMut<'_, SymmetryCache>, ResMut<'_, FileWatcher>, ResMut<'_, CurrentTiling>),
 ) {
     if file_dialog_state.is_changed() {
         match file_dialog_state.mode {
             // We want to save a file.
             FileDialogMode::Save => {
                 if let Some(path) = file_dialog.save_file(file_dialog_state.unwrap_name()) {
-                    if let Some(p) = query.iter_mut().next() {
-                        if let Err(err) = p.con().to_path(&path, Default::default()) {
-                            eprintln!("File saving failed: {}", err);
+                    // Tilings are saved as such, rather than as their patch.
+                    if path.extension().is_some_and(|ext| ext == "toff") {
+                        match tiling.get() {
+                            Some(t) => {
+                                if let Err(err) = t.to_path(&path) {
+                                    eprintln!("File saving failed: {}", err);
+                                }
+                            }
+                            None => eprintln!("File saving failed: the polytope isn't a tiling"),
                         }
+                    } else if let Some(p) = query.iter_mut().next() {
+                        save_path(p.con(), &path, file_dialog_state.unwrap_name(), &save_settings);
+                        file_watcher.saved(&path);
+                    }
+                }
+            }
+
+            // We want to save a polytope from memory.
+            FileDialogMode::SaveSlot => {
+                if let Some(path) = file_dialog.save_file(file_dialog_state.unwrap_name()) {
+                    if let Some((p, name)) = file_dialog_state.slots.first() {
+                        save_path(p, &path, name, &save_settings);
+                        file_watcher.saved(&path);
                     }
                 }
             }
 
+            // We want to write many polytopes from memory into a folder.
+            FileDialogMode::ExportSlots => {
+                if let Some(path) = file_dialog.pick_folder() {
+                    for (p, name) in &file_dialog_state.slots {
+                        let path = path.join(format!("{}.off", name));
+                        save_path(p, &path, name, &save_settings);
+                        file_watcher.saved(&path);
+                    }
+                    println!("Exported {} polytopes.", file_dialog_state.slots.len());
+                }
+            }
+
             // We want to open a file.
             FileDialogMode::Open => {
                 if let Some(path) = file_dialog.pick_file() {
-                    if let Some(mut p) = query.iter_mut().next() {
+                    // Tilings replace the polytope with a patch of them once
+                    // the camera is known.
+                    if path.extension().is_some_and(|ext| ext == "toff") {
+                        match Tiling::from_path(&path) {
+                            Ok(t) => {
+                                tiling.set(t);
+                                name.0 = path.file_stem().unwrap().to_string_lossy().into_owned();
+                            }
+                            Err(err) => eprintln!("File open failed: {}", err),
+                        }
+                    } else if let Some(mut p) = query.iter_mut().next() {
                         match Concrete::from_path(&path) {
-                            Ok(q) => {
+                            Ok(mut q) => {
+                                load_settings.apply(&mut q);
                                 *p = q;
-                                let file_name = path.file_name().unwrap().to_str().unwrap();
-                                name.0 = file_name[..file_name.len()-4].into();
+                                name.0 = path.file_stem().unwrap().to_string_lossy().into_owned();
+                                file_watcher.watch(path);
                             }
                             Err(err) => eprintln!("File open failed: {}", err),
                         }
@@ -295,6 +605,137 @@ pub fn file_dialog(
                 }
             }
 
+            // We want to export a POV-Ray scene from the current viewpoint.
+            FileDialogMode::ExportPov => {
+                if let Some(path) = file_dialog.save_pov_file(file_dialog_state.unwrap_name()) {
+                    if let Some(p) = query.iter_mut().next() {
+                        // We export the polytope as it's projected on screen.
+                        let mut projected = p.clone();
+                        projected.vertices = crate::mesh::vertex_coords(&p, p.vertices.iter(), *projection_type)
+                            .into_iter()
+                            .map(|v| Point::from_iterator(3, v.map(f64::from)))
+                            .collect();
+
+                        let mut options = PovOptions::default();
+                        if let Some(cam_gtf) = cameras.iter().next() {
+                            let location = cam_gtf.translation();
+                            let look_at = location + *cam_gtf.forward();
+                            options.camera = PovCamera {
+                                location: location.to_array().map(f64::from),
+                                look_at: look_at.to_array().map(f64::from),
+                                sky: cam_gtf.up().to_array().map(f64::from),
+                                ..Default::default()
+                            };
+                        }
+
+                        if let Err(err) = projected.to_pov_path(&path, &options) {
+                            eprintln!("POV-Ray export failed: {}", err);
+                        }
+                    }
+                }
+            }
+
+            // We want to export the mesh and wireframe as they're shown.
+            FileDialogMode::ExportGltf => {
+                if let Some(path) = file_dialog.save_gltf_file(file_dialog_state.unwrap_name()) {
+                    if let Some(p) = query.iter_mut().next() {
+                        let mut projected = p.clone();
+                        projected.vertices = crate::mesh::vertex_coords(&p, p.vertices.iter(), *projection_type)
+                            .into_iter()
+                            .map(|v| Point::from_iterator(3, v.map(f64::from)))
+                            .collect();
+
+                        let options = GltfOptions {
+                            face_color: LinearRgba::from(mesh_color.0).to_f32_array(),
+                            edge_color: LinearRgba::from(wf_color.0).to_f32_array(),
+                            transform: transforms
+                                .iter()
+                                .next()
+                                .map_or(Mat4::IDENTITY, GlobalTransform::to_matrix)
+                                .to_cols_array(),
+                        };
+
+                        if let Err(err) = projected.to_glb_path(&path, &options) {
+                            eprintln!("glTF export failed: {}", err);
+                        }
+                    }
+                }
+            }
+
+            // We want to export the mesh as it's projected on screen.
+            FileDialogMode::ExportObj => {
+                if let Some(path) = file_dialog.save_obj_file(file_dialog_state.unwrap_name()) {
+                    if let Some(p) = query.iter_mut().next() {
+                        if let Err(err) = p.export_obj(&path, *projection_type) {
+                            eprintln!("OBJ export failed: {}", err);
+                        }
+                    }
+                }
+            }
+
+            // We want to write every facet into its own file.
+            FileDialogMode::ExportFacets => {
+                if let Some(path) = file_dialog.pick_folder() {
+                    if let Some(p) = query.iter_mut().next() {
+                        let name = file_dialog_state.unwrap_name();
+                        match p.con().facets_to_dir(&path, name, save_settings.off_options(name)) {
+                            Ok(count) => println!("Exported {} facets.", count),
+                            Err(err) => eprintln!("Facet export failed: {}", err),
+                        }
+                    }
+                }
+            }
+
+            // We want to write the symmetry group for a computer algebra
+            // system.
+            FileDialogMode::ExportSymmetry => {
+                if let Some(path) = file_dialog.save_group_file(file_dialog_state.unwrap_name()) {
+                    if let Some(p) = query.iter_mut().next() {
+                        match symmetry.symmetry_group(p.con()) {
+                            Some((group, vertex_map)) => {
+                                let format = GroupFormat::from_path(&path).unwrap_or(GroupFormat::Gap);
+                                if let Err(err) = std::fs::write(&path, group_to_string(group, &vertex_map, format)) {
+                                    eprintln!("Symmetry export failed: {}", err);
+                                }
+                            }
+                            None => eprintln!("Symmetry export failed: the symmetry group could not be computed"),
+                        }
+                    }
+                }
+            }
+
+            // We want to write the topological invariants as text.
+            FileDialogMode::ExportTopology => {
+                if let Some(path) = file_dialog.save_text_file(file_dialog_state.unwrap_name()) {
+                    if let Some(p) = query.iter_mut().next() {
+                        let report = topology_report(&p.abs.topology(), p.is_double_cover());
+                        if let Err(err) = std::fs::write(&path, report) {
+                            eprintln!("Topology export failed: {}", err);
+                        }
+                    }
+                }
+            }
+
+            // We want to write a representative of every edge and face orbit.
+            FileDialogMode::ExportOrbits => {
+                if let Some(path) = file_dialog.save_orbits_file(file_dialog_state.unwrap_name()) {
+                    if let Some(p) = query.iter_mut().next() {
+                        if let Err(err) = p.clone().orbits_to_path(&path) {
+                            eprintln!("Orbit export failed: {}", err);
+                        }
+                    }
+                }
+            }
+
+            // We want to save what's on screen, annotations included.
+            FileDialogMode::Screenshot => {
+                if let Some(path) = file_dialog.save_png_file(file_dialog_state.unwrap_name()) {
+                    commands
+                        .spawn(Screenshot::primary_window())
+                        .observe(save_to_disk(path));
+                }
+            }
+
             // There's nothing to do with the file dialog this frame.
             FileDialogMode::Disabled => {}
         }
@@ -320,13 +761,73 @@ pub type EguiWindows<'a> = (
     ResMut<'a, StarWindow>,
     ResMut<'a, CompoundWindow>), // Workaround for an argument count limit
     ResMut<'a, TruncateWindow>,
+    ResMut<'a, DepthTruncateWindow>,
+    ResMut<'a, ExpandWindow>,
     ResMut<'a, ScaleWindow>,
     ResMut<'a, FacetingSettings>,
     ResMut<'a, RotateWindow>,
     ResMut<'a, PlaneWindow>,
     ResMut<'a, TranslateWindow>,
+    ResMut<'a, LiftWindow>,
+    ResMut<'a, CollapseWindow>,
+    ResMut<'a, StellationWindow>,
+    (ResMut<'a, RandomHullWindow>, ResMut<'a, RandomOrbitsWindow>, ResMut<'a, SectionExplorer>, ResMut<'a, SubgroupBrowser>),
 );
 
+/// Other resources controlled by the top panel, grouped into a single
+/// parameter since systems can't take more than 16.
+#[derive(SystemParam)]
+pub struct PanelResources<'w> {
+    /// How the wireframe is colored.
+    wf_color_mode: ResMut<'w, WfColorMode>,
+
+    /// What each of the wireframe colors means.
+    wf_legend: Res<'w, WfLegend>,
+
+    /// The egui visuals.
+    visuals: ResMut<'w, CurrentVisuals>,
+
+    /// Whether coplanar faces are offset from each other.
+    offset_coplanar: ResMut<'w, OffsetCoplanarFaces>,
+
+    /// Whether face intersections are drawn.
+    show_intersections: ResMut<'w, ShowIntersections>,
+
+    /// Whether operations are previewed before being applied.
+    preview_operations: ResMut<'w, PreviewOperations>,
+
+    /// The operation being previewed.
+    preview: ResMut<'w, Preview>,
+
+    /// The faceting, and the symmetry group or product, running in the
+    /// background.
+    jobs: (ResMut<'w, FacetingJob>, ResMut<'w, BackgroundJob>),
+
+    /// Whether the performance HUD is shown.
+    show_performance: ResMut<'w, ShowPerformance>,
+
+    /// What clicking on the polytope selects.
+    selection_mode: ResMut<'w, SelectionMode>,
+
+    /// The frame coordinates are shown in.
+    coordinate_frame: ResMut<'w, CoordinateFrame>,
+
+    /// The lighting and shading settings.
+    render_settings: ResMut<'w, RenderSettings>,
+
+    /// The cached symmetry groups.
+    symmetry: ResMut<'w, SymmetryCache>,
+
+    /// The annotations shown on the viewport.
+    annotations: ResMut<'w, Annotations>,
+
+    /// The side-by-side view of memory slots.
+    compare_view: ResMut<'w, CompareView>,
+
+    /// The elements of Coxeter groups stored on disk.
+    group_cache: Res<'w, GroupCache>,
+}
+
 macro_rules! element_sort {
     ($p:ident) => {
         if !$p.abs().sorted() {
@@ -347,16 +848,19 @@ pub fn show_top_panel(
     mut section_state: ResMut<'_, SectionState>,
     mut section_direction: ResMut<'_, SectionDirectionVec>,
     mut file_dialog_state: ResMut<'_, FileDialogState>,
-    mut projection_type: ResMut<'_, ProjectionType>,
+    (mut projection_type, mut ortho_scale, mut cam_inputs): (
+        ResMut<'_, ProjectionType>,
+        ResMut<'_, OrthoScale>,
+        MessageWriter<'_, CameraInputEvent>,
+    ),
     mut poly_name: ResMut<'_, PolyName>,
     mut memory: ResMut<'_, Memory>,
     mut show_memory: ResMut<'_, ShowMemory>,
     mut show_help: ResMut<'_, ShowHelp>,
     mut export_memory: ResMut<'_, ExportMemory>,
     mut colors: (ResMut<'_, ClearColor>, ResMut<'_, MeshColor>, ResMut<'_, WfColor>),
-    mut slots_per_page: ResMut<'_, SlotsPerPage>,
-
-    mut visuals: ResMut<'_, CurrentVisuals>,
+    (mut slots_per_page, mut load_settings, mut save_settings, mut file_watcher, mut tiling): (ResMut<'_, SlotsPerPage>, ResMut<'_, LoadSettings>, ResMut<'_, SaveSettings>, ResMut<'_, FileWatcher>, ResMut<'_, CurrentTiling>),
+    panel_resources: PanelResources<'_>,
 
     // The different windows that can be shown.
     (
@@ -372,13 +876,21 @@ pub fn show_top_panel(
         mut star_window,
         mut compound_window),
         mut truncate_window,
+        mut depth_truncate_window,
+        mut expand_window,
         mut scale_window,
         mut faceting_settings,
         mut rotate_window,
         mut plane_window,
         mut translate_window,
+        mut lift_window,
+        mut collapse_window,
+        mut stellation_window,
+        (mut random_hull_window, mut random_orbits_window, mut section_explorer, mut subgroup_browser),
     ): EguiWindows<'_>,
 ) -> Result {
+    let PanelResources { mut wf_color_mode, wf_legend, mut visuals, mut offset_coplanar, mut show_intersections, mut preview_operations, mut preview, jobs: (mut faceting_job, mut background_job), mut show_performance, mut selection_mode, mut coordinate_frame, mut render_settings, mut symmetry, mut annotations, mut compare_view, group_cache } = panel_resources;
+
     // I think the problem may be on the very long closure in here. The clones are safe, so that can't be the source of the error
     let context = egui_ctx.ctx_mut()?;
     egui::TopBottomPanel::top("top_panel").show(&context.clone(), |ui| {
@@ -391,11 +903,75 @@ pub fn show_top_panel(
                     file_dialog_state.open();
                 }
 
+                // Reloads the file whenever it changes on disk.
+                ui.add_enabled(
+                    file_watcher.has_path(),
+                    egui::Checkbox::new(&mut file_watcher.enabled, "Watch file for changes"),
+                );
+
                 // Saves a file.
                 if ui.button("Save").clicked() {
                     file_dialog_state.save(poly_name.0.clone());
                 }
 
+                // How OFF files are written.
+                ui.menu_button("Save options", |ui| {
+                    ui.checkbox(&mut save_settings.comments, "Section comments");
+                    ui.checkbox(&mut save_settings.provenance, "Write polytope name");
+                    ui.checkbox(&mut save_settings.sort, "Canonical order");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut save_settings.round, "Round to");
+                        ui.add_enabled(
+                            save_settings.round,
+                            egui::DragValue::new(&mut save_settings.precision).range(0..=17),
+                        );
+                        ui.label("places");
+                    });
+                    ui.checkbox(&mut save_settings.windows_line_endings, "Windows line endings");
+                });
+
+                // Exports a POV-Ray scene from the current viewpoint.
+                if ui.button("Export POV-Ray scene").clicked() {
+                    file_dialog_state.export_pov(poly_name.0.clone());
+                }
+
+                // Exports the mesh and wireframe with their colors, e.g. for
+                // viewers on the web.
+                if ui.button("Export glTF scene").clicked() {
+                    file_dialog_state.export_gltf(poly_name.0.clone());
+                }
+
+                // Exports the triangulated mesh, e.g. for Blender.
+                if ui.button("Export OBJ mesh").clicked() {
+                    file_dialog_state.export_obj(poly_name.0.clone());
+                }
+
+                // Exports every facet into its own file.
+                if ui.button("Export facets").clicked() {
+                    file_dialog_state.export_facets(poly_name.0.clone());
+                }
+
+                // Exports the symmetry group, e.g. for GAP.
+                if ui.button("Export symmetry group").clicked() {
+                    file_dialog_state.export_symmetry(poly_name.0.clone());
+                }
+
+                // Exports one edge and one face of every orbit, e.g. to build
+                // models from.
+                if ui.button("Export edge and face orbits").clicked() {
+                    file_dialog_state.export_orbits(poly_name.0.clone());
+                }
+
+                // Exports the Euler characteristics, orientability and genus.
+                if ui.button("Export topology").clicked() {
+                    file_dialog_state.export_topology(poly_name.0.clone());
+                }
+
+                // Saves what's on screen, along with the annotations.
+                if ui.button("Save screenshot").clicked() {
+                    file_dialog_state.screenshot(poly_name.0.clone());
+                }
+
                 if ui.button("Export all memory slots").clicked() {
                     export_memory.0 = true;
                     export_memory.1 = 0;
@@ -403,6 +979,14 @@ pub fn show_top_panel(
 
                 ui.separator();
 
+                // Removes the Coxeter groups stored on disk.
+                if ui.button("Clear group cache").clicked() {
+                    match group_cache.clear() {
+                        Ok(()) => println!("Cleared the group cache."),
+                        Err(err) => eprintln!("Clearing the group cache failed: {}", err),
+                    }
+                }
+
                 // Quits the application.
                 if ui.button("Exit").clicked() {
                     std::process::exit(0);
@@ -445,6 +1029,150 @@ pub fn show_top_panel(
                         p.set_changed();
                     }
                 }
+
+                // Curves the edges of polytopes in 4D and up by projecting
+                // them through their circumsphere.
+                let mut stereographic = projection_type.is_stereographic();
+                if ui.checkbox(&mut stereographic, "Stereographic projection").clicked() {
+                    *projection_type = if stereographic {
+                        ProjectionType::Stereographic
+                    } else {
+                        ProjectionType::Perspective
+                    };
+
+                    // Forces an update on all polytopes.
+                    if let Some(mut p) = query.iter_mut().next() {
+                        p.set_changed();
+                    }
+                }
+
+                // The zoom in orthogonal mode.
+                ui.add_enabled_ui(checked, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut ortho_scale.0)
+                                .speed(0.01)
+                                .range(OrthoScale::MIN..=OrthoScale::MAX),
+                        );
+                        ui.label("Orthogonal scale");
+                    });
+                });
+
+                ui.separator();
+
+                // Moves the camera so that the polytope fits on screen.
+                if ui.button("Auto-fit to view").clicked() {
+                    if let Some(p) = query.iter_mut().next() {
+                        match CameraInputEvent::fit_polytope(&p, *projection_type) {
+                            Some(event) => {
+                                cam_inputs.write(event);
+                            }
+                            None => println!("There's nothing to fit to view."),
+                        }
+                    }
+                }
+
+                // Offsets faces on the same plane so that they don't flicker.
+                if ui.checkbox(&mut offset_coplanar.0, "Offset coplanar faces").clicked() {
+                    // Forces an update on all polytopes.
+                    if let Some(mut p) = query.iter_mut().next() {
+                        p.set_changed();
+                    }
+                }
+
+                // Draws the segments where faces cross each other.
+                if ui.checkbox(&mut show_intersections.0, "Show face intersections").clicked() {
+                    // Forces an update on all polytopes.
+                    if let Some(mut p) = query.iter_mut().next() {
+                        p.set_changed();
+                    }
+                }
+
+                // Lights the back of each face like its front.
+                ui.checkbox(&mut render_settings.two_sided_lighting, "Two-sided lighting");
+
+                // Hides the faces that point away from the camera.
+                ui.checkbox(&mut render_settings.backface_culling, "Backface culling");
+
+                // Shows the results of destructive operations before they're
+                // applied.
+                ui.checkbox(&mut preview_operations.0, "Preview operations");
+
+                // Shows how long frames and operations take.
+                ui.checkbox(&mut show_performance.0, "Show performance HUD");
+
+                // Lets the faces be picked with the left mouse button.
+                ui.checkbox(&mut selection_mode.0, "Face selection mode");
+
+                // Labels, arrows and a scale bar drawn over the viewport.
+                if ui.button("Annotations...").clicked() {
+                    annotations.open();
+                }
+
+                ui.separator();
+
+                // How to color the wireframe. We only write to the resource
+                // when the mode changes, since that rebuilds the wireframe.
+                ui.label("Wireframe colors:");
+                let mut mode = *wf_color_mode;
+                ui.radio_value(&mut mode, WfColorMode::Single, "Single color");
+                ui.radio_value(&mut mode, WfColorMode::Orbit, "By edge orbit");
+                ui.radio_value(&mut mode, WfColorMode::Length, "By edge length");
+                if mode != *wf_color_mode {
+                    *wf_color_mode = mode;
+                }
+
+                // Shows what each of the colors means.
+                for (color, label) in &wf_legend.0 {
+                    let [r, g, b, _a] = color.to_srgba().to_u8_array();
+                    ui.colored_label(egui::Color32::from_rgb(r, g, b), label);
+                }
+
+                ui.separator();
+
+                // How to shade the faces. Radial normals point away from the
+                // origin, which only looks right for star-shaped polytopes.
+                ui.label("Face normals:");
+                let mut normals = render_settings.normals;
+                ui.radio_value(&mut normals, NormalMode::Flat, "Flat");
+                ui.radio_value(&mut normals, NormalMode::Radial, "Radial");
+                if normals != render_settings.normals {
+                    render_settings.normals = normals;
+
+                    // Forces an update on all polytopes.
+                    if let Some(mut p) = query.iter_mut().next() {
+                        p.set_changed();
+                    }
+                }
+
+                ui.separator();
+
+                // What to do with polytopes loaded from files or the library.
+                ui.label("Recenter on load:");
+                ui.radio_value(&mut load_settings.recenter, AutoRecenter::None, "Don't recenter");
+                ui.radio_value(&mut load_settings.recenter, AutoRecenter::Gravicenter, "By gravicenter");
+                ui.radio_value(&mut load_settings.recenter, AutoRecenter::Circumcenter, "By circumcenter");
+
+                ui.label("Rescale on load:");
+                ui.radio_value(&mut load_settings.rescale, AutoRescale::None, "Don't rescale");
+                ui.radio_value(&mut load_settings.rescale, AutoRescale::UnitCircumradius, "To unit circumradius");
+                ui.radio_value(&mut load_settings.rescale, AutoRescale::UnitEdge, "To unit edge length");
+
+                ui.checkbox(&mut load_settings.as_abstract, "Load as abstract");
+
+                ui.separator();
+
+                ui.checkbox(&mut load_settings.weld, "Weld coincident vertices");
+                ui.add_enabled_ui(load_settings.weld, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut load_settings.weld_tolerance)
+                                .speed(1e-7)
+                                .range(0.0..=1.0),
+                        );
+                        ui.label("Tolerance");
+                    });
+                });
             });
 
             // Prints out properties about the loaded polytope.
@@ -476,6 +1204,13 @@ pub fn show_top_panel(
                     }
                 }
 
+                // Gets the Euler characteristics, orientability and genus.
+                if ui.button("Topology").clicked() {
+                    if let Some(p) = query.iter_mut().next() {
+                        print!("{}", topology_report(&p.abs.topology(), p.is_double_cover()));
+                    }
+                }
+
                 // Gets the volume of the polytope.
                 if ui.button("Volume").clicked() {
                     if let Some(mut p) = query.iter_mut().next() {
@@ -489,6 +1224,61 @@ pub fn show_top_panel(
                     }
                 }
 
+                // Gets the centroid and the principal moments of inertia.
+                if ui.button("Centroid and inertia").clicked() {
+                    if let Some(p) = query.iter_mut().next() {
+                        if let Some(properties) = p.mass_properties() {
+                            let (moments, axes) = properties.principal_axes();
+                            println!("The centroid is {}.", properties.centroid);
+                            println!("The principal moments of inertia are {:?}.", moments);
+                            println!("The principal axes are the columns of {}.", axes);
+                        } else {
+                            println!("The polytope has no volume.");
+                        }
+                    }
+                }
+
+                // Lists the facets through the gravicenter.
+                if ui.button("Hemi facets").clicked() {
+                    if let Some(p) = query.iter_mut().next() {
+                        let hemi_facets = p.hemi_facets();
+                        if hemi_facets.is_empty() {
+                            println!("The polytope is not a hemi-polytope.");
+                        } else {
+                            println!(
+                                "The polytope is a hemi-polytope, with {} facets through its center: {:?}.",
+                                hemi_facets.len(),
+                                hemi_facets
+                            );
+                        }
+                    }
+                }
+
+                // Determines which elements the symmetries act transitively
+                // on, and whether the polytope is regular, uniform, or noble.
+                if ui.button("Transitivity").clicked() {
+                    if let Some(p) = query.iter_mut().next() {
+                        match p.transitivity() {
+                            Some(t) => {
+                                let yes_no = |verdict: bool| if verdict { "yes" } else { "no" };
+                                println!(
+                                    "Regular: {}, uniform: {}, noble: {}.",
+                                    yes_no(t.regular),
+                                    yes_no(t.uniform),
+                                    yes_no(t.noble())
+                                );
+                                println!(
+                                    "Vertex-transitive: {}, edge-transitive: {}, facet-transitive: {}.",
+                                    yes_no(t.vertex),
+                                    yes_no(t.isotoxal()),
+                                    yes_no(t.facet)
+                                );
+                            }
+                            None => println!("The symmetry group could not be computed."),
+                        }
+                    }
+                }
+
                 // Gets the number of flags of the polytope.
                 if ui.button("Flag count").clicked() {
                     if let Some(p) = query.iter_mut().next() {
@@ -496,22 +1286,70 @@ pub fn show_top_panel(
                     }
                 }
 
-                // Gets the order of the symmetry group of the polytope.
-                if advanced(&keyboard) {
-                    if ui.button("Rotation symmetry group").clicked() {
+                // Opens a window to browse the sections between pairs of
+                // elements.
+                if ui.button("Section explorer...").clicked() {
+                    if let Some(p) = query.iter_mut().next() {
+                        section_explorer.open(&p);
+                    }
+                }
+
+                ui.menu_button("Symmetry", |ui| {
+                    // Gets the order of the symmetry group of the polytope.
+                    // The group is computed in the background if it's not
+                    // cached, so that it can be cancelled.
+                    if advanced(&keyboard) {
+                        if ui.add_enabled(!background_job.is_running(), egui::Button::new("Rotation symmetry group")).clicked() {
+                            if let Some(p) = query.iter().next() {
+                                background_job.report_symmetry(&p, &mut symmetry, SymmetryReport::RotationOrder);
+                            }
+                        }
+                    } else {
+                        if ui.add_enabled(!background_job.is_running(), egui::Button::new("Symmetry group")).clicked() {
+                            if let Some(p) = query.iter().next() {
+                                background_job.report_symmetry(&p, &mut symmetry, SymmetryReport::Order);
+                            }
+                        }
+                    }
+
+                    // Determines whether the polytope is chiral, by comparing
+                    // the orders of its symmetry and rotation groups.
+                    if ui.add_enabled(!background_job.is_running(), egui::Button::new("Chirality")).clicked() {
+                        if let Some(p) = query.iter().next() {
+                            background_job.report_symmetry(&p, &mut symmetry, SymmetryReport::Chirality);
+                        }
+                    }
+
+                    // Looks for translational symmetry, as in a patch of a
+                    // tiling.
+                    if ui.button("Periodicity").clicked() {
+                        if let Some(p) = query.iter_mut().next() {
+                            let periodicity = p.periodicity();
+                            println!("Periodicity: {}.", periodicity);
+                            for period in &periodicity.basis {
+                                println!("Period: {}", period.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", "));
+                            }
+                        }
+                    }
+
+                    ui.separator();
+
+                    // Reflects the polytope into its mirror image.
+                    if ui.button("Create enantiomorph").clicked() {
                         if let Some(mut p) = query.iter_mut().next() {
-                            let group = p.get_rotation_group().unwrap().0;
-                            println!("Rotation symmetry order {}", group.count());
+                            p.enantiomorph_mut();
+                            poly_name.0 = format!("Enantiomorph of {}", poly_name.0);
                         }
                     }
-                } else {
-                    if ui.button("Symmetry group").clicked() {
+
+                    // Compounds the polytope with its mirror image.
+                    if ui.button("Compound with enantiomorph").clicked() {
                         if let Some(mut p) = query.iter_mut().next() {
-                            let group = p.get_symmetry_group().unwrap().0;
-                            println!("Symmetry order {}", group.count());
+                            *p = p.enantiomorph_compound();
+                            poly_name.0 = format!("Compound of {} and its enantiomorph", poly_name.0);
                         }
                     }
-                }
+                });
                 
                 // Gets if it is a compound.
                 if ui.button("Is compound").clicked() {
@@ -581,6 +1419,29 @@ pub fn show_top_panel(
                 
                 ui.separator();
                 
+                // Aligns the principal axes of a polytope with the coordinate axes.
+                if ui.button("Align principal axes").clicked() {
+                    if !query.iter_mut().next().unwrap().align_principal_axes() {
+                        println!("The polytope has no volume.");
+                    }
+                }
+
+                // Puts the main rotation axis of a polyhedron along the z-axis.
+                if ui.button("Standard orientation").clicked() {
+                    if let Some(mut p) = query.iter_mut().next() {
+                        if p.standard_orientation() {
+                            poly_name.0 = format!("Rotated {}", poly_name.0);
+                        } else {
+                            println!("The polytope has no symmetry axes or volume.");
+                        }
+                    }
+                }
+
+                // Shows the coordinates in a frame adapted to the symmetry.
+                if ui.button("Coordinate frame...").clicked() {
+                    coordinate_frame.open();
+                }
+
                 //Translates a polytope by a vector.
                 if ui.button("Translate...").clicked() {
                     translate_window.open();
@@ -595,9 +1456,39 @@ pub fn show_top_panel(
                 if ui.button("Rotate with plane...").clicked() {
                     plane_window.open();
                 }
+
+                ui.separator();
+
+                // Places a polytope in one more dimension, at some offset
+                // along the new axis.
+                if ui.button("Lift into next dimension...").clicked() {
+                    lift_window.open();
+                }
+
+                // Removes the coordinates that are zero for every vertex.
+                if ui.button("Drop trailing zero coordinates").clicked() {
+                    let dropped = query.iter_mut().next().unwrap().drop_zero_coords();
+                    if dropped == 0 {
+                        println!("The polytope has no trailing zero coordinates.");
+                    }
+                }
                 
             });
 
+            // Builds new polytopes.
+            ui.menu_button("Generate", |ui| {
+                // Random polytopes, mostly to stress-test the other operations.
+                ui.menu_button("Random", |ui| {
+                    if ui.button("Convex hull...").clicked() {
+                        random_hull_window.open();
+                    }
+
+                    if ui.button("Symmetric hull...").clicked() {
+                        random_orbits_window.open();
+                    }
+                });
+            });
+
             // Operations on polytopes.
             ui.menu_button("Operations", |ui| {
                 // Converts the active polytope into its dual.
@@ -607,12 +1498,20 @@ pub fn show_top_panel(
                     }
                 } else if let Some(mut p) = query.iter_mut().next() {
                     if ui.button("Dual").clicked() {
-                        match p.try_dual_mut() {
-                            Ok(_) => {
-                                poly_name.0 = format!("Dual of {}", poly_name.0);
-                                println!("Dual succeeded.")
-                            },
-                            Err(err) => eprintln!("Dual failed: {}", err),
+                        // Computes the dual on a copy, to be applied from the preview.
+                        if preview_operations.0 {
+                            match p.try_dual() {
+                                Ok(q) => preview.set(q, format!("Dual of {}", poly_name.0)),
+                                Err(err) => eprintln!("Dual failed: {}", err),
+                            }
+                        } else {
+                            match p.try_dual_mut() {
+                                Ok(_) => {
+                                    poly_name.0 = format!("Dual of {}", poly_name.0);
+                                    println!("Dual succeeded.")
+                                },
+                                Err(err) => eprintln!("Dual failed: {}", err),
+                            }
                         }
                     }
                 }
@@ -634,9 +1533,7 @@ pub fn show_top_panel(
                 // Converts the active polytope into its Petrie polygon.
                 if ui.button("Petrie polygon").clicked() {
                     if let Some(mut p) = query.iter_mut().next() {
-                        p.element_sort();
-                        let flag = p.first_flag();
-                        match p.petrie_polygon_with(flag) {
+                        match p.petrie_polygon() {
                             Some(q) => {
                                 *p = q;
                                 poly_name.0 = format!("Petrie polygon of {}", poly_name.0);
@@ -719,7 +1616,33 @@ pub fn show_top_panel(
                         println!("Hosotope succeeded!");
                     }
                 }
-                
+
+                ui.separator();
+
+                // Compounds the active polytope with its point reflection.
+                if ui.button("Point-reflect and compound").clicked() {
+                    if let Some(mut p) = query.iter_mut().next() {
+                        *p = p.reflection_compound();
+                        poly_name.0 = format!("Compound of {} and its reflection", poly_name.0);
+                    }
+                }
+
+                // Makes a prism between the active polytope and its reflection.
+                if ui.button("Prism with reflection").clicked() {
+                    if let Some(mut p) = query.iter_mut().next() {
+                        *p = p.reflection_prism();
+                        poly_name.0 = format!("Reflection prism of {}", poly_name.0);
+                    }
+                }
+
+                // Makes a tegum between the active polytope and its reflection.
+                if ui.button("Tegum with reflection").clicked() {
+                    if let Some(mut p) = query.iter_mut().next() {
+                        *p = p.reflection_tegum();
+                        poly_name.0 = format!("Reflection tegum of {}", poly_name.0);
+                    }
+                }
+
                 ui.separator();
 
                 // Opens the window to make duopyramids.
@@ -757,6 +1680,24 @@ pub fn show_top_panel(
                 if ui.button("Truncate...").clicked() {
                     truncate_window.open();
                 }
+
+                // Opens the window to cut off the vertices up to some depth.
+                if ui.button("Truncate by depth...").clicked() {
+                    depth_truncate_window.open();
+                }
+
+                // Places a vertex on every flag of the polytope.
+                if ui.button("Omnitruncate").clicked() {
+                    if let Some(mut p) = query.iter_mut().next() {
+                        *p = p.omnitruncate();
+                        poly_name.0 = format!("Omnitruncated {}", poly_name.0);
+                    }
+                }
+
+                // Opens the window to move the facets along their normals.
+                if ui.button("Expand...").clicked() {
+                    expand_window.open();
+                }
                 
                 ui.separator();
 
@@ -766,6 +1707,156 @@ pub fn show_top_panel(
                         println!("Fuse succeeded!");
                     }
                 }
+
+                // Opens the window to collapse the edges shorter than some
+                // tolerance.
+                if ui.button("Collapse short edges...").clicked() {
+                    collapse_window.open();
+                }
+
+                // Keeps only the visible surface of a self-intersecting
+                // polyhedron, as a physical model would look like.
+                if ui.button("Outer shell").clicked() {
+                    if let Some(mut p) = query.iter_mut().next() {
+                        match p.outer_shell() {
+                            Some(shell) => match shell.abs.ranks().is_valid() {
+                                Ok(()) => {
+                                    *p = shell;
+                                    poly_name.0 = format!("Outer shell of {}", poly_name.0);
+                                }
+                                Err(err) => eprintln!("Outer shell failed: {}", err),
+                            },
+                            None => eprintln!("Outer shell failed: the polytope must be a 3D polyhedron."),
+                        }
+                    }
+                }
+
+                // Opens the stellation diagram of the polyhedron, to pick the
+                // cells of a stellation from.
+                if ui.button("Stellate...").clicked() {
+                    if let Some(p) = query.iter_mut().next() {
+                        if !stellation_window.open(&p, &mut symmetry, &poly_name.0) {
+                            eprintln!("Stellation failed: the polytope must be a 3D polyhedron.");
+                        }
+                    }
+                }
+
+                ui.separator();
+
+                // Operations on the tiling the polytope is a patch of, which
+                // work on a single copy of each of its elements.
+                ui.add_enabled_ui(tiling.get().is_some(), |ui| {
+                    ui.menu_button("Tiling", |ui| {
+                        if ui.button("Dual").clicked() {
+                            if let Some(t) = tiling.get() {
+                                let dual = t.dual();
+                                tiling.set(dual);
+                                poly_name.0 = format!("Dual of {}", poly_name.0);
+                            }
+                        }
+
+                        if ui.button("Rectify").clicked() {
+                            if let Some(t) = tiling.get() {
+                                let rectified = t.truncate(1.0);
+                                tiling.set(rectified);
+                                poly_name.0 = format!("Rectified {}", poly_name.0);
+                            }
+                        }
+
+                        ui.horizontal(|ui| {
+                            if ui.button("Truncate").clicked() {
+                                if let Some(t) = tiling.get() {
+                                    let truncated = t.truncate(tiling.depth);
+                                    tiling.set(truncated);
+                                    poly_name.0 = format!("Truncated {}", poly_name.0);
+                                }
+                            }
+
+                            ui.add(egui::DragValue::new(&mut tiling.depth).speed(0.01).range(0.0..=1.0));
+                        });
+                    });
+                });
+
+                ui.separator();
+
+                // Operations that only use the abstract structure of the
+                // polytope. The results get a skeleton embedding.
+                ui.menu_button("Abstract", |ui| {
+                    if ui.button("Forget coordinates").clicked() {
+                        if let Some(mut p) = query.iter_mut().next() {
+                            *p = Concrete::from_abstract(p.abs.clone());
+                            poly_name.0 = format!("Abstract {}", poly_name.0);
+                        }
+                    }
+
+                    if ui.button("Embed on sphere").clicked() {
+                        if let Some(mut p) = query.iter_mut().next() {
+                            let options = EmbeddingOptions {
+                                dim: p.dim_or().max(3),
+                                on_sphere: true,
+                                ..Default::default()
+                            };
+                            *p = Concrete::from_abstract_with(p.abs.clone(), &options);
+                        }
+                    }
+
+                    if ui.button("Abstract dual").clicked() {
+                        if let Some(mut p) = query.iter_mut().next() {
+                            let mut abs = p.abs.clone();
+                            abs.dual_mut();
+                            *p = Concrete::from_abstract(abs);
+                            poly_name.0 = format!("Abstract dual of {}", poly_name.0);
+                        }
+                    }
+
+                    if ui.button("Abstract rectification (medial)").clicked() {
+                        if let Some(mut p) = query.iter_mut().next() {
+                            match p.abs.rectify() {
+                                Some(abs) => {
+                                    *p = Concrete::from_abstract(abs);
+                                    poly_name.0 = format!("Abstract rectified {}", poly_name.0);
+                                }
+                                None => eprintln!("Rectification failed: the polytope has no edges."),
+                            }
+                        }
+                    }
+
+                    if ui.button("Abstract truncation").clicked() {
+                        if let Some(mut p) = query.iter_mut().next() {
+                            match p.abs.truncate() {
+                                Some(abs) => {
+                                    *p = Concrete::from_abstract(abs);
+                                    poly_name.0 = format!("Abstract truncated {}", poly_name.0);
+                                }
+                                None => eprintln!("Truncation failed: the polytope has no edges."),
+                            }
+                        }
+                    }
+
+                    if ui.button("Abstract Petrie contraction").clicked() {
+                        if let Some(mut p) = query.iter_mut().next() {
+                            match p.abs.petrie_contract() {
+                                Some(abs) => {
+                                    *p = Concrete::from_abstract(abs);
+                                    poly_name.0 = format!("Petrie contracted {}", poly_name.0);
+                                }
+                                None => eprintln!("Petrie contraction failed: the polytope must be a polyhedron with a valid Petrial."),
+                            }
+                        }
+                    }
+
+                    if ui.button("Abstract Petrial").clicked() {
+                        if let Some(mut p) = query.iter_mut().next() {
+                            let mut abs = p.abs.clone();
+                            if abs.petrial_mut() {
+                                *p = Concrete::from_abstract(abs);
+                                poly_name.0 = format!("Abstract Petrial of {}", poly_name.0);
+                            } else {
+                                eprintln!("Petrial failed.");
+                            }
+                        }
+                    }
+                });
             });
 
             // Toggles cross-section mode.
@@ -810,47 +1901,89 @@ pub fn show_top_panel(
             }
 
             ui.menu_button("Faceting", |ui| {
-                if ui.button("Enumerate facetings").clicked() {
+                if ui.add_enabled(!faceting_job.is_running(), egui::Button::new("Enumerate facetings")).clicked() {
                     if let Some(p) = query.iter_mut().next() {
-                        let mut vertices_thing = (Vertices(vec![]), vec![]);
-                        if let GroupEnum2::FromSlot(slot) = faceting_settings.group {
-                            vertices_thing = Vertices(p.vertices.clone()).copy_by_symmetry(slot.to_poly(&mut memory, &p).unwrap().clone().get_symmetry_group().unwrap().0);
-                        }
-                        let facetings = p.clone().faceting(
-                            match faceting_settings.group {
-                                GroupEnum2::Chiral(_) => p.vertices.clone(),
-                                GroupEnum2::FromSlot(_) => vertices_thing.0.0
-                            },
-                            match faceting_settings.group {
-                                GroupEnum2::Chiral(chiral) => GroupEnum::Chiral(chiral),
-                                GroupEnum2::FromSlot(_) => GroupEnum::VertexMap(vertices_thing.1)
+                        let mut vertices_thing = (Vertices(vec![]), miratope_core::group::vertex_map::VertexMap::default());
+                        let mut diagram_group = true;
+                        match faceting_settings.group {
+                            GroupEnum2::FromSlot(slot) => {
+                                vertices_thing = Vertices(p.vertices.clone()).copy_by_symmetry(slot.to_poly(&mut memory, &p).unwrap().clone().get_symmetry_group().unwrap().0);
+                            }
+                            // The elements of the group are read from disk if
+                            // it was generated before.
+                            GroupEnum2::Diagram => match Cox::parse(&faceting_settings.diagram).map_err(CoxError::from).and_then(|cox| Ok((cox.dim(), group_cache.try_group(&cox)?))) {
+                                Ok((dim, group)) if dim == p.dim_or() => {
+                                    vertices_thing = Vertices(p.vertices.clone()).copy_by_symmetry(group.cache());
+                                }
+                                Ok((dim, _)) => {
+                                    println!("The group acts on {} dimensions, but the polytope has {}.", dim, p.dim_or());
+                                    diagram_group = false;
+                                }
+                                Err(err) => {
+                                    println!("The group could not be generated: {}", err);
+                                    diagram_group = false;
+                                }
                             },
-                            faceting_settings.any_single_edge_length,
-                            if faceting_settings.do_min_edge_length {Some(faceting_settings.min_edge_length)} else {None}, 
-                            if faceting_settings.do_max_edge_length {Some(faceting_settings.max_edge_length)} else {None}, 
-                            if faceting_settings.do_min_inradius {Some(faceting_settings.min_inradius)} else {None}, 
-                            if faceting_settings.do_max_inradius {Some(faceting_settings.max_inradius)} else {None}, 
-                            faceting_settings.exclude_hemis,
-                            faceting_settings.only_below_vertex,
-                            if faceting_settings.max_facet_types == 0 {None} else {Some(faceting_settings.max_facet_types)},
-                            if faceting_settings.max_per_hyperplane == 0 {None} else {Some(faceting_settings.max_per_hyperplane)},
-                            faceting_settings.uniform,
-                            faceting_settings.compounds,
-                            faceting_settings.mark_fissary,
-                            faceting_settings.label_facets,
-                            faceting_settings.save,
-                            faceting_settings.save_facets,
-                            faceting_settings.save_to_file,
-                            faceting_settings.file_path.clone(),
-                        );
-                        for faceting in facetings {
-                            memory.push(faceting);
+                            _ => {}
+                        }
+                        let vertices = match faceting_settings.group {
+                            GroupEnum2::Chiral(_) | GroupEnum2::Patch => p.vertices.clone(),
+                            GroupEnum2::FromSlot(_) | GroupEnum2::Diagram => vertices_thing.0.0
+                        };
+                        let group = match faceting_settings.group {
+                            GroupEnum2::Chiral(chiral) => Some(GroupEnum::Chiral(chiral)),
+                            GroupEnum2::Patch => p.patch_symmetry_group().map(|(_, vertex_map)| GroupEnum::VertexMap(vertex_map)),
+                            GroupEnum2::FromSlot(_) => Some(GroupEnum::VertexMap(vertices_thing.1)),
+                            GroupEnum2::Diagram => diagram_group.then_some(GroupEnum::VertexMap(vertices_thing.1)),
+                        };
+
+                        match group {
+                            Some(group) => faceting_job.start_with_settings(&p, vertices, group, &faceting_settings),
+                            None => println!("The symmetry group could not be computed."),
                         }
                     }
                 }
                 
+                // Places a faceting from memory on the vertices of the loaded
+                // polytope, without enumerating the facetings again.
+                let mut signature = None;
+                ui.menu_button("Re-instantiate faceting", |ui| {
+                    for (slot_idx, (faceting, label)) in memory
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(idx, s)| s.as_ref().map(|s| (idx, s)))
+                    {
+                        let label = label.clone().unwrap_or_else(|| slot_label(slot_idx));
+                        if ui.button(&label).clicked() {
+                            signature = Some((FacetingSignature::new(faceting), label));
+                        }
+                    }
+                });
+
+                if let Some((signature, label)) = signature
+                    && let Some(p) = query.iter().next()
+                {
+                    let group = match faceting_settings.group {
+                        GroupEnum2::Chiral(true) => p.clone().get_rotation_group(),
+                        GroupEnum2::Patch => p.patch_symmetry_group(),
+                        _ => symmetry.symmetry_group(p),
+                    };
+
+                    match group.map(|(_, vertex_map)| signature.instantiate(&p.vertices, &vertex_map)) {
+                        Some(Ok(poly)) => memory.push((poly, Some(format!("{} (re-instantiated)", label)))),
+                        Some(Err(err)) => eprintln!("Re-instantiating faceting failed: {}", err),
+                        None => eprintln!("Re-instantiating faceting failed: no symmetry group"),
+                    }
+                }
+
                 ui.separator();
 
+                // Opens a window to pick a subgroup of the symmetry group to
+                // facet under.
+                if ui.button("Subgroups...").clicked() {
+                    subgroup_browser.open();
+                }
+
                 if ui.button("Settings...").clicked() {
                     faceting_settings.open();
                 }
@@ -859,7 +1992,7 @@ pub fn show_top_panel(
             if ui.button("Memory").clicked() {
                 show_memory.0 = !show_memory.0;
             }
-            memory.show(&mut query, &mut poly_name, &mut slots_per_page, &mut context.clone(), &mut show_memory.0).unwrap();
+            memory.show(&mut query, &mut poly_name, &mut preview, &mut compare_view, &mut file_dialog_state, &mut slots_per_page, &mut context.clone(), &mut show_memory.0).unwrap();
 
             if ui.button("Help").clicked() {
                 show_help.0 = !show_help.0;
@@ -872,7 +2005,7 @@ pub fn show_top_panel(
                     ui.label("V: toggle faces\nB: toggle wireframe");
                     ui.separator();
                     ui.heading("Camera");
-                    ui.label("WSADRF: move\nQE: roll\nX: reset\nMouse wheel: zoom\nHold Ctrl: move faster\nHold Shift: move slower");
+                    ui.label("WSADRF: move\nQE: roll\nX: reset\nZ: auto-fit to view\nMouse wheel: zoom\nHold Ctrl: move faster\nHold Shift: move slower");
                     ui.separator();
                     ui.heading("UI");
                     ui.label("Hold Ctrl: extra options in some menus\nHold Shift: move number sliders slower");
@@ -955,6 +2088,9 @@ pub fn show_top_panel(
             if let Some(new_visuals) = light_dark_small_toggle_button(&visuals.0, ui) {
                 *visuals = CurrentVisuals(new_visuals);
             }
+
+            // Tells whether the loaded file changed on disk.
+            file_watcher.show_badge(ui);
         });
 
         // Shows secondary views below the menu bar.
@@ -988,6 +2124,88 @@ pub fn light_dark_small_toggle_button(visuals: &Visuals, ui: &mut Ui) -> Option<
     None
 }
 
+/// Moves the first slicing hyperplane of the cross-section view along with the
+/// camera, as long as it's set to follow it. The mouse wheel and the Page Up
+/// and Page Down keys then move the hyperplane back and forth.
+fn slice_with_camera(
+    mut section_state: ResMut<'_, SectionState>,
+    mut section_direction: ResMut<'_, SectionDirectionVec>,
+    cameras: Query<'_, '_, &GlobalTransform, With<Camera>>,
+    keyboard: Res<'_, ButtonInput<KeyCode>>,
+    mut mouse_wheel: MessageReader<'_, '_, MouseWheel>,
+    time: Res<'_, Time>,
+    mut egui_ctx: EguiContexts<'_, '_>,
+) -> Result {
+    /// How far a line of scrolling moves the hyperplane, as a fraction of the
+    /// width of the polytope.
+    const SCROLL_STEP: Float = 0.02;
+
+    /// How far the keys move the hyperplane in a second, as a fraction of the
+    /// width of the polytope.
+    const KEY_SPEED: Float = 0.25;
+
+    /// How much the camera must turn before the cross-section is updated.
+    const DIRECTION_TOL: Float = 1e-3;
+
+    let scroll: f32 = mouse_wheel
+        .read()
+        .map(|MouseWheel { unit, y, .. }| match unit {
+            MouseScrollUnit::Line => *y,
+            MouseScrollUnit::Pixel => y / 12.,
+        })
+        .sum();
+
+    let SectionState::Active { original_polytope, hyperplane_pos, follow, .. } = section_state.as_ref() else {
+        return Ok(());
+    };
+    let (Some(current), Some(camera)) = (section_direction.0.first(), cameras.iter().next()) else {
+        return Ok(());
+    };
+    let Some(direction) = follow.direction(current.0.len(), camera.forward().as_vec3()) else {
+        return Ok(());
+    };
+    let turned = (&direction - &current.0).norm() > DIRECTION_TOL;
+
+    // Scrolling or typing into the UI shouldn't move the slice.
+    let ctx = egui_ctx.ctx_mut()?;
+    let mut delta = 0.0;
+    if !ctx.wants_pointer_input() && !(keyboard.pressed(KeyCode::AltLeft) || keyboard.pressed(KeyCode::AltRight)) {
+        delta += scroll as Float * SCROLL_STEP;
+    }
+    if !ctx.wants_keyboard_input() {
+        let dt = time.delta_secs() as Float;
+        if keyboard.pressed(KeyCode::PageUp) {
+            delta += KEY_SPEED * dt;
+        }
+        if keyboard.pressed(KeyCode::PageDown) {
+            delta -= KEY_SPEED * dt;
+        }
+    }
+
+    // We only write into the resources when something changes, as this
+    // recomputes the cross-section.
+    if !turned && delta == 0.0 {
+        return Ok(());
+    }
+
+    let Some((min, max)) = original_polytope.minmax(direction.clone()) else {
+        return Ok(());
+    };
+    // We stay clear of the ends to avoid empty slices.
+    let pos = (hyperplane_pos[0] + delta * (max - min))
+        .min(max - 0.0000001)
+        .max(min + 0.0000001);
+
+    if let SectionState::Active { hyperplane_pos, .. } = section_state.as_mut() {
+        hyperplane_pos[0] = pos;
+    }
+    if turned {
+        section_direction.0[0].0 = direction;
+    }
+
+    Ok(())
+}
+
 /// Shows any secondary views that are active. Currently, just shows the
 /// cross-section view.
 fn show_views(
@@ -1003,6 +2221,7 @@ fn show_views(
         hyperplane_pos,
         flatten,
         lock,
+        follow,
         ..
     } = (*section_state).clone()
     {
@@ -1050,6 +2269,39 @@ fn show_views(
             i = i + 1;
         }
 
+        // Makes the first slicing hyperplane follow the camera.
+        ui.horizontal(|ui| {
+            let dim = section_direction.0.first().map_or(0, |direction| direction.0.len());
+            let mut new_follow = follow;
+            egui::ComboBox::from_label("Follow camera")
+                .selected_text(new_follow.label())
+                .show_ui(ui, |ui| {
+                    for mode in [SectionFollow::Off, SectionFollow::View]
+                        .into_iter()
+                        .chain((3..dim).map(SectionFollow::Axis))
+                    {
+                        ui.selectable_value(&mut new_follow, mode, mode.label());
+                    }
+                });
+
+            if new_follow != SectionFollow::Off {
+                ui.label("Scroll to move the slice, or hold Alt to zoom.");
+            }
+
+            // Updates the follow mode. A slice perpendicular to the view is
+            // best seen without flattening, so that it stays in place.
+            if follow != new_follow {
+                if let SectionState::Active { follow, flatten, .. } = section_state.as_mut() {
+                    *follow = new_follow;
+                    if new_follow == SectionFollow::View {
+                        *flatten = false;
+                    }
+                } else {
+                    unreachable!()
+                }
+            }
+        });
+
         ui.horizontal(|ui| {
             // Makes the current cross-section into the main polytope.
             if ui.button("Make main").clicked() {
@@ -1125,6 +2377,7 @@ fn show_views(
             flatten,
             lock,
             update,
+            ..
         } = section_state.as_mut() {
             *update = false;
 
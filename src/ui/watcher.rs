@@ -0,0 +1,179 @@
+//! Watches the file the polytope was loaded from, and reloads it whenever it
+//! changes on disk. This lets the coordinates be generated by an external
+//! script while the polytope is being viewed.
+//!
+//! The file is polled every so often, rather than subscribing to file system
+//! events, so that this works the same on every platform. Once the polytope
+//! is replaced in any other way, the file no longer describes it, so we stop
+//! watching it.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use super::config::LoadSettings;
+use crate::Concrete;
+use miratope_core::file::FromFile;
+
+use bevy::prelude::*;
+use bevy_egui::egui::Ui;
+
+/// How often the file is checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long the badge is shown after the file is reloaded.
+const BADGE_DURATION: Duration = Duration::from_secs(3);
+
+/// The plugin in charge of reloading the file.
+pub struct WatcherPlugin;
+
+impl Plugin for WatcherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FileWatcher>()
+            .add_systems(Update, poll_file);
+    }
+}
+
+/// Returns the time a file was last modified, if it can be read.
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).ok()?.modified().ok()
+}
+
+/// The state of the file watcher.
+#[derive(Default, Resource)]
+pub struct FileWatcher {
+    /// Whether the polytope is reloaded as soon as its file changes.
+    pub enabled: bool,
+
+    /// The file the polytope was last loaded from.
+    path: Option<PathBuf>,
+
+    /// When the file was last modified, the last time we checked.
+    modified: Option<SystemTime>,
+
+    /// When we last checked the file.
+    last_poll: Option<Instant>,
+
+    /// Whether the file changed on disk without being reloaded.
+    changed: bool,
+
+    /// Whether the user asked to reload the file.
+    reload_requested: bool,
+
+    /// When the file was last reloaded.
+    reloaded: Option<Instant>,
+
+    /// Whether the polytope was just loaded from the file, so that we don't
+    /// mistake this for it being replaced.
+    just_loaded: bool,
+}
+
+impl FileWatcher {
+    /// Starts watching the file a polytope was just loaded from.
+    pub fn watch(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        self.modified = modified(&path);
+        self.path = Some(path);
+        self.changed = false;
+        self.reload_requested = false;
+        self.reloaded = None;
+        self.just_loaded = true;
+    }
+
+    /// Stops watching the file.
+    pub fn unwatch(&mut self) {
+        *self = Self {
+            enabled: self.enabled,
+            ..Default::default()
+        };
+    }
+
+    /// Notes that the polytope was just saved into some path, so that we
+    /// don't mistake our own changes to the file for someone else's.
+    pub fn saved(&mut self, path: &Path) {
+        if self.path.as_deref() == Some(path) {
+            self.modified = modified(path);
+            self.changed = false;
+        }
+    }
+
+    /// Returns whether there's a file to watch.
+    pub fn has_path(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Shows a badge telling whether the file changed on disk or was just
+    /// reloaded. If it changed without being reloaded, clicking on the badge
+    /// reloads it.
+    pub fn show_badge(&mut self, ui: &mut Ui) {
+        if self.changed {
+            if ui
+                .button("● File changed")
+                .on_hover_text("The file changed on disk. Click to reload it.")
+                .clicked()
+            {
+                self.reload_requested = true;
+            }
+        } else if self.reloaded.is_some_and(|time| time.elapsed() < BADGE_DURATION) {
+            ui.label("⟳ Reloaded");
+        }
+    }
+}
+
+/// Checks whether the file changed, and reloads it if needed.
+fn poll_file(
+    mut watcher: ResMut<'_, FileWatcher>,
+    mut query: Query<'_, '_, &mut Concrete>,
+    load_settings: Res<'_, LoadSettings>,
+) {
+    let Some(path) = watcher.path.clone() else {
+        return;
+    };
+
+    // A system never sees its own changes, so this only happens when
+    // something else changed the polytope.
+    if query.iter_mut().next().is_some_and(|p| p.is_changed()) && !std::mem::take(&mut watcher.just_loaded) {
+        watcher.unwatch();
+        return;
+    }
+
+    if !watcher.reload_requested {
+        if watcher.last_poll.is_some_and(|time| time.elapsed() < POLL_INTERVAL) {
+            return;
+        }
+        watcher.last_poll = Some(Instant::now());
+
+        // The file might have been deleted, or be in the middle of being
+        // rewritten.
+        let new_modified = modified(&path);
+        if new_modified.is_none() || new_modified == watcher.modified {
+            return;
+        }
+        watcher.modified = new_modified;
+
+        if !watcher.enabled {
+            watcher.changed = true;
+            return;
+        }
+    }
+
+    watcher.reload_requested = false;
+    match Concrete::from_path(&path) {
+        Ok(mut q) => {
+            load_settings.apply(&mut q);
+            if let Some(mut p) = query.iter_mut().next() {
+                *p = q;
+            }
+
+            watcher.changed = false;
+            watcher.reloaded = Some(Instant::now());
+        }
+
+        // The file might be only partly written, so we let the user retry.
+        Err(err) => {
+            eprintln!("File reload failed: {}", err);
+            watcher.changed = true;
+        }
+    }
+}
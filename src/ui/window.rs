@@ -6,12 +6,17 @@
 use std::marker::PhantomData;
 
 use super::{
+    cd_editor::CdEditor,
+    config::{Config, PreviewOperations},
+    jobs::{BackgroundJob, Product},
     memory::{slot_label, Memory},
+    perf::PerfStats,
+    preview::Preview,
     PointWidget,
 };
 use crate::{Concrete, Float, Hypersphere, Point, ui::main_window::PolyName};
 
-use miratope_core::{conc::ConcretePolytope, Polytope, abs::Ranked};
+use miratope_core::{abs::product::ProductType, conc::{faceting::{FacetFilter, FacetingOptions, Precision}, symmetry::SymmetryCache, ConcretePolytope}, cox::{cd::Cd, CoxType}, group::disk_cache::GroupCache, Polytope, abs::Ranked};
 
 use bevy::prelude::*;
 use bevy_egui::{egui::{self, Context, Layout, Ui, Widget, Align}, EguiContexts, EguiPrimaryContextPass};
@@ -40,24 +45,37 @@ pub struct WindowPlugin;
 impl Plugin for WindowPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
-            DualWindow::plugin(),
-            PyramidWindow::plugin(),
-            PrismWindow::plugin(),
-            TegumWindow::plugin(),
-            AntiprismWindow::plugin(),
-            DuopyramidWindow::plugin(),
-            DuoprismWindow::plugin(),
-            DuotegumWindow::plugin(),
-            DuocombWindow::plugin(),
-            StarWindow::plugin(),
-            CompoundWindow::plugin(),
-            TruncateWindow::plugin(),
-            ScaleWindow::plugin(),
-            FacetingSettings::plugin(),
-            RotateWindow::plugin()))
+            (
+                DualWindow::plugin(),
+                PyramidWindow::plugin(),
+                PrismWindow::plugin(),
+                TegumWindow::plugin(),
+                AntiprismWindow::plugin(),
+                DuopyramidWindow::plugin(),
+                DuoprismWindow::plugin(),
+                DuotegumWindow::plugin(),
+                DuocombWindow::plugin(),
+                StarWindow::plugin(),
+                CompoundWindow::plugin(),
+            ),
+            (
+                TruncateWindow::plugin(),
+                DepthTruncateWindow::plugin(),
+                ExpandWindow::plugin(),
+                ScaleWindow::plugin(),
+                FacetingSettings::plugin(),
+                RotateWindow::plugin(),
+            ),
+        ))
         .add_plugins((
             PlaneWindow::plugin(),
-            TranslateWindow::plugin()));
+            TranslateWindow::plugin(),
+            LiftWindow::plugin(),
+            CollapseWindow::plugin(),
+            ZonotopeWindow::plugin(),
+            WythoffWindow::plugin(),
+            RandomHullWindow::plugin(),
+            RandomOrbitsWindow::plugin()));
     }
 }
 
@@ -154,13 +172,25 @@ macro_rules! impl_show {
             mut egui_ctx: EguiContexts<'_, '_>,
             mut query: Query<'_, '_, &mut Concrete>,
             mut poly_name: ResMut<'_, PolyName>,
+            (mut preview, preview_operations, mut perf): (ResMut<'_, Preview>, Res<'_, PreviewOperations>, ResMut<'_, PerfStats>),
         ) -> Result where
             Self: 'static,
         {
             match self_.show(egui_ctx.ctx_mut()?) {
+                // Computes the result on a copy, to be applied from the preview.
+                ShowResult::Ok if Self::PREVIEW && preview_operations.0 => {
+                    if let Some(polytope) = query.iter().next() {
+                        let mut candidate = polytope.clone();
+                        perf.time(Self::NAME, || self_.action(&mut candidate));
+                        let mut name = poly_name.0.clone();
+                        self_.name_action(&mut name);
+                        preview.set(candidate, name);
+                    }
+                    self_.close()
+                }
                 ShowResult::Ok => {
                     for mut polytope in query.iter_mut() {
-                        self_.action(polytope.as_mut());
+                        perf.time(Self::NAME, || self_.action(polytope.as_mut()));
                     }
                     self_.name_action(&mut poly_name.0);
                     self_.close()
@@ -177,6 +207,10 @@ macro_rules! impl_show {
 /// A window that doesn't depend on any resources other than itself, and that
 /// doesn't need to be updated when the polytope is changed.
 pub trait PlainWindow: Window {
+    /// Whether the result of the window is previewed before being applied,
+    /// when operation previews are enabled.
+    const PREVIEW: bool = false;
+
     /// Applies the action of the window to the polytope.
     fn action(&self, polytope: &mut Concrete);
 
@@ -214,6 +248,10 @@ impl<T: PlainWindow + 'static> Plugin for PlainWindowPlugin<T> {
 /// A window that doesn't depend on any resources other than itself, but needs
 /// to be updated when the dimension of the polytope is changed.
 pub trait UpdateWindow: Window {
+    /// Whether the result of the window is previewed before being applied,
+    /// when operation previews are enabled.
+    const PREVIEW: bool = false;
+
     /// Applies the action of the window to the polytope.
     fn action(&self, polytope: &mut Concrete);
     
@@ -382,6 +420,13 @@ pub trait DuoWindow: Window {
     /// The duo-operation to apply.
     fn operation(&self, p: &Concrete, q: &Concrete) -> Concrete;
 
+    /// The product the window builds, if the operation is one of the products
+    /// of polytopes. Products are built in the background so that they can be
+    /// cancelled, and their symmetries are built from those of their factors.
+    fn product(&self, _p: &Concrete, _q: &Concrete) -> Option<Product> {
+        None
+    }
+
     /// The slots in memory.
     fn slots(&self) -> [Slot; 2];
 
@@ -405,10 +450,15 @@ pub trait DuoWindow: Window {
             .map(|p| p.map(|poly| poly.dim()).flatten().unwrap_or_default())
     }
 
-    /// Applies the action of the window to the polytope.
-    fn action(&self, polytope: &mut Concrete, memory: &Memory) {
+    /// Applies the action of the window to the polytope. The symmetry group
+    /// of a product is cached from those of its factors whenever possible.
+    fn action(&self, polytope: &mut Concrete, memory: &Memory, symmetry: &mut SymmetryCache) {
         if let [Some(p), Some(q)] = self.polytopes(polytope, memory) {
-            *polytope = self.operation(p, q);
+            let result = self.operation(p, q);
+            if self.product(p, q).is_some() {
+                symmetry.insert_product(&result, p, q);
+            }
+            *polytope = result;
         }
     }
 
@@ -536,14 +586,28 @@ pub trait DuoWindow: Window {
         mut query: Query<'_, '_, &mut Concrete>,
         memory: Res<'_, Memory>,
         mut poly_name: ResMut<'_, PolyName>,
+        mut perf: ResMut<'_, PerfStats>,
+        mut symmetry: ResMut<'_, SymmetryCache>,
+        mut job: ResMut<'_, BackgroundJob>,
     ) -> Result where
         Self: 'static,
     {
         for mut polytope in query.iter_mut() {
             match self_.show(egui_ctx.ctx_mut()?, &polytope, &memory) {
                 ShowResult::Ok => {
-                    self_.action(polytope.as_mut(), &memory);
-                    self_.name_action(&mut poly_name.0, &memory);
+                    let product = match self_.polytopes(&polytope, &memory) {
+                        [Some(p), Some(q)] => self_.product(p, q).map(|product| (p, q, product)),
+                        _ => None,
+                    };
+
+                    if let Some((p, q, product)) = product {
+                        let mut name = poly_name.0.clone();
+                        self_.name_action(&mut name, &memory);
+                        job.product(Self::NAME, (p, q), product, name, &symmetry);
+                    } else {
+                        perf.time(Self::NAME, || self_.action(polytope.as_mut(), &memory, &mut symmetry));
+                        self_.name_action(&mut poly_name.0, &memory);
+                    }
                     self_.close()
                 }
                 ShowResult::Close => self_.close(),
@@ -582,6 +646,10 @@ pub struct DualWindow {
 
     /// The radius of the sphere.
     radius: Float,
+
+    /// Whether to move the center off the hyperplanes of any facets through
+    /// it, so that hemi polytopes have a dual.
+    offset_center: bool,
 }
 
 impl Default for DualWindow {
@@ -590,6 +658,7 @@ impl Default for DualWindow {
             open: false,
             center: Point::zeros(0),
             radius: 1.0,
+            offset_center: false,
         }
     }
 }
@@ -607,8 +676,15 @@ impl Window for DualWindow {
 }
 
 impl UpdateWindow for DualWindow {
+    const PREVIEW: bool = true;
+
     fn action(&self, polytope: &mut Concrete) {
-        let sphere = Hypersphere::with_radius(self.center.clone(), self.radius);
+        let center = if self.offset_center {
+            polytope.reciprocation_center_near(&self.center).unwrap_or_else(|| self.center.clone())
+        } else {
+            self.center.clone()
+        };
+        let sphere = Hypersphere::with_radius(center, self.radius);
 
         if let Err(err) = polytope.try_dual_mut_with(&sphere) {
             eprintln!("Dual failed: {}", err);
@@ -631,6 +707,8 @@ impl UpdateWindow for DualWindow {
 
             ui.label("Radius");
         });
+
+        ui.checkbox(&mut self.offset_center, "Move center off hemi facets");
     }
 
     fn dim(&self) -> usize {
@@ -1008,6 +1086,14 @@ impl DuoWindow for DuopyramidWindow {
         Concrete::duopyramid_with(p, q, p_offset, q_offset, self.height)
     }
 
+    fn product(&self, _: &Concrete, _: &Concrete) -> Option<Product> {
+        Some(Product {
+            product_type: ProductType::Pyramid,
+            offsets: self.offsets.clone(),
+            height: self.height,
+        })
+    }
+
     fn name_action(&self, name: &mut String, memory: &Memory) {
         let name_a = match self.slots[0] {
             Slot::Loaded => name.clone(),
@@ -1127,6 +1213,10 @@ impl DuoWindow for DuoprismWindow {
         p.duoprism(q)
     }
 
+    fn product(&self, p: &Concrete, q: &Concrete) -> Option<Product> {
+        Some(Product::new(ProductType::Prism, p, q))
+    }
+
     fn name_action(&self, name: &mut String, memory: &Memory) {
         let name_a = match self.slots[0] {
             Slot::Loaded => name.clone(),
@@ -1199,6 +1289,14 @@ impl DuoWindow for DuotegumWindow {
         Concrete::duotegum_with(p, q, p_offset, q_offset)
     }
 
+    fn product(&self, _: &Concrete, _: &Concrete) -> Option<Product> {
+        Some(Product {
+            product_type: ProductType::Tegum,
+            offsets: self.offsets.clone(),
+            height: 0.0,
+        })
+    }
+
     fn name_action(&self, name: &mut String, memory: &Memory) {
         let name_a = match self.slots[0] {
             Slot::Loaded => name.clone(),
@@ -1267,6 +1365,10 @@ impl DuoWindow for DuocombWindow {
         p.duocomb(q)
     }
 
+    fn product(&self, p: &Concrete, q: &Concrete) -> Option<Product> {
+        Some(Product::new(ProductType::Comb, p, q))
+    }
+
     fn name_action(&self, name: &mut String, memory: &Memory) {
         let name_a = match self.slots[0] {
             Slot::Loaded => name.clone(),
@@ -1444,6 +1546,8 @@ impl Window for TruncateWindow {
 }
 
 impl UpdateWindow for TruncateWindow {
+    const PREVIEW: bool = true;
+
     fn action(&self, polytope: &mut Concrete) {
         let mut rings = Vec::new();
         for (rank, ringed) in self.truncate_type.iter().enumerate() {
@@ -1508,6 +1612,112 @@ impl UpdateWindow for TruncateWindow {
     }
 }
 
+/// A window that cuts off the vertices of a polytope up to some depth.
+#[derive(Resource)]
+pub struct DepthTruncateWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The fraction of every edge that's cut off, between both ends.
+    depth: f64,
+}
+
+impl Default for DepthTruncateWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            depth: 0.5,
+        }
+    }
+}
+
+impl Window for DepthTruncateWindow {
+    const NAME: &'static str = "Truncate by depth";
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.open
+    }
+}
+
+impl PlainWindow for DepthTruncateWindow {
+    const PREVIEW: bool = true;
+
+    fn action(&self, polytope: &mut Concrete) {
+        *polytope = polytope.truncate(self.depth);
+    }
+
+    fn name_action(&self, name: &mut String) {
+        if self.depth == 1.0 {
+            *name = format!("Rectified {}", name);
+        } else if self.depth != 0.0 {
+            *name = format!("Truncated {}", name);
+        }
+    }
+
+    fn build(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Depth");
+            ui.add(egui::Slider::new(&mut self.depth, 0.0..=1.0));
+        });
+    }
+}
+
+/// A window that moves the facets of a polytope along their normals.
+#[derive(Default, Resource)]
+pub struct ExpandWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The distance by which the facets are moved outwards. Negative
+    /// distances move them inwards.
+    distance: f64,
+}
+
+impl Window for ExpandWindow {
+    const NAME: &'static str = "Expand";
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.open
+    }
+}
+
+impl PlainWindow for ExpandWindow {
+    const PREVIEW: bool = true;
+
+    fn action(&self, polytope: &mut Concrete) {
+        match polytope.expand(self.distance) {
+            Some(p) => *polytope = p,
+            None => println!("Expansion failed: the polytope has no facets, or they aren't flat."),
+        }
+    }
+
+    fn name_action(&self, name: &mut String) {
+        if self.distance > 0.0 {
+            *name = format!("Expanded {}", name);
+        } else if self.distance < 0.0 {
+            *name = format!("Contracted {}", name);
+        }
+    }
+
+    fn build(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Distance");
+            ui.add(
+                egui::Slider::new(&mut self.distance, -1.0..=1.0)
+                    .clamping(egui::SliderClamping::Never)
+            );
+        });
+    }
+}
+
 /// A window that scales a polytope.
 #[derive(Default, Resource)]
 pub struct ScaleWindow {
@@ -1547,18 +1757,450 @@ impl PlainWindow for ScaleWindow {
     }
 }
 
+/// A window that lifts a polytope into one more dimension.
+#[derive(Default, Resource)]
+pub struct LiftWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The coordinate of the polytope along the new axis.
+    offset: f64,
+}
+
+impl Window for LiftWindow {
+    const NAME: &'static str = "Lift";
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.open
+    }
+}
+
+impl PlainWindow for LiftWindow {
+    fn action(&self, polytope: &mut Concrete) {
+        polytope.lift(self.offset);
+    }
+
+    fn name_action(&self, _name: &mut String) {}
+
+    fn build(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Offset");
+            ui.add(
+                egui::DragValue::new(&mut self.offset)
+                    .speed(0.01)
+            );
+        });
+    }
+}
+
+/// A window that collapses the short edges of a polytope, along with the
+/// elements that become degenerate.
+#[derive(Resource)]
+pub struct CollapseWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The length below which edges are collapsed.
+    tolerance: f64,
+}
+
+impl Default for CollapseWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            tolerance: 1e-6,
+        }
+    }
+}
+
+impl Window for CollapseWindow {
+    const NAME: &'static str = "Collapse short edges";
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.open
+    }
+}
+
+impl PlainWindow for CollapseWindow {
+    const PREVIEW: bool = true;
+
+    fn action(&self, polytope: &mut Concrete) {
+        *polytope = polytope.collapse_short_edges(self.tolerance);
+    }
+
+    fn name_action(&self, name: &mut String) {
+        *name = format!("Collapsed {}", name);
+    }
+
+    fn build(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Tolerance");
+            ui.add(
+                egui::DragValue::new(&mut self.tolerance)
+                    .speed(0.001)
+                    .range(0.0..=f64::INFINITY)
+            );
+        });
+    }
+}
+
+/// The largest number of dimensions for the generators of a zonotope.
+const MAX_ZONOTOPE_DIM: usize = 20;
+
+/// A window that builds a zonotope from a list of generator vectors.
+#[derive(Resource)]
+pub struct ZonotopeWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The number of coordinates of the generators.
+    dim: usize,
+
+    /// The generator vectors.
+    generators: Vec<Point>,
+}
+
+impl Default for ZonotopeWindow {
+    fn default() -> Self {
+        // The generators of a cube.
+        Self {
+            open: false,
+            dim: 3,
+            generators: (0..3)
+                .map(|i| Point::from_fn(3, |j, _| if i == j { 1.0 } else { 0.0 }))
+                .collect(),
+        }
+    }
+}
+
+impl Window for ZonotopeWindow {
+    const NAME: &'static str = "Zonotope";
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.open
+    }
+}
+
+impl PlainWindow for ZonotopeWindow {
+    fn action(&self, polytope: &mut Concrete) {
+        *polytope = Concrete::zonotope(&self.generators);
+    }
+
+    fn name_action(&self, name: &mut String) {
+        *name = format!("Zonotope of {} vectors", self.generators.len());
+    }
+
+    fn build(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Dimensions:");
+            if ui
+                .add(egui::DragValue::new(&mut self.dim).speed(0.03).range(1..=MAX_ZONOTOPE_DIM))
+                .changed()
+            {
+                for g in &mut self.generators {
+                    resize(g, self.dim);
+                }
+            }
+        });
+
+        ui.separator();
+
+        let mut removed = None;
+        for (idx, g) in self.generators.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button("−").clicked() {
+                    removed = Some(idx);
+                }
+                ui.add(PointWidget::new(g, format!("v{}", idx + 1)));
+            });
+        }
+
+        if let Some(idx) = removed {
+            self.generators.remove(idx);
+        }
+
+        if ui.button("Add vector").clicked() {
+            self.generators.push(Point::zeros(self.dim));
+        }
+    }
+}
+
+/// A window that builds a Wythoffian from a Coxeter diagram.
+#[derive(Resource)]
+pub struct WythoffWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The Coxeter diagram, in inline ASCII notation.
+    diagram: String,
+
+    /// The editor that builds the diagram by clicking on it, kept in sync with
+    /// the inline notation.
+    editor: CdEditor,
+}
+
+impl Default for WythoffWindow {
+    fn default() -> Self {
+        let diagram = "x4o3o".to_string();
+        let editor = CdEditor::from_cd(&Cd::parse(&diagram).expect("the cube diagram is valid"));
+
+        Self {
+            open: false,
+            diagram,
+            editor,
+        }
+    }
+}
+
+impl Window for WythoffWindow {
+    const NAME: &'static str = "Wythoffian";
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.open
+    }
+}
+
+impl PlainWindow for WythoffWindow {
+    fn action(&self, polytope: &mut Concrete) {
+        match Concrete::parse_wythoff(&self.diagram) {
+            Ok(wythoffian) => *polytope = wythoffian,
+            Err(err) => eprintln!("Wythoffian failed: {}", err),
+        }
+    }
+
+    fn name_action(&self, name: &mut String) {
+        if Cd::parse(&self.diagram).is_ok() {
+            *name = self.diagram.clone();
+        }
+    }
+
+    fn build(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Diagram:");
+            if ui.text_edit_singleline(&mut self.diagram).changed()
+                && let Ok(cd) = Cd::parse(&self.diagram)
+            {
+                self.editor = CdEditor::from_cd(&cd);
+            }
+        });
+
+        if self.editor.show(ui)
+            && let Some(inline) = self.editor.cd().ok().and_then(|cd| cd.inline())
+        {
+            self.diagram = inline;
+        }
+        ui.separator();
+
+        // Tells whether the diagram can be built before trying to.
+        ui.label(match Cd::parse(&self.diagram) {
+            Ok(cd) => match cd.cox().cox_type() {
+                CoxType::Spherical => format!("{} nodes, spherical", cd.dim()),
+                cox_type => format!("{} nodes, {}: its group is infinite", cd.dim(), cox_type),
+            },
+            Err(err) => format!("Invalid diagram: {}", err),
+        });
+    }
+}
+
+/// The largest dimension of the random polytopes.
+const MAX_RANDOM_DIM: usize = 10;
+
+/// Shows the widget for the seed of a random polytope, with a button to pick
+/// the next one.
+fn seed_widget(ui: &mut Ui, seed: &mut u64) {
+    ui.horizontal(|ui| {
+        ui.label("Seed:");
+        ui.add(egui::DragValue::new(seed).speed(0.1));
+        if ui.button("Next").clicked() {
+            *seed = seed.wrapping_add(1);
+        }
+    });
+}
+
+/// A window that builds the convex hull of random points on a sphere.
+#[derive(Resource)]
+pub struct RandomHullWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The number of points.
+    count: usize,
+
+    /// The dimension of the sphere.
+    dim: usize,
+
+    /// The seed of the random points.
+    seed: u64,
+}
+
+impl Default for RandomHullWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            count: 20,
+            dim: 3,
+            seed: 1,
+        }
+    }
+}
+
+impl Window for RandomHullWindow {
+    const NAME: &'static str = "Random hull";
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.open
+    }
+}
+
+impl PlainWindow for RandomHullWindow {
+    fn action(&self, polytope: &mut Concrete) {
+        if let Some(hull) = Concrete::random_hull(self.count, self.dim, self.seed) {
+            *polytope = hull;
+        }
+    }
+
+    fn name_action(&self, name: &mut String) {
+        *name = format!("Random hull of {} points", self.count);
+    }
+
+    fn build(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Points:");
+            ui.add(egui::DragValue::new(&mut self.count).speed(0.1).range(1..=usize::MAX));
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Dimensions:");
+            ui.add(egui::DragValue::new(&mut self.dim).speed(0.03).range(1..=MAX_RANDOM_DIM));
+        });
+
+        seed_widget(ui, &mut self.seed);
+    }
+}
+
+/// A window that builds the convex hull of the orbits of random points under
+/// a Coxeter group.
+#[derive(Resource)]
+pub struct RandomOrbitsWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The Coxeter diagram of the group, in inline ASCII notation.
+    diagram: String,
+
+    /// The number of orbits.
+    count: usize,
+
+    /// The seed of the random points.
+    seed: u64,
+
+    /// The cache the elements of the group are read from.
+    cache: GroupCache,
+}
+
+impl Default for RandomOrbitsWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            diagram: "o4o3o".to_string(),
+            count: 1,
+            seed: 1,
+            cache: Config::group_cache(),
+        }
+    }
+}
+
+impl Window for RandomOrbitsWindow {
+    const NAME: &'static str = "Random symmetric hull";
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.open
+    }
+}
+
+impl PlainWindow for RandomOrbitsWindow {
+    fn action(&self, polytope: &mut Concrete) {
+        match self.cache.try_parse(&self.diagram) {
+            Ok(group) => {
+                if let Some(hull) = Concrete::random_symmetric_hull(group, self.count, self.seed) {
+                    *polytope = hull;
+                }
+            }
+            Err(err) => eprintln!("Random symmetric hull failed: {}", err),
+        }
+    }
+
+    fn name_action(&self, name: &mut String) {
+        if Cd::parse(&self.diagram).is_ok() {
+            *name = format!("Random hull of {} orbits under {}", self.count, self.diagram);
+        }
+    }
+
+    fn build(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Diagram:");
+            ui.text_edit_singleline(&mut self.diagram);
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Orbits:");
+            ui.add(egui::DragValue::new(&mut self.count).speed(0.03).range(1..=usize::MAX));
+        });
+
+        seed_widget(ui, &mut self.seed);
+        ui.separator();
+
+        // Tells whether the group can be built before trying to.
+        ui.label(match Cd::parse(&self.diagram) {
+            Ok(cd) => match cd.cox().cox_type() {
+                CoxType::Spherical => format!("{} dimensions, spherical", cd.dim()),
+                cox_type => format!("{} dimensions, {}: the group is infinite", cd.dim(), cox_type),
+            },
+            Err(err) => format!("Invalid diagram: {}", err),
+        });
+    }
+}
+
 /// Where to get the symmetry group for faceting
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum GroupEnum2 {
     /// Group of matrices
     FromSlot(Slot),
     /// True: take chiral group
     /// False: take full group
     Chiral(bool),
+    /// Take the symmetries of a patch of a tiling about its center
+    Patch,
+    /// Take the Coxeter group of a diagram
+    Diagram,
 }
 
 /// A window that lets the user set settings for faceting.
-#[derive(Resource)]
+#[derive(Clone, Resource)]
 pub struct FacetingSettings {
     /// Whether the window is open.
     open: bool,
@@ -1578,6 +2220,9 @@ pub struct FacetingSettings {
     /// Where to get the symmetry group from.
     pub group: GroupEnum2,
 
+    /// The Coxeter diagram of the group, if it's taken from one.
+    pub diagram: String,
+
     /// Whether to check for all possible edge lengths and facet with each of them.
     /// If `false`, allows picking a range of edge lengths.
     pub any_single_edge_length: bool,
@@ -1619,9 +2264,40 @@ pub struct FacetingSettings {
     /// Whether to check if the faceting is compound or fissary and mark it.
     pub mark_fissary: bool,
 
+    /// Whether to use a minimum facet circumradius.
+    pub do_min_facet_radius: bool,
+
+    /// The minimum facet circumradius.
+    pub min_facet_radius: f64,
+
+    /// Whether to use a maximum facet circumradius.
+    pub do_max_facet_radius: bool,
+
+    /// The maximum facet circumradius.
+    pub max_facet_radius: f64,
+
+    /// Whether to use a minimum number of vertices per facet.
+    pub do_min_facet_vertices: bool,
+
+    /// The minimum number of vertices per facet.
+    pub min_facet_vertices: usize,
+
+    /// Whether to use a maximum number of vertices per facet.
+    pub do_max_facet_vertices: bool,
+
+    /// The maximum number of vertices per facet.
+    pub max_facet_vertices: usize,
+
+    /// The element counts every facet must have, from the vertices up,
+    /// separated by spaces. Empty for no restriction.
+    pub facet_counts: String,
+
     /// Only use uniform or semiuniform elements.
     pub uniform: bool,
 
+    /// The floats the faceting is computed with.
+    pub precision: Precision,
+
     /// Whether to include the facet numbers in the name.
     pub label_facets: bool,
 
@@ -1636,6 +2312,13 @@ pub struct FacetingSettings {
 
     /// The path to save to, if saving to file.
     pub file_path: String,
+
+    /// Whether to name the facetings that are known polytopes.
+    pub recognize: bool,
+
+    /// A fingerprint database or a folder of OFF files with more known
+    /// polytopes, if any.
+    pub known_path: String,
 }
 
 impl Default for FacetingSettings {
@@ -1647,6 +2330,7 @@ impl Default for FacetingSettings {
             max_facet_types: 0,
             max_per_hyperplane: 0,
             group: GroupEnum2::Chiral(false),
+            diagram: "o4o3o".to_string(),
             any_single_edge_length: false,
             do_min_edge_length: true,
             min_edge_length: 1.,
@@ -1659,13 +2343,71 @@ impl Default for FacetingSettings {
             exclude_hemis: false,
             only_below_vertex: false,
             compounds: false,
+            do_min_facet_radius: false,
+            min_facet_radius: 0.,
+            do_max_facet_radius: false,
+            max_facet_radius: 0.,
+            do_min_facet_vertices: false,
+            min_facet_vertices: 0,
+            do_max_facet_vertices: false,
+            max_facet_vertices: 0,
+            facet_counts: "".to_string(),
             mark_fissary: true,
             uniform: false,
+            precision: Precision::Double,
             label_facets: true,
             save: true,
             save_facets: false,
             save_to_file: false,
             file_path: "".to_string(),
+            recognize: true,
+            known_path: "".to_string(),
+        }
+    }
+}
+
+impl FacetingSettings {
+    /// Returns the bounds on the candidate facets. Element counts that can't
+    /// be read are ignored.
+    pub fn facet_filter(&self) -> FacetFilter {
+        let counts: Option<Vec<usize>> = self
+            .facet_counts
+            .split_whitespace()
+            .map(|count| count.parse().ok())
+            .collect();
+
+        FacetFilter {
+            min_circumradius: self.do_min_facet_radius.then_some(self.min_facet_radius),
+            max_circumradius: self.do_max_facet_radius.then_some(self.max_facet_radius),
+            min_vertices: self.do_min_facet_vertices.then_some(self.min_facet_vertices),
+            max_vertices: self.do_max_facet_vertices.then_some(self.max_facet_vertices),
+            element_counts: counts.filter(|counts| !counts.is_empty()),
+        }
+    }
+
+    /// Returns the options that a faceting with these settings is run with.
+    /// Unchecked bounds and zero limits are turned into `None`.
+    pub fn options(&self) -> FacetingOptions {
+        FacetingOptions {
+            any_single_edge_length: self.any_single_edge_length,
+            min_edge_length: self.do_min_edge_length.then_some(self.min_edge_length),
+            max_edge_length: self.do_max_edge_length.then_some(self.max_edge_length),
+            min_inradius: self.do_min_inradius.then_some(self.min_inradius),
+            max_inradius: self.do_max_inradius.then_some(self.max_inradius),
+            exclude_hemis: self.exclude_hemis,
+            only_below_vertex: self.only_below_vertex,
+            noble: (self.max_facet_types != 0).then_some(self.max_facet_types),
+            max_per_hyperplane: (self.max_per_hyperplane != 0).then_some(self.max_per_hyperplane),
+            facet_filter: self.facet_filter(),
+            uniform: self.uniform,
+            include_compounds: self.compounds,
+            mark_fissary: self.mark_fissary,
+            label_facets: self.label_facets,
+            save: self.save,
+            save_facets: self.save_facets,
+            save_to_file: self.save_to_file,
+            file_path: self.file_path.clone(),
+            precision: self.precision,
         }
     }
 }
@@ -1711,6 +2453,15 @@ impl MemoryWindow for FacetingSettings {
 
         ui.radio_value(&mut self.group, GroupEnum2::Chiral(false), "Full group");
         ui.radio_value(&mut self.group, GroupEnum2::Chiral(true), "Chiral subgroup");
+        ui.radio_value(&mut self.group, GroupEnum2::Patch, "Affine symmetries of the patch")
+            .on_hover_text("The symmetries of a patch of a tiling that map it onto itself, even if it isn't centered at the origin.");
+
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.group, GroupEnum2::Diagram, "Coxeter diagram:");
+            if ui.text_edit_singleline(&mut self.diagram).changed() {
+                self.group = GroupEnum2::Diagram;
+            }
+        });
 
         ui.horizontal(|ui| {
             ui.radio_value(&mut self.group, GroupEnum2::FromSlot(self.slot), "From other polytope:");
@@ -1849,6 +2600,55 @@ impl MemoryWindow for FacetingSettings {
             ui.add(
                 egui::Checkbox::new(&mut self.only_below_vertex, "Only hyperplanes perpendicular to a vertex")
             );
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::Checkbox::new(&mut self.do_min_facet_radius, "")
+                );
+                ui.add(
+                    egui::DragValue::new(&mut self.min_facet_radius).range(0.0..=Float::MAX).speed(0.001)
+                );
+                ui.label("Min facet circumradius");
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::Checkbox::new(&mut self.do_max_facet_radius, "")
+                );
+                ui.add(
+                    egui::DragValue::new(&mut self.max_facet_radius).range(0.0..=Float::MAX).speed(0.001)
+                );
+                ui.label("Max facet circumradius");
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::Checkbox::new(&mut self.do_min_facet_vertices, "")
+                );
+                ui.add(
+                    egui::DragValue::new(&mut self.min_facet_vertices).range(0..=usize::MAX).speed(0.05)
+                );
+                ui.label("Min facet vertices");
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::Checkbox::new(&mut self.do_max_facet_vertices, "")
+                );
+                ui.add(
+                    egui::DragValue::new(&mut self.max_facet_vertices).range(0..=usize::MAX).speed(0.05)
+                );
+                ui.label("Max facet vertices");
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Facet element counts");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.facet_counts).hint_text("e.g. 4 6 4")
+                );
+            });
         }
 
         ui.separator();
@@ -1871,6 +2671,29 @@ impl MemoryWindow for FacetingSettings {
             ui.add(
                 egui::Checkbox::new(&mut self.label_facets, "Label facets")
             );
+
+            #[cfg(feature = "big-float")]
+            ui.horizontal(|ui| {
+                ui.label("Precision:");
+                ui.radio_value(&mut self.precision, Precision::Double, "64-bit");
+                ui.radio_value(&mut self.precision, Precision::Quadruple, "128-bit");
+                ui.radio_value(&mut self.precision, Precision::Octuple, "256-bit");
+            });
+        }
+
+        ui.add(
+            egui::Checkbox::new(&mut self.recognize, "Name known uniforms")
+        );
+
+        if self.show_advanced_settings {
+            ui.horizontal(|ui| {
+                ui.label("More known polytopes:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.known_path)
+                        .interactive(self.recognize)
+                        .hint_text("database or folder")
+                );
+            });
         }
 
         ui.separator();